@@ -0,0 +1,97 @@
+using System;
+using System.Runtime.InteropServices;
+
+namespace Squire
+{
+    /// <summary>The C interface that squire_sdk exposes with its ffi feature</summary>
+    internal static class Native
+    {
+        private const string Lib = "squire_sdk";
+
+        [StructLayout(LayoutKind.Sequential)]
+        internal unsafe struct RawStanding
+        {
+            public fixed byte Player[16];
+            public double MatchPoints;
+            public double GamePoints;
+            public double Mwp;
+            public double Gwp;
+            public double OppMwp;
+            public double OppGwp;
+        }
+
+        [StructLayout(LayoutKind.Sequential)]
+        internal struct RawStandings
+        {
+            public IntPtr Data;
+            public UIntPtr Len;
+        }
+
+        [StructLayout(LayoutKind.Sequential)]
+        internal struct RawPairings
+        {
+            public IntPtr Players;
+            public UIntPtr PlayersLen;
+            public IntPtr GroupSizes;
+            public UIntPtr GroupsLen;
+            public IntPtr Rejected;
+            public UIntPtr RejectedLen;
+        }
+
+        [DllImport(Lib)]
+        internal static extern IntPtr squire_manager_new(
+            [MarshalAs(UnmanagedType.LPUTF8Str)] string seed,
+            [MarshalAs(UnmanagedType.LPUTF8Str)] string owner);
+
+        [DllImport(Lib)]
+        internal static extern IntPtr squire_manager_import(
+            [MarshalAs(UnmanagedType.LPUTF8Str)] string export);
+
+        [DllImport(Lib)]
+        internal static extern IntPtr squire_manager_export(IntPtr manager);
+
+        [DllImport(Lib)]
+        internal static extern IntPtr squire_manager_tourn(IntPtr manager);
+
+        [DllImport(Lib)]
+        internal static extern IntPtr squire_manager_op_log(IntPtr manager);
+
+        [DllImport(Lib)]
+        internal static extern IntPtr squire_manager_apply_op(
+            IntPtr manager,
+            [MarshalAs(UnmanagedType.LPUTF8Str)] string op);
+
+        [DllImport(Lib)]
+        internal static extern RawStandings squire_manager_standings(IntPtr manager);
+
+        [DllImport(Lib)]
+        internal static extern void squire_standings_free(RawStandings standings);
+
+        [DllImport(Lib)]
+        internal static extern RawPairings squire_manager_pairings(IntPtr manager);
+
+        [DllImport(Lib)]
+        internal static extern void squire_pairings_free(RawPairings pairings);
+
+        [DllImport(Lib)]
+        internal static extern void squire_manager_free(IntPtr manager);
+
+        [DllImport(Lib)]
+        internal static extern void squire_string_free(IntPtr json);
+
+        /// <summary>Reads a string returned by the library, releasing it</summary>
+        internal static string TakeString(IntPtr ptr)
+        {
+            if (ptr == IntPtr.Zero)
+                throw new SquireException("The native library could not encode the value");
+            try
+            {
+                return Marshal.PtrToStringUTF8(ptr)!;
+            }
+            finally
+            {
+                squire_string_free(ptr);
+            }
+        }
+    }
+}
@@ -0,0 +1,163 @@
+using System;
+using System.Collections.Generic;
+using System.Runtime.InteropServices;
+
+namespace Squire
+{
+    /// <summary>Thrown when the native library rejects a value or fails to create a manager</summary>
+    public class SquireException : Exception
+    {
+        public SquireException(string message) : base(message) { }
+    }
+
+    /// <summary>A player's place in the standings</summary>
+    public sealed record Standing(
+        Guid Player,
+        double MatchPoints,
+        double GamePoints,
+        double Mwp,
+        double Gwp,
+        double OppMwp,
+        double OppGwp);
+
+    /// <summary>The proposed pairings for a tournament's next round</summary>
+    public sealed record Pairings(IReadOnlyList<IReadOnlyList<Guid>> Paired, IReadOnlyList<Guid> Rejected);
+
+    /// <summary>
+    /// A tournament and its operations log, which can be synced with SquireCore. Tournaments,
+    /// operations, and their results are passed as JSON strings using the same encoding as the
+    /// rest of SquireCore.
+    /// </summary>
+    public sealed class TournamentManager : IDisposable
+    {
+        private IntPtr _ptr;
+
+        private TournamentManager(IntPtr ptr)
+        {
+            if (ptr == IntPtr.Zero)
+                throw new SquireException("The native library could not create the manager");
+            _ptr = ptr;
+        }
+
+        /// <summary>
+        /// Creates a manager for a new tournament from a TournamentSeed and the SquireAccount of
+        /// the tournament's owner
+        /// </summary>
+        public static TournamentManager Create(string seedJson, string ownerJson) =>
+            new(Native.squire_manager_new(seedJson, ownerJson));
+
+        /// <summary>
+        /// Recreates a manager from the value returned by Export or a manager that was fetched
+        /// from SquireCore
+        /// </summary>
+        public static TournamentManager Import(string exportJson) =>
+            new(Native.squire_manager_import(exportJson));
+
+        private IntPtr Handle =>
+            _ptr != IntPtr.Zero ? _ptr : throw new ObjectDisposedException(nameof(TournamentManager));
+
+        /// <summary>Exports the manager, which includes both the tournament and its operations log</summary>
+        public string Export() => Native.TakeString(Native.squire_manager_export(Handle));
+
+        /// <summary>Returns the current tournament</summary>
+        public string Tourn() => Native.TakeString(Native.squire_manager_tourn(Handle));
+
+        /// <summary>Returns the operations log</summary>
+        public string OpLog() => Native.TakeString(Native.squire_manager_op_log(Handle));
+
+        /// <summary>
+        /// Applies a TournOp and returns the result, which is either {"Ok": data} or {"Err": error}
+        /// </summary>
+        public string ApplyOp(string opJson)
+        {
+            var ptr = Native.squire_manager_apply_op(Handle, opJson);
+            if (ptr == IntPtr.Zero)
+                throw new SquireException("The native library could not decode the operation");
+            return Native.TakeString(ptr);
+        }
+
+        /// <summary>Returns the current standings, best first</summary>
+        public unsafe IReadOnlyList<Standing> Standings()
+        {
+            var raw = Native.squire_manager_standings(Handle);
+            try
+            {
+                var data = (Native.RawStanding*)raw.Data;
+                var standings = new List<Standing>((int)raw.Len);
+                for (var i = 0; i < (int)raw.Len; i++)
+                {
+                    var s = data[i];
+                    standings.Add(new Standing(
+                        ReadId(s.Player),
+                        s.MatchPoints,
+                        s.GamePoints,
+                        s.Mwp,
+                        s.Gwp,
+                        s.OppMwp,
+                        s.OppGwp));
+                }
+                return standings;
+            }
+            finally
+            {
+                Native.squire_standings_free(raw);
+            }
+        }
+
+        /// <summary>Proposes the pairings for the next round. Nothing is applied to the tournament.</summary>
+        public unsafe Pairings Pairings()
+        {
+            var raw = Native.squire_manager_pairings(Handle);
+            try
+            {
+                var players = (byte*)raw.Players;
+                var sizes = (UIntPtr*)raw.GroupSizes;
+                var paired = new List<IReadOnlyList<Guid>>((int)raw.GroupsLen);
+                var next = 0;
+                for (var g = 0; g < (int)raw.GroupsLen; g++)
+                {
+                    var group = new List<Guid>();
+                    for (var i = 0; i < (int)sizes[g]; i++, next++)
+                        group.Add(ReadId(players + 16 * next));
+                    paired.Add(group);
+                }
+                var rejectedPtr = (byte*)raw.Rejected;
+                var rejected = new List<Guid>((int)raw.RejectedLen);
+                for (var i = 0; i < (int)raw.RejectedLen; i++)
+                    rejected.Add(ReadId(rejectedPtr + 16 * i));
+                return new Pairings(paired, rejected);
+            }
+            finally
+            {
+                Native.squire_pairings_free(raw);
+            }
+        }
+
+        /// <summary>Releases the manager</summary>
+        public void Dispose()
+        {
+            if (_ptr != IntPtr.Zero)
+            {
+                Native.squire_manager_free(_ptr);
+                _ptr = IntPtr.Zero;
+            }
+            GC.SuppressFinalize(this);
+        }
+
+        ~TournamentManager()
+        {
+            if (_ptr != IntPtr.Zero)
+                Native.squire_manager_free(_ptr);
+        }
+
+        /// <summary>
+        /// Reads a player id. Ids are given in the big-endian (RFC 4122) byte order, which Guid
+        /// only reads directly on .NET 8 and later.
+        /// </summary>
+        private static unsafe Guid ReadId(byte* bytes)
+        {
+            var span = new ReadOnlySpan<byte>(bytes, 16);
+            return Guid.ParseExact(Convert.ToHexString(span), "N");
+        }
+    }
+}
@@ -0,0 +1,244 @@
+"""Bindings to SquireCore's tournament manager.
+
+These wrap the C interface that ``squire_sdk`` exposes with its ``ffi`` feature. Tournaments,
+operations, and their results are passed as JSON-compatible values (dicts, lists, strings, ...)
+using the same encoding as the rest of SquireCore. Standings and pairings are returned as plain
+Python objects.
+
+The native library is loaded from the ``SQUIRE_LIB`` environment variable, if it is set. Otherwise,
+it is looked for next to this module and then on the library search path.
+"""
+
+import ctypes
+import json
+import os
+import sys
+from dataclasses import dataclass
+from typing import Any, List
+from uuid import UUID
+
+__all__ = ["Pairings", "Standing", "SquireError", "TournamentManager"]
+
+
+class SquireError(Exception):
+    """Raised when the native library rejects a value or fails to create a manager."""
+
+
+class _Standing(ctypes.Structure):
+    _fields_ = [
+        ("player", ctypes.c_uint8 * 16),
+        ("match_points", ctypes.c_double),
+        ("game_points", ctypes.c_double),
+        ("mwp", ctypes.c_double),
+        ("gwp", ctypes.c_double),
+        ("opp_mwp", ctypes.c_double),
+        ("opp_gwp", ctypes.c_double),
+    ]
+
+
+class _Standings(ctypes.Structure):
+    _fields_ = [
+        ("data", ctypes.POINTER(_Standing)),
+        ("len", ctypes.c_size_t),
+    ]
+
+
+_Id = ctypes.c_uint8 * 16
+
+
+class _Pairings(ctypes.Structure):
+    _fields_ = [
+        ("players", ctypes.POINTER(_Id)),
+        ("players_len", ctypes.c_size_t),
+        ("group_sizes", ctypes.POINTER(ctypes.c_size_t)),
+        ("groups_len", ctypes.c_size_t),
+        ("rejected", ctypes.POINTER(_Id)),
+        ("rejected_len", ctypes.c_size_t),
+    ]
+
+
+def _library_name() -> str:
+    if sys.platform == "win32":
+        return "squire_sdk.dll"
+    if sys.platform == "darwin":
+        return "libsquire_sdk.dylib"
+    return "libsquire_sdk.so"
+
+
+def _load() -> ctypes.CDLL:
+    path = os.environ.get("SQUIRE_LIB")
+    if path is None:
+        local = os.path.join(os.path.dirname(__file__), _library_name())
+        path = local if os.path.exists(local) else _library_name()
+    lib = ctypes.CDLL(path)
+
+    manager = ctypes.c_void_p
+    # Strings are returned as void pointers so that they can be handed back to be freed
+    string = ctypes.c_void_p
+    signatures = {
+        "squire_manager_new": ([ctypes.c_char_p, ctypes.c_char_p], manager),
+        "squire_manager_import": ([ctypes.c_char_p], manager),
+        "squire_manager_export": ([manager], string),
+        "squire_manager_tourn": ([manager], string),
+        "squire_manager_op_log": ([manager], string),
+        "squire_manager_apply_op": ([manager, ctypes.c_char_p], string),
+        "squire_manager_standings": ([manager], _Standings),
+        "squire_standings_free": ([_Standings], None),
+        "squire_manager_pairings": ([manager], _Pairings),
+        "squire_pairings_free": ([_Pairings], None),
+        "squire_manager_free": ([manager], None),
+        "squire_string_free": ([string], None),
+    }
+    for name, (args, ret) in signatures.items():
+        func = getattr(lib, name)
+        func.argtypes = args
+        func.restype = ret
+    return lib
+
+
+_lib = _load()
+
+
+def _encode(value: Any) -> bytes:
+    return json.dumps(value).encode("utf-8")
+
+
+def _decode(ptr: int) -> Any:
+    """Reads a string returned by the library, releasing it."""
+    if not ptr:
+        raise SquireError("the native library could not encode the value")
+    try:
+        return json.loads(ctypes.string_at(ptr).decode("utf-8"))
+    finally:
+        _lib.squire_string_free(ptr)
+
+
+def _uuid(raw) -> UUID:
+    return UUID(bytes=bytes(raw))
+
+
+@dataclass(frozen=True)
+class Standing:
+    """A player's place in the standings."""
+
+    player: UUID
+    match_points: float
+    game_points: float
+    mwp: float
+    gwp: float
+    opp_mwp: float
+    opp_gwp: float
+
+
+@dataclass(frozen=True)
+class Pairings:
+    """The proposed pairings for a tournament's next round."""
+
+    paired: List[List[UUID]]
+    rejected: List[UUID]
+
+    def to_json(self) -> Any:
+        """Encodes the pairings so that they can be used in an ``AdminOp::PairRound``."""
+        return {
+            "paired": [[str(p) for p in group] for group in self.paired],
+            "rejected": [str(p) for p in self.rejected],
+        }
+
+
+class TournamentManager:
+    """A tournament and its operations log, which can be synced with SquireCore.
+
+    The manager owns native memory. Call ``close`` or use it as a context manager to release it.
+    """
+
+    def __init__(self, ptr: int):
+        if not ptr:
+            raise SquireError("the native library could not create the manager")
+        self._ptr = ptr
+
+    @classmethod
+    def create(cls, seed: Any, owner: Any) -> "TournamentManager":
+        """Creates a manager for a new tournament from a ``TournamentSeed`` and the
+        ``SquireAccount`` of the tournament's owner."""
+        return cls(_lib.squire_manager_new(_encode(seed), _encode(owner)))
+
+    @classmethod
+    def import_json(cls, export: Any) -> "TournamentManager":
+        """Recreates a manager from the value returned by ``export`` or a manager that was
+        fetched from SquireCore."""
+        return cls(_lib.squire_manager_import(_encode(export)))
+
+    def _handle(self) -> int:
+        if self._ptr is None:
+            raise SquireError("the manager has been closed")
+        return self._ptr
+
+    def export(self) -> Any:
+        """Exports the manager, which includes both the tournament and its operations log."""
+        return _decode(_lib.squire_manager_export(self._handle()))
+
+    def tourn(self) -> Any:
+        """Returns the current tournament."""
+        return _decode(_lib.squire_manager_tourn(self._handle()))
+
+    def op_log(self) -> Any:
+        """Returns the operations log."""
+        return _decode(_lib.squire_manager_op_log(self._handle()))
+
+    def apply_op(self, op: Any) -> Any:
+        """Applies a ``TournOp`` and returns the result, which is either ``{"Ok": data}`` or
+        ``{"Err": error}``."""
+        ptr = _lib.squire_manager_apply_op(self._handle(), _encode(op))
+        if not ptr:
+            raise SquireError("the native library could not decode the operation")
+        return _decode(ptr)
+
+    def standings(self) -> List[Standing]:
+        """Returns the current standings, best first."""
+        raw = _lib.squire_manager_standings(self._handle())
+        try:
+            return [
+                Standing(
+                    player=_uuid(s.player),
+                    match_points=s.match_points,
+                    game_points=s.game_points,
+                    mwp=s.mwp,
+                    gwp=s.gwp,
+                    opp_mwp=s.opp_mwp,
+                    opp_gwp=s.opp_gwp,
+                )
+                for s in raw.data[: raw.len]
+            ]
+        finally:
+            _lib.squire_standings_free(raw)
+
+    def pairings(self) -> Pairings:
+        """Proposes the pairings for the next round. Nothing is applied to the tournament."""
+        raw = _lib.squire_manager_pairings(self._handle())
+        try:
+            players = [_uuid(p) for p in raw.players[: raw.players_len]]
+            paired = []
+            start = 0
+            for size in raw.group_sizes[: raw.groups_len]:
+                paired.append(players[start : start + size])
+                start += size
+            rejected = [_uuid(p) for p in raw.rejected[: raw.rejected_len]]
+            return Pairings(paired=paired, rejected=rejected)
+        finally:
+            _lib.squire_pairings_free(raw)
+
+    def close(self) -> None:
+        """Releases the manager. It can not be used afterwards."""
+        # The pointer is missing if the manager could not be created
+        if getattr(self, "_ptr", None) is not None:
+            _lib.squire_manager_free(self._ptr)
+            self._ptr = None
+
+    def __enter__(self) -> "TournamentManager":
+        return self
+
+    def __exit__(self, *_) -> None:
+        self.close()
+
+    def __del__(self) -> None:
+        self.close()
@@ -0,0 +1,79 @@
+"""Tests for the Python bindings. These need the native library, see the bindings README."""
+
+import unittest
+import uuid
+
+try:
+    import squire
+except OSError as err:
+    raise unittest.SkipTest(f"the native library could not be loaded: {err}")
+
+
+def spoof_account():
+    account_id = str(uuid.uuid4())
+    return {
+        "user_name": account_id,
+        "display_name": account_id,
+        "gamer_tags": [],
+        "id": account_id,
+        "permissions": "Everything",
+    }
+
+
+SEED = {"name": "Test Tournament", "preset": "Swiss", "format": "Pioneer"}
+
+
+class TournamentManagerTests(unittest.TestCase):
+    def setUp(self):
+        self.owner = spoof_account()
+        self.manager = squire.TournamentManager.create(SEED, self.owner)
+        self.addCleanup(self.manager.close)
+
+    def register(self, count):
+        for _ in range(count):
+            result = self.manager.apply_op({"RegisterPlayer": [spoof_account(), None]})
+            self.assertIn("Ok", result)
+
+    def test_managers_round_trip(self):
+        self.register(1)
+        export = self.manager.export()
+        with squire.TournamentManager.import_json(export) as imported:
+            self.assertEqual(imported.tourn()["id"], self.manager.tourn()["id"])
+            self.assertEqual(len(imported.tourn()["player_reg"]["players"]), 1)
+
+    def test_invalid_values_are_rejected(self):
+        with self.assertRaises(squire.SquireError):
+            self.manager.apply_op("not an op")
+        with self.assertRaises(squire.SquireError):
+            squire.TournamentManager.create({"name": "Missing fields"}, self.owner)
+
+    def test_errors_are_returned(self):
+        # Tournaments can't be started twice
+        start = {"AdminOp": [self.owner["id"], "Start"]}
+        self.assertIn("Ok", self.manager.apply_op(start))
+        self.assertIn("Err", self.manager.apply_op(start))
+
+    def test_standings_and_pairings(self):
+        self.register(4)
+        self.manager.apply_op({"AdminOp": [self.owner["id"], "Start"]})
+        standings = self.manager.standings()
+        self.assertEqual(len(standings), 4)
+        self.assertTrue(all(s.match_points == 0.0 for s in standings))
+
+        pairings = self.manager.pairings()
+        paired = [p for group in pairings.paired for p in group]
+        self.assertEqual(len(paired) + len(pairings.rejected), 4)
+        self.assertEqual({s.player for s in standings}, set(paired) | set(pairings.rejected))
+
+        # Proposed pairings can be applied as they are
+        pair = {"AdminOp": [self.owner["id"], {"PairRound": pairings.to_json()}]}
+        self.assertIn("Ok", self.manager.apply_op(pair))
+
+    def test_closed_managers_can_not_be_used(self):
+        self.manager.close()
+        with self.assertRaises(squire.SquireError):
+            self.manager.tourn()
+
+
+if __name__ == "__main__":
+    unittest.main()
@@ -1,32 +1,38 @@
-use axum::{extract::State, Json};
+use axum::{
+    extract::{Path, State},
+    Json,
+};
 use http::StatusCode;
 use squire_sdk::{
     api::*,
-    model::{accounts::SquireAccount, identifiers::SquireAccountId},
+    model::{accounts::SquireAccount, identifiers::SquireAccountId, settings::TournamentTemplate},
     server::{
         session::{Session, SessionConvert, SquireSession},
         state::ServerState,
     },
 };
 
-use crate::state::AppState;
+use crate::{
+    session::CurrentSession,
+    state::{AppState, RegError},
+};
 
 pub async fn create_account(
     State(state): State<AppState>,
     Json(form): Json<RegForm>,
-) -> (SessionToken, Json<SquireAccountId>) {
-    let id = state.create_account(form).await;
+) -> Result<(SessionToken, Json<SquireAccountId>), RegError> {
+    let id = state.create_account(form).await?;
     let session = state.create_session(id).await;
-    (session, Json(id))
+    Ok((session, Json(id)))
 }
 
-pub struct ActiveSession(SquireAccountId);
+pub struct ActiveSession(pub SquireAccountId);
 
 impl SessionConvert for ActiveSession {
     type Error = StatusCode;
 
     fn convert(_token: SessionToken, session: SquireSession) -> Result<Self, Self::Error> {
-        if let SquireSession::Active(id) = session {
+        if let SquireSession::Active(id) | SquireSession::ApiToken(id) = session {
             Ok(Self(id))
         } else {
             Err(StatusCode::UNAUTHORIZED)
@@ -47,7 +53,7 @@ pub async fn get_account(
 
 pub async fn delete_account(
     State(state): State<AppState>,
-    Session(ActiveSession(id)): Session<ActiveSession>,
+    Session(CurrentSession(_, id)): Session<CurrentSession>,
 ) -> StatusCode {
     if state.delete_account(id).await {
         StatusCode::OK
@@ -55,3 +61,93 @@ pub async fn delete_account(
         StatusCode::BAD_REQUEST
     }
 }
+
+/// Returns an account's profile. Users that do not share their name are redacted for everyone but
+/// themselves.
+pub async fn get_profile(
+    State(state): State<AppState>,
+    Session(session): Session<SquireSession>,
+    Path(id): Path<SquireAccountId>,
+) -> Json<Option<PublicProfile>> {
+    let viewer = match session {
+        SquireSession::Active(viewer) | SquireSession::ApiToken(viewer) => Some(viewer),
+        _ => None,
+    };
+    Json(state.get_profile(id, viewer).await)
+}
+
+/// Lists the tournaments that an account played in. Only the account's owner can see the
+/// tournaments that they did not share their name with.
+pub async fn get_history(
+    State(state): State<AppState>,
+    Session(session): Session<SquireSession>,
+    Path(id): Path<SquireAccountId>,
+) -> Json<Option<Vec<HistoryEntry>>> {
+    let viewer = match session {
+        SquireSession::Active(viewer) | SquireSession::ApiToken(viewer) => Some(viewer),
+        _ => None,
+    };
+    Json(state.get_history(id, viewer).await)
+}
+
+/// Summarizes every tournament that an account played in
+pub async fn get_stats(
+    State(state): State<AppState>,
+    Session(session): Session<SquireSession>,
+    Path(id): Path<SquireAccountId>,
+) -> Json<Option<PlayerStats>> {
+    let viewer = match session {
+        SquireSession::Active(viewer) | SquireSession::ApiToken(viewer) => Some(viewer),
+        _ => None,
+    };
+    Json(state.get_stats(id, viewer).await)
+}
+
+pub async fn save_profile(
+    State(state): State<AppState>,
+    Session(ActiveSession(id)): Session<ActiveSession>,
+    Json(profile): Json<AccountProfile>,
+) -> Json<Result<(), ProfileError>> {
+    Json(state.set_profile(id, profile).await)
+}
+
+pub async fn save_template(
+    State(state): State<AppState>,
+    Session(ActiveSession(id)): Session<ActiveSession>,
+    Json(template): Json<TournamentTemplate>,
+) -> Json<bool> {
+    Json(state.save_template(id, template).await)
+}
+
+pub async fn list_templates(
+    State(state): State<AppState>,
+    Session(ActiveSession(id)): Session<ActiveSession>,
+) -> Json<Vec<TournamentTemplate>> {
+    Json(state.list_templates(id).await)
+}
+
+/// Changes the user's password. The user's other sessions and API tokens are revoked.
+pub async fn change_password(
+    State(state): State<AppState>,
+    Session(CurrentSession(token, id)): Session<CurrentSession>,
+    Json(req): Json<ChangePassword>,
+) -> Json<Result<(), PasswordError>> {
+    Json(state.change_password(id, token, req).await)
+}
+
+/// Sends a password reset token to the account's email address. The response is the same whether
+/// or not the account exists.
+pub async fn request_password_reset(
+    State(state): State<AppState>,
+    Json(req): Json<RequestPasswordReset>,
+) -> Json<()> {
+    state.request_password_reset(req).await;
+    Json(())
+}
+
+pub async fn reset_password(
+    State(state): State<AppState>,
+    Json(req): Json<ResetPassword>,
+) -> Json<Result<(), PasswordError>> {
+    Json(state.reset_password(req).await)
+}
@@ -1,6 +1,6 @@
 use axum::{routing::get, Router};
 use mongodb::Database;
-use squire_sdk::{api::*, server};
+use squire_sdk::{api::*, model::settings::TournamentTemplate, server};
 use tower_http::cors::CorsLayer;
 
 #[cfg(test)]
@@ -10,10 +10,12 @@ mod tests;
 mod assets;
 
 mod accounts;
+mod notifications;
 mod session;
 mod state;
 
 use accounts::*;
+use notifications::*;
 use session::*;
 use state::{AppState, AppStateBuilder};
 
@@ -22,11 +24,35 @@ pub fn create_router(state: AppState) -> Router {
         .add_route::<0, POST, RegForm, _, _>(create_account)
         .add_route::<0, GET, AccountCrud, _, _>(get_account)
         .add_route::<0, DELETE, AccountCrud, _, _>(delete_account)
+        .add_route::<0, POST, TournamentTemplate, _, _>(save_template)
+        .add_route::<0, GET, ListTemplates, _, _>(list_templates)
+        .add_route::<0, POST, ChangePassword, _, _>(change_password)
+        .add_route::<0, POST, RequestPasswordReset, _, _>(request_password_reset)
+        .add_route::<0, POST, ResetPassword, _, _>(reset_password)
+        .add_route::<0, POST, NotificationPrefs, _, _>(save_notification_prefs)
+        .add_route::<0, GET, GetNotificationPrefs, _, _>(get_notification_prefs)
+        .add_route::<0, POST, AccountProfile, _, _>(save_profile)
+        .add_route::<1, GET, GetProfile, _, _>(get_profile)
+        .add_route::<1, GET, GetHistory, _, _>(get_history)
+        .add_route::<1, GET, GetStats, _, _>(get_stats)
+        .add_route::<1, POST, RequestDeckCheck, _, _>(request_deck_check)
         .add_route::<0, POST, Login, _, _>(login)
         .add_route::<0, POST, GuestSession, _, _>(guest)
+        .add_route::<0, POST, HandoffSession, _, _>(handoff)
+        .add_route::<1, GET, GetOAuthRedirect, _, _>(oauth_redirect)
+        .add_route::<1, POST, LinkIdentity, _, _>(link_identity)
         .add_route::<0, POST, Reauth, _, _>(reauth)
         .add_route::<0, DELETE, Terminate, _, _>(terminate)
         .add_route::<0, GET, GetSessionStatus, _, _>(status)
+        .add_route::<0, POST, CreateApiToken, _, _>(create_api_token)
+        .add_route::<0, GET, ListApiTokens, _, _>(list_api_tokens)
+        .add_route::<1, DELETE, RevokeApiToken, _, _>(revoke_api_token)
+        .add_route::<0, GET, ListSessions, _, _>(list_sessions)
+        .add_route::<1, DELETE, RevokeSession, _, _>(revoke_session)
+        .add_route::<0, POST, NameSession, _, _>(name_session)
+        .add_route::<0, POST, AcceptInvite, _, _>(accept_invite)
+        .add_route::<0, POST, RedeemInvite, _, _>(redeem_invite)
+        .add_route::<0, POST, UpgradeGuest, _, _>(upgrade_guest)
         .into_router();
 
     #[cfg(not(debug_assertions))]
@@ -38,5 +64,37 @@ pub fn create_router(state: AppState) -> Router {
 #[shuttle_runtime::main]
 async fn axum(#[shuttle_shared_db::MongoDb] db_conn: Database) -> shuttle_axum::ShuttleAxum {
     let app_state = AppStateBuilder::with_db(db_conn).build();
+    let state = app_state.clone();
+    _ = tokio::spawn(async move {
+        shutdown_signal().await;
+        state.shutdown().await;
+        std::process::exit(0);
+    });
     Ok(create_router(app_state).into())
 }
+
+/// Waits until the process is asked to stop, either by Ctrl+C or, on Unix, by SIGTERM
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        _ = tokio::signal::ctrl_c().await;
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut signal) => {
+                _ = signal.recv().await;
+            }
+            Err(_) => std::future::pending().await,
+        }
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
+    tracing::info!("Received shutdown signal");
+}
@@ -0,0 +1,60 @@
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use squire_sdk::{
+    api::*,
+    model::{
+        identifiers::{SquireAccountId, TournamentId},
+        tournament::TournRole,
+    },
+    server::{session::Session, state::ServerState},
+};
+
+use crate::{
+    accounts::ActiveSession,
+    state::{deck_check_notification, AppState},
+};
+
+pub async fn get_notification_prefs(
+    State(state): State<AppState>,
+    Session(ActiveSession(id)): Session<ActiveSession>,
+) -> Json<NotificationPrefs> {
+    Json(state.get_notifications(id).await)
+}
+
+pub async fn save_notification_prefs(
+    State(state): State<AppState>,
+    Session(ActiveSession(id)): Session<ActiveSession>,
+    Json(prefs): Json<NotificationPrefs>,
+) -> Json<bool> {
+    Json(state.set_notifications(id, prefs).await)
+}
+
+/// Notifies players that a judge wants to check their decks. Only the tournament's admins and
+/// judges can request deck checks, and players that are not in the tournament are skipped.
+pub async fn request_deck_check(
+    State(state): State<AppState>,
+    Session(ActiveSession(id)): Session<ActiveSession>,
+    Path(t_id): Path<TournamentId>,
+    Json(RequestDeckCheck { players }): Json<RequestDeckCheck>,
+) -> Json<bool> {
+    let Some(tourn) = state.get_tourn(t_id).await else {
+        return Json(false);
+    };
+    let tourn = tourn.tourn();
+    if !matches!(
+        tourn.user_role(id.0),
+        TournRole::Admin(_) | TournRole::Judge(_)
+    ) {
+        return Json(false);
+    }
+    let note = deck_check_notification(tourn);
+    let notes = players
+        .into_iter()
+        .filter(|p| tourn.player_reg.get_player(p).is_ok())
+        .map(|p| (SquireAccountId::new(p.0), note.clone()))
+        .collect();
+    state.notify(notes);
+    Json(true)
+}
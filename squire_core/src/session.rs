@@ -1,24 +1,34 @@
-use axum::{extract::State, Json};
-use http::StatusCode;
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use chrono::{DateTime, Utc};
+use http::{HeaderMap, StatusCode};
 use squire_sdk::{
-    api::{Login, SessionStatus, SessionToken},
-    model::accounts::SquireAccount,
+    api::{
+        AcceptInvite, ApiToken, ApiTokenId, CreateApiToken, GuestUpgrade, InviteError, LinkError,
+        LinkIdentity, Login, NameSession, NewApiToken, OAuthProvider, OAuthRedirect, RedeemInvite,
+        SessionId, SessionInfo, SessionStatus, SessionToken, TokenParseError, TournamentInvite,
+        UpgradeError, UpgradeGuest,
+    },
+    model::{accounts::SquireAccount, identifiers::SquireAccountId},
     server::{
-        session::{AnyUser, Session, SquireSession},
+        session::{AnyUser, Session, SessionConvert, SquireSession},
         state::ServerState,
     },
 };
 
-use crate::state::AppState;
+use crate::{accounts::ActiveSession, state::AppState};
 
 /// Takes user credentials (username and password) and returns a new session token to them
 /// (provided the credentials match known credentials).
 pub async fn login(
     State(state): State<AppState>,
+    headers: HeaderMap,
     Json(Login(cred)): Json<Login>,
 ) -> Result<(SessionToken, Json<SquireAccount>), StatusCode> {
     let token = state
-        .login(cred)
+        .login(cred, client_ip(&headers))
         .await
         .map_err(|_| StatusCode::BAD_REQUEST)?;
     state
@@ -28,18 +38,51 @@ pub async fn login(
         .ok_or(StatusCode::BAD_REQUEST)
 }
 
+/// Returns where the user should be sent to log in with an OAuth provider. Once they have logged
+/// in, the code that the provider gives them is used with `login`.
+pub async fn oauth_redirect(
+    State(state): State<AppState>,
+    Path(provider): Path<OAuthProvider>,
+) -> Json<Option<OAuthRedirect>> {
+    Json(state.oauth_redirect(provider))
+}
+
+/// Links the identity that the user logged in to an OAuth provider with to their account, so
+/// that they can log in with the provider as well as their password
+pub async fn link_identity(
+    State(state): State<AppState>,
+    Session(CurrentSession(_, id)): Session<CurrentSession>,
+    Path(provider): Path<OAuthProvider>,
+    Json(link): Json<LinkIdentity>,
+) -> Json<Result<(), LinkError>> {
+    Json(
+        state
+            .link_identity(id, provider, link.code, link.state)
+            .await,
+    )
+}
+
 /// Generates a guest session
 pub async fn guest(State(state): State<AppState>) -> SessionToken {
     state.guest_session().await
 }
 
+/// Generates a guest session for a connection handle, which expires shortly after it is created
+pub async fn handoff(State(state): State<AppState>) -> (SessionToken, Json<DateTime<Utc>>) {
+    let (token, expires) = state.handoff_session().await;
+    (token, Json(expires))
+}
+
 /// Reauthenticates a user by issuing a new session token to them. The user must either have an
 /// active session or a recently expired session. Otherwise, they need to go through `login`.
 pub async fn reauth(
     State(state): State<AppState>,
     Session(session): Session<AnyUser>,
-) -> SessionToken {
-    state.reauth_session(session).await
+) -> Result<SessionToken, StatusCode> {
+    state
+        .reauth_session(session)
+        .await
+        .ok_or(StatusCode::UNAUTHORIZED)
 }
 
 /// Terminates a session.
@@ -58,10 +101,12 @@ pub async fn status(
         SquireSession::NotLoggedIn => SessionStatus::NotLoggedIn,
         SquireSession::UnknownUser => SessionStatus::UnknownUser,
         SquireSession::Guest(_) => SessionStatus::Guest,
-        SquireSession::Active(id) => match state.get_account(id).await {
-            Some(acc) => SessionStatus::ActiveUser(acc),
-            None => SessionStatus::UnknownUser,
-        },
+        SquireSession::Active(id) | SquireSession::ApiToken(id) => {
+            match state.get_account(id).await {
+                Some(acc) => SessionStatus::ActiveUser(acc),
+                None => SessionStatus::UnknownUser,
+            }
+        }
         SquireSession::Expired(id) => match state.get_account(id).await {
             Some(acc) => SessionStatus::ExpiredUser(acc),
             None => SessionStatus::UnknownUser,
@@ -70,3 +115,159 @@ pub async fn status(
     };
     Json(status)
 }
+
+/// Creates a long-lived API token for the user
+pub async fn create_api_token(
+    State(state): State<AppState>,
+    Session(CurrentSession(_, id)): Session<CurrentSession>,
+    Json(CreateApiToken { name }): Json<CreateApiToken>,
+) -> Json<NewApiToken> {
+    Json(state.create_api_token(id, name).await)
+}
+
+pub async fn list_api_tokens(
+    State(state): State<AppState>,
+    Session(CurrentSession(_, id)): Session<CurrentSession>,
+) -> Json<Vec<ApiToken>> {
+    Json(state.list_api_tokens(id).await)
+}
+
+pub async fn revoke_api_token(
+    State(state): State<AppState>,
+    Session(CurrentSession(_, id)): Session<CurrentSession>,
+    Path(tok_id): Path<ApiTokenId>,
+) -> Json<bool> {
+    Json(state.revoke_api_token(id, tok_id).await)
+}
+
+/// The token of an active user's session and the account that it belongs to. Unlike
+/// `ActiveSession`, this is for requests that act on the session itself or on how the account is
+/// logged in to, so API tokens are not accepted.
+pub struct CurrentSession(pub SessionToken, pub SquireAccountId);
+
+impl SessionConvert for CurrentSession {
+    type Error = StatusCode;
+
+    fn convert(token: SessionToken, session: SquireSession) -> Result<Self, Self::Error> {
+        if let SquireSession::Active(id) = session {
+            Ok(Self(token, id))
+        } else {
+            Err(StatusCode::UNAUTHORIZED)
+        }
+    }
+
+    fn empty_session(_err: TokenParseError) -> Result<Self, Self::Error> {
+        Err(StatusCode::UNAUTHORIZED)
+    }
+}
+
+/// The name of the environment variable that is set when the server is behind a proxy that sets
+/// the `x-real-ip` header (e.g. nginx). The header is only trusted when this is set, since clients
+/// can send it themselves.
+const TRUSTED_PROXY_VAR: &str = "SQUIRE_TRUSTED_PROXY";
+
+/// Returns the IP address that a request came from. The server is run behind a proxy, so this is
+/// taken from the headers that the proxy adds.
+fn client_ip(headers: &HeaderMap) -> Option<String> {
+    client_ip_from(headers, std::env::var_os(TRUSTED_PROXY_VAR).is_some())
+}
+
+/// Clients can send their own `x-forwarded-for` header, which each proxy appends the address that
+/// it was reached from to. Only the last entry was added by the proxy in front of the server, so
+/// it is the only one that can be trusted.
+fn client_ip_from(headers: &HeaderMap, trust_real_ip: bool) -> Option<String> {
+    let header = |name: &str| headers.get(name).and_then(|val| val.to_str().ok());
+    let real_ip = header("x-real-ip").filter(|_| trust_real_ip);
+    let forwarded = header("x-forwarded-for").and_then(|val| val.rsplit(',').next());
+    real_ip
+        .or(forwarded)
+        .map(str::trim)
+        .filter(|ip| !ip.is_empty())
+        .map(str::to_owned)
+}
+
+/// Lists the sessions that the user is logged in with, most recently used first
+pub async fn list_sessions(
+    State(state): State<AppState>,
+    Session(CurrentSession(token, _)): Session<CurrentSession>,
+) -> Json<Vec<SessionInfo>> {
+    Json(state.list_sessions(token).await)
+}
+
+/// Revokes one of the user's sessions, e.g. one on a device that they no longer use
+pub async fn revoke_session(
+    State(state): State<AppState>,
+    Session(CurrentSession(token, _)): Session<CurrentSession>,
+    Path(sess_id): Path<SessionId>,
+) -> Json<bool> {
+    Json(state.revoke_session(token, sess_id).await)
+}
+
+/// Names the device that the current session is on
+pub async fn name_session(
+    State(state): State<AppState>,
+    Session(CurrentSession(token, _)): Session<CurrentSession>,
+    Json(NameSession { device }): Json<NameSession>,
+) -> Json<bool> {
+    Json(state.name_session(token, device).await)
+}
+
+/// Accepts an invite on behalf of a guest. The invite is held by their session until they register
+/// an account using `upgrade_guest`.
+pub async fn accept_invite(
+    State(state): State<AppState>,
+    Session(session): Session<SquireSession>,
+    Json(AcceptInvite { token: invite }): Json<AcceptInvite>,
+) -> Json<Result<TournamentInvite, InviteError>> {
+    let SquireSession::Guest(token) = session else {
+        return Json(Err(InviteError::Unauthorized));
+    };
+    Json(state.accept_invite(token, invite).await)
+}
+
+/// Gives the user's account the role that an invite grants. Staff invites can only be redeemed
+/// once.
+pub async fn redeem_invite(
+    State(state): State<AppState>,
+    Session(ActiveSession(id)): Session<ActiveSession>,
+    Json(RedeemInvite { token: invite }): Json<RedeemInvite>,
+) -> Json<Result<TournamentInvite, InviteError>> {
+    Json(state.redeem_invite(id, invite).await)
+}
+
+/// Registers an account for a guest. Their session is kept, but now belongs to the account, and
+/// the account is given the roles of the invites that the guest accepted.
+pub async fn upgrade_guest(
+    State(state): State<AppState>,
+    Session(session): Session<SquireSession>,
+    Json(UpgradeGuest(form)): Json<UpgradeGuest>,
+) -> Json<Result<GuestUpgrade, UpgradeError>> {
+    let SquireSession::Guest(token) = session else {
+        return Json(Err(UpgradeError::NotGuest));
+    };
+    Json(state.upgrade_guest(token, form).await)
+}
+
+#[cfg(test)]
+mod tests {
+    use http::HeaderMap;
+
+    use super::client_ip_from;
+
+    #[test]
+    fn only_proxy_added_addresses_are_used() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(client_ip_from(&headers, true), None);
+
+        // The first entries are whatever the client sent
+        _ = headers.insert("x-forwarded-for", "1.1.1.1, 2.2.2.2".parse().unwrap());
+        assert_eq!(client_ip_from(&headers, false).as_deref(), Some("2.2.2.2"));
+
+        // `x-real-ip` is only used behind a proxy that is known to set it
+        _ = headers.insert("x-real-ip", "3.3.3.3".parse().unwrap());
+        assert_eq!(client_ip_from(&headers, false).as_deref(), Some("2.2.2.2"));
+        assert_eq!(client_ip_from(&headers, true).as_deref(), Some("3.3.3.3"));
+        _ = headers.remove("x-forwarded-for");
+        assert_eq!(client_ip_from(&headers, false), None);
+    }
+}
@@ -1,23 +1,38 @@
 use std::{collections::HashMap, future::Future, hash::Hasher};
 
+use argon2::{
+    password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString},
+    Argon2,
+};
 use axum::response::{IntoResponse, Response};
+use chrono::{DateTime, Duration, Utc};
 use derive_more::From;
 use futures::{FutureExt, StreamExt};
 use fxhash::FxHasher;
 use http::StatusCode;
 use mongodb::{
-    bson::{doc, Document},
+    bson::{doc, spec::BinarySubtype, to_bson, Binary, Document},
     options::{UpdateModifications, UpdateOptions},
     Collection, Database,
 };
 use serde::{Deserialize, Serialize};
 use squire_sdk::{
     actor::*,
-    api::{Credentials, RegForm},
+    api::{
+        AccountProfile, ChangePassword, Credentials, LinkError, NotificationChannel,
+        NotificationPrefs, PasswordError, ProfileError, RegForm, RequestPasswordReset,
+        ResetPassword, MIN_PASSWORD_LENGTH,
+    },
     model::{accounts::SquireAccount, identifiers::SquireAccountId},
 };
 use tracing::Level;
 
+use super::{ExternalIdentity, ExternalLogin};
+
+/// How many hours a password reset token can be used for
+const RESET_TOKEN_HOURS: i64 = 1;
+
+#[derive(Debug)]
 pub struct LoginError;
 
 impl IntoResponse for LoginError {
@@ -26,12 +41,35 @@ impl IntoResponse for LoginError {
     }
 }
 
+/// The reasons that an account could not be created
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RegError {
+    /// Another account already has the username
+    UsernameTaken,
+    /// The password is shorter than `MIN_PASSWORD_LENGTH`
+    PasswordTooShort,
+    /// The password could not be hashed
+    NotHashed,
+}
+
+impl IntoResponse for RegError {
+    fn into_response(self) -> Response {
+        match self {
+            RegError::UsernameTaken => StatusCode::CONFLICT.into_response(),
+            RegError::PasswordTooShort => StatusCode::BAD_REQUEST.into_response(),
+            RegError::NotHashed => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct AccountStoreHandle {
     client: ActorClient<AccountStore>,
 }
 
-fn salt_and_hash(password: &str, username: &str) -> u32 {
+/// The hash used for passwords before they were hashed with argon2. It is only used to check the
+/// passwords of accounts that have not logged in since.
+pub(crate) fn salt_and_hash(password: &str, username: &str) -> u32 {
     let mut hasher = FxHasher::default();
     hasher.write(password.as_bytes());
     hasher.write(username.as_bytes());
@@ -39,18 +77,196 @@ fn salt_and_hash(password: &str, username: &str) -> u32 {
     u32::from_be_bytes([hash[0], hash[1], hash[2], hash[3]])
 }
 
+/// Hashes a password with argon2. Hashing is slow by design, so it is done off of the async
+/// runtime's threads.
+async fn hash_password(password: String) -> Option<String> {
+    tokio::task::spawn_blocking(move || {
+        let salt = SaltString::encode_b64(&rand::random::<[u8; 16]>()).ok()?;
+        Argon2::default()
+            .hash_password(password.as_bytes(), &salt)
+            .ok()
+            .map(|hash| hash.to_string())
+    })
+    .await
+    .ok()
+    .flatten()
+}
+
+/// Checks a password against an argon2 hash
+async fn verify_password(password: String, hash: String) -> bool {
+    tokio::task::spawn_blocking(move || {
+        PasswordHash::new(&hash).is_ok_and(|hash| {
+            Argon2::default()
+                .verify_password(password.as_bytes(), &hash)
+                .is_ok()
+        })
+    })
+    .await
+    .unwrap_or_default()
+}
+
+fn reset_token() -> String {
+    rand::random::<[u8; 32]>()
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
+
+/// The outstanding password reset tokens. These are not persisted, so they are lost when the
+/// server restarts.
+#[derive(Debug, Default)]
+struct ResetTokens(HashMap<String, (SquireAccountId, DateTime<Utc>)>);
+
+impl ResetTokens {
+    /// Creates a token for the account that can be used for `RESET_TOKEN_HOURS` after `now`.
+    /// Tokens that have expired are dropped.
+    fn issue(&mut self, id: SquireAccountId, now: DateTime<Utc>) -> String {
+        self.0.retain(|_, (_, expires)| *expires > now);
+        let token = reset_token();
+        self.0.insert(
+            token.clone(),
+            (id, now + Duration::hours(RESET_TOKEN_HOURS)),
+        );
+        token
+    }
+
+    /// Uses up a token. Returns the account that it was created for if it has not expired.
+    fn redeem(&mut self, token: &str, now: DateTime<Utc>) -> Option<SquireAccountId> {
+        self.0
+            .remove(token)
+            .filter(|(_, expires)| *expires > now)
+            .map(|(id, _)| id)
+    }
+
+    /// Drops all of the account's tokens
+    fn revoke(&mut self, id: SquireAccountId) {
+        self.0.retain(|_, (a_id, _)| *a_id != id);
+    }
+}
+
 impl AccountStoreHandle {
     pub fn new(db: Database) -> Self {
         let client = ActorClient::builder(AccountStore::new(db)).launch();
         Self { client }
     }
 
-    pub fn create(&self, item: RegForm) -> Tracker<SquireAccountId> {
-        self.client.track(item)
+    pub async fn create(&self, form: RegForm) -> Result<SquireAccountId, RegError> {
+        let RegForm {
+            username,
+            display_name,
+            password,
+            email,
+        } = form;
+        if password.chars().count() < MIN_PASSWORD_LENGTH {
+            return Err(RegError::PasswordTooShort);
+        }
+        let password = hash_password(password).await.ok_or(RegError::NotHashed)?;
+        let acc = NewAccount {
+            username,
+            display_name,
+            password,
+            email,
+        };
+        let id: Option<SquireAccountId> = self.client.track(acc).await;
+        id.ok_or(RegError::UsernameTaken)
     }
 
-    pub fn authenticate(&self, item: Credentials) -> Tracker<Option<SquireAccountId>> {
-        self.client.track(item)
+    pub async fn authenticate(&self, cred: Credentials) -> Option<SquireAccountId> {
+        let Credentials::Basic { username, password } = cred else {
+            return None;
+        };
+        let stored: Option<StoredCredentials> = self.client.track(username).await;
+        let stored = stored?;
+        let id = stored.id;
+        if stored.password.is_empty() {
+            // The account has not logged in since passwords were hashed with argon2
+            if stored.cred != Some(salt_and_hash(&password, &stored.username)) {
+                return None;
+            }
+            let hash = hash_password(password).await?;
+            let _: bool = self.client.track((id, hash)).await;
+            Some(id)
+        } else {
+            verify_password(password, stored.password)
+                .await
+                .then_some(id)
+        }
+    }
+
+    /// Finds the account that is linked to the external identity. If there isn't one, an account
+    /// is created for it. These accounts have no password.
+    pub fn login_external(&self, login: ExternalLogin) -> Tracker<Option<SquireAccountId>> {
+        self.client.track(login)
+    }
+
+    /// Links an external identity to an existing account, so that the user can log in with it
+    pub fn link_external(
+        &self,
+        id: SquireAccountId,
+        identity: ExternalIdentity,
+    ) -> Tracker<Result<(), LinkError>> {
+        self.client.track((id, identity))
+    }
+
+    pub async fn change_password(
+        &self,
+        id: SquireAccountId,
+        req: ChangePassword,
+    ) -> Result<(), PasswordError> {
+        let ChangePassword {
+            old_password,
+            new_password,
+        } = req;
+        if new_password.chars().count() < MIN_PASSWORD_LENGTH {
+            return Err(PasswordError::TooShort);
+        }
+        let stored: Option<StoredCredentials> = self.client.track(id).await;
+        let cred = stored.map(|stored| Credentials::Basic {
+            username: stored.username,
+            password: old_password,
+        });
+        match cred {
+            Some(cred) if self.authenticate(cred).await == Some(id) => {}
+            _ => return Err(PasswordError::IncorrectPassword),
+        }
+        let hash = hash_password(new_password)
+            .await
+            .ok_or(PasswordError::NotSaved)?;
+        if self.client.track((id, hash)).await {
+            Ok(())
+        } else {
+            Err(PasswordError::NotSaved)
+        }
+    }
+
+    /// Creates a password reset token for the account. The token is returned along with the
+    /// address that it should be sent to. `None` is returned if the account does not exist or
+    /// has no email address.
+    pub fn request_reset(
+        &self,
+        req: RequestPasswordReset,
+    ) -> Tracker<Option<(String, NotificationChannel)>> {
+        self.client.track(req)
+    }
+
+    /// Sets the password of the account that the reset token was created for. That account is
+    /// returned.
+    pub async fn reset_password(
+        &self,
+        req: ResetPassword,
+    ) -> Result<SquireAccountId, PasswordError> {
+        let ResetPassword {
+            token,
+            new_password,
+        } = req;
+        if new_password.chars().count() < MIN_PASSWORD_LENGTH {
+            return Err(PasswordError::TooShort);
+        }
+        let hash = hash_password(new_password)
+            .await
+            .ok_or(PasswordError::NotSaved)?;
+        let id: Option<SquireAccountId> = self.client.track(PasswordReset { token, hash }).await;
+        id.ok_or(PasswordError::InvalidToken)
     }
 
     pub fn get(&self, item: SquireAccountId) -> Tracker<Option<SquireAccount>> {
@@ -60,20 +276,95 @@ impl AccountStoreHandle {
     pub fn delete(&self, item: SquireAccountId) -> Tracker<bool> {
         self.client.track(item)
     }
+
+    pub fn get_notifications(&self, item: SquireAccountId) -> Tracker<Option<NotificationPrefs>> {
+        self.client.track(item)
+    }
+
+    pub fn set_notifications(
+        &self,
+        id: SquireAccountId,
+        prefs: NotificationPrefs,
+    ) -> Tracker<bool> {
+        self.client.track((id, prefs))
+    }
+
+    pub fn get_profile(
+        &self,
+        id: SquireAccountId,
+    ) -> Tracker<Option<(SquireAccount, AccountProfile)>> {
+        self.client.track(id)
+    }
+
+    pub async fn set_profile(
+        &self,
+        id: SquireAccountId,
+        profile: AccountProfile,
+    ) -> Result<(), ProfileError> {
+        profile.validate()?;
+        if self.client.track((id, profile)).await {
+            Ok(())
+        } else {
+            Err(ProfileError::NotSaved)
+        }
+    }
+}
+
+/// An account that is ready to be created, whose password has already been hashed
+pub struct NewAccount {
+    username: String,
+    display_name: String,
+    password: String,
+    email: Option<String>,
+}
+
+/// A password reset whose new password has already been hashed
+pub struct PasswordReset {
+    token: String,
+    hash: String,
+}
+
+/// What is needed to check a user's password
+pub struct StoredCredentials {
+    id: SquireAccountId,
+    username: String,
+    password: String,
+    cred: Option<u32>,
 }
 
 #[derive(From)]
 pub enum AccountCommand {
-    Create(RegForm, OneshotSender<SquireAccountId>),
-    Authenticate(Credentials, OneshotSender<Option<SquireAccountId>>),
+    Create(NewAccount, OneshotSender<Option<SquireAccountId>>),
+    LoginExternal(ExternalLogin, OneshotSender<Option<SquireAccountId>>),
+    LinkExternal(
+        (SquireAccountId, ExternalIdentity),
+        OneshotSender<Result<(), LinkError>>,
+    ),
+    GetCredentials(String, OneshotSender<Option<StoredCredentials>>),
+    GetCredentialsById(SquireAccountId, OneshotSender<Option<StoredCredentials>>),
+    SetPassword((SquireAccountId, String), OneshotSender<bool>),
+    RequestReset(
+        RequestPasswordReset,
+        OneshotSender<Option<(String, NotificationChannel)>>,
+    ),
+    Reset(PasswordReset, OneshotSender<Option<SquireAccountId>>),
     Get(SquireAccountId, OneshotSender<Option<SquireAccount>>),
     Delete(SquireAccountId, OneshotSender<bool>),
+    GetNotifications(SquireAccountId, OneshotSender<Option<NotificationPrefs>>),
+    SetNotifications((SquireAccountId, NotificationPrefs), OneshotSender<bool>),
+    GetProfile(
+        SquireAccountId,
+        OneshotSender<Option<(SquireAccount, AccountProfile)>>,
+    ),
+    SetProfile((SquireAccountId, AccountProfile), OneshotSender<bool>),
 }
 
 #[derive(Debug)]
 pub struct AccountStore {
-    credentials: HashMap<u32, SquireAccountId>,
+    usernames: HashMap<String, SquireAccountId>,
     users: HashMap<SquireAccountId, DbUser>,
+    identities: HashMap<ExternalIdentity, SquireAccountId>,
+    resets: ResetTokens,
     db: AccountDb,
 }
 
@@ -88,11 +379,52 @@ impl ActorState for AccountStore {
 
     async fn process(&mut self, scheduler: &mut Scheduler<Self>, msg: Self::Message) {
         match msg {
-            AccountCommand::Authenticate(cred, send) => drop(send.send(self.authenticate(cred))),
             AccountCommand::Get(id, send) => drop(send.send(self.get_account(id))),
             AccountCommand::Delete(id, send) => drop(send.send(self.delete_account(id, scheduler))),
-            AccountCommand::Create(form, send) => {
-                let _ = send.send(self.create_account(form, scheduler));
+            AccountCommand::Create(acc, send) => {
+                let _ = send.send(self.create_account(acc, scheduler));
+            }
+            AccountCommand::LoginExternal(login, send) => {
+                let _ = send.send(self.login_external(login, scheduler));
+            }
+            AccountCommand::LinkExternal((id, identity), send) => {
+                let _ = send.send(self.link_external(id, identity, scheduler));
+            }
+            AccountCommand::GetCredentials(username, send) => {
+                let id = self.usernames.get(&username).copied();
+                let _ = send.send(id.and_then(|id| self.get_credentials(id)));
+            }
+            AccountCommand::GetCredentialsById(id, send) => {
+                let _ = send.send(self.get_credentials(id));
+            }
+            AccountCommand::SetPassword((id, hash), send) => {
+                let _ = send.send(self.set_password(id, hash, scheduler));
+            }
+            AccountCommand::RequestReset(req, send) => {
+                let _ = send.send(self.create_reset(req));
+            }
+            AccountCommand::Reset(PasswordReset { token, hash }, send) => {
+                let digest = self
+                    .resets
+                    .redeem(&token, Utc::now())
+                    .filter(|id| self.set_password(*id, hash, scheduler));
+                let _ = send.send(digest);
+            }
+            AccountCommand::GetNotifications(id, send) => {
+                let _ = send.send(self.users.get(&id).map(|user| user.notifications.clone()));
+            }
+            AccountCommand::SetNotifications((id, prefs), send) => {
+                let _ = send.send(self.set_notifications(id, prefs, scheduler));
+            }
+            AccountCommand::GetProfile(id, send) => {
+                let profile = self
+                    .users
+                    .get(&id)
+                    .map(|user| (user.account.clone(), user.profile.clone()));
+                let _ = send.send(profile);
+            }
+            AccountCommand::SetProfile((id, profile), send) => {
+                let _ = send.send(self.set_profile(id, profile, scheduler));
             }
         }
     }
@@ -107,48 +439,194 @@ impl AccountStore {
     fn new(db: Database) -> Self {
         Self {
             users: HashMap::new(),
-            credentials: HashMap::new(),
+            usernames: HashMap::new(),
+            identities: HashMap::new(),
+            resets: ResetTokens::default(),
             db: AccountDb::new(db),
         }
     }
 
     fn create_account(
         &mut self,
-        form: RegForm,
+        acc: NewAccount,
         scheduler: &mut Scheduler<Self>,
-    ) -> SquireAccountId {
-        let cred: Credentials = form.clone().into();
-        let Credentials::Basic { username, password } = cred;
-        let cred = salt_and_hash(&password, &username);
-        if let Some(id) = self.credentials.get(&cred) {
-            return *id;
-        }
-        let RegForm {
+    ) -> Option<SquireAccountId> {
+        let NewAccount {
             username,
             display_name,
-            ..
-        } = form;
-        let account = SquireAccount::new(username, display_name);
+            password,
+            email,
+        } = acc;
+        if self.usernames.contains_key(&username) {
+            return None;
+        }
+        let account = SquireAccount::new(username.clone(), display_name);
         let digest = account.id;
-        let user = DbUser { account, cred };
+        let user = DbUser {
+            account,
+            password,
+            cred: None,
+            email,
+            notifications: NotificationPrefs::default(),
+            identities: Vec::new(),
+            profile: AccountProfile::default(),
+        };
         scheduler.process(self.db.persist_account(user.clone()));
-        self.credentials.insert(cred, digest);
+        self.usernames.insert(username, digest);
         self.users.insert(digest, user);
-        digest
+        Some(digest)
+    }
+
+    fn login_external(
+        &mut self,
+        login: ExternalLogin,
+        scheduler: &mut Scheduler<Self>,
+    ) -> Option<SquireAccountId> {
+        if let Some(id) = self.identities.get(&login.identity) {
+            return Some(*id);
+        }
+        let ExternalLogin {
+            identity,
+            name,
+            email,
+        } = login;
+        // The provider's name for the user might already be taken, so a number is added to it
+        let username = std::iter::once(name.clone())
+            .chain((2..).map(|n| format!("{name}#{n}")))
+            .find(|username| !self.usernames.contains_key(username))?;
+        let account = SquireAccount::new(username.clone(), name);
+        let digest = account.id;
+        let user = DbUser {
+            account,
+            password: String::new(),
+            cred: None,
+            email,
+            notifications: NotificationPrefs::default(),
+            identities: vec![identity.clone()],
+            profile: AccountProfile::default(),
+        };
+        scheduler.process(self.db.persist_account(user.clone()));
+        self.usernames.insert(username, digest);
+        self.identities.insert(identity, digest);
+        self.users.insert(digest, user);
+        Some(digest)
+    }
+
+    fn link_external(
+        &mut self,
+        id: SquireAccountId,
+        identity: ExternalIdentity,
+        scheduler: &mut Scheduler<Self>,
+    ) -> Result<(), LinkError> {
+        match self.identities.get(&identity) {
+            Some(linked) if *linked == id => return Ok(()),
+            Some(_) => return Err(LinkError::LinkedToOtherAccount),
+            None => {}
+        }
+        let user = self.users.get_mut(&id).ok_or(LinkError::NotSaved)?;
+        let doc = to_bson(&identity).map_err(|_| LinkError::NotSaved)?;
+        user.identities.push(identity.clone());
+        self.identities.insert(identity, id);
+        scheduler.process(
+            self.db
+                .update_account(id, doc! {"$push": { "identities": doc }}),
+        );
+        Ok(())
+    }
+
+    fn get_credentials(&self, id: SquireAccountId) -> Option<StoredCredentials> {
+        self.users.get(&id).map(|user| StoredCredentials {
+            id,
+            username: user.account.user_name.clone(),
+            password: user.password.clone(),
+            cred: user.cred,
+        })
+    }
+
+    fn set_password(
+        &mut self,
+        id: SquireAccountId,
+        hash: String,
+        scheduler: &mut Scheduler<Self>,
+    ) -> bool {
+        let Some(user) = self.users.get_mut(&id) else {
+            return false;
+        };
+        user.password = hash.clone();
+        user.cred = None;
+        self.resets.revoke(id);
+        let update = doc! {
+            "$set": { "password": hash },
+            "$unset": { "cred": "" },
+        };
+        scheduler.process(self.db.update_account(id, update));
+        true
     }
 
-    fn authenticate(&mut self, cred: Credentials) -> Option<SquireAccountId> {
-        let Credentials::Basic { username, password } = cred;
-        let hash = salt_and_hash(&password, &username);
-        self.credentials.get(&hash).cloned()
+    fn create_reset(&mut self, req: RequestPasswordReset) -> Option<(String, NotificationChannel)> {
+        let id = *self.usernames.get(&req.username)?;
+        let user = self.users.get(&id)?;
+        // Fall back to the addresses that the user gets notifications at
+        let channel = match &user.email {
+            Some(email) => NotificationChannel::Email(email.clone()),
+            None => user
+                .notifications
+                .channels
+                .iter()
+                .find(|c| matches!(c, NotificationChannel::Email(_)))
+                .cloned()?,
+        };
+        Some((self.resets.issue(id, Utc::now()), channel))
     }
 
     fn get_account(&mut self, id: SquireAccountId) -> Option<SquireAccount> {
         self.users.get(&id).map(|user| &user.account).cloned()
     }
 
+    fn set_notifications(
+        &mut self,
+        id: SquireAccountId,
+        prefs: NotificationPrefs,
+        scheduler: &mut Scheduler<Self>,
+    ) -> bool {
+        let Some(user) = self.users.get_mut(&id) else {
+            return false;
+        };
+        user.notifications = prefs.clone();
+        let Ok(prefs) = to_bson(&prefs) else {
+            return false;
+        };
+        scheduler.process(
+            self.db
+                .update_account(id, doc! {"$set": { "notifications": prefs }}),
+        );
+        true
+    }
+
+    fn set_profile(
+        &mut self,
+        id: SquireAccountId,
+        profile: AccountProfile,
+        scheduler: &mut Scheduler<Self>,
+    ) -> bool {
+        let Some(user) = self.users.get_mut(&id) else {
+            return false;
+        };
+        user.profile = profile.clone();
+        let Ok(profile) = to_bson(&profile) else {
+            return false;
+        };
+        scheduler.process(
+            self.db
+                .update_account(id, doc! {"$set": { "profile": profile }}),
+        );
+        true
+    }
+
     fn delete_account(&mut self, id: SquireAccountId, scheduler: &mut Scheduler<Self>) -> bool {
-        self.credentials.retain(|_, a_id| id != *a_id);
+        self.usernames.retain(|_, a_id| id != *a_id);
+        self.identities.retain(|_, a_id| id != *a_id);
+        self.resets.revoke(id);
         if let Some(user) = self.users.remove(&id) {
             scheduler.process(self.db.remove_account(user));
             true
@@ -161,8 +639,25 @@ impl AccountStore {
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct DbUser {
     account: SquireAccount,
-    /// The salted and hashed password.
-    cred: u32,
+    /// The password, hashed with argon2 and stored in the PHC string format
+    #[serde(default)]
+    password: String,
+    /// The salted and hashed password from before passwords were hashed with argon2. It is
+    /// replaced by `password` the next time that the user logs in.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    cred: Option<u32>,
+    /// The address that password reset tokens are sent to
+    #[serde(default)]
+    email: Option<String>,
+    /// What the user wants to be notified about and where
+    #[serde(default)]
+    notifications: NotificationPrefs,
+    /// The OAuth identities that can be used to log in to the account
+    #[serde(default)]
+    identities: Vec<ExternalIdentity>,
+    /// The optional parts of the user's profile
+    #[serde(default)]
+    profile: AccountProfile,
 }
 
 impl AccountDb {
@@ -181,7 +676,12 @@ impl AccountDb {
         let mut cursor = self.get_table().find(None, None).await.unwrap();
         while let Some(acc) = cursor.next().await {
             if let Ok(user) = acc {
-                cache.credentials.insert(user.cred, user.account.id);
+                cache
+                    .usernames
+                    .insert(user.account.user_name.clone(), user.account.id);
+                for identity in &user.identities {
+                    cache.identities.insert(identity.clone(), user.account.id);
+                }
                 cache.users.insert(user.account.id, user);
             }
         }
@@ -192,6 +692,15 @@ impl AccountDb {
         persist_account(table, acc).map(drop)
     }
 
+    fn update_account(
+        &self,
+        id: SquireAccountId,
+        update: Document,
+    ) -> impl 'static + Future<Output = ()> {
+        let table = self.get_table();
+        update_account(table, id, update).map(drop)
+    }
+
     fn remove_account(&self, acc: DbUser) -> impl 'static + Future<Output = ()> {
         let table = self.get_table();
         delete_account(table, acc).map(drop)
@@ -199,7 +708,7 @@ impl AccountDb {
 }
 
 async fn persist_account(table: Collection<DbUser>, account: DbUser) -> bool {
-    println!("Saving user: {account:?}");
+    tracing::debug!(account = %account.account.id, "Saving user");
     let doc: Document = mongodb::bson::to_raw_document_buf(&account)
         .unwrap()
         .try_into()
@@ -216,7 +725,8 @@ async fn persist_account(table: Collection<DbUser>, account: DbUser) -> bool {
         if let Err(err) = table.insert_one(account.clone(), None).await {
             tracing::event!(
                 Level::WARN,
-                "Could not persist session `{account:?}` got error: {err}",
+                "Could not persist account `{:?}` got error: {err}",
+                account.account,
             );
             return false;
         }
@@ -224,6 +734,24 @@ async fn persist_account(table: Collection<DbUser>, account: DbUser) -> bool {
     true
 }
 
+/// Applies an update to one of the accounts
+async fn update_account(table: Collection<DbUser>, id: SquireAccountId, update: Document) -> bool {
+    let query = doc! { "account.id": Binary {
+        bytes: id.as_bytes().to_vec(),
+        subtype: BinarySubtype::Generic,
+    }};
+    match table.update_one(query, update, None).await {
+        Ok(_) => true,
+        Err(err) => {
+            tracing::event!(
+                Level::WARN,
+                "Could not update account `{id}` got error: {err}",
+            );
+            false
+        }
+    }
+}
+
 async fn delete_account(table: Collection<DbUser>, account: DbUser) -> bool {
     let doc: Document = mongodb::bson::to_raw_document_buf(&account)
         .unwrap()
@@ -231,3 +759,46 @@ async fn delete_account(table: Collection<DbUser>, account: DbUser) -> bool {
         .unwrap();
     table.delete_one(doc, None).await.is_ok()
 }
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, Utc};
+    use squire_tests::spoof_account;
+
+    use super::{ResetTokens, RESET_TOKEN_HOURS};
+
+    #[test]
+    fn reset_tokens_expire() {
+        let mut resets = ResetTokens::default();
+        let id = spoof_account().id;
+        let now = Utc::now();
+
+        // Tokens can only be used once
+        let token = resets.issue(id, now);
+        assert_eq!(resets.redeem(&token, now + Duration::minutes(59)), Some(id));
+        assert_eq!(resets.redeem(&token, now), None);
+
+        let token = resets.issue(id, now);
+        let expiry = now + Duration::hours(RESET_TOKEN_HOURS);
+        assert_eq!(resets.redeem(&token, expiry), None);
+
+        // Expired tokens are dropped when the next token is made
+        let old = resets.issue(id, now);
+        let new = resets.issue(id, expiry);
+        assert!(!resets.0.contains_key(&old));
+        assert_eq!(resets.redeem(&new, expiry), Some(id));
+    }
+
+    #[test]
+    fn reset_tokens_are_revoked_per_account() {
+        let mut resets = ResetTokens::default();
+        let (id, other) = (spoof_account().id, spoof_account().id);
+        let now = Utc::now();
+
+        let token = resets.issue(id, now);
+        let other_token = resets.issue(other, now);
+        resets.revoke(id);
+        assert_eq!(resets.redeem(&token, now), None);
+        assert_eq!(resets.redeem(&other_token, now), Some(other));
+    }
+}
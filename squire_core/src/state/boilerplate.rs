@@ -7,10 +7,20 @@ impl Debug for SessionCommand {
         match self {
             Self::Create(value, _) => write!(f, "Create({value:?})"),
             Self::Guest(_) => write!(f, "Guest()"),
+            Self::Handoff(value, _) => write!(f, "Handoff({value:?})"),
             Self::Get(value, _) => write!(f, "Get({value:?})"),
             Self::Reauth(value, _) => write!(f, "Reauth({value:?})"),
             Self::Delete(value, _) => write!(f, "Delete({value:?})"),
             Self::Subscribe(value, _) => write!(f, "Subscribe({value:?})"),
+            Self::CreateApiToken(value, _) => write!(f, "CreateApiToken({value:?})"),
+            Self::ListApiTokens(value, _) => write!(f, "ListApiTokens({value:?})"),
+            Self::RevokeApiToken(value, _) => write!(f, "RevokeApiToken({value:?})"),
+            Self::ListSessions(value, _) => write!(f, "ListSessions({value:?})"),
+            Self::RevokeSession(value, _) => write!(f, "RevokeSession({value:?})"),
+            Self::RevokeAccount(value, _) => write!(f, "RevokeAccount({value:?})"),
+            Self::NameSession(value, _) => write!(f, "NameSession({value:?})"),
+            Self::HoldInvite(value, _) => write!(f, "HoldInvite({value:?})"),
+            Self::Upgrade(value, _) => write!(f, "Upgrade({value:?})"),
             Self::Expiry(value) => write!(f, "Expiry({value:?})"),
             Self::Revoke(value) => write!(f, "Revoke({value:?})"),
         }
@@ -0,0 +1,51 @@
+use futures::StreamExt;
+use mongodb::{
+    bson::{doc, spec::BinarySubtype, Binary},
+    options::FindOptions,
+    Collection, Database,
+};
+use squire_sdk::{api::LifecycleEvent, model::identifiers::SquireAccountId};
+use tracing::Level;
+
+/// Stores the lifecycle events of tournaments, separately from their operation logs
+#[derive(Debug, Clone)]
+pub struct EventDb {
+    db_conn: Database,
+}
+
+impl EventDb {
+    const EVENT_COLL_NAME: &'static str = "LifecycleEvents";
+
+    pub fn new(db_conn: Database) -> Self {
+        Self { db_conn }
+    }
+
+    fn get_events(&self) -> Collection<LifecycleEvent> {
+        self.db_conn.collection(Self::EVENT_COLL_NAME)
+    }
+
+    pub async fn record_events(&self, events: Vec<LifecycleEvent>) {
+        if events.is_empty() {
+            return;
+        }
+        if let Err(err) = self.get_events().insert_many(events, None).await {
+            tracing::event!(
+                Level::WARN,
+                "Could not record lifecycle events due to error: {err}"
+            );
+        }
+    }
+
+    /// Returns all of the events for an organization, oldest first
+    pub async fn get_org_events(&self, org: SquireAccountId) -> Vec<LifecycleEvent> {
+        let query = doc! { "org": Binary {
+            bytes: org.as_bytes().to_vec(),
+            subtype: BinarySubtype::Generic,
+        }};
+        let opts = FindOptions::builder().sort(doc! { "time": 1 }).build();
+        let Ok(cursor) = self.get_events().find(Some(query), opts).await else {
+            return Vec::new();
+        };
+        cursor.filter_map(|e| async { e.ok() }).collect().await
+    }
+}
@@ -0,0 +1,129 @@
+use futures::StreamExt;
+use mongodb::{
+    bson::{doc, spec::BinarySubtype, Binary},
+    Collection, Database,
+};
+use serde::{Deserialize, Serialize};
+use squire_sdk::{
+    api::{HistoryEntry, PlayerStats},
+    model::{
+        identifiers::{SquireAccountId, TournamentId},
+        settings::Visibility,
+    },
+    sync::TournamentManager,
+};
+use tracing::Level;
+
+use super::StatsCache;
+
+/// An entry in the index from accounts to the tournaments that they played in
+#[derive(Debug, Serialize, Deserialize)]
+struct HistoryRecord {
+    account: SquireAccountId,
+    tourn: TournamentId,
+    /// Whether the player shared their name with the tournament. Other users only see the
+    /// tournaments that the player shared their name with.
+    shares_name: bool,
+    /// The tournament's visibility. Other users only see the tournaments that are public.
+    /// Entries from before this was recorded are treated as public.
+    #[serde(default)]
+    visibility: Visibility,
+    entry: HistoryEntry,
+}
+
+/// Stores which tournaments each account has played in. The index is rebuilt for a tournament
+/// each time that it is persisted, and when synced operations change what is in its players'
+/// histories.
+#[derive(Debug, Clone)]
+pub struct HistoryDb {
+    db_conn: Database,
+    stats: StatsCache,
+}
+
+impl HistoryDb {
+    const HISTORY_COLL_NAME: &'static str = "History";
+
+    pub fn new(db_conn: Database) -> Self {
+        Self {
+            db_conn,
+            stats: StatsCache::new(),
+        }
+    }
+
+    fn get_records(&self) -> Collection<HistoryRecord> {
+        self.db_conn.collection(Self::HISTORY_COLL_NAME)
+    }
+
+    /// Replaces the index entries of the tournament's players. Guests are indexed too, but their
+    /// ids are never used to look up a history.
+    pub async fn index_tourn(&self, tourn: &TournamentManager) {
+        let query = doc! { "tourn": Binary {
+            bytes: tourn.id.as_bytes().to_vec(),
+            subtype: BinarySubtype::Generic,
+        }};
+        let records: Vec<_> = HistoryEntry::for_tourn(tourn)
+            .into_iter()
+            .map(|entry| HistoryRecord {
+                account: entry.player.0.into(),
+                tourn: tourn.id,
+                shares_name: tourn
+                    .player_reg
+                    .players
+                    .get(&entry.player)
+                    .map_or(true, |p| p.sharing.shares_name()),
+                visibility: tourn.settings.visibility,
+                entry,
+            })
+            .collect();
+        let accounts: Vec<_> = records.iter().map(|r| r.account).collect();
+        let coll = self.get_records();
+        let result = match coll.delete_many(query, None).await {
+            Ok(_) if records.is_empty() => Ok(()),
+            Ok(_) => coll.insert_many(records, None).await.map(drop),
+            Err(err) => Err(err),
+        };
+        if let Err(err) = result {
+            tracing::event!(
+                Level::WARN,
+                r#"Could not index the players of tournament with id "{}" due to error: {err}"#,
+                tourn.id,
+            );
+        }
+        for account in accounts {
+            self.stats.invalidate(account);
+        }
+    }
+
+    /// Returns the tournaments that the account played in, most recently active first. Unless
+    /// `all` is set (i.e. the account is looking at its own history), only the public tournaments
+    /// that the player shared their name with are returned.
+    pub async fn get_history(&self, account: SquireAccountId, all: bool) -> Vec<HistoryEntry> {
+        let query = doc! { "account": Binary {
+            bytes: account.as_bytes().to_vec(),
+            subtype: BinarySubtype::Generic,
+        }};
+        let Ok(cursor) = self.get_records().find(Some(query), None).await else {
+            return Vec::new();
+        };
+        let mut digest: Vec<_> = cursor
+            .filter_map(|r| async { r.ok() })
+            .filter(|r| std::future::ready(all || (r.shares_name && r.visibility.is_listed())))
+            .map(|r| r.entry)
+            .collect()
+            .await;
+        digest.sort_by(|a, b| b.last_active.cmp(&a.last_active));
+        digest
+    }
+
+    /// Returns the account's stats, which are only recalculated once one of the tournaments that
+    /// the account played in is persisted. Unless `all` is set, only the tournaments that the
+    /// player shared their name with in public tournaments are counted.
+    pub async fn get_stats(&self, account: SquireAccountId, all: bool) -> PlayerStats {
+        if let Some(stats) = self.stats.get(account, all) {
+            return stats;
+        }
+        let stats = PlayerStats::new(&self.get_history(account, all).await);
+        self.stats.insert(account, all, stats.clone());
+        stats
+    }
+}
@@ -0,0 +1,400 @@
+use std::{
+    collections::HashSet,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use chrono::Utc;
+use futures::StreamExt;
+use mongodb::{
+    bson::{doc, spec::BinarySubtype, Binary, Document},
+    error::{Error, ErrorKind, WriteError, WriteFailure},
+    options::{IndexOptions, UpdateOptions},
+    Collection, Database, IndexModel,
+};
+use serde::{Deserialize, Serialize};
+use squire_sdk::{
+    actor::ActorClient,
+    model::tournament::TournamentId,
+    server::{
+        gathering::{GatheringHall, GatheringHallMessage},
+        state::GatheringNode,
+    },
+};
+use tracing::Level;
+
+use super::TournPersister;
+
+/// The name of the environment variable that holds the address that other nodes use to reach this
+/// node, e.g. `ws://10.0.0.2:8000`
+pub(super) const NODE_URL_VAR: &str = "SQUIRE_NODE_URL";
+
+/// How long a lease lasts without being renewed. If a node goes down, its gatherings can be taken
+/// over once this much time has passed.
+const LEASE_DURATION: Duration = Duration::from_secs(30);
+
+/// How often a node renews the leases that it holds
+const RENEW_INTERVAL: Duration = Duration::from_secs(10);
+
+/// A node's claim on hosting a tournament's gathering
+#[derive(Debug, Serialize, Deserialize)]
+struct GatheringLease {
+    tourn: TournamentId,
+    /// The address of the node that hosts the gathering
+    node: String,
+    /// When the lease runs out, in milliseconds since the Unix epoch
+    expires: i64,
+}
+
+/// Coordinates which node hosts each tournament's gathering when several servers share a
+/// database. A node takes a lease on a tournament when the first user connects to it, and users
+/// that connect to other nodes are proxied to the lease holder. Leases are renewed for as long as
+/// the node is running and released when it shuts down. If a lease is lost anyway (e.g. the node
+/// could not reach the database for a while and another node took the gathering over), the
+/// gathering is closed.
+#[derive(Debug, Clone)]
+pub struct LeaseDb {
+    db_conn: Database,
+    node: Arc<str>,
+    /// The tournaments that this node holds leases on
+    held: Arc<Mutex<HashSet<TournamentId>>>,
+    gatherings: ActorClient<GatheringHall<TournPersister>>,
+}
+
+impl LeaseDb {
+    const LEASE_COLL_NAME: &'static str = "GatheringLeases";
+
+    /// Creates a lease store for this node and starts renewing its leases in the background
+    pub fn new(
+        db_conn: Database,
+        node: impl Into<Arc<str>>,
+        gatherings: ActorClient<GatheringHall<TournPersister>>,
+    ) -> Self {
+        let digest = Self {
+            db_conn,
+            node: node.into(),
+            held: Arc::default(),
+            gatherings,
+        };
+        let leases = digest.clone();
+        _ = tokio::spawn(async move {
+            leases.create_index().await;
+            loop {
+                tokio::time::sleep(RENEW_INTERVAL).await;
+                leases.renew_all().await;
+            }
+        });
+        digest
+    }
+
+    /// Creates a lease store from the `SQUIRE_NODE_URL` environment variable. `None` is returned
+    /// if it is not set, in which case the server runs as a single node.
+    pub fn from_env(
+        db_conn: Database,
+        gatherings: ActorClient<GatheringHall<TournPersister>>,
+    ) -> Option<Self> {
+        match std::env::var(NODE_URL_VAR) {
+            Ok(node) => Some(Self::new(db_conn, node, gatherings)),
+            Err(_) => {
+                tracing::info!("{NODE_URL_VAR} is not set. Running as a single node.");
+                None
+            }
+        }
+    }
+
+    fn get_leases(&self) -> Collection<GatheringLease> {
+        self.db_conn.collection(Self::LEASE_COLL_NAME)
+    }
+
+    fn make_query(id: TournamentId) -> Document {
+        doc! { "tourn": Binary {
+            bytes: id.as_bytes().to_vec(),
+            subtype: BinarySubtype::Generic,
+        }}
+    }
+
+    /// Makes sure that a tournament can only have one lease, which is what keeps two nodes from
+    /// taking a lease at the same time
+    async fn create_index(&self) {
+        let index = IndexModel::builder()
+            .keys(doc! { "tourn": 1 })
+            .options(IndexOptions::builder().unique(true).build())
+            .build();
+        if let Err(err) = self.get_leases().create_index(index, None).await {
+            tracing::event!(
+                Level::WARN,
+                "Could not create the gathering lease index due to error: {err}"
+            );
+        }
+    }
+
+    /// Takes or renews the lease on the tournament's gathering. If another node holds a lease that
+    /// has not run out, the address of that node is returned instead.
+    pub async fn acquire(&self, id: TournamentId) -> GatheringNode {
+        // The other node's lease can run out (or be released) between the upsert and looking up
+        // who holds it, in which case the lease is free to take
+        match self.try_acquire(id).await {
+            Some(node) => node,
+            None => self.try_acquire(id).await.unwrap_or(GatheringNode::Unknown),
+        }
+    }
+
+    /// Tries to take or renew the lease on the tournament's gathering. `None` is returned if
+    /// another node held the lease when it was taken but no longer holds it.
+    async fn try_acquire(&self, id: TournamentId) -> Option<GatheringNode> {
+        let now = Utc::now().timestamp_millis();
+        let mut query = Self::make_query(id);
+        let _ = query.insert(
+            "$or",
+            vec![
+                doc! { "node": &*self.node },
+                doc! { "expires": { "$lt": now } },
+            ],
+        );
+        let update = doc! { "$set": {
+            "node": &*self.node,
+            "expires": now + LEASE_DURATION.as_millis() as i64,
+        }};
+        let opts = UpdateOptions::builder().upsert(true).build();
+        match self.get_leases().update_one(query, update, opts).await {
+            Ok(_) => {
+                _ = self.held.lock().unwrap().insert(id);
+                Some(GatheringNode::Local)
+            }
+            // The unique index turns the upsert away when another node holds a live lease
+            Err(err) if is_duplicate_key(&err) => match self.owner(id).await {
+                Some(node) if *node != *self.node => Some(GatheringNode::Remote(node)),
+                _ => None,
+            },
+            Err(err) => {
+                tracing::event!(
+                    Level::WARN,
+                    "Could not take the lease on {id} due to error: {err}"
+                );
+                Some(GatheringNode::Unknown)
+            }
+        }
+    }
+
+    /// Returns the address of the node that holds the lease on the tournament's gathering, if the
+    /// lease has not run out
+    pub async fn owner(&self, id: TournamentId) -> Option<String> {
+        let mut query = Self::make_query(id);
+        let _ = query.insert("expires", doc! { "$gt": Utc::now().timestamp_millis() });
+        self.get_leases()
+            .find_one(Some(query), None)
+            .await
+            .ok()
+            .flatten()
+            .map(|lease| lease.node)
+    }
+
+    /// Returns whether the node holds a lease on the tournament's gathering that has not run out.
+    /// This is checked without a `LeaseDb`, since the persister is created before the leases are.
+    pub(super) async fn is_held_by(db_conn: &Database, node: &str, id: TournamentId) -> bool {
+        let mut query = Self::make_query(id);
+        let _ = query.insert("node", node);
+        let _ = query.insert("expires", doc! { "$gt": Utc::now().timestamp_millis() });
+        db_conn
+            .collection::<GatheringLease>(Self::LEASE_COLL_NAME)
+            .count_documents(query, None)
+            .await
+            .is_ok_and(|count| count != 0)
+    }
+
+    /// Renews every lease that this node holds. Gatherings live for as long as the node does, so
+    /// all of them are renewed. The gatherings of any leases that were lost are closed.
+    async fn renew_all(&self) {
+        let held = self.held.lock().unwrap().len();
+        if held == 0 {
+            return;
+        }
+        let expires = Utc::now().timestamp_millis() + LEASE_DURATION.as_millis() as i64;
+        match self
+            .get_leases()
+            .update_many(
+                doc! { "node": &*self.node },
+                doc! { "$set": { "expires": expires } },
+                None,
+            )
+            .await
+        {
+            Ok(result) if result.matched_count as usize >= held => {}
+            Ok(_) => self.close_lost().await,
+            Err(err) => {
+                tracing::event!(
+                    Level::WARN,
+                    "Could not renew gathering leases due to error: {err}"
+                );
+            }
+        }
+    }
+
+    /// Finds the leases that this node thought it held but that are now held by other nodes, and
+    /// closes their gatherings
+    async fn close_lost(&self) {
+        let Ok(cursor) = self
+            .get_leases()
+            .find(doc! { "node": &*self.node }, None)
+            .await
+        else {
+            return;
+        };
+        let still_held: HashSet<_> = cursor
+            .filter_map(|lease| async { lease.ok().map(|lease| lease.tourn) })
+            .collect()
+            .await;
+        let lost: Vec<_> = {
+            let mut held = self.held.lock().unwrap();
+            let lost = held.difference(&still_held).copied().collect();
+            held.retain(|id| still_held.contains(id));
+            lost
+        };
+        for id in lost {
+            tracing::event!(
+                Level::WARN,
+                "Lost the lease on {id}, so its gathering is being closed"
+            );
+            self.gatherings
+                .send(GatheringHallMessage::CloseGathering(id));
+        }
+    }
+
+    /// Releases every lease that this node holds so that other nodes can take over its gatherings
+    /// right away
+    pub async fn release_all(&self) {
+        self.held.lock().unwrap().clear();
+        if let Err(err) = self
+            .get_leases()
+            .delete_many(doc! { "node": &*self.node }, None)
+            .await
+        {
+            tracing::event!(
+                Level::WARN,
+                "Could not release gathering leases due to error: {err}"
+            );
+        }
+    }
+}
+
+/// Calculates if the error is the unique index turning away a second lease on a tournament
+fn is_duplicate_key(err: &Error) -> bool {
+    matches!(
+        err.kind.as_ref(),
+        ErrorKind::Write(WriteFailure::WriteError(WriteError { code: 11000, .. }))
+    )
+}
+
+#[cfg(all(test, feature = "db-tests"))]
+mod tests {
+    use chrono::Utc;
+    use mongodb::bson::doc;
+    use squire_sdk::{
+        model::{identifiers::id_from_item, tournament::TournamentId},
+        server::state::GatheringNode,
+    };
+
+    use super::{is_duplicate_key, GatheringLease, LeaseDb};
+    use crate::state::{AppState, AppStateBuilder};
+
+    fn new_id() -> TournamentId {
+        id_from_item(Utc::now(), rand::random::<u64>())
+    }
+
+    async fn nodes(db_name: &str) -> (AppState, LeaseDb, LeaseDb) {
+        let state = AppStateBuilder::new().database_name(db_name).build().await;
+        state.get_db().drop(None).await.unwrap();
+        let a = LeaseDb::new(state.get_db(), "ws://node-a", state.gatherings.clone());
+        let b = LeaseDb::new(state.get_db(), "ws://node-b", state.gatherings.clone());
+        a.create_index().await;
+        (state, a, b)
+    }
+
+    /// Makes every lease on the tournament run out
+    async fn expire(leases: &LeaseDb, id: TournamentId) {
+        let _ = leases
+            .get_leases()
+            .update_many(
+                LeaseDb::make_query(id),
+                doc! { "$set": { "expires": 0_i64 } },
+                None,
+            )
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn expired_leases_are_taken_over() {
+        let (_state, a, b) = nodes("SquireTesting_lease_takeover").await;
+        let id = new_id();
+        assert_eq!(a.acquire(id).await, GatheringNode::Local);
+        assert_eq!(a.acquire(id).await, GatheringNode::Local);
+        assert_eq!(
+            b.acquire(id).await,
+            GatheringNode::Remote("ws://node-a".to_owned())
+        );
+
+        expire(&a, id).await;
+        assert_eq!(b.acquire(id).await, GatheringNode::Local);
+        assert_eq!(
+            a.acquire(id).await,
+            GatheringNode::Remote("ws://node-b".to_owned())
+        );
+        assert_eq!(a.owner(id).await.as_deref(), Some("ws://node-b"));
+    }
+
+    #[tokio::test]
+    async fn lost_leases_are_noticed_when_renewing() {
+        let (_state, a, b) = nodes("SquireTesting_lease_loss").await;
+        let kept = new_id();
+        let lost = new_id();
+        assert_eq!(a.acquire(kept).await, GatheringNode::Local);
+        assert_eq!(a.acquire(lost).await, GatheringNode::Local);
+
+        // Another node takes over while this node is cut off from the database
+        expire(&a, lost).await;
+        assert_eq!(b.acquire(lost).await, GatheringNode::Local);
+
+        a.renew_all().await;
+        let held = a.held.lock().unwrap().clone();
+        assert!(held.contains(&kept));
+        assert!(!held.contains(&lost));
+        assert_eq!(a.owner(kept).await.as_deref(), Some("ws://node-a"));
+        assert_eq!(a.owner(lost).await.as_deref(), Some("ws://node-b"));
+    }
+
+    #[tokio::test]
+    async fn only_live_leases_are_held() {
+        let (state, a, b) = nodes("SquireTesting_lease_held").await;
+        let db = state.get_db();
+        let id = new_id();
+        assert!(!LeaseDb::is_held_by(&db, "ws://node-a", id).await);
+        assert_eq!(a.acquire(id).await, GatheringNode::Local);
+        assert!(LeaseDb::is_held_by(&db, "ws://node-a", id).await);
+        assert!(!LeaseDb::is_held_by(&db, "ws://node-b", id).await);
+
+        // Once the lease is taken over, the old holder may no longer append operations
+        expire(&a, id).await;
+        assert!(!LeaseDb::is_held_by(&db, "ws://node-a", id).await);
+        assert_eq!(b.acquire(id).await, GatheringNode::Local);
+        assert!(LeaseDb::is_held_by(&db, "ws://node-b", id).await);
+    }
+
+    #[tokio::test]
+    async fn second_leases_are_duplicate_keys() {
+        let (_state, a, _) = nodes("SquireTesting_lease_duplicates").await;
+        let lease = || GatheringLease {
+            tourn: new_id(),
+            node: "ws://node-a".to_owned(),
+            expires: 0,
+        };
+        let first = lease();
+        let mut second = lease();
+        second.tourn = first.tourn;
+        let _ = a.get_leases().insert_one(&first, None).await.unwrap();
+        let err = a.get_leases().insert_one(&second, None).await.unwrap_err();
+        assert!(is_duplicate_key(&err));
+        // Leases on other tournaments are not turned away
+        assert!(a.get_leases().insert_one(lease(), None).await.is_ok());
+    }
+}
@@ -0,0 +1,99 @@
+use mongodb::bson::{Bson, Document};
+
+/// The schema version of the tournament documents that this server writes. Whenever a model change
+/// would break the deserialization of persisted tournaments, bump this and add a migration that
+/// upgrades documents from the previous version.
+pub const SCHEMA_VERSION: i32 = 1;
+
+/// A step that upgrades a persisted tournament from the previous schema version
+struct Migration {
+    /// The schema version that the migration upgrades documents to
+    version: i32,
+    /// A short summary of what the migration changes
+    description: &'static str,
+    apply: fn(&mut Document),
+}
+
+/// Every migration, oldest first
+const MIGRATIONS: &[Migration] = &[Migration {
+    version: 1,
+    description: "Nest the common and swiss pairing settings into their settings trees",
+    apply: nest_pairing_settings,
+}];
+
+/// Returns the schema version of a persisted tournament, which is kept in its `schema` field.
+/// Documents that were persisted before schema versions were tracked are version 0.
+pub fn schema_version(doc: &Document) -> i32 {
+    doc.get_i32("schema").unwrap_or(0)
+}
+
+/// Upgrades a persisted tournament to the current schema version. Returns if the document was
+/// changed. Documents that were written by a newer server are left alone.
+pub fn migrate(doc: &mut Document) -> bool {
+    let from = schema_version(doc);
+    if from >= SCHEMA_VERSION {
+        return false;
+    }
+    for migration in MIGRATIONS.iter().filter(|m| m.version > from) {
+        tracing::debug!(
+            "Applying tournament migration {}: {}",
+            migration.version,
+            migration.description
+        );
+        (migration.apply)(doc);
+    }
+    let _ = doc.insert("schema", SCHEMA_VERSION);
+    true
+}
+
+/// Calls the function with the current tournament and, if the log has been compacted, the
+/// tournament in the log's snapshot
+fn for_each_tourn(doc: &mut Document, f: fn(&mut Document)) {
+    if let Ok(tourn) = doc.get_document_mut("tourn") {
+        f(tourn);
+    }
+    if let Ok(tourn) = doc
+        .get_document_mut("log")
+        .and_then(|log| log.get_document_mut("snapshot"))
+        .and_then(|snap| snap.get_document_mut("tourn"))
+    {
+        f(tourn);
+    }
+}
+
+/// Before the settings refactor, the common pairing settings sat directly on the pairing system
+/// and the swiss settings sat directly on the swiss pairings. Those documents still deserialize,
+/// but the old settings are silently replaced by the defaults.
+fn nest_pairing_settings(doc: &mut Document) {
+    for_each_tourn(doc, |tourn| {
+        let Ok(sys) = tourn.get_document_mut("pairing_sys") else {
+            return;
+        };
+        let mut common = match sys.remove("common") {
+            Some(Bson::Document(common)) => common,
+            _ => Document::new(),
+        };
+        for key in ["match_size", "repair_tolerance", "algorithm"] {
+            if let Some(value) = sys.remove(key) {
+                let _ = common.insert(key, value);
+            }
+        }
+        if !common.is_empty() {
+            let _ = sys.insert("common", common);
+        }
+        let Ok(swiss) = sys
+            .get_document_mut("style")
+            .and_then(|style| style.get_document_mut("Swiss"))
+        else {
+            return;
+        };
+        if let Some(do_checkins) = swiss.remove("do_checkins") {
+            let mut settings = match swiss.remove("settings") {
+                Some(Bson::Document(settings)) => settings,
+                _ => Document::new(),
+            };
+            let _ = settings.insert("do_checkins", do_checkins);
+            let _ = swiss.insert("settings", settings);
+        }
+    });
+}
@@ -2,31 +2,59 @@ use std::{borrow::Cow, ops::Range, sync::Arc};
 
 use async_trait::async_trait;
 use axum::extract::ws::WebSocket;
+use chrono::{DateTime, Utc};
 use mongodb::{options::ClientOptions, Client as DbClient, Database};
 use squire_sdk::{
     actor::{ActorBuilder, ActorClient},
     api::*,
     model::{
         accounts::SquireAccount,
-        identifiers::{SquireAccountId, TournamentId},
+        error::TournamentError,
+        identifiers::{SeriesId, SquireAccountId, TournamentId},
+        operations::TournOp,
+        series::Series,
+        settings::TournamentTemplate,
     },
     server::{
-        gathering::{GatheringHall, GatheringHallMessage},
+        gathering::{GatheringHall, GatheringHallMessage, HallLimits, HallMetrics},
+        invite::{is_revoked, redeem_invite, verify_invite},
+        keys::{KeyPurpose, SigningKeys},
         session::{AnyUser, SessionWatcher, SquireSession},
-        state::ServerState,
+        state::{ApplyOpError, GatheringNode, ServerState},
     },
-    sync::TournamentManager,
+    sync::{Compression, TournamentManager},
 };
+use tracing_subscriber::EnvFilter;
 
 mod accounts;
 mod boilerplate;
+mod events;
+mod history;
+mod leases;
+mod migrations;
+mod notifications;
+mod oauth;
+mod series;
 mod session;
+mod stats;
+mod templates;
 mod tournaments;
 mod user_profile;
+mod webhooks;
 
 pub use accounts::*;
+pub use events::*;
+pub use history::*;
+pub use leases::*;
+pub use migrations::*;
+pub use notifications::*;
+pub use oauth::*;
+pub use series::*;
 pub use session::*;
+pub use stats::*;
+pub use templates::*;
 pub use tournaments::*;
+pub use webhooks::*;
 // pub use user_profile::*;
 
 pub type Uri = Cow<'static, str>;
@@ -38,6 +66,8 @@ pub struct AppStateBuilder<T, N> {
     db_conn: T,
     db_name: N,
     tourn_coll: Option<String>,
+    hall_limits: HallLimits,
+    log_filter: Option<String>,
 }
 
 impl AppStateBuilder<(), ()> {
@@ -47,6 +77,8 @@ impl AppStateBuilder<(), ()> {
             db_conn: Cow::Borrowed("mongodb://localhost:27017"),
             db_name: None,
             tourn_coll: None,
+            hall_limits: HallLimits::default(),
+            log_filter: None,
         }
     }
 }
@@ -60,6 +92,8 @@ impl AppStateBuilder<Uri, DbName> {
             db_conn: Cow::Owned(uri.to_string()),
             db_name: None,
             tourn_coll: None,
+            hall_limits: HallLimits::default(),
+            log_filter: None,
         }
     }
 
@@ -94,17 +128,45 @@ impl AppStateBuilder<Uri, DbName> {
     /// # Panics
     /// Panics if a connection can not be established
     pub async fn build(self) -> AppState {
+        init_tracing(self.log_filter.as_deref());
         let client_options = ClientOptions::parse(&self.db_conn).await.unwrap();
         let db_conn = DbClient::with_options(client_options)
             .unwrap()
             .database(self.get_db_name());
         let tourn_coll = Arc::from(self.get_tournament_collection_name());
         let tourn_db = TournDb::new(db_conn.clone(), tourn_coll);
-        let tournaments = ActorClient::builder(TournPersister::new(tourn_db.clone())).launch();
-        let gatherings = ActorBuilder::new(GatheringHall::new(tournaments.clone())).launch();
+        tourn_db.start_migrations();
+        let events = EventDb::new(db_conn.clone());
+        let webhooks = WebhookDb::new(db_conn.clone());
+        let history = HistoryDb::new(db_conn.clone());
+        let accounts = AccountStoreHandle::new(db_conn.clone());
+        let notifier = Notifier::from_env(accounts.clone());
+        let persister = TournPersister::new(
+            tourn_db.clone(),
+            events.clone(),
+            history.clone(),
+            webhooks.clone(),
+            notifier.clone(),
+        );
+        let tournaments = ActorClient::builder(persister).launch();
+        let gatherings = ActorBuilder::new(GatheringHall::with_limits(
+            tournaments.clone(),
+            self.hall_limits,
+        ))
+        .launch();
+        let signing_keys = load_signing_keys();
         AppState {
             sessions: SessionStoreHandle::new(db_conn.clone()),
-            accounts: AccountStoreHandle::new(db_conn),
+            templates: TemplateDb::new(db_conn.clone()),
+            series: SeriesDb::new(db_conn.clone()),
+            events,
+            history,
+            webhooks,
+            notifier,
+            oauth: OAuthClient::from_env(signing_keys.get(KeyPurpose::OAuthState)),
+            accounts,
+            signing_keys,
+            leases: LeaseDb::from_env(db_conn, gatherings.clone()),
             gatherings,
             tourn_db,
         }
@@ -118,18 +180,46 @@ impl AppStateBuilder<Database, ()> {
             db_conn: db,
             db_name: (),
             tourn_coll: None,
+            hall_limits: HallLimits::default(),
+            log_filter: None,
         }
     }
 
     /// Constructs an `AppState` using the held DB client.
     pub fn build(self) -> AppState {
+        init_tracing(self.log_filter.as_deref());
         let tourn_coll: Arc<str> = Arc::from(self.get_tournament_collection_name());
         let tourn_db = TournDb::new(self.db_conn.clone(), tourn_coll);
-        let tourns = ActorClient::builder(TournPersister::new(tourn_db.clone())).launch();
-        let gatherings = ActorBuilder::new(GatheringHall::new(tourns.clone())).launch();
+        tourn_db.start_migrations();
+        let events = EventDb::new(self.db_conn.clone());
+        let webhooks = WebhookDb::new(self.db_conn.clone());
+        let history = HistoryDb::new(self.db_conn.clone());
+        let accounts = AccountStoreHandle::new(self.db_conn.clone());
+        let notifier = Notifier::from_env(accounts.clone());
+        let persister = TournPersister::new(
+            tourn_db.clone(),
+            events.clone(),
+            history.clone(),
+            webhooks.clone(),
+            notifier.clone(),
+        );
+        let tourns = ActorClient::builder(persister).launch();
+        let gatherings =
+            ActorBuilder::new(GatheringHall::with_limits(tourns.clone(), self.hall_limits))
+                .launch();
+        let signing_keys = load_signing_keys();
         AppState {
             sessions: SessionStoreHandle::new(self.db_conn.clone()),
-            accounts: AccountStoreHandle::new(self.db_conn),
+            templates: TemplateDb::new(self.db_conn.clone()),
+            series: SeriesDb::new(self.db_conn.clone()),
+            events,
+            history,
+            webhooks,
+            notifier,
+            oauth: OAuthClient::from_env(signing_keys.get(KeyPurpose::OAuthState)),
+            accounts,
+            signing_keys,
+            leases: LeaseDb::from_env(self.db_conn, gatherings.clone()),
             gatherings,
             tourn_db,
         }
@@ -144,17 +234,83 @@ impl<T, S> AppStateBuilder<T, S> {
         self
     }
 
+    /// Sets the limits on how many tournaments and connections the server will take before it
+    /// starts turning away new connections.
+    #[allow(dead_code)]
+    pub fn hall_limits(mut self, limits: HallLimits) -> Self {
+        self.hall_limits = limits;
+        self
+    }
+
+    /// Sets which logs are recorded, using the `RUST_LOG` syntax (e.g. `squire_sdk=debug,info`).
+    /// By default, the `RUST_LOG` environment variable is used, and `info` is used if that is not
+    /// set.
+    #[allow(dead_code)]
+    pub fn log_filter(mut self, filter: impl Into<String>) -> Self {
+        self.log_filter = Some(filter.into());
+        self
+    }
+
     fn get_tournament_collection_name(&self) -> &str {
         self.tourn_coll.as_deref().unwrap_or("Tournaments")
     }
 }
 
+/// Installs a subscriber that writes the server's logs to stdout. If a subscriber is already
+/// installed (e.g. by the runtime or by an earlier `AppState`), that subscriber is kept.
+fn init_tracing(filter: Option<&str>) {
+    _ = tracing_subscriber::fmt()
+        .with_env_filter(tracing_filter(filter))
+        .try_init();
+}
+
+/// Picks the filter for the server's logs. The given filter takes priority over `RUST_LOG`.
+fn tracing_filter(filter: Option<&str>) -> EnvFilter {
+    match filter {
+        Some(filter) => EnvFilter::new(filter),
+        None => EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info")),
+    }
+}
+
+/// The name of the environment variable that holds the master key, from which the keys used to
+/// sign invites, report tokens, tournament exports, and OAuth states are derived
+const SIGNING_KEY_VAR: &str = "SQUIRE_SIGNING_KEY";
+
+/// Loads the master key and derives the signing keys from it. If no key is configured, a random
+/// key is generated, but nothing signed with it can be verified once the server restarts.
+fn load_signing_keys() -> Arc<SigningKeys> {
+    let master = std::env::var(SIGNING_KEY_VAR)
+        .ok()
+        .filter(|key| !key.is_empty());
+    let keys = match master {
+        Some(key) => SigningKeys::derive(key.as_bytes()),
+        None => {
+            tracing::warn!(
+                "{SIGNING_KEY_VAR} is not set. Using an ephemeral key, so invites, report tokens, \
+                 and exports will stop verifying when the server restarts."
+            );
+            SigningKeys::derive(&rand::random::<[u8; 32]>())
+        }
+    };
+    Arc::new(keys)
+}
+
 #[derive(Debug, Clone)]
 pub struct AppState {
     tourn_db: TournDb,
     sessions: SessionStoreHandle,
     accounts: AccountStoreHandle,
+    templates: TemplateDb,
+    series: SeriesDb,
+    events: EventDb,
+    history: HistoryDb,
+    webhooks: WebhookDb,
+    notifier: Notifier,
+    oauth: OAuthClient,
     gatherings: ActorClient<GatheringHall<TournPersister>>,
+    /// The leases on gatherings, which are only used when several nodes share the database
+    leases: Option<LeaseDb>,
+    signing_keys: Arc<SigningKeys>,
 }
 
 impl AppState {
@@ -166,17 +322,219 @@ impl AppState {
         self.tourn_db.get_db()
     }
 
-    pub async fn login(&self, cred: Credentials) -> Result<SessionToken, LoginError> {
-        match self.accounts.authenticate(cred).await {
-            Some(id) => Ok(self.sessions.create(id).await),
+    /// Returns the current load on the gathering hall and how many connections it has rejected
+    #[allow(dead_code)]
+    pub async fn hall_metrics(&self) -> HallMetrics {
+        self.gatherings.track(()).await
+    }
+
+    /// Closes every gathering and waits for their tournaments to be persisted. The server should
+    /// not take any more requests after this is called.
+    pub async fn shutdown(&self) {
+        self.gatherings.track(()).await;
+        if let Some(leases) = &self.leases {
+            leases.release_all().await;
+        }
+    }
+
+    /// Logs a user in and creates a new session for them. The IP address that they logged in from
+    /// is kept with the session.
+    pub async fn login(
+        &self,
+        cred: Credentials,
+        ip: Option<String>,
+    ) -> Result<SessionToken, LoginError> {
+        let id = match cred {
+            Credentials::OAuth {
+                provider,
+                code,
+                state,
+            } => match self.oauth.identify(provider, code, &state).await {
+                Some(login) => self.accounts.login_external(login).await,
+                None => None,
+            },
+            cred => self.accounts.authenticate(cred).await,
+        };
+        match id {
+            Some(id) => Ok(self.sessions.create(id, ip).await),
             None => Err(LoginError),
         }
     }
 
-    pub async fn create_account(&self, form: RegForm) -> SquireAccountId {
+    pub async fn create_api_token(&self, id: SquireAccountId, name: String) -> NewApiToken {
+        self.sessions.create_api_token(id, name).await
+    }
+
+    pub async fn list_api_tokens(&self, id: SquireAccountId) -> Vec<ApiToken> {
+        self.sessions.list_api_tokens(id).await
+    }
+
+    /// Revokes one of the user's API tokens. Returns `false` if the user has no token with that id.
+    pub async fn revoke_api_token(&self, id: SquireAccountId, tok_id: ApiTokenId) -> bool {
+        self.sessions.revoke_api_token(id, tok_id).await
+    }
+
+    /// Lists the sessions of the user that the given session belongs to
+    pub async fn list_sessions(&self, token: SessionToken) -> Vec<SessionInfo> {
+        self.sessions.list_sessions(token).await
+    }
+
+    /// Revokes one of the sessions of the user that the given session belongs to. Returns `false`
+    /// if the user has no session with that id.
+    pub async fn revoke_session(&self, token: SessionToken, id: SessionId) -> bool {
+        self.sessions.revoke_session(token, id).await
+    }
+
+    pub async fn name_session(&self, token: SessionToken, device: String) -> bool {
+        self.sessions.name_session(token, device).await
+    }
+
+    /// Has a guest's session hold on to an invite until they register an account
+    pub async fn accept_invite(
+        &self,
+        token: SessionToken,
+        invite: String,
+    ) -> Result<TournamentInvite, InviteError> {
+        let invite = verify_invite(self.signing_key(KeyPurpose::Invite), &invite, Utc::now())?;
+        match self.get_tourn(invite.tourn).await {
+            None => return Err(InviteError::TournamentNotFound),
+            Some(tourn) if is_revoked(&tourn, &invite) => return Err(InviteError::Revoked),
+            Some(_) => {}
+        }
+        if !self.sessions.hold_invite(token, invite.clone()).await {
+            return Err(InviteError::Unauthorized);
+        }
+        Ok(invite)
+    }
+
+    /// Gives an account the role that an invite grants
+    pub async fn redeem_invite(
+        &self,
+        id: SquireAccountId,
+        invite: String,
+    ) -> Result<TournamentInvite, InviteError> {
+        let invite = verify_invite(self.signing_key(KeyPurpose::Invite), &invite, Utc::now())?;
+        let Some(account) = self.get_account(id).await else {
+            return Err(InviteError::Unauthorized);
+        };
+        self.grant_invite(&invite, account).await?;
+        Ok(invite)
+    }
+
+    /// Applies the operations that give the account the role granted by the invite, stopping at
+    /// the first one that fails
+    async fn grant_invite(
+        &self,
+        invite: &TournamentInvite,
+        account: SquireAccount,
+    ) -> Result<(), InviteError> {
+        let Some(tourn) = self.get_tourn(invite.tourn).await else {
+            return Err(InviteError::TournamentNotFound);
+        };
+        for op in redeem_invite(&tourn, invite, account)? {
+            match self.apply_op(invite.tourn, op).await {
+                Ok(()) => {}
+                Err(ApplyOpError::Rejected(TournamentError::InviteRevoked)) => {
+                    return Err(InviteError::Revoked)
+                }
+                Err(ApplyOpError::Rejected(err)) => return Err(InviteError::NotRedeemed(err)),
+                Err(ApplyOpError::NotOwner(_)) => return Err(InviteError::NotOwner),
+                Err(ApplyOpError::NotFound) => return Err(InviteError::TournamentNotFound),
+            }
+        }
+        Ok(())
+    }
+
+    /// Registers an account for a guest and turns their session into a session for the account.
+    /// The account is then given the roles of the invites that the guest accepted.
+    pub async fn upgrade_guest(
+        &self,
+        token: SessionToken,
+        form: RegForm,
+    ) -> Result<GuestUpgrade, UpgradeError> {
+        let id = self.create_account(form).await.map_err(|err| match err {
+            RegError::UsernameTaken => UpgradeError::UsernameTaken,
+            RegError::PasswordTooShort => UpgradeError::PasswordTooShort,
+            RegError::NotHashed => UpgradeError::NotCreated,
+        })?;
+        let Some(account) = self.get_account(id).await else {
+            return Err(UpgradeError::NotCreated);
+        };
+        // The session could have expired while the account was being created
+        let Some(invites) = self.sessions.upgrade(token, id).await else {
+            _ = self.delete_account(id).await;
+            return Err(UpgradeError::NotGuest);
+        };
+        let mut granted = Vec::with_capacity(invites.len());
+        let mut not_granted = Vec::new();
+        for invite in invites {
+            match self.grant_invite(&invite, account.clone()).await {
+                Ok(()) => granted.push(invite),
+                Err(err) => not_granted.push((invite, err)),
+            }
+        }
+        Ok(GuestUpgrade {
+            account,
+            granted,
+            not_granted,
+        })
+    }
+
+    /// Returns where a user should be sent to log in with an OAuth provider, or `None` if the
+    /// provider is not configured
+    pub fn oauth_redirect(&self, provider: OAuthProvider) -> Option<OAuthRedirect> {
+        self.oauth.redirect(provider)
+    }
+
+    /// Links the identity that the user logged in to an OAuth provider with to their account
+    pub async fn link_identity(
+        &self,
+        id: SquireAccountId,
+        provider: OAuthProvider,
+        code: String,
+        state: String,
+    ) -> Result<(), LinkError> {
+        let login = self
+            .oauth
+            .identify(provider, code, &state)
+            .await
+            .ok_or(LinkError::NotVerified)?;
+        self.accounts.link_external(id, login.identity).await
+    }
+
+    pub async fn create_account(&self, form: RegForm) -> Result<SquireAccountId, RegError> {
         self.accounts.create(form).await
     }
 
+    /// Changes the user's password. Every other session and API token of theirs is revoked, so
+    /// only the session that made the change stays logged in.
+    pub async fn change_password(
+        &self,
+        id: SquireAccountId,
+        token: SessionToken,
+        req: ChangePassword,
+    ) -> Result<(), PasswordError> {
+        self.accounts.change_password(id, req).await?;
+        self.sessions.revoke_account(id, Some(token)).await;
+        Ok(())
+    }
+
+    /// Emails a password reset token to the account, if it exists and has an email address
+    pub async fn request_password_reset(&self, req: RequestPasswordReset) {
+        if let Some((token, channel)) = self.accounts.request_reset(req).await {
+            self.notifier
+                .send_to(channel, password_reset_notification(&token))
+        }
+    }
+
+    /// Resets a password using a reset token. Every session and API token of the account is
+    /// revoked, since a reset is made without being logged in.
+    pub async fn reset_password(&self, req: ResetPassword) -> Result<(), PasswordError> {
+        let id = self.accounts.reset_password(req).await?;
+        self.sessions.revoke_account(id, None).await;
+        Ok(())
+    }
+
     pub async fn get_account(&self, id: SquireAccountId) -> Option<SquireAccount> {
         self.accounts.get(id).await
     }
@@ -189,8 +547,90 @@ impl AppState {
         }
     }
 
+    /// Deletes the account and revokes all of its sessions and API tokens
     pub async fn delete_account(&self, id: SquireAccountId) -> bool {
-        self.accounts.delete(id).await
+        let digest = self.accounts.delete(id).await;
+        if digest {
+            self.sessions.revoke_account(id, None).await;
+        }
+        digest
+    }
+
+    /// Saves a template to an account. The account becomes the owner of the template.
+    pub async fn save_template(
+        &self,
+        owner: SquireAccountId,
+        mut template: TournamentTemplate,
+    ) -> bool {
+        template.owner = owner;
+        self.templates.save_template(&template).await
+    }
+
+    pub async fn list_templates(&self, owner: SquireAccountId) -> Vec<TournamentTemplate> {
+        self.templates.list_templates(owner).await
+    }
+
+    pub async fn get_notifications(&self, id: SquireAccountId) -> NotificationPrefs {
+        self.accounts
+            .get_notifications(id)
+            .await
+            .unwrap_or_default()
+    }
+
+    pub async fn set_notifications(&self, id: SquireAccountId, prefs: NotificationPrefs) -> bool {
+        self.accounts.set_notifications(id, prefs).await
+    }
+
+    /// Returns an account's profile as it is shown to the viewer
+    pub async fn get_profile(
+        &self,
+        id: SquireAccountId,
+        viewer: Option<SquireAccountId>,
+    ) -> Option<PublicProfile> {
+        let (account, profile) = self.accounts.get_profile(id).await?;
+        Some(PublicProfile::new(&account, profile, viewer))
+    }
+
+    /// Returns the tournaments that an account played in. Other users can only see the history of
+    /// accounts that share their names, and only the public tournaments in it.
+    pub async fn get_history(
+        &self,
+        id: SquireAccountId,
+        viewer: Option<SquireAccountId>,
+    ) -> Option<Vec<HistoryEntry>> {
+        let account = self.accounts.get(id).await?;
+        let owner = viewer == Some(id);
+        if !owner && !account.permissions.shares_name() {
+            return None;
+        }
+        Some(self.history.get_history(id, owner).await)
+    }
+
+    /// Returns the stats of an account, which follow the same visibility rules as its history
+    pub async fn get_stats(
+        &self,
+        id: SquireAccountId,
+        viewer: Option<SquireAccountId>,
+    ) -> Option<PlayerStats> {
+        let account = self.accounts.get(id).await?;
+        let owner = viewer == Some(id);
+        if !owner && !account.permissions.shares_name() {
+            return None;
+        }
+        Some(self.history.get_stats(id, owner).await)
+    }
+
+    pub async fn set_profile(
+        &self,
+        id: SquireAccountId,
+        profile: AccountProfile,
+    ) -> Result<(), ProfileError> {
+        self.accounts.set_profile(id, profile).await
+    }
+
+    /// Sends each notification to its user, if they opted in to its kind
+    pub fn notify(&self, notes: Vec<(SquireAccountId, Notification)>) {
+        self.notifier.notify(notes)
     }
 }
 
@@ -203,8 +643,16 @@ impl ServerState for AppState {
         }
     }
 
-    async fn get_tourn_summaries(&self, including: Range<usize>) -> Vec<TournamentSummary> {
-        self.tourn_db.get_tourn_summaries(including).await
+    fn signing_key(&self, purpose: KeyPurpose) -> &[u8] {
+        self.signing_keys.get(purpose)
+    }
+
+    async fn get_tourn_summaries(
+        &self,
+        filter: &TournamentFilter,
+        including: Range<usize>,
+    ) -> (Vec<TournamentSummary>, usize) {
+        self.tourn_db.get_tourn_summaries(filter, including).await
     }
 
     async fn get_tourn(&self, id: TournamentId) -> Option<TournamentManager> {
@@ -215,10 +663,72 @@ impl ServerState for AppState {
         self.tourn_db.persist_tourn(tourn).await
     }
 
-    async fn handle_new_onlooker(&self, id: TournamentId, user: SessionWatcher, ws: WebSocket) {
-        println!("Passing connection request off to gathering hall...");
-        self.gatherings
-            .send(GatheringHallMessage::NewConnection(id, user, ws))
+    async fn gathering_node(&self, id: TournamentId) -> GatheringNode {
+        match &self.leases {
+            Some(leases) => leases.acquire(id).await,
+            None => GatheringNode::Local,
+        }
+    }
+
+    async fn handle_new_onlooker(
+        &self,
+        id: TournamentId,
+        user: SessionWatcher,
+        compression: Compression,
+        ws: WebSocket,
+    ) {
+        tracing::debug!(tourn = %id, "Passing connection request off to gathering hall");
+        self.gatherings.send(GatheringHallMessage::NewConnection(
+            id,
+            user,
+            compression,
+            ws,
+        ))
+    }
+
+    async fn apply_op(&self, id: TournamentId, op: TournOp) -> Result<(), ApplyOpError> {
+        // Only the node that hosts the gathering can change the tournament
+        match self.gathering_node(id).await {
+            GatheringNode::Local => {}
+            GatheringNode::Remote(node) => return Err(ApplyOpError::NotOwner(Some(node))),
+            GatheringNode::Unknown => return Err(ApplyOpError::NotOwner(None)),
+        }
+        match self.gatherings.track((id, op)).await {
+            Some(digest) => digest.map_err(ApplyOpError::Rejected),
+            None => Err(ApplyOpError::NotFound),
+        }
+    }
+
+    async fn record_events(&self, events: Vec<LifecycleEvent>) {
+        self.events.record_events(events).await
+    }
+
+    async fn get_org_events(&self, org: SquireAccountId) -> Vec<LifecycleEvent> {
+        self.events.get_org_events(org).await
+    }
+
+    async fn list_series(&self) -> Vec<Series> {
+        self.series.list_series().await
+    }
+
+    async fn get_series(&self, id: SeriesId) -> Option<Series> {
+        self.series.get_series(id).await
+    }
+
+    async fn persist_series(&self, series: &Series) -> bool {
+        self.series.persist_series(series).await
+    }
+
+    async fn list_webhooks(&self, tourn: TournamentId) -> Vec<Webhook> {
+        self.webhooks.list_webhooks(tourn).await
+    }
+
+    async fn persist_webhook(&self, hook: &Webhook) -> bool {
+        self.webhooks.persist_webhook(hook).await
+    }
+
+    async fn delete_webhook(&self, tourn: TournamentId, id: WebhookId) -> bool {
+        self.webhooks.delete_webhook(tourn, id).await
     }
 
     async fn get_session(&self, token: SessionToken) -> SquireSession {
@@ -226,14 +736,18 @@ impl ServerState for AppState {
     }
 
     async fn create_session(&self, id: SquireAccountId) -> SessionToken {
-        self.sessions.create(id).await
+        self.sessions.create(id, None).await
     }
 
     async fn guest_session(&self) -> SessionToken {
         self.sessions.guest().await
     }
 
-    async fn reauth_session(&self, user: AnyUser) -> SessionToken {
+    async fn handoff_session(&self) -> (SessionToken, DateTime<Utc>) {
+        self.sessions.handoff(HANDOFF_LIFETIME).await
+    }
+
+    async fn reauth_session(&self, user: AnyUser) -> Option<SessionToken> {
         self.sessions.reauth(user).await
     }
 
@@ -248,3 +762,24 @@ impl ServerState for AppState {
             .map(SessionWatcher::new)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use tracing_subscriber::filter::LevelFilter;
+
+    use super::tracing_filter;
+
+    #[test]
+    fn log_filters_override_the_environment() {
+        let filter = tracing_filter(Some("squire_sdk=debug,warn"));
+        assert_eq!(filter.max_level_hint(), Some(LevelFilter::DEBUG));
+        let filter = tracing_filter(Some("error"));
+        assert_eq!(filter.max_level_hint(), Some(LevelFilter::ERROR));
+        if std::env::var("RUST_LOG").is_err() {
+            assert_eq!(
+                tracing_filter(None).max_level_hint(),
+                Some(LevelFilter::INFO)
+            );
+        }
+    }
+}
@@ -0,0 +1,326 @@
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use squire_sdk::api::{OAuthProvider, OAuthRedirect};
+use tracing::Level;
+
+/// A user's identity with an OAuth provider, which is linked to exactly one account
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ExternalIdentity {
+    /// The provider that vouches for the user
+    pub provider: OAuthProvider,
+    /// The provider's id for the user, which never changes
+    pub subject: String,
+}
+
+/// An identity that the provider has vouched for, along with the profile information that is used
+/// when creating an account for it
+#[derive(Debug, Clone)]
+pub struct ExternalLogin {
+    pub identity: ExternalIdentity,
+    pub name: String,
+    pub email: Option<String>,
+}
+
+/// How the server is registered with a provider
+#[derive(Debug, Clone)]
+struct ProviderConfig {
+    client_id: String,
+    client_secret: String,
+    redirect_uri: String,
+}
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// How long a user has to log in with a provider after they are sent to it
+const STATE_LIFETIME: Duration = Duration::from_secs(600);
+
+/// The response from a provider's token endpoint
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    access_token: String,
+}
+
+/// The parts of a provider's user info that are used. Google calls the user's id `sub` and Discord
+/// calls it `id`.
+#[derive(Debug, Deserialize)]
+struct UserInfo {
+    #[serde(alias = "sub")]
+    id: String,
+    #[serde(default, alias = "username")]
+    name: Option<String>,
+    #[serde(default)]
+    email: Option<String>,
+}
+
+/// Logs users in through OAuth providers using the authorization code flow. A provider is enabled
+/// by setting the `SQUIRE_<PROVIDER>_CLIENT_ID`, `SQUIRE_<PROVIDER>_CLIENT_SECRET`, and
+/// `SQUIRE_<PROVIDER>_REDIRECT_URI` environment variables, e.g. `SQUIRE_GOOGLE_CLIENT_ID`.
+///
+/// The `state` of each redirect is signed, so that the code of a login that no server started (i.e.
+/// a forged redirect) is never exchanged. Since nothing is stored, the user can come back to any
+/// server that shares the signing key. The state names the provider and when it expires, so it
+/// can not be used for another provider or after the login has timed out. Replaying a state does
+/// not help an attacker since the provider only lets its code be exchanged once.
+#[derive(Debug, Clone)]
+pub struct OAuthClient {
+    client: reqwest::Client,
+    providers: HashMap<OAuthProvider, ProviderConfig>,
+    /// The key that the states of redirects are signed with
+    state_key: Arc<[u8]>,
+}
+
+impl OAuthClient {
+    pub fn from_env(state_key: &[u8]) -> Self {
+        let providers = [OAuthProvider::Google, OAuthProvider::Discord]
+            .into_iter()
+            .filter_map(|provider| {
+                let prefix = format!("SQUIRE_{}", provider.as_str().to_uppercase());
+                let var = |name: &str| std::env::var(format!("{prefix}_{name}")).ok();
+                let (Some(client_id), Some(client_secret), Some(redirect_uri)) =
+                    (var("CLIENT_ID"), var("CLIENT_SECRET"), var("REDIRECT_URI"))
+                else {
+                    tracing::info!(
+                        "{prefix}_CLIENT_ID is not set. {provider:?} login is disabled."
+                    );
+                    return None;
+                };
+                let config = ProviderConfig {
+                    client_id,
+                    client_secret,
+                    redirect_uri,
+                };
+                Some((provider, config))
+            })
+            .collect();
+        Self {
+            client: reqwest::Client::new(),
+            providers,
+            state_key: Arc::from(state_key),
+        }
+    }
+
+    /// Returns where a user should be sent to log in with the provider, or `None` if the provider
+    /// is not configured
+    pub fn redirect(&self, provider: OAuthProvider) -> Option<OAuthRedirect> {
+        let config = self.providers.get(&provider)?;
+        let state = self.sign_state(
+            provider,
+            Utc::now().timestamp() + STATE_LIFETIME.as_secs() as i64,
+        );
+        let url = reqwest::Url::parse_with_params(
+            authorize_url(provider),
+            [
+                ("client_id", config.client_id.as_str()),
+                ("redirect_uri", config.redirect_uri.as_str()),
+                ("response_type", "code"),
+                ("scope", scopes(provider)),
+                ("state", state.as_str()),
+            ],
+        )
+        .ok()?;
+        Some(OAuthRedirect {
+            url: url.into(),
+            state,
+        })
+    }
+
+    /// Creates the state of a redirect to the provider. The state is of the form
+    /// `provider.expires.nonce.signature`, where `expires` is a Unix timestamp.
+    fn sign_state(&self, provider: OAuthProvider, expires: i64) -> String {
+        let nonce = hex::encode(rand::random::<[u8; 16]>());
+        let payload = format!("{}.{expires}.{nonce}", provider.as_str());
+        let signature = hex::encode(self.state_signer(&payload).finalize().into_bytes());
+        format!("{payload}.{signature}")
+    }
+
+    /// Checks the state of a redirect to the provider. Returns `false` if the state was not signed
+    /// with this client's key, was issued for another provider, or has expired.
+    fn verify_state(&self, provider: OAuthProvider, state: &str) -> bool {
+        let Some((payload, signature)) = state.rsplit_once('.') else {
+            return false;
+        };
+        let Ok(signature) = hex::decode(signature) else {
+            return false;
+        };
+        if self.state_signer(payload).verify_slice(&signature).is_err() {
+            return false;
+        }
+        let mut parts = payload.split('.');
+        let issued_for = parts.next();
+        let expires = parts.next().and_then(|exp| exp.parse::<i64>().ok());
+        issued_for == Some(provider.as_str())
+            && expires.is_some_and(|exp| exp > Utc::now().timestamp())
+    }
+
+    fn state_signer(&self, payload: &str) -> HmacSha256 {
+        let mut mac =
+            HmacSha256::new_from_slice(&self.state_key).expect("HMAC can take a key of any size");
+        mac.update(payload.as_bytes());
+        mac
+    }
+
+    /// Exchanges an authorization code for the identity of the user that it was issued to. The
+    /// state that the provider sent back with the code must be one that was signed with this
+    /// client's key.
+    pub async fn identify(
+        &self,
+        provider: OAuthProvider,
+        code: String,
+        state: &str,
+    ) -> Option<ExternalLogin> {
+        let config = self.providers.get(&provider)?;
+        if !self.verify_state(provider, state) {
+            tracing::event!(
+                Level::WARN,
+                "Rejected a {provider:?} login with a forged or expired state",
+            );
+            return None;
+        }
+        let params = [
+            ("grant_type", "authorization_code"),
+            ("code", code.as_str()),
+            ("redirect_uri", config.redirect_uri.as_str()),
+            ("client_id", config.client_id.as_str()),
+            ("client_secret", config.client_secret.as_str()),
+        ];
+        let token: TokenResponse = match self
+            .client
+            .post(token_url(provider))
+            .form(&params)
+            .send()
+            .await
+            .and_then(|resp| resp.error_for_status())
+        {
+            Ok(resp) => resp.json().await.ok()?,
+            Err(err) => {
+                tracing::event!(
+                    Level::WARN,
+                    "Could not exchange {provider:?} authorization code due to error: {err}",
+                );
+                return None;
+            }
+        };
+        let info: UserInfo = self
+            .client
+            .get(user_info_url(provider))
+            .bearer_auth(token.access_token)
+            .send()
+            .await
+            .and_then(|resp| resp.error_for_status())
+            .ok()?
+            .json()
+            .await
+            .ok()?;
+        let name = info.name.unwrap_or_else(|| format!("{provider:?} user"));
+        Some(ExternalLogin {
+            identity: ExternalIdentity {
+                provider,
+                subject: info.id,
+            },
+            name,
+            email: info.email,
+        })
+    }
+}
+
+fn authorize_url(provider: OAuthProvider) -> &'static str {
+    match provider {
+        OAuthProvider::Google => "https://accounts.google.com/o/oauth2/v2/auth",
+        OAuthProvider::Discord => "https://discord.com/oauth2/authorize",
+    }
+}
+
+fn token_url(provider: OAuthProvider) -> &'static str {
+    match provider {
+        OAuthProvider::Google => "https://oauth2.googleapis.com/token",
+        OAuthProvider::Discord => "https://discord.com/api/oauth2/token",
+    }
+}
+
+fn user_info_url(provider: OAuthProvider) -> &'static str {
+    match provider {
+        OAuthProvider::Google => "https://openidconnect.googleapis.com/v1/userinfo",
+        OAuthProvider::Discord => "https://discord.com/api/users/@me",
+    }
+}
+
+fn scopes(provider: OAuthProvider) -> &'static str {
+    match provider {
+        OAuthProvider::Google => "openid profile email",
+        OAuthProvider::Discord => "identify email",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+    use squire_sdk::api::{Credentials, OAuthProvider};
+
+    use super::{OAuthClient, ProviderConfig};
+
+    fn client() -> OAuthClient {
+        client_with_key(b"key")
+    }
+
+    fn client_with_key(key: &[u8]) -> OAuthClient {
+        let mut client = OAuthClient::from_env(key);
+        let config = ProviderConfig {
+            client_id: "client".to_owned(),
+            client_secret: "secret".to_owned(),
+            redirect_uri: "https://squire.example/oauth".to_owned(),
+        };
+        _ = client
+            .providers
+            .insert(OAuthProvider::Google, config.clone());
+        _ = client.providers.insert(OAuthProvider::Discord, config);
+        client
+    }
+
+    #[test]
+    fn states_are_checked_against_provider_expiry_and_key() {
+        let client = client();
+        let state = client.redirect(OAuthProvider::Google).unwrap().state;
+        assert!(client.verify_state(OAuthProvider::Google, &state));
+        assert!(!client.verify_state(OAuthProvider::Google, "forged"));
+        assert!(!client.verify_state(OAuthProvider::Discord, &state));
+
+        // Another server that shares the key accepts the state, but one with a different key does not
+        assert!(client_with_key(b"key").verify_state(OAuthProvider::Google, &state));
+        assert!(!client_with_key(b"other").verify_state(OAuthProvider::Google, &state));
+
+        // Changing any part of the state breaks its signature
+        let forged = state.replacen("google", "discord", 1);
+        assert!(!client.verify_state(OAuthProvider::Discord, &forged));
+
+        let expired = client.sign_state(OAuthProvider::Google, Utc::now().timestamp() - 1);
+        assert!(!client.verify_state(OAuthProvider::Google, &expired));
+    }
+
+    #[tokio::test]
+    async fn callbacks_without_an_issued_state_are_rejected() {
+        let client = client();
+        let code = || "code".to_owned();
+        // The code is never exchanged, so these fail before any request is sent to the provider
+        assert!(client
+            .identify(OAuthProvider::Google, code(), "")
+            .await
+            .is_none());
+        assert!(client
+            .identify(OAuthProvider::Google, code(), "forged")
+            .await
+            .is_none());
+        let state = client.redirect(OAuthProvider::Discord).unwrap().state;
+        assert!(client
+            .identify(OAuthProvider::Google, code(), &state)
+            .await
+            .is_none());
+
+        // Callbacks without a state can not be sent to the login API at all
+        let cred = r#"{"OAuth":{"provider":"google","code":"code"}}"#;
+        assert!(serde_json::from_str::<Credentials>(cred).is_err());
+    }
+}
@@ -0,0 +1,78 @@
+use futures::StreamExt;
+use mongodb::{
+    bson::{doc, spec::BinarySubtype, Binary, Document},
+    options::{UpdateModifications, UpdateOptions},
+    Collection, Database,
+};
+use squire_sdk::model::{identifiers::SeriesId, series::Series};
+use tracing::Level;
+
+/// Stores the series of tournaments that organizers have created
+#[derive(Debug, Clone)]
+pub struct SeriesDb {
+    db_conn: Database,
+}
+
+impl SeriesDb {
+    const SERIES_COLL_NAME: &'static str = "Series";
+
+    pub fn new(db_conn: Database) -> Self {
+        Self { db_conn }
+    }
+
+    fn get_series_coll(&self) -> Collection<Series> {
+        self.db_conn.collection(Self::SERIES_COLL_NAME)
+    }
+
+    fn make_query(id: SeriesId) -> Document {
+        doc! { "id": Binary {
+            bytes: id.as_bytes().to_vec(),
+            subtype: BinarySubtype::Generic,
+        }}
+    }
+
+    pub async fn list_series(&self) -> Vec<Series> {
+        let Ok(cursor) = self.get_series_coll().find(None, None).await else {
+            return Vec::new();
+        };
+        cursor.filter_map(|s| async { s.ok() }).collect().await
+    }
+
+    pub async fn get_series(&self, id: SeriesId) -> Option<Series> {
+        self.get_series_coll()
+            .find_one(Some(Self::make_query(id)), None)
+            .await
+            .ok()
+            .flatten()
+    }
+
+    pub async fn persist_series(&self, series: &Series) -> bool {
+        // See `TournDb::persist_tourn` for why the raw document serializer is used
+        let Ok(doc) = mongodb::bson::to_raw_document_buf(series)
+            .map_err(|_| ())
+            .and_then(|doc| Document::try_from(doc).map_err(|_| ()))
+        else {
+            return false;
+        };
+        match self
+            .get_series_coll()
+            .update_one(
+                Self::make_query(series.id),
+                UpdateModifications::Document(doc! {"$set": doc}),
+                UpdateOptions::builder().upsert(true).build(),
+            )
+            .await
+        {
+            Ok(_) => true,
+            Err(err) => {
+                tracing::event!(
+                    Level::WARN,
+                    r#"Could not save series with name "{}" and id "{}" due to error: {err}"#,
+                    series.name,
+                    series.id,
+                );
+                false
+            }
+        }
+    }
+}
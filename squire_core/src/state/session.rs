@@ -34,6 +34,20 @@
 //! and do in the tournament while maintaining their WS connection. This will likely be done via a
 //! broadcast channel to broadcast guest session promotions.
 //!
+//! API tokens are long-lived tokens that scripts and bots use instead of a session. They share
+//! the session tokens' format and are looked up alongside them, but they are kept in their own
+//! table and never expire. They are only removed when their owner revokes them.
+//!
+//! Users can be logged in on several devices at once, each with its own session. Sessions keep
+//! the IP address that they were created from, the last time they were used, and the name that
+//! the user gave the device, so users can see where they are logged in and revoke sessions that
+//! they do not recognize.
+//!
+//! Guests can accept invites to tournaments, but roles can only be given to accounts. The session
+//! holds on to the invites until the guest registers an account, at which point the session is
+//! upgraded in place. This keeps the guest's WS connections open, and the invites are handed back
+//! so that their roles can be given to the new account.
+//!
 //! Note that the work required to make guest session promotion create a better WS expirence is
 //! also needed for reauth connections. If a user establishes a WS connection, the connection
 //! should be termianted (or at least downgraded to be that of a guest) once it expires. Reauth
@@ -48,16 +62,19 @@ use chrono::{DateTime, Utc};
 use derive_more::From;
 use futures::StreamExt;
 use mongodb::{
-    bson::{doc, Document},
+    bson::{doc, spec::BinarySubtype, Binary, Document},
     options::{UpdateModifications, UpdateOptions},
     Collection, Database,
 };
 use rand::{rngs::StdRng, RngCore, SeedableRng};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use squire_sdk::{
     actor::*,
-    api::SessionToken,
-    model::identifiers::SquireAccountId,
+    api::{
+        ApiToken, ApiTokenId, NewApiToken, SessionId, SessionInfo, SessionToken, TournamentInvite,
+    },
+    model::identifiers::{id_from_item, SquireAccountId},
     server::session::{AnyUser, SquireSession},
 };
 use tokio::sync::{
@@ -68,12 +85,28 @@ use tracing::Level;
 
 #[derive(From)]
 pub enum SessionCommand {
-    Create(SquireAccountId, OneshotSender<SessionToken>),
+    Create(
+        (SquireAccountId, Option<String>),
+        OneshotSender<SessionToken>,
+    ),
     Guest(OneshotSender<SessionToken>),
+    Handoff(Duration, OneshotSender<(SessionToken, DateTime<Utc>)>),
     Get(SessionToken, OneshotSender<SquireSession>),
-    Reauth(AnyUser, OneshotSender<SessionToken>),
+    Reauth(AnyUser, OneshotSender<Option<SessionToken>>),
     Delete(AnyUser, OneshotSender<bool>),
     Subscribe(SessionToken, OneshotSender<Option<Watcher<SquireSession>>>),
+    CreateApiToken((SquireAccountId, String), OneshotSender<NewApiToken>),
+    ListApiTokens(SquireAccountId, OneshotSender<Vec<ApiToken>>),
+    RevokeApiToken((SquireAccountId, ApiTokenId), OneshotSender<bool>),
+    ListSessions(SessionToken, OneshotSender<Vec<SessionInfo>>),
+    RevokeSession((SessionToken, SessionId), OneshotSender<bool>),
+    RevokeAccount((SquireAccountId, Option<SessionToken>), OneshotSender<()>),
+    NameSession((SessionToken, String), OneshotSender<bool>),
+    HoldInvite((SessionToken, TournamentInvite), OneshotSender<bool>),
+    Upgrade(
+        (SessionToken, SquireAccountId),
+        OneshotSender<Option<Vec<TournamentInvite>>>,
+    ),
     #[from(ignore)]
     Expiry(SessionToken),
     #[from(ignore)]
@@ -91,6 +124,8 @@ pub struct SessionStore {
     db: SessionDb,
     comms: HashMap<SessionToken, Broadcaster<SquireSession>>,
     sessions: HashMap<SessionToken, Session>,
+    /// API tokens, keyed by their hashes
+    api_tokens: HashMap<String, ApiTokenRecord>,
 }
 
 #[derive(Debug, Clone)]
@@ -98,7 +133,7 @@ pub struct SessionDb {
     db: Database,
 }
 
-#[derive(Debug, Clone, Hash, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 struct Session {
     /// The time that the session was created
     epoch: DateTime<Utc>,
@@ -106,6 +141,34 @@ struct Session {
     token: SessionToken,
     /// If the session belongs to user, this is their account id.
     id: Option<SquireAccountId>,
+    /// The name that the user gave the device that the session is on
+    #[serde(default)]
+    device: Option<String>,
+    /// The IP address that the session was created from
+    #[serde(default)]
+    ip: Option<String>,
+    /// The last time that the session was used. Sessions from before this was tracked use their
+    /// creation time.
+    #[serde(default)]
+    last_active: Option<DateTime<Utc>>,
+    /// The invites that a guest has accepted, which are redeemed once they register an account
+    #[serde(default)]
+    invites: Vec<TournamentInvite>,
+    /// When the session expires, if it is shorter-lived than a normal session (i.e. the guest
+    /// session of a connection handle)
+    #[serde(default)]
+    expires: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct ApiTokenRecord {
+    /// The hex-encoded SHA-256 of the token. The token itself is only shown to its owner when it is
+    /// created.
+    hash: String,
+    /// The account that the token acts as
+    owner: SquireAccountId,
+    /// The info that is shown to the owner
+    info: ApiToken,
 }
 
 #[async_trait]
@@ -122,18 +185,45 @@ impl ActorState for SessionStore {
     }
 
     async fn process(&mut self, scheduler: &mut Scheduler<Self>, msg: Self::Message) {
-        println!("Got session message: {msg:?}");
         match msg {
-            SessionCommand::Create(id, send) => {
-                drop(send.send(self.create_session(scheduler, id).token))
+            SessionCommand::Create((id, ip), send) => {
+                drop(send.send(self.create_session(scheduler, id, ip).token))
             }
-            SessionCommand::Get(token, send) => drop(send.send(self.get_session(token))),
+            SessionCommand::Get(token, send) => drop(send.send(self.get_session(scheduler, token))),
             SessionCommand::Reauth(id, send) => drop(send.send(self.reauth_session(scheduler, id))),
             SessionCommand::Delete(id, send) => drop(send.send(self.delete_session(scheduler, id))),
             SessionCommand::Guest(send) => drop(send.send(self.guest_session(scheduler).token)),
+            SessionCommand::Handoff(lifetime, send) => {
+                drop(send.send(self.handoff_session(scheduler, lifetime)))
+            }
             SessionCommand::Subscribe(token, send) => drop(send.send(self.sub_to_session(&token))),
             SessionCommand::Expiry(token) => self.expire_session(scheduler, token),
             SessionCommand::Revoke(token) => self.revoke_session(scheduler, &token),
+            SessionCommand::CreateApiToken((id, name), send) => {
+                drop(send.send(self.create_api_token(scheduler, id, name)))
+            }
+            SessionCommand::ListApiTokens(id, send) => drop(send.send(self.list_api_tokens(id))),
+            SessionCommand::RevokeApiToken((id, tok_id), send) => {
+                drop(send.send(self.revoke_api_token(scheduler, id, tok_id)))
+            }
+            SessionCommand::ListSessions(token, send) => {
+                drop(send.send(self.list_sessions(&token)))
+            }
+            SessionCommand::RevokeSession((token, id), send) => {
+                drop(send.send(self.revoke_user_session(scheduler, &token, id)))
+            }
+            SessionCommand::RevokeAccount((id, keep), send) => {
+                drop(send.send(self.revoke_account(scheduler, id, keep)))
+            }
+            SessionCommand::NameSession((token, device), send) => {
+                drop(send.send(self.name_session(scheduler, &token, device)))
+            }
+            SessionCommand::HoldInvite((token, invite), send) => {
+                drop(send.send(self.hold_invite(scheduler, &token, invite)))
+            }
+            SessionCommand::Upgrade((token, id), send) => {
+                drop(send.send(self.upgrade_guest(scheduler, &token, id)))
+            }
         }
     }
 }
@@ -146,6 +236,7 @@ impl SessionStore {
             rng: StdRng::from_entropy(),
             comms: HashMap::new(),
             sessions: HashMap::new(),
+            api_tokens: HashMap::new(),
         }
     }
 
@@ -157,10 +248,20 @@ impl SessionStore {
         digest
     }
 
-    fn create_session(&mut self, scheduler: &mut Scheduler<Self>, id: SquireAccountId) -> Session {
+    fn create_session(
+        &mut self,
+        scheduler: &mut Scheduler<Self>,
+        id: SquireAccountId,
+        ip: Option<String>,
+    ) -> Session {
         let token = self.generate_session(scheduler);
-        let session = Session::new_with_id(token.clone(), id);
-        self.sessions.insert(token.clone(), session.clone());
+        let mut session = Session::new_with_id(token, id);
+        session.ip = ip;
+        self.insert_session(scheduler, session)
+    }
+
+    fn insert_session(&mut self, scheduler: &mut Scheduler<Self>, session: Session) -> Session {
+        self.sessions.insert(session.token.clone(), session.clone());
         let db = self.db.clone();
         let db_session = session.clone();
         scheduler.process(async move { db.persist_session(db_session).await });
@@ -177,33 +278,319 @@ impl SessionStore {
         session
     }
 
-    fn get_session(&mut self, token: SessionToken) -> SquireSession {
+    /// Creates a guest session that expires after the given lifetime rather than after
+    /// `Session::SESSION_DUR`
+    fn handoff_session(
+        &mut self,
+        scheduler: &mut Scheduler<Self>,
+        lifetime: Duration,
+    ) -> (SessionToken, DateTime<Utc>) {
+        let mut token = SessionToken::default();
+        self.rng.fill_bytes(&mut token.0);
+        let mut session = Session::new(token.clone());
+        let expires = session.epoch + chrono::Duration::from_std(lifetime).unwrap_or_default();
+        session.expires = Some(expires);
+        scheduler.schedule(
+            session.next_deadline(),
+            SessionCommand::Expiry(token.clone()),
+        );
+        _ = self.insert_session(scheduler, session);
+        (token, expires)
+    }
+
+    fn get_session(
+        &mut self,
+        scheduler: &mut Scheduler<Self>,
+        token: SessionToken,
+    ) -> SquireSession {
+        if let Some(session) = self.sessions.get_mut(&token) {
+            if session.is_active() && session.touch(Utc::now()) {
+                let db = self.db.clone();
+                let db_session = session.clone();
+                scheduler.process(async move { db.persist_session(db_session).await });
+            }
+        }
         self.sessions
             .get(&token)
             .map(Session::as_squire_session)
+            .or_else(|| {
+                self.api_tokens
+                    .get(&hash_api_token(&token))
+                    .map(|rec| SquireSession::ApiToken(rec.owner))
+            })
             .unwrap_or_default()
     }
 
-    fn reauth_session(&mut self, scheduler: &mut Scheduler<Self>, user: AnyUser) -> SessionToken {
+    fn create_api_token(
+        &mut self,
+        scheduler: &mut Scheduler<Self>,
+        owner: SquireAccountId,
+        name: String,
+    ) -> NewApiToken {
+        let mut token = SessionToken::default();
+        self.rng.fill_bytes(&mut token.0);
+        let created = Utc::now();
+        let info = ApiToken {
+            id: id_from_item(created, &token),
+            name,
+            created,
+        };
+        let rec = ApiTokenRecord {
+            hash: hash_api_token(&token),
+            owner,
+            info: info.clone(),
+        };
+        self.api_tokens.insert(rec.hash.clone(), rec.clone());
+        let db = self.db.clone();
+        scheduler.process(async move { db.persist_api_token(rec).await });
+        NewApiToken { info, token }
+    }
+
+    fn list_api_tokens(&self, owner: SquireAccountId) -> Vec<ApiToken> {
+        let mut digest: Vec<_> = self
+            .api_tokens
+            .values()
+            .filter(|rec| rec.owner == owner)
+            .map(|rec| rec.info.clone())
+            .collect();
+        digest.sort_by_key(|info| info.created);
+        digest
+    }
+
+    fn revoke_api_token(
+        &mut self,
+        scheduler: &mut Scheduler<Self>,
+        owner: SquireAccountId,
+        id: ApiTokenId,
+    ) -> bool {
+        let Some(hash) = self
+            .api_tokens
+            .values()
+            .find(|rec| rec.owner == owner && rec.info.id == id)
+            .map(|rec| rec.hash.clone())
+        else {
+            return false;
+        };
+        self.api_tokens.remove(&hash);
+        // Only the hash of the token is stored, so its watchers are found by hashing theirs
+        self.comms.retain(|token, sq_sess| {
+            let revoked = hash_api_token(token) == hash;
+            if revoked {
+                sq_sess.send_replace(SquireSession::NotLoggedIn);
+            }
+            !revoked
+        });
+        let db = self.db.clone();
+        scheduler.process(async move { db.remove_api_token(id).await });
+        true
+    }
+
+    /// Returns the account of an active session
+    fn session_owner(&self, token: &SessionToken) -> Option<SquireAccountId> {
+        self.sessions
+            .get(token)
+            .filter(|s| s.is_active())
+            .and_then(|s| s.id)
+    }
+
+    fn list_sessions(&self, token: &SessionToken) -> Vec<SessionInfo> {
+        let Some(owner) = self.session_owner(token) else {
+            return Vec::new();
+        };
+        let mut digest: Vec<_> = self
+            .sessions
+            .values()
+            .filter(|s| s.id == Some(owner))
+            .map(|s| s.info(token))
+            .collect();
+        digest.sort_by(|a, b| b.last_active.cmp(&a.last_active));
+        digest
+    }
+
+    /// Revokes one of the sessions of the user that owns the given session. Returns `false` if
+    /// they have no session with that id.
+    fn revoke_user_session(
+        &mut self,
+        scheduler: &mut Scheduler<Self>,
+        token: &SessionToken,
+        id: SessionId,
+    ) -> bool {
+        let Some(owner) = self.session_owner(token) else {
+            return false;
+        };
+        let Some(target) = self
+            .sessions
+            .values()
+            .find(|s| s.id == Some(owner) && s.session_id() == id)
+            .map(|s| s.token.clone())
+        else {
+            return false;
+        };
+        self.drop_session(scheduler, &target)
+    }
+
+    /// Revokes all of the account's sessions and API tokens, except for the one that the request
+    /// was made with. This is done when the account's password changes or the account is deleted.
+    fn revoke_account(
+        &mut self,
+        scheduler: &mut Scheduler<Self>,
+        owner: SquireAccountId,
+        keep: Option<SessionToken>,
+    ) {
+        let targets: Vec<_> = self
+            .sessions
+            .values()
+            .filter(|s| s.id == Some(owner) && Some(&s.token) != keep.as_ref())
+            .map(|s| s.token.clone())
+            .collect();
+        for token in targets {
+            _ = self.drop_session(scheduler, &token);
+        }
+        let keep = keep.as_ref().map(hash_api_token);
+        let targets: Vec<_> = self
+            .api_tokens
+            .values()
+            .filter(|rec| rec.owner == owner && Some(&rec.hash) != keep.as_ref())
+            .map(|rec| rec.info.id)
+            .collect();
+        for id in targets {
+            _ = self.revoke_api_token(scheduler, owner, id);
+        }
+    }
+
+    /// Removes a session, whether or not it has expired, and deletes it from the database
+    fn drop_session(&mut self, scheduler: &mut Scheduler<Self>, token: &SessionToken) -> bool {
+        let Some(session) = self.remove_session(token) else {
+            return false;
+        };
+        let db = self.db.clone();
+        if session.is_active() {
+            scheduler.process(async move { db.remove_session(session).await });
+        } else {
+            scheduler.process(async move { db.remove_expired_session(session).await });
+        }
+        true
+    }
+
+    fn name_session(
+        &mut self,
+        scheduler: &mut Scheduler<Self>,
+        token: &SessionToken,
+        device: String,
+    ) -> bool {
+        let Some(session) = self.sessions.get_mut(token).filter(|s| s.is_active()) else {
+            return false;
+        };
+        session.device = Some(device);
+        let db = self.db.clone();
+        let db_session = session.clone();
+        scheduler.process(async move { db.persist_session(db_session).await });
+        true
+    }
+
+    /// Returns the session if it is an active guest session
+    fn active_guest(&mut self, token: &SessionToken) -> Option<&mut Session> {
+        self.sessions
+            .get_mut(token)
+            .filter(|s| s.id.is_none() && s.is_active())
+    }
+
+    /// Has a guest's session hold on to an invite. Returns `false` if the session is not an active
+    /// guest session.
+    fn hold_invite(
+        &mut self,
+        scheduler: &mut Scheduler<Self>,
+        token: &SessionToken,
+        invite: TournamentInvite,
+    ) -> bool {
+        let db = self.db.clone();
+        let Some(session) = self.active_guest(token) else {
+            return false;
+        };
+        // Only the latest invite to each tournament is kept
+        session.invites.retain(|inv| inv.tourn != invite.tourn);
+        session.invites.push(invite);
+        let db_session = session.clone();
+        scheduler.process(async move { db.persist_session(db_session).await });
+        true
+    }
+
+    /// Turns a guest's session into a session for their new account. The invites that the guest
+    /// accepted are returned, or `None` if the session is not an active guest session.
+    fn upgrade_guest(
+        &mut self,
+        scheduler: &mut Scheduler<Self>,
+        token: &SessionToken,
+        id: SquireAccountId,
+    ) -> Option<Vec<TournamentInvite>> {
+        let db = self.db.clone();
+        let session = self.active_guest(token)?;
+        session.id = Some(id);
+        let invites = std::mem::take(&mut session.invites);
+        let sq_sess = session.as_squire_session();
+        let db_session = session.clone();
+        scheduler.process(async move { db.persist_session(db_session).await });
+        if let Some(comm) = self.comms.get(token) {
+            comm.send_replace(sq_sess);
+        }
+        Some(invites)
+    }
+
+    /// Issues a new session to replace the user's current one. Expired sessions can only be
+    /// replaced if they belong to an account and were not created with a fixed lifetime.
+    fn reauth_session(
+        &mut self,
+        scheduler: &mut Scheduler<Self>,
+        user: AnyUser,
+    ) -> Option<SessionToken> {
         match user {
             AnyUser::Guest(token) => {
-                self.sessions.remove(&token);
-                let session = self.guest_session(scheduler);
+                // The guest keeps the invites that they accepted, and a short-lived session is not
+                // extended
+                let (invites, expires) = self
+                    .sessions
+                    .remove(&token)
+                    .map(|s| (s.invites, s.expires))
+                    .unwrap_or_default();
+                let mut session = Session::new(self.generate_session(scheduler));
+                session.invites = invites;
+                session.expires = expires;
+                if expires.is_some() {
+                    scheduler.schedule(
+                        session.next_deadline(),
+                        SessionCommand::Expiry(session.token.clone()),
+                    );
+                }
+                let session = self.insert_session(scheduler, session);
                 if let Some(sq_sess) = self.comms.get(&token) {
                     sq_sess.send_replace(session.as_squire_session());
                 }
-                session.token
+                Some(session.token)
             }
-            AnyUser::Active(token) | AnyUser::Expired(token) | AnyUser::ExpiredGuest(token) => {
-                match self.sessions.remove(&token).and_then(|s| s.id) {
-                    Some(id) => {
-                        let session = self.create_session(scheduler, id);
+            AnyUser::ExpiredGuest(_) => None,
+            AnyUser::Active(token) | AnyUser::Expired(token) => {
+                if !self.sessions.get(&token)?.can_reauth() {
+                    return None;
+                }
+                match self.sessions.remove(&token) {
+                    Some(Session {
+                        id: Some(id),
+                        device,
+                        ip,
+                        ..
+                    }) => {
+                        // The new session is on the same device as the old one
+                        let mut session =
+                            Session::new_with_id(self.generate_session(scheduler), id);
+                        session.device = device;
+                        session.ip = ip;
+                        let session = self.insert_session(scheduler, session);
                         if let Some(sq_sess) = self.comms.get(&token) {
                             sq_sess.send_replace(session.as_squire_session());
                         }
-                        session.token
+                        Some(session.token)
                     }
-                    None => self.generate_session(scheduler),
+                    _ => None,
                 }
             }
         }
@@ -270,8 +657,10 @@ impl SessionStore {
     }
 
     fn create_watcher(&mut self, token: &SessionToken) -> Option<Watcher<SquireSession>> {
-        let session = self.sessions.get(token)?;
-        let sq_sess = session.as_squire_session();
+        let sq_sess = match self.sessions.get(token) {
+            Some(session) => session.as_squire_session(),
+            None => SquireSession::ApiToken(self.api_tokens.get(&hash_api_token(token))?.owner),
+        };
         let (send, recv) = channel(sq_sess);
         self.comms.insert(token.clone(), send);
         Some(recv)
@@ -281,6 +670,7 @@ impl SessionStore {
 impl SessionDb {
     const ACTIVE_SESSION_TABLE: &'static str = "ActiveSessions";
     const EXPIRED_SESSION_TABLE: &'static str = "ExpiredSessions";
+    const API_TOKEN_TABLE: &'static str = "ApiTokens";
 
     pub fn new(db: Database) -> Self {
         Self { db }
@@ -294,6 +684,10 @@ impl SessionDb {
         self.db.collection(Self::EXPIRED_SESSION_TABLE)
     }
 
+    fn get_api_token_table(&self) -> Collection<ApiTokenRecord> {
+        self.db.collection(Self::API_TOKEN_TABLE)
+    }
+
     /// Takes mutable reference to a session store and fills its cache with all the sessions in the
     /// database. This is used on startup.
     pub async fn load_all_sessions(&self, cache: &mut SessionStore) {
@@ -313,6 +707,13 @@ impl SessionDb {
                 cache.sessions.insert(token, session);
             }
         }
+        // Fetch all API tokens
+        let mut cursor = self.get_api_token_table().find(None, None).await.unwrap();
+        while let Some(rec) = cursor.next().await {
+            if let Ok(rec) = rec {
+                cache.api_tokens.insert(rec.hash.clone(), rec);
+            }
+        }
     }
 
     /// Inserts or updates a session in the database.
@@ -336,6 +737,23 @@ impl SessionDb {
         delete_session(table, session).await;
     }
 
+    /// Inserts an API token into the database.
+    async fn persist_api_token(&self, rec: ApiTokenRecord) {
+        if let Err(err) = self.get_api_token_table().insert_one(&rec, None).await {
+            tracing::event!(
+                Level::WARN,
+                "Could not persist API token `{}` got error: {err}",
+                rec.info.id,
+            );
+        }
+    }
+
+    /// Removes an API token from the database.
+    async fn remove_api_token(&self, id: ApiTokenId) {
+        let query = api_token_query(id);
+        let _ = self.get_api_token_table().delete_one(query, None).await;
+    }
+
     /// Removes an expired session from the database.
     async fn remove_expired_session(&self, session: Session) {
         let table = self.get_expired_table();
@@ -343,14 +761,21 @@ impl SessionDb {
     }
 }
 
-async fn persist_session(table: Collection<Session>, session: Session) -> bool {
-    let doc: Document = mongodb::bson::to_raw_document_buf(&session)
+/// Sessions are looked up by their token in the database, since the rest of a session can change
+fn session_docs(session: &Session) -> (Document, Document) {
+    let doc: Document = mongodb::bson::to_raw_document_buf(session)
         .unwrap()
         .try_into()
         .unwrap();
+    let filter = doc! { "token": doc.get("token").cloned() };
+    (filter, doc)
+}
+
+async fn persist_session(table: Collection<Session>, session: Session) -> bool {
+    let (filter, doc) = session_docs(&session);
     if table
         .update_one(
-            doc.clone(),
+            filter,
             UpdateModifications::Document(doc! {"$set": doc}),
             UpdateOptions::builder().upsert(true).build(),
         )
@@ -368,12 +793,22 @@ async fn persist_session(table: Collection<Session>, session: Session) -> bool {
     true
 }
 
+fn api_token_query(id: ApiTokenId) -> Document {
+    doc! { "info.id": Binary {
+        bytes: id.as_bytes().to_vec(),
+        subtype: BinarySubtype::Generic,
+    }}
+}
+
+/// Hashes an API token. Only the hashes of API tokens are stored, so that the tokens can not be
+/// read out of the database.
+fn hash_api_token(token: &SessionToken) -> String {
+    hex::encode(Sha256::digest(token.0))
+}
+
 async fn delete_session(table: Collection<Session>, session: Session) -> bool {
-    let doc: Document = mongodb::bson::to_raw_document_buf(&session)
-        .unwrap()
-        .try_into()
-        .unwrap();
-    table.delete_one(doc, None).await.is_ok()
+    let (filter, _) = session_docs(&session);
+    table.delete_one(filter, None).await.is_ok()
 }
 
 #[derive(Debug, Clone)]
@@ -387,19 +822,23 @@ impl SessionStoreHandle {
         Self { client }
     }
 
-    pub fn create(&self, id: SquireAccountId) -> Tracker<SessionToken> {
-        self.client.track(id)
+    pub fn create(&self, id: SquireAccountId, ip: Option<String>) -> Tracker<SessionToken> {
+        self.client.track((id, ip))
     }
 
     pub fn guest(&self) -> Tracker<SessionToken> {
         self.client.track(())
     }
 
+    pub fn handoff(&self, lifetime: Duration) -> Tracker<(SessionToken, DateTime<Utc>)> {
+        self.client.track(lifetime)
+    }
+
     pub fn get(&self, token: SessionToken) -> Tracker<SquireSession> {
         self.client.track(token)
     }
 
-    pub fn reauth(&self, id: AnyUser) -> Tracker<SessionToken> {
+    pub fn reauth(&self, id: AnyUser) -> Tracker<Option<SessionToken>> {
         self.client.track(id)
     }
 
@@ -410,6 +849,47 @@ impl SessionStoreHandle {
     pub fn watch(&self, token: SessionToken) -> Tracker<Option<Watcher<SquireSession>>> {
         self.client.track(token)
     }
+
+    pub fn create_api_token(&self, id: SquireAccountId, name: String) -> Tracker<NewApiToken> {
+        self.client.track((id, name))
+    }
+
+    pub fn list_api_tokens(&self, id: SquireAccountId) -> Tracker<Vec<ApiToken>> {
+        self.client.track(id)
+    }
+
+    pub fn revoke_api_token(&self, id: SquireAccountId, tok_id: ApiTokenId) -> Tracker<bool> {
+        self.client.track((id, tok_id))
+    }
+
+    pub fn list_sessions(&self, token: SessionToken) -> Tracker<Vec<SessionInfo>> {
+        self.client.track(token)
+    }
+
+    pub fn revoke_session(&self, token: SessionToken, id: SessionId) -> Tracker<bool> {
+        self.client.track((token, id))
+    }
+
+    /// Revokes all of the account's sessions and API tokens, except for the given one
+    pub fn revoke_account(&self, id: SquireAccountId, keep: Option<SessionToken>) -> Tracker<()> {
+        self.client.track((id, keep))
+    }
+
+    pub fn name_session(&self, token: SessionToken, device: String) -> Tracker<bool> {
+        self.client.track((token, device))
+    }
+
+    pub fn hold_invite(&self, token: SessionToken, invite: TournamentInvite) -> Tracker<bool> {
+        self.client.track((token, invite))
+    }
+
+    pub fn upgrade(
+        &self,
+        token: SessionToken,
+        id: SquireAccountId,
+    ) -> Tracker<Option<Vec<TournamentInvite>>> {
+        self.client.track((token, id))
+    }
 }
 
 impl Session {
@@ -417,12 +897,20 @@ impl Session {
     const SESSION_DUR: Duration = Duration::from_secs(518400);
     /// The amount of time an expired session can live for before being forgotten entirely (1 day)
     const EXPIRY_DUR: Duration = Duration::from_secs(86400);
+    /// How often a session's activity is persisted (15 minutes). Persisting every use would write
+    /// to the database on every request.
+    const ACTIVITY_RESOLUTION: Duration = Duration::from_secs(900);
 
     fn new(token: SessionToken) -> Self {
         Self {
             epoch: Utc::now(),
             token,
             id: None,
+            device: None,
+            ip: None,
+            last_active: None,
+            invites: Vec::new(),
+            expires: None,
         }
     }
 
@@ -431,6 +919,11 @@ impl Session {
             epoch: Utc::now(),
             token,
             id: Some(id),
+            device: None,
+            ip: None,
+            last_active: None,
+            invites: Vec::new(),
+            expires: None,
         }
     }
 
@@ -440,21 +933,65 @@ impl Session {
     fn next_deadline(&self) -> Instant {
         // The amount of time that has passed since the creation of the session.
         let elapsed = self.get_elapsed_dur();
+        let lifetime = self.lifetime();
         Instant::now()
-            + Self::SESSION_DUR
+            + lifetime
                 .checked_sub(elapsed)
-                .or_else(|| (Self::SESSION_DUR + Self::EXPIRY_DUR).checked_sub(elapsed))
+                .or_else(|| (lifetime + Self::EXPIRY_DUR).checked_sub(elapsed))
                 .unwrap_or_default()
     }
 
+    /// How long the session is active for
+    fn lifetime(&self) -> Duration {
+        self.expires
+            .map(|expires| (expires - self.epoch).to_std().unwrap_or_default())
+            .unwrap_or(Self::SESSION_DUR)
+    }
+
     fn is_active(&self) -> bool {
-        Self::SESSION_DUR > self.get_elapsed_dur()
+        self.lifetime() > self.get_elapsed_dur()
+    }
+
+    /// Whether the session can be replaced by a new one through reauth. Sessions with a fixed
+    /// lifetime can not outlive it.
+    fn can_reauth(&self) -> bool {
+        self.is_active() || self.expires.is_none()
     }
 
     fn get_elapsed_dur(&self) -> Duration {
         (Utc::now() - self.epoch).to_std().unwrap_or_default()
     }
 
+    /// The public id of the session. The token can not be used since it would let others act as
+    /// the user.
+    fn session_id(&self) -> SessionId {
+        id_from_item(self.epoch, &self.token)
+    }
+
+    fn last_active(&self) -> DateTime<Utc> {
+        self.last_active.unwrap_or(self.epoch)
+    }
+
+    /// Marks the session as used. Returns whether the session should be persisted, which is only
+    /// needed once per `ACTIVITY_RESOLUTION`.
+    fn touch(&mut self, now: DateTime<Utc>) -> bool {
+        let res = Self::ACTIVITY_RESOLUTION.as_secs() as i64;
+        let last = self.last_active();
+        self.last_active = Some(now);
+        last.timestamp() / res != now.timestamp() / res
+    }
+
+    fn info(&self, current: &SessionToken) -> SessionInfo {
+        SessionInfo {
+            id: self.session_id(),
+            device: self.device.clone(),
+            created: self.epoch,
+            last_active: self.last_active(),
+            ip: self.ip.clone(),
+            current: self.token == *current,
+        }
+    }
+
     /// Creates a SquireSession
     fn as_squire_session(&self) -> SquireSession {
         match self.id {
@@ -465,3 +1002,75 @@ impl Session {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, TimeZone, Utc};
+    use squire_sdk::{api::SessionToken, server::session::SquireSession};
+
+    use super::{hash_api_token, Session};
+
+    #[test]
+    fn handoff_sessions_expire_early() {
+        let token = SessionToken([1; 32]);
+        let mut session = Session::new(token.clone());
+        session.expires = Some(session.epoch + Duration::minutes(5));
+        assert_eq!(
+            session.as_squire_session(),
+            SquireSession::Guest(token.clone())
+        );
+
+        session.epoch = Utc::now() - Duration::minutes(10);
+        session.expires = Some(session.epoch + Duration::minutes(5));
+        assert_eq!(
+            session.as_squire_session(),
+            SquireSession::ExpiredGuest(token)
+        );
+        // Normal sessions are still active after the same amount of time
+        session.expires = None;
+        assert!(session.is_active());
+    }
+
+    #[test]
+    fn expired_handoff_sessions_can_not_reauth() {
+        let mut session = Session::new(SessionToken([5; 32]));
+        session.epoch = Utc::now() - Duration::minutes(10);
+        session.expires = Some(session.epoch + Duration::minutes(5));
+        assert!(!session.can_reauth());
+
+        // A session that is re-issued after its expiry has a negative lifetime, which is treated
+        // as already expired rather than as a full session
+        session.epoch = Utc::now();
+        assert_eq!(session.lifetime(), std::time::Duration::ZERO);
+        assert!(!session.is_active());
+        assert!(!session.can_reauth());
+
+        // Normal sessions can be reauthed until they are forgotten
+        session.epoch = Utc::now() - Duration::days(7);
+        session.expires = None;
+        assert!(!session.is_active());
+        assert!(session.can_reauth());
+    }
+
+    #[test]
+    fn api_tokens_are_stored_hashed() {
+        let token = SessionToken([2; 32]);
+        let hash = hash_api_token(&token);
+        assert_eq!(hash.len(), 64);
+        assert_ne!(hash, hash_api_token(&SessionToken([3; 32])));
+        assert!(!hash.contains(&hex::encode(token.0)));
+    }
+
+    #[test]
+    fn activity_is_persisted_once_per_window() {
+        let mut session = Session::new(SessionToken([4; 32]));
+        let window = Duration::from_std(Session::ACTIVITY_RESOLUTION).unwrap();
+        let start = Utc.timestamp_opt(window.num_seconds() * 1000, 0).unwrap();
+        session.last_active = Some(start);
+
+        assert!(!session.touch(start + Duration::minutes(1)));
+        assert_eq!(session.last_active(), start + Duration::minutes(1));
+        assert!(session.touch(start + window));
+        assert!(!session.touch(start + window + Duration::minutes(1)));
+    }
+}
@@ -0,0 +1,33 @@
+use std::sync::Arc;
+
+use dashmap::DashMap;
+use squire_sdk::{api::PlayerStats, model::identifiers::SquireAccountId};
+
+/// Caches the stats of accounts, which are aggregated from their tournament histories. Stats are
+/// cached separately for the account's owner, who can see every tournament that they played in,
+/// and for everyone else.
+#[derive(Debug, Clone, Default)]
+pub struct StatsCache {
+    stats: Arc<DashMap<(SquireAccountId, bool), PlayerStats>>,
+}
+
+impl StatsCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self, account: SquireAccountId, all: bool) -> Option<PlayerStats> {
+        self.stats.get(&(account, all)).map(|stats| stats.clone())
+    }
+
+    pub fn insert(&self, account: SquireAccountId, all: bool, stats: PlayerStats) {
+        _ = self.stats.insert((account, all), stats);
+    }
+
+    /// Drops the cached stats of the account, which need to be recalculated once its history
+    /// changes
+    pub fn invalidate(&self, account: SquireAccountId) {
+        _ = self.stats.remove(&(account, true));
+        _ = self.stats.remove(&(account, false));
+    }
+}
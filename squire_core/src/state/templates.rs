@@ -0,0 +1,84 @@
+use futures::StreamExt;
+use mongodb::{
+    bson::{doc, spec::BinarySubtype, Binary, Document},
+    options::{UpdateModifications, UpdateOptions},
+    Collection, Database,
+};
+use squire_sdk::model::{identifiers::SquireAccountId, settings::TournamentTemplate};
+use tracing::Level;
+
+/// Stores the tournament templates that organizers have saved to their accounts
+#[derive(Debug, Clone)]
+pub struct TemplateDb {
+    db_conn: Database,
+}
+
+impl TemplateDb {
+    const TEMPLATE_COLL_NAME: &'static str = "Templates";
+
+    pub fn new(db_conn: Database) -> Self {
+        Self { db_conn }
+    }
+
+    fn get_templates(&self) -> Collection<TournamentTemplate> {
+        self.db_conn.collection(Self::TEMPLATE_COLL_NAME)
+    }
+
+    fn as_binary(bytes: &[u8]) -> Binary {
+        Binary {
+            bytes: bytes.to_vec(),
+            subtype: BinarySubtype::Generic,
+        }
+    }
+
+    /// Saves a template, replacing the saved copy if there is one. Only the owner of a template
+    /// can replace it.
+    pub async fn save_template(&self, template: &TournamentTemplate) -> bool {
+        let query = doc! { "id": Self::as_binary(template.id.as_bytes()) };
+        match self
+            .get_templates()
+            .find_one(Some(query.clone()), None)
+            .await
+        {
+            Ok(Some(saved)) if saved.owner != template.owner => return false,
+            Ok(_) => {}
+            Err(_) => return false,
+        }
+        // See `TournDb::persist_tourn` for why the raw document serializer is used
+        let Ok(doc) = mongodb::bson::to_raw_document_buf(template)
+            .map_err(|_| ())
+            .and_then(|doc| Document::try_from(doc).map_err(|_| ()))
+        else {
+            return false;
+        };
+        match self
+            .get_templates()
+            .update_one(
+                query,
+                UpdateModifications::Document(doc! {"$set": doc}),
+                UpdateOptions::builder().upsert(true).build(),
+            )
+            .await
+        {
+            Ok(_) => true,
+            Err(err) => {
+                tracing::event!(
+                    Level::WARN,
+                    r#"Could not save template with name "{}" and id "{}" due to error: {err}"#,
+                    template.name,
+                    template.id,
+                );
+                false
+            }
+        }
+    }
+
+    /// Lists all of the templates owned by an account
+    pub async fn list_templates(&self, owner: SquireAccountId) -> Vec<TournamentTemplate> {
+        let query = doc! { "owner": Self::as_binary(owner.as_bytes()) };
+        let Ok(cursor) = self.get_templates().find(Some(query), None).await else {
+            return Vec::new();
+        };
+        cursor.filter_map(|t| async { t.ok() }).collect().await
+    }
+}
@@ -1,25 +1,61 @@
-use std::{ops::Range, sync::Arc};
+use std::{collections::HashMap, ops::Range, sync::Arc};
 
 use futures::StreamExt;
 use mongodb::{
-    bson::{doc, spec::BinarySubtype, Binary, Document},
-    options::{FindOptions, Hint, UpdateModifications, UpdateOptions},
+    bson::{doc, spec::BinarySubtype, to_bson, Binary, Document},
+    options::{CountOptions, FindOptions, UpdateModifications},
     Collection, Database,
 };
+use serde::{Deserialize, Serialize};
 use squire_sdk::{
-    actor::*, api::TournamentSummary, model::tournament::TournamentId,
-    server::gathering::PersistMessage, sync::TournamentManager,
+    actor::*,
+    api::{TournamentFilter, TournamentSummary, Webhook, WebhookEvent},
+    model::{
+        settings::Visibility,
+        simulation::TournamentDiff,
+        tournament::{Tournament, TournamentId},
+    },
+    server::gathering::PersistMessage,
+    sync::{FullOp, TournamentManager},
 };
 use tracing::Level;
 
+use super::{
+    leases::{LeaseDb, NODE_URL_VAR},
+    migrate, EventDb, HistoryDb, Notifier, WebhookDb, SCHEMA_VERSION,
+};
+
 #[derive(Debug, Clone)]
 pub struct TournDb {
     db_conn: Database,
     tourn_coll: Arc<str>,
+    /// The address of this node when several servers share the database. Operations are only
+    /// appended to a tournament's write-ahead log while this node holds the lease on it.
+    node: Option<Arc<str>>,
+}
+
+/// An operation in a tournament's write-ahead log. Operations are appended as they are synced and
+/// are cleared once a full copy of the tournament that holds them is persisted.
+#[derive(Debug, Serialize, Deserialize)]
+struct LoggedOp {
+    tourn: TournamentId,
+    /// The version of the persisted copy that the operation builds on. Operations that were
+    /// logged before this was recorded have no version.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    version: Option<u64>,
+    op: FullOp,
 }
 
 pub struct TournPersister {
     db: TournDb,
+    events: EventDb,
+    history: HistoryDb,
+    webhooks: WebhookDb,
+    notifier: Notifier,
+    /// The latest copy of each tournament that has been persisted or synced through this
+    /// persister, so that the events of each batch of synced operations can be found without
+    /// loading the tournament and replaying its write-ahead log
+    latest: HashMap<TournamentId, Box<TournamentManager>>,
 }
 
 #[async_trait]
@@ -29,32 +65,130 @@ impl ActorState for TournPersister {
     async fn process(&mut self, _scheduler: &mut Scheduler<Self>, msg: Self::Message) {
         match msg {
             PersistMessage::Get(id, send) => {
-                let _ = send.send(self.get_tourn(id).await);
+                let tourn = self.get_tourn(id).await;
+                // The tournament is loaded when a node starts hosting it, so this refreshes any copy
+                // that went stale while another node hosted it
+                if let (Some(tourn), Some(latest)) = (&tourn, self.latest.get_mut(&id)) {
+                    latest.clone_from(tourn);
+                }
+                let _ = send.send(tourn);
             }
             PersistMessage::Persist(tourn) => {
-                self.db.persist_tourn(&tourn).await;
+                let (hooks, old) = self.listeners(tourn.id, false).await;
+                if self.db.persist_tourn(&tourn).await {
+                    self.history.index_tourn(&tourn).await;
+                }
+                if let Some(old) = old {
+                    self.dispatch_events(hooks, &old, &tourn);
+                }
+                _ = self.latest.insert(tourn.id, tourn);
+            }
+            PersistMessage::AppendOps(id, version, ops) => {
+                // The copy is loaded before the operations are appended, since a loaded copy would
+                // already hold them
+                let (hooks, old) = self.listeners(id, true).await;
+                // Operations from a node that no longer hosts the tournament are dropped, along
+                // with their events
+                if !self.db.append_ops(id, version, ops.clone()).await {
+                    if let Some(old) = old {
+                        _ = self.latest.insert(id, old);
+                    }
+                    return;
+                }
+                if let Some(old) = old {
+                    let mut tourn = old.clone();
+                    let _ = tourn.replay_ops(ops);
+                    self.dispatch_events(hooks, &old, &tourn);
+                    // Otherwise, the histories would not change until the next full copy is
+                    // persisted, which could be long after the tournament ends
+                    if changes_history(old.tourn(), tourn.tourn()) {
+                        self.history.index_tourn(&tourn).await;
+                    }
+                    _ = self.latest.insert(id, tourn);
+                }
+            }
+            PersistMessage::RecordEvents(events) => {
+                self.events.record_events(events).await;
+            }
+            PersistMessage::Flush(send) => {
+                let _ = send.send(());
             }
         }
     }
 }
 
 impl TournPersister {
-    pub fn new(db: TournDb) -> Self {
-        Self { db }
+    pub fn new(
+        db: TournDb,
+        events: EventDb,
+        history: HistoryDb,
+        webhooks: WebhookDb,
+        notifier: Notifier,
+    ) -> Self {
+        Self {
+            db,
+            events,
+            history,
+            webhooks,
+            notifier,
+            latest: HashMap::new(),
+        }
     }
 
     pub async fn get_tourn(&self, id: TournamentId) -> Option<Box<TournamentManager>> {
         self.db.get_tourn(id).await
     }
+
+    /// Returns the tournament's webhooks and the latest copy of the tournament, which is needed to
+    /// find the events for the tournament's webhooks and player notifications. For `synced`
+    /// operations, the copy is also needed to tell whether the players' histories changed. The
+    /// copy is kept from the last batch, so the tournament is only loaded if this persister has
+    /// not seen it yet and the copy is needed.
+    async fn listeners(
+        &mut self,
+        id: TournamentId,
+        synced: bool,
+    ) -> (Vec<Webhook>, Option<Box<TournamentManager>>) {
+        let hooks = self.webhooks.list_webhooks(id).await;
+        let old = match self.latest.remove(&id) {
+            Some(old) => Some(old),
+            None if !synced && hooks.is_empty() && !self.notifier.is_enabled() => None,
+            None => self.db.get_tourn(id).await,
+        };
+        (hooks, old)
+    }
+
+    /// Sends the events that happened between the two copies of the tournament to its webhooks and
+    /// the players that they affect
+    fn dispatch_events(
+        &self,
+        hooks: Vec<Webhook>,
+        old: &TournamentManager,
+        new: &TournamentManager,
+    ) {
+        let events = WebhookEvent::between(old.tourn(), new.tourn());
+        self.notifier.notify_events(new.tourn(), &events);
+        self.webhooks.dispatch(hooks, events);
+    }
 }
 
-impl TournDb {
-    const TOURN_INDEX_NAME: &'static str = "tourn_id";
+/// Calculates if the changes between the two copies of the tournament change what is in the
+/// histories of its players, e.g. the tournament ended or the result of a round was certified
+fn changes_history(old: &Tournament, new: &Tournament) -> bool {
+    let diff = TournamentDiff::new(old, new);
+    diff.status.is_some()
+        || !diff.new_players.is_empty()
+        || !diff.player_statuses.is_empty()
+        || !diff.new_rounds.is_empty()
+        || !diff.updated_rounds.is_empty()
+}
 
+impl TournDb {
     pub fn new(db_conn: Database, tourn_coll: Arc<str>) -> Self {
         Self {
             db_conn,
             tourn_coll,
+            node: std::env::var(NODE_URL_VAR).ok().map(Into::into),
         }
     }
 
@@ -66,6 +200,31 @@ impl TournDb {
         self.get_db().collection(&self.tourn_coll)
     }
 
+    /// Returns the tournament collection as raw documents, which is used to upgrade documents
+    /// before they are read
+    fn get_tourn_docs(&self) -> Collection<Document> {
+        self.get_tourns().clone_with_type()
+    }
+
+    /// Reads a tournament from a document. The document goes through the raw document
+    /// deserializer, like documents that are read straight from the tournament collection.
+    fn from_doc(doc: &Document) -> Result<TournamentManager, String> {
+        let bytes = mongodb::bson::to_vec(doc).map_err(|err| err.to_string())?;
+        mongodb::bson::from_slice(&bytes).map_err(|err| err.to_string())
+    }
+
+    /// Returns the collection that holds the write-ahead logs of the tournaments
+    fn get_logged_ops(&self) -> Collection<LoggedOp> {
+        self.get_db().collection(&format!("{}Ops", self.tourn_coll))
+    }
+
+    fn make_ops_query(id: TournamentId) -> Document {
+        doc! { "tourn": Binary {
+            bytes: id.as_bytes().to_vec(),
+            subtype: BinarySubtype::Generic,
+        }}
+    }
+
     fn make_query(id: TournamentId) -> Document {
         doc! { "tourn.id": Binary {
             bytes: id.as_bytes().to_vec(),
@@ -73,70 +232,310 @@ impl TournDb {
         }}
     }
 
+    /// Fetches the latest copy of the tournament, which is the last full copy that was persisted
+    /// plus the operations in its write-ahead log
     pub async fn get_tourn(&self, id: TournamentId) -> Option<Box<TournamentManager>> {
-        self.get_tourns()
+        let mut doc = self
+            .get_tourn_docs()
             .find_one(Some(Self::make_query(id)), None)
             .await
             .ok()
-            .flatten()
-            .map(Box::new)
+            .flatten()?;
+        // Old documents are upgraded as they are loaded. The upgraded copy is written the next
+        // time that the tournament is persisted.
+        let _ = migrate(&mut doc);
+        let mut tourn = match Self::from_doc(&doc) {
+            Ok(tourn) => tourn,
+            Err(err) => {
+                tracing::event!(
+                    Level::WARN,
+                    r#"Could not read tournament with id "{id}" due to error: {err}"#,
+                );
+                return None;
+            }
+        };
+        let ops = self.get_ops(id, tourn.version()).await;
+        if !ops.is_empty() {
+            let _ = tourn.replay_ops(ops);
+        }
+        Some(Box::new(tourn))
+    }
+
+    /// Upgrades every persisted tournament that has an old schema version. Tournaments are also
+    /// upgraded as they are loaded, but listings read documents straight from the collection.
+    pub async fn migrate_all(&self) {
+        let query = doc! { "$or": [
+            { "schema": { "$lt": SCHEMA_VERSION } },
+            { "schema": { "$exists": false } },
+        ] };
+        let Ok(mut cursor) = self.get_tourn_docs().find(query, None).await else {
+            return;
+        };
+        let mut count = 0;
+        while let Some(Ok(mut doc)) = cursor.next().await {
+            let Ok(id) = doc.get_object_id("_id") else {
+                continue;
+            };
+            if !migrate(&mut doc) {
+                continue;
+            }
+            match self
+                .get_tourn_docs()
+                .replace_one(doc! { "_id": id }, &doc, None)
+                .await
+            {
+                Ok(_) => count += 1,
+                Err(err) => tracing::event!(
+                    Level::WARN,
+                    r#"Could not upgrade tournament document "{id}" due to error: {err}"#,
+                ),
+            }
+        }
+        if count != 0 {
+            tracing::info!("Upgraded {count} tournaments to schema version {SCHEMA_VERSION}");
+        }
     }
 
+    /// Upgrades old tournaments in the background
+    pub fn start_migrations(&self) {
+        let db = self.clone();
+        _ = tokio::spawn(async move { db.migrate_all().await });
+    }
+
+    /// Returns the operations in the tournament's write-ahead log that build on the persisted copy
+    /// with the given version, oldest first. Any others were logged by a node that no longer hosts
+    /// the tournament.
+    async fn get_ops(&self, id: TournamentId, version: u64) -> Vec<FullOp> {
+        let opts = FindOptions::builder().sort(doc! { "_id": 1 }).build();
+        let mut query = Self::make_ops_query(id);
+        let _ = query.insert(
+            "$or",
+            vec![
+                doc! { "version": version as i64 },
+                doc! { "version": { "$exists": false } },
+            ],
+        );
+        let Ok(cursor) = self.get_logged_ops().find(Some(query), opts).await else {
+            return Vec::new();
+        };
+        cursor
+            .filter_map(|op| async { op.ok().map(|op| op.op) })
+            .collect()
+            .await
+    }
+
+    /// Appends operations to the tournament's write-ahead log. The operations are only appended
+    /// if they build on the persisted copy of the tournament (i.e. the stored copy has the given
+    /// version) and, when several servers share the database, if this node holds the lease on the
+    /// tournament. Returns `false` if nothing was appended.
+    pub async fn append_ops(&self, id: TournamentId, version: u64, ops: Vec<FullOp>) -> bool {
+        if ops.is_empty() {
+            return false;
+        }
+        if let Some(node) = &self.node {
+            if !LeaseDb::is_held_by(&self.db_conn, node, id).await {
+                tracing::event!(
+                    Level::WARN,
+                    r#"Skipped appending operations for tournament with id "{id}" since this node does not hold its lease"#,
+                );
+                return false;
+            }
+        }
+        let mut query = Self::make_query(id);
+        let _ = query.insert(
+            "$or",
+            vec![
+                doc! { "version": version as i64 },
+                doc! { "version": { "$exists": false } },
+            ],
+        );
+        let opts = CountOptions::builder().limit(1).build();
+        if !matches!(self.get_tourns().count_documents(query, opts).await, Ok(count) if count != 0)
+        {
+            tracing::event!(
+                Level::WARN,
+                r#"Skipped appending operations to stale version {version} of tournament with id "{id}""#,
+            );
+            return false;
+        }
+        let ops = ops.into_iter().map(|op| LoggedOp {
+            tourn: id,
+            version: Some(version),
+            op,
+        });
+        match self.get_logged_ops().insert_many(ops, None).await {
+            Ok(_) => true,
+            Err(err) => {
+                tracing::event!(
+                    Level::WARN,
+                    r#"Could not append operations for tournament with id "{id}" due to error: {err}"#,
+                );
+                false
+            }
+        }
+    }
+
+    /// Clears the tournament's write-ahead log. This is done once a full copy of the tournament
+    /// has been persisted, since that copy holds every operation in the log.
+    async fn clear_ops(&self, id: TournamentId) {
+        if let Err(err) = self
+            .get_logged_ops()
+            .delete_many(Self::make_ops_query(id), None)
+            .await
+        {
+            tracing::event!(
+                Level::WARN,
+                r#"Could not clear operations for tournament with id "{id}" due to error: {err}"#,
+            );
+        }
+    }
+
+    /// Persists a full copy of the tournament. The stored copy is only replaced if it has an older
+    /// version than the given copy, so that a stale copy (e.g. from another server or a gathering
+    /// that has since been restarted) can not overwrite newer state. Returns `false` if nothing was
+    /// written.
     pub async fn persist_tourn(&self, tourn: &TournamentManager) -> bool {
         // There appears to be a problem in bson right now where `Collection::replace_one` uses the
         // normal document serializer, but `Collection::find_one` (and `Collection::insert_one` as
         // well) use the raw document serializer, which unfortunately behave differently. Therefore
         // `Collection::update_one` is used as a workaround so that we can call the raw document
         // serializer here
-        let doc: Document = mongodb::bson::to_raw_document_buf(tourn)
+        let mut doc: Document = mongodb::bson::to_raw_document_buf(tourn)
             .unwrap()
             .try_into()
             .unwrap();
-        match self
+        let _ = doc.insert("schema", SCHEMA_VERSION);
+        let mut query = Self::make_query(tourn.id);
+        let _ = query.insert(
+            "$or",
+            vec![
+                doc! { "version": { "$lt": tourn.version() as i64 } },
+                doc! { "version": { "$exists": false } },
+            ],
+        );
+        let updated = self
             .get_tourns()
             .update_one(
-                Self::make_query(tourn.id),
+                query,
                 UpdateModifications::Document(doc! {"$set": doc}),
-                UpdateOptions::builder()
-                    .upsert(true)
-                    .hint(Hint::Name(Self::TOURN_INDEX_NAME.to_string()))
-                    .build(),
+                None,
             )
-            .await
-        {
-            Ok(result) => result.matched_count != 0,
-            Err(_) => match self.get_tourns().insert_one(tourn, None).await {
-                Ok(_) => true,
-                Err(err) => {
+            .await;
+        let written = match updated {
+            Ok(result) if result.matched_count != 0 => Ok(()),
+            // Either the tournament has not been persisted yet or the stored copy is not older
+            _ => match self
+                .get_tourns()
+                .count_documents(Self::make_query(tourn.id), None)
+                .await
+            {
+                Ok(0) => self.get_tourn_docs().insert_one(doc, None).await.map(drop),
+                Ok(_) => {
                     tracing::event!(
                         Level::WARN,
-                        r#"Could not persist tournament with name "{}" and id "{}" due to error: {err}"#,
-                        tourn.tourn().name,
-                        tourn.tourn().id,
+                        r#"Skipped persisting stale version {} of tournament with id "{}""#,
+                        tourn.version(),
+                        tourn.id,
                     );
-                    false
+                    return false;
                 }
+                Err(err) => Err(err),
             },
+        };
+        match written {
+            Ok(()) => {
+                self.clear_ops(tourn.id).await;
+                true
+            }
+            Err(err) => {
+                tracing::event!(
+                    Level::WARN,
+                    r#"Could not persist tournament with name "{}" and id "{}" due to error: {err}"#,
+                    tourn.tourn().name,
+                    tourn.tourn().id,
+                );
+                false
+            }
         }
     }
 
-    pub async fn get_tourn_summaries(&self, including: Range<usize>) -> Vec<TournamentSummary> {
+    fn make_filter(filter: &TournamentFilter) -> Document {
+        // Only public tournaments are listed. Tournaments from before visibility was added don't
+        // have one, which `$nin` matches.
+        let unlisted = [Visibility::Unlisted, Visibility::Private].map(|v| to_bson(&v).unwrap());
+        let mut query = doc! { "tourn.settings.visibility": { "$nin": unlisted.to_vec() } };
+        if let Some(status) = filter.status {
+            let _ = query.insert("tourn.status", to_bson(&status).unwrap());
+        }
+        if let Some(format) = &filter.format {
+            let _ = query.insert("tourn.settings.format", format);
+        }
+        if let Some(id) = filter.organizer {
+            let _ = query.insert(
+                "log.owner.id",
+                Binary {
+                    bytes: id.as_bytes().to_vec(),
+                    subtype: BinarySubtype::Generic,
+                },
+            );
+        }
+        // The time of the last operation is compared against the bounds. Tournaments without any
+        // operations don't have a time, so they are left out. If every operation has been
+        // compacted, the last compacted operation is used.
+        let last_active = doc! { "$ifNull": [
+            { "$arrayElemAt": ["$log.ops.salt", -1] },
+            "$log.snapshot.anchor.salt",
+        ] };
+        let mut bounds = Vec::new();
+        if let Some(after) = filter.after {
+            bounds.push(doc! { "$gte": [last_active.clone(), to_bson(&after).unwrap()] });
+        }
+        if let Some(before) = filter.before {
+            bounds.push(doc! { "$lt": [last_active, to_bson(&before).unwrap()] });
+        }
+        if !bounds.is_empty() {
+            let _ = query.insert(
+                "$or",
+                vec![
+                    doc! { "log.ops.0": { "$exists": true } },
+                    doc! { "log.snapshot.anchor": { "$exists": true } },
+                ],
+            );
+            let _ = query.insert("$expr", doc! { "$and": bounds });
+        }
+        query
+    }
+
+    pub async fn get_tourn_summaries(
+        &self,
+        filter: &TournamentFilter,
+        including: Range<usize>,
+    ) -> (Vec<TournamentSummary>, usize) {
+        let query = Self::make_filter(filter);
+        let total = self
+            .get_tourns()
+            .count_documents(query.clone(), None)
+            .await
+            .unwrap_or_default() as usize;
         let Ok(cursor) = self
             .get_tourns()
             .find(
-                None,
-                FindOptions::builder().sort(doc! {"$natural":-1}).build(),
+                query,
+                FindOptions::builder()
+                    .sort(doc! {"$natural":-1})
+                    .skip(including.start as u64)
+                    .limit(including.len() as i64)
+                    .build(),
             )
             .await
         else {
-            return vec![];
+            return (vec![], total);
         };
 
-        cursor
-            .skip(including.start)
-            .take(including.count())
+        let summaries = cursor
             .filter_map(|u| async { u.ok().as_ref().map(TournamentSummary::from) })
             .collect()
-            .await
+            .await;
+        (summaries, total)
     }
 }
@@ -0,0 +1,129 @@
+use futures::StreamExt;
+use mongodb::{
+    bson::{doc, spec::BinarySubtype, Binary, Document},
+    Collection, Database,
+};
+use reqwest::header::CONTENT_TYPE;
+use squire_sdk::{
+    api::{Webhook, WebhookEvent, WebhookId},
+    model::identifiers::TournamentId,
+    server::{
+        fetch::{check_public_url, pinned_client},
+        webhooks::{sign_webhook_body, SIGNATURE_HEADER},
+    },
+};
+use tracing::Level;
+
+/// Stores the webhooks that organizers have registered and sends tournament events to them
+#[derive(Debug, Clone)]
+pub struct WebhookDb {
+    db_conn: Database,
+}
+
+impl WebhookDb {
+    const WEBHOOK_COLL_NAME: &'static str = "Webhooks";
+
+    pub fn new(db_conn: Database) -> Self {
+        Self { db_conn }
+    }
+
+    fn get_webhooks(&self) -> Collection<Webhook> {
+        self.db_conn.collection(Self::WEBHOOK_COLL_NAME)
+    }
+
+    fn make_query(tourn: TournamentId) -> Document {
+        doc! { "tourn": Binary {
+            bytes: tourn.as_bytes().to_vec(),
+            subtype: BinarySubtype::Generic,
+        }}
+    }
+
+    pub async fn list_webhooks(&self, tourn: TournamentId) -> Vec<Webhook> {
+        let Ok(cursor) = self
+            .get_webhooks()
+            .find(Self::make_query(tourn), None)
+            .await
+        else {
+            return Vec::new();
+        };
+        cursor.filter_map(|h| async { h.ok() }).collect().await
+    }
+
+    pub async fn persist_webhook(&self, hook: &Webhook) -> bool {
+        match self.get_webhooks().insert_one(hook, None).await {
+            Ok(_) => true,
+            Err(err) => {
+                tracing::event!(
+                    Level::WARN,
+                    r#"Could not save webhook for tournament "{}" due to error: {err}"#,
+                    hook.tourn,
+                );
+                false
+            }
+        }
+    }
+
+    pub async fn delete_webhook(&self, tourn: TournamentId, id: WebhookId) -> bool {
+        let mut query = Self::make_query(tourn);
+        let _ = query.insert(
+            "id",
+            Binary {
+                bytes: id.as_bytes().to_vec(),
+                subtype: BinarySubtype::Generic,
+            },
+        );
+        self.get_webhooks()
+            .delete_one(query, None)
+            .await
+            .is_ok_and(|result| result.deleted_count != 0)
+    }
+
+    /// POSTs each event to each of the webhooks, signed with the webhook's secret. The requests
+    /// are sent in the background so that slow or unreachable webhooks don't hold up persisting
+    /// the tournament. Each webhook's host is checked again before sending, since what it resolves
+    /// to can change after it was registered. Failed requests are logged but not retried.
+    pub fn dispatch(&self, hooks: Vec<Webhook>, events: Vec<WebhookEvent>) {
+        if hooks.is_empty() || events.is_empty() {
+            return;
+        }
+        _ = tokio::spawn(async move {
+            let bodies: Vec<_> = events
+                .iter()
+                .filter_map(|event| serde_json::to_vec(event).ok())
+                .collect();
+            for hook in &hooks {
+                let target = match check_public_url(&hook.url).await {
+                    Ok((url, addr)) => pinned_client(&url, addr).map(|client| (url, client)),
+                    Err(err) => Err(err),
+                };
+                let (url, client) = match target {
+                    Ok(target) => target,
+                    Err(err) => {
+                        tracing::event!(
+                            Level::WARN,
+                            r#"Not sending events to webhook "{}" due to error: {err:?}"#,
+                            hook.url,
+                        );
+                        continue;
+                    }
+                };
+                for body in &bodies {
+                    let mut req = client
+                        .post(url.clone())
+                        .header(CONTENT_TYPE, "application/json")
+                        .body(body.clone());
+                    if !hook.secret.is_empty() {
+                        req = req.header(SIGNATURE_HEADER, sign_webhook_body(&hook.secret, body));
+                    }
+                    if let Err(err) = req.send().await.and_then(|resp| resp.error_for_status()) {
+                        tracing::event!(
+                            Level::WARN,
+                            r#"Could not send event to webhook "{}" due to error: {err}"#,
+                            hook.url,
+                        );
+                    }
+                }
+            }
+        });
+    }
+}
@@ -1,8 +1,35 @@
 #![cfg(feature = "db-tests")]
 
-use squire_sdk::{api::*, server::state::ServerState, sync::TournamentManager};
+use std::time::Duration;
 
-use crate::state::{AppState, AppStateBuilder};
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use http::StatusCode;
+use mongodb::bson::{doc, to_bson, Document};
+use sha2::{Digest, Sha256};
+use squire_sdk::{
+    api::*,
+    model::{
+        identifiers::AdminId,
+        operations::{AdminOp, TournOp},
+        tournament::TournamentStatus,
+    },
+    server::{
+        session::{AnyUser, Session, SessionConvert, SquireSession},
+        state::ServerState,
+    },
+    sync::TournamentManager,
+};
+
+use crate::{
+    accounts::ActiveSession,
+    session::{self, CurrentSession},
+    state::{salt_and_hash, AppState, AppStateBuilder},
+};
+
+const PASSWORD: &str = "correct horse battery";
 
 async fn clear_database(state: AppState) {
     state.get_db().drop(None).await;
@@ -30,14 +57,20 @@ async fn tournament_pages() {
 
     // this way we can do direct comparisons
     assert_eq!(
-        state.get_tourn_summaries(5..15).await,
+        state
+            .get_tourn_summaries(&TournamentFilter::default(), 5..15)
+            .await
+            .0,
         tournament_list[5..15]
             .iter()
             .map(TournamentSummary::from)
             .collect::<Vec<_>>()
     );
     assert_eq!(
-        state.get_tourn_summaries(0..10).await,
+        state
+            .get_tourn_summaries(&TournamentFilter::default(), 0..10)
+            .await
+            .0,
         tournament_list[0..10]
             .iter()
             .map(TournamentSummary::from)
@@ -52,14 +85,20 @@ async fn tournament_pages() {
         .await;
 
     assert_eq!(
-        state.get_tourn_summaries(6..16).await,
+        state
+            .get_tourn_summaries(&TournamentFilter::default(), 6..16)
+            .await
+            .0,
         tournament_list[5..15]
             .iter()
             .map(TournamentSummary::from)
             .collect::<Vec<_>>()
     );
     assert_eq!(
-        state.get_tourn_summaries(1..11).await,
+        state
+            .get_tourn_summaries(&TournamentFilter::default(), 1..11)
+            .await
+            .0,
         tournament_list[0..10]
             .iter()
             .map(TournamentSummary::from)
@@ -104,3 +143,353 @@ async fn check_already_persisted() {
         .expect("Could not retrieve tournament from database");
     assert_eq!(manager, retrieved_tourn);
 }
+
+#[tokio::test]
+async fn stale_copies_are_not_persisted() {
+    let state = AppStateBuilder::new()
+        .database_name("SquireTesting_stale_copies")
+        .build()
+        .await;
+    clear_database(state.clone()).await;
+
+    let manager = TournamentManager::new(squire_tests::spoof_account(), squire_tests::get_seed());
+
+    assert!(state.persist_tourn(&manager).await);
+    // The stored copy has the same version, so it is not replaced
+    assert!(!state.persist_tourn(&manager).await);
+
+    let retrieved_tourn = state
+        .get_tourn(manager.id)
+        .await
+        .expect("Could not retrieve tournament from database");
+    assert_eq!(manager, retrieved_tourn);
+}
+
+#[tokio::test]
+async fn synced_ops_update_histories() {
+    let state = AppStateBuilder::new()
+        .database_name("SquireTesting_synced_histories")
+        .build()
+        .await;
+    clear_database(state.clone()).await;
+
+    let form = RegForm {
+        username: "Player".to_owned(),
+        display_name: "Player".to_owned(),
+        password: PASSWORD.to_owned(),
+        email: None,
+    };
+    let id = state.create_account(form).await.unwrap();
+    let player = state.get_account(id).await.unwrap();
+    let admin = squire_tests::spoof_account();
+    let admin_id: AdminId = admin.id.0.into();
+    let mut manager = TournamentManager::new(admin, squire_tests::get_seed());
+    _ = manager
+        .apply_op(TournOp::RegisterPlayer(player, None))
+        .unwrap();
+    _ = manager
+        .apply_op(TournOp::AdminOp(admin_id, AdminOp::Start))
+        .unwrap();
+    assert!(state.persist_tourn(&manager).await);
+    assert!(state.get_history(id, Some(id)).await.unwrap().is_empty());
+
+    // The operation is appended to the tournament's log rather than persisted in a full copy
+    let op = TournOp::AdminOp(admin_id, AdminOp::End);
+    state.apply_op(manager.id, op).await.unwrap();
+    tokio::time::sleep(Duration::from_secs(7)).await;
+
+    let history = state.get_history(id, Some(id)).await.unwrap();
+    assert_eq!(history.len(), 1);
+    assert_eq!(history[0].tourn.id, manager.id);
+    assert_eq!(history[0].tourn.status, TournamentStatus::Ended);
+}
+
+#[tokio::test]
+async fn old_pairing_settings_are_migrated() {
+    let state = AppStateBuilder::new()
+        .database_name("SquireTesting_old_pairing_settings")
+        .build()
+        .await;
+    clear_database(state.clone()).await;
+
+    let manager = TournamentManager::new(squire_tests::spoof_account(), squire_tests::get_seed());
+
+    // Write the tournament in the shape that it had before the settings refactor
+    let mut doc: Document = mongodb::bson::to_raw_document_buf(&manager)
+        .unwrap()
+        .try_into()
+        .unwrap();
+    let sys = doc
+        .get_document_mut("tourn")
+        .unwrap()
+        .get_document_mut("pairing_sys")
+        .unwrap();
+    let mut common = sys.remove("common").unwrap();
+    let common = common.as_document_mut().unwrap();
+    let _ = common.insert("match_size", 4);
+    for key in ["match_size", "repair_tolerance", "algorithm"] {
+        let _ = sys.insert(key, common.remove(key).unwrap());
+    }
+    let _ = state
+        .get_db()
+        .collection::<Document>("Tournaments")
+        .insert_one(doc, None)
+        .await
+        .unwrap();
+
+    let retrieved_tourn = state
+        .get_tourn(manager.id)
+        .await
+        .expect("Could not retrieve tournament from database");
+    assert_eq!(retrieved_tourn.pairing_sys.common.match_size, 4);
+}
+
+#[tokio::test]
+async fn password_changes_revoke_other_sessions() {
+    let state = AppStateBuilder::new()
+        .database_name("SquireTesting_password_revocation")
+        .build()
+        .await;
+    clear_database(state.clone()).await;
+
+    let form = RegForm {
+        username: "Revoker".to_owned(),
+        display_name: "Revoker".to_owned(),
+        password: PASSWORD.to_owned(),
+        email: None,
+    };
+    let id = state.create_account(form.clone()).await.unwrap();
+    let current = state.login(form.clone().into(), None).await.unwrap();
+    let other = state.login(form.into(), None).await.unwrap();
+    let api = state.create_api_token(id, "Bot".to_owned()).await;
+    assert_eq!(
+        state.get_session(api.token.clone()).await,
+        SquireSession::ApiToken(id)
+    );
+
+    let req = ChangePassword {
+        old_password: PASSWORD.to_owned(),
+        new_password: "battery staple horse".to_owned(),
+    };
+    assert_eq!(
+        state.change_password(id, current.clone(), req).await,
+        Ok(())
+    );
+
+    // Only the session that changed the password is still logged in
+    assert_eq!(
+        state.get_session(current.clone()).await,
+        SquireSession::Active(id)
+    );
+    assert_ne!(state.get_session(other).await, SquireSession::Active(id));
+    assert_ne!(
+        state.get_session(api.token).await,
+        SquireSession::ApiToken(id)
+    );
+    assert!(state.list_api_tokens(id).await.is_empty());
+
+    // Deleting the account logs out every session
+    assert!(state.delete_account(id).await);
+    assert_ne!(state.get_session(current).await, SquireSession::Active(id));
+}
+
+#[tokio::test]
+async fn legacy_password_hashes_are_upgraded() {
+    let name = "SquireTesting_legacy_passwords";
+    let state = AppStateBuilder::new().database_name(name).build().await;
+    clear_database(state.clone()).await;
+
+    // Write an account in the shape that it had before passwords were hashed with argon2
+    let account = squire_tests::spoof_account();
+    let cred = salt_and_hash(PASSWORD, &account.user_name);
+    let accounts = state.get_db().collection::<Document>("Accounts");
+    let _ = accounts
+        .insert_one(
+            doc! { "account": to_bson(&account).unwrap(), "cred": i64::from(cred) },
+            None,
+        )
+        .await
+        .unwrap();
+
+    // Accounts are loaded when the server starts
+    let state = AppStateBuilder::new().database_name(name).build().await;
+    let login = |password: &str| Credentials::Basic {
+        username: account.user_name.clone(),
+        password: password.to_owned(),
+    };
+    assert!(state.login(login("not the password"), None).await.is_err());
+    assert!(state.login(login(PASSWORD), None).await.is_ok());
+
+    // The password is rehashed and the old hash is dropped
+    let mut stored = None;
+    for _ in 0..50 {
+        let doc = accounts.find_one(None, None).await.unwrap().unwrap();
+        if !doc.contains_key("cred") {
+            stored = Some(doc);
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    let stored = stored.expect("The password was never rehashed");
+    assert!(stored.get_str("password").unwrap().starts_with("$argon2"));
+    assert!(state.login(login(PASSWORD), None).await.is_ok());
+    assert!(state.login(login("not the password"), None).await.is_err());
+}
+
+#[tokio::test]
+async fn api_token_endpoints() {
+    let name = "SquireTesting_api_tokens";
+    let state = AppStateBuilder::new().database_name(name).build().await;
+    clear_database(state.clone()).await;
+
+    let form = RegForm {
+        username: "Tokens".to_owned(),
+        display_name: "Tokens".to_owned(),
+        password: PASSWORD.to_owned(),
+        email: None,
+    };
+    let id = state.create_account(form).await.unwrap();
+    let token = state.create_session(id).await;
+    let owner = || Session(CurrentSession(token.clone(), id));
+    let req = CreateApiToken {
+        name: "Bot".to_owned(),
+    };
+    let Json(new) = session::create_api_token(State(state.clone()), owner(), Json(req)).await;
+    assert_eq!(new.info.name, "Bot");
+    let Json(listed) = session::list_api_tokens(State(state.clone()), owner()).await;
+    assert_eq!(listed, vec![new.info.clone()]);
+    assert_eq!(
+        state.get_session(new.token.clone()).await,
+        SquireSession::ApiToken(id)
+    );
+
+    // Only the hash of the token is stored, and tokens are looked up by their hash
+    let stored = state
+        .get_db()
+        .collection::<Document>("ApiTokens")
+        .find_one(None, None)
+        .await
+        .unwrap()
+        .unwrap();
+    let hash = hex::encode(Sha256::digest(new.token.0));
+    assert_eq!(stored.get_str("hash").unwrap(), hash);
+    assert!(!stored.contains_key("token"));
+    let mut hash_as_token = SessionToken::default();
+    hash_as_token
+        .0
+        .copy_from_slice(&Sha256::digest(new.token.0));
+    assert_eq!(
+        state.get_session(hash_as_token).await,
+        SquireSession::default()
+    );
+
+    // Tokens are loaded by their hash when the server restarts
+    let state = AppStateBuilder::new().database_name(name).build().await;
+    assert_eq!(
+        state.get_session(new.token.clone()).await,
+        SquireSession::ApiToken(id)
+    );
+
+    // Other users can not revoke the token
+    let other = Session(CurrentSession(
+        SessionToken::default(),
+        squire_tests::spoof_account().id,
+    ));
+    let path = Path(new.info.id);
+    let Json(revoked) = session::revoke_api_token(State(state.clone()), other, path).await;
+    assert!(!revoked);
+    let path = Path(new.info.id);
+    let Json(revoked) = session::revoke_api_token(State(state.clone()), owner(), path).await;
+    assert!(revoked);
+    let Json(listed) = session::list_api_tokens(State(state.clone()), owner()).await;
+    assert!(listed.is_empty());
+    assert_eq!(state.get_session(new.token).await, SquireSession::default());
+}
+
+#[tokio::test]
+async fn api_tokens_can_not_manage_credentials() {
+    let state = AppStateBuilder::new()
+        .database_name("SquireTesting_api_token_scope")
+        .build()
+        .await;
+    clear_database(state.clone()).await;
+
+    let form = RegForm {
+        username: "Scoped".to_owned(),
+        display_name: "Scoped".to_owned(),
+        password: PASSWORD.to_owned(),
+        email: None,
+    };
+    let id = state.create_account(form).await.unwrap();
+    let new = state.create_api_token(id, "Bot".to_owned()).await;
+    let sq_sess = state.get_session(new.token.clone()).await;
+    assert_eq!(sq_sess, SquireSession::ApiToken(id));
+
+    // The token acts as the user...
+    assert!(matches!(
+        ActiveSession::convert(new.token.clone(), sq_sess.clone()),
+        Ok(ActiveSession(acc)) if acc == id
+    ));
+    // ...but can not mint tokens, change the password, or manage the user's sessions
+    assert_eq!(
+        CurrentSession::convert(new.token.clone(), sq_sess.clone()).err(),
+        Some(StatusCode::UNAUTHORIZED)
+    );
+    assert_eq!(
+        AnyUser::convert(new.token.clone(), sq_sess).err(),
+        Some(StatusCode::UNAUTHORIZED)
+    );
+    assert_eq!(state.list_api_tokens(id).await.len(), 1);
+}
+
+#[tokio::test]
+async fn sessions_are_managed_per_device() {
+    let state = AppStateBuilder::new()
+        .database_name("SquireTesting_devices")
+        .build()
+        .await;
+    clear_database(state.clone()).await;
+
+    let form = |name: &str| RegForm {
+        username: name.to_owned(),
+        display_name: name.to_owned(),
+        password: PASSWORD.to_owned(),
+        email: None,
+    };
+    let id = state.create_account(form("Traveller")).await.unwrap();
+    let _ = state.create_account(form("Bystander")).await.unwrap();
+    let phone = state
+        .login(form("Traveller").into(), Some("10.0.0.1".to_owned()))
+        .await
+        .unwrap();
+    let laptop = state
+        .login(form("Traveller").into(), Some("10.0.0.2".to_owned()))
+        .await
+        .unwrap();
+    let bystander = state.login(form("Bystander").into(), None).await.unwrap();
+    assert!(state.name_session(phone.clone(), "Phone".to_owned()).await);
+
+    let sessions = state.list_sessions(phone.clone()).await;
+    assert_eq!(sessions.len(), 2);
+    let current = sessions.iter().find(|s| s.current).unwrap();
+    assert_eq!(current.device.as_deref(), Some("Phone"));
+    assert_eq!(current.ip.as_deref(), Some("10.0.0.1"));
+    let other = sessions.iter().find(|s| !s.current).unwrap().clone();
+    assert_eq!(other.device, None);
+    assert_eq!(other.ip.as_deref(), Some("10.0.0.2"));
+
+    // Users can only see and revoke their own sessions
+    assert_eq!(state.list_sessions(bystander.clone()).await.len(), 1);
+    assert!(!state.revoke_session(bystander, other.id).await);
+    assert_eq!(
+        state.get_session(laptop.clone()).await,
+        SquireSession::Active(id)
+    );
+
+    assert!(state.revoke_session(phone.clone(), other.id).await);
+    assert_ne!(state.get_session(laptop).await, SquireSession::Active(id));
+    assert!(!state.revoke_session(phone.clone(), other.id).await);
+    let sessions = state.list_sessions(phone).await;
+    assert_eq!(sessions.len(), 1);
+    assert!(sessions[0].current);
+}
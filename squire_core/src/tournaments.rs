@@ -27,7 +27,7 @@ pub async fn join_gathering(
     path: Path<TournamentId>,
     Query(SessionQuery { session }): Query<SessionQuery>,
 ) -> Response {
-    println!("Got alt join request...");
+    tracing::debug!("Got alt join request");
     match session.parse::<SessionToken>() {
         Ok(token) => match AnyUser::convert(token.clone(), state.0.get_session(token).await) {
             Ok(session) => {
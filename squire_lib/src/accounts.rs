@@ -1,16 +1,17 @@
 use std::{collections::HashMap, hash::Hash};
 
 use serde::{Deserialize, Serialize};
-use serde_with::{serde_as, Seq};
+use serde_with::serde_as;
 use uuid::Uuid;
 
 use crate::{
     admin::Admin,
+    boilerplate::ordered::SortedSeq,
     identifiers::SquireAccountId,
     tournament::{Tournament, TournamentSeed},
 };
 
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 /// The platforms that we officially support (plus a wildcard)
 pub enum Platform {
     /// The Cockatrice platform
@@ -41,6 +42,26 @@ pub enum SharingPermissions {
     Nothing,
 }
 
+impl SharingPermissions {
+    /// Calculates if the player's name can be shown to the public
+    pub fn shares_name(self) -> bool {
+        matches!(self, SharingPermissions::Everything)
+    }
+
+    /// Calculates if the contents of the player's decks can be shown to the public
+    pub fn shares_deck_list(self) -> bool {
+        matches!(
+            self,
+            SharingPermissions::Everything | SharingPermissions::OnlyDeckList
+        )
+    }
+
+    /// Calculates if the names of the player's decks can be shown to the public
+    pub fn shares_deck_name(self) -> bool {
+        !matches!(self, SharingPermissions::Nothing)
+    }
+}
+
 #[serde_as]
 #[derive(Serialize, Deserialize, Debug, Clone, Eq)]
 /// The core model for an account for a user
@@ -50,7 +71,7 @@ pub struct SquireAccount {
     /// The name that's displayed on the user's account
     pub display_name: String,
     /// The name of the user on MTG Arena
-    #[serde_as(as = "Seq<(_, _)>")]
+    #[serde_as(as = "SortedSeq")]
     pub gamer_tags: HashMap<Platform, String>,
     /// The user's Id
     pub id: SquireAccountId,
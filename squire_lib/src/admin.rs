@@ -2,7 +2,8 @@ use serde::{Deserialize, Serialize};
 
 use crate::{
     accounts::SquireAccount,
-    identifiers::{AdminId, JudgeId},
+    identifiers::{AdminId, JudgeId, ScorekeeperId},
+    operations::{AdminOp, JudgeOp},
 };
 
 #[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -10,6 +11,8 @@ use crate::{
 pub enum TournOfficialId {
     /// A judge's id
     Judge(JudgeId),
+    /// A scorekeeper's id
+    Scorekeeper(ScorekeeperId),
     /// An admin's id
     Admin(AdminId),
 }
@@ -23,6 +26,16 @@ pub struct Judge {
     pub id: JudgeId,
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+/// The core model for an account for a tournament scorekeeper. Scorekeepers sit between judges
+/// and admins, but they can only enter and confirm results and assign tables.
+pub struct Scorekeeper {
+    /// The user's name
+    pub name: String,
+    /// The user's Id
+    pub id: ScorekeeperId,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
 /// The core model for an account for a tournament admin
 pub struct Admin {
@@ -42,6 +55,33 @@ impl Judge {
     }
 }
 
+impl Scorekeeper {
+    /// Creates a new scorekeeper object from a `SquireAccount`
+    pub fn new(account: SquireAccount) -> Self {
+        Self {
+            name: account.get_user_name(),
+            id: account.id.0.into(),
+        }
+    }
+
+    /// Calculates if a scorekeeper can perform the given judge operation. Only result entry and
+    /// confirmation are allowed.
+    pub fn can_judge(op: &JudgeOp) -> bool {
+        matches!(
+            op,
+            JudgeOp::AdminRecordResult(_, _)
+                | JudgeOp::AdminConfirmResult(_, _)
+                | JudgeOp::ConfirmRound(_)
+        )
+    }
+
+    /// Calculates if a scorekeeper can perform the given admin operation. Only confirming all
+    /// rounds and assigning tables are allowed.
+    pub fn can_admin(op: &AdminOp) -> bool {
+        matches!(op, AdminOp::ConfirmAllRounds | AdminOp::AssignTable(_, _))
+    }
+}
+
 impl Admin {
     /// Creates a new admin object from a `SquireAccount`
     pub fn new(account: SquireAccount) -> Self {
@@ -67,6 +107,12 @@ impl From<JudgeId> for TournOfficialId {
     }
 }
 
+impl From<ScorekeeperId> for TournOfficialId {
+    fn from(id: ScorekeeperId) -> Self {
+        Self::Scorekeeper(id)
+    }
+}
+
 impl From<AdminId> for TournOfficialId {
     fn from(id: AdminId) -> Self {
         Self::Admin(id)
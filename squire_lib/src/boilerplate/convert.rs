@@ -5,8 +5,8 @@ use crate::{
     operations::AdminOp,
     settings::{
         CommonPairingSetting, CommonScoringSetting, FluidPairingSetting, GeneralSetting,
-        PairingSetting, PairingStyleSetting, ScoringSetting, ScoringStyleSetting,
-        StandardScoringSetting, SwissPairingSetting, TournamentSetting,
+        MultiplayerScoringSetting, PairingSetting, PairingStyleSetting, ScoringSetting,
+        ScoringStyleSetting, StandardScoringSetting, SwissPairingSetting, TournamentSetting,
     },
 };
 
@@ -122,6 +122,12 @@ impl From<ScoringSetting> for TournamentSetting {
     }
 }
 
+impl From<MultiplayerScoringSetting> for AdminOp {
+    fn from(setting: MultiplayerScoringSetting) -> Self {
+        AdminOp::UpdateTournSetting(setting.into())
+    }
+}
+
 impl From<CommonScoringSetting> for TournamentSetting {
     fn from(setting: CommonScoringSetting) -> Self {
         Self::ScoringSetting(setting.into())
@@ -140,6 +146,12 @@ impl From<StandardScoringSetting> for TournamentSetting {
     }
 }
 
+impl From<MultiplayerScoringSetting> for TournamentSetting {
+    fn from(setting: MultiplayerScoringSetting) -> Self {
+        Self::ScoringSetting(ScoringSetting::Style(setting.into()))
+    }
+}
+
 /* --------- Convert sub-settings to a `PairingSetting` --------- */
 
 impl From<CommonPairingSetting> for PairingSetting {
@@ -202,6 +214,12 @@ impl From<StandardScoringSetting> for ScoringStyleSetting {
     }
 }
 
+impl From<MultiplayerScoringSetting> for ScoringStyleSetting {
+    fn from(other: MultiplayerScoringSetting) -> Self {
+        Self::Multiplayer(other)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::settings::{StandardScoringSetting, TournamentSetting};
@@ -232,6 +250,7 @@ mod tests {
         let _ = subsetting_to_tourn_setting(IncludeGwp);
         let _ = subsetting_to_tourn_setting(IncludeOppMwp);
         let _ = subsetting_to_tourn_setting(IncludeOppGwp);
+        let _ = subsetting_to_tourn_setting(IncludeDroppedOpponents);
     }
 
     #[test]
@@ -7,10 +7,15 @@ use std::time::Duration;
 use crate::{
     pairings::PairingAlgorithm,
     r64,
+    rounds::TableAssignment,
+    schedule::Schedule,
+    scoring::StageScoring,
     settings::{
-        CommonScoringSettingsTree, FluidPairingSettingsTree, GeneralSettingsTree,
-        PairingCommonSettingsTree, PairingSettingsTree, PairingStyleSettingsTree,
-        ScoringStyleSettingsTree, StandardScoringSettingsTree, SwissPairingSettingsTree,
+        CommonScoringSettingsTree, DecklistVisibility, FluidPairingSettingsTree,
+        GeneralSettingsTree, MatchStructure, MultiplayerScoringSettingsTree,
+        PairingCommonSettingsTree, PairingSettingsTree, PairingStyleSettingsTree, PermissionMatrix,
+        PublicNameStyle, RegistrationWindow, ScoringStyleSettingsTree, StandardScoringSettingsTree,
+        SwissPairingSettingsTree, Visibility,
     },
     tournament::TournamentPreset,
 };
@@ -28,6 +33,17 @@ impl Default for GeneralSettingsTree {
             require_check_in: false,
             require_deck_reg: false,
             round_length: Duration::from_secs(3000),
+            player_cap: 0,
+            permissions: PermissionMatrix::default(),
+            decklist_visibility: DecklistVisibility::default(),
+            public_names: PublicNameStyle::default(),
+            match_structure: MatchStructure::BestOf(3),
+            auto_certify: None,
+            tardiness: None,
+            schedule: Schedule::default(),
+            visibility: Visibility::default(),
+            registration: RegistrationWindow::default(),
+            prizes: None,
         }
     }
 }
@@ -38,13 +54,18 @@ impl Default for PairingCommonSettingsTree {
             match_size: 2,
             repair_tolerance: 0,
             algorithm: PairingAlgorithm::Branching,
+            table_assignment: TableAssignment::Sequential,
+            allow_repeat_byes: true,
         }
     }
 }
 
 impl Default for SwissPairingSettingsTree {
     fn default() -> Self {
-        Self { do_checkins: false }
+        Self {
+            do_checkins: false,
+            pair_within_pods: false,
+        }
     }
 }
 
@@ -56,7 +77,9 @@ impl Default for FluidPairingSettingsTree {
 
 impl Default for CommonScoringSettingsTree {
     fn default() -> Self {
-        Self {}
+        Self {
+            stage_scoring: StageScoring::CarryOver,
+        }
     }
 }
 
@@ -66,6 +89,20 @@ impl Default for ScoringStyleSettingsTree {
     }
 }
 
+impl Default for MultiplayerScoringSettingsTree {
+    fn default() -> Self {
+        Self {
+            first_place_points: r64::from_integer(5),
+            second_place_points: r64::from_integer(3),
+            third_place_points: r64::from_integer(2),
+            fourth_place_points: r64::from_integer(1),
+            draw_points: r64::from_integer(1),
+            bye_points: r64::from_integer(5),
+            include_byes: true,
+        }
+    }
+}
+
 impl Default for StandardScoringSettingsTree {
     fn default() -> Self {
         Self {
@@ -83,6 +120,7 @@ impl Default for StandardScoringSettingsTree {
             include_gwp: true,
             include_opp_mwp: true,
             include_opp_gwp: true,
+            include_dropped_opponents: true,
         }
     }
 }
@@ -1,9 +1,12 @@
 use std::fmt::{self, Display, Formatter};
 
-use crate::settings::{
-    CommonPairingSetting, CommonScoringSetting, FluidPairingSetting, GeneralSetting,
-    PairingSetting, PairingStyleSetting, ScoringSetting, ScoringStyleSetting,
-    StandardScoringSetting, SwissPairingSetting, TournamentSetting,
+use crate::{
+    prizes::PrizeDistribution,
+    settings::{
+        CommonPairingSetting, CommonScoringSetting, FluidPairingSetting, GeneralSetting,
+        MultiplayerScoringSetting, PairingSetting, PairingStyleSetting, ScoringSetting,
+        ScoringStyleSetting, StandardScoringSetting, SwissPairingSetting, TournamentSetting,
+    },
 };
 
 impl Display for TournamentSetting {
@@ -51,6 +54,73 @@ impl Display for GeneralSetting {
             RoundLength(dur) => {
                 write!(f, "Round Length: {} sec", dur.as_secs())
             }
+            PlayerCap(0) => {
+                write!(f, "Player Cap: none")
+            }
+            PlayerCap(s) => {
+                write!(f, "Player Cap: {s}")
+            }
+            Permissions(perms) => {
+                write!(
+                    f,
+                    "Permissions: {} for judges, {} for players",
+                    perms.judge.len(),
+                    perms.player.len()
+                )
+            }
+            DecklistVisibility(vis) => {
+                write!(f, "Decklists Visible: {vis:?}")
+            }
+            PublicNames(style) => {
+                write!(f, "Public Names: {style:?}")
+            }
+            MatchStructure(structure) => {
+                write!(f, "Match Structure: {structure}")
+            }
+            AutoCertify(None) => {
+                write!(f, "Auto-certify: off")
+            }
+            AutoCertify(Some(wait)) => {
+                write!(f, "Auto-certify: {} sec", wait.as_secs())
+            }
+            Tardiness(None) => {
+                write!(f, "Tardiness: off")
+            }
+            Tardiness(Some(policy)) => {
+                write!(
+                    f,
+                    "Tardiness: {} after {} sec",
+                    policy.penalty,
+                    policy.grace.as_secs()
+                )
+            }
+            Schedule(schedule) => match (schedule.start, schedule.day_two) {
+                (None, _) => write!(f, "Schedule: none"),
+                (Some(start), None) => write!(f, "Schedule: starts {start}"),
+                (Some(start), Some(day_two)) => write!(
+                    f,
+                    "Schedule: starts {start}, day two starts {} with round {}",
+                    day_two.start, day_two.first_round
+                ),
+            },
+            Visibility(vis) => {
+                write!(f, "Visibility: {vis:?}")
+            }
+            RegistrationWindow(window) => match (window.opens, window.closes) {
+                (None, None) => write!(f, "Registration Window: none"),
+                (Some(opens), None) => write!(f, "Registration Window: opens {opens}"),
+                (None, Some(closes)) => write!(f, "Registration Window: closes {closes}"),
+                (Some(opens), Some(closes)) => {
+                    write!(f, "Registration Window: {opens} to {closes}")
+                }
+            },
+            Prizes(None) => write!(f, "Prizes: none"),
+            Prizes(Some(prizes)) => match &prizes.distribution {
+                PrizeDistribution::ByPlacement(shares) => {
+                    write!(f, "Prizes: {} across {} places", prizes.pool, shares.len())
+                }
+                PrizeDistribution::ByRecord(_) => write!(f, "Prizes: {} by record", prizes.pool),
+            },
         }
     }
 }
@@ -86,7 +156,10 @@ impl Display for ScoringSetting {
 
 impl Display for CommonScoringSetting {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        write!(f, "CommonScoring Setting")
+        use CommonScoringSetting::*;
+        match self {
+            StageScoring(scoring) => write!(f, "Stage Scoring: {scoring}"),
+        }
     }
 }
 
@@ -95,6 +168,7 @@ impl Display for ScoringStyleSetting {
         use ScoringStyleSetting::*;
         match self {
             Standard(s) => write!(f, "{s}"),
+            Multiplayer(s) => write!(f, "{s}"),
         }
     }
 }
@@ -106,6 +180,10 @@ impl Display for CommonPairingSetting {
             MatchSize(size) => write!(f, "Match Size: {size}"),
             RepairTolerance(tol) => write!(f, "Repair Tolerance: {tol}"),
             Algorithm(alg) => write!(f, "Algorithm: {alg}"),
+            TableAssignment(strat) => write!(f, "Table Assignment: {strat}"),
+            AllowRepeatByes(allow) => {
+                write!(f, "Repeat Byes?: {}", if *allow { "yes" } else { "no" })
+            }
         }
     }
 }
@@ -115,6 +193,7 @@ impl Display for SwissPairingSetting {
         use SwissPairingSetting::*;
         match self {
             DoCheckIns(s) => write!(f, "Check Ins?: {s}"),
+            PairWithinPods(s) => write!(f, "Pair Within Pods?: {s}"),
         }
     }
 }
@@ -144,6 +223,24 @@ impl Display for StandardScoringSetting {
             IncludeGwp(s) => write!(f, "GWP?: {}", if *s { "yes" } else { "no" }),
             IncludeOppMwp(s) => write!(f, "Opp MWP?: {}", if *s { "yes" } else { "no" }),
             IncludeOppGwp(s) => write!(f, "Opp GWP?: {}", if *s { "yes" } else { "no" }),
+            IncludeDroppedOpponents(s) => {
+                write!(f, "Dropped Opps?: {}", if *s { "yes" } else { "no" })
+            }
+        }
+    }
+}
+
+impl Display for MultiplayerScoringSetting {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        use MultiplayerScoringSetting::*;
+        match self {
+            FirstPlacePoints(s) => write!(f, "First Place: {s}"),
+            SecondPlacePoints(s) => write!(f, "Second Place: {s}"),
+            ThirdPlacePoints(s) => write!(f, "Third Place: {s}"),
+            FourthPlacePoints(s) => write!(f, "Fourth Place: {s}"),
+            DrawPoints(s) => write!(f, "Draw: {s}"),
+            ByePoints(s) => write!(f, "Bye Win: {s}"),
+            IncludeByes(s) => write!(f, "Byes?: {}", if *s { "yes" } else { "no" }),
         }
     }
 }
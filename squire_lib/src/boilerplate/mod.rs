@@ -1,6 +1,7 @@
 //! This modules contains submodules of common types of boilerplate code, such as implementations
-//! of `From<>` and `Default`.
+//! of `From<>` and `Default`, and serialization adapters.
 
 mod convert;
 mod default;
 mod display;
+pub(crate) mod ordered;
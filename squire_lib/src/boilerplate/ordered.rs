@@ -0,0 +1,78 @@
+//! Serialization adapters for hash-based collections. Hash maps and sets are iterated in an order
+//! that changes every time they are deserialized, so these adapters serialize them in sorted
+//! order. This way, equal tournaments always serialize to the same bytes, which lets their
+//! serialized forms be hashed and signed.
+
+use std::{
+    collections::{HashMap, HashSet},
+    hash::{BuildHasher, Hash},
+    marker::PhantomData,
+};
+
+use itertools::Itertools;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_with::{ser::SerializeAsWrap, DeserializeAs, Same, Seq, SerializeAs};
+
+/// Serializes a `HashSet` as a sequence of its elements in sorted order
+pub(crate) struct SortedSet;
+
+/// Serializes a `HashMap` as a sequence of key-value pairs, sorted by key, like
+/// `serde_with::Seq<(_, _)>` does. The values are serialized using `V`.
+pub(crate) struct SortedSeq<V = Same>(PhantomData<V>);
+
+impl<T, H> SerializeAs<HashSet<T, H>> for SortedSet
+where
+    T: Serialize + Ord,
+{
+    fn serialize_as<S>(source: &HashSet<T, H>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_seq(source.iter().sorted())
+    }
+}
+
+impl<'de, T, H> DeserializeAs<'de, HashSet<T, H>> for SortedSet
+where
+    T: Deserialize<'de> + Eq + Hash,
+    H: BuildHasher + Default,
+{
+    fn deserialize_as<D>(deserializer: D) -> Result<HashSet<T, H>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        HashSet::deserialize(deserializer)
+    }
+}
+
+impl<K, V, VAs, H> SerializeAs<HashMap<K, V, H>> for SortedSeq<VAs>
+where
+    K: Serialize + Ord,
+    VAs: SerializeAs<V>,
+{
+    fn serialize_as<S>(source: &HashMap<K, V, H>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_seq(
+            source
+                .iter()
+                .sorted_by(|(a, _), (b, _)| a.cmp(b))
+                .map(|(k, v)| (k, SerializeAsWrap::<V, VAs>::new(v))),
+        )
+    }
+}
+
+impl<'de, K, V, VAs, H> DeserializeAs<'de, HashMap<K, V, H>> for SortedSeq<VAs>
+where
+    K: Deserialize<'de> + Eq + Hash,
+    VAs: DeserializeAs<'de, V>,
+    H: BuildHasher + Default,
+{
+    fn deserialize_as<D>(deserializer: D) -> Result<HashMap<K, V, H>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        <Seq<(Same, VAs)> as DeserializeAs<'de, HashMap<K, V, H>>>::deserialize_as(deserializer)
+    }
+}
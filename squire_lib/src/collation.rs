@@ -0,0 +1,90 @@
+use std::cmp::Ordering;
+
+#[cfg(feature = "collation")]
+use icu_collator::{Collator, CollatorOptions, Strength};
+
+/// Compares player names the way that people expect them to be listed.
+///
+/// With the `collation` feature, names are compared using the Unicode Collation Algorithm (via
+/// ICU), so accented and non-Latin names sort alongside their unaccented counterparts (e.g.
+/// "Émile" comes between "Edgar" and "Felix") and the order can follow the rules of a specific
+/// locale. Without it, names are compared ignoring case, which is only correct for ASCII names.
+///
+/// Names that collate as equal are then compared byte-wise, so the order is always total.
+pub struct NameCollator {
+    #[cfg(feature = "collation")]
+    collator: Collator,
+}
+
+impl NameCollator {
+    /// Creates a collator that uses the root collation order, which suits most languages
+    pub fn new() -> Self {
+        #[cfg(feature = "collation")]
+        {
+            Self::with_locale(&icu_locid::Locale::UND)
+        }
+        #[cfg(not(feature = "collation"))]
+        {
+            Self {}
+        }
+    }
+
+    /// Creates a collator for the given locale (e.g. "sv" or "de-AT"). The root collation order is
+    /// used if the locale is unknown or can not be parsed.
+    pub fn for_locale(locale: &str) -> Self {
+        #[cfg(feature = "collation")]
+        {
+            match locale.parse::<icu_locid::Locale>() {
+                Ok(locale) => Self::with_locale(&locale),
+                Err(_) => Self::new(),
+            }
+        }
+        #[cfg(not(feature = "collation"))]
+        {
+            _ = locale;
+            Self::new()
+        }
+    }
+
+    #[cfg(feature = "collation")]
+    fn with_locale(locale: &icu_locid::Locale) -> Self {
+        let mut options = CollatorOptions::new();
+        options.strength = Some(Strength::Tertiary);
+        let collator = Collator::try_new(&locale.into(), options)
+            .or_else(|_| Collator::try_new(&Default::default(), options))
+            .expect("The root collation data is compiled in");
+        Self { collator }
+    }
+
+    /// Compares two names
+    pub fn compare(&self, a: &str, b: &str) -> Ordering {
+        #[cfg(feature = "collation")]
+        let digest = self.collator.compare(a, b);
+        #[cfg(not(feature = "collation"))]
+        let digest = a
+            .chars()
+            .flat_map(char::to_lowercase)
+            .cmp(b.chars().flat_map(char::to_lowercase));
+        digest.then_with(|| a.cmp(b))
+    }
+
+    /// Sorts the given items by name
+    pub fn sort_by_name<T, F>(&self, items: &mut [T], name: F)
+    where
+        F: Fn(&T) -> &str,
+    {
+        items.sort_by(|a, b| self.compare(name(a), name(b)))
+    }
+}
+
+impl Default for NameCollator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::fmt::Debug for NameCollator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("NameCollator").finish_non_exhaustive()
+    }
+}
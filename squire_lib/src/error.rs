@@ -58,6 +58,67 @@ pub enum TournamentError {
     TimeOverflow,
     /// The given name cannot be used as a tournament name
     BadTournamentName,
+    /// The specified player is not an alternate
+    PlayerNotAlternate,
+    /// A player can only be substituted by an alternate before they have completed a round
+    InvalidSubstitution,
+    /// Alternates can only be promoted into the main field before the first round is paired
+    AlternatesClosed,
+    /// The specified player is not on the waitlist
+    PlayerNotWaitlisted,
+    /// The table is already being used by an active round
+    TableInUse,
+    /// The specified round is not a feature match
+    NotFeatureMatch,
+    /// Point values can not be negative and wins must be worth more than zero points
+    InvalidPointValue,
+    /// Pairing constraints must contain at least two distinct players
+    InvalidPairingConstraint,
+    /// Players can only be merged if they are different players that have not shared a round
+    InvalidMerge,
+    /// Announcements must contain some text
+    EmptyAnnouncement,
+    /// The specified player has not been dropped
+    PlayerNotDropped,
+    /// Checkpoints must have a name
+    EmptyCheckpointName,
+    /// The tournament's permissions do not allow the operation to be performed by its submitter
+    PermissionDenied,
+    /// A deck check is already underway for the round
+    DeckCheckInProgress,
+    /// There is no deck check underway for the round
+    NoDeckCheck,
+    /// Draft pods need to seat at least one player
+    InvalidPodSize,
+    /// The placement was not between first and last place of the round
+    InvalidPlacement,
+    /// The result has more game wins than the match structure allows
+    TooManyWins,
+    /// A best-of match structure needs at least one game
+    InvalidMatchStructure,
+    /// The round can not be certified automatically yet, or the tournament does not certify rounds
+    /// automatically
+    AutoCertifyNotDue,
+    /// The player has checked in at their table, their grace period has not passed yet, or the
+    /// tournament does not have a tardiness policy
+    PlayerNotTardy,
+    /// Format names can not be blank or too long
+    InvalidFormat,
+    /// Scheduled rounds must be numbered from 1 and listed once, and day two can not start with the
+    /// first round or before the tournament starts
+    InvalidSchedule,
+    /// Venue zones need a name and at least one table, and can not share tables
+    InvalidVenue,
+    /// Registration can not close before it opens
+    InvalidRegistrationWindow,
+    /// Prize structures must pay out something, and record prizes need distinct point thresholds
+    InvalidPrizeStructure,
+    /// Flights need a name
+    InvalidFlightName,
+    /// Decks can have at most `MAX_DECK_SIZE` cards
+    DeckTooLarge,
+    /// The invite has already been used or revoked
+    InviteRevoked,
 }
 
 impl fmt::Display for TournamentError {
@@ -89,6 +150,35 @@ impl fmt::Display for TournamentError {
             MaxDecksReached => "MaxDecksReached",
             TimeOverflow => "TimeOverflow",
             BadTournamentName => "BadTournamentName",
+            PlayerNotAlternate => "PlayerNotAlternate",
+            InvalidSubstitution => "InvalidSubstitution",
+            AlternatesClosed => "AlternatesClosed",
+            PlayerNotWaitlisted => "PlayerNotWaitlisted",
+            TableInUse => "TableInUse",
+            NotFeatureMatch => "NotFeatureMatch",
+            InvalidPointValue => "InvalidPointValue",
+            InvalidPairingConstraint => "InvalidPairingConstraint",
+            InvalidMerge => "InvalidMerge",
+            EmptyAnnouncement => "EmptyAnnouncement",
+            PlayerNotDropped => "PlayerNotDropped",
+            EmptyCheckpointName => "EmptyCheckpointName",
+            PermissionDenied => "PermissionDenied",
+            DeckCheckInProgress => "DeckCheckInProgress",
+            NoDeckCheck => "NoDeckCheck",
+            InvalidPodSize => "InvalidPodSize",
+            InvalidPlacement => "InvalidPlacement",
+            TooManyWins => "TooManyWins",
+            InvalidMatchStructure => "InvalidMatchStructure",
+            AutoCertifyNotDue => "AutoCertifyNotDue",
+            PlayerNotTardy => "PlayerNotTardy",
+            InvalidFormat => "InvalidFormat",
+            InvalidSchedule => "InvalidSchedule",
+            InvalidVenue => "InvalidVenue",
+            InvalidRegistrationWindow => "InvalidRegistrationWindow",
+            InvalidPrizeStructure => "InvalidPrizeStructure",
+            InvalidFlightName => "InvalidFlightName",
+            DeckTooLarge => "DeckTooLarge",
+            InviteRevoked => "InviteRevoked",
         };
         write!(f, "{s}")
     }
@@ -0,0 +1,145 @@
+use serde::{Deserialize, Serialize};
+
+use crate::identifiers::PlayerId;
+
+/// The secondary flights of a tournament, such as a consolation flight for players that were
+/// eliminated from the main event. Each flight is paired on its own and has its own standings,
+/// which only count the rounds that were played in the flight. Players that are not in a flight
+/// play in the main event.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq)]
+pub struct Flights {
+    /// The flights, in the order that they were created
+    pub flights: Vec<Flight>,
+}
+
+/// A secondary flight of a tournament
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Flight {
+    /// The flight's name (e.g. "Consolation")
+    pub name: String,
+    /// The players in the flight, in the order that they joined it
+    pub players: Vec<PlayerId>,
+}
+
+impl Flights {
+    /// Calculates if the tournament does not have any flights
+    pub fn is_empty(&self) -> bool {
+        self.flights.is_empty()
+    }
+
+    /// Returns the (one-indexed) flight that the player is in, if they are not in the main event
+    pub fn flight_of(&self, id: &PlayerId) -> Option<usize> {
+        self.flights
+            .iter()
+            .position(|f| f.players.contains(id))
+            .map(|f| f + 1)
+    }
+
+    /// Returns the (one-indexed) flight that all of the given players are in, if they share one
+    pub fn shared_flight(&self, plyrs: &[PlayerId]) -> Option<usize> {
+        let (first, rest) = plyrs.split_first()?;
+        let flight = self.flight_of(first)?;
+        rest.iter()
+            .all(|p| self.flights[flight - 1].players.contains(p))
+            .then_some(flight)
+    }
+
+    /// Returns the (one-indexed) flight with the given name. Names are not case sensitive.
+    pub fn find(&self, name: &str) -> Option<usize> {
+        self.flights
+            .iter()
+            .position(|f| f.name.eq_ignore_ascii_case(name))
+            .map(|f| f + 1)
+    }
+
+    /// Returns the (one-indexed) flight
+    pub fn get(&self, flight: usize) -> Option<&Flight> {
+        self.flights.get(flight.checked_sub(1)?)
+    }
+
+    /// Moves the players into the named flight, which is created if it does not exist yet. If no
+    /// flight is given, the players are moved back into the main event.
+    pub(crate) fn move_players(&mut self, plyrs: &[PlayerId], flight: Option<&str>) {
+        for f in self.flights.iter_mut() {
+            f.players.retain(|p| !plyrs.contains(p));
+        }
+        let Some(name) = flight else {
+            return;
+        };
+        let index = match self.find(name) {
+            Some(flight) => flight - 1,
+            None => {
+                self.flights.push(Flight {
+                    name: name.to_owned(),
+                    players: Vec::new(),
+                });
+                self.flights.len() - 1
+            }
+        };
+        self.flights[index].players.extend_from_slice(plyrs);
+    }
+
+    /// Gives a player a new id, i.e. when a guest is linked to an account. If the new id is
+    /// already in a flight (i.e. when merging duplicate players), the old id is removed instead.
+    pub(crate) fn swap_player_ids(&mut self, old: PlayerId, new: PlayerId) {
+        if self.flight_of(&new).is_some() {
+            self.move_players(&[old], None);
+        } else {
+            self.flights
+                .iter_mut()
+                .flat_map(|f| f.players.iter_mut())
+                .filter(|p| **p == old)
+                .for_each(|p| *p = new);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use uuid::Uuid;
+
+    use super::Flights;
+    use crate::identifiers::PlayerId;
+
+    #[test]
+    fn players_move_between_flights() {
+        let plyrs: Vec<_> = (0..4).map(|_| PlayerId::new(Uuid::new_v4())).collect();
+        let mut flights = Flights::default();
+        flights.move_players(&plyrs[..2], Some("Consolation"));
+        assert_eq!(flights.flight_of(&plyrs[0]), Some(1));
+        assert_eq!(flights.flight_of(&plyrs[2]), None);
+        assert_eq!(flights.shared_flight(&plyrs[..2]), Some(1));
+        assert_eq!(flights.shared_flight(&plyrs[1..3]), None);
+
+        // Flights are found by name, regardless of case
+        flights.move_players(&plyrs[2..], Some("consolation"));
+        assert_eq!(flights.flights.len(), 1);
+        assert_eq!(flights.get(1).unwrap().players, plyrs);
+
+        flights.move_players(&plyrs[..1], Some("Last chance"));
+        assert_eq!(flights.flight_of(&plyrs[0]), Some(2));
+        flights.move_players(&plyrs[..2], None);
+        assert_eq!(flights.flight_of(&plyrs[0]), None);
+        assert_eq!(flights.flight_of(&plyrs[1]), None);
+        assert_eq!(flights.get(1).unwrap().players, plyrs[2..]);
+    }
+
+    #[test]
+    fn merged_players_are_in_one_flight() {
+        let plyrs: Vec<_> = (0..3).map(|_| PlayerId::new(Uuid::new_v4())).collect();
+        let mut flights = Flights::default();
+        flights.move_players(&plyrs[..1], Some("Consolation"));
+        flights.move_players(&plyrs[1..], Some("Last chance"));
+
+        // The player that is kept stays in their own flight
+        flights.swap_player_ids(plyrs[1], plyrs[0]);
+        assert_eq!(flights.flight_of(&plyrs[0]), Some(1));
+        assert_eq!(flights.flight_of(&plyrs[1]), None);
+        assert_eq!(flights.get(2).unwrap().players, plyrs[2..]);
+
+        // A player that isn't in a flight takes the old id's place
+        let new = PlayerId::new(Uuid::new_v4());
+        flights.swap_player_ids(plyrs[2], new);
+        assert_eq!(flights.get(2).unwrap().players, vec![new]);
+    }
+}
@@ -0,0 +1,243 @@
+use std::{fmt, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::TournamentError;
+
+/// The longest name that a custom format can have
+pub const MAX_FORMAT_NAME_LENGTH: usize = 64;
+
+/// The format that a tournament is played in. Formats are stored and sent as their names, and
+/// known formats always use the same name regardless of how they were written (e.g. "edh" and
+/// "Commander" are both [`Constructed::Commander`]).
+#[derive(Serialize, Deserialize, Debug, Hash, Clone, PartialEq, Eq)]
+#[serde(try_from = "String", into = "String")]
+pub enum Format {
+    /// A format where players bring the decks that they play
+    Constructed(Constructed),
+    /// A format where players build their decks at the tournament
+    Limited(Limited),
+    /// Any other format, named by the organizer
+    Custom(String),
+}
+
+/// The constructed formats that are known to Squire
+#[derive(Serialize, Deserialize, Debug, Hash, Clone, Copy, PartialEq, Eq)]
+pub enum Constructed {
+    /// The Standard format
+    Standard,
+    /// The Pioneer format
+    Pioneer,
+    /// The Modern format
+    Modern,
+    /// The Legacy format
+    Legacy,
+    /// The Vintage format
+    Vintage,
+    /// The Pauper format
+    Pauper,
+    /// The Commander format, also known as EDH
+    Commander,
+    /// The Oathbreaker format
+    Oathbreaker,
+    /// The Premodern format
+    Premodern,
+}
+
+/// The limited formats that are known to Squire
+#[derive(Serialize, Deserialize, Debug, Hash, Clone, Copy, PartialEq, Eq)]
+pub enum Limited {
+    /// Players draft their decks from booster packs
+    Draft,
+    /// Players build their decks from a set of unopened booster packs
+    Sealed,
+    /// Players draft their decks from an organizer's cube
+    Cube,
+}
+
+impl Constructed {
+    /// Every known constructed format
+    pub const ALL: [Self; 9] = [
+        Self::Standard,
+        Self::Pioneer,
+        Self::Modern,
+        Self::Legacy,
+        Self::Vintage,
+        Self::Pauper,
+        Self::Commander,
+        Self::Oathbreaker,
+        Self::Premodern,
+    ];
+
+    /// Returns the format's name
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Standard => "Standard",
+            Self::Pioneer => "Pioneer",
+            Self::Modern => "Modern",
+            Self::Legacy => "Legacy",
+            Self::Vintage => "Vintage",
+            Self::Pauper => "Pauper",
+            Self::Commander => "Commander",
+            Self::Oathbreaker => "Oathbreaker",
+            Self::Premodern => "Premodern",
+        }
+    }
+}
+
+impl Limited {
+    /// Every known limited format
+    pub const ALL: [Self; 3] = [Self::Draft, Self::Sealed, Self::Cube];
+
+    /// Returns the format's name
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Draft => "Draft",
+            Self::Sealed => "Sealed",
+            Self::Cube => "Cube",
+        }
+    }
+}
+
+impl Format {
+    /// Creates a custom format. If the name belongs to a known format, that format is returned
+    /// instead. Names can not be blank or longer than [`MAX_FORMAT_NAME_LENGTH`].
+    pub fn custom(name: impl Into<String>) -> Result<Self, TournamentError> {
+        name.into().parse()
+    }
+
+    /// Returns the format's name
+    pub fn name(&self) -> &str {
+        match self {
+            Format::Constructed(format) => format.name(),
+            Format::Limited(format) => format.name(),
+            Format::Custom(name) => name,
+        }
+    }
+
+    /// Calculates if the format is a known constructed format
+    pub fn is_constructed(&self) -> bool {
+        matches!(self, Format::Constructed(_))
+    }
+
+    /// Calculates if the format is a known limited format
+    pub fn is_limited(&self) -> bool {
+        matches!(self, Format::Limited(_))
+    }
+
+    /// Reads the format of a tournament. Tournaments from before formats were validated can have
+    /// any name, so names that are not valid are kept as custom formats.
+    pub fn from_name(name: &str) -> Self {
+        name.parse()
+            .unwrap_or_else(|_| Format::Custom(name.to_owned()))
+    }
+}
+
+impl FromStr for Format {
+    type Err = TournamentError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name = s.trim();
+        if name.is_empty() || name.chars().count() > MAX_FORMAT_NAME_LENGTH {
+            return Err(TournamentError::InvalidFormat);
+        }
+        if name.eq_ignore_ascii_case("EDH") {
+            return Ok(Format::Constructed(Constructed::Commander));
+        }
+        if let Some(format) = Constructed::ALL
+            .into_iter()
+            .find(|f| f.name().eq_ignore_ascii_case(name))
+        {
+            return Ok(Format::Constructed(format));
+        }
+        if let Some(format) = Limited::ALL
+            .into_iter()
+            .find(|f| f.name().eq_ignore_ascii_case(name))
+        {
+            return Ok(Format::Limited(format));
+        }
+        Ok(Format::Custom(name.to_owned()))
+    }
+}
+
+impl TryFrom<String> for Format {
+    type Error = TournamentError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<Format> for String {
+    fn from(value: Format) -> Self {
+        match value {
+            Format::Custom(name) => name,
+            format => format.name().to_owned(),
+        }
+    }
+}
+
+impl From<Constructed> for Format {
+    fn from(value: Constructed) -> Self {
+        Format::Constructed(value)
+    }
+}
+
+impl From<Limited> for Format {
+    fn from(value: Limited) -> Self {
+        Format::Limited(value)
+    }
+}
+
+impl fmt::Display for Format {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Constructed, Format, Limited, MAX_FORMAT_NAME_LENGTH};
+    use crate::error::TournamentError;
+
+    #[test]
+    fn known_formats_have_one_name() {
+        assert_eq!(
+            "pioneer".parse(),
+            Ok(Format::Constructed(Constructed::Pioneer))
+        );
+        assert_eq!(
+            " EDH ".parse(),
+            Ok(Format::Constructed(Constructed::Commander))
+        );
+        assert_eq!("SEALED".parse(), Ok(Format::Limited(Limited::Sealed)));
+        assert_eq!(
+            Format::custom("Commander").unwrap().to_string(),
+            "Commander"
+        );
+        assert_eq!(
+            Format::custom(" Canadian Highlander "),
+            Ok(Format::Custom("Canadian Highlander".to_owned()))
+        );
+    }
+
+    #[test]
+    fn format_names_are_validated() {
+        assert_eq!(Format::custom("  "), Err(TournamentError::InvalidFormat));
+        let long = "a".repeat(MAX_FORMAT_NAME_LENGTH + 1);
+        assert_eq!(
+            Format::custom(long.clone()),
+            Err(TournamentError::InvalidFormat)
+        );
+        assert_eq!(Format::from_name(&long), Format::Custom(long));
+
+        // Formats are sent as their names
+        let json = serde_json::to_string(&Format::from(Limited::Cube)).unwrap();
+        assert_eq!(json, r#""Cube""#);
+        assert_eq!(
+            serde_json::from_str::<Format>(r#""modern""#).unwrap(),
+            Format::Constructed(Constructed::Modern)
+        );
+        assert!(serde_json::from_str::<Format>(r#""""#).is_err());
+    }
+}
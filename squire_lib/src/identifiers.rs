@@ -14,9 +14,11 @@ use uuid::Uuid;
 
 use crate::{
     accounts::SquireAccount,
-    admin::{Admin, Judge},
+    admin::{Admin, Judge, Scorekeeper},
     players::Player,
     rounds::Round,
+    series::Series,
+    settings::TournamentTemplate,
     tournament::Tournament,
 };
 
@@ -69,8 +71,14 @@ pub type TournamentId = TypeId<Tournament>;
 pub type SquireAccountId = TypeId<SquireAccount>;
 /// A type-checked Uuid for tournament judges
 pub type JudgeId = TypeId<Judge>;
+/// A type-checked Uuid for tournament scorekeepers
+pub type ScorekeeperId = TypeId<Scorekeeper>;
 /// A type-checked Uuid for tournament admin
 pub type AdminId = TypeId<Admin>;
+/// A type-checked Uuid for tournament templates
+pub type TemplateId = TypeId<TournamentTemplate>;
+/// A type-checked Uuid for tournament series
+pub type SeriesId = TypeId<Series>;
 
 #[derive(Serialize, Deserialize, Debug, Clone, Hash, PartialEq, Eq)]
 /// An enum for identifying a player
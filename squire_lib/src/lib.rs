@@ -27,10 +27,16 @@ mod boilerplate;
 
 /// Contains the models for user and organization accounts
 pub mod accounts;
-/// Contains the models for judges and admins
+/// Contains the models for judges, scorekeepers, and admins
 pub mod admin;
+/// Contains the rules used to sort player names
+pub mod collation;
 /// Contains the errors used throughout SquireLib
 pub mod error;
+/// Contains the secondary flights of a tournament, such as consolation flights
+pub mod flights;
+/// Contains the registry of formats that tournaments are played in
+pub mod formats;
 /// Contains identifiers for all major tournament types
 pub mod identifiers;
 /// Contains the client-server sync protocol
@@ -39,11 +45,28 @@ pub mod operations;
 pub mod pairings;
 /// Contains everything relating to the player model
 pub mod players;
+/// Contains the prize structures of tournaments and calculates payouts
+pub mod prizes;
+/// Contains the printable sheets that are posted at a venue
+pub mod report;
 /// Contains the round model
 pub mod rounds;
+/// Contains the model for scheduling a tournament's rounds
+pub mod schedule;
 /// Contains the model for communicating scores
 pub mod scoring;
+/// Contains the model for seating players at draft pods
+pub mod seating;
+/// Contains the model for recurring series of tournaments
+pub mod series;
 /// Contains the models for all the different tournament settings
 pub mod settings;
+/// Contains the model for previewing operations
+pub mod simulation;
 /// Contains the core tournament model
 pub mod tournament;
+/// Contains the model for the halls and tables of a tournament's venue
+pub mod venue;
+/// Contains the JavaScript bindings for tournaments
+#[cfg(feature = "wasm-bindgen")]
+pub mod wasm;
@@ -1,4 +1,5 @@
 use chrono::{DateTime, Utc};
+use itertools::Itertools;
 use serde::{Deserialize, Serialize};
 
 use crate::{
@@ -6,8 +7,11 @@ use crate::{
     identifiers::{PlayerId, RoundId},
     operations::OpUpdate,
     pairings::Pairings,
+    players::PlayerStatus,
     rounds::{Round, RoundResult},
-    settings::TournamentSetting,
+    settings::{Permission, TournamentSetting},
+    tournament::Tournament,
+    venue::Venue,
 };
 
 /// Operations that only tournament admin can perform
@@ -29,10 +33,14 @@ pub enum AdminOp {
     AdminOverwriteResult(RoundId, RoundResult),
     /// Operation for adding a new judge to the tournament
     RegisterJudge(SquireAccount),
+    /// Operation for adding a new scorekeeper to the tournament
+    RegisterScorekeeper(SquireAccount),
     /// Operation for adding a new tournament admin
     RegisterAdmin(SquireAccount),
     /// Operation to drop a player via an admin
     AdminDropPlayer(PlayerId),
+    /// Operation to return a dropped player to the tournament
+    ReinstatePlayer(PlayerId),
     /// Operation to kill a round
     RemoveRound(RoundId),
     /// Operation to update a single tournament setting
@@ -49,6 +57,55 @@ pub enum AdminOp {
     PrunePlayers,
     /// Operation to confirm the results of all active rounds
     ConfirmAllRounds,
+    /// Operation to move a player out of the main field and into the alternates
+    MakeAlternate(PlayerId),
+    /// Operation to promote an alternate into the main field
+    PromoteAlternate(PlayerId),
+    /// Operation to replace a player in their first round with an alternate. The first id is the
+    /// alternate and the second is the player being replaced.
+    SubstituteAlternate(PlayerId, PlayerId),
+    /// Operation to move a player from the waitlist into the tournament, regardless of the player
+    /// cap
+    PromoteFromWaitlist(PlayerId),
+    /// Operation to set the table numbers that are not automatically assigned to new rounds
+    ReserveTables(Vec<u64>),
+    /// Operation to set the pool of tables that a player can be seated at (an empty pool removes
+    /// the player's pool)
+    SetTablePool(PlayerId, Vec<u64>),
+    /// Operation to set (or remove) the layout of the venue's tables
+    SetVenue(Option<Venue>),
+    /// Operation to move an active round to a specific (possibly reserved) table
+    AssignTable(RoundId, u64),
+    /// Operation to mark a round as a feature match
+    SetFeatureMatch(RoundId),
+    /// Operation to remove the feature match designation from a round
+    RemoveFeatureMatch(RoundId),
+    /// Operation to set (or clear) the stream URL of a feature match
+    SetStreamUrl(RoundId, Option<String>),
+    /// Operation to declare a group of players that should not be paired against each other
+    AddPairingConstraint(Vec<PlayerId>),
+    /// Operation to link a guest player to an account. The player takes the id of the account and
+    /// keeps their results, decks, and seat in the tournament.
+    LinkGuestAccount(PlayerId, SquireAccount),
+    /// Operation to merge a duplicate registration (the first player) into another player (the
+    /// second player). The duplicate's rounds, results, and decks are moved to the other player
+    /// and the duplicate is removed from the tournament.
+    MergePlayers(PlayerId, PlayerId),
+    /// Operation to post an announcement to everyone following the tournament
+    Announce(String),
+    /// Operation to mark a named point in the tournament's history that the tournament can later
+    /// be rolled back to
+    Checkpoint(String),
+    /// Operation to split the players into draft pods of (at most) the given size and assign each
+    /// player a seat in their pod
+    CreateDraftPods(usize),
+    /// Operation to move players out of the main event and into the named flight (i.e. a
+    /// consolation flight), which is created if it does not exist yet. If no flight is given, the
+    /// players are moved back into the main event.
+    MoveToFlight(Vec<PlayerId>, Option<String>),
+    /// Operation to revoke an invite to the tournament, given the invite's nonce. Staff invites
+    /// are revoked as they are used, so that they can only be used once.
+    RevokeInvite(u64),
 }
 
 impl AdminOp {
@@ -61,12 +118,161 @@ impl AdminOp {
         }
     }
 
+    pub(crate) fn name(&self) -> &'static str {
+        match self {
+            AdminOp::UpdateReg(_) => "Update registration",
+            AdminOp::Start => "Start tournament",
+            AdminOp::Freeze => "Freeze tournament",
+            AdminOp::Thaw => "Thaw tournament",
+            AdminOp::End => "End tournament",
+            AdminOp::Cancel => "Cancel tournament",
+            AdminOp::AdminOverwriteResult(_, _) => "Overwrite result",
+            AdminOp::RegisterJudge(_) => "Register judge",
+            AdminOp::RegisterScorekeeper(_) => "Register scorekeeper",
+            AdminOp::RegisterAdmin(_) => "Register admin",
+            AdminOp::AdminDropPlayer(_) => "Drop player",
+            AdminOp::ReinstatePlayer(_) => "Reinstate player",
+            AdminOp::RemoveRound(_) => "Remove round",
+            AdminOp::UpdateTournSetting(_) => "Update setting",
+            AdminOp::GiveBye(_) => "Give bye",
+            AdminOp::CreateRound(_) => "Create round",
+            AdminOp::PairRound(_) => "Pair round",
+            AdminOp::Cut(_) => "Cut",
+            AdminOp::PrunePlayers => "Prune players",
+            AdminOp::ConfirmAllRounds => "Confirm all rounds",
+            AdminOp::MakeAlternate(_) => "Make alternate",
+            AdminOp::PromoteAlternate(_) => "Promote alternate",
+            AdminOp::SubstituteAlternate(_, _) => "Substitute alternate",
+            AdminOp::PromoteFromWaitlist(_) => "Promote from waitlist",
+            AdminOp::ReserveTables(_) => "Reserve tables",
+            AdminOp::SetTablePool(_, _) => "Set table pool",
+            AdminOp::SetVenue(_) => "Set venue",
+            AdminOp::AssignTable(_, _) => "Assign table",
+            AdminOp::SetFeatureMatch(_) => "Set feature match",
+            AdminOp::RemoveFeatureMatch(_) => "Remove feature match",
+            AdminOp::SetStreamUrl(_, _) => "Set stream URL",
+            AdminOp::AddPairingConstraint(_) => "Add pairing constraint",
+            AdminOp::LinkGuestAccount(_, _) => "Link guest account",
+            AdminOp::MergePlayers(_, _) => "Merge players",
+            AdminOp::Announce(_) => "Announce",
+            AdminOp::Checkpoint(_) => "Create checkpoint",
+            AdminOp::CreateDraftPods(_) => "Create draft pods",
+            AdminOp::MoveToFlight(_, _) => "Move to flight",
+            AdminOp::RevokeInvite(_) => "Revoke invite",
+        }
+    }
+
+    /// Returns the permission that lets judges perform the operation. Operations without one can
+    /// only be performed by admins.
+    pub(crate) fn permission(&self) -> Option<Permission> {
+        let digest = match self {
+            AdminOp::AdminOverwriteResult(_, _) => Permission::OverwriteResults,
+            AdminOp::ConfirmAllRounds => Permission::ConfirmResults,
+            AdminOp::AdminDropPlayer(_) | AdminOp::ReinstatePlayer(_) => Permission::DropPlayers,
+            AdminOp::RemoveRound(_)
+            | AdminOp::GiveBye(_)
+            | AdminOp::CreateRound(_)
+            | AdminOp::PairRound(_)
+            | AdminOp::CreateDraftPods(_)
+            | AdminOp::MoveToFlight(_, _) => Permission::PairRounds,
+            AdminOp::MakeAlternate(_)
+            | AdminOp::PromoteAlternate(_)
+            | AdminOp::SubstituteAlternate(_, _)
+            | AdminOp::PromoteFromWaitlist(_) => Permission::ManageAlternates,
+            AdminOp::ReserveTables(_)
+            | AdminOp::SetTablePool(_, _)
+            | AdminOp::SetVenue(_)
+            | AdminOp::AssignTable(_, _) => Permission::ManageTables,
+            AdminOp::SetFeatureMatch(_)
+            | AdminOp::RemoveFeatureMatch(_)
+            | AdminOp::SetStreamUrl(_, _) => Permission::FeatureMatches,
+            AdminOp::UpdateReg(_)
+            | AdminOp::Start
+            | AdminOp::Freeze
+            | AdminOp::Thaw
+            | AdminOp::End
+            | AdminOp::Cancel
+            | AdminOp::RegisterJudge(_)
+            | AdminOp::RegisterScorekeeper(_)
+            | AdminOp::RegisterAdmin(_)
+            | AdminOp::UpdateTournSetting(_)
+            | AdminOp::Cut(_)
+            | AdminOp::PrunePlayers
+            | AdminOp::AddPairingConstraint(_)
+            | AdminOp::LinkGuestAccount(_, _)
+            | AdminOp::MergePlayers(_, _)
+            | AdminOp::Announce(_)
+            | AdminOp::Checkpoint(_)
+            | AdminOp::RevokeInvite(_) => return None,
+        };
+        Some(digest)
+    }
+
+    pub(crate) fn player_ids(&self) -> Vec<PlayerId> {
+        match self {
+            AdminOp::AdminDropPlayer(p_id)
+            | AdminOp::ReinstatePlayer(p_id)
+            | AdminOp::GiveBye(p_id)
+            | AdminOp::MakeAlternate(p_id)
+            | AdminOp::PromoteAlternate(p_id)
+            | AdminOp::PromoteFromWaitlist(p_id)
+            | AdminOp::SetTablePool(p_id, _)
+            | AdminOp::LinkGuestAccount(p_id, _) => vec![*p_id],
+            AdminOp::SubstituteAlternate(alt, plyr) | AdminOp::MergePlayers(alt, plyr) => {
+                vec![*alt, *plyr]
+            }
+            AdminOp::CreateRound(plyrs)
+            | AdminOp::AddPairingConstraint(plyrs)
+            | AdminOp::MoveToFlight(plyrs, _) => plyrs.clone(),
+            AdminOp::PairRound(pairings) => pairings
+                .paired
+                .iter()
+                .flatten()
+                .chain(pairings.rejected.iter())
+                .copied()
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    pub(crate) fn round_ids(&self) -> Vec<RoundId> {
+        match self {
+            AdminOp::AdminOverwriteResult(r_id, _)
+            | AdminOp::RemoveRound(r_id)
+            | AdminOp::AssignTable(r_id, _)
+            | AdminOp::SetFeatureMatch(r_id)
+            | AdminOp::RemoveFeatureMatch(r_id)
+            | AdminOp::SetStreamUrl(r_id, _) => vec![*r_id],
+            _ => Vec::new(),
+        }
+    }
+
     pub(crate) fn swap_player_ids(&mut self, old: PlayerId, new: PlayerId) {
         match self {
-            AdminOp::AdminDropPlayer(p_id) | AdminOp::GiveBye(p_id) if *p_id == old => {
+            AdminOp::AdminDropPlayer(p_id)
+            | AdminOp::ReinstatePlayer(p_id)
+            | AdminOp::GiveBye(p_id)
+            | AdminOp::MakeAlternate(p_id)
+            | AdminOp::PromoteAlternate(p_id)
+            | AdminOp::PromoteFromWaitlist(p_id)
+                if *p_id == old =>
+            {
                 *p_id = new;
             }
-            AdminOp::CreateRound(plyrs) => {
+            AdminOp::SetTablePool(p_id, _) | AdminOp::LinkGuestAccount(p_id, _) if *p_id == old => {
+                *p_id = new;
+            }
+            AdminOp::SubstituteAlternate(alt, plyr) | AdminOp::MergePlayers(alt, plyr) => {
+                if *alt == old {
+                    *alt = new;
+                }
+                if *plyr == old {
+                    *plyr = new;
+                }
+            }
+            AdminOp::CreateRound(plyrs)
+            | AdminOp::AddPairingConstraint(plyrs)
+            | AdminOp::MoveToFlight(plyrs, _) => {
                 plyrs.iter_mut().filter(|p| **p == old).for_each(|p| {
                     *p = new;
                 });
@@ -78,9 +284,123 @@ impl AdminOp {
         }
     }
 
+    /// Calculates the operations that reverse this operation. The tournament needs to be in the
+    /// state it was in before this operation was applied, and the salt needs to be the one that
+    /// this operation was applied with.
+    pub(crate) fn inverse(&self, salt: DateTime<Utc>, before: &Tournament) -> Option<Vec<AdminOp>> {
+        let digest = match self {
+            AdminOp::UpdateReg(_) => vec![AdminOp::UpdateReg(before.reg_open)],
+            AdminOp::Freeze => {
+                let mut ops = vec![AdminOp::Thaw];
+                if before.reg_open {
+                    ops.push(AdminOp::UpdateReg(true));
+                }
+                ops
+            }
+            AdminOp::Thaw => vec![AdminOp::Freeze],
+            AdminOp::AdminDropPlayer(p_id) => {
+                // Players that were not in the main field can not be returned to where they were
+                if before.player_reg.get_player(p_id).ok()?.status != PlayerStatus::Registered {
+                    return None;
+                }
+                vec![AdminOp::ReinstatePlayer(*p_id)]
+            }
+            AdminOp::ReinstatePlayer(p_id) => vec![AdminOp::AdminDropPlayer(*p_id)],
+            AdminOp::GiveBye(_) | AdminOp::CreateRound(_) | AdminOp::PairRound(_) => {
+                let OpUpdate::RoundId(ids) = self.get_update(salt) else {
+                    return None;
+                };
+                ids.into_iter().map(AdminOp::RemoveRound).collect()
+            }
+            AdminOp::MakeAlternate(p_id) => vec![AdminOp::PromoteAlternate(*p_id)],
+            AdminOp::PromoteAlternate(p_id) => vec![AdminOp::MakeAlternate(*p_id)],
+            AdminOp::ReserveTables(_) => {
+                let tables = before.round_reg.reserved_tables.iter().copied().sorted();
+                vec![AdminOp::ReserveTables(tables.collect())]
+            }
+            AdminOp::SetTablePool(p_id, _) => {
+                let pool = before.round_reg.table_pools.get(p_id).cloned();
+                vec![AdminOp::SetTablePool(*p_id, pool.unwrap_or_default())]
+            }
+            AdminOp::SetVenue(_) => vec![AdminOp::SetVenue(before.round_reg.venue.clone())],
+            AdminOp::MoveToFlight(plyrs, _) => {
+                // Each player is moved back to the flight that they were in
+                let flights = &before.pairing_sys.flights;
+                plyrs
+                    .iter()
+                    .sorted_by_key(|p| flights.flight_of(p))
+                    .group_by(|p| flights.flight_of(p))
+                    .into_iter()
+                    .map(|(flight, group)| {
+                        let name = flight.and_then(|f| flights.get(f)).map(|f| f.name.clone());
+                        AdminOp::MoveToFlight(group.copied().collect(), name)
+                    })
+                    .collect()
+            }
+            AdminOp::AssignTable(r_id, _) => {
+                let rnd = before.round_reg.get_round(r_id).ok()?;
+                vec![AdminOp::AssignTable(*r_id, rnd.table_number)]
+            }
+            AdminOp::SetFeatureMatch(r_id) => {
+                // Feature matches can be moved to a reserved table, so the round is moved back
+                let rnd = before.round_reg.get_round(r_id).ok()?;
+                let mut ops = Vec::with_capacity(2);
+                if !rnd.is_feature {
+                    ops.push(AdminOp::RemoveFeatureMatch(*r_id));
+                }
+                ops.push(AdminOp::AssignTable(*r_id, rnd.table_number));
+                ops
+            }
+            AdminOp::RemoveFeatureMatch(r_id) => {
+                let rnd = before.round_reg.get_round(r_id).ok()?;
+                if rnd.is_feature {
+                    vec![
+                        AdminOp::SetFeatureMatch(*r_id),
+                        AdminOp::AssignTable(*r_id, rnd.table_number),
+                    ]
+                } else {
+                    Vec::new()
+                }
+            }
+            AdminOp::SetStreamUrl(r_id, _) => {
+                let rnd = before.round_reg.get_round(r_id).ok()?;
+                vec![AdminOp::SetStreamUrl(*r_id, rnd.stream_url.clone())]
+            }
+            AdminOp::Start
+            | AdminOp::End
+            | AdminOp::Cancel
+            | AdminOp::AdminOverwriteResult(_, _)
+            | AdminOp::RegisterJudge(_)
+            | AdminOp::RegisterScorekeeper(_)
+            | AdminOp::RegisterAdmin(_)
+            | AdminOp::RemoveRound(_)
+            | AdminOp::UpdateTournSetting(_)
+            | AdminOp::Cut(_)
+            | AdminOp::PrunePlayers
+            | AdminOp::ConfirmAllRounds
+            | AdminOp::CreateDraftPods(_)
+            | AdminOp::SubstituteAlternate(_, _)
+            | AdminOp::PromoteFromWaitlist(_)
+            | AdminOp::AddPairingConstraint(_)
+            | AdminOp::LinkGuestAccount(_, _)
+            | AdminOp::MergePlayers(_, _)
+            | AdminOp::Announce(_)
+            | AdminOp::Checkpoint(_)
+            | AdminOp::RevokeInvite(_) => return None,
+        };
+        Some(digest)
+    }
+
     pub(crate) fn swap_round_ids(&mut self, old: RoundId, new: RoundId) {
         match self {
-            AdminOp::AdminOverwriteResult(r_id, _) | AdminOp::RemoveRound(r_id) if *r_id == old => {
+            AdminOp::AdminOverwriteResult(r_id, _)
+            | AdminOp::RemoveRound(r_id)
+            | AdminOp::AssignTable(r_id, _)
+            | AdminOp::SetFeatureMatch(r_id)
+            | AdminOp::RemoveFeatureMatch(r_id)
+            | AdminOp::SetStreamUrl(r_id, _)
+                if *r_id == old =>
+            {
                 *r_id = new;
             }
             _ => {}
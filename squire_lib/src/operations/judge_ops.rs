@@ -8,7 +8,9 @@ use crate::{
     identifiers::{PlayerId, RoundId},
     operations::OpUpdate,
     players::{Deck, Player},
-    rounds::RoundResult,
+    rounds::{DeckCheckResult, RoundResult},
+    settings::Permission,
+    tournament::Tournament,
 };
 
 /// Operations that judges and tournament admin can perform
@@ -20,6 +22,9 @@ pub enum JudgeOp {
     ReRegisterGuest(String),
     /// Operation to register a player via an admin
     AdminRegisterPlayer(SquireAccount, Option<String>),
+    /// Operation to register many players at once via an admin, e.g. when importing a
+    /// preregistration list. If any player can not be registered, none of them are.
+    AdminRegisterPlayers(Vec<(SquireAccount, Option<String>)>),
     /// Operation to record the result of a round via an admin
     AdminRecordResult(RoundId, RoundResult),
     /// Operation to confirm the result of a round via an admin
@@ -36,6 +41,18 @@ pub enum JudgeOp {
     TimeExtension(RoundId, Duration),
     /// Confirms the round result for all players
     ConfirmRound(RoundId),
+    /// Confirms the round result for all players once the tournament's auto-certify wait has
+    /// passed (see [crate::rounds::Round::auto_certify_time]). This is applied by the server rather
+    /// than by an official.
+    AutoCertifyRound(RoundId),
+    /// Penalizes a player that has not checked in at their table according to the tournament's
+    /// tardiness policy
+    ApplyTardiness(RoundId, PlayerId),
+    /// Starts a deck check for the players in a round
+    StartDeckCheck(RoundId),
+    /// Completes a round's deck check. The round is given a time extension for the time that the
+    /// deck check took.
+    CompleteDeckCheck(RoundId, DeckCheckResult),
 }
 
 impl JudgeOp {
@@ -46,6 +63,77 @@ impl JudgeOp {
         }
     }
 
+    pub(crate) fn name(&self) -> &'static str {
+        match self {
+            JudgeOp::RegisterGuest(_) => "Register guest",
+            JudgeOp::ReRegisterGuest(_) => "Re-register guest",
+            JudgeOp::AdminRegisterPlayer(_, _) => "Register player",
+            JudgeOp::AdminRegisterPlayers(_) => "Register players",
+            JudgeOp::AdminRecordResult(_, _) => "Record result",
+            JudgeOp::AdminConfirmResult(_, _) => "Confirm result",
+            JudgeOp::AdminAddDeck(_, _, _) => "Add deck",
+            JudgeOp::AdminRemoveDeck(_, _) => "Remove deck",
+            JudgeOp::AdminReadyPlayer(_) => "Ready player",
+            JudgeOp::AdminUnReadyPlayer(_) => "Unready player",
+            JudgeOp::TimeExtension(_, _) => "Time extension",
+            JudgeOp::ConfirmRound(_) => "Confirm round",
+            JudgeOp::AutoCertifyRound(_) => "Auto-certify round",
+            JudgeOp::ApplyTardiness(_, _) => "Apply tardiness",
+            JudgeOp::StartDeckCheck(_) => "Start deck check",
+            JudgeOp::CompleteDeckCheck(_, _) => "Complete deck check",
+        }
+    }
+
+    pub(crate) fn permission(&self) -> Permission {
+        match self {
+            JudgeOp::RegisterGuest(_)
+            | JudgeOp::ReRegisterGuest(_)
+            | JudgeOp::AdminRegisterPlayer(_, _)
+            | JudgeOp::AdminRegisterPlayers(_) => Permission::RegisterPlayers,
+            JudgeOp::AdminRecordResult(_, _) | JudgeOp::ApplyTardiness(_, _) => {
+                Permission::RecordResults
+            }
+            JudgeOp::AdminConfirmResult(_, _)
+            | JudgeOp::ConfirmRound(_)
+            | JudgeOp::AutoCertifyRound(_) => Permission::ConfirmResults,
+            JudgeOp::AdminAddDeck(_, _, _) | JudgeOp::AdminRemoveDeck(_, _) => {
+                Permission::ManageDecks
+            }
+            JudgeOp::AdminReadyPlayer(_) | JudgeOp::AdminUnReadyPlayer(_) => {
+                Permission::ReadyPlayers
+            }
+            JudgeOp::TimeExtension(_, _) => Permission::TimeExtensions,
+            JudgeOp::StartDeckCheck(_) | JudgeOp::CompleteDeckCheck(_, _) => Permission::DeckChecks,
+        }
+    }
+
+    pub(crate) fn player_ids(&self) -> Vec<PlayerId> {
+        match self {
+            JudgeOp::AdminConfirmResult(_, p_id)
+            | JudgeOp::AdminAddDeck(p_id, _, _)
+            | JudgeOp::AdminRemoveDeck(p_id, _)
+            | JudgeOp::AdminReadyPlayer(p_id)
+            | JudgeOp::AdminUnReadyPlayer(p_id)
+            | JudgeOp::ApplyTardiness(_, p_id)
+            | JudgeOp::CompleteDeckCheck(_, DeckCheckResult::Failed(p_id, _)) => vec![*p_id],
+            _ => Vec::new(),
+        }
+    }
+
+    pub(crate) fn round_ids(&self) -> Vec<RoundId> {
+        match self {
+            JudgeOp::AdminRecordResult(r_id, _)
+            | JudgeOp::AdminConfirmResult(r_id, _)
+            | JudgeOp::TimeExtension(r_id, _)
+            | JudgeOp::ConfirmRound(r_id)
+            | JudgeOp::AutoCertifyRound(r_id)
+            | JudgeOp::ApplyTardiness(r_id, _)
+            | JudgeOp::StartDeckCheck(r_id)
+            | JudgeOp::CompleteDeckCheck(r_id, _) => vec![*r_id],
+            _ => Vec::new(),
+        }
+    }
+
     pub(crate) fn swap_player_ids(&mut self, old: PlayerId, new: PlayerId) {
         match self {
             JudgeOp::AdminConfirmResult(_, p_id)
@@ -53,6 +141,8 @@ impl JudgeOp {
             | JudgeOp::AdminRemoveDeck(p_id, _)
             | JudgeOp::AdminReadyPlayer(p_id)
             | JudgeOp::AdminUnReadyPlayer(p_id)
+            | JudgeOp::ApplyTardiness(_, p_id)
+            | JudgeOp::CompleteDeckCheck(_, DeckCheckResult::Failed(p_id, _))
                 if *p_id == old =>
             {
                 *p_id = new;
@@ -61,12 +151,48 @@ impl JudgeOp {
         }
     }
 
+    /// Calculates the operations that reverse this operation. The tournament needs to be in the
+    /// state it was in before this operation was applied.
+    pub(crate) fn inverse(&self, before: &Tournament) -> Option<Vec<JudgeOp>> {
+        let digest = match self {
+            JudgeOp::AdminReadyPlayer(p_id) => JudgeOp::AdminUnReadyPlayer(*p_id),
+            JudgeOp::AdminUnReadyPlayer(p_id) => JudgeOp::AdminReadyPlayer(*p_id),
+            JudgeOp::AdminAddDeck(p_id, name, _) => {
+                match before.player_reg.get_player(p_id).ok()?.get_deck(name) {
+                    Some(deck) => JudgeOp::AdminAddDeck(*p_id, name.clone(), deck.clone()),
+                    None => JudgeOp::AdminRemoveDeck(*p_id, name.clone()),
+                }
+            }
+            JudgeOp::AdminRemoveDeck(p_id, name) => {
+                let deck = before.player_reg.get_player(p_id).ok()?.get_deck(name)?;
+                JudgeOp::AdminAddDeck(*p_id, name.clone(), deck.clone())
+            }
+            JudgeOp::RegisterGuest(_)
+            | JudgeOp::ReRegisterGuest(_)
+            | JudgeOp::AdminRegisterPlayer(_, _)
+            | JudgeOp::AdminRegisterPlayers(_)
+            | JudgeOp::AdminRecordResult(_, _)
+            | JudgeOp::AdminConfirmResult(_, _)
+            | JudgeOp::TimeExtension(_, _)
+            | JudgeOp::ConfirmRound(_)
+            | JudgeOp::AutoCertifyRound(_)
+            | JudgeOp::ApplyTardiness(_, _)
+            | JudgeOp::StartDeckCheck(_)
+            | JudgeOp::CompleteDeckCheck(_, _) => return None,
+        };
+        Some(vec![digest])
+    }
+
     pub(crate) fn swap_round_ids(&mut self, old: RoundId, new: RoundId) {
         match self {
             JudgeOp::AdminRecordResult(r_id, _)
             | JudgeOp::AdminConfirmResult(r_id, _)
             | JudgeOp::TimeExtension(r_id, _)
             | JudgeOp::ConfirmRound(r_id)
+            | JudgeOp::AutoCertifyRound(r_id)
+            | JudgeOp::ApplyTardiness(r_id, _)
+            | JudgeOp::StartDeckCheck(r_id)
+            | JudgeOp::CompleteDeckCheck(r_id, _)
                 if *r_id == old =>
             {
                 *r_id = new;
@@ -3,11 +3,12 @@ use serde::{Deserialize, Serialize};
 
 use crate::{
     accounts::SquireAccount,
-    admin::{Admin, Judge, TournOfficialId},
+    admin::{Admin, Judge, Scorekeeper, TournOfficialId},
     error::TournamentError,
     identifiers::{AdminId, PlayerId},
     rounds::{RoundId, RoundStatus},
-    tournament::TournRole,
+    settings::{PermissionMatrix, PermissionRole},
+    tournament::{TournRole, Tournament},
 };
 
 mod admin_ops;
@@ -39,8 +40,12 @@ pub enum OpData {
     Nothing,
     /// A player was registerd and this is their id
     RegisterPlayer(PlayerId),
+    /// Several players were registerd and these are their ids, in the order they were given
+    RegisterPlayers(Vec<PlayerId>),
     /// A player was registerd and this is their id
     RegisterJudge(Judge),
+    /// A scorekeeper was registerd and this is their id
+    RegisterScorekeeper(Scorekeeper),
     /// A player was registerd and this is their id
     RegisterAdmin(Admin),
     /// A round result was confirmed and this is the current status of that round
@@ -82,6 +87,16 @@ impl OpData {
         }
     }
 
+    /// Assumes contained data is from `RegisterPlayers` and returns those ids, analogous to `unwrap`.
+    ///
+    /// PANICS: If the data is anything else, this method panics.
+    pub fn assume_register_players(self) -> Vec<PlayerId> {
+        match self {
+            Self::RegisterPlayers(ids) => ids,
+            _ => panic!("Assumed OpData was register players failed"),
+        }
+    }
+
     /// Assumes contained data is from `RegisterJudge` and returns that id, analogous to `unwrap`.
     ///
     /// PANICS: If the data is anything else, this method panics.
@@ -92,6 +107,17 @@ impl OpData {
         }
     }
 
+    /// Assumes contained data is from `RegisterScorekeeper` and returns that id, analogous to
+    /// `unwrap`.
+    ///
+    /// PANICS: If the data is anything else, this method panics.
+    pub fn assume_register_scorekeeper(self) -> Scorekeeper {
+        match self {
+            Self::RegisterScorekeeper(scorekeeper) => scorekeeper,
+            _ => panic!("Assumed OpData was register scorekeeper failed"),
+        }
+    }
+
     /// Assumes contained data is from `RegisterAdmin` and returns that id, analogous to `unwrap`.
     ///
     /// PANICS: If the data is anything else, this method panics.
@@ -175,20 +201,39 @@ impl OpUpdate {
 }
 
 impl TournOp {
-    /// Calculates if a given role is allowed to submit the given operation.
-    pub fn valid_op(&self, role: TournRole) -> bool {
+    /// Calculates if a given role is allowed to submit the given operation under the given
+    /// permissions.
+    pub fn valid_op(&self, role: TournRole, perms: &PermissionMatrix) -> bool {
         match (role, self) {
             // The only thing that an admin can't do is submit an operation for of another admin
             (TournRole::Admin(a_id), TournOp::AdminOp(id, _)) => a_id == *id,
             (TournRole::Admin(_), _) => true,
-            // Judges can submit judge and player ops, but not for other judges or admin ops
-            (TournRole::Judge(_), TournOp::AdminOp(_, _)) => false,
-            (TournRole::Judge(j_id), TournOp::JudgeOp(TournOfficialId::Judge(id), _)) => {
-                j_id == *id
+            // Judges can submit judge ops, and admin ops that they have been granted, for
+            // themselves. Admin ops are submitted with the judge's id.
+            (TournRole::Judge(j_id), TournOp::AdminOp(id, op)) => {
+                j_id.convert::<Admin>() == *id
+                    && op
+                        .permission()
+                        .is_some_and(|perm| perms.allows(PermissionRole::Judge, perm))
+            }
+            (TournRole::Judge(j_id), TournOp::JudgeOp(TournOfficialId::Judge(id), op)) => {
+                j_id == *id && perms.allows(PermissionRole::Judge, op.permission())
             }
             (TournRole::Judge(_), _) => false,
+            // Scorekeepers can only submit the few judge and admin ops that deal with results and
+            // tables for themselves. Admin ops are submitted with the scorekeeper's id.
+            (TournRole::Scorekeeper(s_id), TournOp::AdminOp(id, op)) => {
+                s_id.convert::<Admin>() == *id && Scorekeeper::can_admin(op)
+            }
+            (
+                TournRole::Scorekeeper(s_id),
+                TournOp::JudgeOp(TournOfficialId::Scorekeeper(id), op),
+            ) => s_id == *id && Scorekeeper::can_judge(op),
+            (TournRole::Scorekeeper(_), _) => false,
             // Players can only submit player operations for themselves
-            (TournRole::Player(p_id), TournOp::PlayerOp(id, _)) => p_id == *id,
+            (TournRole::Player(p_id), TournOp::PlayerOp(id, op)) => {
+                p_id == *id && perms.allows(PermissionRole::Player, op.permission())
+            }
             (TournRole::Player(_), _) => false,
             // Specators can only register for tournaments
             (TournRole::Spectator, TournOp::RegisterPlayer(_, _)) => true,
@@ -206,6 +251,104 @@ impl TournOp {
         }
     }
 
+    /// Returns a short, human-readable name for the operation, e.g. "Record result"
+    pub fn name(&self) -> &'static str {
+        match self {
+            TournOp::RegisterPlayer(_, _) => "Register",
+            TournOp::PlayerOp(_, p_op) => p_op.name(),
+            TournOp::JudgeOp(_, j_op) => j_op.name(),
+            TournOp::AdminOp(_, a_op) => a_op.name(),
+        }
+    }
+
+    /// Returns the ids of all players that the operation acts on. For player operations, this
+    /// includes the player that submitted the operation.
+    pub fn player_ids(&self) -> Vec<PlayerId> {
+        match self {
+            TournOp::RegisterPlayer(_, _) => Vec::new(),
+            TournOp::PlayerOp(p_id, _) => vec![*p_id],
+            TournOp::JudgeOp(_, j_op) => j_op.player_ids(),
+            TournOp::AdminOp(_, a_op) => a_op.player_ids(),
+        }
+    }
+
+    /// Returns the ids of all rounds that the operation acts on
+    pub fn round_ids(&self) -> Vec<RoundId> {
+        match self {
+            TournOp::RegisterPlayer(_, _) => Vec::new(),
+            TournOp::PlayerOp(_, p_op) => p_op.round_ids(),
+            TournOp::JudgeOp(_, j_op) => j_op.round_ids(),
+            TournOp::AdminOp(_, a_op) => a_op.round_ids(),
+        }
+    }
+
+    /// Calculates if the operation carries a decklist, i.e. it adds a deck to a player
+    pub fn has_decklist(&self) -> bool {
+        matches!(
+            self,
+            TournOp::PlayerOp(_, PlayerOp::AddDeck(_, _))
+                | TournOp::JudgeOp(_, JudgeOp::AdminAddDeck(_, _, _))
+        )
+    }
+
+    /// Calculates if the operation carries the account of a player, which holds their real name
+    pub fn has_player_account(&self) -> bool {
+        matches!(
+            self,
+            TournOp::RegisterPlayer(_, _)
+                | TournOp::JudgeOp(
+                    _,
+                    JudgeOp::AdminRegisterPlayer(_, _) | JudgeOp::AdminRegisterPlayers(_)
+                )
+                | TournOp::AdminOp(_, AdminOp::LinkGuestAccount(_, _))
+        )
+    }
+
+    /// Calculates if the operation carries something that users that aren't officials might not
+    /// be allowed to see, i.e. a decklist or a player's account
+    pub fn is_private(&self) -> bool {
+        self.has_decklist() || self.has_player_account()
+    }
+
+    /// Calculates if the operation is checked against the tournament's registration window. These
+    /// operations are checked using the time that they were created at.
+    pub fn checks_reg_window(&self) -> bool {
+        matches!(
+            self,
+            TournOp::RegisterPlayer(_, _) | TournOp::PlayerOp(_, PlayerOp::AddDeck(_, _))
+        )
+    }
+
+    /// Calculates the operations that reverse this operation, e.g. removing the rounds that were
+    /// just paired or reinstating a player that was just dropped. The tournament needs to be in the
+    /// state it was in before this operation was applied, and the salt needs to be the one that
+    /// this operation was applied with. The reversing operations are submitted by the given admin.
+    ///
+    /// `None` is returned if the operation can not be reversed. Only judge and admin operations can
+    /// be reversed.
+    pub fn inverse(
+        &self,
+        admin: AdminId,
+        salt: DateTime<Utc>,
+        before: &Tournament,
+    ) -> Option<Vec<TournOp>> {
+        match self {
+            TournOp::RegisterPlayer(_, _) | TournOp::PlayerOp(_, _) => None,
+            TournOp::JudgeOp(_, j_op) => Some(
+                j_op.inverse(before)?
+                    .into_iter()
+                    .map(|op| TournOp::JudgeOp(admin.into(), op))
+                    .collect(),
+            ),
+            TournOp::AdminOp(_, a_op) => Some(
+                a_op.inverse(salt, before)?
+                    .into_iter()
+                    .map(|op| TournOp::AdminOp(admin, op))
+                    .collect(),
+            ),
+        }
+    }
+
     /// Replaces an old player id with a new player id in the operation
     pub fn swap_player_ids(&mut self, old: PlayerId, new: PlayerId) {
         match self {
@@ -1,7 +1,10 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
-use crate::{identifiers::RoundId, operations::OpUpdate, players::Deck, rounds::RoundResult};
+use crate::{
+    identifiers::RoundId, operations::OpUpdate, players::Deck, rounds::RoundResult,
+    settings::Permission,
+};
 
 #[derive(Serialize, Deserialize, Debug, Hash, Clone, PartialEq, Eq)]
 /// Operations that players can perform
@@ -24,6 +27,8 @@ pub enum PlayerOp {
     ReadyPlayer,
     /// Operation for a player to mark themself as unready for their next round
     UnReadyPlayer,
+    /// Operation for a player to check in at the table of one of their rounds
+    CheckInAtTable(RoundId),
 }
 
 impl PlayerOp {
@@ -31,9 +36,49 @@ impl PlayerOp {
         OpUpdate::None
     }
 
+    pub(crate) fn name(&self) -> &'static str {
+        match self {
+            PlayerOp::CheckIn => "Check in",
+            PlayerOp::DropPlayer => "Drop",
+            PlayerOp::RecordResult(_, _) => "Record result",
+            PlayerOp::ConfirmResult(_) => "Confirm result",
+            PlayerOp::AddDeck(_, _) => "Add deck",
+            PlayerOp::RemoveDeck(_) => "Remove deck",
+            PlayerOp::SetGamerTag(_) => "Set gamer tag",
+            PlayerOp::ReadyPlayer => "Ready",
+            PlayerOp::UnReadyPlayer => "Unready",
+            PlayerOp::CheckInAtTable(_) => "Check in at table",
+        }
+    }
+
+    pub(crate) fn permission(&self) -> Permission {
+        match self {
+            PlayerOp::CheckIn | PlayerOp::CheckInAtTable(_) => Permission::CheckIn,
+            PlayerOp::DropPlayer => Permission::DropPlayers,
+            PlayerOp::RecordResult(_, _) => Permission::RecordResults,
+            PlayerOp::ConfirmResult(_) => Permission::ConfirmResults,
+            PlayerOp::AddDeck(_, _) | PlayerOp::RemoveDeck(_) => Permission::ManageDecks,
+            PlayerOp::SetGamerTag(_) => Permission::SetGamerTag,
+            PlayerOp::ReadyPlayer | PlayerOp::UnReadyPlayer => Permission::ReadyPlayers,
+        }
+    }
+
+    pub(crate) fn round_ids(&self) -> Vec<RoundId> {
+        match self {
+            PlayerOp::RecordResult(r_id, _)
+            | PlayerOp::ConfirmResult(r_id)
+            | PlayerOp::CheckInAtTable(r_id) => vec![*r_id],
+            _ => Vec::new(),
+        }
+    }
+
     pub(crate) fn swap_round_ids(&mut self, old: RoundId, new: RoundId) {
         match self {
-            PlayerOp::RecordResult(r_id, _) | PlayerOp::ConfirmResult(r_id) if *r_id == old => {
+            PlayerOp::RecordResult(r_id, _)
+            | PlayerOp::ConfirmResult(r_id)
+            | PlayerOp::CheckInAtTable(r_id)
+                if *r_id == old =>
+            {
                 *r_id = new;
             }
             _ => {}
@@ -0,0 +1,39 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    identifiers::PlayerId,
+    pairings::{PairingConstraints, Pairings},
+    r64,
+    settings::PairingCommonSettingsTree,
+};
+
+/// A record of the inputs that were used to create a set of pairings.
+///
+/// Pairing is deterministic: given the same players in the same order with the same points, the
+/// same settings and constraints, the same seed, and the opponents from the earlier rounds, the
+/// pairing algorithm creates the same pairings. This lets a pairing be reproduced and audited
+/// after the fact.
+#[derive(Serialize, Deserialize, Debug, Clone, Hash, PartialEq, Eq)]
+pub struct PairingAudit {
+    /// The number of the pairing round, starting at 1
+    pub round_number: u64,
+    /// The seed used to shuffle the players when the pairing algorithm is rerun
+    pub seed: u64,
+    /// The pairing settings at the time, which includes the pairing algorithm
+    pub settings: PairingCommonSettingsTree,
+    /// The players that could be paired, in the order that they were given to the pairing
+    /// algorithm. For swiss pairings, this is the standings from first place to last place.
+    pub standings: Vec<PlayerId>,
+    /// The points of each player in `standings`, in the same order. This is empty for pairing
+    /// styles that do not pair by standings.
+    #[serde(default)]
+    pub points: Vec<r64>,
+    /// The groups of players that were kept apart
+    pub constraints: PairingConstraints,
+    /// The players that had already received a bye, in id order. Byes are moved away from these
+    /// players when possible (see [Pairings::reassign_byes]).
+    #[serde(default)]
+    pub prior_byes: Vec<PlayerId>,
+    /// The pairings that were used to create the rounds
+    pub pairings: Pairings,
+}
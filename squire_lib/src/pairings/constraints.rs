@@ -0,0 +1,72 @@
+use std::collections::{HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{error::TournamentError, identifiers::PlayerId};
+
+/// Groups of players that should not be paired against each other, such as teammates or players
+/// from the same store. Players in the same group are treated as if they had already played each
+/// other, so pairing algorithms penalize pairing them the same way that they penalize repeat
+/// opponents.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Hash, PartialEq, Eq)]
+pub struct PairingConstraints {
+    /// The groups of players that should be kept apart
+    pub groups: Vec<Vec<PlayerId>>,
+}
+
+impl PairingConstraints {
+    /// Creates an empty set of constraints
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub(crate) fn swap_player_ids(&mut self, old: PlayerId, new: PlayerId) {
+        for group in &mut self.groups {
+            if let Some(p) = group.iter_mut().find(|p| **p == old) {
+                *p = new;
+                group.sort();
+                group.dedup();
+            }
+        }
+        self.groups.retain(|group| group.len() > 1);
+    }
+
+    /// Adds a group of players that should not be paired against each other. Repeated players are
+    /// ignored and groups need at least two distinct players.
+    pub fn add_group(&mut self, mut group: Vec<PlayerId>) -> Result<(), TournamentError> {
+        group.sort();
+        group.dedup();
+        if group.len() < 2 {
+            return Err(TournamentError::InvalidPairingConstraint);
+        }
+        if !self.groups.contains(&group) {
+            self.groups.push(group);
+        }
+        Ok(())
+    }
+
+    /// Calculates if the two players are in a group together
+    pub fn are_constrained(&self, a: &PlayerId, b: &PlayerId) -> bool {
+        self.groups
+            .iter()
+            .any(|g| a != b && g.contains(a) && g.contains(b))
+    }
+
+    /// Returns a copy of the given opponent map where every player is also considered an opponent
+    /// of the players that they share a group with.
+    pub fn apply(
+        &self,
+        opps: &HashMap<PlayerId, HashSet<PlayerId>>,
+    ) -> HashMap<PlayerId, HashSet<PlayerId>> {
+        let mut digest = opps.clone();
+        for group in self.groups.iter() {
+            for plyr in group.iter() {
+                digest
+                    .entry(*plyr)
+                    .or_default()
+                    .extend(group.iter().filter(|p| *p != plyr));
+            }
+        }
+        digest
+    }
+}
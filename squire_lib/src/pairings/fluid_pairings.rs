@@ -1,21 +1,25 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
 use serde::{Deserialize, Serialize};
+use serde_with::serde_as;
 
 use crate::{
+    boilerplate::ordered::SortedSet,
     identifiers::PlayerId,
     pairings::Pairings,
     players::PlayerRegistry,
-    rounds::{RoundContext, RoundRegistry},
+    rounds::RoundContext,
     settings::{FluidPairingSetting, FluidPairingSettingsTree, PairingCommonSettingsTree},
 };
 
+#[serde_as]
 #[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq)]
 /// Fluid pairings are also known as a looking-for-game queue and are used for on-the-fly pairings
 /// between players.
 pub struct FluidPairings {
     #[serde(default)]
     settings: FluidPairingSettingsTree,
+    #[serde_as(as = "SortedSet")]
     check_ins: HashSet<PlayerId>,
     queue: Vec<PlayerId>,
 }
@@ -56,6 +60,20 @@ impl FluidPairings {
         }
     }
 
+    pub(crate) fn swap_player_ids(&mut self, old: PlayerId, new: PlayerId) {
+        if self.check_ins.remove(&old) {
+            _ = self.check_ins.insert(new);
+        }
+        if self.queue.contains(&new) {
+            self.queue.retain(|p| *p != old);
+        } else {
+            self.queue
+                .iter_mut()
+                .filter(|p| **p == old)
+                .for_each(|p| *p = new);
+        }
+    }
+
     /// Gets the round context for the system
     pub fn get_context(&self) -> RoundContext {
         RoundContext::Contextless
@@ -79,31 +97,39 @@ impl FluidPairings {
         self.queue.retain(|p| !plyrs.contains(p));
     }
 
-    /// Attempts to pair all players in the queue.
+    /// Orders the players in the queue in the order that they are given to the pairing algorithm
+    pub fn pairing_order(&self) -> Vec<PlayerId> {
+        self.queue
+            .iter()
+            .chain(self.check_ins.iter())
+            .cloned()
+            .collect()
+    }
+
+    /// Attempts to pair all players in the queue. The given opponents are used to penalize
+    /// pairings, so they can include more than the players' past opponents.
     /// NOTE: This does not create any round, only pairings.
     pub fn pair(
         &self,
         common: &PairingCommonSettingsTree,
         _players: &PlayerRegistry,
-        matches: &RoundRegistry,
+        opps: &HashMap<PlayerId, HashSet<PlayerId>>,
     ) -> Option<Pairings> {
         let PairingCommonSettingsTree {
             match_size,
             repair_tolerance,
             algorithm,
+            ..
         } = common;
         if !self.ready_to_pair(*match_size as usize) {
             return None;
         }
-        let plyrs = self
-            .queue
-            .iter()
-            .chain(self.check_ins.iter())
-            .cloned()
-            .collect();
-        let mut digest = (algorithm.as_alg())(
+        let plyrs = self.pairing_order();
+        // Players in the queue do not have standings, so they all count as having the same points
+        let mut digest = algorithm.pair(
             plyrs,
-            &matches.opponents,
+            &HashMap::new(),
+            opps,
             *match_size as usize,
             *repair_tolerance,
         );
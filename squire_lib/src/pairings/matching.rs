@@ -0,0 +1,852 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    identifiers::PlayerId,
+    pairings::{branching_pairings, count_opps, Pairings},
+    r64,
+};
+
+/// A pairing algorithm that finds the best set of pairings for the whole field at once rather than
+/// building them one match at a time.
+///
+/// Every two players that can be paired (i.e. their pairing is within the repair tolerance) are
+/// connected by an edge. The weight of that edge goes down as the difference between the players'
+/// points grows and goes down much further if the players have already played each other. Between
+/// pairings that are equally far apart in points, players that are closer together in the standings
+/// are preferred. The maximum-weight matching with the most matches is then found using Edmonds'
+/// blossom algorithm. This means that as many players as possible are paired, repeat opponents are
+/// only used when they can not be avoided, and players are kept within their point group whenever
+/// possible. Players that are missing from `points` are treated as having no points.
+///
+/// If there is an odd number of players, a phantom player is placed a point below everyone else and
+/// whoever is matched against it is rejected, which gives the bye to a player at the bottom of the
+/// standings.
+///
+/// Matching only works for one-on-one matches. For any other match size, this falls back to
+/// `branching_pairings`. It also falls back when the points are too far apart (or too finely
+/// divided) for the edge weights to fit in an `i64`.
+pub fn matching_pairings(
+    plyrs: Vec<PlayerId>,
+    points: &HashMap<PlayerId, r64>,
+    opps: &HashMap<PlayerId, HashSet<PlayerId>>,
+    match_size: usize,
+    repair_tol: u64,
+) -> Pairings {
+    if match_size != 2 {
+        return branching_pairings(plyrs, opps, match_size, repair_tol);
+    }
+    let count = plyrs.len();
+    let vertex_count = count + count % 2;
+    let size = vertex_count as i64;
+    let points: Vec<r64> = plyrs
+        .iter()
+        .map(|p| points.get(p).copied().unwrap_or_default())
+        .collect();
+    // If the points can not be scaled or weighed without overflowing, the field is paired greedily
+    // instead
+    let Some(scaled) = scale_points(&points, vertex_count != count) else {
+        return branching_pairings(plyrs, opps, match_size, repair_tol);
+    };
+    let spread = scaled.iter().max().copied().unwrap_or_default()
+        - scaled.iter().min().copied().unwrap_or_default();
+    let Some(weights) = EdgeWeights::new(spread, size) else {
+        return branching_pairings(plyrs, opps, match_size, repair_tol);
+    };
+    let cost = |i: usize, j: usize| {
+        let diff = scaled[i] - scaled[j];
+        let dist = (j - i) as i64;
+        diff * diff * weights.point_weight + dist * dist
+    };
+    let mut edges = Vec::new();
+    for i in 0..count {
+        for j in i + 1..count {
+            let repeats = count_opps(&[plyrs[i], plyrs[j]], opps);
+            if repeats > repair_tol {
+                continue;
+            }
+            // Beyond two repeats, the penalty no longer grows. This keeps every weight positive.
+            let penalty = repeats.min(2) as i64 * weights.repeat_penalty;
+            edges.push((i, j, weights.base - cost(i, j) - penalty));
+        }
+    }
+    if vertex_count != count {
+        for i in 0..count {
+            edges.push((i, count, weights.base - cost(i, count)));
+        }
+    }
+    let mates = Matcher::new(&edges, vertex_count).solve();
+    let mut digest = Pairings {
+        paired: Vec::with_capacity(count / 2),
+        rejected: Vec::new(),
+    };
+    for (i, plyr) in plyrs.iter().enumerate() {
+        match mates[i] {
+            Some(j) if j < count => {
+                if i < j {
+                    digest.paired.push(vec![*plyr, plyrs[j]]);
+                }
+            }
+            _ => digest.rejected.push(*plyr),
+        }
+    }
+    digest
+}
+
+const NONE: usize = usize::MAX;
+
+/// Scales the points up to whole numbers so that the weights stay exact. If `phantom` is set, a
+/// phantom player is added a point below everyone else. Returns `None` if the scaled points do
+/// not fit in an `i64`.
+fn scale_points(points: &[r64], phantom: bool) -> Option<Vec<i64>> {
+    let scale = points
+        .iter()
+        .try_fold(1, |acc, p| lcm(acc, i64::from(*p.denom())))?;
+    let mut scaled = points
+        .iter()
+        .map(|p| i64::from(*p.numer()).checked_mul(scale / i64::from(*p.denom())))
+        .collect::<Option<Vec<_>>>()?;
+    if phantom {
+        let lowest = scaled.iter().min().copied().unwrap_or_default();
+        scaled.push(lowest.checked_sub(scale)?);
+    }
+    let highest = scaled.iter().max().copied().unwrap_or_default();
+    let lowest = scaled.iter().min().copied().unwrap_or_default();
+    highest.checked_sub(lowest).map(|_| scaled)
+}
+
+/// The parts that the weight of an edge is built from. Pairing players closer together in the
+/// standings must be worth less than any change in how far apart the paired players are in
+/// points, and avoiding a single repeat pairing must be worth more than both.
+struct EdgeWeights {
+    point_weight: i64,
+    repeat_penalty: i64,
+    base: i64,
+}
+
+impl EdgeWeights {
+    /// Calculates the weights for a field of `size` players whose scaled points are at most
+    /// `spread` apart. Returns `None` if the weights are too large for the matcher to work with.
+    fn new(spread: i64, size: i64) -> Option<Self> {
+        let point_weight = size.checked_pow(3)?.checked_add(1)?;
+        let max_cost = spread
+            .checked_mul(spread)?
+            .checked_mul(point_weight)?
+            .checked_add(size.checked_mul(size)?)?;
+        let repeat_penalty = size.checked_mul(max_cost)?.checked_add(1)?;
+        let base = repeat_penalty
+            .checked_mul(2)?
+            .checked_add(max_cost)?
+            .checked_add(1)?;
+        // The matcher's duals and slacks are sums of a few weights, so leave room for them
+        let _ = base.checked_mul(4)?;
+        Some(Self {
+            point_weight,
+            repeat_penalty,
+            base,
+        })
+    }
+}
+
+/// Calculates the least common multiple of two positive numbers. Returns `None` if it does not
+/// fit in an `i64`.
+fn lcm(a: i64, b: i64) -> Option<i64> {
+    let (mut x, mut y) = (a, b);
+    while y != 0 {
+        (x, y) = (y, x % y);
+    }
+    (a / x).checked_mul(b)
+}
+
+/// Indexes into a slice, where negative indices count from the back
+fn at(list: &[usize], index: isize) -> usize {
+    let len = list.len() as isize;
+    list[((index % len + len) % len) as usize]
+}
+
+/// The state of the maximum-weight matching algorithm.
+///
+/// This follows the structure of Joris van Rantwijk's well-known implementation of Edmonds'
+/// algorithm with only integer arithmetic. Edge `k` has the endpoints `2k` and `2k + 1`, which lets
+/// the other end of an edge be found with `p ^ 1`. Vertices are numbered `0..n` and blossoms are
+/// numbered `n..2n`. Labels are `0` for free, `1` for an S-blossom, and `2` for a T-blossom.
+struct Matcher<'a> {
+    edges: &'a [(usize, usize, i64)],
+    vertex_count: usize,
+    endpoint: Vec<usize>,
+    neighbors: Vec<Vec<usize>>,
+    mate: Vec<usize>,
+    label: Vec<i8>,
+    label_end: Vec<usize>,
+    in_blossom: Vec<usize>,
+    blossom_parent: Vec<usize>,
+    blossom_children: Vec<Vec<usize>>,
+    blossom_base: Vec<usize>,
+    blossom_endpoints: Vec<Vec<usize>>,
+    best_edge: Vec<usize>,
+    blossom_best_edges: Vec<Option<Vec<usize>>>,
+    unused_blossoms: Vec<usize>,
+    dual: Vec<i64>,
+    allow_edge: Vec<bool>,
+    queue: Vec<usize>,
+}
+
+impl<'a> Matcher<'a> {
+    fn new(edges: &'a [(usize, usize, i64)], vertex_count: usize) -> Self {
+        let n = vertex_count;
+        let max_weight = edges.iter().map(|e| e.2).max().unwrap_or_default().max(0);
+        let endpoint = (0..2 * edges.len())
+            .map(|p| match p % 2 {
+                0 => edges[p / 2].0,
+                _ => edges[p / 2].1,
+            })
+            .collect();
+        let mut neighbors = vec![Vec::new(); n];
+        for (k, (i, j, _)) in edges.iter().enumerate() {
+            neighbors[*i].push(2 * k + 1);
+            neighbors[*j].push(2 * k);
+        }
+        Self {
+            edges,
+            vertex_count,
+            endpoint,
+            neighbors,
+            mate: vec![NONE; n],
+            label: vec![0; 2 * n],
+            label_end: vec![NONE; 2 * n],
+            in_blossom: (0..n).collect(),
+            blossom_parent: vec![NONE; 2 * n],
+            blossom_children: vec![Vec::new(); 2 * n],
+            blossom_base: (0..n).chain(std::iter::repeat(NONE).take(n)).collect(),
+            blossom_endpoints: vec![Vec::new(); 2 * n],
+            best_edge: vec![NONE; 2 * n],
+            blossom_best_edges: vec![None; 2 * n],
+            unused_blossoms: (n..2 * n).collect(),
+            dual: std::iter::repeat(max_weight)
+                .take(n)
+                .chain(std::iter::repeat(0).take(n))
+                .collect(),
+            allow_edge: vec![false; edges.len()],
+            queue: Vec::new(),
+        }
+    }
+
+    /// Finds the maximum-weight matching among all matchings with the most edges. Returns the mate
+    /// of each vertex.
+    fn solve(mut self) -> Vec<Option<usize>> {
+        let n = self.vertex_count;
+        // Each stage either augments the matching or proves that it is optimal
+        for _ in 0..n {
+            self.label.fill(0);
+            self.best_edge.fill(NONE);
+            self.blossom_best_edges[n..].fill(None);
+            self.allow_edge.fill(false);
+            self.queue.clear();
+            for v in 0..n {
+                if self.mate[v] == NONE && self.label[self.in_blossom[v]] == 0 {
+                    self.assign_label(v, 1, NONE);
+                }
+            }
+            let mut augmented = false;
+            loop {
+                while !augmented {
+                    let Some(v) = self.queue.pop() else {
+                        break;
+                    };
+                    for p in self.neighbors[v].clone() {
+                        let k = p / 2;
+                        let w = self.endpoint[p];
+                        if self.in_blossom[v] == self.in_blossom[w] {
+                            continue;
+                        }
+                        let mut k_slack = 0;
+                        if !self.allow_edge[k] {
+                            k_slack = self.slack(k);
+                            if k_slack <= 0 {
+                                self.allow_edge[k] = true;
+                            }
+                        }
+                        if self.allow_edge[k] {
+                            if self.label[self.in_blossom[w]] == 0 {
+                                self.assign_label(w, 2, p ^ 1);
+                            } else if self.label[self.in_blossom[w]] == 1 {
+                                let base = self.scan_blossom(v, w);
+                                if base != NONE {
+                                    self.add_blossom(base, k);
+                                } else {
+                                    self.augment_matching(k);
+                                    augmented = true;
+                                    break;
+                                }
+                            } else if self.label[w] == 0 {
+                                self.label[w] = 2;
+                                self.label_end[w] = p ^ 1;
+                            }
+                        } else if self.label[self.in_blossom[w]] == 1 {
+                            let b = self.in_blossom[v];
+                            if self.best_edge[b] == NONE || k_slack < self.slack(self.best_edge[b])
+                            {
+                                self.best_edge[b] = k;
+                            }
+                        } else if self.label[w] == 0
+                            && (self.best_edge[w] == NONE
+                                || k_slack < self.slack(self.best_edge[w]))
+                        {
+                            self.best_edge[w] = k;
+                        }
+                    }
+                }
+                if augmented {
+                    break;
+                }
+
+                // Nothing more can be done with the tight edges, so the duals need to be adjusted
+                let mut delta_type = 0;
+                let mut delta = 0;
+                let mut delta_edge = NONE;
+                let mut delta_blossom = NONE;
+                for v in 0..n {
+                    if self.label[self.in_blossom[v]] == 0 && self.best_edge[v] != NONE {
+                        let d = self.slack(self.best_edge[v]);
+                        if delta_type == 0 || d < delta {
+                            delta = d;
+                            delta_type = 2;
+                            delta_edge = self.best_edge[v];
+                        }
+                    }
+                }
+                for b in 0..2 * n {
+                    if self.blossom_parent[b] == NONE
+                        && self.label[b] == 1
+                        && self.best_edge[b] != NONE
+                    {
+                        let d = self.slack(self.best_edge[b]) / 2;
+                        if delta_type == 0 || d < delta {
+                            delta = d;
+                            delta_type = 3;
+                            delta_edge = self.best_edge[b];
+                        }
+                    }
+                }
+                for b in n..2 * n {
+                    if self.blossom_base[b] != NONE
+                        && self.blossom_parent[b] == NONE
+                        && self.label[b] == 2
+                        && (delta_type == 0 || self.dual[b] < delta)
+                    {
+                        delta = self.dual[b];
+                        delta_type = 4;
+                        delta_blossom = b;
+                    }
+                }
+                if delta_type == 0 {
+                    // No further improvement is possible
+                    delta_type = 1;
+                    delta = self.dual[..n]
+                        .iter()
+                        .copied()
+                        .min()
+                        .unwrap_or_default()
+                        .max(0);
+                }
+
+                for v in 0..n {
+                    match self.label[self.in_blossom[v]] {
+                        1 => self.dual[v] -= delta,
+                        2 => self.dual[v] += delta,
+                        _ => {}
+                    }
+                }
+                for b in n..2 * n {
+                    if self.blossom_base[b] != NONE && self.blossom_parent[b] == NONE {
+                        match self.label[b] {
+                            1 => self.dual[b] += delta,
+                            2 => self.dual[b] -= delta,
+                            _ => {}
+                        }
+                    }
+                }
+
+                match delta_type {
+                    1 => break,
+                    2 => {
+                        self.allow_edge[delta_edge] = true;
+                        let (i, j, _) = self.edges[delta_edge];
+                        let i = if self.label[self.in_blossom[i]] == 0 {
+                            j
+                        } else {
+                            i
+                        };
+                        self.queue.push(i);
+                    }
+                    3 => {
+                        self.allow_edge[delta_edge] = true;
+                        self.queue.push(self.edges[delta_edge].0);
+                    }
+                    _ => self.expand_blossom(delta_blossom, false),
+                }
+            }
+            if !augmented {
+                break;
+            }
+            // Blossoms that have a zero dual are no longer needed
+            for b in n..2 * n {
+                if self.blossom_parent[b] == NONE
+                    && self.blossom_base[b] != NONE
+                    && self.label[b] == 1
+                    && self.dual[b] == 0
+                {
+                    self.expand_blossom(b, true);
+                }
+            }
+        }
+        self.mate
+            .iter()
+            .map(|p| (*p != NONE).then(|| self.endpoint[*p]))
+            .collect()
+    }
+
+    fn slack(&self, k: usize) -> i64 {
+        let (i, j, wt) = self.edges[k];
+        self.dual[i] + self.dual[j] - 2 * wt
+    }
+
+    /// Gets all of the vertices contained in a (possibly nested) blossom
+    fn leaves(&self, b: usize) -> Vec<usize> {
+        let mut digest = Vec::new();
+        self.push_leaves(b, &mut digest);
+        digest
+    }
+
+    fn push_leaves(&self, b: usize, digest: &mut Vec<usize>) {
+        if b < self.vertex_count {
+            digest.push(b);
+        } else {
+            for t in self.blossom_children[b].iter() {
+                self.push_leaves(*t, digest);
+            }
+        }
+    }
+
+    /// Labels the top-level blossom containing `w`, which was reached through endpoint `p`
+    fn assign_label(&mut self, w: usize, t: i8, p: usize) {
+        let b = self.in_blossom[w];
+        self.label[w] = t;
+        self.label[b] = t;
+        self.label_end[w] = p;
+        self.label_end[b] = p;
+        self.best_edge[w] = NONE;
+        self.best_edge[b] = NONE;
+        if t == 1 {
+            let leaves = self.leaves(b);
+            self.queue.extend(leaves);
+        } else if t == 2 {
+            let mate = self.mate[self.blossom_base[b]];
+            self.assign_label(self.endpoint[mate], 1, mate ^ 1);
+        }
+    }
+
+    /// Traces back from `v` and `w` to find either a new blossom or an augmenting path. Returns the
+    /// base of the new blossom or `NONE` if there is an augmenting path.
+    fn scan_blossom(&mut self, mut v: usize, mut w: usize) -> usize {
+        let mut path = Vec::new();
+        let mut base = NONE;
+        while v != NONE || w != NONE {
+            let mut b = self.in_blossom[v];
+            if self.label[b] & 4 != 0 {
+                base = self.blossom_base[b];
+                break;
+            }
+            path.push(b);
+            self.label[b] = 5;
+            if self.label_end[b] == NONE {
+                v = NONE;
+            } else {
+                v = self.endpoint[self.label_end[b]];
+                b = self.in_blossom[v];
+                v = self.endpoint[self.label_end[b]];
+            }
+            if w != NONE {
+                std::mem::swap(&mut v, &mut w);
+            }
+        }
+        for b in path {
+            self.label[b] = 1;
+        }
+        base
+    }
+
+    /// Creates a new blossom out of the cycle formed by edge `k` with the given base
+    fn add_blossom(&mut self, base: usize, k: usize) {
+        let (mut v, mut w, _) = self.edges[k];
+        let bb = self.in_blossom[base];
+        let mut bv = self.in_blossom[v];
+        let mut bw = self.in_blossom[w];
+        let b = self.unused_blossoms.pop().unwrap();
+        self.blossom_base[b] = base;
+        self.blossom_parent[b] = NONE;
+        self.blossom_parent[bb] = b;
+        let mut path = Vec::new();
+        let mut endps = Vec::new();
+        while bv != bb {
+            self.blossom_parent[bv] = b;
+            path.push(bv);
+            endps.push(self.label_end[bv]);
+            v = self.endpoint[self.label_end[bv]];
+            bv = self.in_blossom[v];
+        }
+        path.push(bb);
+        path.reverse();
+        endps.reverse();
+        endps.push(2 * k);
+        while bw != bb {
+            self.blossom_parent[bw] = b;
+            path.push(bw);
+            endps.push(self.label_end[bw] ^ 1);
+            w = self.endpoint[self.label_end[bw]];
+            bw = self.in_blossom[w];
+        }
+        self.label[b] = 1;
+        self.label_end[b] = self.label_end[bb];
+        self.dual[b] = 0;
+        self.blossom_children[b] = path.clone();
+        self.blossom_endpoints[b] = endps;
+        for v in self.leaves(b) {
+            if self.label[self.in_blossom[v]] == 2 {
+                self.queue.push(v);
+            }
+            self.in_blossom[v] = b;
+        }
+
+        // Find the least-slack edges from the new blossom to each S-blossom
+        let mut best_edge_to = vec![NONE; 2 * self.vertex_count];
+        for bv in path {
+            let edges: Vec<usize> = match self.blossom_best_edges[bv].take() {
+                Some(edges) => edges,
+                None => self
+                    .leaves(bv)
+                    .into_iter()
+                    .flat_map(|v| self.neighbors[v].iter().map(|p| p / 2))
+                    .collect(),
+            };
+            for k in edges {
+                let (i, j, _) = self.edges[k];
+                let j = if self.in_blossom[j] == b { i } else { j };
+                let bj = self.in_blossom[j];
+                if bj != b
+                    && self.label[bj] == 1
+                    && (best_edge_to[bj] == NONE || self.slack(k) < self.slack(best_edge_to[bj]))
+                {
+                    best_edge_to[bj] = k;
+                }
+            }
+            self.best_edge[bv] = NONE;
+        }
+        let best_edges: Vec<usize> = best_edge_to.into_iter().filter(|k| *k != NONE).collect();
+        self.best_edge[b] = NONE;
+        for k in best_edges.iter() {
+            if self.best_edge[b] == NONE || self.slack(*k) < self.slack(self.best_edge[b]) {
+                self.best_edge[b] = *k;
+            }
+        }
+        self.blossom_best_edges[b] = Some(best_edges);
+    }
+
+    /// Breaks a blossom back up into its sub-blossoms
+    fn expand_blossom(&mut self, b: usize, end_stage: bool) {
+        for s in self.blossom_children[b].clone() {
+            self.blossom_parent[s] = NONE;
+            if s < self.vertex_count {
+                self.in_blossom[s] = s;
+            } else if end_stage && self.dual[s] == 0 {
+                self.expand_blossom(s, end_stage);
+            } else {
+                for v in self.leaves(s) {
+                    self.in_blossom[v] = s;
+                }
+            }
+        }
+        if !end_stage && self.label[b] == 2 {
+            // The expanded T-blossom needs its sub-blossoms relabeled along the even-length path
+            // from the entry child to the base
+            let children = self.blossom_children[b].clone();
+            let endps = self.blossom_endpoints[b].clone();
+            let entry_child = self.in_blossom[self.endpoint[self.label_end[b] ^ 1]];
+            let mut j = children.iter().position(|c| *c == entry_child).unwrap() as isize;
+            let (j_step, trick): (isize, usize) = if j & 1 == 1 {
+                j -= children.len() as isize;
+                (1, 0)
+            } else {
+                (-1, 1)
+            };
+            let mut p = self.label_end[b];
+            while j != 0 {
+                self.label[self.endpoint[p ^ 1]] = 0;
+                let q = at(&endps, j - trick as isize);
+                self.label[self.endpoint[q ^ trick ^ 1]] = 0;
+                self.assign_label(self.endpoint[p ^ 1], 2, p);
+                self.allow_edge[q / 2] = true;
+                j += j_step;
+                p = at(&endps, j - trick as isize) ^ trick;
+                self.allow_edge[p / 2] = true;
+                j += j_step;
+            }
+            let bv = at(&children, j);
+            let end = self.endpoint[p ^ 1];
+            self.label[end] = 2;
+            self.label[bv] = 2;
+            self.label_end[end] = p;
+            self.label_end[bv] = p;
+            self.best_edge[bv] = NONE;
+            j += j_step;
+            while at(&children, j) != entry_child {
+                let bv = at(&children, j);
+                j += j_step;
+                if self.label[bv] == 1 {
+                    continue;
+                }
+                if let Some(v) = self.leaves(bv).into_iter().find(|v| self.label[*v] != 0) {
+                    self.label[v] = 0;
+                    self.label[self.endpoint[self.mate[self.blossom_base[bv]]]] = 0;
+                    self.assign_label(v, 2, self.label_end[v]);
+                }
+            }
+        }
+        self.label[b] = -1;
+        self.label_end[b] = NONE;
+        self.blossom_children[b].clear();
+        self.blossom_endpoints[b].clear();
+        self.blossom_base[b] = NONE;
+        self.blossom_best_edges[b] = None;
+        self.best_edge[b] = NONE;
+        self.unused_blossoms.push(b);
+    }
+
+    /// Swaps the matched and unmatched edges along the path from `v` to the base of blossom `b`
+    fn augment_blossom(&mut self, b: usize, v: usize) {
+        let mut t = v;
+        while self.blossom_parent[t] != b {
+            t = self.blossom_parent[t];
+        }
+        if t >= self.vertex_count {
+            self.augment_blossom(t, v);
+        }
+        let children = self.blossom_children[b].clone();
+        let endps = self.blossom_endpoints[b].clone();
+        let i = children.iter().position(|c| *c == t).unwrap();
+        let mut j = i as isize;
+        let (j_step, trick): (isize, usize) = if j & 1 == 1 {
+            j -= children.len() as isize;
+            (1, 0)
+        } else {
+            (-1, 1)
+        };
+        while j != 0 {
+            j += j_step;
+            let t = at(&children, j);
+            let p = at(&endps, j - trick as isize) ^ trick;
+            if t >= self.vertex_count {
+                self.augment_blossom(t, self.endpoint[p]);
+            }
+            j += j_step;
+            let t = at(&children, j);
+            if t >= self.vertex_count {
+                self.augment_blossom(t, self.endpoint[p ^ 1]);
+            }
+            self.mate[self.endpoint[p]] = p ^ 1;
+            self.mate[self.endpoint[p ^ 1]] = p;
+        }
+        self.blossom_children[b].rotate_left(i);
+        self.blossom_endpoints[b].rotate_left(i);
+        self.blossom_base[b] = self.blossom_base[self.blossom_children[b][0]];
+    }
+
+    /// Swaps the matched and unmatched edges along the augmenting path through edge `k`
+    fn augment_matching(&mut self, k: usize) {
+        let (v, w, _) = self.edges[k];
+        for (mut s, mut p) in [(v, 2 * k + 1), (w, 2 * k)] {
+            loop {
+                let bs = self.in_blossom[s];
+                if bs >= self.vertex_count {
+                    self.augment_blossom(bs, s);
+                }
+                self.mate[s] = p;
+                if self.label_end[bs] == NONE {
+                    break;
+                }
+                let t = self.endpoint[self.label_end[bs]];
+                let bt = self.in_blossom[t];
+                s = self.endpoint[self.label_end[bt]];
+                let j = self.endpoint[self.label_end[bt] ^ 1];
+                if bt >= self.vertex_count {
+                    self.augment_blossom(bt, j);
+                }
+                self.mate[j] = self.label_end[bt];
+                p = self.label_end[bt] ^ 1;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{HashMap, HashSet};
+
+    use uuid::Uuid;
+
+    use super::{matching_pairings, Matcher};
+    use crate::{
+        identifiers::PlayerId,
+        pairings::{greedy_pairings, Pairings},
+        r64,
+    };
+
+    fn spoof_players(count: usize) -> Vec<PlayerId> {
+        std::iter::repeat_with(|| PlayerId::new(Uuid::new_v4()))
+            .take(count)
+            .collect()
+    }
+
+    fn played(pairs: &[(PlayerId, PlayerId)]) -> HashMap<PlayerId, HashSet<PlayerId>> {
+        let mut digest: HashMap<PlayerId, HashSet<PlayerId>> = HashMap::new();
+        for (a, b) in pairs {
+            let _ = digest.entry(*a).or_default().insert(*b);
+            let _ = digest.entry(*b).or_default().insert(*a);
+        }
+        digest
+    }
+
+    #[test]
+    fn blossom_matching() {
+        // Vertices 0, 1, and 2 form an odd cycle, which is shrunk into a blossom
+        let edges = [
+            (0, 1, 8),
+            (0, 2, 9),
+            (1, 2, 10),
+            (2, 3, 7),
+            (3, 4, 6),
+            (4, 5, 5),
+            (1, 5, 4),
+        ];
+        let mates = Matcher::new(&edges, 6).solve();
+        assert_eq!(
+            mates,
+            vec![Some(1), Some(0), Some(3), Some(2), Some(5), Some(4)]
+        );
+
+        // The heaviest edge is not in the matching with the most edges
+        let edges = [(0, 1, 2), (1, 2, 10), (2, 3, 2)];
+        let mates = Matcher::new(&edges, 4).solve();
+        assert_eq!(mates, vec![Some(1), Some(0), Some(3), Some(2)]);
+    }
+
+    #[test]
+    fn pair_by_standings() {
+        let players = spoof_players(8);
+        let Pairings { paired, rejected } =
+            matching_pairings(players.clone(), &HashMap::new(), &HashMap::new(), 2, 0);
+        assert!(rejected.is_empty());
+        assert_eq!(
+            paired,
+            players.chunks(2).map(|p| p.to_vec()).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn odd_player_out() {
+        let players = spoof_players(5);
+        let Pairings { paired, rejected } =
+            matching_pairings(players.clone(), &HashMap::new(), &HashMap::new(), 2, 0);
+        assert_eq!(paired.len(), 2);
+        assert_eq!(rejected, vec![players[4]]);
+    }
+
+    #[test]
+    fn beats_greedy() {
+        let players = spoof_players(4);
+        let opps = played(&[(players[2], players[3]), (players[0], players[3])]);
+
+        // Greedy pairing takes the first two players and strands the other two
+        let greedy = greedy_pairings(players.clone(), &opps, 2, 0);
+        assert_eq!(greedy.paired.len(), 1);
+
+        let Pairings { paired, rejected } =
+            matching_pairings(players.clone(), &HashMap::new(), &opps, 2, 0);
+        assert!(rejected.is_empty());
+        assert_eq!(
+            paired,
+            vec![vec![players[0], players[2]], vec![players[1], players[3]]]
+        );
+    }
+
+    #[test]
+    fn pair_within_point_groups() {
+        let players = spoof_players(6);
+        let points: HashMap<_, _> = players
+            .iter()
+            .zip([3, 3, 3, 3, 0, 0])
+            .map(|(p, pts)| (*p, r64::from_integer(pts)))
+            .collect();
+        let opps = played(&[(players[2], players[3])]);
+
+        // Greedy pairing takes the first two players, so the other two players with three points
+        // have to be paired against players without any points
+        let greedy = greedy_pairings(players.clone(), &opps, 2, 0);
+        assert!(greedy.rejected.is_empty());
+        assert!(greedy
+            .paired
+            .iter()
+            .any(|pair| points[&pair[0]] != points[&pair[1]]));
+
+        let Pairings { paired, rejected } =
+            matching_pairings(players.clone(), &points, &opps, 2, 0);
+        assert!(rejected.is_empty());
+        assert_eq!(
+            paired,
+            vec![
+                vec![players[0], players[2]],
+                vec![players[1], players[3]],
+                vec![players[4], players[5]]
+            ]
+        );
+    }
+
+    #[test]
+    fn repeats_only_when_needed() {
+        let players = spoof_players(4);
+        let opps = played(&[
+            (players[0], players[1]),
+            (players[0], players[2]),
+            (players[1], players[3]),
+        ]);
+
+        // The only pairings without repeats pair the top player with the bottom player
+        let Pairings { paired, rejected } =
+            matching_pairings(players.clone(), &HashMap::new(), &opps, 2, 2);
+        assert!(rejected.is_empty());
+        assert_eq!(
+            paired,
+            vec![vec![players[0], players[3]], vec![players[1], players[2]]]
+        );
+
+        // A repeat is used when there is no other option
+        let opps = played(&[(players[0], players[1]), (players[2], players[3])]);
+        let players = players[..2].to_vec();
+        let Pairings { paired, .. } =
+            matching_pairings(players.clone(), &HashMap::new(), &opps, 2, 2);
+        assert_eq!(paired, vec![players]);
+    }
+
+    #[test]
+    fn huge_point_spreads_do_not_overflow() {
+        let players = spoof_players(64);
+        let points: HashMap<_, _> = players
+            .iter()
+            .enumerate()
+            .map(|(i, p)| (*p, r64::new(i as i32 * 30_000_000, 1 + i as i32 % 7)))
+            .collect();
+        let Pairings { paired, rejected } =
+            matching_pairings(players.clone(), &points, &HashMap::new(), 2, 0);
+        assert!(rejected.is_empty());
+        assert_eq!(paired.len(), 32);
+    }
+}
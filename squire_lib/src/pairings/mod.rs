@@ -1,19 +1,26 @@
 use std::{
     collections::{HashMap, HashSet},
     fmt::Display,
+    hash::{Hash, Hasher},
     str::FromStr,
 };
 
 use chrono::{DateTime, Utc};
+use deterministic_hash::DeterministicHasher;
+use fxhash::FxHasher64;
+use itertools::Itertools;
 use serde::{Deserialize, Serialize};
 
 use crate::{
     error::TournamentError,
+    flights::Flights,
     identifiers::{PlayerId, RoundId},
     operations::OpResult,
     players::PlayerRegistry,
-    rounds::{Round, RoundContext, RoundRegistry},
+    r64,
+    rounds::{Round, RoundContext, RoundRegistry, TableAssignment},
     scoring::{Score, Standings},
+    seating::DraftPods,
     settings::{
         PairingCommonSettingsTree, PairingSetting, PairingSettingsTree, PairingStyleSetting,
         PairingStyleSettingsTree, SettingsTree,
@@ -26,16 +33,26 @@ pub mod fluid_pairings;
 /// The swiss pairing sytle
 pub mod swiss_pairings;
 
+/// The pairing audit module
+pub mod audit;
+/// The pairing constraints module
+pub mod constraints;
+
 /// The branching pairings module
 pub mod branching;
 /// The greedy pairings module
 pub mod greedy;
+/// The matching pairings module
+pub mod matching;
 /// The rotary pairings module
 pub mod rotary;
 
+pub use audit::PairingAudit;
 pub use branching::branching_pairings;
+pub use constraints::PairingConstraints;
 pub use fluid_pairings::FluidPairings;
 pub use greedy::greedy_pairings;
+pub use matching::matching_pairings;
 pub use rotary::rotary_pairings;
 pub use swiss_pairings::SwissPairings;
 
@@ -84,6 +101,8 @@ pub enum PairingAlgorithm {
     Branching,
     /// This variant corresponds to the `rotary_pairings` function
     Rotary,
+    /// This variant corresponds to the `matching_pairings` function
+    Matching,
 }
 
 /// An enum that encodes all the possible pairing systems a tournament can have.
@@ -95,6 +114,15 @@ pub struct PairingSystem {
     /// The style of pairings that is used
     #[serde(default = "default_style")]
     pub style: PairingStyle,
+    /// Groups of players that should not be paired against each other
+    #[serde(default)]
+    pub constraints: PairingConstraints,
+    /// The draft pods that the players have been split into, if any
+    #[serde(default)]
+    pub pods: DraftPods,
+    /// The secondary flights that players have been moved into, if any
+    #[serde(default)]
+    pub flights: Flights,
 }
 
 fn default_style() -> PairingStyle {
@@ -133,6 +161,47 @@ impl Pairings {
     pub fn is_valid(&self, opps: &HashMap<PlayerId, HashSet<PlayerId>>, repair_tol: u64) -> bool {
         !self.paired.iter().any(|p| count_opps(p, opps) > repair_tol)
     }
+
+    /// Rejected players receive byes. This attempts to give those byes to players that have not
+    /// had one yet by swapping rejected players who have had a bye with paired players who have
+    /// not. Swaps that lead to too many repeat opponents are only made if `strict` is set, in which
+    /// case repeat byes only happen when every candidate has already had a bye.
+    pub fn reassign_byes(
+        &mut self,
+        byes: &HashSet<PlayerId>,
+        opps: &HashMap<PlayerId, HashSet<PlayerId>>,
+        repair_tol: u64,
+        strict: bool,
+    ) {
+        for i in 0..self.rejected.len() {
+            let plyr = self.rejected[i];
+            if !byes.contains(&plyr) {
+                continue;
+            }
+            let find_swap = |tol: u64| {
+                // Later pairings hold the lower ranked players, which should get byes first
+                self.paired
+                    .iter()
+                    .enumerate()
+                    .rev()
+                    .find_map(|(m, pairing)| {
+                        pairing.iter().enumerate().rev().find_map(|(s, p)| {
+                            if byes.contains(p) {
+                                return None;
+                            }
+                            let mut buffer = pairing.clone();
+                            buffer[s] = plyr;
+                            (count_opps(&buffer, opps) <= tol).then_some((m, s))
+                        })
+                    })
+            };
+            let swap =
+                find_swap(repair_tol).or_else(|| strict.then(|| find_swap(u64::MAX)).flatten());
+            if let Some((m, s)) = swap {
+                self.rejected[i] = std::mem::replace(&mut self.paired[m][s], plyr);
+            }
+        }
+    }
 }
 
 impl PairingSystem {
@@ -140,15 +209,23 @@ impl PairingSystem {
     pub fn new(preset: TournamentPreset) -> Self {
         use TournamentPreset::*;
         let common = PairingCommonSettingsTree {
-            match_size: 2,
+            match_size: preset.match_size(),
             repair_tolerance: 0,
             algorithm: PairingAlgorithm::Branching,
+            table_assignment: TableAssignment::Sequential,
+            allow_repeat_byes: true,
         };
         let style: PairingStyle = match preset {
-            Swiss => SwissPairings::new().into(),
+            Swiss | Multiplayer => SwissPairings::new().into(),
             Fluid => FluidPairings::new().into(),
         };
-        PairingSystem { common, style }
+        PairingSystem {
+            common,
+            style,
+            constraints: PairingConstraints::new(),
+            pods: DraftPods::default(),
+            flights: Flights::default(),
+        }
     }
 
     /// Returns a copy of the current set of settings
@@ -159,6 +236,14 @@ impl PairingSystem {
         }
     }
 
+    /// Gives a player a new id, i.e. when a guest is linked to an account
+    pub(crate) fn swap_player_ids(&mut self, old: PlayerId, new: PlayerId) {
+        self.style.swap_player_ids(old, new);
+        self.constraints.swap_player_ids(old, new);
+        self.pods.swap_player_ids(old, new);
+        self.flights.swap_player_ids(old, new);
+    }
+
     /// Marks a player as ready to play in their next round
     pub fn ready_player(&mut self, id: PlayerId) {
         use PairingStyle::*;
@@ -195,6 +280,16 @@ impl PairingSystem {
         }
     }
 
+    /// Gets the round context for a new round between the given players, which records the flight
+    /// that the players share
+    pub fn context_for(&self, plyrs: &[PlayerId]) -> RoundContext {
+        let context = self.get_context();
+        match self.flights.shared_flight(plyrs) {
+            Some(flight) => context.combine(RoundContext::Flight(flight)),
+            None => context,
+        }
+    }
+
     /// Updates the inner pairing style with incoming pairings.
     pub fn update(&mut self, pairings: &Pairings) {
         use PairingStyle::*;
@@ -204,6 +299,49 @@ impl PairingSystem {
         }
     }
 
+    /// Calculates the seed used to shuffle players when the pairing algorithm is rerun. The seed
+    /// only depends on the state of the tournament, so every copy of the tournament creates the
+    /// same pairings.
+    pub fn seed(&self, rnd_reg: &RoundRegistry) -> u64 {
+        let mut hasher = DeterministicHasher::new(FxHasher64::default());
+        rnd_reg.rounds.len().hash(&mut hasher);
+        self.get_context().hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// Records the inputs that were used to create the given pairings
+    pub fn audit<S>(
+        &self,
+        plyr_reg: &PlayerRegistry,
+        rnd_reg: &RoundRegistry,
+        standings: Standings<S>,
+        pairings: Pairings,
+    ) -> PairingAudit
+    where
+        S: Score,
+    {
+        use PairingStyle::*;
+        let (standings, points) = match &self.style {
+            Swiss(_) => {
+                let points = standings.points();
+                let order = SwissPairings::pairing_order(plyr_reg, standings);
+                let points = order.iter().map(|p| points[p]).collect();
+                (order, points)
+            }
+            Fluid(sys) => (sys.pairing_order(), Vec::new()),
+        };
+        PairingAudit {
+            round_number: rnd_reg.pairing_round_count() + 1,
+            seed: self.seed(rnd_reg),
+            settings: self.common.clone(),
+            standings,
+            points,
+            constraints: self.constraints.clone(),
+            prior_byes: rnd_reg.bye_history().into_iter().sorted().collect(),
+            pairings,
+        }
+    }
+
     /// Attempts to create the next set of pairings
     pub fn pair<S>(
         &self,
@@ -215,9 +353,20 @@ impl PairingSystem {
         S: Score,
     {
         use PairingStyle::*;
+        let opps = self.constraints.apply(&rnd_reg.opponents);
+        let seed = self.seed(rnd_reg);
         match &self.style {
-            Swiss(sys) => sys.pair(&self.common, plyr_reg, rnd_reg, standings),
-            Fluid(sys) => sys.pair(&self.common, plyr_reg, rnd_reg),
+            Swiss(sys) => sys.pair(
+                &self.common,
+                plyr_reg,
+                rnd_reg,
+                &opps,
+                standings,
+                &self.pods,
+                &self.flights,
+                seed,
+            ),
+            Fluid(sys) => sys.pair(&self.common, plyr_reg, &opps),
         }
     }
 
@@ -235,7 +384,9 @@ impl PairingStyle {
     /// Creates empty pairings
     pub fn new(preset: TournamentPreset) -> Self {
         match preset {
-            TournamentPreset::Swiss => Self::Swiss(Default::default()),
+            TournamentPreset::Swiss | TournamentPreset::Multiplayer => {
+                Self::Swiss(Default::default())
+            }
             TournamentPreset::Fluid => Self::Fluid(Default::default()),
         }
     }
@@ -248,6 +399,13 @@ impl PairingStyle {
         }
     }
 
+    pub(crate) fn swap_player_ids(&mut self, old: PlayerId, new: PlayerId) {
+        match self {
+            PairingStyle::Swiss(style) => style.swap_player_ids(old, new),
+            PairingStyle::Fluid(style) => style.swap_player_ids(old, new),
+        }
+    }
+
     /// Attempts to update the settings of the held pairing style
     pub fn update(&mut self, setting: PairingStyleSetting) -> OpResult {
         match (self, setting) {
@@ -263,16 +421,23 @@ impl PairingStyle {
 }
 
 impl PairingAlgorithm {
-    /// Returns a closure that contains the function that coresponds to the algorithm.
-    pub fn as_alg(
+    /// Pairs the players, who are given in pairing order, using the function that corresponds to
+    /// the algorithm. Only the matching algorithm uses the players' points; the other algorithms
+    /// only use the order of the players.
+    pub fn pair(
         &self,
-    ) -> impl FnOnce(Vec<PlayerId>, &HashMap<PlayerId, HashSet<PlayerId>>, usize, u64) -> Pairings
-    {
+        plyrs: Vec<PlayerId>,
+        points: &HashMap<PlayerId, r64>,
+        opps: &HashMap<PlayerId, HashSet<PlayerId>>,
+        match_size: usize,
+        repair_tol: u64,
+    ) -> Pairings {
         use PairingAlgorithm::*;
         match self {
-            Greedy => greedy_pairings,
-            Branching => branching_pairings,
-            Rotary => rotary_pairings,
+            Greedy => greedy_pairings(plyrs, opps, match_size, repair_tol),
+            Branching => branching_pairings(plyrs, opps, match_size, repair_tol),
+            Rotary => rotary_pairings(plyrs, opps, match_size, repair_tol),
+            Matching => matching_pairings(plyrs, points, opps, match_size, repair_tol),
         }
     }
 }
@@ -284,6 +449,7 @@ impl Display for PairingAlgorithm {
             Greedy => write!(f, "Greedy"),
             Branching => write!(f, "Branching"),
             Rotary => write!(f, "Rotary"),
+            Matching => write!(f, "Matching"),
         }
     }
 }
@@ -300,6 +466,19 @@ impl From<FluidPairings> for PairingStyle {
     }
 }
 
+/// Shuffles the players using the given state, which is advanced along the way. This is a
+/// Fisher-Yates shuffle driven by splitmix64, so the same state always gives the same order.
+pub(crate) fn shuffle(plyrs: &mut [PlayerId], state: &mut u64) {
+    for i in (1..plyrs.len()).rev() {
+        *state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = *state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^= z >> 31;
+        plyrs.swap(i, (z % (i as u64 + 1)) as usize);
+    }
+}
+
 /// Calculates the number of repeat opponents there are in a set of players
 pub fn count_opps(plyrs: &[PlayerId], opps: &HashMap<PlayerId, HashSet<PlayerId>>) -> u64 {
     let mut digest = 0;
@@ -321,6 +500,7 @@ impl FromStr for PairingAlgorithm {
             "Greedy" | "greedy" => Ok(Self::Greedy),
             "Branching" | "branching" => Ok(Self::Branching),
             "Rotary" | "rotary" => Ok(Self::Rotary),
+            "Matching" | "matching" => Ok(Self::Matching),
             _ => Err("Unable to convert string to pairing algorithm".to_owned()),
         }
     }
@@ -1,16 +1,19 @@
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashMap, HashSet};
 
-use cycle_map::GroupMap;
 use serde::{Deserialize, Serialize};
+use serde_with::serde_as;
 
 use crate::{
+    boilerplate::ordered::SortedSet,
+    flights::Flights,
     identifiers::PlayerId,
     operations::OpResult,
-    pairings::Pairings,
+    pairings::{shuffle, Pairings},
     players::PlayerRegistry,
     r64,
     rounds::{RoundContext, RoundRegistry},
     scoring::{Score, Standings},
+    seating::DraftPods,
     settings::{
         PairingCommonSettingsTree, SettingsTree, SwissPairingSetting, SwissPairingSettingsTree,
     },
@@ -22,11 +25,13 @@ pub struct SwissContext {
     swiss_round_number: u8,
 }
 
+#[serde_as]
 #[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq)]
 /// Swiss pairings are the "traditional" pairings system for Magic tournaments
 pub struct SwissPairings {
     #[serde(default)]
     settings: SwissPairingSettingsTree,
+    #[serde_as(as = "SortedSet")]
     check_ins: HashSet<PlayerId>,
     #[serde(default)]
     swiss_round_number: u8,
@@ -62,6 +67,12 @@ impl SwissPairings {
         _ = self.check_ins.remove(&plyr);
     }
 
+    pub(crate) fn swap_player_ids(&mut self, old: PlayerId, new: PlayerId) {
+        if self.check_ins.remove(&old) {
+            _ = self.check_ins.insert(new);
+        }
+    }
+
     /// Updates a single pairings setting
     pub fn update_setting(&mut self, setting: SwissPairingSetting) -> OpResult {
         self.settings.update(setting)
@@ -74,7 +85,7 @@ impl SwissPairings {
         plyr_reg: &PlayerRegistry,
         rnd_reg: &RoundRegistry,
     ) -> bool {
-        let SwissPairingSettingsTree { do_checkins } = self.settings;
+        let SwissPairingSettingsTree { do_checkins, .. } = self.settings;
         let count = plyr_reg.active_player_count();
         let mut digest = rnd_reg.active_round_count() == 0;
         digest &= count >= match_size;
@@ -104,55 +115,108 @@ impl SwissPairings {
         }
     }
 
-    /// Attempts to create the next set of pairings.
+    /// Orders the players that can be paired from first place to last place in the standings
+    pub fn pairing_order<S>(players: &PlayerRegistry, mut standings: Standings<S>) -> Vec<PlayerId>
+    where
+        S: Score,
+    {
+        standings
+            .scores
+            .drain(0..)
+            .filter_map(|(p, _)| players.get_player(&p).ok()?.can_play().then_some(p))
+            .rev()
+            .collect()
+    }
+
+    /// Attempts to create the next set of pairings. The given opponents are used to penalize
+    /// pairings, so they can include more than the players' past opponents. If some players can
+    /// not be paired, the players are shuffled using the given seed and paired again. Each flight
+    /// is paired on its own. If pairings are kept within draft pods, each pod is paired on its own
+    /// too, and players that were not seated at a pod are paired together.
     /// NOTE: This does not create new rounds, only pairings
+    #[allow(clippy::too_many_arguments)]
     pub fn pair<S>(
         &self,
         common: &PairingCommonSettingsTree,
         players: &PlayerRegistry,
         matches: &RoundRegistry,
-        mut standings: Standings<S>,
+        opps: &HashMap<PlayerId, HashSet<PlayerId>>,
+        standings: Standings<S>,
+        pods: &DraftPods,
+        flights: &Flights,
+        seed: u64,
     ) -> Option<Pairings>
     where
         S: Score,
     {
+        if !self.ready_to_pair(common.match_size as usize, players, matches) {
+            return None;
+        }
+        let points = standings.points();
+        let plyrs = Self::pairing_order(players, standings);
+        let within_pods = self.settings.pair_within_pods && !pods.is_empty();
+        if !within_pods && flights.is_empty() {
+            return Some(Self::pair_group(
+                common, matches, opps, &points, plyrs, seed,
+            ));
+        }
+        // Players are grouped by flight (the main event first) and then by pod
+        let mut groups: BTreeMap<(usize, usize), Vec<PlayerId>> = BTreeMap::new();
+        for p in plyrs {
+            let flight = flights.flight_of(&p).unwrap_or_default();
+            // Players without a pod are put in the last group
+            let pod = if within_pods {
+                pods.pod_of(&p).map_or(pods.pods.len(), |pod| pod - 1)
+            } else {
+                0
+            };
+            groups.entry((flight, pod)).or_default().push(p);
+        }
+        let mut pairings = Pairings::new();
+        for group in groups.into_values() {
+            let Pairings { paired, rejected } =
+                Self::pair_group(common, matches, opps, &points, group, seed);
+            pairings.paired.extend(paired);
+            pairings.rejected.extend(rejected);
+        }
+        Some(pairings)
+    }
+
+    /// Pairs a group of players, who are given in pairing order
+    fn pair_group(
+        common: &PairingCommonSettingsTree,
+        matches: &RoundRegistry,
+        opps: &HashMap<PlayerId, HashSet<PlayerId>>,
+        points: &HashMap<PlayerId, r64>,
+        plyrs: Vec<PlayerId>,
+        seed: u64,
+    ) -> Pairings {
         let PairingCommonSettingsTree {
             match_size,
             repair_tolerance,
             algorithm,
+            allow_repeat_byes,
+            ..
         } = common;
-        if !self.ready_to_pair(*match_size as usize, players, matches) {
-            return None;
-        }
-        let plyrs_and_scores: Vec<(PlayerId, r64)> = standings
-            .scores
-            .drain(0..)
-            .filter_map(|(p, s)| {
-                players
-                    .get_player(&p)
-                    .ok()?
-                    .can_play()
-                    .then(|| (p, s.primary_score()))
-            })
-            .rev()
-            .collect();
-        let mut plyrs: Vec<PlayerId> = plyrs_and_scores.iter().map(|(p, _)| p).cloned().collect();
-        let mut pairings = (*algorithm).as_alg()(
-            std::mem::take(&mut plyrs),
-            &matches.opponents,
+        let mut pairings = algorithm.pair(
+            plyrs.clone(),
+            points,
+            opps,
             *match_size as usize,
             *repair_tolerance,
         );
 
+        let mut state = seed;
         for _ in 0..100 {
             if pairings.rejected.is_empty() {
                 break;
             }
-            let grouped_plyrs: GroupMap<_, _> = plyrs_and_scores.iter().cloned().collect();
-            plyrs.extend(grouped_plyrs.iter().filter_map(|(plyr, _)| plyr).cloned());
-            let buffer = (*algorithm).as_alg()(
-                std::mem::take(&mut plyrs),
-                &matches.opponents,
+            let mut buffer = plyrs.clone();
+            shuffle(&mut buffer, &mut state);
+            let buffer = algorithm.pair(
+                buffer,
+                points,
+                opps,
                 *match_size as usize,
                 *repair_tolerance,
             );
@@ -160,6 +224,12 @@ impl SwissPairings {
                 pairings = buffer;
             }
         }
-        Some(pairings)
+        pairings.reassign_byes(
+            &matches.bye_history(),
+            opps,
+            *repair_tolerance,
+            !*allow_repeat_byes,
+        );
+        pairings
     }
 }
@@ -0,0 +1,119 @@
+use std::collections::BTreeMap;
+
+use serde_json::Value;
+use sha1::{Digest, Sha1};
+
+use super::Deck;
+
+/// The digits that Cockatrice uses to write deck hashes
+const BASE32_DIGITS: &[u8; 32] = b"0123456789abcdefghijklmnopqrstuv";
+
+/// The most cards that a deck can have. Deck hashes list every copy of every card, so larger decks
+/// are turned away when they are added.
+pub const MAX_DECK_SIZE: u64 = 10_000;
+
+/// Calculates the deck's hash in the format used by Cockatrice (e.g. "6og0ej32"). Two decks have
+/// the same hash if they have the same cards in their main decks and sideboards, regardless of the
+/// printings of those cards. This lets a judge compare a deck that is being played against the
+/// deck that was submitted without seeing the submitted list.
+pub fn deck_hash(deck: &Deck) -> String {
+    let deck = serde_json::to_value(deck).unwrap_or_default();
+    cockatrice_hash(cards(&deck))
+}
+
+/// Counts the cards in the deck, including its sideboard and commanders
+pub fn deck_size(deck: &Deck) -> u64 {
+    let deck = serde_json::to_value(deck).unwrap_or_default();
+    cards(&deck).fold(0, |acc, (_, _, count)| acc.saturating_add(count))
+}
+
+/// Lists the cards of a deck by whether they are in the sideboard, their name, and their number of
+/// copies. Cockatrice keeps commanders in the main deck.
+fn cards(deck: &Value) -> impl Iterator<Item = (bool, &str, u64)> {
+    let board = move |board: &'static str, side: bool| {
+        deck[board]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(move |card| {
+                let name = card["name"].as_str()?;
+                let count = card["count"].as_u64().unwrap_or(1);
+                Some((side, name, count))
+            })
+    };
+    board("mainBoard", false)
+        .chain(board("commander", false))
+        .chain(board("sideBoard", true))
+}
+
+/// Hashes a list of cards, which are given by whether they are in the sideboard, their name, and
+/// their number of copies. Each copy is listed by its lowercased name, with sideboard cards
+/// prefixed by "SB:". The sorted list is joined by semicolons and hashed with SHA-1. The first 40
+/// bits of the hash are written in base 32.
+///
+/// The list is fed to the hasher one copy at a time rather than being built up front, and at most
+/// [MAX_DECK_SIZE] copies of each card are listed.
+fn cockatrice_hash<'a, I>(cards: I) -> String
+where
+    I: IntoIterator<Item = (bool, &'a str, u64)>,
+{
+    let mut counts: BTreeMap<String, u64> = BTreeMap::new();
+    for (side, name, count) in cards {
+        let entry = format!("{}{}", if side { "SB:" } else { "" }, name.to_lowercase());
+        let total = counts.entry(entry).or_default();
+        *total = total.saturating_add(count);
+    }
+    let mut hasher = Sha1::new();
+    let mut first = true;
+    for (entry, count) in counts {
+        for _ in 0..count.min(MAX_DECK_SIZE) {
+            if !first {
+                hasher.update(b";");
+            }
+            first = false;
+            hasher.update(entry.as_bytes());
+        }
+    }
+    let digest = hasher.finalize();
+    let mut number = digest[..5]
+        .iter()
+        .fold(0u64, |acc, byte| (acc << 8) | u64::from(*byte));
+    let mut hash = [b'0'; 8];
+    for digit in hash.iter_mut().rev() {
+        *digit = BASE32_DIGITS[(number % 32) as usize];
+        number /= 32;
+    }
+    hash.iter().map(|d| char::from(*d)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{cockatrice_hash, MAX_DECK_SIZE};
+
+    #[test]
+    fn cockatrice_hashes() {
+        let cards = [
+            (false, "Lightning Bolt", 4),
+            (true, "Abrade", 2),
+            (false, "Mountain", 20),
+        ];
+        assert_eq!(cockatrice_hash(cards), "6og0ej32");
+        // The order of the list and the capitalization of names do not matter
+        let cards = [
+            (false, "mountain", 20),
+            (true, "ABRADE", 2),
+            (false, "Lightning Bolt", 3),
+            (false, "Lightning Bolt", 1),
+        ];
+        assert_eq!(cockatrice_hash(cards), "6og0ej32");
+        assert_ne!(cockatrice_hash([(false, "Abrade", 2)]), "6og0ej32");
+    }
+
+    #[test]
+    fn huge_card_counts_are_capped() {
+        assert_eq!(
+            cockatrice_hash([(false, "Island", u64::MAX)]),
+            cockatrice_hash([(false, "Island", MAX_DECK_SIZE)])
+        );
+    }
+}
@@ -12,9 +12,16 @@ use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 pub use crate::identifiers::PlayerId;
-use crate::{accounts::SquireAccount, error::TournamentError, identifiers::id_from_item};
+use crate::{
+    accounts::{SharingPermissions, SquireAccount},
+    error::TournamentError,
+    identifiers::id_from_item,
+    settings::PublicNameStyle,
+};
 
+mod deck_hash;
 mod player_registry;
+pub use deck_hash::{deck_hash, deck_size, MAX_DECK_SIZE};
 pub use player_registry::PlayerRegistry;
 
 #[derive(
@@ -28,6 +35,10 @@ pub enum PlayerStatus {
     Registered,
     /// The player has been dropped from the tournament
     Dropped,
+    /// The player is registered as an alternate and is not a part of the main field
+    Alternate,
+    /// The player registered after the tournament was full and is waiting for a spot to open
+    Waitlisted,
 }
 
 //#[serde_as]
@@ -51,8 +62,24 @@ pub struct Player {
     //#[serde_as(as = "Seq<(_, _)>")]
     #[serde(skip_deserializing, skip_serializing, default)]
     pub decks: HashMap<String, Deck>,
+    /// The Cockatrice hashes of the player's registered decks, by deck name. The hashes are
+    /// withheld along with the decks, since a hash can be matched against known lists.
+    #[serde(default)]
+    pub deck_hashes: HashMap<String, String>,
     /// The player's status
     pub status: PlayerStatus,
+    /// How much about the player can be shown to the public. Guests share everything.
+    #[serde(default)]
+    pub sharing: SharingPermissions,
+    /// The order that the player joined the tournament in, starting at 1. Players that don't share
+    /// their names are shown by it. Players that joined before players were numbered have a 0
+    /// until the registry numbers them.
+    #[serde(default)]
+    pub number: u32,
+    /// Whether the player was registered without an account. Only guests can be linked to an
+    /// account. Guests that registered before this was recorded are not marked.
+    #[serde(default)]
+    pub guest: bool,
 }
 
 impl Player {
@@ -73,7 +100,11 @@ impl Player {
             game_name: None,
             deck_ordering: Vec::new(),
             decks: HashMap::new(),
+            deck_hashes: HashMap::new(),
             status: PlayerStatus::Registered,
+            sharing: SharingPermissions::Everything,
+            number: 0,
+            guest: false,
         }
     }
 
@@ -89,12 +120,61 @@ impl Player {
             game_name: Some(account.get_display_name()),
             deck_ordering: Vec::new(),
             decks: HashMap::new(),
+            deck_hashes: HashMap::new(),
             status: PlayerStatus::Registered,
+            sharing: account.permissions,
+            number: 0,
+            guest: false,
+        }
+    }
+
+    /// Returns the name that is shown in place of the player's name when they don't share it. The
+    /// name only tells players apart by when they joined, so it reveals nothing about their
+    /// accounts.
+    pub fn anonymous_name(&self) -> String {
+        format!("Anonymous {}", self.number)
+    }
+
+    /// Returns the player's name in the given style. Players that don't share their name are always
+    /// shown anonymously.
+    pub fn public_name(&self, style: PublicNameStyle) -> String {
+        if !self.sharing.shares_name() {
+            return self.anonymous_name();
+        }
+        match style {
+            PublicNameStyle::FullName => self.name.clone(),
+            PublicNameStyle::FirstNameInitial => {
+                let mut names = self.name.split_whitespace();
+                let first = names.next().unwrap_or_default();
+                match names.last().and_then(|last| last.chars().next()) {
+                    Some(initial) => format!("{first} {initial}."),
+                    None => first.to_owned(),
+                }
+            }
+            PublicNameStyle::Alias => self.anonymous_name(),
+        }
+    }
+
+    /// Removes everything that the player chose not to share, so that the player can be shown to
+    /// the public
+    pub fn redact(&mut self) {
+        if !self.sharing.shares_name() {
+            self.name = self.anonymous_name();
+            self.game_name = None;
+        }
+        if !self.sharing.shares_deck_list() {
+            self.decks.clear();
+            self.deck_hashes.clear();
+        }
+        if !self.sharing.shares_deck_name() {
+            self.deck_ordering.clear();
+            self.deck_hashes.clear();
         }
     }
 
     /// Adds a deck to the player
     pub fn add_deck(&mut self, name: String, deck: Deck) {
+        _ = self.deck_hashes.insert(name.clone(), deck_hash(&deck));
         _ = self.decks.insert(name.clone(), deck);
         self.deck_ordering.retain(|n| n != &name);
         self.deck_ordering.push(name);
@@ -111,6 +191,7 @@ impl Player {
             .decks
             .remove(&name)
             .ok_or(TournamentError::DeckLookup)?;
+        _ = self.deck_hashes.remove(&name);
         self.deck_ordering.retain(|n| n != &name);
         Ok(())
     }
@@ -124,6 +205,16 @@ impl Player {
     pub fn can_play(&self) -> bool {
         self.status == PlayerStatus::Registered
     }
+
+    /// Calculates if the player is an alternate
+    pub fn is_alternate(&self) -> bool {
+        self.status == PlayerStatus::Alternate
+    }
+
+    /// Calculates if the player is on the waitlist
+    pub fn is_waitlisted(&self) -> bool {
+        self.status == PlayerStatus::Waitlisted
+    }
 }
 
 impl Display for PlayerStatus {
@@ -134,6 +225,8 @@ impl Display for PlayerStatus {
             match self {
                 PlayerStatus::Registered => "Registered",
                 PlayerStatus::Dropped => "Dropped",
+                PlayerStatus::Alternate => "Alternate",
+                PlayerStatus::Waitlisted => "Waitlisted",
             }
         )
     }
@@ -157,6 +250,8 @@ impl FromStr for PlayerStatus {
         match s {
             "Active" | "active" | "Registered" | "registered" => Ok(Self::Registered),
             "Dropped" | "dropped" => Ok(Self::Dropped),
+            "Alternate" | "alternate" => Ok(Self::Alternate),
+            "Waitlisted" | "waitlisted" => Ok(Self::Waitlisted),
             _ => Err(PlayerStatusParseError),
         }
     }
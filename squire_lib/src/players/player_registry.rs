@@ -2,11 +2,16 @@ use std::collections::{HashMap, HashSet};
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use serde_with::{serde_as, Seq};
-use TournamentError::{PlayerAlreadyRegistered, PlayerNotFound};
+use serde_with::serde_as;
+use TournamentError::{
+    PlayerAlreadyRegistered, PlayerNotAlternate, PlayerNotDropped, PlayerNotFound,
+    PlayerNotWaitlisted,
+};
 
 use crate::{
     accounts::SquireAccount,
+    boilerplate::ordered::{SortedSeq, SortedSet},
+    collation::NameCollator,
     error::TournamentError,
     identifiers::PlayerId,
     players::{Player, PlayerStatus},
@@ -19,13 +24,20 @@ pub struct PlayerRegistry {
     /// A lookup table between player ids and their names
     // TODO: We don't need this. A GroupMap between PlayerIdentifiers and Players would suffice for
     // the players field
-    #[serde_as(as = "Seq<(_, _)>")]
+    #[serde_as(as = "SortedSeq")]
     pub name_and_id: HashMap<String, PlayerId>,
     /// All players in a tournament
-    #[serde_as(as = "Seq<(_, _)>")]
+    #[serde_as(as = "SortedSeq")]
     pub players: HashMap<PlayerId, Player>,
     /// A map of players that have checked into the tournament for registration
+    #[serde_as(as = "SortedSet")]
     pub(crate) check_ins: HashSet<PlayerId>,
+    /// The alternates of the tournament, in the order that they should be promoted
+    #[serde(default)]
+    pub(crate) alternates: Vec<PlayerId>,
+    /// The players waiting for a spot in the tournament, in the order that they registered
+    #[serde(default)]
+    pub(crate) waitlist: Vec<PlayerId>,
 }
 
 /*
@@ -48,6 +60,8 @@ impl PlayerRegistry {
             name_and_id: HashMap::new(),
             players: HashMap::new(),
             check_ins: HashSet::new(),
+            alternates: Vec::new(),
+            waitlist: Vec::new(),
         }
     }
 
@@ -56,6 +70,13 @@ impl PlayerRegistry {
         self.players.keys().cloned().collect()
     }
 
+    /// Returns all players, sorted by name using the given collator
+    pub fn get_players_by_name(&self, collator: &NameCollator) -> Vec<&Player> {
+        let mut digest: Vec<&Player> = self.players.values().collect();
+        collator.sort_by_name(&mut digest, |p| p.name.as_str());
+        digest
+    }
+
     /// Checks in a player for registration
     pub fn check_in(&mut self, id: PlayerId) -> Result<(), TournamentError> {
         if self.players.contains_key(&id) {
@@ -84,6 +105,159 @@ impl PlayerRegistry {
             .count()
     }
 
+    /// Calculates the number of alternates that have checked in
+    pub fn count_alternate_check_ins(&self) -> usize {
+        self.alternates
+            .iter()
+            .filter(|id| self.is_checked_in(id))
+            .count()
+    }
+
+    /// Calculates if a player is an alternate
+    pub fn is_alternate(&self, id: &PlayerId) -> bool {
+        self.players.get(id).is_some_and(Player::is_alternate)
+    }
+
+    /// Returns the alternates in the order that they should be promoted
+    pub fn get_alternates(&self) -> &[PlayerId] {
+        &self.alternates
+    }
+
+    /// Returns the next alternate that should be promoted. Alternates that have checked in are
+    /// prefered over those that have not.
+    pub fn next_alternate(&self) -> Option<PlayerId> {
+        self.alternates
+            .iter()
+            .find(|id| self.is_checked_in(id))
+            .or_else(|| self.alternates.first())
+            .copied()
+    }
+
+    /// Moves a player out of the main field and into the list of alternates
+    pub fn make_alternate(&mut self, id: PlayerId) -> Result<(), TournamentError> {
+        self.get_mut_player(&id)?
+            .update_status(PlayerStatus::Alternate);
+        self.waitlist.retain(|p| *p != id);
+        if !self.alternates.contains(&id) {
+            self.alternates.push(id);
+        }
+        Ok(())
+    }
+
+    /// Moves an alternate into the main field
+    pub fn promote_alternate(&mut self, id: &PlayerId) -> Result<(), TournamentError> {
+        let plyr = self.get_mut_player(id)?;
+        if !plyr.is_alternate() {
+            return Err(PlayerNotAlternate);
+        }
+        plyr.update_status(PlayerStatus::Registered);
+        self.alternates.retain(|p| p != id);
+        Ok(())
+    }
+
+    /// Calculates if a player is on the waitlist
+    pub fn is_waitlisted(&self, id: &PlayerId) -> bool {
+        self.players.get(id).is_some_and(Player::is_waitlisted)
+    }
+
+    /// Returns the waitlisted players in the order that they should be promoted
+    pub fn get_waitlist(&self) -> &[PlayerId] {
+        &self.waitlist
+    }
+
+    /// Moves a player to the back of the waitlist
+    pub fn waitlist_player(&mut self, id: PlayerId) -> Result<(), TournamentError> {
+        self.get_mut_player(&id)?
+            .update_status(PlayerStatus::Waitlisted);
+        self.alternates.retain(|p| *p != id);
+        if !self.waitlist.contains(&id) {
+            self.waitlist.push(id);
+        }
+        Ok(())
+    }
+
+    /// Moves a player from the waitlist into the tournament
+    pub fn promote_from_waitlist(&mut self, id: &PlayerId) -> Result<(), TournamentError> {
+        let plyr = self.get_mut_player(id)?;
+        if !plyr.is_waitlisted() {
+            return Err(PlayerNotWaitlisted);
+        }
+        plyr.update_status(PlayerStatus::Registered);
+        self.waitlist.retain(|p| p != id);
+        Ok(())
+    }
+
+    /// Removes a player from the alternates and the waitlist
+    fn remove_from_queues(&mut self, id: &PlayerId) {
+        self.alternates.retain(|p| p != id);
+        self.waitlist.retain(|p| p != id);
+    }
+
+    /// Returns a re-registering player to the tournament. Waitlisted players keep their place in
+    /// the waitlist instead of being moved to the back of it.
+    fn readmit_player(&mut self, id: &PlayerId) {
+        let Some(plyr) = self.players.get_mut(id) else {
+            return;
+        };
+        if !plyr.is_waitlisted() {
+            plyr.status = PlayerStatus::Registered;
+            self.remove_from_queues(id);
+        }
+    }
+
+    /// Gives a player a new id, i.e. when a guest is linked to an account
+    pub(crate) fn swap_player_ids(&mut self, old: PlayerId, new: PlayerId) {
+        let Some(mut plyr) = self.players.remove(&old) else {
+            return;
+        };
+        plyr.id = new;
+        _ = self.players.insert(new, plyr);
+        self.name_and_id
+            .values_mut()
+            .chain(self.alternates.iter_mut())
+            .chain(self.waitlist.iter_mut())
+            .filter(|p| **p == old)
+            .for_each(|p| *p = new);
+        if self.check_ins.remove(&old) {
+            _ = self.check_ins.insert(new);
+        }
+    }
+
+    /// Merges a duplicate player into another player. The duplicate's decks (and their hashes)
+    /// are moved over, unless the other player has a deck of the same name, and the duplicate is
+    /// removed.
+    pub(crate) fn merge_players(&mut self, old: PlayerId, new: PlayerId) {
+        let Some(Player {
+            deck_ordering,
+            mut decks,
+            mut deck_hashes,
+            ..
+        }) = self.players.remove(&old)
+        else {
+            return;
+        };
+        if let Some(plyr) = self.players.get_mut(&new) {
+            for name in deck_ordering {
+                if plyr.deck_ordering.contains(&name) {
+                    continue;
+                }
+                if let Some(deck) = decks.remove(&name) {
+                    _ = plyr.decks.insert(name.clone(), deck);
+                }
+                if let Some(hash) = deck_hashes.remove(&name) {
+                    _ = plyr.deck_hashes.insert(name.clone(), hash);
+                }
+                plyr.deck_ordering.push(name);
+            }
+        }
+        self.name_and_id.retain(|_, id| *id != old);
+        self.alternates.retain(|id| *id != old);
+        self.waitlist.retain(|id| *id != old);
+        if self.check_ins.remove(&old) {
+            _ = self.check_ins.insert(new);
+        }
+    }
+
     /// Calculates if there are no players registered
     pub fn is_empty(&self) -> bool {
         self.players.is_empty()
@@ -115,8 +289,9 @@ impl PlayerRegistry {
         match self.players.get_mut(&(account.id.0.into())) {
             Some(player) => {
                 // Re-registering
-                player.status = PlayerStatus::Registered;
-                Ok(account.id.0.into())
+                let id = player.id;
+                self.readmit_player(&id);
+                Ok(id)
             }
             None => {
                 // Not re-registering
@@ -126,7 +301,8 @@ impl PlayerRegistry {
                 else {
                     return Err(TournamentError::NameTaken);
                 };
-                let plyr = Player::from_account(account);
+                let mut plyr = Player::from_account(account);
+                plyr.number = self.next_number();
                 let digest = Ok(plyr.id);
                 _ = self.name_and_id.insert(name, plyr.id);
                 _ = self.players.insert(plyr.id, plyr);
@@ -135,6 +311,45 @@ impl PlayerRegistry {
         }
     }
 
+    /// Numbers the players that joined before players were numbered, in the order of their ids
+    fn number_players(&mut self) {
+        let mut next = self.players.values().map(|p| p.number).max().unwrap_or(0);
+        let mut unnumbered: Vec<&mut Player> = self
+            .players
+            .values_mut()
+            .filter(|p| p.number == 0)
+            .collect();
+        unnumbered.sort_by_key(|p| p.id);
+        for plyr in unnumbered {
+            next += 1;
+            plyr.number = next;
+        }
+    }
+
+    /// Returns the number of the next player to join the tournament
+    fn next_number(&mut self) -> u32 {
+        self.number_players();
+        self.players.values().map(|p| p.number).max().unwrap_or(0) + 1
+    }
+
+    /// Removes everything that the players chose not to share, so that the registry can be shown
+    /// to the public
+    pub fn redact(&mut self) {
+        self.number_players();
+        self.players.values_mut().for_each(Player::redact);
+        self.name_and_id.retain(|_, id| {
+            self.players
+                .get(id)
+                .map_or(true, |p| p.sharing.shares_name())
+        });
+        self.name_and_id.extend(
+            self.players
+                .values()
+                .filter(|p| !p.sharing.shares_name())
+                .map(|p| (p.name.clone(), p.id)),
+        );
+    }
+
     /// Creates a new player
     pub fn register_player(&mut self, account: SquireAccount) -> Result<PlayerId, TournamentError> {
         self.register_player_with_name(account, None)
@@ -152,6 +367,8 @@ impl PlayerRegistry {
         } else {
             let mut plyr = Player::new(name.clone());
             plyr.id = Player::create_guest_id(salt, &name);
+            plyr.number = self.next_number();
+            plyr.guest = true;
             let digest = Ok(plyr.id);
             _ = self.name_and_id.insert(name, plyr.id);
             _ = self.players.insert(plyr.id, plyr);
@@ -160,19 +377,32 @@ impl PlayerRegistry {
     }
 
     /// Creates a new player without an account
-    pub fn reregister_guest(&mut self, name: String) -> Result<(), TournamentError> {
-        self.name_and_id
+    pub fn reregister_guest(&mut self, name: String) -> Result<PlayerId, TournamentError> {
+        let plyr = self
+            .name_and_id
             .get(&name)
             .and_then(|id| self.players.get_mut(id))
-            .ok_or(PlayerNotFound)?
-            .status = PlayerStatus::Registered;
-        Ok(())
+            .ok_or(PlayerNotFound)?;
+        let id = plyr.id;
+        self.readmit_player(&id);
+        Ok(id)
     }
 
     /// Sets the specified player's status to `Dropped`
     pub fn drop_player(&mut self, id: &PlayerId) -> Result<(), TournamentError> {
         self.get_mut_player(id)?
             .update_status(PlayerStatus::Dropped);
+        self.remove_from_queues(id);
+        Ok(())
+    }
+
+    /// Returns a dropped player to the tournament
+    pub fn reinstate_player(&mut self, id: &PlayerId) -> Result<(), TournamentError> {
+        let plyr = self.get_mut_player(id)?;
+        if plyr.status != PlayerStatus::Dropped {
+            return Err(PlayerNotDropped);
+        }
+        plyr.update_status(PlayerStatus::Registered);
         Ok(())
     }
 
@@ -0,0 +1,281 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::TournamentError,
+    identifiers::PlayerId,
+    r64,
+    scoring::{StandardScore, Standings},
+};
+
+/// How a tournament's prize pool is paid out. Amounts are whole units of whatever the tournament
+/// awards (e.g. cents or booster packs).
+#[derive(Serialize, Deserialize, Debug, Hash, Clone, PartialEq, Eq)]
+pub struct PrizeStructure {
+    /// The total amount that is paid out
+    pub pool: u64,
+    /// How the pool is divided between the players
+    pub distribution: PrizeDistribution,
+    /// Whether players that finish with the same match points (i.e. after an intentional draw)
+    /// split their combined prizes evenly
+    #[serde(default)]
+    pub split_ties: bool,
+}
+
+/// How a prize pool is divided between the players
+#[derive(Serialize, Deserialize, Debug, Hash, Clone, PartialEq, Eq)]
+pub enum PrizeDistribution {
+    /// Each place is given a share of the pool, starting with first place. Places beyond the end
+    /// of the list are not paid, and the shares of places that no one finished in are not paid
+    /// out.
+    ByPlacement(Vec<u32>),
+    /// Each player is given the share of the best record prize that they qualified for, and the
+    /// pool is split in proportion to the players' shares
+    ByRecord(Vec<RecordPrize>),
+}
+
+/// The prize for finishing with at least a given number of match points
+#[derive(Serialize, Deserialize, Debug, Hash, Clone, Copy, PartialEq, Eq)]
+pub struct RecordPrize {
+    /// The fewest match points that qualify for the prize
+    pub min_points: u32,
+    /// The share of the pool that each qualifying player is given
+    pub share: u32,
+}
+
+/// The amount that a player is paid, as calculated by [`PrizeStructure::payouts`]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Payout {
+    /// The player's id
+    pub player: PlayerId,
+    /// The player's place in the standings, starting at 1
+    pub place: usize,
+    /// The amount that the player is paid
+    pub amount: u64,
+    /// Whether the player's prize was split with players that finished on the same record
+    pub split: bool,
+}
+
+impl PrizeStructure {
+    /// Checks that the distribution pays out something. Record prizes also need distinct point
+    /// thresholds.
+    pub fn validate(&self) -> Result<(), TournamentError> {
+        let valid = match &self.distribution {
+            PrizeDistribution::ByPlacement(shares) => shares.iter().any(|s| *s != 0),
+            PrizeDistribution::ByRecord(prizes) => {
+                let mut points: Vec<_> = prizes.iter().map(|p| p.min_points).collect();
+                points.sort_unstable();
+                points.dedup();
+                points.len() == prizes.len() && prizes.iter().any(|p| p.share != 0)
+            }
+        };
+        if !valid {
+            return Err(TournamentError::InvalidPrizeStructure);
+        }
+        Ok(())
+    }
+
+    /// Calculates how much each player is paid from the given standings. Players that are not paid
+    /// anything are left out, and payouts are ordered by place.
+    pub fn payouts(&self, standings: &Standings<StandardScore>) -> Vec<Payout> {
+        let scores = &standings.scores;
+        let mut amounts = match &self.distribution {
+            PrizeDistribution::ByPlacement(shares) => {
+                let mut amounts = divide(self.pool, shares);
+                amounts.resize(scores.len(), 0);
+                amounts
+            }
+            PrizeDistribution::ByRecord(prizes) => {
+                let shares: Vec<_> = scores
+                    .iter()
+                    .map(|(_, score)| {
+                        prizes
+                            .iter()
+                            .filter(|p| {
+                                r64::from_integer(p.min_points as i32) <= score.match_points
+                            })
+                            .max_by_key(|p| p.min_points)
+                            .map_or(0, |p| p.share)
+                    })
+                    .collect();
+                divide(self.pool, &shares)
+            }
+        };
+        let mut split = vec![false; scores.len()];
+        if self.split_ties {
+            let mut start = 0;
+            while start < scores.len() {
+                let points = scores[start].1.match_points;
+                let len = scores[start..]
+                    .iter()
+                    .take_while(|(_, score)| score.match_points == points)
+                    .count();
+                let group = start..start + len;
+                let total = amounts[group.clone()].iter().sum();
+                if len > 1 && total != 0 {
+                    amounts[group.clone()].copy_from_slice(&divide(total, &vec![1; len]));
+                    split[group].fill(true);
+                }
+                start += len;
+            }
+        }
+        scores
+            .iter()
+            .zip(amounts)
+            .zip(split)
+            .enumerate()
+            .filter(|(_, ((_, amount), _))| *amount != 0)
+            .map(|(i, (((player, _), amount), split))| Payout {
+                player: *player,
+                place: i + 1,
+                amount,
+                split,
+            })
+            .collect()
+    }
+}
+
+/// Divides the amount in proportion to the given shares. The units that are left over from
+/// rounding down go to the earliest shares, one each.
+fn divide(amount: u64, shares: &[u32]) -> Vec<u64> {
+    let total: u128 = shares.iter().map(|s| u128::from(*s)).sum();
+    if total == 0 {
+        return vec![0; shares.len()];
+    }
+    let mut digest: Vec<u64> = shares
+        .iter()
+        .map(|s| (u128::from(amount) * u128::from(*s) / total) as u64)
+        .collect();
+    let mut leftover = amount - digest.iter().sum::<u64>();
+    for (paid, share) in digest.iter_mut().zip(shares) {
+        if leftover == 0 {
+            break;
+        }
+        if *share != 0 {
+            *paid += 1;
+            leftover -= 1;
+        }
+    }
+    digest
+}
+
+#[cfg(test)]
+mod tests {
+    use uuid::Uuid;
+
+    use super::{divide, Payout, PrizeDistribution, PrizeStructure, RecordPrize};
+    use crate::{
+        error::TournamentError,
+        identifiers::PlayerId,
+        r64,
+        scoring::{StandardScore, Standings},
+    };
+
+    fn standings(points: &[i32]) -> Standings<StandardScore> {
+        Standings {
+            scores: points
+                .iter()
+                .map(|p| {
+                    let score = StandardScore {
+                        match_points: r64::from_integer(*p),
+                        ..Default::default()
+                    };
+                    (PlayerId::new(Uuid::new_v4()), score)
+                })
+                .collect(),
+        }
+    }
+
+    fn amounts(payouts: &[Payout]) -> Vec<(usize, u64, bool)> {
+        payouts
+            .iter()
+            .map(|p| (p.place, p.amount, p.split))
+            .collect()
+    }
+
+    #[test]
+    fn pools_are_divided_by_share() {
+        assert_eq!(divide(100, &[50, 30, 20]), vec![50, 30, 20]);
+        assert_eq!(divide(10, &[1, 0, 1, 1]), vec![4, 0, 3, 3]);
+        assert_eq!(divide(10, &[0, 0]), vec![0, 0]);
+    }
+
+    #[test]
+    fn prizes_are_paid_by_placement() {
+        let mut prizes = PrizeStructure {
+            pool: 1000,
+            distribution: PrizeDistribution::ByPlacement(vec![50, 30, 20]),
+            split_ties: false,
+        };
+        assert_eq!(prizes.validate(), Ok(()));
+        let standings = standings(&[10, 9, 9, 6, 3]);
+        assert_eq!(
+            amounts(&prizes.payouts(&standings)),
+            vec![(1, 500, false), (2, 300, false), (3, 200, false)]
+        );
+
+        // Second and third drew into the same record, so they split their prizes
+        prizes.split_ties = true;
+        assert_eq!(
+            amounts(&prizes.payouts(&standings)),
+            vec![(1, 500, false), (2, 250, true), (3, 250, true)]
+        );
+
+        // Players that tie with a paid place split its prize, even when their place is not paid
+        prizes.distribution = PrizeDistribution::ByPlacement(vec![60, 40]);
+        let payouts = prizes.payouts(&standings);
+        assert_eq!(
+            amounts(&payouts),
+            vec![(1, 600, false), (2, 200, true), (3, 200, true)]
+        );
+        assert_eq!(payouts[2].player, standings.scores[2].0);
+
+        prizes.distribution = PrizeDistribution::ByPlacement(vec![0]);
+        assert_eq!(
+            prizes.validate(),
+            Err(TournamentError::InvalidPrizeStructure)
+        );
+    }
+
+    #[test]
+    fn prizes_are_paid_by_record() {
+        let mut prizes = PrizeStructure {
+            pool: 90,
+            distribution: PrizeDistribution::ByRecord(vec![
+                RecordPrize {
+                    min_points: 9,
+                    share: 4,
+                },
+                RecordPrize {
+                    min_points: 6,
+                    share: 1,
+                },
+            ]),
+            split_ties: true,
+        };
+        assert_eq!(prizes.validate(), Ok(()));
+        assert_eq!(
+            amounts(&prizes.payouts(&standings(&[12, 10, 7, 6, 3]))),
+            vec![(1, 36, false), (2, 36, false), (3, 9, false), (4, 9, false)]
+        );
+        // Rounding leftovers are split between players on the same record
+        assert_eq!(
+            amounts(&prizes.payouts(&standings(&[6, 6, 6, 6]))),
+            vec![(1, 23, true), (2, 23, true), (3, 22, true), (4, 22, true)]
+        );
+
+        prizes.distribution = PrizeDistribution::ByRecord(vec![
+            RecordPrize {
+                min_points: 9,
+                share: 4,
+            },
+            RecordPrize {
+                min_points: 9,
+                share: 1,
+            },
+        ]);
+        assert_eq!(
+            prizes.validate(),
+            Err(TournamentError::InvalidPrizeStructure)
+        );
+    }
+}
@@ -0,0 +1,189 @@
+use std::fmt::Write;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    collation::NameCollator, identifiers::PlayerId, r64, rounds::Round, settings::PublicNameStyle,
+    tournament::Tournament,
+};
+
+/// The sheets that can be printed and posted at a venue. Every sheet is rendered as an HTML page
+/// that is laid out for printing, so it can also be saved as a PDF from a browser.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportKind {
+    /// The active rounds, ordered by table
+    #[default]
+    PairingsByTable,
+    /// Every player in an active round, ordered by name, with their table and opponents
+    PairingsByName,
+    /// The current standings
+    Standings,
+}
+
+impl Tournament {
+    /// Renders the given sheet as a printable HTML page
+    pub fn report_html(&self, kind: ReportKind, css: &str) -> String {
+        match kind {
+            ReportKind::PairingsByTable => self.pairings_by_table_html(css),
+            ReportKind::PairingsByName => self.pairings_by_name_html(css),
+            ReportKind::Standings => self.standings_html(css),
+        }
+    }
+
+    /// Renders the active rounds as a printable HTML page, ordered by table
+    pub fn pairings_by_table_html(&self, css: &str) -> String {
+        let mut ret = self.report_header("Pairings", css);
+        let mut rounds: Vec<_> = self.active_rounds().collect();
+        rounds.sort_by_key(|r| (self.table_of(r), r.match_number));
+
+        let _ = write!(ret, "<table style='width: 100%;'>");
+        let _ = write!(
+            ret,
+            "<tr><th>{}</th><th>Players</th></tr>",
+            self.table_header()
+        );
+        for r in rounds {
+            let names: Vec<_> = r.players.iter().map(|p| self.report_name(p)).collect();
+            let players = if r.is_bye() {
+                format!("{} (BYE)", names.join(", "))
+            } else {
+                names.join(" vs. ")
+            };
+            let _ = write!(ret, "<tr style='break-inside: avoid;'>");
+            let _ = write!(ret, "<td>{}</td>", self.table_of(r));
+            let _ = write!(ret, "<td>{}</td>", html_escape::encode_text(&players));
+            let _ = write!(ret, "</tr>");
+        }
+        let _ = write!(ret, "</table></body></html>");
+
+        ret
+    }
+
+    /// Renders every player in an active round as a printable HTML page, ordered by name, so that
+    /// players can quickly find their table
+    pub fn pairings_by_name_html(&self, css: &str) -> String {
+        let mut ret = self.report_header("Pairings", css);
+        let mut seats: Vec<_> = self
+            .active_rounds()
+            .flat_map(|r| r.players.iter().map(move |p| (self.report_name(p), p, r)))
+            .collect();
+        NameCollator::new().sort_by_name(&mut seats, |(name, _, _)| name.as_str());
+
+        let _ = write!(ret, "<table style='width: 100%;'>");
+        let _ = write!(
+            ret,
+            "<tr><th>Player</th><th>{}</th><th>Opponents</th></tr>",
+            self.table_header()
+        );
+        for (name, id, r) in seats {
+            let opponents = if r.is_bye() {
+                "BYE".to_owned()
+            } else {
+                r.players
+                    .iter()
+                    .filter(|p| *p != id)
+                    .map(|p| self.report_name(p))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            };
+            let _ = write!(ret, "<tr style='break-inside: avoid;'>");
+            let _ = write!(ret, "<td>{}</td>", html_escape::encode_text(&name));
+            let _ = write!(ret, "<td>{}</td>", self.table_of(r));
+            let _ = write!(ret, "<td>{}</td>", html_escape::encode_text(&opponents));
+            let _ = write!(ret, "</tr>");
+        }
+        let _ = write!(ret, "</table></body></html>");
+
+        ret
+    }
+
+    /// Renders the current standings as a printable HTML page
+    pub fn standings_html(&self, css: &str) -> String {
+        let mut ret = self.report_header("Standings", css);
+
+        let _ = write!(ret, "<table style='width: 100%;'>");
+        let _ = write!(ret, "<tr><th>Rank</th><th>Player</th><th>Points</th>");
+        let _ = write!(ret, "<th>OMW%</th><th>GW%</th><th>OGW%</th></tr>");
+        for (i, (id, score)) in self.get_standings().scores.iter().enumerate() {
+            let name = self.report_name(id);
+            let _ = write!(ret, "<tr style='break-inside: avoid;'>");
+            let _ = write!(ret, "<td>{}</td>", i + 1);
+            let _ = write!(ret, "<td>{}</td>", html_escape::encode_text(&name));
+            let _ = write!(ret, "<td>{}</td>", points(score.match_points));
+            let _ = write!(ret, "<td>{}</td>", percent(score.opp_mwp));
+            let _ = write!(ret, "<td>{}</td>", percent(score.gwp));
+            let _ = write!(ret, "<td>{}</td>", percent(score.opp_gwp));
+            let _ = write!(ret, "</tr>");
+        }
+        let _ = write!(ret, "</table></body></html>");
+
+        ret
+    }
+
+    /// Opens a report's page, up to and including its title
+    fn report_header(&self, sheet: &str, css: &str) -> String {
+        let mut ret: String = "<!DOCTYPE HTML>".to_string();
+
+        let _ = write!(ret, "<html lang=\"en\">");
+        let _ = write!(ret, "<head><style>");
+        let _ = write!(ret, "{css}");
+        let _ = write!(ret, "</style></head>");
+        let _ = write!(ret, "<body>");
+        let _ = write!(
+            ret,
+            "<h1>{} - {sheet}</h1>",
+            html_escape::encode_text(&self.name)
+        );
+
+        ret
+    }
+
+    fn active_rounds(&self) -> impl Iterator<Item = &Round> {
+        self.round_reg.rounds.values().filter(|r| r.is_active())
+    }
+
+    /// The number that players look for to find their seat
+    fn table_of(&self, r: &Round) -> u64 {
+        if self.settings.use_table_number {
+            r.table_number
+        } else {
+            r.match_number
+        }
+    }
+
+    fn table_header(&self) -> &'static str {
+        if self.settings.use_table_number {
+            "Table"
+        } else {
+            "Match"
+        }
+    }
+
+    /// The name of a player, as it is shown in reports
+    fn report_name(&self, id: &PlayerId) -> String {
+        self.player_reg
+            .get_player(id)
+            .map(|p| match self.settings.public_names {
+                PublicNameStyle::FullName => p.all_names(),
+                style => p.public_name(style),
+            })
+            .unwrap_or_default()
+    }
+}
+
+fn points(val: r64) -> String {
+    if val.is_integer() {
+        val.to_integer().to_string()
+    } else {
+        format!("{:.2}", to_f64(val))
+    }
+}
+
+fn percent(val: r64) -> String {
+    format!("{:.2}", to_f64(val) * 100.0)
+}
+
+fn to_f64(val: r64) -> f64 {
+    f64::from(*val.numer()) / f64::from(*val.denom())
+}
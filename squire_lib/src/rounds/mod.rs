@@ -9,10 +9,11 @@ use std::{
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use serde_with::{serde_as, Seq};
+use serde_with::serde_as;
 
 pub use crate::identifiers::RoundId;
 use crate::{
+    boilerplate::ordered::{SortedSeq, SortedSet},
     error::TournamentError,
     identifiers::{id_from_list, PlayerId, RoundIdentifier},
     pairings::swiss_pairings::SwissContext,
@@ -43,6 +44,43 @@ pub enum RoundResult {
     Wins(PlayerId, u32),
     /// There was a drawn game in the round
     Draw(u32),
+    /// The specified player finished in the given place (starting at 1) of a multiplayer round
+    Placement(PlayerId, u32),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Hash, PartialEq, Eq)]
+/// The outcome of a deck check
+pub enum DeckCheckResult {
+    /// Every deck in the round matched its registered list
+    Passed,
+    /// The player's deck did not match their registered list. The problem is described.
+    Failed(PlayerId, String),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+/// A deck check that was done during a round
+pub struct DeckCheck {
+    /// When the deck check started
+    pub started: DateTime<Utc>,
+    /// How long the deck check took. This time was given to the round as an extension.
+    pub length: Duration,
+    /// The outcome of the deck check
+    pub result: DeckCheckResult,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, Hash, PartialEq, Eq)]
+/// The strategy used to assign table numbers to new rounds
+pub enum TableAssignment {
+    /// Rounds are given the lowest open table number
+    #[default]
+    Sequential,
+    /// Rounds created together are spread across the open table numbers in a (deterministic)
+    /// random order
+    Random,
+    /// Rounds with a player that has a table pool are placed at an open table from that pool
+    FixedPools,
+    /// Rounds are given the lowest open table that none of their players have sat at before
+    AvoidReuse,
 }
 
 #[derive(Serialize, Deserialize, Debug, Default, Clone, Hash, PartialEq, Eq)]
@@ -53,6 +91,13 @@ pub enum RoundContext {
     Contextless,
     /// The context from the swiss pairings
     Swiss(SwissContext),
+    /// The number of the pairing round (starting at 1) that created the round. The record of how
+    /// it was paired is kept by the round registry.
+    Pairing(u64),
+    /// The (one-indexed) draft pod that all of the round's players were seated at
+    DraftPod(usize),
+    /// The (one-indexed) flight that all of the round's players were in when it was created
+    Flight(usize),
     /// The context from multiple sources
     Multiple(Vec<RoundContext>),
 }
@@ -85,14 +130,20 @@ pub struct Round {
     /// The winner after certification, if one exists
     pub winner: Option<PlayerId>,
     /// The winner after certification, if one exists
+    #[serde_as(as = "SortedSet")]
     pub confirmations: HashSet<PlayerId>,
     /// The winner after certification, if one exists
+    #[serde_as(as = "SortedSet")]
     pub drops: HashSet<PlayerId>,
     /// The winner after certification, if one exists
-    #[serde_as(as = "Seq<(_, _)>")]
+    #[serde_as(as = "SortedSeq")]
     pub results: HashMap<PlayerId, u32>,
     /// The winner after certification, if one exists
     pub draws: u32,
+    /// The place (starting at 1) that each player finished in, for multiplayer rounds
+    #[serde_as(as = "SortedSeq")]
+    #[serde(default)]
+    pub placements: HashMap<PlayerId, u32>,
     /// The round context that the round was created in
     #[serde(default)]
     pub context: RoundContext,
@@ -104,6 +155,25 @@ pub struct Round {
     pub extension: Duration,
     /// Whether or not this round is a bye
     pub is_bye: bool,
+    /// Whether or not this round is a feature match
+    #[serde(default)]
+    pub is_feature: bool,
+    /// The URL of the stream that is broadcasting this round, if any
+    #[serde(default)]
+    pub stream_url: Option<String>,
+    /// When the deck check that is underway started, if there is one
+    #[serde(default)]
+    pub deck_check_start: Option<DateTime<Utc>>,
+    /// The deck checks that have been completed during the round
+    #[serde(default)]
+    pub deck_checks: Vec<DeckCheck>,
+    /// When the round's result was last changed, if it has one
+    #[serde(default)]
+    pub result_time: Option<DateTime<Utc>>,
+    /// The players that have checked in at the round's table
+    #[serde(default)]
+    #[serde_as(as = "SortedSet")]
+    pub arrivals: HashSet<PlayerId>,
 }
 
 impl Round {
@@ -126,6 +196,7 @@ impl Round {
             players,
             confirmations,
             results,
+            placements: HashMap::new(),
             context,
             draws: 0,
             timer: salt,
@@ -135,6 +206,12 @@ impl Round {
             winner: None,
             extension: Duration::from_secs(0),
             is_bye: false,
+            is_feature: false,
+            stream_url: None,
+            deck_check_start: None,
+            deck_checks: Vec::new(),
+            result_time: None,
+            arrivals: HashSet::new(),
         }
     }
 
@@ -157,6 +234,7 @@ impl Round {
             players: vec![plyr],
             confirmations: HashSet::new(),
             results: HashMap::new(),
+            placements: HashMap::new(),
             draws: 0,
             status: RoundStatus::Certified,
             drops: HashSet::new(),
@@ -165,10 +243,25 @@ impl Round {
             length: len,
             extension: Duration::from_secs(0),
             is_bye: true,
+            is_feature: false,
+            stream_url: None,
+            deck_check_start: None,
+            deck_checks: Vec::new(),
+            result_time: None,
+            arrivals: HashSet::new(),
             context,
         }
     }
 
+    /// Marks (or unmarks) the round as a feature match. Removing the designation also removes the
+    /// stream URL.
+    pub fn set_feature(&mut self, is_feature: bool) {
+        self.is_feature = is_feature;
+        if !is_feature {
+            self.stream_url = None;
+        }
+    }
+
     /// Calculates if an identifier matches data in this round
     pub fn match_ident(&self, ident: RoundIdentifier) -> bool {
         match ident {
@@ -180,8 +273,14 @@ impl Round {
 
     /// Calculates the time left in the round, factoring in time extenstions.
     pub fn time_left(&self) -> Duration {
+        self.time_left_at(Utc::now())
+    }
+
+    /// Calculates the time that would be left in the round at the given time, factoring in time
+    /// extenstions.
+    pub fn time_left_at(&self, now: DateTime<Utc>) -> Duration {
         let length = self.length + self.extension;
-        let elapsed = Duration::from_secs((Utc::now() - self.timer).num_seconds().max(0) as u64);
+        let elapsed = Duration::from_secs((now - self.timer).num_seconds().max(0) as u64);
         if elapsed < length {
             length - elapsed
         } else {
@@ -189,6 +288,56 @@ impl Round {
         }
     }
 
+    /// Calculates when the round's time runs out, factoring in time extenstions.
+    pub fn deadline(&self) -> DateTime<Utc> {
+        chrono::Duration::from_std(self.length + self.extension)
+            .ok()
+            .and_then(|length| self.timer.checked_add_signed(length))
+            .unwrap_or(DateTime::<Utc>::MAX_UTC)
+    }
+
+    /// Calculates when the round's result is certified automatically, given how long to wait after
+    /// the round's time runs out. The wait starts over whenever the result changes. Rounds that are
+    /// not active or have no result are not certified automatically.
+    pub fn auto_certify_time(&self, wait: Duration) -> Option<DateTime<Utc>> {
+        if !self.is_active() || !self.has_result() {
+            return None;
+        }
+        let deadline = self.deadline();
+        let start = self.result_time.map_or(deadline, |time| time.max(deadline));
+        start.checked_add_signed(chrono::Duration::from_std(wait).ok()?)
+    }
+
+    /// Marks a player as having checked in at the round's table
+    pub fn check_in_at_table(&mut self, plyr: PlayerId) -> Result<(), TournamentError> {
+        if !self.is_active() {
+            Err(TournamentError::IncorrectRoundStatus(self.status))
+        } else if !self.players.contains(&plyr) {
+            Err(TournamentError::PlayerNotInRound)
+        } else {
+            _ = self.arrivals.insert(plyr);
+            Ok(())
+        }
+    }
+
+    /// Calculates which players have not checked in at the round's table by the given time, once
+    /// the grace period after the round started has passed. Byes have no table, so they never
+    /// have tardy players.
+    pub fn tardy_players(&self, grace: Duration, now: DateTime<Utc>) -> Vec<PlayerId> {
+        let late = chrono::Duration::from_std(grace)
+            .ok()
+            .and_then(|grace| self.timer.checked_add_signed(grace))
+            .is_some_and(|cutoff| cutoff <= now);
+        if !late || !self.is_active() || self.is_bye() {
+            return Vec::new();
+        }
+        self.players
+            .iter()
+            .filter(|p| !self.arrivals.contains(p) && !self.drops.contains(p))
+            .copied()
+            .collect()
+    }
+
     /// Adds a time extension to the round
     pub fn time_extension(&mut self, dur: Duration) {
         self.extension += dur;
@@ -199,49 +348,135 @@ impl Round {
         self.drops.retain(|p| p != plyr);
     }
 
+    /// Gives a player a new id, i.e. when a guest is linked to an account
+    pub(crate) fn swap_player_ids(&mut self, old: PlayerId, new: PlayerId) {
+        self.players
+            .iter_mut()
+            .chain(self.winner.iter_mut())
+            .filter(|p| **p == old)
+            .for_each(|p| *p = new);
+        for set in [&mut self.confirmations, &mut self.drops, &mut self.arrivals] {
+            if set.remove(&old) {
+                _ = set.insert(new);
+            }
+        }
+        if let Some(wins) = self.results.remove(&old) {
+            _ = self.results.insert(new, wins);
+        }
+        if let Some(place) = self.placements.remove(&old) {
+            _ = self.placements.insert(new, place);
+        }
+        for check in &mut self.deck_checks {
+            match &mut check.result {
+                DeckCheckResult::Failed(p_id, _) if *p_id == old => *p_id = new,
+                _ => {}
+            }
+        }
+    }
+
+    /// Starts a deck check for the round's players
+    pub fn start_deck_check(&mut self, salt: DateTime<Utc>) -> Result<(), TournamentError> {
+        if !self.is_active() {
+            return Err(TournamentError::IncorrectRoundStatus(self.status));
+        }
+        if self.deck_check_start.is_some() {
+            return Err(TournamentError::DeckCheckInProgress);
+        }
+        self.deck_check_start = Some(salt);
+        Ok(())
+    }
+
+    /// Completes the deck check that is underway. The time that the deck check took, rounded up
+    /// to the minute, is given to the round as a time extension and returned.
+    pub fn complete_deck_check(
+        &mut self,
+        salt: DateTime<Utc>,
+        result: DeckCheckResult,
+    ) -> Result<Duration, TournamentError> {
+        let started = self.deck_check_start.ok_or(TournamentError::NoDeckCheck)?;
+        if let DeckCheckResult::Failed(p_id, _) = &result {
+            if !self.contains_player(p_id) {
+                return Err(TournamentError::PlayerNotInRound);
+            }
+        }
+        let secs = (salt - started).num_seconds().max(0) as u64;
+        let length = Duration::from_secs(secs.div_ceil(60) * 60);
+        self.extension = self
+            .extension
+            .checked_add(length)
+            .ok_or(TournamentError::TimeOverflow)?;
+        self.deck_check_start = None;
+        self.deck_checks.push(DeckCheck {
+            started,
+            length,
+            result,
+        });
+        Ok(length)
+    }
+
     /// Calculates if there is a result recorded for the match
     pub fn has_result(&self) -> bool {
-        self.draws != 0 || self.results.values().sum::<u32>() != 0
+        self.draws != 0 || self.results.values().sum::<u32>() != 0 || !self.placements.is_empty()
+    }
+
+    /// Returns the place (starting at 1) that the player finished in, if one was recorded
+    pub fn placement(&self, id: &PlayerId) -> Option<u32> {
+        self.placements.get(id).copied()
     }
 
-    fn verify_result(&self, result: &RoundResult) -> bool {
+    fn verify_result(&self, result: &RoundResult) -> Result<(), TournamentError> {
         match result {
-            RoundResult::Wins(p_id, _) => self.players.contains(p_id),
-            RoundResult::Draw(_) => true,
+            RoundResult::Wins(p_id, _) | RoundResult::Placement(p_id, _)
+                if !self.players.contains(p_id) =>
+            {
+                Err(TournamentError::PlayerNotInRound)
+            }
+            RoundResult::Placement(_, place)
+                if *place == 0 || *place as usize > self.players.len() =>
+            {
+                Err(TournamentError::InvalidPlacement)
+            }
+            _ => Ok(()),
         }
     }
 
     /// Records part of the result of the round.
     pub fn record_result(&mut self, result: RoundResult) -> Result<(), TournamentError> {
-        if self.verify_result(&result) {
-            if self.is_active() {
-                self.confirmations.clear();
-            }
-            match result {
-                RoundResult::Wins(p_id, count) => {
-                    _ = self.results.insert(p_id, count);
-                    let mut max = 0;
-                    for (p, num) in self.results.iter() {
-                        match max.cmp(num) {
-                            Ordering::Less => {
-                                max = *num;
-                                self.winner = Some(*p);
-                            }
-                            Ordering::Equal => {
-                                self.winner = None;
-                            }
-                            Ordering::Greater => {}
+        self.verify_result(&result)?;
+        if self.is_active() {
+            self.confirmations.clear();
+        }
+        match result {
+            RoundResult::Wins(p_id, count) => {
+                _ = self.results.insert(p_id, count);
+                let mut max = 0;
+                for (p, num) in self.results.iter() {
+                    match max.cmp(num) {
+                        Ordering::Less => {
+                            max = *num;
+                            self.winner = Some(*p);
+                        }
+                        Ordering::Equal => {
+                            self.winner = None;
                         }
+                        Ordering::Greater => {}
                     }
                 }
-                RoundResult::Draw(count) => {
-                    self.draws = count;
-                }
             }
-            Ok(())
-        } else {
-            Err(TournamentError::PlayerNotInRound)
+            RoundResult::Draw(count) => {
+                self.draws = count;
+            }
+            RoundResult::Placement(p_id, place) => {
+                _ = self.placements.insert(p_id, place);
+                // Only an outright first place wins the round
+                let mut firsts = self.placements.iter().filter(|(_, place)| **place == 1);
+                self.winner = match (firsts.next(), firsts.next()) {
+                    (Some((p, _)), None) => Some(*p),
+                    _ => None,
+                };
+            }
         }
+        Ok(())
     }
 
     /// Confirms the result of the round for a player
@@ -291,6 +526,64 @@ impl Round {
     pub fn contains_player(&self, p_id: &PlayerId) -> bool {
         self.players.contains(p_id) || self.drops.contains(p_id)
     }
+
+    /// Returns the players that still need to confirm the result of the round
+    pub fn unconfirmed(&self) -> Vec<PlayerId> {
+        self.players
+            .iter()
+            .filter(|p| !self.confirmations.contains(p) && !self.drops.contains(p))
+            .copied()
+            .collect()
+    }
+}
+
+/// A snapshot of a round that is still being played, used by scorekeepers to track which matches
+/// need their attention.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct MatchProgress {
+    /// The id of the round
+    pub id: RoundId,
+    /// The match number of the round
+    pub match_number: u64,
+    /// The table number the round is assigned to
+    pub table_number: u64,
+    /// The players in the round
+    pub players: Vec<PlayerId>,
+    /// The time left in the round, including extensions
+    pub time_left: Duration,
+    /// Whether or not any result has been recorded for the round
+    pub has_result: bool,
+    /// The players that have not confirmed the result
+    pub unconfirmed: Vec<PlayerId>,
+}
+
+impl MatchProgress {
+    /// Creates a snapshot of the round's progress
+    pub fn new(rnd: &Round) -> Self {
+        Self {
+            id: rnd.id,
+            match_number: rnd.match_number,
+            table_number: rnd.table_number,
+            players: rnd.players.clone(),
+            time_left: rnd.time_left(),
+            has_result: rnd.has_result(),
+            unconfirmed: rnd.unconfirmed(),
+        }
+    }
+
+    /// Calculates if the round has run out of time
+    pub fn is_overtime(&self) -> bool {
+        self.time_left.is_zero()
+    }
+
+    /// Compares the urgency of two rounds. Rounds with less time left come first, then rounds
+    /// without a result come before rounds that are only missing confirmations.
+    pub fn cmp_urgency(&self, other: &Self) -> Ordering {
+        self.time_left
+            .cmp(&other.time_left)
+            .then(self.has_result.cmp(&other.has_result))
+            .then(self.match_number.cmp(&other.match_number))
+    }
 }
 
 impl Display for RoundStatus {
@@ -307,30 +600,62 @@ impl Display for RoundStatus {
     }
 }
 
+impl Display for TableAssignment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::Sequential => "Sequential",
+                Self::Random => "Random",
+                Self::FixedPools => "Fixed Pools",
+                Self::AvoidReuse => "Avoid Reuse",
+            }
+        )
+    }
+}
+
 impl RoundContext {
     /// Combines two round contexts
     pub fn combine(self, other: Self) -> Self {
         use RoundContext::*;
+        match (self, other) {
+            (Contextless, context) | (context, Contextless) => context,
+            (Swiss(ctx), Swiss(_)) => Swiss(ctx),
+            (Multiple(mut ctx), Multiple(context)) => {
+                ctx.extend(context);
+                Multiple(ctx)
+            }
+            (Multiple(mut ctx), context) | (context, Multiple(mut ctx)) => {
+                ctx.push(context);
+                Multiple(ctx)
+            }
+            (ctx, context) => Multiple(vec![ctx, context]),
+        }
+    }
+
+    /// Gets the number of the pairing round that created the round, if there is one
+    pub fn pairing_round(&self) -> Option<u64> {
         match self {
-            Contextless => other,
-            Swiss(ctx) => match other {
-                Contextless | Swiss(_) => Swiss(ctx),
-                Multiple(mut context) => {
-                    context.push(Swiss(ctx));
-                    Multiple(context)
-                }
-            },
-            Multiple(mut ctx) => match other {
-                Contextless => Multiple(ctx),
-                Swiss(context) => {
-                    ctx.push(Swiss(context));
-                    Multiple(ctx)
-                }
-                Multiple(context) => {
-                    ctx.extend(context);
-                    Multiple(ctx)
-                }
-            },
+            RoundContext::Pairing(number) => Some(*number),
+            RoundContext::Multiple(ctx) => ctx.iter().find_map(RoundContext::pairing_round),
+            RoundContext::Contextless
+            | RoundContext::Swiss(_)
+            | RoundContext::DraftPod(_)
+            | RoundContext::Flight(_) => None,
+        }
+    }
+
+    /// Gets the (one-indexed) flight that the round was played in, if it was not played in the
+    /// main event
+    pub fn flight(&self) -> Option<usize> {
+        match self {
+            RoundContext::Flight(flight) => Some(*flight),
+            RoundContext::Multiple(ctx) => ctx.iter().find_map(RoundContext::flight),
+            RoundContext::Contextless
+            | RoundContext::Swiss(_)
+            | RoundContext::Pairing(_)
+            | RoundContext::DraftPod(_) => None,
         }
     }
 }
@@ -345,6 +670,9 @@ impl RoundResult {
             RoundResult::Draw(res) => {
                 *res = result;
             }
+            RoundResult::Placement(_, res) => {
+                *res = result;
+            }
         }
     }
     /// Get value ignoring type
@@ -352,6 +680,7 @@ impl RoundResult {
         match self {
             RoundResult::Wins(_, res) => *res,
             RoundResult::Draw(res) => *res,
+            RoundResult::Placement(_, res) => *res,
         }
     }
     /// Increment value by 1 ignoring type
@@ -364,6 +693,20 @@ impl RoundResult {
     }
 }
 
+impl FromStr for TableAssignment {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "Sequential" | "sequential" => Ok(Self::Sequential),
+            "Random" | "random" => Ok(Self::Random),
+            "Fixed Pools" | "fixed pools" | "FixedPools" => Ok(Self::FixedPools),
+            "Avoid Reuse" | "avoid reuse" | "AvoidReuse" => Ok(Self::AvoidReuse),
+            _ => Err("Unable to convert string to table assignment".to_owned()),
+        }
+    }
+}
+
 /// Error type returned when parsing a string into a `RoundStatus`
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub struct RoundStatusParseError;
@@ -4,16 +4,18 @@ use std::{
 };
 
 use chrono::{DateTime, Utc};
-use itertools::Itertools;
+use itertools::{Either, Itertools};
 use serde::{Deserialize, Serialize};
-use serde_with::{serde_as, Seq};
+use serde_with::serde_as;
 
-use super::RoundContext;
+use super::{RoundContext, TableAssignment};
 use crate::{
-    error::TournamentError::{self, NoActiveRound, RoundLookup},
+    boilerplate::ordered::{SortedSeq, SortedSet},
+    error::TournamentError::{self, NoActiveRound, RoundLookup, TableInUse},
     identifiers::{PlayerId, RoundId},
-    pairings::Pairings,
+    pairings::{PairingAudit, Pairings},
     rounds::{Round, RoundStatus},
+    venue::{MapTable, TableZone, Venue, VenueMap, ZoneMap},
 };
 
 #[serde_as]
@@ -21,14 +23,14 @@ use crate::{
 /// The struct that creates and manages all rounds.
 pub struct RoundRegistry {
     /// A lookup table between round ids and match numbers
-    #[serde_as(as = "Seq<(_, _)>")]
+    #[serde_as(as = "SortedSeq")]
     pub num_and_id: HashMap<u64, RoundId>,
     /// All the rounds in a tournament
-    #[serde_as(as = "Seq<(_, _)>")]
+    #[serde_as(as = "SortedSeq")]
     pub rounds: HashMap<RoundId, Round>,
     /// A lookup table between players and their opponents. This is duplicate data, but used
     /// heavily by scoring and pairings systems
-    #[serde_as(as = "Seq<(_, _)>")]
+    #[serde_as(as = "SortedSeq<SortedSet>")]
     pub opponents: HashMap<PlayerId, HashSet<PlayerId>>,
     /// The starting table number for assigning table numbers
     pub starting_table: u64,
@@ -36,8 +38,24 @@ pub struct RoundRegistry {
     pub length: Duration,
     /// The players' seating scores, for seeded table ordering
     #[serde(default)]
-    #[serde_as(as = "Seq<(_, _)>")]
+    #[serde_as(as = "SortedSeq")]
     seat_scores: HashMap<PlayerId, usize>,
+    /// The tables that players must be seated at when using the `FixedPools` strategy
+    #[serde(default)]
+    #[serde_as(as = "SortedSeq")]
+    pub table_pools: HashMap<PlayerId, Vec<u64>>,
+    /// Table numbers that are never automatically assigned (i.e. for feature matches)
+    #[serde(default)]
+    #[serde_as(as = "SortedSet")]
+    pub reserved_tables: HashSet<u64>,
+    /// The layout of the venue's tables. If set, new rounds are seated in the venue's zones
+    /// instead of counting up from the starting table.
+    #[serde(default)]
+    pub venue: Option<Venue>,
+    /// The records of how each pairing round was paired, in the order that they were paired. The
+    /// rounds of a pairing round only hold its number.
+    #[serde(default)]
+    pub pairing_audits: Vec<PairingAudit>,
 }
 
 impl RoundRegistry {
@@ -50,6 +68,10 @@ impl RoundRegistry {
             starting_table,
             length: len,
             seat_scores: HashMap::new(),
+            table_pools: HashMap::new(),
+            reserved_tables: HashSet::new(),
+            venue: None,
+            pairing_audits: Vec::new(),
         }
     }
 
@@ -89,21 +111,147 @@ impl RoundRegistry {
     /// Gets the next table number. Not all pairing systems force all matches to be over before
     /// pairing more players. This ensure new rounds don't the same table number as an active round
     pub(crate) fn get_table_number(&self) -> u64 {
-        let mut tracker = self.starting_table;
+        // There are finitely many active rounds, so an open table will always be found
+        self.open_tables().next().unwrap_or(self.starting_table)
+    }
+
+    /// Calculates if a table is being used by an active round
+    pub fn table_in_use(&self, n: u64) -> bool {
         self.rounds
             .values()
-            .filter_map(|r| r.is_active().then_some(r.table_number))
-            .sorted()
-            .zip(self.starting_table..(self.rounds.len() as u64 + self.starting_table))
-            .find_map(|(active, new)| {
-                if active == new {
-                    tracker += 1;
-                    None
-                } else {
-                    Some(new)
-                }
+            .any(|r| r.is_active() && !r.is_bye && r.table_number == n)
+    }
+
+    /// Returns (in order) the table numbers that are neither in use nor reserved. If there is a
+    /// venue, its tables are used first, followed by the tables after its last zone.
+    fn open_tables(&self) -> impl Iterator<Item = u64> + '_ {
+        let used: HashSet<u64> = self
+            .rounds
+            .values()
+            .filter_map(|r| (r.is_active() && !r.is_bye).then_some(r.table_number))
+            .collect();
+        let tables = match &self.venue {
+            Some(venue) => Either::Left(venue.tables().chain(venue.overflow_start()..)),
+            None => Either::Right(self.starting_table..),
+        };
+        tables.filter(move |n| !used.contains(n) && !self.reserved_tables.contains(n))
+    }
+
+    /// Returns the venue zone that a table is in
+    pub fn zone_of(&self, table: u64) -> Option<&TableZone> {
+        self.venue.as_ref().and_then(|v| v.zone_of(table))
+    }
+
+    /// Draws a floor map of the venue that shows which round is seated at each table
+    pub fn venue_map(&self) -> VenueMap {
+        let Some(venue) = &self.venue else {
+            return VenueMap::default();
+        };
+        let seated: HashMap<u64, u64> = self
+            .rounds
+            .values()
+            .filter(|r| r.is_active() && !r.is_bye)
+            .map(|r| (r.table_number, r.match_number))
+            .collect();
+        let zones = venue
+            .zones
+            .iter()
+            .map(|zone| ZoneMap {
+                name: zone.name.clone(),
+                tables: zone
+                    .tables()
+                    .filter_map(|table| {
+                        let (row, column) = zone.position(table)?;
+                        Some(MapTable {
+                            table,
+                            row,
+                            column,
+                            match_number: seated.get(&table).copied(),
+                            reserved: self.reserved_tables.contains(&table),
+                        })
+                    })
+                    .collect(),
             })
-            .unwrap_or(tracker)
+            .collect();
+        VenueMap { zones }
+    }
+
+    /// Picks a table number for a new round using the given table assignment strategy. The `batch`
+    /// is the number of rounds (including this one) that are being created together.
+    fn assign_table(
+        &self,
+        salt: DateTime<Utc>,
+        plyrs: &[PlayerId],
+        tables: TableAssignment,
+        batch: usize,
+    ) -> u64 {
+        match tables {
+            TableAssignment::Sequential => self.get_table_number(),
+            TableAssignment::Random => {
+                let open: Vec<u64> = self.open_tables().take(batch.max(1)).collect();
+                let index = Round::create_id(salt, plyrs).as_u128() % open.len() as u128;
+                open[index as usize]
+            }
+            TableAssignment::FixedPools => plyrs
+                .iter()
+                .filter_map(|p| self.table_pools.get(p))
+                .flatten()
+                .copied()
+                .find(|n| !self.table_in_use(*n))
+                .unwrap_or_else(|| self.get_table_number()),
+            TableAssignment::AvoidReuse => {
+                let used: HashSet<u64> = self
+                    .rounds
+                    .values()
+                    .filter(|r| r.status != RoundStatus::Dead && !r.is_bye)
+                    .filter(|r| r.players.iter().any(|p| plyrs.contains(p)))
+                    .map(|r| r.table_number)
+                    .collect();
+                self.open_tables()
+                    .find(|n| !used.contains(n))
+                    .unwrap_or_else(|| self.get_table_number())
+            }
+        }
+    }
+
+    /// Sets the table numbers that will not be automatically assigned to new rounds
+    pub fn reserve_tables(&mut self, tables: impl IntoIterator<Item = u64>) {
+        self.reserved_tables = tables.into_iter().collect();
+    }
+
+    /// Sets (or removes) the layout of the venue's tables
+    pub fn set_venue(&mut self, venue: Option<Venue>) -> Result<(), TournamentError> {
+        if let Some(venue) = &venue {
+            venue.validate()?;
+        }
+        self.venue = venue;
+        Ok(())
+    }
+
+    /// Sets the tables that a player can be seated at. An empty pool removes the player's pool.
+    pub fn set_table_pool(&mut self, plyr: PlayerId, pool: Vec<u64>) {
+        if pool.is_empty() {
+            _ = self.table_pools.remove(&plyr);
+        } else {
+            _ = self.table_pools.insert(plyr, pool);
+        }
+    }
+
+    /// Moves an active round to the given table, which can be a reserved table
+    pub fn assign_round_table(&mut self, id: &RoundId, n: u64) -> Result<(), TournamentError> {
+        if self
+            .rounds
+            .values()
+            .any(|r| r.id != *id && r.is_active() && !r.is_bye && r.table_number == n)
+        {
+            return Err(TableInUse);
+        }
+        let rnd = self.get_mut_round(id)?;
+        if !rnd.is_active() {
+            return Err(TournamentError::IncorrectRoundStatus(rnd.status));
+        }
+        rnd.table_number = n;
+        Ok(())
     }
 
     /// Marks a round as dead
@@ -126,6 +274,117 @@ impl RoundRegistry {
         Ok(())
     }
 
+    /// Gives a player a new id in every round, i.e. when a guest is linked to an account
+    pub(crate) fn swap_player_ids(&mut self, old: PlayerId, new: PlayerId) {
+        for rnd in self.rounds.values_mut() {
+            rnd.swap_player_ids(old, new);
+        }
+        if let Some(opps) = self.opponents.remove(&old) {
+            _ = self.opponents.insert(new, opps);
+        }
+        for opps in self.opponents.values_mut() {
+            if opps.remove(&old) {
+                _ = opps.insert(new);
+            }
+        }
+        if let Some(score) = self.seat_scores.remove(&old) {
+            _ = self.seat_scores.insert(new, score);
+        }
+        if let Some(pool) = self.table_pools.remove(&old) {
+            _ = self.table_pools.insert(new, pool);
+        }
+    }
+
+    /// Moves all of a duplicate player's rounds, opponents, and seating data to another player.
+    /// The two players must not share a round.
+    pub(crate) fn merge_players(&mut self, old: PlayerId, new: PlayerId) {
+        for rnd in self.rounds.values_mut() {
+            rnd.swap_player_ids(old, new);
+        }
+        if let Some(opps) = self.opponents.remove(&old) {
+            self.opponents.entry(new).or_default().extend(opps);
+        }
+        for opps in self.opponents.values_mut() {
+            if opps.remove(&old) {
+                _ = opps.insert(new);
+            }
+        }
+        if let Some(score) = self.seat_scores.remove(&old) {
+            *self.seat_scores.entry(new).or_default() += score;
+        }
+        if let Some(pool) = self.table_pools.remove(&old) {
+            _ = self.table_pools.entry(new).or_insert(pool);
+        }
+    }
+
+    /// Replaces a player in their active round with another player. The new player takes the seat
+    /// (and opponents) of the replaced player.
+    pub fn substitute_player(
+        &mut self,
+        old: &PlayerId,
+        new: PlayerId,
+    ) -> Result<RoundId, TournamentError> {
+        let rnd = self.get_player_active_round(old)?;
+        let id = rnd.id;
+        let Some(seat) = rnd.players.iter().position(|p| p == old) else {
+            return Err(NoActiveRound);
+        };
+        rnd.players[seat] = new;
+        _ = rnd.confirmations.remove(old);
+        _ = rnd.drops.remove(old);
+        _ = rnd.arrivals.remove(old);
+        if let Some(wins) = rnd.results.remove(old) {
+            _ = rnd.results.insert(new, wins);
+        }
+        if let Some(place) = rnd.placements.remove(old) {
+            _ = rnd.placements.insert(new, place);
+        }
+        if rnd.winner.as_ref() == Some(old) {
+            rnd.winner = Some(new);
+        }
+        let players = rnd.players.clone();
+        _ = self
+            .seat_scores
+            .entry(*old)
+            .and_modify(|n| *n = n.saturating_sub(seat));
+        *self.seat_scores.entry(new).or_default() += seat;
+        _ = self
+            .opponents
+            .entry(*old)
+            .and_modify(|opps| opps.retain(|o| !players.contains(o)));
+        for plyr in players.iter() {
+            let opps = self.opponents.entry(*plyr).or_default();
+            _ = opps.remove(old);
+            opps.extend(players.iter().filter(|p| *p != plyr));
+        }
+        Ok(id)
+    }
+
+    /// Returns every player that has received a bye (excluding byes that have been removed)
+    pub fn bye_history(&self) -> HashSet<PlayerId> {
+        self.rounds
+            .values()
+            .filter(|r| r.is_bye && r.status != RoundStatus::Dead)
+            .flat_map(|r| r.players.iter().copied())
+            .collect()
+    }
+
+    /// Gets the record of how the given pairing round was paired
+    pub fn get_pairing_audit(&self, round_number: u64) -> Option<&PairingAudit> {
+        self.pairing_audits
+            .iter()
+            .find(|a| a.round_number == round_number)
+    }
+
+    /// Calculates the number of pairing rounds that have been recorded
+    pub fn pairing_round_count(&self) -> u64 {
+        self.pairing_audits
+            .iter()
+            .map(|a| a.round_number)
+            .max()
+            .unwrap_or_default()
+    }
+
     /// Calculates the number of rounds that are not confirmed or dead
     pub fn active_round_count(&self) -> usize {
         self.rounds.iter().filter(|(_, r)| r.is_active()).count()
@@ -137,13 +396,25 @@ impl RoundRegistry {
         salt: DateTime<Utc>,
         pairings: Pairings,
         context: RoundContext,
+    ) -> Vec<RoundId> {
+        self.rounds_from_pairings_with_tables(salt, pairings, context, TableAssignment::Sequential)
+    }
+
+    /// Creates a series of matches from pairings, which are seated using the given table
+    /// assignment strategy
+    pub fn rounds_from_pairings_with_tables(
+        &mut self,
+        salt: DateTime<Utc>,
+        pairings: Pairings,
+        context: RoundContext,
+        tables: TableAssignment,
     ) -> Vec<RoundId> {
         let mut digest = Vec::with_capacity(pairings.len());
+        let batch = pairings.paired.len();
         digest.extend(
-            pairings
-                .paired
-                .into_iter()
-                .map(|p| self.create_round(salt, p, context.clone())),
+            pairings.paired.into_iter().enumerate().map(|(i, p)| {
+                self.create_batched_round(salt, p, context.clone(), tables, batch - i)
+            }),
         );
         digest.extend(
             pairings
@@ -175,6 +446,30 @@ impl RoundRegistry {
         salt: DateTime<Utc>,
         plyrs: Vec<PlayerId>,
         context: RoundContext,
+    ) -> RoundId {
+        self.create_round_with_tables(salt, plyrs, context, TableAssignment::Sequential)
+    }
+
+    /// Creates a new round, fills it with players, seats it using the given table assignment
+    /// strategy, and returns its id
+    pub fn create_round_with_tables(
+        &mut self,
+        salt: DateTime<Utc>,
+        plyrs: Vec<PlayerId>,
+        context: RoundContext,
+        tables: TableAssignment,
+    ) -> RoundId {
+        self.create_batched_round(salt, plyrs, context, tables, 1)
+    }
+
+    /// Creates a new round as a part of a batch of `batch` rounds that still need to be created
+    fn create_batched_round(
+        &mut self,
+        salt: DateTime<Utc>,
+        plyrs: Vec<PlayerId>,
+        context: RoundContext,
+        tables: TableAssignment,
+        batch: usize,
     ) -> RoundId {
         // Sort players by their prior seating order. Lower seating order is means you last
         let plyrs: Vec<_> = plyrs
@@ -191,7 +486,7 @@ impl RoundRegistry {
                 .extend(plyrs.iter().filter(|p| *p != plyr));
         }
         let match_num = 1 + self.rounds.len() as u64;
-        let table_number = self.get_table_number();
+        let table_number = self.assign_table(salt, &plyrs, tables, batch);
         let round = Round::new(salt, plyrs, match_num, table_number, self.length, context);
         let id = round.id;
         _ = self.num_and_id.insert(match_num, id);
@@ -260,7 +555,8 @@ mod tests {
 
     use crate::{
         identifiers::id_from_item,
-        rounds::{RoundContext, RoundRegistry, RoundStatus},
+        rounds::{RoundContext, RoundRegistry, RoundStatus, TableAssignment},
+        venue::{TableZone, Venue},
     };
 
     #[test]
@@ -286,6 +582,110 @@ mod tests {
         }
     }
 
+    #[test]
+    fn reserved_tables_test() {
+        let mut reg = RoundRegistry::new(1, Duration::from_secs(10));
+        reg.reserve_tables([1, 3]);
+        let id_one = reg.create_round(Utc::now(), vec![], RoundContext::Contextless);
+        assert_eq!(reg.get_round(&id_one).unwrap().table_number, 2);
+        let id_two = reg.create_round(Utc::now(), vec![], RoundContext::Contextless);
+        assert_eq!(reg.get_round(&id_two).unwrap().table_number, 4);
+        // Reserved tables can be assigned manually, but only to one round at a time
+        reg.assign_round_table(&id_one, 1).unwrap();
+        assert_eq!(reg.round_from_table_number(1).unwrap().id, id_one);
+        assert!(reg.assign_round_table(&id_two, 1).is_err());
+    }
+
+    #[test]
+    fn venue_tables_test() {
+        let mut reg = RoundRegistry::new(1, Duration::from_secs(10));
+        let venue = Venue {
+            zones: vec![
+                TableZone::new("Hall B", 101, 102),
+                TableZone::new("Hall A", 1, 1),
+            ],
+        };
+        reg.set_venue(Some(venue)).unwrap();
+        reg.reserve_tables([102]);
+        let tables: Vec<_> = (0..3)
+            .map(|_| reg.create_round(Utc::now(), vec![], RoundContext::Contextless))
+            .map(|id| reg.get_round(&id).unwrap().table_number)
+            .collect();
+        // Once the venue is full, rounds are seated after its last table
+        assert_eq!(tables, vec![101, 1, 103]);
+        assert_eq!(reg.zone_of(1).unwrap().name, "Hall A");
+        assert!(reg.zone_of(103).is_none());
+        let map = reg.venue_map();
+        assert_eq!(map.zones[0].tables.len(), 2);
+        assert!(map.zones[0].tables[0].match_number.is_some());
+        assert!(map.zones[0].tables[1].reserved);
+
+        let overlapping = Venue {
+            zones: vec![
+                TableZone::new("Hall A", 1, 10),
+                TableZone::new("Hall B", 5, 20),
+            ],
+        };
+        assert!(reg.set_venue(Some(overlapping)).is_err());
+        assert!(reg.venue.is_some());
+        reg.set_venue(None).unwrap();
+        assert_eq!(reg.get_table_number(), 2);
+    }
+
+    #[test]
+    fn table_assignment_strategies_test() {
+        let plyrs: Vec<_> = (0..4).map(|i| id_from_item(Utc::now(), i)).collect();
+        let mut reg = RoundRegistry::new(1, Duration::from_secs(10));
+        let tables = TableAssignment::FixedPools;
+        reg.set_table_pool(plyrs[0], vec![7, 8]);
+        let id = reg.create_round_with_tables(
+            Utc::now(),
+            plyrs[..2].to_vec(),
+            RoundContext::Contextless,
+            tables,
+        );
+        assert_eq!(reg.get_round(&id).unwrap().table_number, 7);
+        let id = reg.create_round_with_tables(
+            Utc::now(),
+            plyrs[..2].to_vec(),
+            RoundContext::Contextless,
+            tables,
+        );
+        assert_eq!(reg.get_round(&id).unwrap().table_number, 8);
+        let id = reg.create_round_with_tables(
+            Utc::now(),
+            plyrs[2..].to_vec(),
+            RoundContext::Contextless,
+            tables,
+        );
+        assert_eq!(reg.get_round(&id).unwrap().table_number, 1);
+
+        let mut reg = RoundRegistry::new(1, Duration::from_secs(10));
+        let tables = TableAssignment::AvoidReuse;
+        let id = reg.create_round_with_tables(
+            Utc::now(),
+            plyrs[..2].to_vec(),
+            RoundContext::Contextless,
+            tables,
+        );
+        assert_eq!(reg.get_round(&id).unwrap().table_number, 1);
+        reg.get_mut_round(&id).unwrap().status = RoundStatus::Certified;
+        let id = reg.create_round_with_tables(
+            Utc::now(),
+            plyrs[1..3].to_vec(),
+            RoundContext::Contextless,
+            tables,
+        );
+        assert_eq!(reg.get_round(&id).unwrap().table_number, 2);
+        let id = reg.create_round_with_tables(
+            Utc::now(),
+            plyrs[3..].to_vec(),
+            RoundContext::Contextless,
+            tables,
+        );
+        assert_eq!(reg.get_round(&id).unwrap().table_number, 1);
+    }
+
     #[test]
     fn simple_seating_scores_test() {
         let plyrs = vec![
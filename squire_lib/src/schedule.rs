@@ -0,0 +1,206 @@
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::error::TournamentError;
+
+/// When a tournament and its pairing rounds are scheduled to start. The schedule is only published
+/// to players; rounds are still paired by the tournament's officials.
+#[derive(Serialize, Deserialize, Debug, Default, Hash, Clone, PartialEq, Eq)]
+pub struct Schedule {
+    /// When the tournament starts
+    #[serde(default)]
+    pub start: Option<DateTime<Utc>>,
+    /// When pairing rounds are scheduled to start, ordered by round number (starting at 1). Rounds
+    /// that are not listed are expected to start once the round before them has ended.
+    #[serde(default)]
+    pub rounds: Vec<(u64, DateTime<Utc>)>,
+    /// The second day of the tournament, if it has one
+    #[serde(default)]
+    pub day_two: Option<DayTwo>,
+}
+
+/// The second day of a multi-day tournament
+#[derive(Serialize, Deserialize, Debug, Hash, Clone, Copy, PartialEq, Eq)]
+pub struct DayTwo {
+    /// The first pairing round that is played on day two
+    pub first_round: u64,
+    /// When the first round of day two starts
+    pub start: DateTime<Utc>,
+    /// The fewest match points that a player needs to play on day two. If this is not set, every
+    /// player can play on day two.
+    pub min_points: Option<u32>,
+}
+
+/// The published schedule of a tournament, as calculated by
+/// [`Tournament::schedule`](crate::tournament::Tournament::schedule)
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct EventSchedule {
+    /// When the tournament starts
+    pub start: Option<DateTime<Utc>>,
+    /// The second day of the tournament, if it has one
+    pub day_two: Option<DayTwo>,
+    /// The pairing rounds that have a known or estimated start time, ordered by round number
+    pub rounds: Vec<ScheduledRound>,
+}
+
+/// When a pairing round starts
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScheduledRound {
+    /// The round number, starting at 1
+    pub round: u64,
+    /// The day that the round is played on, starting at 1
+    pub day: u8,
+    /// When the round starts
+    pub start: DateTime<Utc>,
+    /// Whether the start time was estimated from the round before it
+    pub estimated: bool,
+    /// Whether the round has been paired
+    pub paired: bool,
+}
+
+impl Schedule {
+    /// Checks that the schedule is sensible and orders its rounds. Rounds must be numbered from 1
+    /// and can only be listed once, and day two can not start before the tournament does.
+    pub fn validate(&mut self) -> Result<(), TournamentError> {
+        self.rounds.sort_by_key(|(round, _)| *round);
+        let repeated = self.rounds.windows(2).any(|w| w[0].0 == w[1].0);
+        if repeated || self.rounds.first().is_some_and(|(round, _)| *round == 0) {
+            return Err(TournamentError::InvalidSchedule);
+        }
+        match (self.start, self.day_two) {
+            (_, Some(day_two)) if day_two.first_round < 2 => Err(TournamentError::InvalidSchedule),
+            (Some(start), Some(day_two)) if day_two.start < start => {
+                Err(TournamentError::InvalidSchedule)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns the day that a round is played on
+    pub fn day_of(&self, round: u64) -> u8 {
+        match self.day_two {
+            Some(day_two) if round >= day_two.first_round => 2,
+            _ => 1,
+        }
+    }
+
+    /// Returns the time that a round is scheduled for, if it was given one
+    pub fn scheduled_time(&self, round: u64) -> Option<DateTime<Utc>> {
+        self.rounds
+            .iter()
+            .find_map(|(r, time)| (*r == round).then_some(*time))
+    }
+
+    /// Calculates the start of each pairing round, up to the last round that is scheduled or has
+    /// been paired. Rounds without a time start `round_length` after the round before them, and
+    /// the first round of each day starts with the day.
+    pub fn calculate(&self, round_length: Duration, paired_rounds: u64) -> EventSchedule {
+        let last = self
+            .rounds
+            .iter()
+            .map(|(round, _)| *round)
+            .chain(self.day_two.map(|d| d.first_round))
+            .chain([paired_rounds])
+            .max()
+            .unwrap_or_default();
+        let round_length = chrono::Duration::from_std(round_length).unwrap_or_default();
+        let mut rounds = Vec::new();
+        let mut prev: Option<DateTime<Utc>> = None;
+        for round in 1..=last {
+            let day_start = match self.day_two {
+                Some(day_two) if day_two.first_round == round => Some(day_two.start),
+                _ if round == 1 => self.start,
+                _ => None,
+            };
+            let (start, estimated) = match (self.scheduled_time(round), day_start) {
+                (Some(time), _) | (None, Some(time)) => (Some(time), false),
+                (None, None) => (prev.map(|time| time + round_length), true),
+            };
+            prev = start;
+            if let Some(start) = start {
+                rounds.push(ScheduledRound {
+                    round,
+                    day: self.day_of(round),
+                    start,
+                    estimated,
+                    paired: round <= paired_rounds,
+                });
+            }
+        }
+        EventSchedule {
+            start: self.start,
+            day_two: self.day_two,
+            rounds,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use chrono::{TimeZone, Utc};
+
+    use super::{DayTwo, Schedule};
+    use crate::error::TournamentError;
+
+    #[test]
+    fn schedules_are_validated() {
+        let start = Utc.with_ymd_and_hms(2024, 6, 1, 9, 0, 0).unwrap();
+        let mut schedule = Schedule {
+            start: Some(start),
+            rounds: vec![(2, start), (1, start)],
+            day_two: None,
+        };
+        assert_eq!(schedule.validate(), Ok(()));
+        assert_eq!(schedule.rounds[0].0, 1);
+
+        schedule.rounds.push((2, start));
+        assert_eq!(schedule.validate(), Err(TournamentError::InvalidSchedule));
+        _ = schedule.rounds.pop();
+
+        schedule.day_two = Some(DayTwo {
+            first_round: 9,
+            start: start - chrono::Duration::days(1),
+            min_points: None,
+        });
+        assert_eq!(schedule.validate(), Err(TournamentError::InvalidSchedule));
+    }
+
+    #[test]
+    fn two_day_schedules_are_calculated() {
+        let sat = Utc.with_ymd_and_hms(2024, 6, 1, 9, 0, 0).unwrap();
+        let sun = Utc.with_ymd_and_hms(2024, 6, 2, 10, 0, 0).unwrap();
+        let schedule = Schedule {
+            start: Some(sat),
+            rounds: vec![(4, sat + chrono::Duration::hours(5))],
+            day_two: Some(DayTwo {
+                first_round: 9,
+                start: sun,
+                min_points: Some(18),
+            }),
+        };
+        let calculated = schedule.calculate(Duration::from_secs(50 * 60), 2);
+        assert_eq!(calculated.rounds.len(), 9);
+
+        let first = calculated.rounds[0];
+        assert_eq!(
+            (first.start, first.estimated, first.paired),
+            (sat, false, true)
+        );
+        let second = calculated.rounds[1];
+        assert_eq!(second.start, sat + chrono::Duration::minutes(50));
+        assert!(second.estimated && second.paired);
+        let fourth = calculated.rounds[3];
+        assert_eq!(fourth.start, sat + chrono::Duration::hours(5));
+        assert!(!fourth.estimated && !fourth.paired);
+
+        // Round 9 starts on Sunday
+        let ninth = calculated.rounds[8];
+        assert_eq!((ninth.round, ninth.day, ninth.start), (9, 2, sun));
+        assert!(!ninth.estimated);
+        assert_eq!(calculated.rounds[7].day, 1);
+    }
+}
@@ -1,8 +1,16 @@
+use std::{
+    borrow::Cow,
+    collections::{HashMap, HashSet},
+    fmt::Display,
+    str::FromStr,
+};
+
 use serde::{Deserialize, Serialize};
 
 use crate::{
+    error::TournamentError,
     identifiers::PlayerId,
-    operations::{OpData, OpResult},
+    operations::OpResult,
     players::PlayerRegistry,
     r64,
     rounds::RoundRegistry,
@@ -13,10 +21,15 @@ use crate::{
     tournament::TournamentPreset,
 };
 
+/// Contains the models for the multiplayer score
+pub mod multiplayer_scoring;
 /// Contains the models for the standard score
 pub mod standard_scoring;
 
-pub use standard_scoring::{StandardScore, StandardScoring};
+pub use multiplayer_scoring::MultiplayerScoring;
+pub use standard_scoring::{
+    OpponentBreakdown, RoundBreakdown, ScoreExplanation, StandardScore, StandardScoring,
+};
 
 /// The trait the defines the interface for a score
 pub trait Score
@@ -34,6 +47,29 @@ pub struct Standings<S> {
     pub scores: Vec<(PlayerId, S)>,
 }
 
+/// The standings of a single group (i.e. a round-robin pod), where the top players qualify for the
+/// next stage of the tournament
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct GroupStandings<S> {
+    /// The ordered scores of the players in the group
+    pub standings: Standings<S>,
+    /// The number of players at the top of the group that qualify for the next stage
+    pub qualifiers: usize,
+}
+
+/// A difference between a player's score in two sets of standings, i.e. a cached score and a score
+/// that was recalculated from scratch. A missing score means the player was absent from that set of
+/// standings.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ScoreMismatch<S> {
+    /// The player whose scores differ
+    pub player: PlayerId,
+    /// The player's score in the standings being audited
+    pub cached: Option<S>,
+    /// The player's score in the recalculated standings
+    pub computed: Option<S>,
+}
+
 /// A scoring system that contain a style of calculating and ordering scores as well as some common
 /// settings upon all scoring styles
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
@@ -44,6 +80,37 @@ pub struct ScoringSystem {
     /// Settings of the active scoring system
     #[serde(default = "default_style")]
     pub style: ScoringStyle,
+    /// The start of the current stage, if the tournament has been cut to a later stage
+    #[serde(default)]
+    pub stage: Option<StageStart>,
+}
+
+/// How the scores from an earlier stage are used once the tournament moves to the next stage (i.e.
+/// from swiss rounds to a top 8)
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, Hash, PartialEq, Eq)]
+pub enum StageScoring {
+    /// Scores from earlier stages carry over into the next stage
+    #[default]
+    CarryOver,
+    /// Every player starts the next stage with a score of zero
+    Reset,
+    /// Every player starts the next stage with a score of zero, but ties are broken by the
+    /// standings at the end of the previous stage
+    Seeding,
+}
+
+/// The point at which the current stage of the tournament began
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq)]
+pub struct StageStart {
+    /// The match number of the first round of the stage. Rounds with a lower match number belong
+    /// to earlier stages.
+    pub first_match: u64,
+    /// The standings at the end of the previous stage, from first place to last place
+    pub seeding: Vec<PlayerId>,
+    /// The (one-indexed) flight whose rounds are counted. If this is not set, only the rounds of
+    /// the main event are counted.
+    #[serde(default)]
+    pub flight: Option<usize>,
 }
 
 fn default_style() -> ScoringStyle {
@@ -55,22 +122,43 @@ fn default_style() -> ScoringStyle {
 pub enum ScoringStyle {
     /// The tournament is using standard-style scoring
     Standard(StandardScoring),
+    /// The tournament is using placement-based scoring for multiplayer rounds
+    Multiplayer(MultiplayerScoring),
 }
 
 impl ScoringStyle {
     /// Creates a new scoring style for a tournament preset
-    pub fn new(_: TournamentPreset) -> Self {
-        Self::Standard(Default::default())
+    pub fn new(preset: TournamentPreset) -> Self {
+        match preset {
+            TournamentPreset::Swiss | TournamentPreset::Fluid => Self::Standard(Default::default()),
+            TournamentPreset::Multiplayer => Self::Multiplayer(Default::default()),
+        }
     }
 
-    /// Returns the current standings for all players
+    /// Returns the current standings for all players, only counting the rounds in the given stage
     pub fn get_standings(
         &self,
         plyrs: &PlayerRegistry,
         rnds: &RoundRegistry,
+        stage: &StageStart,
     ) -> Standings<StandardScore> {
         match self {
-            ScoringStyle::Standard(style) => style.get_standings(plyrs, rnds),
+            ScoringStyle::Standard(style) => style.get_stage_standings(plyrs, rnds, stage),
+            ScoringStyle::Multiplayer(style) => style.get_stage_standings(plyrs, rnds, stage),
+        }
+    }
+
+    /// Explains how a player's score was calculated, only counting the rounds in the given stage
+    pub fn explain_score(
+        &self,
+        plyrs: &PlayerRegistry,
+        rnds: &RoundRegistry,
+        id: &PlayerId,
+        stage: &StageStart,
+    ) -> Result<ScoreExplanation, TournamentError> {
+        match self {
+            ScoringStyle::Standard(style) => style.explain_stage_score(plyrs, rnds, id, stage),
+            ScoringStyle::Multiplayer(style) => style.explain_stage_score(plyrs, rnds, id, stage),
         }
     }
 
@@ -78,6 +166,9 @@ impl ScoringStyle {
     pub fn settings(&self) -> ScoringStyleSettingsTree {
         match self {
             ScoringStyle::Standard(tree) => ScoringStyleSettingsTree::Standard(tree.settings()),
+            ScoringStyle::Multiplayer(tree) => {
+                ScoringStyleSettingsTree::Multiplayer(tree.settings())
+            }
         }
     }
 
@@ -87,8 +178,11 @@ impl ScoringStyle {
             (ScoringStyle::Standard(style), ScoringStyleSetting::Standard(setting)) => {
                 style.update_setting(setting)
             }
+            (ScoringStyle::Multiplayer(style), ScoringStyleSetting::Multiplayer(setting)) => {
+                style.update_setting(setting)
+            }
+            _ => Err(TournamentError::IncompatibleScoringSystem),
         }
-        Ok(OpData::Nothing)
     }
 }
 
@@ -100,6 +194,90 @@ where
     pub fn new(scores: Vec<(PlayerId, S)>) -> Self {
         Standings { scores }
     }
+
+    /// Returns each player's primary score (e.g. their match points)
+    pub fn points(&self) -> HashMap<PlayerId, r64> {
+        self.scores
+            .iter()
+            .map(|(p, s)| (*p, s.primary_score()))
+            .collect()
+    }
+
+    /// Compares these (possibly stale) standings against freshly computed standings and returns
+    /// every player whose score differs. The order of the standings is not considered since tied
+    /// players can be ordered arbitrarily.
+    pub fn diff(&self, computed: &Self) -> Vec<ScoreMismatch<S>>
+    where
+        S: Clone + PartialEq,
+    {
+        let cached: HashMap<&PlayerId, &S> = self.scores.iter().map(|(p, s)| (p, s)).collect();
+        let fresh: HashMap<&PlayerId, &S> = computed.scores.iter().map(|(p, s)| (p, s)).collect();
+        let mut digest: Vec<_> = computed
+            .scores
+            .iter()
+            .filter(|(p, s)| cached.get(p).copied() != Some(s))
+            .map(|(p, s)| ScoreMismatch {
+                player: *p,
+                cached: cached.get(p).map(|c| (*c).clone()),
+                computed: Some(s.clone()),
+            })
+            .collect();
+        digest.extend(
+            self.scores
+                .iter()
+                .filter(|(p, _)| !fresh.contains_key(p))
+                .map(|(p, s)| ScoreMismatch {
+                    player: *p,
+                    cached: Some(s.clone()),
+                    computed: None,
+                }),
+        );
+        digest
+    }
+
+    /// Splits the standings into the given groups. The relative order of the players is preserved
+    /// and the top `qualifiers` players of each group are marked as qualifying. Players that are
+    /// not in any group are omitted.
+    pub fn into_groups(
+        self,
+        groups: &[Vec<PlayerId>],
+        qualifiers: usize,
+    ) -> Vec<GroupStandings<S>> {
+        let lookup: HashMap<PlayerId, usize> = groups
+            .iter()
+            .enumerate()
+            .flat_map(|(i, g)| g.iter().map(move |p| (*p, i)))
+            .collect();
+        let mut digest: Vec<_> = groups
+            .iter()
+            .map(|_| GroupStandings {
+                standings: Standings::new(Vec::new()),
+                qualifiers,
+            })
+            .collect();
+        for (p, s) in self.scores {
+            if let Some(i) = lookup.get(&p) {
+                digest[*i].standings.scores.push((p, s));
+            }
+        }
+        digest
+    }
+}
+
+impl<S> GroupStandings<S> {
+    /// Returns the players that qualify for the next stage, in order
+    pub fn qualified(&self) -> impl Iterator<Item = &PlayerId> {
+        self.standings
+            .scores
+            .iter()
+            .take(self.qualifiers)
+            .map(|(p, _)| p)
+    }
+
+    /// Calculates if the given player qualifies for the next stage
+    pub fn qualifies(&self, id: &PlayerId) -> bool {
+        self.qualified().any(|p| p == id)
+    }
 }
 
 impl ScoringSystem {
@@ -108,6 +286,7 @@ impl ScoringSystem {
         Self {
             common: CommonScoringSettingsTree::new(),
             style: ScoringStyle::new(preset),
+            stage: None,
         }
     }
 
@@ -125,7 +304,71 @@ impl ScoringSystem {
         player_reg: &PlayerRegistry,
         round_reg: &RoundRegistry,
     ) -> Standings<StandardScore> {
-        self.style.get_standings(player_reg, round_reg)
+        self.style
+            .get_standings(player_reg, round_reg, &self.scoring_stage())
+    }
+
+    /// Gets the standings of a (one-indexed) flight. Only the rounds that were played in the flight
+    /// are counted, so every player starts the flight with a score of zero.
+    pub fn get_flight_standings(
+        &self,
+        player_reg: &PlayerRegistry,
+        round_reg: &RoundRegistry,
+        flight: usize,
+    ) -> Standings<StandardScore> {
+        let stage = StageStart {
+            flight: Some(flight),
+            ..Default::default()
+        };
+        self.style.get_standings(player_reg, round_reg, &stage)
+    }
+
+    /// Explains how a player's score was calculated
+    pub fn explain_score(
+        &self,
+        player_reg: &PlayerRegistry,
+        round_reg: &RoundRegistry,
+        id: &PlayerId,
+    ) -> Result<ScoreExplanation, TournamentError> {
+        self.style
+            .explain_score(player_reg, round_reg, id, &self.scoring_stage())
+    }
+
+    /// Marks the start of a new stage of the tournament. The given standings are recorded as the
+    /// seeding for the new stage and are used according to the stage scoring setting.
+    pub fn start_stage(&mut self, round_reg: &RoundRegistry, standings: Vec<PlayerId>) {
+        self.stage = Some(StageStart {
+            first_match: 1 + round_reg.rounds.len() as u64,
+            seeding: standings,
+            flight: None,
+        });
+    }
+
+    /// Gives a player a new id, i.e. when a guest is linked to an account
+    pub(crate) fn swap_player_ids(&mut self, old: PlayerId, new: PlayerId) {
+        if let Some(stage) = &mut self.stage {
+            stage
+                .seeding
+                .iter_mut()
+                .filter(|p| **p == old)
+                .for_each(|p| *p = new);
+            // When the new id was already seeded, it keeps the better of the two seeds
+            let mut seen = HashSet::new();
+            stage.seeding.retain(|p| seen.insert(*p));
+        }
+    }
+
+    /// Returns the rounds and seeding that the standings are calculated from
+    fn scoring_stage(&self) -> Cow<'_, StageStart> {
+        match (&self.stage, self.common.stage_scoring) {
+            (None, _) | (Some(_), StageScoring::CarryOver) => Cow::Owned(StageStart::default()),
+            (Some(stage), StageScoring::Reset) => Cow::Owned(StageStart {
+                first_match: stage.first_match,
+                seeding: Vec::new(),
+                flight: None,
+            }),
+            (Some(stage), StageScoring::Seeding) => Cow::Borrowed(stage),
+        }
     }
 
     /// Updates a given setting for the scoring system
@@ -136,3 +379,30 @@ impl ScoringSystem {
         }
     }
 }
+
+impl Display for StageScoring {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::CarryOver => "Carry Over",
+                Self::Reset => "Reset",
+                Self::Seeding => "Seeding",
+            }
+        )
+    }
+}
+
+impl FromStr for StageScoring {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "Carry Over" | "carry over" | "CarryOver" => Ok(Self::CarryOver),
+            "Reset" | "reset" => Ok(Self::Reset),
+            "Seeding" | "seeding" => Ok(Self::Seeding),
+            _ => Err("Unable to convert string to stage scoring".to_owned()),
+        }
+    }
+}
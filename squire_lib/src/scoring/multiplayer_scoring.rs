@@ -0,0 +1,214 @@
+use std::collections::{HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::TournamentError,
+    identifiers::PlayerId,
+    operations::OpResult,
+    players::PlayerRegistry,
+    r64,
+    rounds::{Round, RoundRegistry},
+    scoring::{
+        standard_scoring::{place_of, sort_standings},
+        OpponentBreakdown, RoundBreakdown, ScoreExplanation, StageStart, StandardScore, Standings,
+    },
+    settings::{MultiplayerScoringSetting, MultiplayerScoringSettingsTree, SettingsTree},
+};
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq)]
+/// The scoring system for multiplayer rounds (i.e. Commander pods), where players earn points
+/// based on the place they finished in rather than by winning games.
+///
+/// Scores are given as standard scores. The match points are the placement points, the match win
+/// percentage is the share of the first place points that the player earned, and the opponent
+/// match win percentage is the same share across all of the player's opponents. Game points are
+/// not used.
+pub struct MultiplayerScoring {
+    /// The settings for the scoring system
+    #[serde(default)]
+    pub settings: MultiplayerScoringSettingsTree,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+/// A counter used to track player info while calculating scores
+struct PlacementCounter {
+    /// The number of rounds played, not including byes
+    rounds: i32,
+    /// The number of byes
+    byes: i32,
+    /// The points earned, not including byes
+    points: r64,
+    opponents: HashSet<PlayerId>,
+}
+
+impl MultiplayerScoring {
+    /// Creates a new multiplayer scoring system
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns a copy of the current settings
+    pub fn settings(&self) -> MultiplayerScoringSettingsTree {
+        self.settings.clone()
+    }
+
+    /// Updates a single scoring setting
+    pub fn update_setting(&mut self, setting: MultiplayerScoringSetting) -> OpResult {
+        self.settings.update(setting)
+    }
+
+    fn new_score(&self) -> StandardScore {
+        StandardScore {
+            include_match_points: true,
+            include_mwp: true,
+            include_opp_mwp: true,
+            ..Default::default()
+        }
+    }
+
+    /// Calculates the points a player earned in a round. Players without a recorded placement
+    /// are given first place if they won the round and last place if someone else did. Otherwise,
+    /// the round is scored as a draw.
+    fn round_points(&self, rnd: &Round, id: &PlayerId) -> r64 {
+        if rnd.is_bye() {
+            return self.settings.bye_points;
+        }
+        match (rnd.placement(id), rnd.winner) {
+            (Some(place), _) => self.settings.placement_points(place),
+            (None, Some(winner)) if winner == *id => self.settings.first_place_points,
+            (None, Some(_)) => self.settings.placement_points(rnd.players.len() as u32),
+            (None, None) => self.settings.draw_points,
+        }
+    }
+
+    /// Determines if a round counts towards the scores of its players
+    fn counts_round(&self, round: &Round, stage: &StageStart) -> bool {
+        round.is_certified()
+            && (!round.is_bye() || self.settings.include_byes)
+            && round.match_number >= stage.first_match
+            && round.context.flight() == stage.flight
+    }
+
+    /// Tallies the results of every round that counts towards the players' scores
+    fn tally(
+        &self,
+        player_reg: &PlayerRegistry,
+        round_reg: &RoundRegistry,
+        stage: &StageStart,
+    ) -> HashMap<PlayerId, PlacementCounter> {
+        let mut counters: HashMap<PlayerId, PlacementCounter> = player_reg
+            .players
+            .keys()
+            .map(|id| (*id, PlacementCounter::default()))
+            .collect();
+        for rnd in round_reg
+            .rounds
+            .values()
+            .filter(|r| self.counts_round(r, stage))
+        {
+            for p in rnd.players.iter() {
+                let Some(counter) = counters.get_mut(p) else {
+                    continue;
+                };
+                if rnd.is_bye() {
+                    counter.byes += 1;
+                } else {
+                    counter.rounds += 1;
+                    counter.points += self.round_points(rnd, p);
+                    counter
+                        .opponents
+                        .extend(rnd.players.iter().filter(|o| *o != p));
+                }
+            }
+        }
+        counters
+    }
+
+    /// Calculates the standings for the active players, only counting the rounds in the given
+    /// stage. Tied players are ordered by the stage's seeding.
+    pub fn get_stage_standings(
+        &self,
+        player_reg: &PlayerRegistry,
+        round_reg: &RoundRegistry,
+        stage: &StageStart,
+    ) -> Standings<StandardScore> {
+        let first = self.settings.first_place_points;
+        let counters = self.tally(player_reg, round_reg, stage);
+        let scores = counters
+            .iter()
+            .map(|(id, counter)| {
+                let mut score = self.new_score();
+                score.match_points = counter.points + self.settings.bye_points * counter.byes;
+                // Players that have only had byes have nothing to compare against
+                if counter.rounds != 0 {
+                    score.mwp = counter.points / (first * counter.rounds);
+                    let (opp_points, opp_rounds) = counter
+                        .opponents
+                        .iter()
+                        .filter_map(|o| counters.get(o))
+                        .fold((r64::default(), 0), |(points, rounds), opp| {
+                            (points + opp.points, rounds + opp.rounds)
+                        });
+                    if opp_rounds != 0 {
+                        score.opp_mwp = opp_points / (first * opp_rounds);
+                    }
+                }
+                (*id, score)
+            })
+            .collect();
+        sort_standings(scores, player_reg, stage)
+    }
+
+    /// Explains how a player's score was calculated, only counting the rounds in the given stage
+    pub fn explain_stage_score(
+        &self,
+        player_reg: &PlayerRegistry,
+        round_reg: &RoundRegistry,
+        id: &PlayerId,
+        stage: &StageStart,
+    ) -> Result<ScoreExplanation, TournamentError> {
+        _ = player_reg.get_player(id)?;
+        let counters = self.tally(player_reg, round_reg, stage);
+        let standings = self.get_stage_standings(player_reg, round_reg, stage);
+        let score = standings
+            .scores
+            .iter()
+            .find_map(|(p, s)| (p == id).then(|| s.clone()));
+        let place = score.as_ref().map(|score| place_of(&standings, score));
+        let mut rounds: Vec<RoundBreakdown> = round_reg
+            .rounds
+            .values()
+            .filter(|r| r.players.contains(id))
+            .map(|r| RoundBreakdown {
+                id: r.id,
+                match_number: r.match_number,
+                counted: self.counts_round(r, stage),
+                match_points: self.round_points(r, id),
+                game_points: r64::default(),
+            })
+            .collect();
+        rounds.sort_by_key(|r| r.match_number);
+        let mut opponents: Vec<OpponentBreakdown> = counters[id]
+            .opponents
+            .iter()
+            .filter_map(|p| counters.get(p).map(|c| (p, c)))
+            .map(|(p, counter)| OpponentBreakdown {
+                id: *p,
+                counted: true,
+                match_points: counter.points,
+                matches: counter.rounds,
+                game_points: r64::default(),
+                games: 0,
+            })
+            .collect();
+        opponents.sort_by_key(|o| o.id);
+        Ok(ScoreExplanation {
+            player: *id,
+            place,
+            score: score.unwrap_or_else(|| self.new_score()),
+            rounds,
+            opponents,
+        })
+    }
+}
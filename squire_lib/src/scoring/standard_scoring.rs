@@ -1,4 +1,5 @@
 use std::{
+    cmp::Ordering,
     collections::{HashMap, HashSet},
     fmt::{Display, Write as _},
 };
@@ -6,11 +7,14 @@ use std::{
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    identifiers::PlayerId,
+    collation::NameCollator,
+    error::TournamentError,
+    identifiers::{PlayerId, RoundId},
+    operations::OpResult,
     players::PlayerRegistry,
     r64,
     rounds::{Round, RoundRegistry},
-    scoring::{Score, Standings},
+    scoring::{Score, StageStart, Standings},
     settings::{SettingsTree, StandardScoringSetting, StandardScoringSettingsTree},
 };
 
@@ -44,6 +48,60 @@ pub struct StandardScore {
     pub include_opp_gwp: bool,
 }
 
+/// A breakdown of how a player's score was calculated, used to explain a player's place in the
+/// standings.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ScoreExplanation {
+    /// The player whose score is being explained
+    pub player: PlayerId,
+    /// The player's place in the standings, starting at 1. Tied players share a place. Players
+    /// that are not in the standings (i.e. dropped players) have no place.
+    pub place: Option<usize>,
+    /// The player's score
+    pub score: StandardScore,
+    /// Every round that the player has played in
+    pub rounds: Vec<RoundBreakdown>,
+    /// Every opponent that the player has played against
+    pub opponents: Vec<OpponentBreakdown>,
+}
+
+/// What a single round contributed to a player's score
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RoundBreakdown {
+    /// The id of the round
+    pub id: RoundId,
+    /// The match number of the round
+    pub match_number: u64,
+    /// Whether or not the round counts towards the score. Only certified rounds count, byes only
+    /// count if the settings allow for it, and rounds from earlier stages only count if scores
+    /// carry over between stages.
+    pub counted: bool,
+    /// The match points that the player earned in the round
+    pub match_points: r64,
+    /// The game points that the player earned in the round
+    pub game_points: r64,
+}
+
+/// What a single opponent contributed to a player's opponent match and game win percentages. The
+/// opponent percentages are the total points of all counted opponents divided by the total points
+/// available to them, so opponents that have played more count for more.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct OpponentBreakdown {
+    /// The id of the opponent
+    pub id: PlayerId,
+    /// Whether or not the opponent counts towards the percentages. Dropped opponents only count if
+    /// the settings allow for it.
+    pub counted: bool,
+    /// The match points that the opponent has earned, not including byes
+    pub match_points: r64,
+    /// The number of matches that the opponent has played, not including byes
+    pub matches: i32,
+    /// The game points that the opponent has earned
+    pub game_points: r64,
+    /// The number of games that the opponent has played
+    pub games: i32,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 /// A counter used to track player info while calculating scores
 struct ScoreCounter {
@@ -131,22 +189,33 @@ impl StandardScoring {
             + game_loss_points * counter.game_losses
     }
 
+    /// Determines if a player's results should be used when calculating the tiebreakers of their
+    /// opponents. Dropped players are only counted if the settings allow for it.
+    fn counts_as_opponent(&self, player_reg: &PlayerRegistry, id: &PlayerId) -> bool {
+        self.settings.include_dropped_opponents
+            || player_reg.get_player(id).is_ok_and(|p| p.can_play())
+    }
+
     /// Updates a single scoring setting
-    pub fn update_setting(&mut self, setting: StandardScoringSetting) {
-        _ = self.settings.update(setting);
+    pub fn update_setting(&mut self, setting: StandardScoringSetting) -> OpResult {
+        self.settings.update(setting)
     }
 
-    /// Calculates all the standing for the active players
-    pub fn get_standings(
+    /// Determines if a round counts towards the scores of its players
+    fn counts_round(&self, round: &Round, stage: &StageStart) -> bool {
+        round.is_certified()
+            && (!round.is_bye() || self.settings.include_byes)
+            && round.match_number >= stage.first_match
+            && round.context.flight() == stage.flight
+    }
+
+    /// Tallies the results of every round that counts towards the players' scores
+    fn tally(
         &self,
         player_reg: &PlayerRegistry,
         round_reg: &RoundRegistry,
-    ) -> Standings<StandardScore> {
-        let StandardScoringSettingsTree {
-            match_win_points,
-            game_win_points,
-            ..
-        } = self.settings;
+        stage: &StageStart,
+    ) -> HashMap<PlayerId, ScoreCounter> {
         let mut counters: HashMap<PlayerId, ScoreCounter> = player_reg
             .players
             .keys()
@@ -155,12 +224,107 @@ impl StandardScoring {
         round_reg
             .rounds
             .values()
-            .filter(|r| r.is_certified())
-            .filter(|r| !r.is_bye() || self.settings.include_byes)
+            .filter(|r| self.counts_round(r, stage))
             .flat_map(|r| r.players.iter().map(move |p| (p, r)))
             .for_each(|(p, r)| {
                 _ = counters.entry(*p).and_modify(|c| c.add_round(r));
             });
+        counters
+    }
+
+    /// Explains how a player's score was calculated, including what each of their rounds and
+    /// opponents contributed.
+    pub fn explain_score(
+        &self,
+        player_reg: &PlayerRegistry,
+        round_reg: &RoundRegistry,
+        id: &PlayerId,
+    ) -> Result<ScoreExplanation, TournamentError> {
+        self.explain_stage_score(player_reg, round_reg, id, &StageStart::default())
+    }
+
+    /// Explains how a player's score was calculated, only counting the rounds in the given stage
+    pub fn explain_stage_score(
+        &self,
+        player_reg: &PlayerRegistry,
+        round_reg: &RoundRegistry,
+        id: &PlayerId,
+        stage: &StageStart,
+    ) -> Result<ScoreExplanation, TournamentError> {
+        _ = player_reg.get_player(id)?;
+        let counters = self.tally(player_reg, round_reg, stage);
+        let standings = self.get_stage_standings(player_reg, round_reg, stage);
+        let score = standings
+            .scores
+            .iter()
+            .find_map(|(p, s)| (p == id).then(|| s.clone()));
+        let place = score.as_ref().map(|score| place_of(&standings, score));
+        let mut rounds: Vec<RoundBreakdown> = round_reg
+            .rounds
+            .values()
+            .filter(|r| r.players.contains(id))
+            .map(|r| {
+                let mut counter = ScoreCounter::new(*id);
+                counter.add_round(r);
+                RoundBreakdown {
+                    id: r.id,
+                    match_number: r.match_number,
+                    counted: self.counts_round(r, stage),
+                    match_points: self.calculate_match_points_with_byes(&counter),
+                    game_points: self.calculate_game_points(&counter),
+                }
+            })
+            .collect();
+        rounds.sort_by_key(|r| r.match_number);
+        let mut opponents: Vec<OpponentBreakdown> = counters[id]
+            .opponents
+            .iter()
+            .filter(|p| *p != id)
+            .filter_map(|p| counters.get(p).map(|c| (p, c)))
+            .map(|(p, counter)| OpponentBreakdown {
+                id: *p,
+                counted: self.counts_as_opponent(player_reg, p),
+                match_points: self.calculate_match_points_without_byes(counter),
+                matches: counter.rounds - counter.byes,
+                game_points: self.calculate_game_points(counter),
+                games: counter.games,
+            })
+            .collect();
+        opponents.sort_by_key(|o| o.id);
+        Ok(ScoreExplanation {
+            player: *id,
+            place,
+            score: score.unwrap_or_else(|| self.new_score()),
+            rounds,
+            opponents,
+        })
+    }
+
+    /// Calculates all the standing for the active players. Scores are recomputed from every
+    /// certified round using the current point values, so changes to the settings also apply to
+    /// rounds that have already been played.
+    pub fn get_standings(
+        &self,
+        player_reg: &PlayerRegistry,
+        round_reg: &RoundRegistry,
+    ) -> Standings<StandardScore> {
+        self.get_stage_standings(player_reg, round_reg, &StageStart::default())
+    }
+
+    /// Calculates the standings for the active players, only counting the rounds in the given
+    /// stage. Tied players are ordered by the stage's seeding.
+    pub fn get_stage_standings(
+        &self,
+        player_reg: &PlayerRegistry,
+        round_reg: &RoundRegistry,
+        stage: &StageStart,
+    ) -> Standings<StandardScore> {
+        let StandardScoringSettingsTree {
+            match_win_points,
+            game_win_points,
+            ..
+        } = self.settings;
+        let counters = self.tally(player_reg, round_reg, stage);
         // We have tallied everyone's round results. Time to calculate everyone's scores
         let mut digest: HashMap<PlayerId, StandardScore> = HashMap::with_capacity(counters.len());
         for (id, counter) in &counters {
@@ -187,7 +351,12 @@ impl StandardScoring {
             let mut opp_matches: i32 = 0;
             let mut opp_gp: r64 = Default::default();
             let mut opp_games: i32 = 0;
-            for plyr in counter.opponents.iter().filter(|i| *i != id) {
+            for plyr in counter
+                .opponents
+                .iter()
+                .filter(|i| *i != id)
+                .filter(|i| self.counts_as_opponent(player_reg, i))
+            {
                 opp_mp += self.calculate_match_points_without_byes(&counters[plyr]);
                 opp_matches += counters[plyr].rounds - counters[plyr].byes;
                 opp_gp += self.calculate_game_points(&counters[plyr]);
@@ -205,15 +374,50 @@ impl StandardScoring {
                 opp_gp / (game_win_points * opp_games)
             };
         }
-        let mut results: Vec<(PlayerId, StandardScore)> = digest
-            .drain()
-            .filter(|(p, _)| player_reg.get_player(p).is_ok_and(|p| p.can_play()))
-            .collect();
-        results.sort_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap());
-        Standings::new(results)
+        sort_standings(digest, player_reg, stage)
     }
 }
 
+/// Orders the scores of the active players into standings. Players that are tied are ordered by
+/// the stage's seeding and then by name.
+pub(super) fn sort_standings(
+    scores: HashMap<PlayerId, StandardScore>,
+    player_reg: &PlayerRegistry,
+    stage: &StageStart,
+) -> Standings<StandardScore> {
+    let mut results: Vec<(PlayerId, StandardScore)> = scores
+        .into_iter()
+        .filter(|(p, _)| player_reg.get_player(p).is_ok_and(|p| p.can_play()))
+        .collect();
+    // Standings go from last place to first place, so better seeds are sorted later. Players
+    // that are still tied are listed alphabetically.
+    let seeds: HashMap<&PlayerId, usize> = stage
+        .seeding
+        .iter()
+        .enumerate()
+        .map(|(i, p)| (p, i))
+        .collect();
+    let seed = |id: &PlayerId| seeds.get(id).copied().unwrap_or(usize::MAX);
+    let name = |id: &PlayerId| player_reg.get_player_name(id).map_or("", String::as_str);
+    let collator = NameCollator::new();
+    results.sort_by(|(a_id, a), (b_id, b)| {
+        a.partial_cmp(b)
+            .unwrap()
+            .then_with(|| seed(b_id).cmp(&seed(a_id)))
+            .then_with(|| collator.compare(name(b_id), name(a_id)))
+    });
+    Standings::new(results)
+}
+
+/// Calculates a player's place in the standings, starting at 1. Tied players share a place.
+pub(super) fn place_of(standings: &Standings<StandardScore>, score: &StandardScore) -> usize {
+    1 + standings
+        .scores
+        .iter()
+        .filter(|(_, s)| s.partial_cmp(score) == Some(Ordering::Greater))
+        .count()
+}
+
 impl StandardScore {
     fn new(
         include_match_points: bool,
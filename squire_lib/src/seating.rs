@@ -0,0 +1,203 @@
+use std::fmt::Write;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{identifiers::PlayerId, pairings::shuffle, players::PlayerRegistry};
+
+/// The default number of players seated at a single draft pod
+pub const DEFAULT_POD_SIZE: usize = 8;
+
+/// A single seat at a draft pod
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct SeatAssignment {
+    /// The (one-indexed) number of the pod
+    pub pod: usize,
+    /// The (one-indexed) seat within the pod
+    pub seat: usize,
+    /// The id of the seated player
+    pub player: PlayerId,
+    /// The display name of the seated player
+    pub name: String,
+}
+
+/// The seating of all active players across all draft pods
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq)]
+pub struct SeatMap {
+    /// Every seat assignment, ordered by pod and then seat
+    pub seats: Vec<SeatAssignment>,
+}
+
+impl SeatMap {
+    /// Seats all active players into pods of at most `pod_size` players. Pods are filled as evenly
+    /// as possible, so pod sizes differ by at most one. Players are ordered by their ids, so every
+    /// copy of the tournament produces the same seat map.
+    pub fn new(plyrs: &PlayerRegistry, pod_size: usize) -> Self {
+        let mut players: Vec<_> = plyrs
+            .players
+            .values()
+            .filter(|p| p.can_play())
+            .map(|p| p.id)
+            .collect();
+        players.sort();
+        Self::from_pods(plyrs, &split_evenly(players, pod_size))
+    }
+
+    /// Creates the seat map for the given pods, which list their players in seat order
+    fn from_pods(plyrs: &PlayerRegistry, pods: &[Vec<PlayerId>]) -> Self {
+        let seats = pods
+            .iter()
+            .enumerate()
+            .flat_map(|(pod, players)| {
+                players
+                    .iter()
+                    .enumerate()
+                    .map(move |(seat, id)| SeatAssignment {
+                        pod: pod + 1,
+                        seat: seat + 1,
+                        player: *id,
+                        name: plyrs
+                            .get_player(id)
+                            .map(|p| p.all_names())
+                            .unwrap_or_default(),
+                    })
+            })
+            .collect();
+        Self { seats }
+    }
+
+    /// Returns the number of pods in the seat map
+    pub fn pod_count(&self) -> usize {
+        self.seats.last().map(|s| s.pod).unwrap_or_default()
+    }
+
+    /// Returns the seat assignment of the given player, if they are seated
+    pub fn get_seat(&self, id: &PlayerId) -> Option<&SeatAssignment> {
+        self.seats.iter().find(|s| s.player == *id)
+    }
+
+    /// Renders the seat map as a CSV with a `pod,seat,player` header
+    pub fn to_csv(&self) -> String {
+        let mut ret = String::from("pod,seat,player\n");
+        for s in self.seats.iter() {
+            let _ = writeln!(
+                ret,
+                "{},{},\"{}\"",
+                s.pod,
+                s.seat,
+                s.name.replace('"', "\"\"")
+            );
+        }
+        ret
+    }
+
+    /// Renders the seat map as a printable HTML page, with one table per pod
+    pub fn to_html(&self, title: &str, css: &str) -> String {
+        let mut ret: String = "<!DOCTYPE HTML>".to_string();
+
+        let _ = write!(ret, "<html lang=\"en\">");
+        let _ = write!(ret, "<head><style>");
+        let _ = write!(ret, "{css}");
+        let _ = write!(ret, "</style></head>");
+        let _ = write!(ret, "<body>");
+        let _ = write!(ret, "<h1>{}</h1>", html_escape::encode_text(title));
+
+        for pod in 1..=self.pod_count() {
+            let _ = write!(ret, "<div class='pod' style='break-inside: avoid;'>");
+            let _ = write!(ret, "<h2>Pod #{pod}</h2>");
+            let _ = write!(ret, "<table style='width: 100%;'>");
+            let _ = write!(ret, "<tr><th>Seat</th><th>Player</th></tr>");
+            for s in self.seats.iter().filter(|s| s.pod == pod) {
+                let _ = write!(
+                    ret,
+                    "<tr><td>{}</td><td>{}</td></tr>",
+                    s.seat,
+                    html_escape::encode_text(&s.name)
+                );
+            }
+            let _ = write!(ret, "</table></div>");
+        }
+        let _ = write!(ret, "</body></html>");
+
+        ret
+    }
+}
+
+/// The draft pods that a tournament's players have been split into. Unlike a `SeatMap`, which is
+/// calculated from the current players, the pods are recorded in the tournament so that pairings
+/// can be kept within each pod.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq)]
+pub struct DraftPods {
+    /// The players in each pod, in seat order
+    pub pods: Vec<Vec<PlayerId>>,
+}
+
+impl DraftPods {
+    /// Splits the players into pods of at most `pod_size` players. Pods are filled as evenly as
+    /// possible. The players are shuffled with the given seed to assign seats, so every copy of
+    /// the tournament produces the same pods.
+    pub fn new(mut players: Vec<PlayerId>, pod_size: usize, seed: u64) -> Self {
+        players.sort();
+        let mut state = seed;
+        shuffle(&mut players, &mut state);
+        Self {
+            pods: split_evenly(players, pod_size),
+        }
+    }
+
+    /// Calculates if the players have not been split into pods
+    pub fn is_empty(&self) -> bool {
+        self.pods.is_empty()
+    }
+
+    /// Returns the (one-indexed) pod that the player was seated at, if they were seated
+    pub fn pod_of(&self, id: &PlayerId) -> Option<usize> {
+        self.pods
+            .iter()
+            .position(|pod| pod.contains(id))
+            .map(|pod| pod + 1)
+    }
+
+    /// Returns the (one-indexed) pod that all of the given players were seated at, if they share
+    /// one
+    pub fn shared_pod(&self, plyrs: &[PlayerId]) -> Option<usize> {
+        let (first, rest) = plyrs.split_first()?;
+        let pod = self.pod_of(first)?;
+        rest.iter()
+            .all(|p| self.pods[pod - 1].contains(p))
+            .then_some(pod)
+    }
+
+    /// Creates the seat map for the pods
+    pub fn seat_map(&self, plyrs: &PlayerRegistry) -> SeatMap {
+        SeatMap::from_pods(plyrs, &self.pods)
+    }
+
+    /// Gives a player a new id, i.e. when a guest is linked to an account. If the new id is
+    /// already seated (i.e. when merging duplicate players), the old id's seat is removed.
+    pub(crate) fn swap_player_ids(&mut self, old: PlayerId, new: PlayerId) {
+        if self.pod_of(&new).is_some() {
+            self.pods
+                .iter_mut()
+                .for_each(|pod| pod.retain(|p| *p != old));
+        } else {
+            self.pods
+                .iter_mut()
+                .flatten()
+                .filter(|p| **p == old)
+                .for_each(|p| *p = new);
+        }
+    }
+}
+
+/// Splits the players into groups of at most `size` players, whose sizes differ by at most one
+fn split_evenly(players: Vec<PlayerId>, size: usize) -> Vec<Vec<PlayerId>> {
+    if players.is_empty() {
+        return Vec::new();
+    }
+    let count = players.len().div_ceil(size.max(1));
+    let (base, extra) = (players.len() / count, players.len() % count);
+    let mut iter = players.into_iter();
+    (0..count)
+        .map(|i| iter.by_ref().take(base + usize::from(i < extra)).collect())
+        .collect()
+}
@@ -0,0 +1,143 @@
+use std::{cmp::Reverse, collections::HashMap};
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    identifiers::{PlayerId, SeriesId, SquireAccountId, TournamentId},
+    r64,
+    tournament::Tournament,
+};
+
+/// How the results of the events in a series are combined into its leaderboard
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SeriesScoring {
+    /// A player's points from every event are added together
+    #[default]
+    TotalPoints,
+    /// Only a player's best results count, up to the given number of events
+    BestResults(usize),
+}
+
+/// A recurring series of tournaments (e.g. a weekly league) whose results are combined into a
+/// single leaderboard.
+///
+/// Players are matched across events by their ids. Players that register with an account have the
+/// same id in every event, but guests get a new id in each event, so their results are not combined.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Series {
+    /// The series' id
+    pub id: SeriesId,
+    /// The account that runs the series
+    pub owner: SquireAccountId,
+    /// The name of the series
+    pub name: String,
+    /// The tournaments in the series, in the order that they were added
+    pub events: Vec<TournamentId>,
+    /// How the results of the events are combined
+    pub scoring: SeriesScoring,
+}
+
+/// A player's combined results across a series
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct SeriesResult {
+    /// The player's id
+    pub player: PlayerId,
+    /// The player's name in the most recent event that they played in
+    pub name: String,
+    /// The number of points that count towards the leaderboard
+    pub points: r64,
+    /// The number of events that the player played in
+    pub events: usize,
+}
+
+/// The leaderboard of a series
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq)]
+pub struct SeriesStandings {
+    /// The combined results of every player, from first place to last place. Players with the same
+    /// points are ordered by the number of events that they played in and then by name.
+    pub results: Vec<SeriesResult>,
+}
+
+impl Series {
+    /// Creates a new series without any events
+    pub fn new(owner: SquireAccountId, name: String, scoring: SeriesScoring) -> Self {
+        Self {
+            id: SeriesId::new(Uuid::new_v4()),
+            owner,
+            name,
+            events: Vec::new(),
+            scoring,
+        }
+    }
+
+    /// Adds a tournament to the series. Returns false if the tournament was already in the series.
+    pub fn add_event(&mut self, id: TournamentId) -> bool {
+        let digest = !self.events.contains(&id);
+        if digest {
+            self.events.push(id);
+        }
+        digest
+    }
+
+    /// Removes a tournament from the series. Returns false if the tournament was not in the series.
+    pub fn remove_event(&mut self, id: TournamentId) -> bool {
+        let len = self.events.len();
+        self.events.retain(|t_id| *t_id != id);
+        len != self.events.len()
+    }
+
+    /// Calculates the leaderboard of the series. A player's points from an event are their match
+    /// points in that event's standings. Tournaments that are not in the series are ignored.
+    ///
+    /// The players' names are taken from the given tournaments as they are, so the tournaments
+    /// should be redacted before the leaderboard is shown to anyone that is not one of their
+    /// officials.
+    pub fn standings<'a, I>(&self, tourns: I) -> SeriesStandings
+    where
+        I: IntoIterator<Item = &'a Tournament>,
+    {
+        let mut tourns: Vec<_> = tourns
+            .into_iter()
+            .filter_map(|t| Some((self.events.iter().position(|id| *id == t.id)?, t)))
+            .collect();
+        tourns.sort_by_key(|(i, _)| *i);
+        tourns.dedup_by_key(|(i, _)| *i);
+
+        let mut players: HashMap<PlayerId, (String, Vec<r64>)> = HashMap::new();
+        for (_, tourn) in tourns {
+            for (id, score) in tourn.get_standings().scores {
+                let Ok(plyr) = tourn.player_reg.get_player(&id) else {
+                    continue;
+                };
+                let entry = players.entry(id).or_default();
+                entry.0.clone_from(&plyr.name);
+                entry.1.push(score.match_points);
+            }
+        }
+
+        let mut results: Vec<_> = players
+            .into_iter()
+            .map(|(player, (name, mut points))| {
+                let events = points.len();
+                if let SeriesScoring::BestResults(count) = self.scoring {
+                    points.sort_by_key(|p| Reverse(*p));
+                    points.truncate(count);
+                }
+                SeriesResult {
+                    player,
+                    name,
+                    points: points.into_iter().sum(),
+                    events,
+                }
+            })
+            .collect();
+        results.sort_by(|a, b| {
+            b.points
+                .cmp(&a.points)
+                .then_with(|| b.events.cmp(&a.events))
+                .then_with(|| a.name.cmp(&b.name))
+        });
+        SeriesStandings { results }
+    }
+}
@@ -1,11 +1,15 @@
-use std::time::Duration;
+use std::{fmt, time::Duration};
 
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
-use super::SettingsTree;
+use super::{PermissionMatrix, SettingsTree};
 use crate::{
     error::TournamentError,
     operations::{OpData, OpResult},
+    prizes::PrizeStructure,
+    rounds::{Round, RoundResult},
+    schedule::Schedule,
 };
 
 /// An enum that encode all of the general tournament settings
@@ -27,6 +31,212 @@ pub enum GeneralSetting {
     RequireDeckReg(bool),
     /// Adjusts the amount of time new rounds will have
     RoundLength(Duration),
+    /// Adjusts the maximum number of players in the tournament (zero means there is no cap)
+    PlayerCap(u32),
+    /// Adjusts which operations judges and players can perform
+    Permissions(PermissionMatrix),
+    /// Adjusts when players' decklists become visible to everyone
+    DecklistVisibility(DecklistVisibility),
+    /// Adjusts how players' names are shown in the public standings and pairings
+    PublicNames(PublicNameStyle),
+    /// Adjusts how many games are played in each match
+    MatchStructure(MatchStructure),
+    /// Adjusts how long after a round's time runs out its result is certified automatically
+    /// (`None` means results are never certified automatically)
+    AutoCertify(Option<Duration>),
+    /// Adjusts how players that don't arrive at their table are penalized (`None` means tardy
+    /// players are not flagged)
+    Tardiness(Option<TardinessPolicy>),
+    /// Adjusts when the tournament and its rounds are scheduled to start
+    Schedule(Schedule),
+    /// Adjusts who can find and follow the tournament
+    Visibility(Visibility),
+    /// Adjusts when players can register for the tournament
+    RegistrationWindow(RegistrationWindow),
+    /// Adjusts how the tournament's prize pool is paid out (`None` means there are no prizes)
+    Prizes(Option<PrizeStructure>),
+}
+
+/// When the players' decklists become visible to users that aren't officials. Officials can always
+/// see decklists.
+#[derive(Serialize, Deserialize, Debug, Default, Hash, Clone, Copy, PartialEq, Eq)]
+pub enum DecklistVisibility {
+    /// Decklists are never made public
+    Never,
+    /// Decklists are made public once the tournament starts, i.e. an open-decklist event
+    AfterStart,
+    /// Decklists are made public once the tournament is over, i.e. a closed-decklist event
+    #[default]
+    AfterEnd,
+}
+
+/// How players' names are shown in the public standings and pairings, which lets events with
+/// privacy requirements (e.g. events for minors) avoid publishing full names
+#[derive(Serialize, Deserialize, Debug, Default, Hash, Clone, Copy, PartialEq, Eq)]
+pub enum PublicNameStyle {
+    /// Players are shown by their full names
+    #[default]
+    FullName,
+    /// Players are shown by their first name and the initial of their last name, e.g. "Jane D."
+    FirstNameInitial,
+    /// Players are shown by an alias that is derived from their id
+    Alias,
+}
+
+/// Who can find and follow a tournament. Officials and players can always follow their
+/// tournaments.
+#[derive(Serialize, Deserialize, Debug, Default, Hash, Clone, Copy, PartialEq, Eq)]
+pub enum Visibility {
+    /// The tournament is listed and anyone can follow it
+    #[default]
+    Public,
+    /// The tournament is not listed, but anyone with its id (i.e. a link) can follow it
+    Unlisted,
+    /// The tournament is not listed, and only users with an invite can follow it
+    Private,
+}
+
+impl Visibility {
+    /// Returns if the tournament is listed alongside other tournaments
+    pub fn is_listed(self) -> bool {
+        self == Visibility::Public
+    }
+
+    /// Returns if an invite is needed to follow the tournament
+    pub fn needs_invite(self) -> bool {
+        self == Visibility::Private
+    }
+}
+
+/// When players can register for a tournament. Registration is also closed whenever the
+/// tournament's organizers close it by hand, and officials can register players at any time.
+#[derive(Serialize, Deserialize, Debug, Default, Hash, Clone, Copy, PartialEq, Eq)]
+pub struct RegistrationWindow {
+    /// When registration opens. If this is not set, registration is open from the start.
+    #[serde(default)]
+    pub opens: Option<DateTime<Utc>>,
+    /// When registration closes. If this is not set, registration stays open until the tournament
+    /// starts.
+    #[serde(default)]
+    pub closes: Option<DateTime<Utc>>,
+}
+
+impl RegistrationWindow {
+    /// Checks that registration does not close before it opens
+    pub fn validate(&self) -> Result<(), TournamentError> {
+        match (self.opens, self.closes) {
+            (Some(opens), Some(closes)) if closes <= opens => {
+                Err(TournamentError::InvalidRegistrationWindow)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Calculates if the given time is inside the window
+    pub fn contains(&self, now: DateTime<Utc>) -> bool {
+        self.opens.map_or(true, |opens| opens <= now)
+            && self.closes.map_or(true, |closes| now < closes)
+    }
+
+    /// Returns the next time that registration opens or closes, which clients can count down to
+    pub fn next_change(&self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        [self.opens, self.closes]
+            .into_iter()
+            .flatten()
+            .filter(|time| now < *time)
+            .min()
+    }
+}
+
+/// How many games are played in each match, which limits the results that can be recorded
+#[derive(Serialize, Deserialize, Debug, Hash, Clone, Copy, PartialEq, Eq)]
+pub enum MatchStructure {
+    /// There is no limit on the number of games in a match
+    Unrestricted,
+    /// Matches are played as a best of the given number of games, so a player wins the match by
+    /// winning more than half of the games (i.e. two games in a best-of-three)
+    BestOf(u32),
+}
+
+impl MatchStructure {
+    /// Returns the number of games that a player needs to win to win the match, if there is a
+    /// limit
+    pub fn games_to_win(self) -> Option<u32> {
+        match self {
+            MatchStructure::Unrestricted => None,
+            MatchStructure::BestOf(games) => Some(games / 2 + 1),
+        }
+    }
+
+    /// Returns the most games that can be won across all players in a match, if there is a limit.
+    /// Drawn games are not counted.
+    pub fn max_games(self) -> Option<u32> {
+        match self {
+            MatchStructure::Unrestricted => None,
+            MatchStructure::BestOf(games) => Some(games),
+        }
+    }
+
+    /// Checks that a result can be recorded for the round. A player can not win more games than
+    /// are needed to win the match, and the players can not win more games between them than can
+    /// be played.
+    pub fn check_result(self, rnd: &Round, result: &RoundResult) -> Result<(), TournamentError> {
+        let (RoundResult::Wins(p_id, wins), Some(to_win), Some(max)) =
+            (result, self.games_to_win(), self.max_games())
+        else {
+            return Ok(());
+        };
+        let others: u32 = rnd
+            .results
+            .iter()
+            .filter(|(p, _)| *p != p_id)
+            .map(|(_, w)| *w)
+            .sum();
+        if *wins > to_win || others + wins > max {
+            Err(TournamentError::TooManyWins)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl fmt::Display for MatchStructure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MatchStructure::Unrestricted => write!(f, "Unrestricted"),
+            MatchStructure::BestOf(games) => write!(f, "Best of {games}"),
+        }
+    }
+}
+
+/// How players that have not checked in at their table are penalized
+#[derive(Serialize, Deserialize, Debug, Hash, Clone, Copy, PartialEq, Eq)]
+pub struct TardinessPolicy {
+    /// How long after a round starts that players have to check in at their table
+    pub grace: Duration,
+    /// The penalty that judges give to tardy players
+    pub penalty: TardinessPenalty,
+}
+
+/// The penalties that can be given to a player for being tardy
+#[derive(Serialize, Deserialize, Debug, Hash, Clone, Copy, PartialEq, Eq)]
+pub enum TardinessPenalty {
+    /// Each of the player's opponents is given a game win
+    GameLoss,
+    /// The player's opponents are given the match, which is then certified
+    MatchLoss,
+    /// The player is dropped from the tournament
+    Drop,
+}
+
+impl fmt::Display for TardinessPenalty {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TardinessPenalty::GameLoss => write!(f, "Game loss"),
+            TardinessPenalty::MatchLoss => write!(f, "Match loss"),
+            TardinessPenalty::Drop => write!(f, "Drop"),
+        }
+    }
 }
 
 /// A structure that holds a value for each general tournament setting
@@ -48,6 +258,46 @@ pub struct GeneralSettingsTree {
     pub require_deck_reg: bool,
     /// The length of all new rounds
     pub round_length: Duration,
+    /// The maximum number of registered players. Players that register beyond this are
+    /// waitlisted. A cap of zero means there is no cap.
+    #[serde(default)]
+    pub player_cap: u32,
+    /// Which operations judges and players can perform
+    #[serde(default)]
+    pub permissions: PermissionMatrix,
+    /// When the players' decklists become visible to everyone
+    #[serde(default)]
+    pub decklist_visibility: DecklistVisibility,
+    /// How players' names are shown in the public standings and pairings
+    #[serde(default)]
+    pub public_names: PublicNameStyle,
+    /// How many games are played in each match
+    #[serde(default = "default_match_structure")]
+    pub match_structure: MatchStructure,
+    /// How long after a round's time runs out its result is certified automatically, if at all.
+    /// The wait starts over whenever the result changes.
+    #[serde(default)]
+    pub auto_certify: Option<Duration>,
+    /// How players that don't check in at their table are penalized, if at all
+    #[serde(default)]
+    pub tardiness: Option<TardinessPolicy>,
+    /// When the tournament and its rounds are scheduled to start
+    #[serde(default)]
+    pub schedule: Schedule,
+    /// Who can find and follow the tournament
+    #[serde(default)]
+    pub visibility: Visibility,
+    /// When players can register for the tournament
+    #[serde(default)]
+    pub registration: RegistrationWindow,
+    /// How the tournament's prize pool is paid out, if it has one
+    #[serde(default)]
+    pub prizes: Option<PrizeStructure>,
+}
+
+/// Tournaments from before match structures were added did not limit results
+fn default_match_structure() -> MatchStructure {
+    MatchStructure::Unrestricted
 }
 
 impl GeneralSettingsTree {
@@ -78,6 +328,31 @@ impl SettingsTree for GeneralSettingsTree {
             GeneralSetting::RequireCheckIn(check_in) => self.require_check_in = check_in,
             GeneralSetting::RequireDeckReg(deck_reg) => self.require_deck_reg = deck_reg,
             GeneralSetting::RoundLength(len) => self.round_length = len,
+            GeneralSetting::PlayerCap(cap) => self.player_cap = cap,
+            GeneralSetting::Permissions(perms) => self.permissions = perms,
+            GeneralSetting::DecklistVisibility(vis) => self.decklist_visibility = vis,
+            GeneralSetting::PublicNames(style) => self.public_names = style,
+            GeneralSetting::MatchStructure(MatchStructure::BestOf(0)) => {
+                return Err(TournamentError::InvalidMatchStructure)
+            }
+            GeneralSetting::MatchStructure(structure) => self.match_structure = structure,
+            GeneralSetting::AutoCertify(wait) => self.auto_certify = wait,
+            GeneralSetting::Tardiness(policy) => self.tardiness = policy,
+            GeneralSetting::Schedule(mut schedule) => {
+                schedule.validate()?;
+                self.schedule = schedule;
+            }
+            GeneralSetting::Visibility(vis) => self.visibility = vis,
+            GeneralSetting::RegistrationWindow(window) => {
+                window.validate()?;
+                self.registration = window;
+            }
+            GeneralSetting::Prizes(prizes) => {
+                if let Some(prizes) = &prizes {
+                    prizes.validate()?;
+                }
+                self.prizes = prizes;
+            }
         }
         Ok(OpData::Nothing)
     }
@@ -93,6 +368,17 @@ impl SettingsTree for GeneralSettingsTree {
                 GeneralSetting::RequireCheckIn(self.require_check_in),
                 GeneralSetting::RequireDeckReg(self.require_deck_reg),
                 GeneralSetting::RoundLength(self.round_length),
+                GeneralSetting::PlayerCap(self.player_cap),
+                GeneralSetting::Permissions(self.permissions.clone()),
+                GeneralSetting::DecklistVisibility(self.decklist_visibility),
+                GeneralSetting::PublicNames(self.public_names),
+                GeneralSetting::MatchStructure(self.match_structure),
+                GeneralSetting::AutoCertify(self.auto_certify),
+                GeneralSetting::Tardiness(self.tardiness),
+                GeneralSetting::Schedule(self.schedule.clone()),
+                GeneralSetting::Visibility(self.visibility),
+                GeneralSetting::RegistrationWindow(self.registration),
+                GeneralSetting::Prizes(self.prizes.clone()),
             ]
             .into_iter(),
         )
@@ -3,11 +3,15 @@ use serde::{Deserialize, Serialize};
 
 mod general;
 mod pairing;
+mod permissions;
 mod scoring;
+mod template;
 
 pub use general::*;
 pub use pairing::*;
+pub use permissions::*;
 pub use scoring::*;
+pub use template::*;
 
 use crate::{operations::OpResult, tournament::TournamentPreset};
 
@@ -5,6 +5,7 @@ use crate::{
     error::TournamentError,
     operations::{OpData, OpResult},
     pairings::PairingAlgorithm,
+    rounds::TableAssignment,
     tournament::TournamentPreset,
 };
 
@@ -27,6 +28,10 @@ pub enum CommonPairingSetting {
     RepairTolerance(u64),
     /// Adjusts the algorithm that will be used to pair players
     Algorithm(PairingAlgorithm),
+    /// Adjusts how table numbers are assigned to new rounds
+    TableAssignment(TableAssignment),
+    /// Adjusts if a player can receive more than one bye
+    AllowRepeatByes(bool),
 }
 
 /// Settings for a given pairing style
@@ -67,13 +72,27 @@ pub struct PairingCommonSettingsTree {
     pub repair_tolerance: u64,
     /// The algorithm used to pair players
     pub algorithm: PairingAlgorithm,
+    /// The strategy used to assign table numbers to new rounds
+    #[serde(default)]
+    pub table_assignment: TableAssignment,
+    /// Whether or not a player can be given a bye after already having had one. Players without
+    /// a bye are always preferred.
+    #[serde(default = "default_allow_repeat_byes")]
+    pub allow_repeat_byes: bool,
+}
+
+fn default_allow_repeat_byes() -> bool {
+    true
 }
 
 impl PairingSettingsTree {
     /// Creates a new, default settings tree
     pub fn with_preset(preset: TournamentPreset) -> Self {
         Self {
-            common: Default::default(),
+            common: PairingCommonSettingsTree {
+                match_size: preset.match_size(),
+                ..Default::default()
+            },
             style: PairingStyleSettingsTree::with_preset(preset),
         }
     }
@@ -103,7 +122,9 @@ impl PairingStyleSettingsTree {
     /// Creates a new tree using a tournament preset
     pub fn with_preset(preset: TournamentPreset) -> Self {
         match preset {
-            TournamentPreset::Swiss => Self::Swiss(Default::default()),
+            TournamentPreset::Swiss | TournamentPreset::Multiplayer => {
+                Self::Swiss(Default::default())
+            }
             TournamentPreset::Fluid => Self::Fluid(Default::default()),
         }
     }
@@ -145,6 +166,8 @@ impl SettingsTree for PairingCommonSettingsTree {
             }
             CommonPairingSetting::RepairTolerance(tol) => self.repair_tolerance = tol,
             CommonPairingSetting::Algorithm(alg) => self.algorithm = alg,
+            CommonPairingSetting::TableAssignment(strat) => self.table_assignment = strat,
+            CommonPairingSetting::AllowRepeatByes(allow) => self.allow_repeat_byes = allow,
         }
         Ok(OpData::Nothing)
     }
@@ -155,6 +178,8 @@ impl SettingsTree for PairingCommonSettingsTree {
                 CommonPairingSetting::MatchSize(self.match_size),
                 CommonPairingSetting::RepairTolerance(self.repair_tolerance),
                 CommonPairingSetting::Algorithm(self.algorithm),
+                CommonPairingSetting::TableAssignment(self.table_assignment),
+                CommonPairingSetting::AllowRepeatByes(self.allow_repeat_byes),
             ]
             .into_iter()
             .map(Into::into),
@@ -168,6 +193,8 @@ impl SettingsTree for PairingCommonSettingsTree {
 pub enum SwissPairingSetting {
     /// Whether or not player need to check in before a round is paired
     DoCheckIns(bool),
+    /// Whether or not players are only paired against players in their draft pod
+    PairWithinPods(bool),
 }
 
 /// A structure that holds a value for each pairing setting
@@ -175,6 +202,10 @@ pub enum SwissPairingSetting {
 pub struct SwissPairingSettingsTree {
     /// Whether or not checkins need to performed before pairings can be created
     pub do_checkins: bool,
+    /// Whether or not players are only paired against players in their draft pod, once the players
+    /// have been split into pods
+    #[serde(default)]
+    pub pair_within_pods: bool,
 }
 
 impl SettingsTree for SwissPairingSettingsTree {
@@ -183,14 +214,19 @@ impl SettingsTree for SwissPairingSettingsTree {
     fn update(&mut self, setting: Self::Setting) -> OpResult {
         match setting {
             SwissPairingSetting::DoCheckIns(b) => self.do_checkins = b,
+            SwissPairingSetting::PairWithinPods(b) => self.pair_within_pods = b,
         }
         Ok(OpData::Nothing)
     }
 
     fn iter(&self) -> Box<dyn Iterator<Item = Self::Setting>> {
-        Box::new(std::iter::once(SwissPairingSetting::DoCheckIns(
-            self.do_checkins,
-        )))
+        Box::new(
+            [
+                SwissPairingSetting::DoCheckIns(self.do_checkins),
+                SwissPairingSetting::PairWithinPods(self.pair_within_pods),
+            ]
+            .into_iter(),
+        )
     }
 }
 
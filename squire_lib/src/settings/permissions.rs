@@ -0,0 +1,142 @@
+use std::collections::BTreeSet;
+
+use serde::{Deserialize, Serialize};
+
+/// A group of related operations that can be granted to or taken away from a role. Operations
+/// that shape the tournament as a whole (e.g. starting it, changing its settings, or adding
+/// officials) are not covered by any permission and can only be done by admins.
+#[derive(Serialize, Deserialize, Debug, Hash, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Permission {
+    /// Checking into the tournament
+    CheckIn,
+    /// Setting a player's gamer tag
+    SetGamerTag,
+    /// Registering players and guests
+    RegisterPlayers,
+    /// Adding and removing decks
+    ManageDecks,
+    /// Marking players as ready or unready for their next round
+    ReadyPlayers,
+    /// Recording the results of rounds
+    RecordResults,
+    /// Confirming the results of rounds
+    ConfirmResults,
+    /// Overwriting the results of rounds that have been confirmed
+    OverwriteResults,
+    /// Giving rounds time extensions
+    TimeExtensions,
+    /// Dropping and reinstating players
+    DropPlayers,
+    /// Pairing rounds, creating rounds by hand, giving byes, and removing rounds
+    PairRounds,
+    /// Managing alternates and the waitlist
+    ManageAlternates,
+    /// Reserving and assigning tables
+    ManageTables,
+    /// Marking feature matches and setting their streams
+    FeatureMatches,
+    /// Starting and completing deck checks
+    DeckChecks,
+}
+
+impl Permission {
+    /// Every permission, in order
+    pub const ALL: [Permission; 15] = [
+        Permission::CheckIn,
+        Permission::SetGamerTag,
+        Permission::RegisterPlayers,
+        Permission::ManageDecks,
+        Permission::ReadyPlayers,
+        Permission::RecordResults,
+        Permission::ConfirmResults,
+        Permission::OverwriteResults,
+        Permission::TimeExtensions,
+        Permission::DropPlayers,
+        Permission::PairRounds,
+        Permission::ManageAlternates,
+        Permission::ManageTables,
+        Permission::FeatureMatches,
+        Permission::DeckChecks,
+    ];
+}
+
+/// The roles whose permissions can be configured. Admins hold every permission and spectators
+/// can only register.
+#[derive(Serialize, Deserialize, Debug, Hash, Clone, Copy, PartialEq, Eq)]
+pub enum PermissionRole {
+    /// The tournament's judges, who act on other players
+    Judge,
+    /// The tournament's players, who only act on themselves
+    Player,
+}
+
+/// Which permissions each role holds. The default matrix lets judges do what a floor judge does
+/// and players do everything that they can do for themselves.
+#[derive(Serialize, Deserialize, Debug, Hash, Clone, PartialEq, Eq)]
+pub struct PermissionMatrix {
+    /// The permissions that judges hold
+    pub judge: BTreeSet<Permission>,
+    /// The permissions that players hold
+    pub player: BTreeSet<Permission>,
+}
+
+impl Default for PermissionMatrix {
+    fn default() -> Self {
+        use Permission::*;
+        Self {
+            judge: [
+                RegisterPlayers,
+                ManageDecks,
+                ReadyPlayers,
+                RecordResults,
+                ConfirmResults,
+                TimeExtensions,
+                DeckChecks,
+            ]
+            .into_iter()
+            .collect(),
+            player: [
+                CheckIn,
+                SetGamerTag,
+                ManageDecks,
+                ReadyPlayers,
+                RecordResults,
+                ConfirmResults,
+                DropPlayers,
+            ]
+            .into_iter()
+            .collect(),
+        }
+    }
+}
+
+impl PermissionMatrix {
+    /// Calculates if the role holds the permission
+    pub fn allows(&self, role: PermissionRole, perm: Permission) -> bool {
+        self.role(role).contains(&perm)
+    }
+
+    /// Gives the permission to the role
+    pub fn grant(&mut self, role: PermissionRole, perm: Permission) {
+        _ = self.role_mut(role).insert(perm);
+    }
+
+    /// Takes the permission away from the role
+    pub fn revoke(&mut self, role: PermissionRole, perm: Permission) {
+        _ = self.role_mut(role).remove(&perm);
+    }
+
+    fn role(&self, role: PermissionRole) -> &BTreeSet<Permission> {
+        match role {
+            PermissionRole::Judge => &self.judge,
+            PermissionRole::Player => &self.player,
+        }
+    }
+
+    fn role_mut(&mut self, role: PermissionRole) -> &mut BTreeSet<Permission> {
+        match role {
+            PermissionRole::Judge => &mut self.judge,
+            PermissionRole::Player => &mut self.player,
+        }
+    }
+}
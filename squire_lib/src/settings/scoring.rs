@@ -2,8 +2,10 @@ use serde::{Deserialize, Serialize};
 
 use super::SettingsTree;
 use crate::{
+    error::TournamentError,
     operations::{OpData, OpResult},
     r64,
+    scoring::StageScoring,
     tournament::TournamentPreset,
 };
 
@@ -21,21 +23,32 @@ pub enum ScoringSetting {
 pub enum ScoringStyleSetting {
     /// Settings for the standard scoring style
     Standard(StandardScoringSetting),
+    /// Settings for the multiplayer scoring style
+    Multiplayer(MultiplayerScoringSetting),
 }
 
 /// An enum that captures common settings of all scoring systems
 #[derive(Serialize, Deserialize, Debug, Hash, Clone, PartialEq, Eq)]
-pub enum CommonScoringSetting {}
+pub enum CommonScoringSetting {
+    /// Adjusts how scores from earlier stages are used after a cut to the next stage
+    StageScoring(StageScoring),
+}
 
 /// The set of settings common to all scoring systems
 #[derive(Serialize, Deserialize, Debug, Hash, Clone, PartialEq, Eq)]
-pub struct CommonScoringSettingsTree;
+pub struct CommonScoringSettingsTree {
+    /// How scores from earlier stages are used after a cut to the next stage
+    #[serde(default)]
+    pub stage_scoring: StageScoring,
+}
 
 /// A enum that holds settings for the active scoring sytle
 #[derive(Serialize, Deserialize, Debug, Hash, Clone, PartialEq, Eq)]
 pub enum ScoringStyleSettingsTree {
     /// The set of settings for standard-style scoring
     Standard(StandardScoringSettingsTree),
+    /// The set of settings for multiplayer-style scoring
+    Multiplayer(MultiplayerScoringSettingsTree),
 }
 
 /// A structure that holds a value for each scoring setting
@@ -52,7 +65,7 @@ impl ScoringSettingsTree {
     /// Creates a new, default settings tree
     pub fn with_preset(preset: TournamentPreset) -> Self {
         Self {
-            common: CommonScoringSettingsTree,
+            common: CommonScoringSettingsTree::new(),
             style: ScoringStyleSettingsTree::with_preset(preset),
         }
     }
@@ -81,19 +94,27 @@ impl SettingsTree for ScoringSettingsTree {
 impl SettingsTree for CommonScoringSettingsTree {
     type Setting = CommonScoringSetting;
 
-    fn update(&mut self, _setting: Self::Setting) -> OpResult {
+    fn update(&mut self, setting: Self::Setting) -> OpResult {
+        match setting {
+            CommonScoringSetting::StageScoring(stage_scoring) => self.stage_scoring = stage_scoring,
+        }
         Ok(OpData::Nothing)
     }
 
     fn iter(&self) -> Box<dyn Iterator<Item = Self::Setting>> {
-        Box::new(std::iter::empty())
+        Box::new(std::iter::once(CommonScoringSetting::StageScoring(
+            self.stage_scoring,
+        )))
     }
 }
 
 impl ScoringStyleSettingsTree {
     /// Creates a new tree from a tournament preset
-    pub fn with_preset(_: TournamentPreset) -> Self {
-        Self::default()
+    pub fn with_preset(preset: TournamentPreset) -> Self {
+        match preset {
+            TournamentPreset::Swiss | TournamentPreset::Fluid => Self::Standard(Default::default()),
+            TournamentPreset::Multiplayer => Self::Multiplayer(Default::default()),
+        }
     }
 }
 
@@ -105,12 +126,18 @@ impl SettingsTree for ScoringStyleSettingsTree {
             (ScoringStyleSettingsTree::Standard(style), ScoringStyleSetting::Standard(setting)) => {
                 style.update(setting)
             }
+            (
+                ScoringStyleSettingsTree::Multiplayer(style),
+                ScoringStyleSetting::Multiplayer(setting),
+            ) => style.update(setting),
+            _ => Err(TournamentError::IncompatibleScoringSystem),
         }
     }
 
     fn iter(&self) -> Box<dyn Iterator<Item = Self::Setting>> {
         match self {
             ScoringStyleSettingsTree::Standard(tree) => Box::new(tree.iter().map(Into::into)),
+            ScoringStyleSettingsTree::Multiplayer(tree) => Box::new(tree.iter().map(Into::into)),
         }
     }
 }
@@ -147,6 +174,8 @@ pub enum StandardScoringSetting {
     IncludeOppMwp(bool),
     /// Adjusts if opponent gwp is used in scoring
     IncludeOppGwp(bool),
+    /// Adjusts if the results of dropped players count towards their opponents' tiebreakers
+    IncludeDroppedOpponents(bool),
 }
 
 /// A structure that holds a value for each scoring setting
@@ -167,12 +196,39 @@ pub struct StandardScoringSettingsTree {
     pub include_gwp: bool,
     pub include_opp_mwp: bool,
     pub include_opp_gwp: bool,
+    /// Whether or not the results of dropped players are used when calculating opponent mwp and
+    /// gwp. When false, dropped players are removed from everyone's list of opponents.
+    #[serde(default = "default_include_dropped_opponents")]
+    pub include_dropped_opponents: bool,
+}
+
+fn default_include_dropped_opponents() -> bool {
+    true
 }
 
 impl SettingsTree for StandardScoringSettingsTree {
     type Setting = StandardScoringSetting;
 
     fn update(&mut self, setting: Self::Setting) -> OpResult {
+        // Win points are used as the denominator of the win percentages, so they must be positive
+        match setting {
+            StandardScoringSetting::MatchWinPoints(points)
+            | StandardScoringSetting::GameWinPoints(points)
+                if points <= r64::default() =>
+            {
+                return Err(TournamentError::InvalidPointValue)
+            }
+            StandardScoringSetting::MatchDrawPoints(points)
+            | StandardScoringSetting::MatchLossPoints(points)
+            | StandardScoringSetting::GameDrawPoints(points)
+            | StandardScoringSetting::GameLossPoints(points)
+            | StandardScoringSetting::ByePoints(points)
+                if points < r64::default() =>
+            {
+                return Err(TournamentError::InvalidPointValue)
+            }
+            _ => {}
+        }
         match setting {
             StandardScoringSetting::MatchWinPoints(points) => self.match_win_points = points,
             StandardScoringSetting::MatchDrawPoints(points) => self.match_draw_points = points,
@@ -192,6 +248,9 @@ impl SettingsTree for StandardScoringSettingsTree {
             StandardScoringSetting::IncludeGwp(include) => self.include_gwp = include,
             StandardScoringSetting::IncludeOppMwp(include) => self.include_opp_mwp = include,
             StandardScoringSetting::IncludeOppGwp(include) => self.include_opp_gwp = include,
+            StandardScoringSetting::IncludeDroppedOpponents(include) => {
+                self.include_dropped_opponents = include
+            }
         }
         Ok(OpData::Nothing)
     }
@@ -213,6 +272,111 @@ impl SettingsTree for StandardScoringSettingsTree {
                 StandardScoringSetting::IncludeGwp(self.include_gwp),
                 StandardScoringSetting::IncludeOppMwp(self.include_opp_mwp),
                 StandardScoringSetting::IncludeOppGwp(self.include_opp_gwp),
+                StandardScoringSetting::IncludeDroppedOpponents(self.include_dropped_opponents),
+            ]
+            .into_iter(),
+        )
+    }
+}
+
+/// An enum that encodes all the adjustable settings of multiplayer scoring systems
+#[derive(Serialize, Deserialize, Debug, Hash, Clone, PartialEq, Eq)]
+pub enum MultiplayerScoringSetting {
+    /// Adjusts the number of points that finishing first is worth
+    FirstPlacePoints(r64),
+    /// Adjusts the number of points that finishing second is worth
+    SecondPlacePoints(r64),
+    /// Adjusts the number of points that finishing third is worth
+    ThirdPlacePoints(r64),
+    /// Adjusts the number of points that finishing fourth (or lower) is worth
+    FourthPlacePoints(r64),
+    /// Adjusts the number of points that a drawn round is worth
+    DrawPoints(r64),
+    /// Adjusts the number of points a bye is worth
+    ByePoints(r64),
+    /// Adjusts if byes are used in scoring
+    IncludeByes(bool),
+}
+
+/// A structure that holds a value for each multiplayer scoring setting
+#[derive(Serialize, Deserialize, Debug, Hash, Clone, PartialEq, Eq)]
+pub struct MultiplayerScoringSettingsTree {
+    /// The points for finishing first
+    pub first_place_points: r64,
+    /// The points for finishing second
+    pub second_place_points: r64,
+    /// The points for finishing third
+    pub third_place_points: r64,
+    /// The points for finishing fourth or lower
+    pub fourth_place_points: r64,
+    /// The points that every player gets when a round ends in a draw, i.e. when no placements
+    /// were recorded and there is no winner
+    pub draw_points: r64,
+    /// The points for a bye
+    pub bye_points: r64,
+    /// Whether or not byes are used in scoring
+    pub include_byes: bool,
+}
+
+impl MultiplayerScoringSettingsTree {
+    /// Returns the points for finishing in the given place (starting at 1)
+    pub fn placement_points(&self, place: u32) -> r64 {
+        match place {
+            1 => self.first_place_points,
+            2 => self.second_place_points,
+            3 => self.third_place_points,
+            _ => self.fourth_place_points,
+        }
+    }
+}
+
+impl SettingsTree for MultiplayerScoringSettingsTree {
+    type Setting = MultiplayerScoringSetting;
+
+    fn update(&mut self, setting: Self::Setting) -> OpResult {
+        // First place points are used as the denominator of the point percentages, so they must
+        // be positive
+        match setting {
+            MultiplayerScoringSetting::FirstPlacePoints(points) if points <= r64::default() => {
+                return Err(TournamentError::InvalidPointValue)
+            }
+            MultiplayerScoringSetting::SecondPlacePoints(points)
+            | MultiplayerScoringSetting::ThirdPlacePoints(points)
+            | MultiplayerScoringSetting::FourthPlacePoints(points)
+            | MultiplayerScoringSetting::DrawPoints(points)
+            | MultiplayerScoringSetting::ByePoints(points)
+                if points < r64::default() =>
+            {
+                return Err(TournamentError::InvalidPointValue)
+            }
+            _ => {}
+        }
+        match setting {
+            MultiplayerScoringSetting::FirstPlacePoints(points) => self.first_place_points = points,
+            MultiplayerScoringSetting::SecondPlacePoints(points) => {
+                self.second_place_points = points
+            }
+            MultiplayerScoringSetting::ThirdPlacePoints(points) => self.third_place_points = points,
+            MultiplayerScoringSetting::FourthPlacePoints(points) => {
+                self.fourth_place_points = points
+            }
+            MultiplayerScoringSetting::DrawPoints(points) => self.draw_points = points,
+            MultiplayerScoringSetting::ByePoints(points) => self.bye_points = points,
+            MultiplayerScoringSetting::IncludeByes(include) => self.include_byes = include,
+        }
+        Ok(OpData::Nothing)
+    }
+
+    fn iter(&self) -> Box<dyn Iterator<Item = Self::Setting>> {
+        Box::new(
+            [
+                MultiplayerScoringSetting::FirstPlacePoints(self.first_place_points),
+                MultiplayerScoringSetting::SecondPlacePoints(self.second_place_points),
+                MultiplayerScoringSetting::ThirdPlacePoints(self.third_place_points),
+                MultiplayerScoringSetting::FourthPlacePoints(self.fourth_place_points),
+                MultiplayerScoringSetting::DrawPoints(self.draw_points),
+                MultiplayerScoringSetting::ByePoints(self.bye_points),
+                MultiplayerScoringSetting::IncludeByes(self.include_byes),
             ]
             .into_iter(),
         )
@@ -0,0 +1,69 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    admin::{Admin, Judge, Scorekeeper},
+    identifiers::{SquireAccountId, TemplateId},
+    pairings::PairingStyle,
+    schedule::Schedule,
+    scoring::ScoringStyle,
+    settings::{RegistrationWindow, TournamentSettingsTree},
+    tournament::{Tournament, TournamentPreset},
+};
+
+/// A reusable set of tournament settings and officials. Organizers can save a template from a
+/// tournament and give it to a [`TournamentSeed`](crate::tournament::TournamentSeed) to create new
+/// tournaments with the same setup.
+#[derive(Serialize, Deserialize, Debug, Clone, Hash, PartialEq, Eq)]
+pub struct TournamentTemplate {
+    /// The template's id
+    pub id: TemplateId,
+    /// The account that owns the template
+    pub owner: SquireAccountId,
+    /// The name of the template
+    pub name: String,
+    /// The preset that new tournaments are created with, which decides their pairing and scoring
+    /// systems
+    pub preset: TournamentPreset,
+    /// The settings that new tournaments are given. The format of a new tournament comes from its
+    /// seed instead.
+    pub settings: TournamentSettingsTree,
+    /// The judges that new tournaments are given
+    pub judges: Vec<Judge>,
+    /// The admins that new tournaments are given
+    pub admins: Vec<Admin>,
+    /// The scorekeepers that new tournaments are given
+    #[serde(default)]
+    pub scorekeepers: Vec<Scorekeeper>,
+}
+
+impl TournamentTemplate {
+    /// Creates a template from the current settings and officials of a tournament
+    pub fn from_tournament(owner: SquireAccountId, name: String, tourn: &Tournament) -> Self {
+        let preset = match (&tourn.pairing_sys.style, &tourn.scoring_sys.style) {
+            (PairingStyle::Swiss(_), ScoringStyle::Multiplayer(_)) => TournamentPreset::Multiplayer,
+            (PairingStyle::Swiss(_), _) => TournamentPreset::Swiss,
+            (PairingStyle::Fluid(_), _) => TournamentPreset::Fluid,
+        };
+        let mut judges: Vec<Judge> = tourn.judges.values().cloned().collect();
+        judges.sort_by_key(|j| j.id);
+        let mut admins: Vec<Admin> = tourn.admins.values().cloned().collect();
+        admins.sort_by_key(|a| a.id);
+        let mut scorekeepers: Vec<Scorekeeper> = tourn.scorekeepers.values().cloned().collect();
+        scorekeepers.sort_by_key(|s| s.id);
+        let mut settings = tourn.settings();
+        // Schedules and registration windows are specific to a single event
+        settings.general.schedule = Schedule::default();
+        settings.general.registration = RegistrationWindow::default();
+        Self {
+            id: TemplateId::new(Uuid::new_v4()),
+            owner,
+            name,
+            preset,
+            settings,
+            judges,
+            admins,
+            scorekeepers,
+        }
+    }
+}
@@ -0,0 +1,150 @@
+use chrono::{DateTime, Utc};
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::TournamentError,
+    identifiers::{PlayerId, RoundId},
+    operations::{OpData, OpResult, TournOp},
+    players::PlayerStatus,
+    tournament::{Tournament, TournamentStatus},
+};
+
+/// The outcome of applying operations to a copy of a tournament, which lets admins preview what
+/// an operation would do (e.g. who would get a bye if the next round was paired) before applying
+/// it
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Simulation {
+    /// The data returned by each operation, in the order the operations were given
+    pub data: Vec<OpData>,
+    /// How the tournament would change
+    pub diff: TournamentDiff,
+}
+
+/// The differences between two states of a tournament
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq)]
+pub struct TournamentDiff {
+    /// The tournament's new status, if it changed
+    pub status: Option<TournamentStatus>,
+    /// Whether registration is open, if that changed
+    pub reg_open: Option<bool>,
+    /// The players that were added
+    pub new_players: Vec<PlayerId>,
+    /// The existing players whose status changed, paired with their new status
+    pub player_statuses: Vec<(PlayerId, PlayerStatus)>,
+    /// The rounds that were created, ordered by match number
+    pub new_rounds: Vec<RoundId>,
+    /// The existing rounds that were changed
+    pub updated_rounds: Vec<RoundId>,
+    /// The players that were given a bye in one of the new rounds
+    pub byes: Vec<PlayerId>,
+    /// The registered players that are not in any of the new rounds. This is empty if no rounds
+    /// were created.
+    pub sitting_out: Vec<PlayerId>,
+}
+
+impl TournamentDiff {
+    /// Calculates the differences between the old and new states of a tournament
+    pub fn new(old: &Tournament, new: &Tournament) -> Self {
+        let status = (old.status != new.status).then_some(new.status);
+        let reg_open = (old.reg_open != new.reg_open).then_some(new.reg_open);
+        let old_players = &old.player_reg.players;
+        let new_players = new
+            .player_reg
+            .players
+            .keys()
+            .copied()
+            .filter(|id| !old_players.contains_key(id))
+            .sorted()
+            .collect();
+        let player_statuses = new
+            .player_reg
+            .players
+            .iter()
+            .filter_map(|(id, plyr)| {
+                let old = old_players.get(id)?;
+                (old.status != plyr.status).then_some((*id, plyr.status))
+            })
+            .sorted()
+            .collect();
+        let old_rounds = &old.round_reg.rounds;
+        let rounds = new
+            .round_reg
+            .rounds
+            .values()
+            .filter(|rnd| !old_rounds.contains_key(&rnd.id))
+            .sorted_by_key(|rnd| rnd.match_number)
+            .collect_vec();
+        let updated_rounds = new
+            .round_reg
+            .rounds
+            .iter()
+            .filter(|(id, rnd)| old_rounds.get(*id).is_some_and(|old| old != *rnd))
+            .map(|(id, _)| *id)
+            .sorted()
+            .collect();
+        let byes = rounds
+            .iter()
+            .filter(|rnd| rnd.is_bye())
+            .flat_map(|rnd| rnd.players.iter().copied())
+            .sorted()
+            .collect();
+        let sitting_out = if rounds.is_empty() {
+            Vec::new()
+        } else {
+            new.player_reg
+                .players
+                .values()
+                .filter(|plyr| plyr.can_play())
+                .map(|plyr| plyr.id)
+                .filter(|id| !rounds.iter().any(|rnd| rnd.players.contains(id)))
+                .sorted()
+                .collect()
+        };
+        Self {
+            status,
+            reg_open,
+            new_players,
+            player_statuses,
+            new_rounds: rounds.into_iter().map(|rnd| rnd.id).collect(),
+            updated_rounds,
+            byes,
+            sitting_out,
+        }
+    }
+
+    /// Calculates if the tournament did not change
+    pub fn is_empty(&self) -> bool {
+        self == &Self::default()
+    }
+}
+
+impl Tournament {
+    /// Applies an operation to a copy of the tournament and returns the outcome. The tournament
+    /// itself is not changed.
+    pub fn simulate_op(&self, salt: DateTime<Utc>, op: TournOp) -> OpResult {
+        self.clone().apply_op(salt, op)
+    }
+
+    /// Applies the operations, in order, to a copy of the tournament and reports how the
+    /// tournament would change. The tournament itself is not changed. If any of the operations
+    /// fails, its error is returned.
+    pub fn simulate_ops<I>(
+        &self,
+        salt: DateTime<Utc>,
+        ops: I,
+    ) -> Result<Simulation, TournamentError>
+    where
+        I: IntoIterator<Item = TournOp>,
+    {
+        let mut tourn = self.clone();
+        let data = ops
+            .into_iter()
+            .map(|op| tourn.apply_op(salt, op))
+            .collect::<Result<_, _>>()?;
+        Ok(Simulation {
+            data,
+            diff: TournamentDiff::new(self, &tourn),
+        })
+    }
+}
@@ -1,27 +1,46 @@
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     fmt::{Display, Write},
+    hash::{Hash, Hasher},
     time::Duration,
 };
 
 use chrono::{DateTime, Utc};
+use deterministic_hash::DeterministicHasher;
+use fxhash::FxHasher64;
 use itertools::Itertools;
 use serde::{Deserialize, Serialize};
-use serde_with::{serde_as, Seq};
+use serde_with::serde_as;
 use uuid::Uuid;
 
 pub use crate::identifiers::{TournamentId, TournamentIdentifier};
 use crate::{
     accounts::SquireAccount,
-    admin::{Admin, Judge, TournOfficialId},
+    admin::{Admin, Judge, Scorekeeper, TournOfficialId},
+    boilerplate::ordered::{SortedSeq, SortedSet},
     error::TournamentError,
-    identifiers::{AdminId, JudgeId, PlayerId, PlayerIdentifier, RoundId, RoundIdentifier},
+    formats::Format,
+    identifiers::{
+        AdminId, JudgeId, PlayerId, PlayerIdentifier, RoundId, RoundIdentifier, ScorekeeperId,
+    },
     operations::{AdminOp, JudgeOp, OpData, OpResult, PlayerOp, TournOp},
-    pairings::{PairingStyle, PairingSystem, Pairings},
-    players::{Deck, Player, PlayerRegistry, PlayerStatus},
-    rounds::{Round, RoundRegistry, RoundResult, RoundStatus},
-    scoring::{ScoringSystem, StandardScore, Standings},
-    settings::{GeneralSettingsTree, SettingsTree, TournamentSetting, TournamentSettingsTree},
+    pairings::{PairingAudit, PairingStyle, PairingSystem, Pairings},
+    players::{deck_size, Deck, Player, PlayerRegistry, PlayerStatus, MAX_DECK_SIZE},
+    prizes::Payout,
+    rounds::{
+        DeckCheckResult, MatchProgress, Round, RoundContext, RoundRegistry, RoundResult,
+        RoundStatus,
+    },
+    schedule::EventSchedule,
+    scoring::{
+        GroupStandings, ScoreExplanation, ScoreMismatch, ScoringSystem, StandardScore, Standings,
+    },
+    seating::{DraftPods, SeatMap},
+    settings::{
+        DecklistVisibility, GeneralSettingsTree, Permission, PermissionRole, SettingsTree,
+        TardinessPenalty, TournamentSetting, TournamentSettingsTree, TournamentTemplate,
+    },
+    venue::Venue,
 };
 
 #[derive(Serialize, Deserialize, Debug, Clone, Copy, Hash, PartialEq, Eq)]
@@ -32,6 +51,19 @@ pub enum TournamentPreset {
     Swiss,
     /// The tournament will have a fluid pairing system and a standard scoring system
     Fluid,
+    /// The tournament will have a swiss pairing system that pairs players into pods of four and a
+    /// multiplayer scoring system that awards points for placements (i.e. for Commander)
+    Multiplayer,
+}
+
+impl TournamentPreset {
+    /// The number of players in each round of a new tournament
+    pub(crate) fn match_size(self) -> u8 {
+        match self {
+            TournamentPreset::Swiss | TournamentPreset::Fluid => 2,
+            TournamentPreset::Multiplayer => 4,
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, Hash, PartialEq, Eq)]
@@ -42,7 +74,25 @@ pub struct TournamentSeed {
     /// The initial present of the to-be tournament
     pub preset: TournamentPreset,
     /// The initial format fo the to-be tournament
-    pub format: String,
+    pub format: Format,
+    /// The template that the to-be tournament is set up from, if any
+    #[serde(default)]
+    pub template: Option<TournamentTemplate>,
+}
+
+/// The parts of a tournament that are changed when it is cloned into a new tournament. Everything
+/// that isn't overridden is taken from the cloned tournament.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq)]
+pub struct SeedOverrides {
+    /// The name of the new tournament
+    #[serde(default)]
+    pub name: Option<String>,
+    /// The format of the new tournament
+    #[serde(default)]
+    pub format: Option<Format>,
+    /// Whether the cloned tournament's players are registered for the new tournament
+    #[serde(default)]
+    pub keep_players: bool,
 }
 
 #[derive(
@@ -64,6 +114,22 @@ pub enum TournamentStatus {
     Cancelled,
 }
 
+/// The number of announcements that a tournament keeps. Once this is exceeded, the oldest
+/// announcements are removed.
+pub const MAX_ANNOUNCEMENTS: usize = 50;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+/// A message from an admin to everyone that is following the tournament, e.g. "Round 4 pairings are
+/// up"
+pub struct Announcement {
+    /// The admin that made the announcement
+    pub admin: AdminId,
+    /// The text of the announcement
+    pub message: String,
+    /// When the announcement was made
+    pub posted: DateTime<Utc>,
+}
+
 #[serde_as]
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 /// The core tournament structure
@@ -80,7 +146,8 @@ pub struct Tournament {
     pub pairing_sys: PairingSystem,
     /// The scoring system used to rank players
     pub scoring_sys: ScoringSystem,
-    /// Whether or not new players can sign up for the tournament
+    /// Whether or not the organizers have opened registration. Players can only sign up while this
+    /// is set and the registration window is open (see [`Tournament::is_reg_open`]).
     pub reg_open: bool,
     /// General settings for the tournament, including round length and whether or not to use table
     /// numbers
@@ -89,21 +156,33 @@ pub struct Tournament {
     /// The status of the tournament
     pub status: TournamentStatus,
     /// The set of judges for the tournament
-    #[serde_as(as = "Seq<(_, _)>")]
+    #[serde_as(as = "SortedSeq")]
     pub judges: HashMap<JudgeId, Judge>,
+    /// The set of scorekeepers for the tournament
+    #[serde(default)]
+    #[serde_as(as = "SortedSeq")]
+    pub scorekeepers: HashMap<ScorekeeperId, Scorekeeper>,
     /// The set of admins for the tournament
-    #[serde_as(as = "Seq<(_, _)>")]
+    #[serde_as(as = "SortedSeq")]
     pub admins: HashMap<AdminId, Admin>,
+    /// The most recent announcements, oldest first
+    #[serde(default)]
+    pub announcements: Vec<Announcement>,
+    /// The nonces of the invites to the tournament that have been revoked, including staff
+    /// invites that have been used
+    #[serde(default)]
+    #[serde_as(as = "SortedSet")]
+    pub revoked_invites: HashSet<u64>,
 }
 
 impl Tournament {
     /// Creates a new tournament from the defaults established by the given preset
-    fn from_preset(name: String, preset: TournamentPreset, format: String) -> Self {
+    fn from_preset(name: String, preset: TournamentPreset, format: Format) -> Self {
         Tournament {
             // TODO: This should be calculated from some salt and the name
             id: TournamentId::new(Uuid::new_v4()),
             name,
-            settings: GeneralSettingsTree::with_format(format),
+            settings: GeneralSettingsTree::with_format(format.into()),
             player_reg: PlayerRegistry::new(),
             round_reg: RoundRegistry::new(0, Duration::from_secs(3000)),
             pairing_sys: PairingSystem::new(preset),
@@ -111,15 +190,43 @@ impl Tournament {
             reg_open: true,
             status: TournamentStatus::Planned,
             judges: HashMap::new(),
+            scorekeepers: HashMap::new(),
             admins: HashMap::new(),
+            announcements: Vec::new(),
+            revoked_invites: HashSet::new(),
         }
     }
 
+    /// Creates a tournament from a template. The template's preset is used in place of the seed's,
+    /// but the tournament keeps the seed's format.
+    fn from_template(name: String, format: Format, template: TournamentTemplate) -> Self {
+        let TournamentTemplate {
+            preset,
+            settings,
+            judges,
+            admins,
+            scorekeepers,
+            ..
+        } = template;
+        let mut tourn = Tournament::from_preset(name, preset, format.clone());
+        // The settings were taken from a tournament with the same preset, so they all apply
+        for setting in settings.iter() {
+            _ = tourn.update_setting(setting);
+        }
+        tourn.settings.format = format.into();
+        tourn.judges.extend(judges.into_iter().map(|j| (j.id, j)));
+        tourn.admins.extend(admins.into_iter().map(|a| (a.id, a)));
+        tourn
+            .scorekeepers
+            .extend(scorekeepers.into_iter().map(|s| (s.id, s)));
+        tourn
+    }
+
     /// Applies a tournament operation to the tournament
     pub fn apply_op(&mut self, salt: DateTime<Utc>, op: TournOp) -> OpResult {
         use TournOp::*;
         match op {
-            RegisterPlayer(account, tourn_name) => self.register_player(account, tourn_name),
+            RegisterPlayer(account, tourn_name) => self.register_player(salt, account, tourn_name),
             PlayerOp(p_id, op) => self.apply_player_op(salt, p_id, op),
             JudgeOp(ta_id, op) => self.apply_judge_op(salt, ta_id, op),
             AdminOp(a_id, op) => self.apply_admin_op(salt, a_id, op),
@@ -127,16 +234,20 @@ impl Tournament {
     }
 
     fn apply_player_op(&mut self, salt: DateTime<Utc>, p_id: PlayerId, op: PlayerOp) -> OpResult {
+        if !self.role_allows(PermissionRole::Player, op.permission()) {
+            return OpResult::Err(TournamentError::PermissionDenied);
+        }
         match op {
             PlayerOp::CheckIn => self.check_in(p_id),
-            PlayerOp::RecordResult(r_id, result) => self.record_result(&r_id, result),
+            PlayerOp::RecordResult(r_id, result) => self.record_result(salt, &r_id, result),
             PlayerOp::ConfirmResult(r_id) => self.confirm_round(r_id, p_id),
             PlayerOp::DropPlayer => self.drop_player(p_id),
-            PlayerOp::AddDeck(name, deck) => self.player_add_deck(p_id, name, deck),
+            PlayerOp::AddDeck(name, deck) => self.player_add_deck(salt, p_id, name, deck),
             PlayerOp::RemoveDeck(name) => self.remove_player_deck(&p_id, name),
             PlayerOp::SetGamerTag(tag) => self.player_set_game_name(&p_id, tag),
             PlayerOp::ReadyPlayer => self.ready_player(salt, &p_id),
             PlayerOp::UnReadyPlayer => self.unready_player(p_id),
+            PlayerOp::CheckInAtTable(r_id) => self.check_in_at_table(&r_id, p_id),
         }
     }
 
@@ -149,31 +260,57 @@ impl Tournament {
         if !self.is_official(&ta_id) {
             return OpResult::Err(TournamentError::OfficalLookup);
         }
+        let allowed = match ta_id {
+            TournOfficialId::Judge(_) => self.role_allows(PermissionRole::Judge, op.permission()),
+            TournOfficialId::Scorekeeper(_) => Scorekeeper::can_judge(&op),
+            TournOfficialId::Admin(_) => true,
+        };
+        if !allowed {
+            return OpResult::Err(TournamentError::PermissionDenied);
+        }
         match op {
             JudgeOp::AdminRegisterPlayer(account, name) => {
                 self.admin_register_player(account, name)
             }
+            JudgeOp::AdminRegisterPlayers(players) => self.admin_register_players(players),
             JudgeOp::RegisterGuest(name) => self.register_guest(salt, name),
             JudgeOp::ReRegisterGuest(name) => self.reregister_guest(name),
             JudgeOp::AdminAddDeck(plyr, name, deck) => self.admin_add_deck(plyr, name, deck),
             JudgeOp::AdminRemoveDeck(plyr, name) => self.admin_remove_deck(plyr, name),
             JudgeOp::AdminReadyPlayer(p_id) => self.admin_ready_player(salt, p_id),
             JudgeOp::AdminUnReadyPlayer(p_id) => self.admin_unready_player(p_id),
-            JudgeOp::AdminRecordResult(rnd, result) => self.admin_record_result(rnd, result),
+            JudgeOp::AdminRecordResult(rnd, result) => self.admin_record_result(salt, rnd, result),
             JudgeOp::AdminConfirmResult(r_id, p_id) => self.admin_confirm_result(r_id, p_id),
             JudgeOp::TimeExtension(rnd, ext) => self.give_time_extension(&rnd, ext),
             JudgeOp::ConfirmRound(rnd) => self.confirm_single_round(&rnd),
+            JudgeOp::AutoCertifyRound(rnd) => self.auto_certify_round(salt, &rnd),
+            JudgeOp::ApplyTardiness(rnd, plyr) => self.apply_tardiness(salt, &rnd, plyr),
+            JudgeOp::StartDeckCheck(rnd) => self.start_deck_check(salt, &rnd),
+            JudgeOp::CompleteDeckCheck(rnd, result) => self.complete_deck_check(salt, &rnd, result),
         }
     }
 
     fn apply_admin_op(&mut self, salt: DateTime<Utc>, a_id: AdminId, op: AdminOp) -> OpResult {
         if !self.is_admin(&a_id) {
-            return OpResult::Err(TournamentError::OfficalLookup);
+            // Judges can perform the admin operations that they have been granted, and
+            // scorekeepers can perform the few that deal with results and tables
+            let allowed = if self.is_judge(&a_id.convert()) {
+                op.permission()
+                    .is_some_and(|perm| self.role_allows(PermissionRole::Judge, perm))
+            } else if self.is_scorekeeper(&a_id.convert()) {
+                Scorekeeper::can_admin(&op)
+            } else {
+                return OpResult::Err(TournamentError::OfficalLookup);
+            };
+            if !allowed {
+                return OpResult::Err(TournamentError::PermissionDenied);
+            }
         }
         match op {
             AdminOp::RemoveRound(r_id) => self.remove_round(&r_id),
             AdminOp::AdminOverwriteResult(rnd, result) => self.admin_overwrite_result(rnd, result),
             AdminOp::AdminDropPlayer(p_id) => self.admin_drop_player(p_id),
+            AdminOp::ReinstatePlayer(p_id) => self.reinstate_player(&p_id),
             AdminOp::UpdateReg(b) => self.update_reg(b),
             AdminOp::Start => self.start(),
             AdminOp::Freeze => self.freeze(),
@@ -187,8 +324,28 @@ impl Tournament {
             AdminOp::Cut(n) => self.cut_to_top(n),
             AdminOp::PrunePlayers => self.prune_players(),
             AdminOp::RegisterJudge(account) => self.register_judge(account),
+            AdminOp::RegisterScorekeeper(account) => self.register_scorekeeper(account),
             AdminOp::RegisterAdmin(account) => self.register_admin(account),
             AdminOp::ConfirmAllRounds => self.confirm_all_rounds(),
+            AdminOp::MakeAlternate(p_id) => self.make_alternate(p_id),
+            AdminOp::PromoteAlternate(p_id) => self.promote_alternate(&p_id),
+            AdminOp::SubstituteAlternate(alt, p_id) => self.substitute_alternate(alt, p_id),
+            AdminOp::PromoteFromWaitlist(p_id) => self.promote_from_waitlist(&p_id),
+            AdminOp::ReserveTables(tables) => self.reserve_tables(tables),
+            AdminOp::SetTablePool(p_id, pool) => self.set_table_pool(p_id, pool),
+            AdminOp::SetVenue(venue) => self.set_venue(venue),
+            AdminOp::AssignTable(r_id, table) => self.assign_table(&r_id, table),
+            AdminOp::SetFeatureMatch(r_id) => self.set_feature_match(&r_id),
+            AdminOp::RemoveFeatureMatch(r_id) => self.remove_feature_match(&r_id),
+            AdminOp::SetStreamUrl(r_id, url) => self.set_stream_url(&r_id, url),
+            AdminOp::AddPairingConstraint(plyrs) => self.add_pairing_constraint(plyrs),
+            AdminOp::LinkGuestAccount(p_id, account) => self.link_guest_account(p_id, account),
+            AdminOp::MergePlayers(dup, p_id) => self.merge_players(dup, p_id),
+            AdminOp::Announce(message) => self.announce(salt, a_id, message),
+            AdminOp::Checkpoint(name) => self.checkpoint(&name),
+            AdminOp::CreateDraftPods(size) => self.create_draft_pods(salt, size),
+            AdminOp::MoveToFlight(plyrs, flight) => self.move_to_flight(plyrs, flight),
+            AdminOp::RevokeInvite(nonce) => self.revoke_invite(nonce),
         }
     }
 
@@ -222,15 +379,27 @@ impl Tournament {
         self.judges.contains_key(id)
     }
 
+    /// Calculates if someone is a scorekeeper
+    pub fn is_scorekeeper(&self, id: &ScorekeeperId) -> bool {
+        self.scorekeepers.contains_key(id)
+    }
+
     /// Calculates if someone is a tournament admin
     pub fn is_admin(&self, id: &AdminId) -> bool {
         self.admins.contains_key(id)
     }
 
+    /// Calculates if the tournament's permissions let the role perform operations that need the
+    /// permission
+    pub fn role_allows(&self, role: PermissionRole, perm: Permission) -> bool {
+        self.settings.permissions.allows(role, perm)
+    }
+
     /// Calculates if someone is a tournament official
     pub fn is_official(&self, id: &TournOfficialId) -> bool {
         match id {
             TournOfficialId::Judge(id) => self.is_judge(id),
+            TournOfficialId::Scorekeeper(id) => self.is_scorekeeper(id),
             TournOfficialId::Admin(id) => self.is_admin(id),
         }
     }
@@ -303,6 +472,29 @@ impl Tournament {
         self.round_reg.get_round(id)
     }
 
+    /// Gets all active feature matches, ordered by table number
+    pub fn get_feature_matches(&self) -> Vec<&Round> {
+        self.round_reg
+            .rounds
+            .values()
+            .filter(|r| r.is_feature && r.is_active())
+            .sorted_by_key(|r| r.table_number)
+            .collect()
+    }
+
+    /// Gets the progress of all active rounds (except byes), with the most urgent rounds first.
+    /// Rounds that have run out of time come first, then rounds that are missing results, and
+    /// then rounds that are only missing confirmations.
+    pub fn get_matches_in_progress(&self) -> Vec<MatchProgress> {
+        self.round_reg
+            .rounds
+            .values()
+            .filter(|r| r.is_active() && !r.is_bye())
+            .map(MatchProgress::new)
+            .sorted_by(MatchProgress::cmp_urgency)
+            .collect()
+    }
+
     /// Gets a vec of all active rounds
     pub fn get_active_rounds(&self) -> Vec<&Round> {
         self.round_reg
@@ -348,10 +540,99 @@ impl Tournament {
         self.get_player(ident).map(|p| &p.decks)
     }
 
-    /// Gets the current standing of the tournament
+    /// Gets the current standing of the tournament. Players that were moved into a flight are not
+    /// included (see [`Tournament::get_flight_standings`]).
     pub fn get_standings(&self) -> Standings<StandardScore> {
+        let mut standings = self
+            .scoring_sys
+            .get_standings(&self.player_reg, &self.round_reg);
+        let flights = &self.pairing_sys.flights;
+        if !flights.is_empty() {
+            standings
+                .scores
+                .retain(|(p, _)| flights.flight_of(p).is_none());
+        }
+        standings
+    }
+
+    /// Gets the current standings of a (one-indexed) flight, which only count the rounds that were
+    /// played in the flight
+    pub fn get_flight_standings(&self, flight: usize) -> Standings<StandardScore> {
+        let mut standings =
+            self.scoring_sys
+                .get_flight_standings(&self.player_reg, &self.round_reg, flight);
+        let flights = &self.pairing_sys.flights;
+        standings
+            .scores
+            .retain(|(p, _)| flights.flight_of(p) == Some(flight));
+        standings
+    }
+
+    /// Gets the standings that are used to pair the next round, which are the standings of the
+    /// main event followed by the standings of each flight
+    fn pairing_standings(&self) -> Standings<StandardScore> {
+        let mut standings = self.get_standings();
+        for flight in 1..=self.pairing_sys.flights.flights.len() {
+            standings
+                .scores
+                .extend(self.get_flight_standings(flight).scores);
+        }
+        standings
+    }
+
+    /// Explains how a player's score and tiebreakers were calculated, including which rounds counted
+    /// and what each opponent contributed
+    pub fn explain_score(&self, id: &PlayerId) -> Result<ScoreExplanation, TournamentError> {
         self.scoring_sys
-            .get_standings(&self.player_reg, &self.round_reg)
+            .explain_score(&self.player_reg, &self.round_reg, id)
+    }
+
+    /// Recalculates all scores and tiebreakers from scratch and compares them against previously
+    /// calculated standings. Any returned mismatch means that the given standings are stale or
+    /// were calculated incorrectly.
+    pub fn audit_standings(
+        &self,
+        cached: &Standings<StandardScore>,
+    ) -> Vec<ScoreMismatch<StandardScore>> {
+        cached.diff(&self.get_standings())
+    }
+
+    /// Calculates the standings within each of the given groups, marking the top `qualifiers` of
+    /// each group as qualifying for the next stage
+    pub fn get_group_standings(
+        &self,
+        groups: &[Vec<PlayerId>],
+        qualifiers: usize,
+    ) -> Vec<GroupStandings<StandardScore>> {
+        self.get_standings().into_groups(groups, qualifiers)
+    }
+
+    /// Calculates how much each player is paid using the tournament's prize structure and current
+    /// standings. Once the tournament has ended, these are the final payouts.
+    pub fn payouts(&self) -> Vec<Payout> {
+        self.settings
+            .prizes
+            .as_ref()
+            .map(|prizes| prizes.payouts(&self.get_standings()))
+            .unwrap_or_default()
+    }
+
+    /// Calculates when each pairing round starts, using the tournament's schedule and round length
+    pub fn schedule(&self) -> EventSchedule {
+        self.settings.schedule.calculate(
+            self.settings.round_length,
+            self.round_reg.pairing_round_count(),
+        )
+    }
+
+    /// Calculates the seating of all active players across draft pods of at most `pod_size`
+    /// players. If draft pods have been created, their seating is used instead.
+    pub fn seat_map(&self, pod_size: usize) -> SeatMap {
+        if self.pairing_sys.pods.is_empty() {
+            SeatMap::new(&self.player_reg, pod_size)
+        } else {
+            self.pairing_sys.pods.seat_map(&self.player_reg)
+        }
     }
 
     /// Removes players from the tournament that did not complete registration.
@@ -375,6 +656,10 @@ impl Tournament {
                 }
             }
         }
+        let players = &self.player_reg.players;
+        self.player_reg
+            .alternates
+            .retain(|id| players.get(id).is_some_and(Player::is_alternate));
         Ok(OpData::Nothing)
     }
 
@@ -392,6 +677,33 @@ impl Tournament {
         Ok(OpData::Nothing)
     }
 
+    /// Starts a deck check for the players in a round
+    fn start_deck_check(&mut self, salt: DateTime<Utc>, rnd: &RoundId) -> OpResult {
+        if !self.is_active() {
+            return Err(TournamentError::IncorrectStatus(self.status));
+        }
+        self.round_reg.get_mut_round(rnd)?.start_deck_check(salt)?;
+        Ok(OpData::Nothing)
+    }
+
+    /// Completes a round's deck check, which gives the round a time extension for the time that
+    /// the deck check took
+    fn complete_deck_check(
+        &mut self,
+        salt: DateTime<Utc>,
+        rnd: &RoundId,
+        result: DeckCheckResult,
+    ) -> OpResult {
+        if !self.is_active() {
+            return Err(TournamentError::IncorrectStatus(self.status));
+        }
+        _ = self
+            .round_reg
+            .get_mut_round(rnd)?
+            .complete_deck_check(salt, result)?;
+        Ok(OpData::Nothing)
+    }
+
     /// Checks in a player for the tournament.
     pub(crate) fn check_in(&mut self, id: PlayerId) -> OpResult {
         if !self.is_ongoing() {
@@ -410,11 +722,41 @@ impl Tournament {
         if !self.is_active() {
             return Err(TournamentError::IncorrectStatus(self.status));
         }
+        let audit = self.pairing_sys.audit(
+            &self.player_reg,
+            &self.round_reg,
+            self.pairing_standings(),
+            pairings.clone(),
+        );
         self.pairing_sys.update(&pairings);
-        let context = self.pairing_sys.get_context();
-        Ok(OpData::Pair(
-            self.round_reg.rounds_from_pairings(salt, pairings, context),
-        ))
+        let context = self
+            .pairing_sys
+            .get_context()
+            .combine(RoundContext::Pairing(audit.round_number));
+        self.round_reg.pairing_audits.push(audit);
+        let tables = self.pairing_sys.common.table_assignment;
+        let ids = self
+            .round_reg
+            .rounds_from_pairings_with_tables(salt, pairings, context, tables);
+        let pods = &self.pairing_sys.pods;
+        if !pods.is_empty() {
+            for id in ids.iter() {
+                let rnd = self.round_reg.get_mut_round(id)?;
+                if let Some(pod) = pods.shared_pod(&rnd.players) {
+                    rnd.context = rnd.context.clone().combine(RoundContext::DraftPod(pod));
+                }
+            }
+        }
+        let flights = &self.pairing_sys.flights;
+        if !flights.is_empty() {
+            for id in ids.iter() {
+                let rnd = self.round_reg.get_mut_round(id)?;
+                if let Some(flight) = flights.shared_flight(&rnd.players) {
+                    rnd.context = rnd.context.clone().combine(RoundContext::Flight(flight));
+                }
+            }
+        }
+        Ok(OpData::Pair(ids))
     }
 
     /// Attempts to create the next set of rounds for the tournament
@@ -422,11 +764,14 @@ impl Tournament {
         if !self.is_active() {
             return None;
         }
-        let standings = self
-            .scoring_sys
-            .get_standings(&self.player_reg, &self.round_reg);
         self.pairing_sys
-            .pair(&self.player_reg, &self.round_reg, standings)
+            .pair(&self.player_reg, &self.round_reg, self.pairing_standings())
+    }
+
+    /// Gets the record of the inputs that were used to pair the given pairing round (starting at
+    /// 1), which can be used to reproduce or audit those pairings
+    pub fn explain_pairings(&self, round_number: u64) -> Option<PairingAudit> {
+        self.round_reg.get_pairing_audit(round_number).cloned()
     }
 
     /// Makes a round irrelevant to the tournament.
@@ -446,11 +791,16 @@ impl Tournament {
         if self.is_dead() {
             return Err(TournamentError::IncorrectStatus(self.status));
         }
-        match setting {
+        let digest = match setting {
             GeneralSetting(setting) => self.settings.update(setting),
             PairingSetting(setting) => self.pairing_sys.update_setting(setting),
             ScoringSetting(setting) => self.scoring_sys.update_setting(setting),
+        };
+        // Raising (or removing) the player cap can open up spots for waitlisted players
+        if digest.is_ok() && self.is_planned() {
+            self.fill_from_waitlist();
         }
+        digest
     }
 
     /// Changes the registration status
@@ -516,30 +866,46 @@ impl Tournament {
         }
     }
 
+    /// Calculates if players can register at the given time, i.e. registration has been opened and
+    /// the time is inside the registration window. Officials can register players regardless.
+    pub fn is_reg_open(&self, now: DateTime<Utc>) -> bool {
+        self.reg_open && self.settings.registration.contains(now)
+    }
+
     /// Adds a player to the tournament
     pub(crate) fn register_player(
         &mut self,
+        salt: DateTime<Utc>,
         account: SquireAccount,
         tourn_name: Option<String>,
     ) -> OpResult {
         if !self.is_ongoing() {
             Err(TournamentError::IncorrectStatus(self.status))
-        } else if !self.reg_open {
+        } else if !self.is_reg_open(salt) {
             Err(TournamentError::RegClosed)
         } else {
             let id = self
                 .player_reg
                 .register_player_with_name(account, tourn_name)?;
+            self.apply_player_cap(id)?;
             Ok(OpData::RegisterPlayer(id))
         }
     }
 
     /// Records part of the result of a round
-    pub(crate) fn record_result(&mut self, r_id: &RoundId, result: RoundResult) -> OpResult {
+    pub(crate) fn record_result(
+        &mut self,
+        salt: DateTime<Utc>,
+        r_id: &RoundId,
+        result: RoundResult,
+    ) -> OpResult {
         if !self.is_active() {
             Err(TournamentError::IncorrectStatus(self.status))
         } else {
-            self.round_reg.get_mut_round(r_id)?.record_result(result)?;
+            let rnd = self.round_reg.get_mut_round(r_id)?;
+            self.settings.match_structure.check_result(rnd, &result)?;
+            rnd.record_result(result)?;
+            rnd.result_time = Some(salt);
             Ok(OpData::Nothing)
         }
     }
@@ -571,6 +937,119 @@ impl Tournament {
         }
     }
 
+    /// Certifies a round on the server's behalf once the tournament's auto-certify wait has passed
+    /// since the round's time ran out and its result last changed
+    fn auto_certify_round(&mut self, salt: DateTime<Utc>, id: &RoundId) -> OpResult {
+        let due = self
+            .settings
+            .auto_certify
+            .and_then(|wait| self.round_reg.get_round(id).ok()?.auto_certify_time(wait));
+        match due {
+            Some(time) if time <= salt => self.confirm_single_round(id),
+            _ => Err(TournamentError::AutoCertifyNotDue),
+        }
+    }
+
+    /// Returns the active rounds whose results are due to be certified automatically at the given
+    /// time
+    pub fn auto_certify_due(&self, now: DateTime<Utc>) -> Vec<RoundId> {
+        let Some(wait) = self.settings.auto_certify else {
+            return Vec::new();
+        };
+        if !self.is_active() {
+            return Vec::new();
+        }
+        self.round_reg
+            .rounds
+            .values()
+            .filter(|rnd| rnd.auto_certify_time(wait).is_some_and(|time| time <= now))
+            .map(|rnd| rnd.id)
+            .collect()
+    }
+
+    /// A player checks in at the table of one of their rounds
+    pub(crate) fn check_in_at_table(&mut self, r_id: &RoundId, p_id: PlayerId) -> OpResult {
+        if !self.is_active() {
+            return Err(TournamentError::IncorrectStatus(self.status));
+        }
+        self.round_reg
+            .get_mut_round(r_id)?
+            .check_in_at_table(p_id)?;
+        Ok(OpData::Nothing)
+    }
+
+    /// Penalizes a player that has not checked in at their table according to the tournament's
+    /// tardiness policy. The player is then treated as having arrived, so the penalty is only
+    /// applied once.
+    fn apply_tardiness(&mut self, salt: DateTime<Utc>, r_id: &RoundId, p_id: PlayerId) -> OpResult {
+        if !self.is_active() {
+            return Err(TournamentError::IncorrectStatus(self.status));
+        }
+        let policy = self
+            .settings
+            .tardiness
+            .ok_or(TournamentError::PlayerNotTardy)?;
+        let structure = self.settings.match_structure;
+        let rnd = self.round_reg.get_mut_round(r_id)?;
+        if !rnd.tardy_players(policy.grace, salt).contains(&p_id) {
+            return Err(TournamentError::PlayerNotTardy);
+        }
+        let opponents: Vec<PlayerId> = rnd
+            .players
+            .iter()
+            .filter(|p| **p != p_id)
+            .copied()
+            .collect();
+        match policy.penalty {
+            TardinessPenalty::GameLoss => {
+                for opp in opponents {
+                    let wins = rnd.results.get(&opp).copied().unwrap_or_default() + 1;
+                    let result = RoundResult::Wins(opp, wins);
+                    structure.check_result(rnd, &result)?;
+                    rnd.record_result(result)?;
+                }
+                rnd.result_time = Some(salt);
+                _ = rnd.arrivals.insert(p_id);
+                Ok(OpData::Nothing)
+            }
+            TardinessPenalty::MatchLoss => {
+                let to_win = structure.games_to_win().unwrap_or(1);
+                rnd.record_result(RoundResult::Wins(p_id, 0))?;
+                for opp in opponents {
+                    rnd.record_result(RoundResult::Wins(opp, to_win))?;
+                }
+                rnd.result_time = Some(salt);
+                _ = rnd.arrivals.insert(p_id);
+                self.confirm_single_round(r_id)
+            }
+            TardinessPenalty::Drop => {
+                _ = rnd.arrivals.insert(p_id);
+                self.drop_player(p_id)
+            }
+        }
+    }
+
+    /// Returns the players in active rounds that have not checked in at their table within the
+    /// grace period of the tournament's tardiness policy
+    pub fn tardy_players(&self, now: DateTime<Utc>) -> Vec<(RoundId, PlayerId)> {
+        let Some(policy) = self.settings.tardiness else {
+            return Vec::new();
+        };
+        if !self.is_active() {
+            return Vec::new();
+        }
+        self.round_reg
+            .rounds
+            .values()
+            .flat_map(|rnd| {
+                rnd.tardy_players(policy.grace, now)
+                    .into_iter()
+                    .map(|p| (rnd.id, p))
+            })
+            .filter(|(_, p)| self.player_reg.get_player(p).is_ok_and(Player::can_play))
+            .collect()
+    }
+
     /// Confirms all active rounds in the tournament. If there is at least one active round without
     /// a result, this operations fails atomically.
     pub(crate) fn confirm_all_rounds(&mut self) -> OpResult {
@@ -607,6 +1086,9 @@ impl Tournament {
         for rnd in self.round_reg.get_player_active_rounds(&id) {
             rnd.drop_player(&id);
         }
+        if self.is_planned() {
+            self.fill_from_waitlist();
+        }
         Ok(OpData::Nothing)
     }
 
@@ -619,15 +1101,33 @@ impl Tournament {
         for rnd in self.round_reg.get_player_active_rounds(&id) {
             rnd.drop_player(&id);
         }
+        if self.is_planned() {
+            self.fill_from_waitlist();
+        }
+        Ok(OpData::Nothing)
+    }
+
+    /// An admin returns a dropped player to the tournament
+    pub(crate) fn reinstate_player(&mut self, id: &PlayerId) -> OpResult {
+        if self.is_dead() {
+            return Err(TournamentError::IncorrectStatus(self.status));
+        }
+        self.player_reg.reinstate_player(id)?;
         Ok(OpData::Nothing)
     }
 
     /// Adds a deck to a player's registration data
-    pub(crate) fn player_add_deck(&mut self, id: PlayerId, name: String, deck: Deck) -> OpResult {
+    pub(crate) fn player_add_deck(
+        &mut self,
+        salt: DateTime<Utc>,
+        id: PlayerId,
+        name: String,
+        deck: Deck,
+    ) -> OpResult {
         if !self.is_ongoing() {
             return Err(TournamentError::IncorrectStatus(self.status));
         }
-        if !self.reg_open {
+        if !self.is_reg_open(salt) {
             return Err(TournamentError::RegClosed);
         }
         self.add_deck(id, name, deck)
@@ -679,7 +1179,10 @@ impl Tournament {
                 {
                     Some(pairings) => {
                         let context = self.pairing_sys.get_context();
-                        let rounds = self.round_reg.rounds_from_pairings(salt, pairings, context);
+                        let tables = self.pairing_sys.common.table_assignment;
+                        let rounds = self
+                            .round_reg
+                            .rounds_from_pairings_with_tables(salt, pairings, context, tables);
                         Ok(OpData::Pair(rounds))
                     }
                     None => Ok(OpData::Nothing),
@@ -703,7 +1206,7 @@ impl Tournament {
         if !self.is_active() {
             return Err(TournamentError::IncorrectStatus(self.status));
         }
-        let context = self.pairing_sys.get_context();
+        let context = self.pairing_sys.context_for(&[plyr]);
         if self.player_reg.players.contains_key(&plyr) {
             Ok(OpData::GiveBye(
                 self.round_reg.give_bye(salt, plyr, context),
@@ -713,6 +1216,333 @@ impl Tournament {
         }
     }
 
+    /// Moves a player out of the main field and into the alternates
+    pub(crate) fn make_alternate(&mut self, id: PlayerId) -> OpResult {
+        if !self.is_planned() {
+            return Err(TournamentError::IncorrectStatus(self.status));
+        }
+        self.player_reg.make_alternate(id)?;
+        Ok(OpData::Nothing)
+    }
+
+    /// Promotes an alternate into the main field. Once the first round is paired, alternates can
+    /// only come in by substituting for a player.
+    pub(crate) fn promote_alternate(&mut self, id: &PlayerId) -> OpResult {
+        if !self.is_ongoing() {
+            return Err(TournamentError::IncorrectStatus(self.status));
+        }
+        if !self.round_reg.rounds.is_empty() {
+            return Err(TournamentError::AlternatesClosed);
+        }
+        self.player_reg.promote_alternate(id)?;
+        Ok(OpData::Nothing)
+    }
+
+    /// Replaces a player with an alternate. This is only allowed while the replaced player is
+    /// playing their first round.
+    pub(crate) fn substitute_alternate(&mut self, alt: PlayerId, id: PlayerId) -> OpResult {
+        if !self.is_active() {
+            return Err(TournamentError::IncorrectStatus(self.status));
+        }
+        if !self.player_reg.is_alternate(&alt) {
+            return Err(TournamentError::PlayerNotAlternate);
+        }
+        if !self.player_reg.get_player(&id)?.can_play() {
+            return Err(TournamentError::PlayerNotFound);
+        }
+        let has_played = self
+            .round_reg
+            .rounds
+            .values()
+            .any(|r| r.is_certified() && r.players.contains(&id));
+        if has_played {
+            return Err(TournamentError::InvalidSubstitution);
+        }
+        _ = self.round_reg.substitute_player(&id, alt)?;
+        self.player_reg.drop_player(&id)?;
+        self.player_reg.promote_alternate(&alt)?;
+        Ok(OpData::Nothing)
+    }
+
+    /// Moves a player from the waitlist into the tournament, ignoring the player cap
+    pub(crate) fn promote_from_waitlist(&mut self, id: &PlayerId) -> OpResult {
+        if !self.is_ongoing() {
+            return Err(TournamentError::IncorrectStatus(self.status));
+        }
+        self.player_reg.promote_from_waitlist(id)?;
+        Ok(OpData::Nothing)
+    }
+
+    /// Sets the table numbers that will not be automatically assigned to new rounds
+    pub(crate) fn reserve_tables(&mut self, tables: Vec<u64>) -> OpResult {
+        if self.is_dead() {
+            return Err(TournamentError::IncorrectStatus(self.status));
+        }
+        self.round_reg.reserve_tables(tables);
+        Ok(OpData::Nothing)
+    }
+
+    /// Sets (or removes) the layout of the venue's tables
+    pub(crate) fn set_venue(&mut self, venue: Option<Venue>) -> OpResult {
+        if self.is_dead() {
+            return Err(TournamentError::IncorrectStatus(self.status));
+        }
+        self.round_reg.set_venue(venue)?;
+        Ok(OpData::Nothing)
+    }
+
+    /// Sets the tables that a player can be seated at
+    pub(crate) fn set_table_pool(&mut self, id: PlayerId, pool: Vec<u64>) -> OpResult {
+        if self.is_dead() {
+            return Err(TournamentError::IncorrectStatus(self.status));
+        }
+        if !self.player_reg.is_registered(&id) {
+            return Err(TournamentError::PlayerNotFound);
+        }
+        self.round_reg.set_table_pool(id, pool);
+        Ok(OpData::Nothing)
+    }
+
+    /// Declares a group of players that should not be paired against each other
+    pub(crate) fn add_pairing_constraint(&mut self, plyrs: Vec<PlayerId>) -> OpResult {
+        if self.is_dead() {
+            return Err(TournamentError::IncorrectStatus(self.status));
+        }
+        if plyrs.iter().any(|p| !self.player_reg.is_registered(p)) {
+            return Err(TournamentError::PlayerNotFound);
+        }
+        self.pairing_sys.constraints.add_group(plyrs)?;
+        Ok(OpData::Nothing)
+    }
+
+    /// Links a guest player to an account. The player's id is replaced by the account's id
+    /// everywhere in the tournament, so the operations that came before the link are still valid
+    /// and operations that come after it must use the new id. Players that registered with an
+    /// account can not be linked to another one.
+    pub(crate) fn link_guest_account(&mut self, id: PlayerId, account: SquireAccount) -> OpResult {
+        if self.is_dead() {
+            return Err(TournamentError::IncorrectStatus(self.status));
+        }
+        let new: PlayerId = account.id.0.into();
+        if self.player_reg.players.contains_key(&new) {
+            return Err(TournamentError::PlayerAlreadyRegistered);
+        }
+        let plyr = self.player_reg.get_mut_player(&id)?;
+        if !plyr.guest {
+            return Err(TournamentError::PlayerNotFound);
+        }
+        plyr.guest = false;
+        if plyr.game_name.is_none() {
+            plyr.game_name = Some(account.get_display_name());
+        }
+        plyr.sharing = account.permissions;
+        self.player_reg.swap_player_ids(id, new);
+        self.round_reg.swap_player_ids(id, new);
+        self.pairing_sys.swap_player_ids(id, new);
+        self.scoring_sys.swap_player_ids(id, new);
+        Ok(OpData::Nothing)
+    }
+
+    /// Merges a duplicate registration into another player. The players can not have played in
+    /// the same round, and at most one of them can be in an active round.
+    pub(crate) fn merge_players(&mut self, dup: PlayerId, id: PlayerId) -> OpResult {
+        if self.is_dead() {
+            return Err(TournamentError::IncorrectStatus(self.status));
+        }
+        if !self.player_reg.is_registered(&dup) || !self.player_reg.is_registered(&id) {
+            return Err(TournamentError::PlayerNotFound);
+        }
+        let shared = self
+            .round_reg
+            .rounds
+            .values()
+            .any(|r| r.players.contains(&dup) && r.players.contains(&id));
+        let both_active = [dup, id].iter().all(|p| {
+            self.round_reg
+                .rounds
+                .values()
+                .any(|r| r.is_active() && r.players.contains(p))
+        });
+        if dup == id || shared || both_active {
+            return Err(TournamentError::InvalidMerge);
+        }
+        self.player_reg.merge_players(dup, id);
+        self.round_reg.merge_players(dup, id);
+        self.pairing_sys.swap_player_ids(dup, id);
+        self.scoring_sys.swap_player_ids(dup, id);
+        Ok(OpData::Nothing)
+    }
+
+    /// Moves an active round to the given table
+    pub(crate) fn assign_table(&mut self, id: &RoundId, table: u64) -> OpResult {
+        if !self.is_active() {
+            return Err(TournamentError::IncorrectStatus(self.status));
+        }
+        self.round_reg.assign_round_table(id, table)?;
+        Ok(OpData::Nothing)
+    }
+
+    /// Marks a round as a feature match. If there is an open reserved table, the round is moved to
+    /// it.
+    pub(crate) fn set_feature_match(&mut self, id: &RoundId) -> OpResult {
+        if !self.is_active() {
+            return Err(TournamentError::IncorrectStatus(self.status));
+        }
+        let rnd = self.round_reg.get_mut_round(id)?;
+        if !rnd.is_active() {
+            return Err(TournamentError::IncorrectRoundStatus(rnd.status));
+        }
+        rnd.set_feature(true);
+        let current = rnd.table_number;
+        if !self.round_reg.reserved_tables.contains(&current) {
+            let table = self
+                .round_reg
+                .reserved_tables
+                .iter()
+                .copied()
+                .filter(|n| !self.round_reg.table_in_use(*n))
+                .min();
+            if let Some(table) = table {
+                self.round_reg.assign_round_table(id, table)?;
+            }
+        }
+        Ok(OpData::Nothing)
+    }
+
+    /// Removes the feature match designation (and stream URL) from a round
+    pub(crate) fn remove_feature_match(&mut self, id: &RoundId) -> OpResult {
+        if !self.is_ongoing() {
+            return Err(TournamentError::IncorrectStatus(self.status));
+        }
+        self.round_reg.get_mut_round(id)?.set_feature(false);
+        Ok(OpData::Nothing)
+    }
+
+    /// Sets the stream URL of a feature match
+    pub(crate) fn set_stream_url(&mut self, id: &RoundId, url: Option<String>) -> OpResult {
+        if !self.is_ongoing() {
+            return Err(TournamentError::IncorrectStatus(self.status));
+        }
+        let rnd = self.round_reg.get_mut_round(id)?;
+        if !rnd.is_feature {
+            return Err(TournamentError::NotFeatureMatch);
+        }
+        rnd.stream_url = url;
+        Ok(OpData::Nothing)
+    }
+
+    /// Posts an announcement, removing the oldest announcement if there are too many
+    pub(crate) fn announce(
+        &mut self,
+        salt: DateTime<Utc>,
+        admin: AdminId,
+        message: String,
+    ) -> OpResult {
+        if message.trim().is_empty() {
+            return Err(TournamentError::EmptyAnnouncement);
+        }
+        self.announcements.push(Announcement {
+            admin,
+            message,
+            posted: salt,
+        });
+        if self.announcements.len() > MAX_ANNOUNCEMENTS {
+            let excess = self.announcements.len() - MAX_ANNOUNCEMENTS;
+            _ = self.announcements.drain(..excess);
+        }
+        Ok(OpData::Nothing)
+    }
+
+    /// Marks a point in the tournament's history. The checkpoint only lives in the operation log,
+    /// so the tournament itself is unchanged.
+    pub(crate) fn checkpoint(&self, name: &str) -> OpResult {
+        if name.trim().is_empty() {
+            return Err(TournamentError::EmptyCheckpointName);
+        }
+        Ok(OpData::Nothing)
+    }
+
+    /// Revokes the invite with the given nonce. An invite can only be revoked once, which is what
+    /// makes staff invites single-use.
+    pub(crate) fn revoke_invite(&mut self, nonce: u64) -> OpResult {
+        if self.revoked_invites.insert(nonce) {
+            Ok(OpData::Nothing)
+        } else {
+            Err(TournamentError::InviteRevoked)
+        }
+    }
+
+    /// Splits the players into draft pods and assigns each player a seat. The pods are used for
+    /// the seat map and, if enabled, to keep Swiss pairings within each pod.
+    pub(crate) fn create_draft_pods(&mut self, salt: DateTime<Utc>, size: usize) -> OpResult {
+        if !(self.is_planned() || self.is_active()) {
+            return Err(TournamentError::IncorrectStatus(self.status));
+        }
+        if size == 0 {
+            return Err(TournamentError::InvalidPodSize);
+        }
+        let plyrs: Vec<_> = self
+            .player_reg
+            .players
+            .values()
+            .filter(|p| p.can_play())
+            .map(|p| p.id)
+            .collect();
+        let mut hasher = DeterministicHasher::new(FxHasher64::default());
+        salt.hash(&mut hasher);
+        plyrs.len().hash(&mut hasher);
+        self.pairing_sys.pods = DraftPods::new(plyrs, size, hasher.finish());
+        Ok(OpData::Nothing)
+    }
+
+    /// Moves players into the named flight (i.e. a consolation flight for players that were
+    /// eliminated from the main event), creating it if needed. If no flight is given, the players
+    /// are moved back into the main event.
+    pub(crate) fn move_to_flight(
+        &mut self,
+        plyrs: Vec<PlayerId>,
+        flight: Option<String>,
+    ) -> OpResult {
+        if !self.is_active() {
+            return Err(TournamentError::IncorrectStatus(self.status));
+        }
+        let flight = flight.map(|name| name.trim().to_owned());
+        if flight.as_ref().is_some_and(|name| name.is_empty()) {
+            return Err(TournamentError::InvalidFlightName);
+        }
+        if plyrs
+            .iter()
+            .any(|p| !self.player_reg.get_player(p).is_ok_and(|p| p.can_play()))
+        {
+            return Err(TournamentError::PlayerNotFound);
+        }
+        self.pairing_sys
+            .flights
+            .move_players(&plyrs, flight.as_deref());
+        Ok(OpData::Nothing)
+    }
+
+    /// Waitlists a newly (re-)registered player if the tournament is over its player cap
+    fn apply_player_cap(&mut self, id: PlayerId) -> Result<(), TournamentError> {
+        let cap = self.settings.player_cap as usize;
+        if cap != 0 && self.player_reg.active_player_count() > cap {
+            self.player_reg.waitlist_player(id)?;
+        }
+        Ok(())
+    }
+
+    /// Promotes players from the front of the waitlist until the tournament is at its player cap
+    fn fill_from_waitlist(&mut self) {
+        let cap = self.settings.player_cap as usize;
+        while let Some(id) = self.player_reg.get_waitlist().first().copied() {
+            if cap != 0 && self.player_reg.active_player_count() >= cap {
+                break;
+            }
+            // The waitlist only contains waitlisted players, so this can not fail
+            let _ = self.player_reg.promote_from_waitlist(&id);
+        }
+    }
+
     /// Creates a new round from a list of players
     pub fn create_round(&mut self, salt: DateTime<Utc>, plyrs: Vec<PlayerId>) -> OpResult {
         if !self.is_active() {
@@ -724,27 +1554,37 @@ impl Tournament {
         } else if !plyrs.iter().all_unique() {
             Err(TournamentError::RepeatedPlayerInMatch)
         } else {
-            let context = self.pairing_sys.get_context();
+            let context = self.pairing_sys.context_for(&plyrs);
             Ok(OpData::CreateRound(
-                self.round_reg.create_round(salt, plyrs, context),
+                self.round_reg.create_round_with_tables(
+                    salt,
+                    plyrs,
+                    context,
+                    self.pairing_sys.common.table_assignment,
+                ),
             ))
         }
     }
 
-    /// Drops all by the top N players (by standings)
+    /// Drops all by the top N players (by standings) and starts the next stage of the tournament.
+    /// How the scores from before the cut are used is determined by the stage scoring setting.
     pub(crate) fn cut_to_top(&mut self, len: usize) -> OpResult {
         if !self.is_active() {
             return Err(TournamentError::IncorrectStatus(self.status));
         }
-        let player_iter = self
+        // Standings go from last place to first place
+        let standings: Vec<PlayerId> = self
             .get_standings()
             .scores
             .into_iter()
-            .skip(len)
-            .map(|(id, _)| id);
-        for id in player_iter {
-            let _ = self.drop_player(id);
-        }
+            .rev()
+            .map(|(id, _)| id)
+            .collect();
+        for id in standings.iter().skip(len) {
+            let _ = self.drop_player(*id);
+        }
+        let seeding = standings.into_iter().take(len).collect();
+        self.scoring_sys.start_stage(&self.round_reg, seeding);
         Ok(OpData::Nothing)
     }
 
@@ -756,20 +1596,43 @@ impl Tournament {
         if !self.is_ongoing() {
             Err(TournamentError::IncorrectStatus(self.status))
         } else {
-            Ok(OpData::RegisterPlayer(
-                self.player_reg
-                    .register_player_with_name(account, tourn_name)?,
-            ))
+            let id = self
+                .player_reg
+                .register_player_with_name(account, tourn_name)?;
+            self.apply_player_cap(id)?;
+            Ok(OpData::RegisterPlayer(id))
+        }
+    }
+
+    fn admin_register_players(
+        &mut self,
+        players: Vec<(SquireAccount, Option<String>)>,
+    ) -> OpResult {
+        if !self.is_ongoing() {
+            return Err(TournamentError::IncorrectStatus(self.status));
+        }
+        // The whole list is registered or none of it is
+        let backup = self.player_reg.clone();
+        let mut ids = Vec::with_capacity(players.len());
+        for (account, tourn_name) in players {
+            match self.admin_register_player(account, tourn_name) {
+                Ok(data) => ids.push(data.assume_register_player()),
+                Err(err) => {
+                    self.player_reg = backup;
+                    return Err(err);
+                }
+            }
         }
+        Ok(OpData::RegisterPlayers(ids))
     }
 
     fn register_guest(&mut self, salt: DateTime<Utc>, name: String) -> OpResult {
         if !self.is_ongoing() {
             Err(TournamentError::IncorrectStatus(self.status))
         } else {
-            Ok(OpData::RegisterPlayer(
-                self.player_reg.add_guest(salt, name)?,
-            ))
+            let id = self.player_reg.add_guest(salt, name)?;
+            self.apply_player_cap(id)?;
+            Ok(OpData::RegisterPlayer(id))
         }
     }
 
@@ -777,7 +1640,8 @@ impl Tournament {
         if !self.is_ongoing() {
             Err(TournamentError::IncorrectStatus(self.status))
         } else {
-            self.player_reg.reregister_guest(name)?;
+            let id = self.player_reg.reregister_guest(name)?;
+            self.apply_player_cap(id)?;
             Ok(OpData::Nothing)
         }
     }
@@ -792,6 +1656,18 @@ impl Tournament {
         }
     }
 
+    fn register_scorekeeper(&mut self, account: SquireAccount) -> OpResult {
+        if !self.is_ongoing() {
+            Err(TournamentError::IncorrectStatus(self.status))
+        } else {
+            let scorekeeper = Scorekeeper::new(account);
+            _ = self
+                .scorekeepers
+                .insert(scorekeeper.id, scorekeeper.clone());
+            Ok(OpData::RegisterScorekeeper(scorekeeper))
+        }
+    }
+
     fn register_admin(&mut self, account: SquireAccount) -> OpResult {
         if !self.is_ongoing() {
             Err(TournamentError::IncorrectStatus(self.status))
@@ -814,6 +1690,9 @@ impl Tournament {
     // NOTE: Does *not* contain checks for tournament/player validity. Those are handled by the
     // admin and player versions
     fn add_deck(&mut self, id: PlayerId, name: String, deck: Deck) -> OpResult {
+        if deck_size(&deck) > MAX_DECK_SIZE {
+            return Err(TournamentError::DeckTooLarge);
+        }
         let plyr = self.player_reg.get_mut_player(&id)?;
         if !plyr.decks.contains_key(&name)
             && plyr.decks.len() >= self.settings.max_deck_count as usize
@@ -832,11 +1711,19 @@ impl Tournament {
         Ok(OpData::Nothing)
     }
 
-    fn admin_record_result(&mut self, id: RoundId, result: RoundResult) -> OpResult {
+    fn admin_record_result(
+        &mut self,
+        salt: DateTime<Utc>,
+        id: RoundId,
+        result: RoundResult,
+    ) -> OpResult {
         if !self.is_active() {
             return Err(TournamentError::IncorrectStatus(self.status));
         }
-        self.round_reg.get_mut_round(&id)?.record_result(result)?;
+        let rnd = self.round_reg.get_mut_round(&id)?;
+        self.settings.match_structure.check_result(rnd, &result)?;
+        rnd.record_result(result)?;
+        rnd.result_time = Some(salt);
         Ok(OpData::Nothing)
     }
 
@@ -874,7 +1761,10 @@ impl Tournament {
                 {
                     Some(pairings) => {
                         let context = self.pairing_sys.get_context();
-                        let rounds = self.round_reg.rounds_from_pairings(salt, pairings, context);
+                        let tables = self.pairing_sys.common.table_assignment;
+                        let rounds = self
+                            .round_reg
+                            .rounds_from_pairings_with_tables(salt, pairings, context, tables);
                         Ok(OpData::Pair(rounds))
                     }
                     None => Ok(OpData::Nothing),
@@ -888,7 +1778,9 @@ impl Tournament {
         if !self.is_active() {
             return Err(TournamentError::IncorrectStatus(self.status));
         }
-        self.round_reg.get_mut_round(&id)?.record_result(result)?;
+        let rnd = self.round_reg.get_mut_round(&id)?;
+        self.settings.match_structure.check_result(rnd, &result)?;
+        rnd.record_result(result)?;
         Ok(OpData::Nothing)
     }
 
@@ -982,14 +1874,40 @@ impl Tournament {
         ret
     }
 
+    /// Calculates if the players' decklists can be shown to users that aren't officials
+    pub fn decklists_visible(&self) -> bool {
+        match self.settings.decklist_visibility {
+            DecklistVisibility::Never => false,
+            DecklistVisibility::AfterStart => !self.is_planned(),
+            DecklistVisibility::AfterEnd => self.is_dead(),
+        }
+    }
+
+    /// Removes the contents of every player's decks, along with their hashes. The names of the
+    /// decks are kept.
+    pub fn hide_decklists(&mut self) {
+        self.player_reg.players.values_mut().for_each(|plyr| {
+            plyr.decks.clear();
+            plyr.deck_hashes.clear();
+        });
+    }
+
+    /// Removes everything that the players chose not to share, so that the tournament can be shown
+    /// to users that aren't officials
+    pub fn redact(&mut self) {
+        self.player_reg.redact();
+    }
+
     /// Determines the role of a user in the tournament.
     pub fn user_role(&self, id: Uuid) -> TournRole {
         if self.admins.contains_key(&AdminId::new(id)) {
             TournRole::Admin(id.into())
+        } else if self.scorekeepers.contains_key(&ScorekeeperId::new(id)) {
+            TournRole::Scorekeeper(id.into())
         } else if self.judges.contains_key(&JudgeId::new(id)) {
             TournRole::Judge(id.into())
         } else if let Ok(plyr) = self.get_player_by_id(&PlayerId::new(id)) {
-            if plyr.can_play() {
+            if plyr.can_play() || plyr.is_alternate() || plyr.is_waitlisted() {
                 TournRole::Player(id.into())
             } else {
                 TournRole::Spectator
@@ -1104,11 +2022,12 @@ impl TournamentSeed {
     }
 
     /// Creates a new tournament seed, generating a name using [Self::default_name]
-    pub fn new_named(preset: TournamentPreset, format: String) -> Self {
+    pub fn new_named(preset: TournamentPreset, format: Format) -> Self {
         Self {
             name: Self::default_name(),
             preset,
             format,
+            template: None,
         }
     }
 
@@ -1116,7 +2035,7 @@ impl TournamentSeed {
     pub fn new(
         name: String,
         preset: TournamentPreset,
-        format: String,
+        format: Format,
     ) -> Result<Self, TournamentError> {
         // name validation
         if !Self::validate_name(&name) {
@@ -1127,9 +2046,16 @@ impl TournamentSeed {
             name,
             preset,
             format,
+            template: None,
         })
     }
 
+    /// Sets up the to-be tournament using the given template
+    pub fn with_template(mut self, template: TournamentTemplate) -> Self {
+        self.template = Some(template);
+        self
+    }
+
     /// Validates a tournament name. Returns true if the name is valid.
     pub fn validate_name(name: &str) -> bool {
         !name.trim().is_empty()
@@ -1150,18 +2076,34 @@ pub enum TournRole {
     Player(PlayerId),
     /// The user is a judge
     Judge(JudgeId),
+    /// The user is a scorekeeper
+    Scorekeeper(ScorekeeperId),
     /// The user is a admin
     Admin(AdminId),
 }
 
+impl TournRole {
+    /// Calculates if the role is one of the tournament's officials
+    pub fn is_official(&self) -> bool {
+        matches!(
+            self,
+            TournRole::Judge(_) | TournRole::Scorekeeper(_) | TournRole::Admin(_)
+        )
+    }
+}
+
 impl From<TournamentSeed> for Tournament {
     fn from(seed: TournamentSeed) -> Self {
         let TournamentSeed {
             name,
             preset,
             format,
+            template,
         } = seed;
-        Tournament::from_preset(name, preset, format)
+        match template {
+            Some(template) => Tournament::from_template(name, format, template),
+            None => Tournament::from_preset(name, preset, format),
+        }
     }
 }
 
@@ -1194,6 +2136,7 @@ mod tests {
         accounts::{SharingPermissions, SquireAccount},
         admin::Admin,
         error::TournamentError,
+        formats::Format,
         operations::{AdminOp, PlayerOp, TournOp},
         rounds::RoundResult,
         tournament::TournamentSeed,
@@ -1212,8 +2155,11 @@ mod tests {
 
     #[test]
     fn players_in_paired_rounds() {
-        let mut tourn =
-            Tournament::from_preset("Test".into(), TournamentPreset::Swiss, "Test".into());
+        let mut tourn = Tournament::from_preset(
+            "Test".into(),
+            TournamentPreset::Swiss,
+            Format::custom("Test").unwrap(),
+        );
         assert_eq!(tourn.pairing_sys.common.match_size, 2);
         let acc = spoof_account();
         let admin = Admin::new(acc);
@@ -1247,8 +2193,11 @@ mod tests {
 
     #[test]
     fn confirm_all_rounds_test() {
-        let mut tourn =
-            Tournament::from_preset("Test".into(), TournamentPreset::Swiss, "Test".into());
+        let mut tourn = Tournament::from_preset(
+            "Test".into(),
+            TournamentPreset::Swiss,
+            Format::custom("Test").unwrap(),
+        );
         assert_eq!(tourn.pairing_sys.common.match_size, 2);
         let acc = spoof_account();
         let admin = Admin::new(acc);
@@ -1331,7 +2280,7 @@ mod tests {
             TournamentSeed::new(
                 name.to_string(),
                 TournamentPreset::Fluid,
-                "Test".to_string(),
+                Format::custom("Test").unwrap(),
             )
         }
 
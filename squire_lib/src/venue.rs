@@ -0,0 +1,169 @@
+use serde::{Deserialize, Serialize};
+
+use crate::error::TournamentError;
+
+/// The layout of the tables at a tournament's venue. When a tournament has a venue, new rounds
+/// are only seated at tables that belong to one of its zones.
+#[derive(Serialize, Deserialize, Debug, Default, Hash, Clone, PartialEq, Eq)]
+pub struct Venue {
+    /// The zones of the venue, in the order that their tables are filled
+    pub zones: Vec<TableZone>,
+}
+
+/// A named range of tables, such as a hall or a section of a hall (e.g. "Hall A" with tables 1
+/// through 50)
+#[derive(Serialize, Deserialize, Debug, Hash, Clone, PartialEq, Eq)]
+pub struct TableZone {
+    /// The zone's name, which is shown to players
+    pub name: String,
+    /// The first table in the zone
+    pub first_table: u64,
+    /// The last table in the zone (inclusive)
+    pub last_table: u64,
+    /// The number of tables in each row of the zone, for drawing a floor map. If this is not set,
+    /// the zone is drawn as a single row.
+    #[serde(default)]
+    pub columns: Option<u32>,
+}
+
+impl TableZone {
+    /// Creates a zone from the first to the last table (inclusive)
+    pub fn new(name: impl Into<String>, first_table: u64, last_table: u64) -> Self {
+        Self {
+            name: name.into(),
+            first_table,
+            last_table,
+            columns: None,
+        }
+    }
+
+    /// Calculates if the table is in this zone
+    pub fn contains(&self, table: u64) -> bool {
+        (self.first_table..=self.last_table).contains(&table)
+    }
+
+    /// Returns the tables in this zone, in order
+    pub fn tables(&self) -> impl Iterator<Item = u64> {
+        self.first_table..=self.last_table
+    }
+
+    /// Returns the row and column that a table is drawn at on a floor map, starting at 0
+    pub fn position(&self, table: u64) -> Option<(u64, u64)> {
+        let columns = u64::from(self.columns.unwrap_or(0));
+        let offset = self.contains(table).then(|| table - self.first_table)?;
+        Some(match columns {
+            0 => (0, offset),
+            n => (offset / n, offset % n),
+        })
+    }
+}
+
+impl Venue {
+    /// Checks that every zone has a name and at least one table, and that no two zones share a
+    /// table
+    pub fn validate(&self) -> Result<(), TournamentError> {
+        let mut ranges: Vec<_> = self
+            .zones
+            .iter()
+            .map(|z| (z.first_table, z.last_table))
+            .collect();
+        ranges.sort_unstable();
+        let overlaps = ranges.windows(2).any(|w| w[1].0 <= w[0].1);
+        let invalid = self
+            .zones
+            .iter()
+            .any(|z| z.name.trim().is_empty() || z.first_table > z.last_table);
+        if overlaps || invalid {
+            return Err(TournamentError::InvalidVenue);
+        }
+        Ok(())
+    }
+
+    /// Returns the zone that a table is in
+    pub fn zone_of(&self, table: u64) -> Option<&TableZone> {
+        self.zones.iter().find(|z| z.contains(table))
+    }
+
+    /// Returns every table in the venue, zone by zone
+    pub fn tables(&self) -> impl Iterator<Item = u64> + '_ {
+        self.zones.iter().flat_map(TableZone::tables)
+    }
+
+    /// The first table number after every table in the venue. Once every table in the venue is in
+    /// use, new rounds are seated at overflow tables starting at this number.
+    pub fn overflow_start(&self) -> u64 {
+        self.zones
+            .iter()
+            .map(|z| z.last_table.saturating_add(1))
+            .max()
+            .unwrap_or_default()
+    }
+}
+
+/// A floor map of a tournament's venue, which shows the round that is seated at each table
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq)]
+pub struct VenueMap {
+    /// The venue's zones. This is empty if the tournament does not have a venue.
+    pub zones: Vec<ZoneMap>,
+}
+
+/// One zone of a venue's floor map
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ZoneMap {
+    /// The zone's name
+    pub name: String,
+    /// The zone's tables, in order
+    pub tables: Vec<MapTable>,
+}
+
+/// One table on a venue's floor map
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MapTable {
+    /// The table number
+    pub table: u64,
+    /// The row of the zone that the table is drawn in, starting at 0
+    pub row: u64,
+    /// The column of the zone that the table is drawn in, starting at 0
+    pub column: u64,
+    /// The match number of the active round that is seated at the table
+    pub match_number: Option<u64>,
+    /// Whether the table is reserved, i.e. for feature matches
+    pub reserved: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{TableZone, Venue};
+    use crate::error::TournamentError;
+
+    #[test]
+    fn venues_are_validated() {
+        let mut venue = Venue {
+            zones: vec![
+                TableZone::new("Hall A", 1, 50),
+                TableZone::new("Hall B", 101, 150),
+            ],
+        };
+        assert_eq!(venue.validate(), Ok(()));
+        assert_eq!(venue.zone_of(120).unwrap().name, "Hall B");
+        assert!(venue.zone_of(75).is_none());
+        assert_eq!(venue.overflow_start(), 151);
+
+        venue.zones.push(TableZone::new("Side events", 150, 160));
+        assert_eq!(venue.validate(), Err(TournamentError::InvalidVenue));
+        venue.zones[2] = TableZone::new(" ", 200, 210);
+        assert_eq!(venue.validate(), Err(TournamentError::InvalidVenue));
+        venue.zones[2] = TableZone::new("Side events", 210, 200);
+        assert_eq!(venue.validate(), Err(TournamentError::InvalidVenue));
+    }
+
+    #[test]
+    fn tables_have_floor_map_positions() {
+        let mut zone = TableZone::new("Hall A", 11, 20);
+        assert_eq!(zone.position(13), Some((0, 2)));
+        zone.columns = Some(4);
+        assert_eq!(zone.position(11), Some((0, 0)));
+        assert_eq!(zone.position(16), Some((1, 1)));
+        assert_eq!(zone.position(21), None);
+    }
+}
@@ -0,0 +1,178 @@
+//! JavaScript bindings for tournaments, which let lightweight web tools (e.g. a standalone bracket
+//! viewer) use the tournament model without pulling in the whole SDK.
+//!
+//! Tournaments are wrapped in a JS `Tournament` class. Everything else crosses the boundary as a
+//! plain JS value with the same shape as the JSON encoding used by the rest of Squire. Errors are
+//! thrown as JS `Error`s.
+
+use chrono::{DateTime, Utc};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_wasm_bindgen::Serializer;
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    identifiers::PlayerId,
+    operations::TournOp,
+    r64,
+    tournament::{Tournament, TournamentSeed},
+};
+
+fn from_js<T: DeserializeOwned>(value: JsValue) -> Result<T, JsError> {
+    serde_wasm_bindgen::from_value(value).map_err(|err| JsError::new(&err.to_string()))
+}
+
+fn to_js<T: Serialize>(value: &T) -> Result<JsValue, JsError> {
+    value
+        .serialize(&Serializer::json_compatible())
+        .map_err(|err| JsError::new(&err.to_string()))
+}
+
+fn to_f64(val: r64) -> f64 {
+    f64::from(*val.numer()) / f64::from(*val.denom())
+}
+
+/// A player's place in the standings. Unlike the scores in the model, the values are plain
+/// numbers rather than ratios.
+#[derive(Serialize, Debug, Clone)]
+struct Standing {
+    place: usize,
+    player: PlayerId,
+    name: String,
+    match_points: f64,
+    game_points: f64,
+    mwp: f64,
+    gwp: f64,
+    opp_mwp: f64,
+    opp_gwp: f64,
+}
+
+/// A tournament, exposed to JS as the `Tournament` class
+#[wasm_bindgen(js_name = Tournament)]
+#[derive(Debug, Clone)]
+pub struct JsTournament {
+    tourn: Tournament,
+}
+
+#[wasm_bindgen(js_class = Tournament)]
+impl JsTournament {
+    /// Creates a new tournament from a `TournamentSeed`
+    #[wasm_bindgen(constructor)]
+    pub fn new(seed: JsValue) -> Result<JsTournament, JsError> {
+        let seed: TournamentSeed = from_js(seed)?;
+        Ok(Self { tourn: seed.into() })
+    }
+
+    /// Loads a tournament from the value returned by `toJson` or a tournament that was fetched
+    /// from SquireCore
+    #[wasm_bindgen(js_name = fromJson)]
+    pub fn from_json(value: JsValue) -> Result<JsTournament, JsError> {
+        from_js(value).map(|tourn| Self { tourn })
+    }
+
+    /// Returns the whole tournament as a plain value
+    #[wasm_bindgen(js_name = toJson)]
+    pub fn to_json(&self) -> Result<JsValue, JsError> {
+        to_js(&self.tourn)
+    }
+
+    /// The tournament's id
+    #[wasm_bindgen(getter)]
+    pub fn id(&self) -> String {
+        self.tourn.id.to_string()
+    }
+
+    /// The tournament's name
+    #[wasm_bindgen(getter)]
+    pub fn name(&self) -> String {
+        self.tourn.name.clone()
+    }
+
+    /// The tournament's status
+    #[wasm_bindgen(getter)]
+    pub fn status(&self) -> String {
+        self.tourn.status.to_string()
+    }
+
+    /// Applies a `TournOp` to the tournament and returns the resulting `OpData`. The salt is the
+    /// time the operation was made at, as an RFC 3339 string, and defaults to now. If the
+    /// operation fails, the `TournamentError` is thrown and the tournament is unchanged.
+    #[wasm_bindgen(js_name = applyOp)]
+    pub fn apply_op(&mut self, op: JsValue, salt: Option<String>) -> Result<JsValue, JsError> {
+        let op: TournOp = from_js(op)?;
+        let salt = match salt {
+            Some(salt) => DateTime::parse_from_rfc3339(&salt)
+                .map_err(|err| JsError::new(&err.to_string()))?
+                .with_timezone(&Utc),
+            None => Utc::now(),
+        };
+        let data = self
+            .tourn
+            .apply_op(salt, op)
+            .map_err(|err| JsError::new(&err.to_string()))?;
+        to_js(&data)
+    }
+
+    /// Returns the current standings, best first
+    pub fn standings(&self) -> Result<JsValue, JsError> {
+        let standings: Vec<_> = self
+            .tourn
+            .get_standings()
+            .scores
+            .into_iter()
+            .enumerate()
+            .map(|(i, (player, score))| Standing {
+                place: i + 1,
+                player,
+                name: self
+                    .tourn
+                    .player_reg
+                    .get_player(&player)
+                    .map(|plyr| plyr.name.clone())
+                    .unwrap_or_default(),
+                match_points: to_f64(score.match_points),
+                game_points: to_f64(score.game_points),
+                mwp: to_f64(score.mwp),
+                gwp: to_f64(score.gwp),
+                opp_mwp: to_f64(score.opp_mwp),
+                opp_gwp: to_f64(score.opp_gwp),
+            })
+            .collect();
+        to_js(&standings)
+    }
+}
+
+#[cfg(all(test, target_arch = "wasm32"))]
+mod tests {
+    use squire_tests::spoof_account;
+    use wasm_bindgen::JsValue;
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    use super::{from_js, to_js, JsTournament};
+    use crate::{
+        formats::Constructed,
+        operations::TournOp,
+        tournament::{TournamentPreset, TournamentSeed},
+    };
+
+    #[wasm_bindgen_test]
+    fn ops_and_standings_cross_the_boundary() {
+        let seed = TournamentSeed::new(
+            "Test".into(),
+            TournamentPreset::Swiss,
+            Constructed::Pioneer.into(),
+        )
+        .unwrap();
+        let mut tourn = JsTournament::new(to_js(&seed).unwrap()).unwrap();
+        for _ in 0..4 {
+            let op = TournOp::RegisterPlayer(spoof_account(), None);
+            let _ = tourn.apply_op(to_js(&op).unwrap(), None).unwrap();
+        }
+        let standings: Vec<serde_json::Value> = from_js(tourn.standings().unwrap()).unwrap();
+        assert_eq!(standings.len(), 4);
+        assert_eq!(standings[0]["place"], 1);
+
+        let copy = JsTournament::from_json(tourn.to_json().unwrap()).unwrap();
+        assert_eq!(copy.tourn, tourn.tourn);
+        assert!(tourn.apply_op(JsValue::NULL, None).is_err());
+    }
+}
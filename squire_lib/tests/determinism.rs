@@ -1,5 +1,7 @@
 #[cfg(test)]
 mod tests {
+    use std::collections::HashMap;
+
     use chrono::{DateTime, Utc};
     use squire_lib::{
         accounts::SquireAccount,
@@ -77,6 +79,66 @@ mod tests {
         assert_eq!(tourn_one, tourn_two);
     }
 
+    #[test]
+    fn pairings_can_be_reproduced() {
+        let account = SquireAccount::new("Test".into(), "Test".into());
+        let a_id: AdminId = account.id.0.into();
+        let mut tourn = account.create_tournament(get_seed());
+        let op = TournOp::AdminOp(
+            a_id,
+            AdminOp::UpdateTournSetting(TournamentSetting::PairingSetting(PairingSetting::Common(
+                CommonPairingSetting::MatchSize(2),
+            ))),
+        );
+        tourn.apply_op(Utc::now(), op).unwrap().assume_nothing();
+        for i in 0..5 {
+            let op = TournOp::JudgeOp(a_id.into(), JudgeOp::RegisterGuest(format!("Player{i}")));
+            let _ = tourn.apply_op(Utc::now(), op).unwrap();
+        }
+        tourn
+            .apply_op(Utc::now(), TournOp::AdminOp(a_id, AdminOp::Start))
+            .unwrap()
+            .assume_nothing();
+        assert!(tourn.explain_pairings(1).is_none());
+
+        // The same tournament always creates the same pairings
+        let pairings = tourn.create_pairings().unwrap();
+        assert_eq!(tourn.create_pairings().unwrap(), pairings);
+        let op = TournOp::AdminOp(a_id, AdminOp::PairRound(pairings.clone()));
+        let r_ids = tourn.apply_op(Utc::now(), op).unwrap().assume_pair();
+
+        // The audit is stored once for the whole pairing round
+        assert_eq!(tourn.round_reg.pairing_audits.len(), 1);
+        for id in r_ids {
+            let rnd = tourn.round_reg.get_round(&id).unwrap();
+            assert_eq!(rnd.context.pairing_round(), Some(1));
+        }
+        let audit = tourn.explain_pairings(1).unwrap();
+        assert_eq!(audit.round_number, 1);
+        assert_eq!(audit.pairings, pairings);
+        assert_eq!(audit.standings.len(), 5);
+        assert_eq!(audit.settings.match_size, 2);
+        assert!(audit.prior_byes.is_empty());
+        assert!(tourn.explain_pairings(2).is_none());
+
+        // The recorded inputs give back the same pairings
+        assert_eq!(audit.points.len(), 5);
+        let points = audit
+            .standings
+            .iter()
+            .copied()
+            .zip(audit.points.iter().copied())
+            .collect();
+        let reproduced = audit.settings.algorithm.pair(
+            audit.standings.clone(),
+            &points,
+            &HashMap::new(),
+            2,
+            audit.settings.repair_tolerance,
+        );
+        assert_eq!(reproduced, pairings);
+    }
+
     type HashDeterminismCase = (DateTime<Utc>, Vec<u64>, TypeId<()>, TypeId<()>);
 
     /// Independently tests `squire_lib::identifiers::id_from_item` for consistent behavior across
@@ -1,2 +1,493 @@
 #[cfg(test)]
-mod tests {}
+mod tests {
+    use std::{cmp::Ordering, time::Duration};
+
+    use chrono::{Duration as ChronoDuration, Utc};
+    use squire_lib::{
+        error::TournamentError,
+        identifiers::{AdminId, PlayerId},
+        operations::{AdminOp::*, JudgeOp, PlayerOp, TournOp},
+        rounds::{DeckCheckResult, RoundResult, RoundStatus},
+        settings::{GeneralSetting, MatchStructure, TardinessPenalty, TardinessPolicy},
+    };
+    use squire_tests::{get_seed, spoof_account};
+
+    #[test]
+    fn feature_match_tests() {
+        let admin = spoof_account();
+        let admin_id: AdminId = admin.id.0.into();
+        let mut tourn = admin.create_tournament(get_seed());
+        let plyrs: Vec<PlayerId> = (0..4)
+            .map(|_| {
+                tourn
+                    .apply_op(Utc::now(), TournOp::RegisterPlayer(spoof_account(), None))
+                    .unwrap()
+                    .assume_register_player()
+            })
+            .collect();
+        tourn
+            .apply_op(
+                Utc::now(),
+                TournOp::AdminOp(admin_id, ReserveTables(vec![100])),
+            )
+            .unwrap()
+            .assume_nothing();
+        tourn
+            .apply_op(Utc::now(), TournOp::AdminOp(admin_id, Start))
+            .unwrap()
+            .assume_nothing();
+        let r_id = tourn
+            .apply_op(
+                Utc::now(),
+                TournOp::AdminOp(admin_id, CreateRound(plyrs[..2].to_vec())),
+            )
+            .unwrap()
+            .assume_create_round();
+        let _ = tourn
+            .apply_op(
+                Utc::now(),
+                TournOp::AdminOp(admin_id, CreateRound(plyrs[2..].to_vec())),
+            )
+            .unwrap();
+
+        // Only feature matches can have a stream
+        let url = Some("https://twitch.tv/squire".to_owned());
+        assert_eq!(
+            tourn.apply_op(
+                Utc::now(),
+                TournOp::AdminOp(admin_id, SetStreamUrl(r_id, url.clone()))
+            ),
+            Err(TournamentError::NotFeatureMatch)
+        );
+        assert!(tourn.get_feature_matches().is_empty());
+
+        // Feature matches are moved to the reserved tables
+        tourn
+            .apply_op(
+                Utc::now(),
+                TournOp::AdminOp(admin_id, SetFeatureMatch(r_id)),
+            )
+            .unwrap()
+            .assume_nothing();
+        tourn
+            .apply_op(
+                Utc::now(),
+                TournOp::AdminOp(admin_id, SetStreamUrl(r_id, url.clone())),
+            )
+            .unwrap()
+            .assume_nothing();
+        let features = tourn.get_feature_matches();
+        assert_eq!(features.len(), 1);
+        assert_eq!(features[0].id, r_id);
+        assert_eq!(features[0].table_number, 100);
+        assert_eq!(features[0].stream_url, url);
+
+        // Removing the designation clears the stream
+        tourn
+            .apply_op(
+                Utc::now(),
+                TournOp::AdminOp(admin_id, RemoveFeatureMatch(r_id)),
+            )
+            .unwrap()
+            .assume_nothing();
+        assert!(tourn.get_feature_matches().is_empty());
+        assert_eq!(tourn.round_reg.get_round(&r_id).unwrap().stream_url, None);
+    }
+
+    #[test]
+    fn matches_in_progress_tests() {
+        let admin = spoof_account();
+        let admin_id: AdminId = admin.id.0.into();
+        let mut tourn = admin.create_tournament(get_seed());
+        let plyrs: Vec<PlayerId> = (0..6)
+            .map(|_| {
+                tourn
+                    .apply_op(Utc::now(), TournOp::RegisterPlayer(spoof_account(), None))
+                    .unwrap()
+                    .assume_register_player()
+            })
+            .collect();
+        tourn
+            .apply_op(Utc::now(), TournOp::AdminOp(admin_id, Start))
+            .unwrap()
+            .assume_nothing();
+        let rnds: Vec<_> = plyrs
+            .chunks(2)
+            .map(|chunk| {
+                tourn
+                    .apply_op(
+                        Utc::now(),
+                        TournOp::AdminOp(admin_id, CreateRound(chunk.to_vec())),
+                    )
+                    .unwrap()
+                    .assume_create_round()
+            })
+            .collect();
+
+        // The second round has a result and one of its two confirmations
+        tourn
+            .apply_op(
+                Utc::now(),
+                TournOp::PlayerOp(
+                    plyrs[2],
+                    PlayerOp::RecordResult(rnds[1], RoundResult::Wins(plyrs[2], 2)),
+                ),
+            )
+            .unwrap()
+            .assume_nothing();
+        let _ = tourn
+            .apply_op(
+                Utc::now(),
+                TournOp::PlayerOp(plyrs[2], PlayerOp::ConfirmResult(rnds[1])),
+            )
+            .unwrap();
+
+        // The third round has more time than the others
+        tourn
+            .apply_op(
+                Utc::now(),
+                TournOp::JudgeOp(
+                    admin_id.into(),
+                    JudgeOp::TimeExtension(rnds[2], Duration::from_secs(300)),
+                ),
+            )
+            .unwrap()
+            .assume_nothing();
+
+        let matches = tourn.get_matches_in_progress();
+        assert_eq!(matches.len(), 3);
+        assert_eq!(matches[2].id, rnds[2]);
+        let missing_result = matches.iter().find(|m| m.id == rnds[0]).unwrap();
+        assert!(!missing_result.has_result);
+        assert_eq!(missing_result.unconfirmed.len(), 2);
+        let mut missing_confirm = matches.iter().find(|m| m.id == rnds[1]).unwrap().clone();
+        assert!(missing_confirm.has_result);
+        assert_eq!(missing_confirm.unconfirmed, vec![plyrs[3]]);
+
+        // With the same time left, rounds missing a result are more urgent
+        missing_confirm.time_left = missing_result.time_left;
+        assert_eq!(missing_result.cmp_urgency(&missing_confirm), Ordering::Less);
+
+        // Certified rounds are no longer in progress
+        let _ = tourn
+            .apply_op(
+                Utc::now(),
+                TournOp::PlayerOp(plyrs[3], PlayerOp::ConfirmResult(rnds[1])),
+            )
+            .unwrap();
+        let matches = tourn.get_matches_in_progress();
+        assert_eq!(matches.len(), 2);
+        assert!(matches.iter().all(|m| m.id != rnds[1]));
+    }
+
+    #[test]
+    fn deck_check_tests() {
+        let admin = spoof_account();
+        let admin_id: AdminId = admin.id.0.into();
+        let mut tourn = admin.create_tournament(get_seed());
+        let plyrs: Vec<PlayerId> = (0..2)
+            .map(|_| {
+                tourn
+                    .apply_op(Utc::now(), TournOp::RegisterPlayer(spoof_account(), None))
+                    .unwrap()
+                    .assume_register_player()
+            })
+            .collect();
+        tourn
+            .apply_op(Utc::now(), TournOp::AdminOp(admin_id, Start))
+            .unwrap()
+            .assume_nothing();
+        let r_id = tourn
+            .apply_op(
+                Utc::now(),
+                TournOp::AdminOp(admin_id, CreateRound(plyrs.clone())),
+            )
+            .unwrap()
+            .assume_create_round();
+        let check_op = |op| TournOp::JudgeOp(admin_id.into(), op);
+
+        // A deck check has to be started before it can be completed
+        let start = Utc::now();
+        assert_eq!(
+            tourn.apply_op(
+                start,
+                check_op(JudgeOp::CompleteDeckCheck(r_id, DeckCheckResult::Passed))
+            ),
+            Err(TournamentError::NoDeckCheck)
+        );
+        tourn
+            .apply_op(start, check_op(JudgeOp::StartDeckCheck(r_id)))
+            .unwrap()
+            .assume_nothing();
+        assert_eq!(
+            tourn.apply_op(start, check_op(JudgeOp::StartDeckCheck(r_id))),
+            Err(TournamentError::DeckCheckInProgress)
+        );
+
+        // The round is given the time the deck check took, rounded up to the minute
+        let end = start + ChronoDuration::seconds(7 * 60 + 10);
+        let result = DeckCheckResult::Failed(plyrs[0], "Sideboard has 16 cards".to_owned());
+        tourn
+            .apply_op(
+                end,
+                check_op(JudgeOp::CompleteDeckCheck(r_id, result.clone())),
+            )
+            .unwrap()
+            .assume_nothing();
+        let rnd = tourn.round_reg.get_round(&r_id).unwrap();
+        assert_eq!(rnd.extension, Duration::from_secs(8 * 60));
+        assert_eq!(rnd.deck_check_start, None);
+        assert_eq!(rnd.deck_checks.len(), 1);
+        assert_eq!(rnd.deck_checks[0].result, result);
+    }
+
+    #[test]
+    fn match_structure_tests() {
+        let admin = spoof_account();
+        let admin_id: AdminId = admin.id.0.into();
+        let mut tourn = admin.create_tournament(get_seed());
+        assert_eq!(tourn.settings.match_structure, MatchStructure::BestOf(3));
+        let plyrs: Vec<PlayerId> = (0..2)
+            .map(|_| {
+                tourn
+                    .apply_op(Utc::now(), TournOp::RegisterPlayer(spoof_account(), None))
+                    .unwrap()
+                    .assume_register_player()
+            })
+            .collect();
+        tourn
+            .apply_op(Utc::now(), TournOp::AdminOp(admin_id, Start))
+            .unwrap()
+            .assume_nothing();
+        let r_id = tourn
+            .apply_op(
+                Utc::now(),
+                TournOp::AdminOp(admin_id, CreateRound(plyrs.clone())),
+            )
+            .unwrap()
+            .assume_create_round();
+        let record = |plyr: PlayerId, wins: u32| {
+            TournOp::PlayerOp(
+                plyr,
+                PlayerOp::RecordResult(r_id, RoundResult::Wins(plyr, wins)),
+            )
+        };
+
+        // In a best-of-three, a player wins at most two games and the players win at most three
+        assert_eq!(
+            tourn.apply_op(Utc::now(), record(plyrs[0], 3)),
+            Err(TournamentError::TooManyWins)
+        );
+        let _ = tourn.apply_op(Utc::now(), record(plyrs[0], 2)).unwrap();
+        assert_eq!(
+            tourn.apply_op(Utc::now(), record(plyrs[1], 2)),
+            Err(TournamentError::TooManyWins)
+        );
+        let _ = tourn.apply_op(Utc::now(), record(plyrs[1], 1)).unwrap();
+        // Draws are not limited
+        let draw = JudgeOp::AdminRecordResult(r_id, RoundResult::Draw(2));
+        let _ = tourn
+            .apply_op(Utc::now(), TournOp::JudgeOp(admin_id.into(), draw))
+            .unwrap();
+
+        // Bo5 allows more wins, and an unrestricted structure allows any number
+        let bo5 = GeneralSetting::MatchStructure(MatchStructure::BestOf(5));
+        let _ = tourn
+            .apply_op(
+                Utc::now(),
+                TournOp::AdminOp(admin_id, UpdateTournSetting(bo5.into())),
+            )
+            .unwrap();
+        let _ = tourn.apply_op(Utc::now(), record(plyrs[0], 3)).unwrap();
+        let unrestricted = GeneralSetting::MatchStructure(MatchStructure::Unrestricted);
+        let _ = tourn
+            .apply_op(
+                Utc::now(),
+                TournOp::AdminOp(admin_id, UpdateTournSetting(unrestricted.into())),
+            )
+            .unwrap();
+        let _ = tourn.apply_op(Utc::now(), record(plyrs[0], 4)).unwrap();
+        let bo0 = GeneralSetting::MatchStructure(MatchStructure::BestOf(0));
+        assert_eq!(
+            tourn.apply_op(
+                Utc::now(),
+                TournOp::AdminOp(admin_id, UpdateTournSetting(bo0.into()))
+            ),
+            Err(TournamentError::InvalidMatchStructure)
+        );
+    }
+
+    #[test]
+    fn auto_certify_tests() {
+        let admin = spoof_account();
+        let admin_id: AdminId = admin.id.0.into();
+        let mut tourn = admin.create_tournament(get_seed());
+        let plyrs: Vec<PlayerId> = (0..2)
+            .map(|_| {
+                tourn
+                    .apply_op(Utc::now(), TournOp::RegisterPlayer(spoof_account(), None))
+                    .unwrap()
+                    .assume_register_player()
+            })
+            .collect();
+        tourn
+            .apply_op(Utc::now(), TournOp::AdminOp(admin_id, Start))
+            .unwrap()
+            .assume_nothing();
+        let start = Utc::now();
+        let r_id = tourn
+            .apply_op(
+                start,
+                TournOp::AdminOp(admin_id, CreateRound(plyrs.clone())),
+            )
+            .unwrap()
+            .assume_create_round();
+        let deadline = tourn.round_reg.get_round(&r_id).unwrap().deadline();
+        let minutes = |mins: i64| deadline + ChronoDuration::minutes(mins);
+        let certify = TournOp::JudgeOp(admin_id.into(), JudgeOp::AutoCertifyRound(r_id));
+        let record = |wins: u32| {
+            TournOp::PlayerOp(
+                plyrs[0],
+                PlayerOp::RecordResult(r_id, RoundResult::Wins(plyrs[0], wins)),
+            )
+        };
+        let _ = tourn.apply_op(start, record(2)).unwrap();
+
+        // Nothing is certified automatically until the setting is turned on
+        assert!(tourn.auto_certify_due(minutes(60)).is_empty());
+        assert_eq!(
+            tourn.apply_op(minutes(60), certify.clone()),
+            Err(TournamentError::AutoCertifyNotDue)
+        );
+        let wait = GeneralSetting::AutoCertify(Some(Duration::from_secs(600)));
+        let _ = tourn
+            .apply_op(
+                Utc::now(),
+                TournOp::AdminOp(admin_id, UpdateTournSetting(wait.into())),
+            )
+            .unwrap();
+
+        // The wait starts once the round's time runs out
+        assert!(tourn.auto_certify_due(minutes(9)).is_empty());
+        assert_eq!(tourn.auto_certify_due(minutes(10)), vec![r_id]);
+
+        // Changing the result after time is called starts the wait over
+        let _ = tourn.apply_op(minutes(5), record(1)).unwrap();
+        assert!(tourn.auto_certify_due(minutes(10)).is_empty());
+        assert_eq!(
+            tourn.apply_op(minutes(10), certify.clone()),
+            Err(TournamentError::AutoCertifyNotDue)
+        );
+        assert_eq!(tourn.auto_certify_due(minutes(15)), vec![r_id]);
+        let (_, status) = tourn
+            .apply_op(minutes(15), certify)
+            .unwrap()
+            .assume_confirm_result();
+        assert_eq!(status, RoundStatus::Certified);
+        assert!(tourn.auto_certify_due(minutes(60)).is_empty());
+    }
+
+    #[test]
+    fn tardiness_tests() {
+        let admin = spoof_account();
+        let admin_id: AdminId = admin.id.0.into();
+        let mut tourn = admin.create_tournament(get_seed());
+        let plyrs: Vec<PlayerId> = (0..4)
+            .map(|_| {
+                tourn
+                    .apply_op(Utc::now(), TournOp::RegisterPlayer(spoof_account(), None))
+                    .unwrap()
+                    .assume_register_player()
+            })
+            .collect();
+        tourn
+            .apply_op(Utc::now(), TournOp::AdminOp(admin_id, Start))
+            .unwrap()
+            .assume_nothing();
+        let start = Utc::now();
+        let rnds: Vec<_> = plyrs
+            .chunks(2)
+            .map(|pair| {
+                tourn
+                    .apply_op(
+                        start,
+                        TournOp::AdminOp(admin_id, CreateRound(pair.to_vec())),
+                    )
+                    .unwrap()
+                    .assume_create_round()
+            })
+            .collect();
+        let minutes = |mins: i64| start + ChronoDuration::minutes(mins);
+        let tardy =
+            |r_id, p_id| TournOp::JudgeOp(admin_id.into(), JudgeOp::ApplyTardiness(r_id, p_id));
+        let policy = |penalty| {
+            let policy = TardinessPolicy {
+                grace: Duration::from_secs(600),
+                penalty,
+            };
+            let setting = GeneralSetting::Tardiness(Some(policy));
+            TournOp::AdminOp(admin_id, UpdateTournSetting(setting.into()))
+        };
+
+        // No one is tardy until the tournament has a policy
+        assert!(tourn.tardy_players(minutes(60)).is_empty());
+        assert_eq!(
+            tourn.apply_op(minutes(60), tardy(rnds[0], plyrs[1])),
+            Err(TournamentError::PlayerNotTardy)
+        );
+        let _ = tourn
+            .apply_op(Utc::now(), policy(TardinessPenalty::GameLoss))
+            .unwrap();
+
+        // Players can only check in at the tables of their own rounds
+        for (r_id, p_id) in [(rnds[0], plyrs[0]), (rnds[1], plyrs[2])] {
+            let op = TournOp::PlayerOp(p_id, PlayerOp::CheckInAtTable(r_id));
+            let _ = tourn.apply_op(minutes(1), op).unwrap();
+        }
+        assert_eq!(
+            tourn.apply_op(
+                minutes(1),
+                TournOp::PlayerOp(plyrs[0], PlayerOp::CheckInAtTable(rnds[1]))
+            ),
+            Err(TournamentError::PlayerNotInRound)
+        );
+
+        // Players that have not checked in are tardy once the grace period passes
+        assert!(tourn.tardy_players(minutes(9)).is_empty());
+        let late = tourn.tardy_players(minutes(10));
+        assert_eq!(late.len(), 2);
+        assert!(late.contains(&(rnds[0], plyrs[1])));
+        assert!(late.contains(&(rnds[1], plyrs[3])));
+        assert_eq!(
+            tourn.apply_op(minutes(10), tardy(rnds[0], plyrs[0])),
+            Err(TournamentError::PlayerNotTardy)
+        );
+
+        // A game loss gives the opponent a game, and the penalty is only given once
+        let _ = tourn
+            .apply_op(minutes(10), tardy(rnds[0], plyrs[1]))
+            .unwrap();
+        let rnd = tourn.round_reg.get_round(&rnds[0]).unwrap();
+        assert_eq!(rnd.results.get(&plyrs[0]), Some(&1));
+        assert!(rnd.is_active());
+        assert_eq!(
+            tourn.apply_op(minutes(11), tardy(rnds[0], plyrs[1])),
+            Err(TournamentError::PlayerNotTardy)
+        );
+        assert_eq!(tourn.tardy_players(minutes(11)), vec![(rnds[1], plyrs[3])]);
+
+        // A match loss ends the round
+        let _ = tourn
+            .apply_op(Utc::now(), policy(TardinessPenalty::MatchLoss))
+            .unwrap();
+        let (_, status) = tourn
+            .apply_op(minutes(11), tardy(rnds[1], plyrs[3]))
+            .unwrap()
+            .assume_confirm_result();
+        assert_eq!(status, RoundStatus::Certified);
+        let rnd = tourn.round_reg.get_round(&rnds[1]).unwrap();
+        assert_eq!(rnd.winner, Some(plyrs[2]));
+        assert_eq!(rnd.results.get(&plyrs[2]), Some(&2));
+        assert!(tourn.tardy_players(minutes(12)).is_empty());
+    }
+}
@@ -1,9 +1,14 @@
 #[cfg(test)]
 mod tests {
-    use chrono::Utc;
+    use chrono::{Duration, Utc};
     use squire_lib::{
-        identifiers::AdminId,
+        accounts::SharingPermissions,
+        collation::NameCollator,
+        error::TournamentError,
+        identifiers::{AdminId, PlayerId},
         operations::{AdminOp::*, JudgeOp::*, TournOp},
+        players::{Player, PlayerStatus},
+        settings::{GeneralSetting, PublicNameStyle, RegistrationWindow},
     };
     use squire_tests::{get_seed, spoof_account};
 
@@ -78,6 +83,67 @@ mod tests {
             .is_err());
     }
 
+    #[test]
+    fn registration_window_tests() {
+        let admin = spoof_account();
+        let admin_id: AdminId = admin.id.0.into();
+        let mut tourn = admin.create_tournament(get_seed());
+        let now = Utc::now();
+        let window = RegistrationWindow {
+            opens: Some(now + Duration::hours(1)),
+            closes: Some(now + Duration::hours(2)),
+        };
+        let setting = GeneralSetting::RegistrationWindow(window).into();
+        tourn
+            .apply_op(now, TournOp::AdminOp(admin_id, UpdateTournSetting(setting)))
+            .unwrap()
+            .assume_nothing();
+        assert_eq!(tourn.settings.registration.next_change(now), window.opens);
+
+        // Players can only register inside the window
+        assert_eq!(
+            tourn.apply_op(now, TournOp::RegisterPlayer(spoof_account(), None)),
+            Err(TournamentError::RegClosed)
+        );
+        let during = now + Duration::minutes(90);
+        assert!(tourn.is_reg_open(during));
+        assert!(tourn
+            .apply_op(during, TournOp::RegisterPlayer(spoof_account(), None))
+            .is_ok());
+        let after = now + Duration::hours(3);
+        assert_eq!(
+            tourn.apply_op(after, TournOp::RegisterPlayer(spoof_account(), None)),
+            Err(TournamentError::RegClosed)
+        );
+        assert_eq!(tourn.settings.registration.next_change(after), None);
+
+        // Officials can still register players
+        assert!(tourn
+            .apply_op(
+                after,
+                TournOp::JudgeOp(admin_id.into(), AdminRegisterPlayer(spoof_account(), None))
+            )
+            .is_ok());
+
+        // Closing registration by hand overrides the window
+        tourn
+            .apply_op(during, TournOp::AdminOp(admin_id, UpdateReg(false)))
+            .unwrap()
+            .assume_nothing();
+        assert!(!tourn.is_reg_open(during));
+
+        // Registration can not close before it opens
+        let backwards = RegistrationWindow {
+            opens: window.closes,
+            closes: window.opens,
+        };
+        let setting = GeneralSetting::RegistrationWindow(backwards).into();
+        assert_eq!(
+            tourn.apply_op(now, TournOp::AdminOp(admin_id, UpdateTournSetting(setting))),
+            Err(TournamentError::InvalidRegistrationWindow)
+        );
+    }
+
     #[test]
     fn admin_reg_tests() {
         let admin = spoof_account();
@@ -178,4 +244,521 @@ mod tests {
             )
             .is_err());
     }
+
+    #[test]
+    fn alternate_reg_tests() {
+        let admin = spoof_account();
+        let admin_id: AdminId = admin.id.0.into();
+        let mut tourn = admin.create_tournament(get_seed());
+        let ids: Vec<PlayerId> = (0..3)
+            .map(|_| {
+                tourn
+                    .apply_op(Utc::now(), TournOp::RegisterPlayer(spoof_account(), None))
+                    .unwrap()
+                    .assume_register_player()
+            })
+            .collect();
+        let (first, second, alt) = (ids[0], ids[1], ids[2]);
+        assert!(tourn
+            .apply_op(Utc::now(), TournOp::AdminOp(admin_id, MakeAlternate(alt)))
+            .is_ok());
+        assert!(tourn.player_reg.is_alternate(&alt));
+        assert_eq!(tourn.player_reg.get_alternates(), &[alt]);
+        assert_eq!(tourn.player_reg.active_player_count(), 2);
+        // Only alternates can be promoted or substituted in
+        assert!(tourn
+            .apply_op(
+                Utc::now(),
+                TournOp::AdminOp(admin_id, PromoteAlternate(first))
+            )
+            .is_err());
+        assert!(tourn
+            .apply_op(Utc::now(), TournOp::AdminOp(admin_id, Start))
+            .is_ok());
+        assert!(tourn
+            .apply_op(
+                Utc::now(),
+                TournOp::AdminOp(admin_id, SubstituteAlternate(first, second))
+            )
+            .is_err());
+        // An alternate replaces a player in their first round
+        assert!(tourn
+            .apply_op(
+                Utc::now(),
+                TournOp::AdminOp(admin_id, CreateRound(vec![first, second]))
+            )
+            .is_ok());
+        assert!(tourn
+            .apply_op(
+                Utc::now(),
+                TournOp::AdminOp(admin_id, SubstituteAlternate(alt, second))
+            )
+            .is_ok());
+        assert!(!tourn.player_reg.is_alternate(&alt));
+        assert!(tourn.player_reg.get_alternates().is_empty());
+        assert_eq!(
+            tourn.player_reg.get_player_status(&second),
+            Ok(PlayerStatus::Dropped)
+        );
+        // The alternate takes the replaced player's seat
+        let rnd = tourn.round_reg.get_player_active_round(&alt).unwrap();
+        assert_eq!(rnd.players, vec![first, alt]);
+        assert!(tourn.round_reg.opponents[&first].contains(&alt));
+        assert!(!tourn.round_reg.opponents[&first].contains(&second));
+    }
+
+    #[test]
+    fn alternates_are_promoted_before_pairing() {
+        let admin = spoof_account();
+        let admin_id: AdminId = admin.id.0.into();
+        let mut tourn = admin.create_tournament(get_seed());
+        let ids: Vec<PlayerId> = (0..4)
+            .map(|_| {
+                tourn
+                    .apply_op(Utc::now(), TournOp::RegisterPlayer(spoof_account(), None))
+                    .unwrap()
+                    .assume_register_player()
+            })
+            .collect();
+        for alt in &ids[2..] {
+            assert!(tourn
+                .apply_op(Utc::now(), TournOp::AdminOp(admin_id, MakeAlternate(*alt)))
+                .is_ok());
+        }
+        assert!(tourn
+            .apply_op(Utc::now(), TournOp::AdminOp(admin_id, Start))
+            .is_ok());
+        // Alternates can fill the field until the first round is paired
+        assert!(tourn
+            .apply_op(
+                Utc::now(),
+                TournOp::AdminOp(admin_id, PromoteAlternate(ids[2]))
+            )
+            .is_ok());
+        assert_eq!(tourn.player_reg.get_alternates(), &ids[3..]);
+        assert!(tourn
+            .apply_op(
+                Utc::now(),
+                TournOp::AdminOp(admin_id, CreateRound(vec![ids[0], ids[1]]))
+            )
+            .is_ok());
+        assert_eq!(
+            tourn.apply_op(
+                Utc::now(),
+                TournOp::AdminOp(admin_id, PromoteAlternate(ids[3]))
+            ),
+            Err(TournamentError::AlternatesClosed)
+        );
+        assert!(tourn.player_reg.is_alternate(&ids[3]));
+    }
+
+    #[test]
+    fn waitlist_reg_tests() {
+        let admin = spoof_account();
+        let admin_id: AdminId = admin.id.0.into();
+        let mut tourn = admin.create_tournament(get_seed());
+        assert!(tourn
+            .apply_op(
+                Utc::now(),
+                TournOp::AdminOp(
+                    admin_id,
+                    UpdateTournSetting(GeneralSetting::PlayerCap(2).into())
+                )
+            )
+            .is_ok());
+        let ids: Vec<PlayerId> = (0..4)
+            .map(|_| {
+                tourn
+                    .apply_op(Utc::now(), TournOp::RegisterPlayer(spoof_account(), None))
+                    .unwrap()
+                    .assume_register_player()
+            })
+            .collect();
+        // Players beyond the cap are waitlisted in the order they registered
+        assert_eq!(tourn.player_reg.active_player_count(), 2);
+        assert_eq!(tourn.player_reg.get_waitlist(), &ids[2..]);
+        assert_eq!(
+            tourn.player_reg.get_player_status(&ids[2]),
+            Ok(PlayerStatus::Waitlisted)
+        );
+        // Dropping before the tournament starts promotes the front of the waitlist
+        assert!(tourn
+            .apply_op(
+                Utc::now(),
+                TournOp::AdminOp(admin_id, AdminDropPlayer(ids[0]))
+            )
+            .is_ok());
+        assert_eq!(
+            tourn.player_reg.get_player_status(&ids[2]),
+            Ok(PlayerStatus::Registered)
+        );
+        assert_eq!(tourn.player_reg.get_waitlist(), &ids[3..]);
+        // Admins can promote past the cap
+        assert!(tourn
+            .apply_op(
+                Utc::now(),
+                TournOp::AdminOp(admin_id, PromoteFromWaitlist(ids[1]))
+            )
+            .is_err());
+        assert!(tourn
+            .apply_op(
+                Utc::now(),
+                TournOp::AdminOp(admin_id, PromoteFromWaitlist(ids[3]))
+            )
+            .is_ok());
+        assert_eq!(tourn.player_reg.active_player_count(), 3);
+        assert!(tourn.player_reg.get_waitlist().is_empty());
+    }
+
+    #[test]
+    fn waitlist_rereg_tests() {
+        let admin = spoof_account();
+        let admin_id: AdminId = admin.id.0.into();
+        let mut tourn = admin.create_tournament(get_seed());
+        assert!(tourn
+            .apply_op(
+                Utc::now(),
+                TournOp::AdminOp(
+                    admin_id,
+                    UpdateTournSetting(GeneralSetting::PlayerCap(1).into())
+                )
+            )
+            .is_ok());
+        let accounts: Vec<_> = (0..3).map(|_| spoof_account()).collect();
+        let ids: Vec<PlayerId> = accounts
+            .iter()
+            .map(|acc| {
+                tourn
+                    .apply_op(Utc::now(), TournOp::RegisterPlayer(acc.clone(), None))
+                    .unwrap()
+                    .assume_register_player()
+            })
+            .collect();
+        assert_eq!(tourn.player_reg.get_waitlist(), &ids[1..]);
+        // Registering again does not move a waitlisted player to the back of the waitlist
+        assert_eq!(
+            tourn
+                .apply_op(
+                    Utc::now(),
+                    TournOp::RegisterPlayer(accounts[1].clone(), None)
+                )
+                .unwrap()
+                .assume_register_player(),
+            ids[1]
+        );
+        assert_eq!(tourn.player_reg.get_waitlist(), &ids[1..]);
+        assert_eq!(
+            tourn.player_reg.get_player_status(&ids[1]),
+            Ok(PlayerStatus::Waitlisted)
+        );
+    }
+
+    #[test]
+    fn bulk_reg_tests() {
+        let admin = spoof_account();
+        let admin_id: AdminId = admin.id.0.into();
+        let mut tourn = admin.create_tournament(get_seed());
+        let players: Vec<_> = (0..200).map(|_| (spoof_account(), None)).collect();
+        let ids = tourn
+            .apply_op(
+                Utc::now(),
+                TournOp::JudgeOp(admin_id.into(), AdminRegisterPlayers(players.clone())),
+            )
+            .unwrap()
+            .assume_register_players();
+        assert_eq!(ids.len(), 200);
+        assert_eq!(tourn.player_reg.active_player_count(), 200);
+        for ((account, _), id) in players.iter().zip(ids) {
+            assert_eq!(tourn.player_reg.get_player_id(&account.user_name), Ok(id));
+        }
+        // A single bad entry rejects the whole list
+        let mut dup = spoof_account();
+        dup.user_name = players[0].0.user_name.clone();
+        let players = vec![(spoof_account(), None), (dup, None)];
+        assert!(tourn
+            .apply_op(
+                Utc::now(),
+                TournOp::JudgeOp(admin_id.into(), AdminRegisterPlayers(players))
+            )
+            .is_err());
+        assert_eq!(tourn.player_reg.active_player_count(), 200);
+    }
+
+    #[test]
+    fn link_guest_tests() {
+        let admin = spoof_account();
+        let admin_id: AdminId = admin.id.0.into();
+        let mut tourn = admin.create_tournament(get_seed());
+        let guest = tourn
+            .apply_op(
+                Utc::now(),
+                TournOp::JudgeOp(admin_id.into(), RegisterGuest("Guest".to_owned())),
+            )
+            .unwrap()
+            .assume_register_player();
+        let opp = tourn
+            .apply_op(Utc::now(), TournOp::RegisterPlayer(spoof_account(), None))
+            .unwrap()
+            .assume_register_player();
+        assert!(tourn
+            .apply_op(Utc::now(), TournOp::AdminOp(admin_id, Start))
+            .is_ok());
+        let r_id = tourn
+            .apply_op(
+                Utc::now(),
+                TournOp::AdminOp(admin_id, CreateRound(vec![guest, opp])),
+            )
+            .unwrap()
+            .assume_create_round();
+
+        // The guest keeps their name and round, but takes the account's id
+        let account = spoof_account();
+        let new: PlayerId = account.id.0.into();
+        assert!(tourn
+            .apply_op(
+                Utc::now(),
+                TournOp::AdminOp(admin_id, LinkGuestAccount(guest, account.clone()))
+            )
+            .is_ok());
+        assert!(tourn.player_reg.get_player(&guest).is_err());
+        assert_eq!(tourn.player_reg.get_player_id("Guest"), Ok(new));
+        assert_eq!(tourn.player_reg.get_player(&new).unwrap().id, new);
+        assert!(tourn
+            .round_reg
+            .get_round(&r_id)
+            .unwrap()
+            .players
+            .contains(&new));
+        assert!(tourn.round_reg.opponents[&opp].contains(&new));
+        assert!(tourn.round_reg.opponents.contains_key(&new));
+        assert!(tourn.get_standings().scores.iter().any(|(p, _)| *p == new));
+
+        // The old id can't be linked again and the account can only be linked once
+        assert!(tourn
+            .apply_op(
+                Utc::now(),
+                TournOp::AdminOp(admin_id, LinkGuestAccount(guest, spoof_account()))
+            )
+            .is_err());
+        assert!(tourn
+            .apply_op(
+                Utc::now(),
+                TournOp::AdminOp(admin_id, LinkGuestAccount(opp, account))
+            )
+            .is_err());
+
+        // Players that registered with an account are not guests, so they can't be re-keyed
+        assert_eq!(
+            tourn.apply_op(
+                Utc::now(),
+                TournOp::AdminOp(admin_id, LinkGuestAccount(opp, spoof_account()))
+            ),
+            Err(TournamentError::PlayerNotFound)
+        );
+        assert!(tourn.player_reg.get_player(&opp).is_ok());
+        // Nor can a guest once they have been linked
+        assert_eq!(
+            tourn.apply_op(
+                Utc::now(),
+                TournOp::AdminOp(admin_id, LinkGuestAccount(new, spoof_account()))
+            ),
+            Err(TournamentError::PlayerNotFound)
+        );
+    }
+
+    #[test]
+    fn merge_players_tests() {
+        let admin = spoof_account();
+        let admin_id: AdminId = admin.id.0.into();
+        let mut tourn = admin.create_tournament(get_seed());
+        let mut register_guest = |name: &str| {
+            tourn
+                .apply_op(
+                    Utc::now(),
+                    TournOp::JudgeOp(admin_id.into(), RegisterGuest(name.to_owned())),
+                )
+                .unwrap()
+                .assume_register_player()
+        };
+        let dup = register_guest("Dup");
+        let canon = register_guest("Canon");
+        let opp = register_guest("Opp");
+        let other = register_guest("Other");
+        assert!(tourn
+            .apply_op(Utc::now(), TournOp::AdminOp(admin_id, Start))
+            .is_ok());
+        let r_id = tourn
+            .apply_op(
+                Utc::now(),
+                TournOp::AdminOp(admin_id, CreateRound(vec![dup, opp])),
+            )
+            .unwrap()
+            .assume_create_round();
+
+        // The duplicate's round and opponents are moved to the canonical player
+        assert!(tourn
+            .apply_op(
+                Utc::now(),
+                TournOp::AdminOp(admin_id, MergePlayers(dup, canon))
+            )
+            .is_ok());
+        assert!(tourn.player_reg.get_player(&dup).is_err());
+        assert!(tourn.player_reg.get_player_id("Dup").is_err());
+        assert_eq!(
+            tourn.round_reg.get_round(&r_id).unwrap().players,
+            vec![canon, opp]
+        );
+        assert!(tourn.round_reg.opponents[&opp].contains(&canon));
+        assert!(tourn.round_reg.opponents[&canon].contains(&opp));
+        assert!(!tourn.round_reg.opponents.contains_key(&dup));
+
+        // Players that share a round and players merged with themselves can not be merged
+        assert_eq!(
+            tourn.apply_op(
+                Utc::now(),
+                TournOp::AdminOp(admin_id, MergePlayers(opp, canon))
+            ),
+            Err(TournamentError::InvalidMerge)
+        );
+        assert_eq!(
+            tourn.apply_op(
+                Utc::now(),
+                TournOp::AdminOp(admin_id, MergePlayers(other, other))
+            ),
+            Err(TournamentError::InvalidMerge)
+        );
+        assert_eq!(
+            tourn.apply_op(
+                Utc::now(),
+                TournOp::AdminOp(admin_id, MergePlayers(dup, other))
+            ),
+            Err(TournamentError::PlayerNotFound)
+        );
+    }
+
+    #[test]
+    fn merge_after_pods_tests() {
+        let admin = spoof_account();
+        let admin_id: AdminId = admin.id.0.into();
+        let mut tourn = admin.create_tournament(get_seed());
+        let plyrs: Vec<PlayerId> = (0..8)
+            .map(|i| {
+                tourn
+                    .apply_op(
+                        Utc::now(),
+                        TournOp::JudgeOp(admin_id.into(), RegisterGuest(format!("Guest {i}"))),
+                    )
+                    .unwrap()
+                    .assume_register_player()
+            })
+            .collect();
+        assert!(tourn
+            .apply_op(Utc::now(), TournOp::AdminOp(admin_id, CreateDraftPods(4)))
+            .is_ok());
+        let pods = &tourn.pairing_sys.pods;
+        let canon = plyrs[0];
+        let dup = *plyrs
+            .iter()
+            .find(|p| pods.pod_of(p) != pods.pod_of(&canon))
+            .unwrap();
+        let canon_pod = pods.pod_of(&canon);
+
+        // The canonical player keeps their seat and the duplicate's seat is removed
+        assert!(tourn
+            .apply_op(
+                Utc::now(),
+                TournOp::AdminOp(admin_id, MergePlayers(dup, canon))
+            )
+            .is_ok());
+        let pods = &tourn.pairing_sys.pods;
+        assert_eq!(pods.pod_of(&canon), canon_pod);
+        assert_eq!(pods.pod_of(&dup), None);
+        let seats: Vec<_> = pods.pods.iter().flatten().collect();
+        assert_eq!(seats.len(), 7);
+        assert_eq!(seats.iter().filter(|p| ***p == canon).count(), 1);
+    }
+
+    #[test]
+    fn players_by_name_tests() {
+        let admin = spoof_account();
+        let admin_id: AdminId = admin.id.0.into();
+        let mut tourn = admin.create_tournament(get_seed());
+        for name in ["carol", "Bob", "alice", "Émile", "Felix", "Edgar"] {
+            tourn
+                .apply_op(
+                    Utc::now(),
+                    TournOp::JudgeOp(admin_id.into(), RegisterGuest(name.to_owned())),
+                )
+                .unwrap();
+        }
+        let names: Vec<&str> = tourn
+            .player_reg
+            .get_players_by_name(&NameCollator::new())
+            .into_iter()
+            .map(|p| p.name.as_str())
+            .collect();
+        // Without ICU, only case is ignored, so accented names sort after every ASCII name
+        #[cfg(feature = "collation")]
+        let expected = ["alice", "Bob", "carol", "Edgar", "Émile", "Felix"];
+        #[cfg(not(feature = "collation"))]
+        let expected = ["alice", "Bob", "carol", "Edgar", "Felix", "Émile"];
+        assert_eq!(names, expected);
+    }
+
+    #[test]
+    fn redaction_respects_sharing_permissions() {
+        let admin = spoof_account();
+        let mut tourn = admin.create_tournament(get_seed());
+        let mut private = spoof_account();
+        private.permissions = SharingPermissions::OnlyDeckName;
+        let private_id = tourn
+            .apply_op(Utc::now(), TournOp::RegisterPlayer(private.clone(), None))
+            .unwrap()
+            .assume_register_player();
+        let public = spoof_account();
+        let public_id = tourn
+            .apply_op(Utc::now(), TournOp::RegisterPlayer(public.clone(), None))
+            .unwrap()
+            .assume_register_player();
+
+        let plyr = tourn.player_reg.players.get_mut(&private_id).unwrap();
+        plyr.deck_ordering.push("Burn".into());
+        _ = plyr.deck_hashes.insert("Burn".into(), "abcd1234".into());
+
+        tourn.redact();
+        let plyr = tourn.player_reg.get_player(&private_id).unwrap();
+        assert_eq!(plyr.name, "Anonymous 1");
+        assert!(plyr.game_name.is_none());
+        // The deck's name is shared, but not its list or the hash of the list
+        assert_eq!(plyr.deck_ordering, vec!["Burn".to_owned()]);
+        assert!(plyr.deck_hashes.is_empty());
+        assert!(tourn.player_reg.get_by_name(&private.user_name).is_err());
+        assert_eq!(
+            tourn.player_reg.get_by_name(&plyr.name).unwrap().id,
+            private_id
+        );
+        let plyr = tourn.player_reg.get_player(&public_id).unwrap();
+        assert_eq!(plyr.name, public.user_name);
+    }
+
+    #[test]
+    fn public_names_follow_style() {
+        let mut plyr = Player::new("Jane Q. Doe".into());
+        assert_eq!(plyr.public_name(PublicNameStyle::FullName), "Jane Q. Doe");
+        assert_eq!(
+            plyr.public_name(PublicNameStyle::FirstNameInitial),
+            "Jane D."
+        );
+        assert_eq!(
+            plyr.public_name(PublicNameStyle::Alias),
+            plyr.anonymous_name()
+        );
+        plyr.name = "Cher".into();
+        assert_eq!(plyr.public_name(PublicNameStyle::FirstNameInitial), "Cher");
+        plyr.sharing = SharingPermissions::Nothing;
+        assert_eq!(
+            plyr.public_name(PublicNameStyle::FullName),
+            plyr.anonymous_name()
+        );
+    }
 }
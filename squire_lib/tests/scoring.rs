@@ -0,0 +1,360 @@
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+    use squire_lib::{
+        error::TournamentError,
+        identifiers::PlayerId,
+        r64,
+        rounds::{RoundContext, RoundRegistry, RoundResult},
+        scoring::{ScoringSystem, StageScoring, StandardScore, Standings},
+        series::{Series, SeriesScoring},
+        settings::{CommonScoringSetting, MultiplayerScoringSetting, StandardScoringSetting},
+        tournament::TournamentPreset,
+    };
+    use squire_tests::{get_seed, spoof_account, spoof_data};
+
+    fn play_round(rnds: &mut RoundRegistry, winner: PlayerId, loser: PlayerId) {
+        let id = rnds.create_round(Utc::now(), vec![winner, loser], RoundContext::Contextless);
+        let rnd = rnds.rounds.get_mut(&id).unwrap();
+        rnd.record_result(RoundResult::Wins(winner, 2)).unwrap();
+        let _ = rnd.confirm_round(winner).unwrap();
+        let _ = rnd.confirm_round(loser).unwrap();
+        assert!(rnd.is_certified());
+    }
+
+    fn get_score(standings: &Standings<StandardScore>, id: PlayerId) -> StandardScore {
+        standings
+            .scores
+            .iter()
+            .find_map(|(p, s)| (*p == id).then(|| s.clone()))
+            .unwrap()
+    }
+
+    #[test]
+    fn dropped_opponents_in_tiebreakers() {
+        let (_, mut plyrs, mut rnds, mut scoring) = spoof_data(3);
+        let ids: Vec<PlayerId> = plyrs.players.keys().copied().collect();
+        let (first, second, third) = (ids[0], ids[1], ids[2]);
+        play_round(&mut rnds, first, second);
+        play_round(&mut rnds, second, third);
+
+        // The second player went 1-1, so the first player's opp mwp is 50%
+        let standings = scoring.get_standings(&plyrs, &rnds);
+        assert_eq!(get_score(&standings, first).opp_mwp, r64::new(1, 2));
+
+        // By default, dropping does not change the tiebreakers of opponents
+        plyrs.drop_player(&second).unwrap();
+        let standings = scoring.get_standings(&plyrs, &rnds);
+        assert_eq!(standings.scores.len(), 2);
+        assert_eq!(get_score(&standings, first).opp_mwp, r64::new(1, 2));
+
+        // Once excluded, the first player no longer has any opponents
+        scoring
+            .update_setting(StandardScoringSetting::IncludeDroppedOpponents(false))
+            .unwrap();
+        let standings = scoring.get_standings(&plyrs, &rnds);
+        assert_eq!(get_score(&standings, first).opp_mwp, r64::default());
+        assert_eq!(get_score(&standings, first).opp_gwp, r64::default());
+        assert_eq!(get_score(&standings, third).opp_mwp, r64::default());
+    }
+
+    #[test]
+    fn score_explanations() {
+        let (_, mut plyrs, mut rnds, scoring) = spoof_data(3);
+        let ids: Vec<PlayerId> = plyrs.players.keys().copied().collect();
+        let (first, second, third) = (ids[0], ids[1], ids[2]);
+        play_round(&mut rnds, first, second);
+        play_round(&mut rnds, second, third);
+
+        let explanation = scoring.explain_score(&plyrs, &rnds, &second).unwrap();
+        assert_eq!(explanation.place, Some(2));
+        assert_eq!(
+            explanation.score,
+            get_score(&scoring.get_standings(&plyrs, &rnds), second)
+        );
+        assert_eq!(explanation.rounds.len(), 2);
+        assert!(explanation.rounds.iter().all(|r| r.counted));
+        let points: r64 = explanation.rounds.iter().map(|r| r.match_points).sum();
+        assert_eq!(points, explanation.score.match_points);
+        assert_eq!(explanation.opponents.len(), 2);
+        let opp = explanation
+            .opponents
+            .iter()
+            .find(|o| o.id == first)
+            .unwrap();
+        assert!(opp.counted);
+        assert_eq!(opp.matches, 1);
+        assert_eq!(opp.match_points, r64::from_integer(3));
+
+        // Dropped players are explained, but no longer have a place
+        plyrs.drop_player(&first).unwrap();
+        let explanation = scoring.explain_score(&plyrs, &rnds, &first).unwrap();
+        assert_eq!(explanation.place, None);
+        assert_eq!(
+            scoring.explain_score(&plyrs, &rnds, &spoof_account().id.0.into()),
+            Err(TournamentError::PlayerNotFound)
+        );
+    }
+
+    #[test]
+    fn multiplayer_placements() {
+        let (_, plyrs, mut rnds, _) = spoof_data(4);
+        let mut scoring = ScoringSystem::new(TournamentPreset::Multiplayer);
+        let ids: Vec<PlayerId> = plyrs.players.keys().copied().collect();
+        let id = rnds.create_round(Utc::now(), ids.clone(), RoundContext::Contextless);
+        let rnd = rnds.rounds.get_mut(&id).unwrap();
+        assert_eq!(
+            rnd.record_result(RoundResult::Placement(ids[0], 5)),
+            Err(TournamentError::InvalidPlacement)
+        );
+        assert_eq!(
+            rnd.record_result(RoundResult::Placement(spoof_account().id.0.into(), 1)),
+            Err(TournamentError::PlayerNotInRound)
+        );
+        for (place, p) in ids.iter().enumerate() {
+            rnd.record_result(RoundResult::Placement(*p, place as u32 + 1))
+                .unwrap();
+        }
+        assert_eq!(rnd.winner, Some(ids[0]));
+        for p in ids.iter() {
+            let _ = rnd.confirm_round(*p).unwrap();
+        }
+
+        // Players are placed by their placement points
+        let standings = scoring.get_standings(&plyrs, &rnds);
+        for (place, p) in ids.iter().enumerate() {
+            let explanation = scoring.explain_score(&plyrs, &rnds, p).unwrap();
+            assert_eq!(explanation.place, Some(place + 1));
+        }
+        let points: Vec<_> = ids
+            .iter()
+            .map(|p| get_score(&standings, *p).match_points)
+            .collect();
+        assert_eq!(points, [5, 3, 2, 1].map(r64::from_integer));
+        assert_eq!(get_score(&standings, ids[1]).mwp, r64::new(3, 5));
+        assert_eq!(get_score(&standings, ids[0]).opp_mwp, r64::new(6, 15));
+
+        // A round without placements or a winner is a draw
+        let id = rnds.create_round(Utc::now(), ids.clone(), RoundContext::Contextless);
+        let rnd = rnds.rounds.get_mut(&id).unwrap();
+        rnd.record_result(RoundResult::Draw(1)).unwrap();
+        for p in ids.iter() {
+            let _ = rnd.confirm_round(*p).unwrap();
+        }
+        let explanation = scoring.explain_score(&plyrs, &rnds, &ids[3]).unwrap();
+        assert_eq!(explanation.place, Some(4));
+        assert_eq!(explanation.rounds.len(), 2);
+        assert_eq!(explanation.score.match_points, r64::from_integer(2));
+
+        // Settings are checked against the active scoring style
+        let _ = scoring
+            .style
+            .update(MultiplayerScoringSetting::FourthPlacePoints(r64::default()).into())
+            .unwrap();
+        let standings = scoring.get_standings(&plyrs, &rnds);
+        assert_eq!(
+            get_score(&standings, ids[3]).match_points,
+            r64::from_integer(1)
+        );
+        assert_eq!(
+            scoring
+                .style
+                .update(StandardScoringSetting::ByePoints(r64::default()).into()),
+            Err(TournamentError::IncompatibleScoringSystem)
+        );
+        assert_eq!(
+            scoring
+                .style
+                .update(MultiplayerScoringSetting::FirstPlacePoints(r64::default()).into()),
+            Err(TournamentError::InvalidPointValue)
+        );
+    }
+
+    #[test]
+    fn group_standings() {
+        let (_, plyrs, mut rnds, scoring) = spoof_data(4);
+        let ids: Vec<PlayerId> = plyrs.players.keys().copied().collect();
+        play_round(&mut rnds, ids[0], ids[1]);
+        play_round(&mut rnds, ids[3], ids[2]);
+
+        let groups = vec![vec![ids[0], ids[1]], vec![ids[2], ids[3]]];
+        let standings = scoring.get_standings(&plyrs, &rnds).into_groups(&groups, 1);
+        assert_eq!(standings.len(), 2);
+        assert_eq!(standings[0].standings.scores.len(), 2);
+        assert_eq!(standings[0].qualified().collect::<Vec<_>>(), vec![&ids[0]]);
+        assert!(standings[1].qualifies(&ids[3]));
+        assert!(!standings[1].qualifies(&ids[2]));
+        assert!(!standings[1].qualifies(&ids[0]));
+    }
+
+    #[test]
+    fn standings_audit() {
+        let (_, mut plyrs, mut rnds, scoring) = spoof_data(4);
+        let ids: Vec<PlayerId> = plyrs.players.keys().copied().collect();
+        play_round(&mut rnds, ids[0], ids[1]);
+        let cached = scoring.get_standings(&plyrs, &rnds);
+        assert!(cached
+            .diff(&scoring.get_standings(&plyrs, &rnds))
+            .is_empty());
+
+        // New results change the scores of the players in the round and their past opponents
+        play_round(&mut rnds, ids[1], ids[2]);
+        let mismatches = cached.diff(&scoring.get_standings(&plyrs, &rnds));
+        assert_eq!(mismatches.len(), 3);
+        assert!(mismatches.iter().all(|m| m.player != ids[3]));
+
+        // Dropped players are no longer in the standings
+        plyrs.drop_player(&ids[3]).unwrap();
+        let cached = scoring.get_standings(&plyrs, &rnds);
+        plyrs
+            .register_player_with_name(spoof_account(), None)
+            .unwrap();
+        let mismatches = scoring.get_standings(&plyrs, &rnds).diff(&cached);
+        assert_eq!(mismatches.len(), 1);
+        assert!(mismatches[0].computed.is_none());
+    }
+
+    #[test]
+    fn point_values_apply_retroactively() {
+        let (_, plyrs, mut rnds, mut scoring) = spoof_data(2);
+        let ids: Vec<PlayerId> = plyrs.players.keys().copied().collect();
+        play_round(&mut rnds, ids[0], ids[1]);
+        let standings = scoring.get_standings(&plyrs, &rnds);
+        assert_eq!(
+            get_score(&standings, ids[0]).match_points,
+            r64::from_integer(3)
+        );
+        assert_eq!(get_score(&standings, ids[1]).match_points, r64::default());
+
+        // Changing the point values updates the scores of rounds that have already been played
+        scoring
+            .update_setting(StandardScoringSetting::MatchWinPoints(r64::from_integer(2)))
+            .unwrap();
+        scoring
+            .update_setting(StandardScoringSetting::MatchLossPoints(r64::from_integer(
+                1,
+            )))
+            .unwrap();
+        let standings = scoring.get_standings(&plyrs, &rnds);
+        assert_eq!(
+            get_score(&standings, ids[0]).match_points,
+            r64::from_integer(2)
+        );
+        assert_eq!(get_score(&standings, ids[0]).mwp, r64::from_integer(1));
+        assert_eq!(
+            get_score(&standings, ids[1]).match_points,
+            r64::from_integer(1)
+        );
+
+        // Win points are used as a denominator, so they must be positive
+        assert_eq!(
+            scoring.update_setting(StandardScoringSetting::MatchWinPoints(r64::default())),
+            Err(TournamentError::InvalidPointValue)
+        );
+        assert_eq!(
+            scoring.update_setting(StandardScoringSetting::ByePoints(r64::from_integer(-1))),
+            Err(TournamentError::InvalidPointValue)
+        );
+        assert_eq!(scoring.settings().match_win_points, r64::from_integer(2));
+    }
+
+    fn set_stage_scoring(scoring: &mut ScoringSystem, stage_scoring: StageScoring) {
+        scoring
+            .update_setting(CommonScoringSetting::StageScoring(stage_scoring).into())
+            .unwrap();
+    }
+
+    #[test]
+    fn stage_scoring() {
+        let (_, plyrs, mut rnds, _) = spoof_data(4);
+        let mut scoring = ScoringSystem::new(TournamentPreset::Swiss);
+        let ids: Vec<PlayerId> = plyrs.players.keys().copied().collect();
+        play_round(&mut rnds, ids[0], ids[1]);
+        play_round(&mut rnds, ids[2], ids[3]);
+        let seeding = vec![ids[2], ids[0], ids[3], ids[1]];
+        scoring.start_stage(&rnds, seeding.clone());
+
+        // By default, scores carry over into the next stage
+        let standings = scoring.get_standings(&plyrs, &rnds);
+        assert_eq!(
+            get_score(&standings, ids[0]).match_points,
+            r64::from_integer(3)
+        );
+
+        // Resetting ignores every round from the earlier stage
+        set_stage_scoring(&mut scoring, StageScoring::Reset);
+        let standings = scoring.get_standings(&plyrs, &rnds);
+        assert!(standings
+            .scores
+            .iter()
+            .all(|(_, s)| s.match_points == r64::default()));
+        let explanation = scoring.explain_score(&plyrs, &rnds, &ids[0]).unwrap();
+        assert!(explanation.rounds.iter().all(|r| !r.counted));
+
+        // Seeding also ignores the earlier stage, but ties are broken by the seeding
+        set_stage_scoring(&mut scoring, StageScoring::Seeding);
+        let order: Vec<_> = scoring
+            .get_standings(&plyrs, &rnds)
+            .scores
+            .into_iter()
+            .rev()
+            .map(|(p, _)| p)
+            .collect();
+        assert_eq!(order, seeding);
+
+        // Results in the new stage take priority over the seeding
+        play_round(&mut rnds, ids[1], ids[2]);
+        let standings = scoring.get_standings(&plyrs, &rnds);
+        assert_eq!(standings.scores.last().unwrap().0, ids[1]);
+        assert_eq!(
+            get_score(&standings, ids[1]).match_points,
+            r64::from_integer(3)
+        );
+    }
+
+    #[test]
+    fn series_standings() {
+        let (first, second) = (spoof_account(), spoof_account());
+        let first_id: PlayerId = first.id.0.into();
+        let second_id: PlayerId = second.id.0.into();
+        let mut series = Series::new(first.id, "Weekly".into(), SeriesScoring::TotalPoints);
+        let mut tourns = Vec::new();
+        for (winner, loser) in [
+            (first_id, second_id),
+            (first_id, second_id),
+            (second_id, first_id),
+        ] {
+            let mut tourn = spoof_account().create_tournament(get_seed());
+            let _ = tourn.player_reg.register_player(first.clone()).unwrap();
+            let _ = tourn.player_reg.register_player(second.clone()).unwrap();
+            play_round(&mut tourn.round_reg, winner, loser);
+            assert!(series.add_event(tourn.id));
+            tourns.push(tourn);
+        }
+        assert!(!series.add_event(tourns[0].id));
+        // Tournaments that are not in the series are ignored
+        let mut other = spoof_account().create_tournament(get_seed());
+        let _ = other.player_reg.register_player(second.clone()).unwrap();
+        tourns.push(other);
+
+        let standings = series.standings(&tourns);
+        assert_eq!(standings.results.len(), 2);
+        assert_eq!(standings.results[0].player, first_id);
+        assert_eq!(standings.results[0].points, r64::from_integer(6));
+        assert_eq!(standings.results[0].events, 3);
+        assert_eq!(standings.results[1].points, r64::from_integer(3));
+
+        // Only each player's best result counts
+        series.scoring = SeriesScoring::BestResults(1);
+        let standings = series.standings(&tourns);
+        assert!(standings
+            .results
+            .iter()
+            .all(|r| r.points == r64::from_integer(3) && r.events == 3));
+
+        assert!(series.remove_event(tourns[2].id));
+        let standings = series.standings(&tourns);
+        assert_eq!(standings.results[1].player, second_id);
+        assert_eq!(standings.results[1].points, r64::default());
+    }
+}
@@ -1,11 +1,16 @@
 #[cfg(test)]
 mod tests {
-    use chrono::Utc;
+    use chrono::{Duration, Utc};
     use squire_lib::{
+        admin::TournOfficialId,
         error::TournamentError,
+        formats::Constructed,
         identifiers::AdminId,
-        operations::{AdminOp::*, TournOp},
+        operations::{AdminOp::*, JudgeOp, PlayerOp, TournOp},
+        rounds::RoundResult,
+        schedule::{DayTwo, Schedule},
         settings::*,
+        tournament::{TournRole, TournamentPreset, TournamentSeed},
     };
     use squire_tests::{get_fluid_seed, get_seed, spoof_account};
 
@@ -123,4 +128,275 @@ mod tests {
             )
         );
     }
+
+    #[test]
+    fn tournament_templates() {
+        let admin = spoof_account();
+        let admin_id: AdminId = admin.id.0.into();
+        let mut tourn = admin.create_tournament(get_fluid_seed());
+        tourn
+            .apply_op(
+                Utc::now(),
+                TournOp::AdminOp(
+                    admin_id,
+                    UpdateTournSetting(TournamentSetting::GeneralSetting(
+                        GeneralSetting::MaxDeckCount(3),
+                    )),
+                ),
+            )
+            .unwrap();
+        let judge = tourn
+            .apply_op(
+                Utc::now(),
+                TournOp::AdminOp(admin_id, RegisterJudge(spoof_account())),
+            )
+            .unwrap()
+            .assume_register_judge();
+        let template = TournamentTemplate::from_tournament(admin.id, "Weekly".into(), &tourn);
+        assert_eq!(template.preset, TournamentPreset::Fluid);
+        assert_eq!(template.judges, vec![judge.clone()]);
+
+        // The template's preset, settings, and officials are used, but the seed's format is kept
+        let seed = TournamentSeed::new(
+            "Next week".into(),
+            TournamentPreset::Swiss,
+            Constructed::Modern.into(),
+        )
+        .unwrap()
+        .with_template(template);
+        let new_tourn = spoof_account().create_tournament(seed);
+        assert_eq!(new_tourn.settings.max_deck_count, 3);
+        assert_eq!(new_tourn.settings.format, "Modern");
+        assert_eq!(
+            new_tourn.pairing_sys.settings(),
+            tourn.pairing_sys.settings()
+        );
+        assert_eq!(new_tourn.judges.get(&judge.id), Some(&judge));
+        assert_eq!(new_tourn.admins.len(), 2);
+    }
+
+    #[test]
+    fn permission_matrix_is_enforced() {
+        let admin = spoof_account();
+        let admin_id: AdminId = admin.id.0.into();
+        let mut tourn = admin.create_tournament(get_seed());
+        let plyrs: Vec<_> = (0..4)
+            .map(|_| {
+                tourn
+                    .apply_op(Utc::now(), TournOp::RegisterPlayer(spoof_account(), None))
+                    .unwrap()
+                    .assume_register_player()
+            })
+            .collect();
+        let judge = tourn
+            .apply_op(
+                Utc::now(),
+                TournOp::AdminOp(admin_id, RegisterJudge(spoof_account())),
+            )
+            .unwrap()
+            .assume_register_judge();
+        let judge_role = tourn.user_role(*judge.id);
+        tourn
+            .apply_op(Utc::now(), TournOp::AdminOp(admin_id, Start))
+            .unwrap()
+            .assume_nothing();
+
+        // By default, judges can't pair rounds
+        let pairings = tourn.create_pairings().unwrap();
+        let pair = TournOp::AdminOp(judge.id.convert(), PairRound(pairings));
+        assert!(!pair.valid_op(judge_role, &tourn.settings.permissions));
+        assert_eq!(
+            tourn.apply_op(Utc::now(), pair.clone()),
+            Err(TournamentError::PermissionDenied)
+        );
+
+        // Once they are granted the permission, they can
+        let mut perms = tourn.settings.permissions.clone();
+        perms.grant(PermissionRole::Judge, Permission::PairRounds);
+        perms.revoke(PermissionRole::Judge, Permission::RecordResults);
+        perms.revoke(PermissionRole::Player, Permission::DropPlayers);
+        let setting = GeneralSetting::Permissions(perms).into();
+        tourn
+            .apply_op(
+                Utc::now(),
+                TournOp::AdminOp(admin_id, UpdateTournSetting(setting)),
+            )
+            .unwrap()
+            .assume_nothing();
+        assert!(pair.valid_op(judge_role, &tourn.settings.permissions));
+        let rounds = tourn.apply_op(Utc::now(), pair).unwrap().assume_pair();
+
+        // Permissions can also be taken away from judges and players
+        let record = TournOp::JudgeOp(
+            TournOfficialId::Judge(judge.id),
+            JudgeOp::AdminRecordResult(rounds[0], RoundResult::Draw(1)),
+        );
+        assert!(!record.valid_op(judge_role, &tourn.settings.permissions));
+        assert_eq!(
+            tourn.apply_op(Utc::now(), record),
+            Err(TournamentError::PermissionDenied)
+        );
+        assert_eq!(
+            tourn.apply_op(
+                Utc::now(),
+                TournOp::PlayerOp(plyrs[0], PlayerOp::DropPlayer)
+            ),
+            Err(TournamentError::PermissionDenied)
+        );
+        // Admins are not bound by the matrix
+        tourn
+            .apply_op(
+                Utc::now(),
+                TournOp::AdminOp(admin_id, AdminDropPlayer(plyrs[0])),
+            )
+            .unwrap()
+            .assume_nothing();
+    }
+
+    #[test]
+    fn scorekeepers_only_handle_results_and_tables() {
+        let admin = spoof_account();
+        let admin_id: AdminId = admin.id.0.into();
+        let mut tourn = admin.create_tournament(get_seed());
+        for _ in 0..4 {
+            _ = tourn
+                .apply_op(Utc::now(), TournOp::RegisterPlayer(spoof_account(), None))
+                .unwrap();
+        }
+        let sk = tourn
+            .apply_op(
+                Utc::now(),
+                TournOp::AdminOp(admin_id, RegisterScorekeeper(spoof_account())),
+            )
+            .unwrap()
+            .assume_register_scorekeeper();
+        let sk_role = tourn.user_role(*sk.id);
+        assert_eq!(sk_role, TournRole::Scorekeeper(sk.id));
+        tourn
+            .apply_op(Utc::now(), TournOp::AdminOp(admin_id, Start))
+            .unwrap()
+            .assume_nothing();
+        let pairings = tourn.create_pairings().unwrap();
+        let rounds = tourn
+            .apply_op(Utc::now(), TournOp::AdminOp(admin_id, PairRound(pairings)))
+            .unwrap()
+            .assume_pair();
+
+        // Scorekeepers can assign tables and enter and confirm results
+        let ops = [
+            TournOp::AdminOp(sk.id.convert(), AssignTable(rounds[0], 100)),
+            TournOp::JudgeOp(
+                TournOfficialId::Scorekeeper(sk.id),
+                JudgeOp::AdminRecordResult(rounds[0], RoundResult::Draw(1)),
+            ),
+            TournOp::JudgeOp(
+                TournOfficialId::Scorekeeper(sk.id),
+                JudgeOp::ConfirmRound(rounds[0]),
+            ),
+        ];
+        for op in ops {
+            assert!(op.valid_op(sk_role, &tourn.settings.permissions));
+            assert!(tourn.apply_op(Utc::now(), op).is_ok());
+        }
+
+        // But nothing else, even if judges are granted it
+        let mut perms = tourn.settings.permissions.clone();
+        perms.grant(PermissionRole::Judge, Permission::PairRounds);
+        let setting = GeneralSetting::Permissions(perms).into();
+        tourn
+            .apply_op(
+                Utc::now(),
+                TournOp::AdminOp(admin_id, UpdateTournSetting(setting)),
+            )
+            .unwrap()
+            .assume_nothing();
+        let ops = [
+            TournOp::AdminOp(sk.id.convert(), RemoveRound(rounds[1])),
+            TournOp::JudgeOp(
+                TournOfficialId::Scorekeeper(sk.id),
+                JudgeOp::RegisterGuest("Guest".into()),
+            ),
+        ];
+        for op in ops {
+            assert!(!op.valid_op(sk_role, &tourn.settings.permissions));
+            assert_eq!(
+                tourn.apply_op(Utc::now(), op),
+                Err(TournamentError::PermissionDenied)
+            );
+        }
+    }
+
+    #[test]
+    fn decklist_visibility_follows_status() {
+        let admin = spoof_account();
+        let admin_id: AdminId = admin.id.0.into();
+        let visibility = |vis| {
+            let mut tourn = admin.create_tournament(get_seed());
+            let setting = GeneralSetting::DecklistVisibility(vis).into();
+            tourn
+                .apply_op(
+                    Utc::now(),
+                    TournOp::AdminOp(admin_id, UpdateTournSetting(setting)),
+                )
+                .unwrap();
+            let mut digest = vec![tourn.decklists_visible()];
+            for op in [Start, End] {
+                tourn
+                    .apply_op(Utc::now(), TournOp::AdminOp(admin_id, op))
+                    .unwrap();
+                digest.push(tourn.decklists_visible());
+            }
+            digest
+        };
+        assert_eq!(visibility(DecklistVisibility::Never), [false; 3]);
+        assert_eq!(
+            visibility(DecklistVisibility::AfterStart),
+            [false, true, true]
+        );
+        assert_eq!(
+            visibility(DecklistVisibility::AfterEnd),
+            [false, false, true]
+        );
+    }
+
+    #[test]
+    fn schedules_are_published_but_not_templated() {
+        let admin = spoof_account();
+        let admin_id: AdminId = admin.id.0.into();
+        let mut tourn = admin.create_tournament(get_seed());
+        let start = Utc::now();
+        let update = |schedule| {
+            TournOp::AdminOp(
+                admin_id,
+                UpdateTournSetting(GeneralSetting::Schedule(schedule).into()),
+            )
+        };
+        let bad = Schedule {
+            rounds: vec![(0, start)],
+            ..Default::default()
+        };
+        assert_eq!(
+            tourn.apply_op(Utc::now(), update(bad)),
+            Err(TournamentError::InvalidSchedule)
+        );
+        let day_two = start + Duration::days(1);
+        let schedule = Schedule {
+            start: Some(start),
+            rounds: Vec::new(),
+            day_two: Some(DayTwo {
+                first_round: 9,
+                start: day_two,
+                min_points: None,
+            }),
+        };
+        tourn.apply_op(Utc::now(), update(schedule)).unwrap();
+
+        let published = tourn.schedule();
+        assert_eq!(published.rounds.len(), 9);
+        assert_eq!(published.rounds[8].start, day_two);
+        assert_eq!(published.rounds[8].day, 2);
+
+        let template = TournamentTemplate::from_tournament(admin.id, "Weekly".into(), &tourn);
+        assert_eq!(template.settings.general.schedule, Schedule::default());
+    }
 }
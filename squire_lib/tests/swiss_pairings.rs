@@ -1,7 +1,12 @@
 #[cfg(test)]
 mod tests {
+    use std::collections::{HashMap, HashSet};
+
     use chrono::Utc;
     use squire_lib::{
+        error::TournamentError,
+        identifiers::PlayerId,
+        pairings::{PairingAlgorithm, Pairings},
         rounds::{RoundContext, RoundResult},
         settings::SwissPairingSetting,
     };
@@ -182,4 +187,87 @@ mod tests {
         println!("The number of byes is: {}", pairings.rejected.len());
         assert_eq!(count, goal);
     }
+
+    #[test]
+    fn byes_go_to_new_players() {
+        let (_, plyrs, mut rnds, _) = spoof_data(3);
+        let ids: Vec<PlayerId> = plyrs.players.keys().copied().collect();
+        let _ = rnds.give_bye(Utc::now(), ids[2], RoundContext::Contextless);
+        let byes = rnds.bye_history();
+        assert_eq!(byes, HashSet::from([ids[2]]));
+
+        // The player with a bye is swapped with a paired player
+        let mut pairings = Pairings {
+            paired: vec![vec![ids[0], ids[1]]],
+            rejected: vec![ids[2]],
+        };
+        pairings.reassign_byes(&byes, &HashMap::new(), 0, false);
+        assert_eq!(pairings.rejected, vec![ids[1]]);
+        assert_eq!(pairings.paired, vec![vec![ids[0], ids[2]]]);
+
+        // Repeat opponents block the swap unless repeat byes are forbidden
+        let opps = HashMap::from([
+            (ids[0], HashSet::from([ids[2]])),
+            (ids[1], HashSet::from([ids[2]])),
+            (ids[2], HashSet::from([ids[0], ids[1]])),
+        ]);
+        let mut pairings = Pairings {
+            paired: vec![vec![ids[0], ids[1]]],
+            rejected: vec![ids[2]],
+        };
+        pairings.reassign_byes(&byes, &opps, 0, false);
+        assert_eq!(pairings.rejected, vec![ids[2]]);
+        pairings.reassign_byes(&byes, &opps, 0, true);
+        assert_eq!(pairings.rejected, vec![ids[1]]);
+    }
+
+    #[test]
+    fn byes_go_to_new_players_with_every_algorithm() {
+        let (mut sys, plyrs, mut rnds, standings) = spoof_data(3);
+        sys.common.match_size = 2;
+        let ids: Vec<PlayerId> = plyrs.players.keys().copied().collect();
+        let _ = rnds.give_bye(Utc::now(), ids[0], RoundContext::Contextless);
+        let _ = rnds.give_bye(Utc::now(), ids[1], RoundContext::Contextless);
+        for alg in [
+            PairingAlgorithm::Greedy,
+            PairingAlgorithm::Branching,
+            PairingAlgorithm::Rotary,
+            PairingAlgorithm::Matching,
+        ] {
+            sys.common.algorithm = alg;
+            let pairings = sys
+                .pair(&plyrs, &rnds, standings.get_standings(&plyrs, &rnds))
+                .unwrap();
+            assert_eq!(pairings.rejected, vec![ids[2]]);
+        }
+    }
+
+    #[test]
+    fn pairing_constraints_are_avoided() {
+        let (mut sys, plyrs, rnds, standings) = spoof_data(4);
+        sys.common.match_size = 2;
+        let ids: Vec<PlayerId> = plyrs.players.keys().copied().collect();
+        sys.constraints.add_group(vec![ids[0], ids[1]]).unwrap();
+        sys.constraints.add_group(vec![ids[2], ids[3]]).unwrap();
+        assert_eq!(
+            sys.constraints.add_group(vec![ids[0], ids[0]]),
+            Err(TournamentError::InvalidPairingConstraint)
+        );
+        for alg in [
+            PairingAlgorithm::Greedy,
+            PairingAlgorithm::Branching,
+            PairingAlgorithm::Rotary,
+            PairingAlgorithm::Matching,
+        ] {
+            sys.common.algorithm = alg;
+            let pairings = sys
+                .pair(&plyrs, &rnds, standings.get_standings(&plyrs, &rnds))
+                .unwrap();
+            assert_eq!(pairings.paired.len(), 2);
+            assert!(pairings
+                .paired
+                .iter()
+                .all(|p| !sys.constraints.are_constrained(&p[0], &p[1])));
+        }
+    }
 }
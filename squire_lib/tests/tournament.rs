@@ -2,11 +2,15 @@ use chrono::Utc;
 use itertools::Itertools;
 use squire_lib::{
     error::TournamentError,
-    operations::TournOp,
-    players::PlayerId,
-    tournament::{Tournament, TournamentStatus},
+    identifiers::AdminId,
+    operations::{AdminOp, OpData, TournOp},
+    players::{PlayerId, PlayerStatus},
+    report::ReportKind,
+    rounds::{RoundContext, RoundResult},
+    settings::SwissPairingSetting,
+    tournament::{Tournament, TournamentStatus, MAX_ANNOUNCEMENTS},
 };
-use squire_tests::spoof_account;
+use squire_tests::{get_seed, spoof_account};
 use uuid::Uuid;
 
 #[test]
@@ -63,3 +67,409 @@ fn create_round_test() {
         Err(TournamentError::RepeatedPlayerInMatch)
     );
 }
+
+#[test]
+fn seat_map_test() {
+    let mut tourn: Tournament = squire_tests::get_seed().into();
+    let players = std::iter::repeat_with(|| {
+        tourn
+            .apply_op(Utc::now(), TournOp::RegisterPlayer(spoof_account(), None))
+            .unwrap()
+            .assume_register_player()
+    })
+    .take(10)
+    .collect_vec();
+
+    // Ten players split into two pods of five, rather than pods of eight and two
+    let seats = tourn.seat_map(8);
+    assert_eq!(seats.seats.len(), 10);
+    assert_eq!(seats.pod_count(), 2);
+    assert_eq!(seats.seats.iter().filter(|s| s.pod == 1).count(), 5);
+    assert!(players.iter().all(|p| seats.get_seat(p).is_some()));
+    assert_eq!(seats, tourn.seat_map(8));
+
+    // Dropped players are not seated
+    tourn.player_reg.drop_player(&players[0]).unwrap();
+    let seats = tourn.seat_map(8);
+    assert_eq!(seats.pod_count(), 2);
+    assert!(seats.get_seat(&players[0]).is_none());
+    assert_eq!(seats.to_csv().lines().count(), 10);
+    assert_eq!(tourn.seat_map(9).pod_count(), 1);
+}
+
+#[test]
+fn draft_pods_test() {
+    let admin = spoof_account();
+    let admin_id: AdminId = admin.id.0.into();
+    let mut tourn = admin.create_tournament(get_seed());
+    let players = std::iter::repeat_with(|| {
+        tourn
+            .apply_op(Utc::now(), TournOp::RegisterPlayer(spoof_account(), None))
+            .unwrap()
+            .assume_register_player()
+    })
+    .take(8)
+    .collect_vec();
+    let setting = SwissPairingSetting::PairWithinPods(true).into();
+    let ops = [
+        AdminOp::UpdateTournSetting(setting),
+        AdminOp::CreateDraftPods(4),
+        AdminOp::Start,
+    ];
+    assert_eq!(
+        tourn.apply_op(
+            Utc::now(),
+            TournOp::AdminOp(admin_id, AdminOp::CreateDraftPods(0))
+        ),
+        Err(TournamentError::InvalidPodSize)
+    );
+    for op in ops {
+        let _ = tourn
+            .apply_op(Utc::now(), TournOp::AdminOp(admin_id, op))
+            .unwrap();
+    }
+
+    // The pods are used for seating, regardless of the requested pod size
+    let pods = tourn.pairing_sys.pods.clone();
+    assert_eq!(pods.pods.len(), 2);
+    let seats = tourn.seat_map(8);
+    assert_eq!(seats.pod_count(), 2);
+    assert!(players
+        .iter()
+        .all(|p| seats.get_seat(p).unwrap().pod == pods.pod_of(p).unwrap()));
+
+    // Players are only paired against players in their pod
+    let pairings = tourn.create_pairings().unwrap();
+    assert_eq!(pairings.paired.len(), 4);
+    assert!(pairings.paired.iter().all(|p| pods.shared_pod(p).is_some()));
+    let ids = tourn
+        .apply_op(
+            Utc::now(),
+            TournOp::AdminOp(admin_id, AdminOp::PairRound(pairings)),
+        )
+        .unwrap()
+        .assume_pair();
+    for id in ids {
+        let rnd = tourn.round_reg.get_round(&id).unwrap();
+        let pod = RoundContext::DraftPod(pods.shared_pod(&rnd.players).unwrap());
+        let RoundContext::Multiple(ctx) = &rnd.context else {
+            panic!("The round should have multiple contexts");
+        };
+        assert!(ctx.contains(&pod));
+    }
+}
+
+#[test]
+fn consolation_flight_test() {
+    let admin = spoof_account();
+    let admin_id: AdminId = admin.id.0.into();
+    let mut tourn = admin.create_tournament(get_seed());
+    let _ = std::iter::repeat_with(|| {
+        tourn
+            .apply_op(Utc::now(), TournOp::RegisterPlayer(spoof_account(), None))
+            .unwrap()
+            .assume_register_player()
+    })
+    .take(8)
+    .count();
+    let admin_op = |tourn: &mut Tournament, op| {
+        tourn
+            .apply_op(Utc::now(), TournOp::AdminOp(admin_id, op))
+            .unwrap()
+    };
+    let play_round = |tourn: &mut Tournament| {
+        let pairings = tourn.create_pairings().unwrap();
+        let ids = admin_op(tourn, AdminOp::PairRound(pairings)).assume_pair();
+        for id in ids.iter() {
+            let winner = tourn.round_reg.get_round(id).unwrap().players[0];
+            let result = RoundResult::Wins(winner, 2);
+            let _ = admin_op(tourn, AdminOp::AdminOverwriteResult(*id, result));
+        }
+        let _ = admin_op(tourn, AdminOp::ConfirmAllRounds);
+        ids
+    };
+    let _ = admin_op(&mut tourn, AdminOp::Start);
+    let _ = play_round(&mut tourn);
+
+    // The players that lost their first round drop to the consolation flight
+    let losers: Vec<_> = tourn
+        .get_standings()
+        .scores
+        .into_iter()
+        .filter(|(_, s)| s.match_points == Default::default())
+        .map(|(p, _)| p)
+        .collect();
+    assert_eq!(losers.len(), 4);
+    assert_eq!(
+        tourn.apply_op(
+            Utc::now(),
+            TournOp::AdminOp(
+                admin_id,
+                AdminOp::MoveToFlight(losers.clone(), Some(" ".into()))
+            )
+        ),
+        Err(TournamentError::InvalidFlightName)
+    );
+    let op = AdminOp::MoveToFlight(losers.clone(), Some("Consolation".into()));
+    let _ = admin_op(&mut tourn, op);
+    let flights = tourn.pairing_sys.flights.clone();
+    assert!(losers.iter().all(|p| flights.flight_of(p) == Some(1)));
+
+    // Each flight has its own standings, which only count the rounds played in the flight
+    let main = tourn.get_standings();
+    assert_eq!(main.scores.len(), 4);
+    assert!(main.scores.iter().all(|(p, _)| !losers.contains(p)));
+    let consolation = tourn.get_flight_standings(1);
+    assert_eq!(consolation.scores.len(), 4);
+    assert!(consolation
+        .scores
+        .iter()
+        .all(|(_, s)| s.match_points == Default::default()));
+
+    // The flights are paired on their own
+    let ids = play_round(&mut tourn);
+    assert_eq!(ids.len(), 4);
+    let flight_rounds = ids
+        .iter()
+        .map(|id| tourn.round_reg.get_round(id).unwrap())
+        .filter(|rnd| rnd.context.flight() == Some(1))
+        .inspect(|rnd| assert!(rnd.players.iter().all(|p| losers.contains(p))))
+        .count();
+    assert_eq!(flight_rounds, 2);
+    let consolation = tourn.get_flight_standings(1);
+    let winners = consolation
+        .scores
+        .iter()
+        .filter(|(_, s)| s.match_points != Default::default())
+        .count();
+    assert_eq!(winners, 2);
+    let main_points = tourn.get_standings().scores[0].1.match_points;
+    assert!(main_points > consolation.scores[0].1.match_points);
+}
+
+#[test]
+fn report_test() {
+    let admin = spoof_account();
+    let admin_id: AdminId = admin.id.0.into();
+    let mut tourn = admin.create_tournament(get_seed());
+    tourn.name = "Friday <Night>".into();
+    let players = std::iter::repeat_with(|| {
+        tourn
+            .apply_op(Utc::now(), TournOp::RegisterPlayer(spoof_account(), None))
+            .unwrap()
+            .assume_register_player()
+    })
+    .take(4)
+    .collect_vec();
+    tourn
+        .apply_op(Utc::now(), TournOp::AdminOp(admin_id, AdminOp::Start))
+        .unwrap()
+        .assume_nothing();
+    let pairings = tourn.create_pairings().unwrap();
+    tourn
+        .apply_op(
+            Utc::now(),
+            TournOp::AdminOp(admin_id, AdminOp::PairRound(pairings)),
+        )
+        .unwrap();
+
+    let rows = |html: &str| html.matches("<tr style").count();
+    let by_table = tourn.report_html(ReportKind::PairingsByTable, "");
+    assert!(by_table.contains("<h1>Friday &lt;Night&gt; - Pairings</h1>"));
+    assert_eq!(rows(&by_table), 2);
+    let by_name = tourn.report_html(ReportKind::PairingsByName, "");
+    assert_eq!(rows(&by_name), 4);
+    let standings = tourn.report_html(ReportKind::Standings, "");
+    assert!(standings.contains("<td>1</td>"));
+    for id in players {
+        let name = tourn.player_reg.get_player(&id).unwrap().all_names();
+        assert!(by_table.contains(&name));
+        assert!(by_name.contains(&name));
+        assert!(standings.contains(&name));
+    }
+}
+
+#[test]
+fn announcements_test() {
+    let admin = spoof_account();
+    let admin_id: AdminId = admin.id.0.into();
+    let mut tourn = admin.create_tournament(get_seed());
+    let announce = |tourn: &mut Tournament, msg: &str| {
+        tourn.apply_op(
+            Utc::now(),
+            TournOp::AdminOp(admin_id, AdminOp::Announce(msg.to_owned())),
+        )
+    };
+
+    assert_eq!(
+        announce(&mut tourn, "  "),
+        Err(TournamentError::EmptyAnnouncement)
+    );
+    assert!(tourn.announcements.is_empty());
+
+    announce(&mut tourn, "Lunch break for 30 minutes")
+        .unwrap()
+        .assume_nothing();
+    assert_eq!(tourn.announcements.len(), 1);
+    assert_eq!(tourn.announcements[0].admin, admin_id);
+    assert_eq!(tourn.announcements[0].message, "Lunch break for 30 minutes");
+
+    // Only the most recent announcements are kept
+    for i in 0..MAX_ANNOUNCEMENTS {
+        announce(&mut tourn, &format!("Announcement {i}"))
+            .unwrap()
+            .assume_nothing();
+    }
+    assert_eq!(tourn.announcements.len(), MAX_ANNOUNCEMENTS);
+    assert_eq!(tourn.announcements[0].message, "Announcement 0");
+    assert_eq!(
+        tourn.announcements.last().unwrap().message,
+        format!("Announcement {}", MAX_ANNOUNCEMENTS - 1)
+    );
+
+    // Only admins can make announcements
+    let op = TournOp::AdminOp(
+        spoof_account().id.0.into(),
+        AdminOp::Announce("Hello".into()),
+    );
+    assert_eq!(
+        tourn.apply_op(Utc::now(), op),
+        Err(TournamentError::OfficalLookup)
+    );
+}
+
+#[test]
+fn simulate_ops_test() {
+    let admin = spoof_account();
+    let admin_id: AdminId = admin.id.0.into();
+    let mut tourn = admin.create_tournament(get_seed());
+    let plyrs = std::iter::repeat_with(|| {
+        tourn
+            .apply_op(Utc::now(), TournOp::RegisterPlayer(spoof_account(), None))
+            .unwrap()
+            .assume_register_player()
+    })
+    .take(4)
+    .collect_vec();
+    let before = tourn.clone();
+
+    // Errors are reported and nothing is applied
+    assert_eq!(
+        tourn.simulate_op(
+            Utc::now(),
+            TournOp::AdminOp(admin_id, AdminOp::PairRound(Default::default()))
+        ),
+        Err(TournamentError::IncorrectStatus(TournamentStatus::Planned))
+    );
+    let drop = TournOp::AdminOp(admin_id, AdminOp::AdminDropPlayer(plyrs[0]));
+    tourn
+        .simulate_op(Utc::now(), drop.clone())
+        .unwrap()
+        .assume_nothing();
+    let sim = tourn
+        .simulate_ops(
+            Utc::now(),
+            [TournOp::AdminOp(admin_id, AdminOp::Start), drop.clone()],
+        )
+        .unwrap();
+    assert_eq!(sim.data, vec![OpData::Nothing, OpData::Nothing]);
+    assert_eq!(sim.diff.status, Some(TournamentStatus::Started));
+    assert_eq!(
+        sim.diff.player_statuses,
+        vec![(plyrs[0], PlayerStatus::Dropped)]
+    );
+    assert!(sim.diff.new_rounds.is_empty());
+    assert_eq!(tourn, before);
+
+    // Pairing previews show who would play
+    tourn
+        .apply_op(Utc::now(), TournOp::AdminOp(admin_id, AdminOp::Start))
+        .unwrap()
+        .assume_nothing();
+    let pairings = tourn.create_pairings().unwrap();
+    let sim = tourn
+        .simulate_ops(
+            Utc::now(),
+            [TournOp::AdminOp(admin_id, AdminOp::PairRound(pairings))],
+        )
+        .unwrap();
+    assert_eq!(sim.diff.new_rounds.len(), 2);
+    assert!(sim.diff.byes.is_empty());
+    assert!(sim.diff.sitting_out.is_empty());
+    assert!(tourn.round_reg.rounds.is_empty());
+
+    // With an odd number of players, someone gets a bye or sits out
+    tourn.apply_op(Utc::now(), drop).unwrap().assume_nothing();
+    let pairings = tourn.create_pairings().unwrap();
+    let sim = tourn
+        .simulate_ops(
+            Utc::now(),
+            [TournOp::AdminOp(admin_id, AdminOp::PairRound(pairings))],
+        )
+        .unwrap();
+    assert_eq!(sim.diff.byes.len() + sim.diff.sitting_out.len(), 1);
+    assert!(!sim.diff.byes.contains(&plyrs[0]));
+    assert!(!sim.diff.sitting_out.contains(&plyrs[0]));
+    assert!(!sim.diff.is_empty());
+}
+
+#[test]
+fn inverse_ops_test() {
+    let admin = spoof_account();
+    let admin_id: AdminId = admin.id.0.into();
+    let mut tourn = admin.create_tournament(get_seed());
+    let plyrs = std::iter::repeat_with(|| {
+        tourn
+            .apply_op(Utc::now(), TournOp::RegisterPlayer(spoof_account(), None))
+            .unwrap()
+            .assume_register_player()
+    })
+    .take(3)
+    .collect_vec();
+
+    // Dropping a player is reversed by reinstating them
+    let op = TournOp::AdminOp(admin_id, AdminOp::AdminDropPlayer(plyrs[0]));
+    let (salt, before) = (Utc::now(), tourn.clone());
+    tourn.apply_op(salt, op.clone()).unwrap().assume_nothing();
+    let inverse = op.inverse(admin_id, salt, &before).unwrap();
+    assert_eq!(
+        inverse,
+        vec![TournOp::AdminOp(
+            admin_id,
+            AdminOp::ReinstatePlayer(plyrs[0])
+        )]
+    );
+    for op in inverse {
+        tourn.apply_op(Utc::now(), op).unwrap().assume_nothing();
+    }
+    let plyr = tourn.player_reg.get_player(&plyrs[0]).unwrap();
+    assert_eq!(plyr.status, PlayerStatus::Registered);
+    assert_eq!(
+        tourn.apply_op(
+            Utc::now(),
+            TournOp::AdminOp(admin_id, AdminOp::ReinstatePlayer(plyrs[0]))
+        ),
+        Err(TournamentError::PlayerNotDropped)
+    );
+
+    // Creating a round is reversed by removing it
+    tourn
+        .apply_op(Utc::now(), TournOp::AdminOp(admin_id, AdminOp::Start))
+        .unwrap()
+        .assume_nothing();
+    let op = TournOp::AdminOp(admin_id, AdminOp::CreateRound(plyrs[1..].to_vec()));
+    let (salt, before) = (Utc::now(), tourn.clone());
+    let r_id = tourn
+        .apply_op(salt, op.clone())
+        .unwrap()
+        .assume_create_round();
+    let inverse = op.inverse(admin_id, salt, &before).unwrap();
+    assert_eq!(
+        inverse,
+        vec![TournOp::AdminOp(admin_id, AdminOp::RemoveRound(r_id))]
+    );
+
+    // Some operations can not be reversed
+    let op = TournOp::AdminOp(admin_id, AdminOp::End);
+    assert!(op.inverse(admin_id, Utc::now(), &tourn).is_none());
+}
@@ -0,0 +1,96 @@
+//! Replays a tournament's operations log, one operation at a time, and reports the first operation
+//! whose replay diverges from the recorded tournament. This is used to debug reports of
+//! tournaments that ended up in an unexpected state.
+//!
+//! Usage: `squire_replay <tournament.json> [--verbose]`
+//!
+//! The file can hold either a serialized `TournamentManager` or a signed tournament export.
+
+use std::process::ExitCode;
+
+use squire_sdk::{
+    api::SignedExport,
+    sync::{ReplayDivergence, ReplayStep, TournamentManager},
+};
+
+fn main() -> ExitCode {
+    let mut path = None;
+    let mut verbose = false;
+    for arg in std::env::args().skip(1) {
+        match arg.as_str() {
+            "-v" | "--verbose" => verbose = true,
+            _ if path.is_none() => path = Some(arg),
+            _ => return usage(),
+        }
+    }
+    let Some(path) = path else {
+        return usage();
+    };
+    let tourn = match load(&path) {
+        Ok(tourn) => tourn,
+        Err(err) => {
+            eprintln!("Could not load {path}: {err}");
+            return ExitCode::FAILURE;
+        }
+    };
+    let log = tourn.op_log();
+    println!(
+        "Replaying {} operations of \"{}\" ({} were compacted into a snapshot)",
+        log.len(),
+        tourn.name,
+        log.compacted_len()
+    );
+    if verbose {
+        for step in tourn.replay() {
+            print_step(&step);
+        }
+    }
+    match tourn.verify_replay() {
+        Ok(count) => {
+            println!("All {count} operations replayed to the recorded tournament");
+            ExitCode::SUCCESS
+        }
+        Err(ReplayDivergence::Failed(step)) => {
+            println!("The replay diverged. This operation could not be applied:");
+            print_step(&step);
+            ExitCode::FAILURE
+        }
+        Err(ReplayDivergence::Nondeterministic(step)) => {
+            println!("The replay diverged. This operation applies differently each time:");
+            print_step(&step);
+            ExitCode::FAILURE
+        }
+        Err(ReplayDivergence::Mismatch(parts)) => {
+            println!(
+                "Every operation replayed, but the result differs from the recorded tournament"
+            );
+            println!("The differing parts are: {}", parts.join(", "));
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn usage() -> ExitCode {
+    eprintln!("Usage: squire_replay <tournament.json> [--verbose]");
+    ExitCode::FAILURE
+}
+
+/// Reads a tournament from a file, which can hold a `TournamentManager` or a signed export
+fn load(path: &str) -> Result<TournamentManager, String> {
+    let data = std::fs::read_to_string(path).map_err(|err| err.to_string())?;
+    match serde_json::from_str::<TournamentManager>(&data) {
+        Ok(tourn) => Ok(tourn),
+        Err(err) => serde_json::from_str::<SignedExport>(&data)
+            .map(|export| export.tourn)
+            .map_err(|_| err.to_string()),
+    }
+}
+
+fn print_step(step: &ReplayStep) {
+    let ReplayStep { index, op, result } = step;
+    let (name, id, salt) = (op.op().name(), op.id(), op.salt());
+    match result {
+        Ok(_) => println!("#{index} {name} ({id}) at {salt}"),
+        Err(err) => println!("#{index} {name} ({id}) at {salt}: {err:?}"),
+    }
+}
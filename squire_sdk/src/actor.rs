@@ -1,7 +1,8 @@
 use std::{
-    fmt::Debug,
+    fmt::{self, Debug},
     pin::Pin,
     task::{Context, Poll},
+    time::Duration,
 };
 
 pub use async_trait::async_trait;
@@ -18,7 +19,8 @@ pub use tokio::sync::oneshot::{
 use tokio_stream::wrappers::UnboundedReceiverStream;
 
 use crate::compat::{
-    sleep_until, spawn_task, Sendable, SendableFuture, SendableStream, SendableWrapper, Sleep,
+    sleep, sleep_until, spawn_task, Sendable, SendableFuture, SendableStream, SendableWrapper,
+    Sleep,
 };
 
 // This state needs to be send because of constraints of `async_trait`. Ideally, it would be
@@ -250,6 +252,33 @@ impl<A: ActorState> ActorClient<A> {
     }
 }
 
+/// The reasons that a tracker can stop waiting on an actor's response
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TrackerError {
+    /// The actor stopped (i.e. it panicked) without responding
+    ActorDied,
+    /// The actor did not respond before the tracker's deadline
+    TimedOut,
+    /// The tracker was cancelled
+    Cancelled,
+}
+
+impl fmt::Display for TrackerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            TrackerError::ActorDied => "the actor stopped without responding",
+            TrackerError::TimedOut => "the actor did not respond in time",
+            TrackerError::Cancelled => "the tracker was cancelled",
+        };
+        write!(f, "{s}")
+    }
+}
+
+impl std::error::Error for TrackerError {}
+
+/// Waits on an actor's response to a message. Awaiting a tracker directly panics if the actor
+/// stops without responding. Use `Tracker::try_output` to handle that instead, or to stop waiting
+/// after a deadline or when cancelled.
 pub struct Tracker<T> {
     recv: OneshotReceiver<T>,
 }
@@ -258,6 +287,17 @@ impl<T> Tracker<T> {
     pub fn new(recv: OneshotReceiver<T>) -> Self {
         Self { recv }
     }
+
+    /// Returns a future that waits for the response, but yields an error instead of panicking if
+    /// the response does not come (see `TrackerError`). Only these trackers can time out or be
+    /// cancelled.
+    pub fn try_output(self) -> TryTracker<T> {
+        TryTracker {
+            recv: self.recv,
+            deadline: None,
+            cancelled: false,
+        }
+    }
 }
 
 impl<T> Future for Tracker<T> {
@@ -268,6 +308,49 @@ impl<T> Future for Tracker<T> {
     }
 }
 
+/// A tracker that yields an error when the actor's response does not come (see
+/// `Tracker::try_output`)
+pub struct TryTracker<T> {
+    recv: OneshotReceiver<T>,
+    deadline: Option<Sleep>,
+    cancelled: bool,
+}
+
+impl<T> TryTracker<T> {
+    /// Sets how long, starting now, the tracker waits for the response before giving up
+    pub fn timeout(mut self, dur: Duration) -> Self {
+        self.deadline = Some(sleep(dur));
+        self
+    }
+
+    /// Stops waiting on the response. The actor's response, if it comes, is dropped.
+    pub fn cancel(&mut self) {
+        self.recv.close();
+        self.cancelled = true;
+    }
+}
+
+impl<T> Future for TryTracker<T> {
+    type Output = Result<T, TrackerError>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if self.cancelled {
+            return Poll::Ready(Err(TrackerError::Cancelled));
+        }
+        if let Poll::Ready(res) = Pin::new(&mut self.recv).poll(cx) {
+            return Poll::Ready(res.map_err(|_| TrackerError::ActorDied));
+        }
+        match self
+            .deadline
+            .as_mut()
+            .map(|deadline| deadline.poll_unpin(cx))
+        {
+            Some(Poll::Ready(())) => Poll::Ready(Err(TrackerError::TimedOut)),
+            _ => Poll::Pending,
+        }
+    }
+}
+
 impl<A: ActorState> Debug for ActorClient<A> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, r#"ActorClient {{ "send": {:?} }}"#, &*self.send)
@@ -282,3 +365,77 @@ where
         Self::builder(A::default()).launch()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use async_trait::async_trait;
+
+    use super::{ActorClient, ActorState, OneshotSender, Scheduler, Tracker, TrackerError};
+
+    enum Request {
+        Echo(u32),
+        Hold,
+        Panic,
+    }
+
+    /// An actor that answers, ignores, or panics on requests
+    #[derive(Default)]
+    struct TestActor {
+        held: Vec<OneshotSender<u32>>,
+    }
+
+    #[async_trait]
+    impl ActorState for TestActor {
+        type Message = (Request, OneshotSender<u32>);
+
+        async fn process(&mut self, _: &mut Scheduler<Self>, (req, send): Self::Message) {
+            match req {
+                Request::Echo(n) => {
+                    _ = send.send(n);
+                }
+                Request::Hold => self.held.push(send),
+                Request::Panic => panic!("The actor was asked to panic"),
+            }
+        }
+    }
+
+    fn track(client: &ActorClient<TestActor>, req: Request) -> Tracker<u32> {
+        client.track(req)
+    }
+
+    #[tokio::test]
+    async fn trackers_time_out() {
+        let client = ActorClient::builder(TestActor::default()).launch();
+        assert_eq!(track(&client, Request::Echo(1)).await, 1);
+        let res = track(&client, Request::Hold)
+            .try_output()
+            .timeout(Duration::from_millis(10))
+            .await;
+        assert_eq!(res, Err(TrackerError::TimedOut));
+        let res = track(&client, Request::Echo(2))
+            .try_output()
+            .timeout(Duration::from_secs(10))
+            .await;
+        assert_eq!(res, Ok(2));
+    }
+
+    #[tokio::test]
+    async fn trackers_can_be_cancelled() {
+        let client = ActorClient::builder(TestActor::default()).launch();
+        let mut tracker = track(&client, Request::Hold).try_output();
+        tracker.cancel();
+        assert_eq!(tracker.await, Err(TrackerError::Cancelled));
+        assert_eq!(track(&client, Request::Echo(3)).await, 3);
+    }
+
+    #[tokio::test]
+    async fn trackers_notice_dead_actors() {
+        let client = ActorClient::builder(TestActor::default()).launch();
+        let res = track(&client, Request::Panic).try_output().await;
+        assert_eq!(res, Err(TrackerError::ActorDied));
+        let res = track(&client, Request::Echo(4)).try_output().await;
+        assert_eq!(res, Err(TrackerError::ActorDied));
+    }
+}
@@ -1,7 +1,21 @@
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use squire_lib::accounts::SquireAccount;
-
-use crate::{extend, sync::TournamentManager};
+use squire_lib::{
+    accounts::SquireAccount,
+    prizes::Payout,
+    schedule::EventSchedule,
+    scoring::ScoreExplanation,
+    seating::SeatMap,
+    series::{Series, SeriesStandings},
+    settings::TournamentTemplate,
+    tournament::{SeedOverrides, TournamentId},
+    venue::VenueMap,
+};
+
+use crate::{
+    extend,
+    sync::{AuditEntry, TournamentManager},
+};
 
 mod model;
 mod request;
@@ -35,7 +49,7 @@ pub struct ListTournaments;
 
 impl GetRequest<1> for ListTournaments {
     const ROUTE: Url<1> = extend!(TOURNAMENTS_ROUTE, LIST_TOURNAMENTS_ENDPOINT);
-    type Response = Vec<TournamentSummary>;
+    type Response = TournamentPage;
 }
 
 const SUBSCRIBE_ENDPOINT: Url<1> = Url::new("/subscribe/:t_id", [":t_id"]);
@@ -48,6 +62,231 @@ impl GetRequest<1> for Subscribe {
     type Response = ();
 }
 
+const SEAT_MAP_ENDPOINT: Url<1> = Url::new("/:t_id/seating", [":t_id"]);
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GetSeatMap;
+
+impl GetRequest<1> for GetSeatMap {
+    const ROUTE: Url<1> = extend!(TOURNAMENTS_ROUTE, SEAT_MAP_ENDPOINT);
+    type Response = Option<SeatMap>;
+}
+
+const STANDINGS_ENDPOINT: Url<1> = Url::new("/:t_id/standings", [":t_id"]);
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GetStandings;
+
+impl GetRequest<1> for GetStandings {
+    const ROUTE: Url<1> = extend!(TOURNAMENTS_ROUTE, STANDINGS_ENDPOINT);
+    type Response = Option<Vec<PublicStanding>>;
+}
+
+const FLIGHT_STANDINGS_ENDPOINT: Url<2> =
+    Url::new("/:t_id/flights/:flight/standings", [":t_id", ":flight"]);
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GetFlightStandings;
+
+impl GetRequest<2> for GetFlightStandings {
+    const ROUTE: Url<2> = extend!(TOURNAMENTS_ROUTE, FLIGHT_STANDINGS_ENDPOINT);
+    type Response = Option<Vec<PublicStanding>>;
+}
+
+const GROUP_STANDINGS_ENDPOINT: Url<2> = Url::new(
+    "/:t_id/pods/standings/:qualifiers",
+    [":t_id", ":qualifiers"],
+);
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GetGroupStandings;
+
+impl GetRequest<2> for GetGroupStandings {
+    const ROUTE: Url<2> = extend!(TOURNAMENTS_ROUTE, GROUP_STANDINGS_ENDPOINT);
+    type Response = Option<Vec<PublicGroupStandings>>;
+}
+
+const PAIRINGS_ENDPOINT: Url<2> = Url::new("/:t_id/pairings/:round", [":t_id", ":round"]);
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GetPairings;
+
+impl GetRequest<2> for GetPairings {
+    const ROUTE: Url<2> = extend!(TOURNAMENTS_ROUTE, PAIRINGS_ENDPOINT);
+    type Response = Option<Vec<PublicPairing>>;
+}
+
+const PLAYERS_ENDPOINT: Url<1> = Url::new("/:t_id/players", [":t_id"]);
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GetPlayers;
+
+impl GetRequest<1> for GetPlayers {
+    const ROUTE: Url<1> = extend!(TOURNAMENTS_ROUTE, PLAYERS_ENDPOINT);
+    type Response = Option<Vec<PublicPlayer>>;
+}
+
+const SCHEDULE_ENDPOINT: Url<1> = Url::new("/:t_id/schedule", [":t_id"]);
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GetSchedule;
+
+impl GetRequest<1> for GetSchedule {
+    const ROUTE: Url<1> = extend!(TOURNAMENTS_ROUTE, SCHEDULE_ENDPOINT);
+    type Response = Option<EventSchedule>;
+}
+
+const VENUE_ENDPOINT: Url<1> = Url::new("/:t_id/venue", [":t_id"]);
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GetVenue;
+
+impl GetRequest<1> for GetVenue {
+    const ROUTE: Url<1> = extend!(TOURNAMENTS_ROUTE, VENUE_ENDPOINT);
+    type Response = Option<VenueMap>;
+}
+
+const PAYOUTS_ENDPOINT: Url<1> = Url::new("/:t_id/payouts", [":t_id"]);
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GetPayouts;
+
+impl GetRequest<1> for GetPayouts {
+    const ROUTE: Url<1> = extend!(TOURNAMENTS_ROUTE, PAYOUTS_ENDPOINT);
+    type Response = Option<Vec<Payout>>;
+}
+
+const MY_ROUND_ENDPOINT: Url<1> = Url::new("/:t_id/my-round", [":t_id"]);
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GetMyRound;
+
+impl GetRequest<1> for GetMyRound {
+    const ROUTE: Url<1> = extend!(TOURNAMENTS_ROUTE, MY_ROUND_ENDPOINT);
+    type Response = Option<MyRound>;
+}
+
+const REPORT_ENDPOINT: Url<1> = Url::new("/:t_id/report", [":t_id"]);
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GetReport;
+
+impl GetRequest<1> for GetReport {
+    const ROUTE: Url<1> = extend!(TOURNAMENTS_ROUTE, REPORT_ENDPOINT);
+    type Response = String;
+}
+
+const EXPLAIN_SCORE_ENDPOINT: Url<2> = Url::new("/:t_id/standings/:p_id", [":t_id", ":p_id"]);
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExplainScore;
+
+impl GetRequest<2> for ExplainScore {
+    const ROUTE: Url<2> = extend!(TOURNAMENTS_ROUTE, EXPLAIN_SCORE_ENDPOINT);
+    type Response = Option<ScoreExplanation>;
+}
+
+const AUDIT_LOG_ENDPOINT: Url<1> = Url::new("/:t_id/audit", [":t_id"]);
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GetAuditLog;
+
+impl GetRequest<1> for GetAuditLog {
+    const ROUTE: Url<1> = extend!(TOURNAMENTS_ROUTE, AUDIT_LOG_ENDPOINT);
+    type Response = Option<Vec<AuditEntry>>;
+}
+
+const EXPORT_TOURNAMENT_ENDPOINT: Url<1> = Url::new("/:t_id/export", [":t_id"]);
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExportTournament;
+
+impl GetRequest<1> for ExportTournament {
+    const ROUTE: Url<1> = extend!(TOURNAMENTS_ROUTE, EXPORT_TOURNAMENT_ENDPOINT);
+    type Response = Option<SignedExport>;
+}
+
+const VERIFY_EXPORT_ENDPOINT: Url<0> = Url::from("/verify");
+
+impl PostRequest<0> for SignedExport {
+    const ROUTE: Url<0> = extend!(TOURNAMENTS_ROUTE, VERIFY_EXPORT_ENDPOINT);
+    type Response = ExportVerification;
+}
+
+const IMPORT_REGISTRATIONS_ENDPOINT: Url<1> = Url::new("/:t_id/import", [":t_id"]);
+
+impl PostRequest<1> for ImportRegistrations {
+    const ROUTE: Url<1> = extend!(TOURNAMENTS_ROUTE, IMPORT_REGISTRATIONS_ENDPOINT);
+    type Response = Result<RegistrationImport, ImportError>;
+}
+
+const CREATE_INVITE_ENDPOINT: Url<1> = Url::new("/:t_id/invite", [":t_id"]);
+
+impl PostRequest<1> for CreateInvite {
+    const ROUTE: Url<1> = extend!(TOURNAMENTS_ROUTE, CREATE_INVITE_ENDPOINT);
+    type Response = Result<TournamentInvite, InviteError>;
+}
+
+const CHECK_INVITE_ENDPOINT: Url<0> = Url::from("/invite/check");
+
+impl PostRequest<0> for CheckInvite {
+    const ROUTE: Url<0> = extend!(TOURNAMENTS_ROUTE, CHECK_INVITE_ENDPOINT);
+    type Response = Result<TournamentInvite, InviteError>;
+}
+
+const REPORT_TOKENS_ENDPOINT: Url<1> = Url::new("/:t_id/report_tokens", [":t_id"]);
+
+impl PostRequest<1> for CreateReportTokens {
+    const ROUTE: Url<1> = extend!(TOURNAMENTS_ROUTE, REPORT_TOKENS_ENDPOINT);
+    type Response = Result<Vec<ReportToken>, ReportError>;
+}
+
+const REPORT_RESULT_ENDPOINT: Url<0> = Url::from("/report");
+
+impl PostRequest<0> for ReportResult {
+    const ROUTE: Url<0> = extend!(TOURNAMENTS_ROUTE, REPORT_RESULT_ENDPOINT);
+    type Response = Result<(), ReportError>;
+}
+
+const WEBHOOKS_ENDPOINT: Url<1> = Url::new("/:t_id/webhooks", [":t_id"]);
+
+impl PostRequest<1> for RegisterWebhook {
+    const ROUTE: Url<1> = extend!(TOURNAMENTS_ROUTE, WEBHOOKS_ENDPOINT);
+    type Response = Result<Webhook, WebhookError>;
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ListWebhooks;
+
+impl GetRequest<1> for ListWebhooks {
+    const ROUTE: Url<1> = extend!(TOURNAMENTS_ROUTE, WEBHOOKS_ENDPOINT);
+    type Response = Option<Vec<Webhook>>;
+}
+
+const WEBHOOK_ENDPOINT: Url<2> = Url::new("/:t_id/webhooks/:w_id", [":t_id", ":w_id"]);
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DeleteWebhook;
+
+impl DeleteRequest<2> for DeleteWebhook {
+    const ROUTE: Url<2> = extend!(TOURNAMENTS_ROUTE, WEBHOOK_ENDPOINT);
+    type Response = bool;
+}
+
+const DECK_CHECK_ENDPOINT: Url<1> = Url::new("/:t_id/deck_check", [":t_id"]);
+
+impl PostRequest<1> for RequestDeckCheck {
+    const ROUTE: Url<1> = extend!(TOURNAMENTS_ROUTE, DECK_CHECK_ENDPOINT);
+    type Response = bool;
+}
+
+const CLONE_TOURNAMENT_ENDPOINT: Url<1> = Url::new("/:t_id/clone", [":t_id"]);
+
+impl PostRequest<1> for SeedOverrides {
+    const ROUTE: Url<1> = extend!(TOURNAMENTS_ROUTE, CLONE_TOURNAMENT_ENDPOINT);
+    type Response = Option<TournamentId>;
+}
+
 const IMPORT_TOURN_ENDPOINT: Url<0> = Url::from("/");
 
 impl PostRequest<0> for TournamentManager {
@@ -55,6 +294,57 @@ impl PostRequest<0> for TournamentManager {
     type Response = ();
 }
 
+/* ---------- Series Routes ---------- */
+const SERIES_ROUTE: Url<0> = extend!(API_BASE, "/series");
+
+const LIST_SERIES_ENDPOINT: Url<0> = Url::from("/list");
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ListSeries;
+
+impl GetRequest<0> for ListSeries {
+    const ROUTE: Url<0> = extend!(SERIES_ROUTE, LIST_SERIES_ENDPOINT);
+    type Response = Vec<Series>;
+}
+
+const GET_SERIES_ENDPOINT: Url<1> = Url::new("/:s_id", [":s_id"]);
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GetSeries;
+
+impl GetRequest<1> for GetSeries {
+    const ROUTE: Url<1> = extend!(SERIES_ROUTE, GET_SERIES_ENDPOINT);
+    type Response = Option<Series>;
+}
+
+const SERIES_STANDINGS_ENDPOINT: Url<1> = Url::new("/:s_id/standings", [":s_id"]);
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GetSeriesStandings;
+
+impl GetRequest<1> for GetSeriesStandings {
+    const ROUTE: Url<1> = extend!(SERIES_ROUTE, SERIES_STANDINGS_ENDPOINT);
+    type Response = Option<SeriesStandings>;
+}
+
+impl PostRequest<0> for Series {
+    const ROUTE: Url<0> = SERIES_ROUTE;
+    type Response = bool;
+}
+
+/* ---------- Organization Routes ---------- */
+const ORGS_ROUTE: Url<0> = extend!(API_BASE, "/orgs");
+
+const ORG_EVENTS_ENDPOINT: Url<1> = Url::new("/:o_id/events", [":o_id"]);
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GetOrgEvents;
+
+impl GetRequest<1> for GetOrgEvents {
+    const ROUTE: Url<1> = extend!(ORGS_ROUTE, ORG_EVENTS_ENDPOINT);
+    type Response = Option<Vec<LifecycleEvent>>;
+}
+
 /* ---------- Account Routes ---------- */
 const ACCOUNTS_ROUTE: Url<0> = extend!(API_BASE, "/accounts");
 
@@ -73,6 +363,79 @@ impl DeleteRequest<0> for AccountCrud {
     type Response = bool;
 }
 
+const TEMPLATES_ENDPOINT: Url<0> = Url::from("/templates");
+
+impl PostRequest<0> for TournamentTemplate {
+    const ROUTE: Url<0> = extend!(ACCOUNTS_ROUTE, TEMPLATES_ENDPOINT);
+    type Response = bool;
+}
+
+impl GetRequest<0> for ListTemplates {
+    const ROUTE: Url<0> = extend!(ACCOUNTS_ROUTE, TEMPLATES_ENDPOINT);
+    type Response = Vec<TournamentTemplate>;
+}
+
+const PASSWORD_ENDPOINT: Url<0> = Url::from("/password");
+
+impl PostRequest<0> for ChangePassword {
+    const ROUTE: Url<0> = extend!(ACCOUNTS_ROUTE, PASSWORD_ENDPOINT);
+    type Response = Result<(), PasswordError>;
+}
+
+const PASSWORD_RESET_ENDPOINT: Url<0> = Url::from("/password/reset");
+
+impl PostRequest<0> for RequestPasswordReset {
+    const ROUTE: Url<0> = extend!(ACCOUNTS_ROUTE, PASSWORD_RESET_ENDPOINT);
+    type Response = ();
+}
+
+const CONFIRM_RESET_ENDPOINT: Url<0> = Url::from("/password/reset/confirm");
+
+impl PostRequest<0> for ResetPassword {
+    const ROUTE: Url<0> = extend!(ACCOUNTS_ROUTE, CONFIRM_RESET_ENDPOINT);
+    type Response = Result<(), PasswordError>;
+}
+
+const NOTIFICATIONS_ENDPOINT: Url<0> = Url::from("/notifications");
+
+impl PostRequest<0> for NotificationPrefs {
+    const ROUTE: Url<0> = extend!(ACCOUNTS_ROUTE, NOTIFICATIONS_ENDPOINT);
+    type Response = bool;
+}
+
+impl GetRequest<0> for GetNotificationPrefs {
+    const ROUTE: Url<0> = extend!(ACCOUNTS_ROUTE, NOTIFICATIONS_ENDPOINT);
+    type Response = NotificationPrefs;
+}
+
+const PROFILE_ENDPOINT: Url<0> = Url::from("/profile");
+
+impl PostRequest<0> for AccountProfile {
+    const ROUTE: Url<0> = extend!(ACCOUNTS_ROUTE, PROFILE_ENDPOINT);
+    type Response = Result<(), ProfileError>;
+}
+
+const PUBLIC_PROFILE_ENDPOINT: Url<1> = Url::new("/:acc_id/profile", [":acc_id"]);
+
+impl GetRequest<1> for GetProfile {
+    const ROUTE: Url<1> = extend!(ACCOUNTS_ROUTE, PUBLIC_PROFILE_ENDPOINT);
+    type Response = Option<PublicProfile>;
+}
+
+const HISTORY_ENDPOINT: Url<1> = Url::new("/:acc_id/history", [":acc_id"]);
+
+impl GetRequest<1> for GetHistory {
+    const ROUTE: Url<1> = extend!(ACCOUNTS_ROUTE, HISTORY_ENDPOINT);
+    type Response = Option<Vec<HistoryEntry>>;
+}
+
+const STATS_ENDPOINT: Url<1> = Url::new("/:acc_id/stats", [":acc_id"]);
+
+impl GetRequest<1> for GetStats {
+    const ROUTE: Url<1> = extend!(ACCOUNTS_ROUTE, STATS_ENDPOINT);
+    type Response = Option<PlayerStats>;
+}
+
 /* ---------- Session Routes ---------- */
 const SESSION_ROUTE: Url<0> = extend!(API_BASE, "/session");
 
@@ -90,6 +453,88 @@ impl PostRequest<0> for GuestSession {
     type Response = ();
 }
 
+const HANDOFF_ENDPOINT: Url<0> = Url::from("/handoff");
+
+impl PostRequest<0> for HandoffSession {
+    const ROUTE: Url<0> = extend!(SESSION_ROUTE, HANDOFF_ENDPOINT);
+    type Response = DateTime<Utc>;
+}
+
+const OAUTH_ENDPOINT: Url<1> = Url::new("/oauth/:provider", [":provider"]);
+
+impl GetRequest<1> for GetOAuthRedirect {
+    const ROUTE: Url<1> = extend!(SESSION_ROUTE, OAUTH_ENDPOINT);
+    type Response = Option<OAuthRedirect>;
+}
+
+const OAUTH_LINK_ENDPOINT: Url<1> = Url::new("/oauth/:provider/link", [":provider"]);
+
+impl PostRequest<1> for LinkIdentity {
+    const ROUTE: Url<1> = extend!(SESSION_ROUTE, OAUTH_LINK_ENDPOINT);
+    type Response = Result<(), LinkError>;
+}
+
+const API_TOKENS_ENDPOINT: Url<0> = Url::from("/tokens");
+
+impl PostRequest<0> for CreateApiToken {
+    const ROUTE: Url<0> = extend!(SESSION_ROUTE, API_TOKENS_ENDPOINT);
+    type Response = NewApiToken;
+}
+
+impl GetRequest<0> for ListApiTokens {
+    const ROUTE: Url<0> = extend!(SESSION_ROUTE, API_TOKENS_ENDPOINT);
+    type Response = Vec<ApiToken>;
+}
+
+const API_TOKEN_ENDPOINT: Url<1> = Url::new("/tokens/:tok_id", [":tok_id"]);
+
+impl DeleteRequest<1> for RevokeApiToken {
+    const ROUTE: Url<1> = extend!(SESSION_ROUTE, API_TOKEN_ENDPOINT);
+    type Response = bool;
+}
+
+const SESSIONS_ENDPOINT: Url<0> = Url::from("/devices");
+
+impl GetRequest<0> for ListSessions {
+    const ROUTE: Url<0> = extend!(SESSION_ROUTE, SESSIONS_ENDPOINT);
+    type Response = Vec<SessionInfo>;
+}
+
+const SESSION_ENDPOINT: Url<1> = Url::new("/devices/:sess_id", [":sess_id"]);
+
+impl DeleteRequest<1> for RevokeSession {
+    const ROUTE: Url<1> = extend!(SESSION_ROUTE, SESSION_ENDPOINT);
+    type Response = bool;
+}
+
+const DEVICE_ENDPOINT: Url<0> = Url::from("/device");
+
+impl PostRequest<0> for NameSession {
+    const ROUTE: Url<0> = extend!(SESSION_ROUTE, DEVICE_ENDPOINT);
+    type Response = bool;
+}
+
+const INVITES_ENDPOINT: Url<0> = Url::from("/invites");
+
+impl PostRequest<0> for AcceptInvite {
+    const ROUTE: Url<0> = extend!(SESSION_ROUTE, INVITES_ENDPOINT);
+    type Response = Result<TournamentInvite, InviteError>;
+}
+
+const REDEEM_INVITE_ENDPOINT: Url<0> = Url::from("/invites/redeem");
+
+impl PostRequest<0> for RedeemInvite {
+    const ROUTE: Url<0> = extend!(SESSION_ROUTE, REDEEM_INVITE_ENDPOINT);
+    type Response = Result<TournamentInvite, InviteError>;
+}
+
+const UPGRADE_ENDPOINT: Url<0> = Url::from("/upgrade");
+
+impl PostRequest<0> for UpgradeGuest {
+    const ROUTE: Url<0> = extend!(SESSION_ROUTE, UPGRADE_ENDPOINT);
+    type Response = Result<GuestUpgrade, UpgradeError>;
+}
+
 impl GetRequest<0> for GetSessionStatus {
     const ROUTE: Url<0> = SESSION_ROUTE;
     type Response = SessionStatus;
@@ -118,6 +563,8 @@ impl GetRequest<0> for GetVersion {
 
 #[cfg(test)]
 mod tests {
+    use squire_lib::{series::Series, settings::TournamentTemplate, tournament::SeedOverrides};
+
     use crate::api::*;
 
     #[test]
@@ -135,6 +582,106 @@ mod tests {
             <ListTournaments as GetRequest<1>>::ROUTE.as_str(),
             "/api/v1/tournaments/list/:page"
         );
+        assert_eq!(
+            <GetSeatMap as GetRequest<1>>::ROUTE.as_str(),
+            "/api/v1/tournaments/:t_id/seating"
+        );
+        assert_eq!(
+            <GetStandings as GetRequest<1>>::ROUTE.as_str(),
+            "/api/v1/tournaments/:t_id/standings"
+        );
+        assert_eq!(
+            <GetFlightStandings as GetRequest<2>>::ROUTE.as_str(),
+            "/api/v1/tournaments/:t_id/flights/:flight/standings"
+        );
+        assert_eq!(
+            <GetGroupStandings as GetRequest<2>>::ROUTE.as_str(),
+            "/api/v1/tournaments/:t_id/pods/standings/:qualifiers"
+        );
+        assert_eq!(
+            <GetPairings as GetRequest<2>>::ROUTE.as_str(),
+            "/api/v1/tournaments/:t_id/pairings/:round"
+        );
+        assert_eq!(
+            <GetPlayers as GetRequest<1>>::ROUTE.as_str(),
+            "/api/v1/tournaments/:t_id/players"
+        );
+        assert_eq!(
+            <GetSchedule as GetRequest<1>>::ROUTE.as_str(),
+            "/api/v1/tournaments/:t_id/schedule"
+        );
+        assert_eq!(
+            <GetVenue as GetRequest<1>>::ROUTE.as_str(),
+            "/api/v1/tournaments/:t_id/venue"
+        );
+        assert_eq!(
+            <GetPayouts as GetRequest<1>>::ROUTE.as_str(),
+            "/api/v1/tournaments/:t_id/payouts"
+        );
+        assert_eq!(
+            <GetMyRound as GetRequest<1>>::ROUTE.as_str(),
+            "/api/v1/tournaments/:t_id/my-round"
+        );
+        assert_eq!(
+            <GetReport as GetRequest<1>>::ROUTE.as_str(),
+            "/api/v1/tournaments/:t_id/report"
+        );
+        assert_eq!(
+            <ExplainScore as GetRequest<2>>::ROUTE.as_str(),
+            "/api/v1/tournaments/:t_id/standings/:p_id"
+        );
+        assert_eq!(
+            <GetAuditLog as GetRequest<1>>::ROUTE.as_str(),
+            "/api/v1/tournaments/:t_id/audit"
+        );
+        assert_eq!(
+            <ExportTournament as GetRequest<1>>::ROUTE.as_str(),
+            "/api/v1/tournaments/:t_id/export"
+        );
+        assert_eq!(
+            <SignedExport as PostRequest<0>>::ROUTE.as_str(),
+            "/api/v1/tournaments/verify"
+        );
+        assert_eq!(
+            <ImportRegistrations as PostRequest<1>>::ROUTE.as_str(),
+            "/api/v1/tournaments/:t_id/import"
+        );
+        assert_eq!(
+            <CreateInvite as PostRequest<1>>::ROUTE.as_str(),
+            "/api/v1/tournaments/:t_id/invite"
+        );
+        assert_eq!(
+            <CheckInvite as PostRequest<0>>::ROUTE.as_str(),
+            "/api/v1/tournaments/invite/check"
+        );
+        assert_eq!(
+            <CreateReportTokens as PostRequest<1>>::ROUTE.as_str(),
+            "/api/v1/tournaments/:t_id/report_tokens"
+        );
+        assert_eq!(
+            <ReportResult as PostRequest<0>>::ROUTE.as_str(),
+            "/api/v1/tournaments/report"
+        );
+        assert_eq!(
+            <RegisterWebhook as PostRequest<1>>::ROUTE.as_str(),
+            "/api/v1/tournaments/:t_id/webhooks"
+        );
+        assert_eq!(
+            <ListWebhooks as GetRequest<1>>::ROUTE.as_str(),
+            "/api/v1/tournaments/:t_id/webhooks"
+        );
+        assert_eq!(
+            <DeleteWebhook as DeleteRequest<2>>::ROUTE.as_str(),
+            "/api/v1/tournaments/:t_id/webhooks/:w_id"
+        );
+        assert_eq!(
+            <RequestDeckCheck as PostRequest<1>>::ROUTE.as_str(),
+            "/api/v1/tournaments/:t_id/deck_check"
+        );
+        assert_eq!(
+            <SeedOverrides as PostRequest<1>>::ROUTE.as_str(),
+            "/api/v1/tournaments/:t_id/clone"
+        );
     }
 
     #[test]
@@ -146,5 +693,118 @@ mod tests {
             <GetVersion as GetRequest<0>>::ROUTE.as_str(),
             "/api/v1/version"
         );
+        assert_eq!(
+            <ListTemplates as GetRequest<0>>::ROUTE.as_str(),
+            "/api/v1/accounts/templates"
+        );
+        assert_eq!(
+            <TournamentTemplate as PostRequest<0>>::ROUTE.as_str(),
+            "/api/v1/accounts/templates"
+        );
+        assert_eq!(
+            <ChangePassword as PostRequest<0>>::ROUTE.as_str(),
+            "/api/v1/accounts/password"
+        );
+        assert_eq!(
+            <RequestPasswordReset as PostRequest<0>>::ROUTE.as_str(),
+            "/api/v1/accounts/password/reset"
+        );
+        assert_eq!(
+            <ResetPassword as PostRequest<0>>::ROUTE.as_str(),
+            "/api/v1/accounts/password/reset/confirm"
+        );
+        assert_eq!(
+            <GetNotificationPrefs as GetRequest<0>>::ROUTE.as_str(),
+            "/api/v1/accounts/notifications"
+        );
+        assert_eq!(
+            <NotificationPrefs as PostRequest<0>>::ROUTE.as_str(),
+            "/api/v1/accounts/notifications"
+        );
+        assert_eq!(
+            <AccountProfile as PostRequest<0>>::ROUTE.as_str(),
+            "/api/v1/accounts/profile"
+        );
+        assert_eq!(
+            <GetProfile as GetRequest<1>>::ROUTE.as_str(),
+            "/api/v1/accounts/:acc_id/profile"
+        );
+        assert_eq!(
+            <GetHistory as GetRequest<1>>::ROUTE.as_str(),
+            "/api/v1/accounts/:acc_id/history"
+        );
+        assert_eq!(
+            <GetStats as GetRequest<1>>::ROUTE.as_str(),
+            "/api/v1/accounts/:acc_id/stats"
+        );
+        assert_eq!(
+            <GetOAuthRedirect as GetRequest<1>>::ROUTE.as_str(),
+            "/api/v1/session/oauth/:provider"
+        );
+        assert_eq!(
+            <LinkIdentity as PostRequest<1>>::ROUTE.as_str(),
+            "/api/v1/session/oauth/:provider/link"
+        );
+        assert_eq!(
+            <CreateApiToken as PostRequest<0>>::ROUTE.as_str(),
+            "/api/v1/session/tokens"
+        );
+        assert_eq!(
+            <ListApiTokens as GetRequest<0>>::ROUTE.as_str(),
+            "/api/v1/session/tokens"
+        );
+        assert_eq!(
+            <RevokeApiToken as DeleteRequest<1>>::ROUTE.as_str(),
+            "/api/v1/session/tokens/:tok_id"
+        );
+        assert_eq!(
+            <ListSessions as GetRequest<0>>::ROUTE.as_str(),
+            "/api/v1/session/devices"
+        );
+        assert_eq!(
+            <RevokeSession as DeleteRequest<1>>::ROUTE.as_str(),
+            "/api/v1/session/devices/:sess_id"
+        );
+        assert_eq!(
+            <NameSession as PostRequest<0>>::ROUTE.as_str(),
+            "/api/v1/session/device"
+        );
+        assert_eq!(
+            <AcceptInvite as PostRequest<0>>::ROUTE.as_str(),
+            "/api/v1/session/invites"
+        );
+        assert_eq!(
+            <RedeemInvite as PostRequest<0>>::ROUTE.as_str(),
+            "/api/v1/session/invites/redeem"
+        );
+        assert_eq!(
+            <UpgradeGuest as PostRequest<0>>::ROUTE.as_str(),
+            "/api/v1/session/upgrade"
+        );
+    }
+
+    #[test]
+    fn verify_series_routes() {
+        assert_eq!(
+            <ListSeries as GetRequest<0>>::ROUTE.as_str(),
+            "/api/v1/series/list"
+        );
+        assert_eq!(
+            <GetSeries as GetRequest<1>>::ROUTE.as_str(),
+            "/api/v1/series/:s_id"
+        );
+        assert_eq!(
+            <GetSeriesStandings as GetRequest<1>>::ROUTE.as_str(),
+            "/api/v1/series/:s_id/standings"
+        );
+        assert_eq!(<Series as PostRequest<0>>::ROUTE.as_str(), "/api/v1/series");
+    }
+
+    #[test]
+    fn verify_org_routes() {
+        assert_eq!(
+            <GetOrgEvents as GetRequest<1>>::ROUTE.as_str(),
+            "/api/v1/orgs/:o_id/events"
+        );
     }
 }
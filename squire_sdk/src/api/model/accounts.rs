@@ -1,12 +1,28 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use squire_lib::{
+    accounts::{Platform, SquireAccount},
+    identifiers::{PlayerId, SquireAccountId},
+    rounds::Round,
+};
+
+use super::{Credentials, HeadToHead, MatchRecord, TournamentSummary};
+use crate::sync::TournamentManager;
 
-use super::Credentials;
+/// The shortest password that the backend accepts
+pub const MIN_PASSWORD_LENGTH: usize = 8;
 
 #[derive(Debug, Clone, Serialize, Deserialize, Hash, PartialEq, Eq)]
 pub struct RegForm {
     pub username: String,
     pub display_name: String,
+    /// Must be at least [MIN_PASSWORD_LENGTH] characters long
     pub password: String,
+    /// The address that password reset tokens are sent to
+    #[serde(default)]
+    pub email: Option<String>,
 }
 
 impl From<RegForm> for Credentials {
@@ -21,3 +37,412 @@ impl From<RegForm> for Credentials {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AccountCrud;
+
+/// The request type taken by the `accounts/password` SC API, which changes the user's password
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ChangePassword {
+    /// The user's current password
+    pub old_password: String,
+    /// Must be at least [MIN_PASSWORD_LENGTH] characters long
+    pub new_password: String,
+}
+
+/// The request type taken by the `accounts/password/reset` SC API. If the account has an email
+/// address, a reset token is sent to it. The backend responds the same way whether or not the
+/// account exists, so the API can't be used to discover usernames.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct RequestPasswordReset {
+    /// The username of the account
+    pub username: String,
+}
+
+/// The request type taken by the `accounts/password/reset/confirm` SC API, which sets a new
+/// password using a token from [RequestPasswordReset]. Tokens can only be used once.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ResetPassword {
+    /// The token that was sent to the account's email address
+    pub token: String,
+    /// Must be at least [MIN_PASSWORD_LENGTH] characters long
+    pub new_password: String,
+}
+
+/// The reasons that a password could not be changed
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum PasswordError {
+    /// The given current password is wrong
+    IncorrectPassword,
+    /// The new password is shorter than [MIN_PASSWORD_LENGTH]
+    TooShort,
+    /// The reset token is unknown, has expired, or has already been used
+    InvalidToken,
+    /// The new password could not be saved
+    NotSaved,
+}
+
+/// The request type taken by the `accounts/templates` SC API, which lists the tournament templates
+/// saved to the user's account. Templates are saved by posting a `TournamentTemplate` to the same
+/// route.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ListTemplates;
+
+/// The things that a player can opt in to being notified about
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Hash, PartialEq, Eq)]
+pub enum NotificationKind {
+    /// The player was paired for a new round
+    NewPairing,
+    /// A new round of a tournament that the player is in has started
+    RoundStarting,
+    /// A judge asked the player to bring their deck for a deck check
+    DeckCheck,
+}
+
+/// Where a notification is delivered
+#[derive(Debug, Clone, Serialize, Deserialize, Hash, PartialEq, Eq)]
+pub enum NotificationChannel {
+    /// An email address
+    Email(String),
+    /// A browser's web push subscription, as returned by `PushManager.subscribe`
+    WebPush {
+        /// The URL of the push service
+        endpoint: String,
+    },
+}
+
+/// A user's notification preferences, which are stored alongside their account. Notifications
+/// are opt-in, so no notifications are sent by default.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct NotificationPrefs {
+    /// The places that notifications are sent to
+    pub channels: Vec<NotificationChannel>,
+    /// The kinds of notifications that the user wants
+    pub kinds: Vec<NotificationKind>,
+}
+
+impl NotificationPrefs {
+    /// Returns if the user wants notifications of the given kind
+    pub fn wants(&self, kind: NotificationKind) -> bool {
+        !self.channels.is_empty() && self.kinds.contains(&kind)
+    }
+}
+
+/// The request type taken by the `accounts/notifications` SC API, which returns the user's
+/// notification preferences. Preferences are updated by posting a [NotificationPrefs] to the same
+/// route.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GetNotificationPrefs;
+
+/// The longest bio that the backend accepts, in characters
+pub const MAX_BIO_LENGTH: usize = 500;
+
+/// The optional parts of a user's profile, which are stored alongside their account. These are
+/// kept out of [SquireAccount] since accounts are copied into the tournaments that the user is in.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct AccountProfile {
+    /// A link to the user's avatar. Must be an `http` or `https` URL.
+    pub avatar_url: Option<String>,
+    /// The user's pronouns, e.g. "they/them"
+    pub pronouns: Option<String>,
+    /// A short description of the user. Must be at most [MAX_BIO_LENGTH] characters long.
+    pub bio: Option<String>,
+    /// The country that the user plays in, as an ISO 3166-1 alpha-2 code (e.g. "US")
+    pub country: Option<String>,
+}
+
+impl AccountProfile {
+    /// Checks that the profile can be saved
+    pub fn validate(&self) -> Result<(), ProfileError> {
+        if let Some(url) = &self.avatar_url {
+            if !(url.starts_with("https://") || url.starts_with("http://")) {
+                return Err(ProfileError::InvalidAvatar);
+            }
+        }
+        if let Some(bio) = &self.bio {
+            if bio.chars().count() > MAX_BIO_LENGTH {
+                return Err(ProfileError::BioTooLong);
+            }
+        }
+        if let Some(country) = &self.country {
+            if country.len() != 2 || !country.chars().all(|c| c.is_ascii_uppercase()) {
+                return Err(ProfileError::InvalidCountry);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The reasons that a profile could not be saved
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ProfileError {
+    /// The avatar URL is not an `http` or `https` URL
+    InvalidAvatar,
+    /// The bio is longer than [MAX_BIO_LENGTH]
+    BioTooLong,
+    /// The country is not an ISO 3166-1 alpha-2 code
+    InvalidCountry,
+    /// The profile could not be saved
+    NotSaved,
+}
+
+/// The request type taken by the `accounts/<id>/profile` SC API, which returns an account's public
+/// profile. Users update their own profile by posting an [AccountProfile] to `accounts/profile`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GetProfile;
+
+/// An account's profile as it is shown to others. Users that do not share their name (see
+/// [SharingPermissions](squire_lib::accounts::SharingPermissions)) only have their id shown.
+/// Users always see the whole of their own profile.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PublicProfile {
+    /// The account's id
+    pub id: SquireAccountId,
+    /// The account's display name
+    pub display_name: Option<String>,
+    /// The account's names on other platforms
+    pub gamer_tags: Vec<(Platform, String)>,
+    /// The rest of the account's profile
+    pub profile: AccountProfile,
+}
+
+impl PublicProfile {
+    /// Creates the profile of the account that is shown to the given viewer
+    pub fn new(
+        account: &SquireAccount,
+        profile: AccountProfile,
+        viewer: Option<SquireAccountId>,
+    ) -> Self {
+        if viewer != Some(account.id) && !account.permissions.shares_name() {
+            return Self {
+                id: account.id,
+                display_name: None,
+                gamer_tags: Vec::new(),
+                profile: AccountProfile::default(),
+            };
+        }
+        let mut gamer_tags: Vec<_> = account
+            .gamer_tags
+            .iter()
+            .map(|(platform, tag)| (platform.clone(), tag.clone()))
+            .collect();
+        gamer_tags.sort_by(|a, b| a.1.cmp(&b.1));
+        Self {
+            id: account.id,
+            display_name: Some(account.display_name.clone()),
+            gamer_tags,
+            profile,
+        }
+    }
+}
+
+/// The request type taken by the `accounts/<id>/history` SC API, which lists the tournaments that
+/// an account played in, most recent first
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GetHistory;
+
+/// A tournament that a user played in
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct HistoryEntry {
+    /// The tournament
+    pub tourn: TournamentSummary,
+    /// The user's id as a player in the tournament
+    pub player: PlayerId,
+    /// When the tournament last changed
+    pub last_active: Option<DateTime<Utc>>,
+    /// The user's match record
+    pub record: MatchRecord,
+    /// The user's place in the standings, starting at 1. Once the tournament has ended, this is
+    /// their final placement. Players that dropped are not placed.
+    pub place: Option<usize>,
+    /// The user's record against each of their opponents
+    #[serde(default)]
+    pub opponents: Vec<HeadToHead>,
+}
+
+impl HistoryEntry {
+    /// Calculates the entry of every player in the tournament
+    pub fn for_tourn(tourn: &TournamentManager) -> Vec<Self> {
+        let summary = TournamentSummary::from(tourn);
+        let last_active = tourn.op_log().last_op().map(|op| op.salt);
+        let standings = tourn.get_standings();
+        tourn
+            .player_reg
+            .players
+            .keys()
+            .map(|id| Self {
+                tourn: summary.clone(),
+                player: *id,
+                last_active,
+                record: Self::record(tourn, id),
+                opponents: Self::opponents(tourn, id),
+                place: standings
+                    .scores
+                    .iter()
+                    .position(|(p, _)| p == id)
+                    .map(|i| i + 1),
+            })
+            .collect()
+    }
+
+    fn record(tourn: &TournamentManager, id: &PlayerId) -> MatchRecord {
+        let mut digest = MatchRecord::default();
+        for rnd in Self::played_rounds(tourn, id) {
+            digest.add_round(rnd, id);
+        }
+        digest
+    }
+
+    fn opponents(tourn: &TournamentManager, id: &PlayerId) -> Vec<HeadToHead> {
+        let mut records: HashMap<PlayerId, MatchRecord> = HashMap::new();
+        for rnd in Self::played_rounds(tourn, id).filter(|r| !r.is_bye()) {
+            for opp in rnd.players.iter().filter(|p| *p != id) {
+                records.entry(*opp).or_default().add_round(rnd, id);
+            }
+        }
+        let mut digest: Vec<_> = records
+            .into_iter()
+            .map(|(opponent, record)| HeadToHead {
+                opponent,
+                name: tourn
+                    .player_reg
+                    .get_player(&opponent)
+                    .map(|p| p.public_name(tourn.settings.public_names))
+                    .unwrap_or_default(),
+                record,
+            })
+            .collect();
+        digest.sort_by_key(|h2h| h2h.opponent);
+        digest
+    }
+
+    /// Returns the certified rounds that the player played in
+    fn played_rounds<'a>(
+        tourn: &'a TournamentManager,
+        id: &'a PlayerId,
+    ) -> impl Iterator<Item = &'a Round> {
+        tourn
+            .round_reg
+            .rounds
+            .values()
+            .filter(move |r| r.is_certified() && r.players.contains(id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use squire_lib::{
+        accounts::SharingPermissions,
+        identifiers::{AdminId, PlayerId},
+        operations::{AdminOp, JudgeOp, PlayerOp, TournOp},
+        rounds::RoundResult,
+    };
+    use squire_tests::{get_seed, spoof_account};
+
+    use super::{
+        AccountProfile, HistoryEntry, MatchRecord, ProfileError, PublicProfile, MAX_BIO_LENGTH,
+    };
+    use crate::sync::TournamentManager;
+
+    fn profile() -> AccountProfile {
+        AccountProfile {
+            avatar_url: Some("https://example.com/avatar.png".to_owned()),
+            pronouns: Some("they/them".to_owned()),
+            bio: Some("Limited enjoyer".to_owned()),
+            country: Some("CA".to_owned()),
+        }
+    }
+
+    #[test]
+    fn profiles_are_validated() {
+        assert_eq!(profile().validate(), Ok(()));
+        assert_eq!(AccountProfile::default().validate(), Ok(()));
+
+        let mut bad = profile();
+        bad.avatar_url = Some("javascript:alert(1)".to_owned());
+        assert_eq!(bad.validate(), Err(ProfileError::InvalidAvatar));
+
+        let mut bad = profile();
+        bad.bio = Some("a".repeat(MAX_BIO_LENGTH + 1));
+        assert_eq!(bad.validate(), Err(ProfileError::BioTooLong));
+
+        let mut bad = profile();
+        bad.country = Some("Canada".to_owned());
+        assert_eq!(bad.validate(), Err(ProfileError::InvalidCountry));
+    }
+
+    #[test]
+    fn profiles_respect_sharing_permissions() {
+        let mut account = spoof_account();
+        let shown = PublicProfile::new(&account, profile(), None);
+        assert_eq!(shown.display_name, Some(account.display_name.clone()));
+        assert_eq!(shown.profile, profile());
+
+        // Users that don't share their name are hidden from others, but not from themselves
+        account.permissions = SharingPermissions::OnlyDeckList;
+        let hidden = PublicProfile::new(&account, profile(), None);
+        assert_eq!(hidden.display_name, None);
+        assert_eq!(hidden.profile, AccountProfile::default());
+        let own = PublicProfile::new(&account, profile(), Some(account.id));
+        assert_eq!(own.profile, profile());
+    }
+
+    #[test]
+    fn history_entries_hold_records_and_places() {
+        let admin = spoof_account();
+        let admin_id: AdminId = admin.id.0.into();
+        let mut tourn = TournamentManager::new(admin, get_seed());
+        let plyrs: Vec<PlayerId> = (0..2)
+            .map(|_| {
+                tourn
+                    .apply_op(TournOp::RegisterPlayer(spoof_account(), None))
+                    .unwrap()
+                    .assume_register_player()
+            })
+            .collect();
+        _ = tourn
+            .apply_op(TournOp::AdminOp(admin_id, AdminOp::Start))
+            .unwrap();
+        let r_id = tourn
+            .apply_op(TournOp::AdminOp(
+                admin_id,
+                AdminOp::CreateRound(plyrs.clone()),
+            ))
+            .unwrap()
+            .assume_create_round();
+        _ = tourn
+            .apply_op(TournOp::PlayerOp(
+                plyrs[0],
+                PlayerOp::RecordResult(r_id, RoundResult::Wins(plyrs[0], 2)),
+            ))
+            .unwrap();
+        _ = tourn
+            .apply_op(TournOp::JudgeOp(
+                admin_id.into(),
+                JudgeOp::ConfirmRound(r_id),
+            ))
+            .unwrap();
+
+        let entries = HistoryEntry::for_tourn(&tourn);
+        assert_eq!(entries.len(), 2);
+        let winner = entries.iter().find(|e| e.player == plyrs[0]).unwrap();
+        let loser = entries.iter().find(|e| e.player == plyrs[1]).unwrap();
+        assert_eq!(
+            winner.record,
+            MatchRecord {
+                wins: 1,
+                ..Default::default()
+            }
+        );
+        assert_eq!(
+            loser.record,
+            MatchRecord {
+                losses: 1,
+                ..Default::default()
+            }
+        );
+        assert_eq!(winner.opponents.len(), 1);
+        assert_eq!(winner.opponents[0].opponent, plyrs[1]);
+        assert_eq!(winner.opponents[0].record, winner.record);
+        assert_eq!(winner.place, Some(1));
+        assert_eq!(loser.place, Some(2));
+        assert!(winner.last_active.is_some());
+    }
+}
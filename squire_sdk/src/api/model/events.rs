@@ -0,0 +1,109 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use squire_lib::{
+    identifiers::{SquireAccountId, TournamentId},
+    operations::{AdminOp, TournOp},
+};
+
+use crate::{response::SquireResponse, sync::full_op::FullOp};
+
+/// The kinds of high-level changes in a tournament's lifecycle
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LifecycleEventKind {
+    /// The tournament was imported to the server
+    Created,
+    /// The tournament was started
+    Started,
+    /// The tournament was cut to the given number of players, starting its next stage
+    StageChanged(usize),
+    /// The tournament was ended
+    Ended,
+    /// The tournament was cancelled before it started
+    Cancelled,
+}
+
+/// A high-level change in a tournament's lifecycle. Events are recorded by the server for the
+/// organization that runs the tournament, which is the account that created it.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct LifecycleEvent {
+    /// The organization that runs the tournament
+    pub org: SquireAccountId,
+    /// The tournament that the event is for
+    pub tourn: TournamentId,
+    /// What happened
+    pub kind: LifecycleEventKind,
+    /// The account that caused the event
+    pub actor: SquireAccountId,
+    /// When the event happened
+    pub time: DateTime<Utc>,
+}
+
+impl LifecycleEvent {
+    /// Creates the event for a tournament being imported to the server
+    pub fn created(org: SquireAccountId, tourn: TournamentId, actor: SquireAccountId) -> Self {
+        Self {
+            org,
+            tourn,
+            kind: LifecycleEventKind::Created,
+            actor,
+            time: Utc::now(),
+        }
+    }
+
+    /// Creates the event caused by an operation, if the operation changes the tournament's
+    /// lifecycle. The time of the event is the time the operation was made.
+    pub fn from_op(org: SquireAccountId, tourn: TournamentId, op: &FullOp) -> Option<Self> {
+        let TournOp::AdminOp(a_id, a_op) = &op.op else {
+            return None;
+        };
+        let kind = match a_op {
+            AdminOp::Start => LifecycleEventKind::Started,
+            AdminOp::Cut(len) => LifecycleEventKind::StageChanged(*len),
+            AdminOp::End => LifecycleEventKind::Ended,
+            AdminOp::Cancel => LifecycleEventKind::Cancelled,
+            _ => return None,
+        };
+        Some(Self {
+            org,
+            tourn,
+            kind,
+            // Admins have the same ids as their accounts
+            actor: a_id.0.into(),
+            time: op.salt,
+        })
+    }
+}
+
+/// The response type used by the `orgs/<id>/events` SC API. The option encodes that only the
+/// organization can see its event history.
+pub type GetOrgEventsResponse = SquireResponse<Option<Vec<LifecycleEvent>>>;
+
+#[cfg(test)]
+mod tests {
+    use squire_lib::{
+        identifiers::AdminId,
+        operations::{AdminOp, TournOp},
+    };
+    use squire_tests::spoof_account;
+
+    use super::{LifecycleEvent, LifecycleEventKind};
+    use crate::sync::full_op::FullOp;
+
+    #[test]
+    fn lifecycle_events_from_ops() {
+        let acc = spoof_account();
+        let admin_id: AdminId = acc.id.0.into();
+        let org = spoof_account().id;
+        let tourn = Default::default();
+
+        let op = FullOp::new(TournOp::AdminOp(admin_id, AdminOp::Cut(8)));
+        let event = LifecycleEvent::from_op(org, tourn, &op).unwrap();
+        assert_eq!(event.kind, LifecycleEventKind::StageChanged(8));
+        assert_eq!(event.actor, acc.id);
+        assert_eq!(event.org, org);
+
+        // Operations that do not change the tournament's lifecycle are not events
+        let op = FullOp::new(TournOp::AdminOp(admin_id, AdminOp::Freeze));
+        assert!(LifecycleEvent::from_op(org, tourn, &op).is_none());
+    }
+}
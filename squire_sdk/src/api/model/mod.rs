@@ -1,13 +1,25 @@
 /// Request/response types for accounts
 mod accounts;
+/// Request/response types for tournament lifecycle events
+mod events;
+/// Request/response types for tournament series
+mod series;
 /// Request/response types for session
 mod session;
+/// Request/response types for account statistics
+mod stats;
 /// Request/response types for SquireCore tournament apis
 mod tournaments;
 /// Request/response types for server version
 mod version;
+/// Request/response types for tournament webhooks
+mod webhooks;
 
 pub use accounts::*;
+pub use events::*;
+pub use series::*;
 pub use session::*;
+pub use stats::*;
 pub use tournaments::*;
 pub use version::*;
+pub use webhooks::*;
@@ -0,0 +1,18 @@
+use squire_lib::series::{Series, SeriesStandings};
+
+use crate::response::SquireResponse;
+
+/// The response type used by the `series/list` SC API.
+pub type ListSeriesResponse = SquireResponse<Vec<Series>>;
+
+/// The response type used by the `series/<id>` SC API. The option encodes that the requested
+/// series might not be found.
+pub type GetSeriesResponse = SquireResponse<Option<Series>>;
+
+/// The response type used by the `series/<id>/standings` SC API. The option encodes that the
+/// requested series might not be found.
+pub type GetSeriesStandingsResponse = SquireResponse<Option<SeriesStandings>>;
+
+/// The response type used when posting a `Series` to the SC API. The bool encodes whether the
+/// series was saved.
+pub type SaveSeriesResponse = SquireResponse<bool>;
@@ -1,9 +1,84 @@
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use squire_lib::accounts::SquireAccount;
+use squire_lib::{accounts::SquireAccount, identifiers::TypeId};
+
+use crate::api::{InviteError, RegForm, SessionToken, TournamentInvite};
 
 #[derive(Debug, Clone, Serialize, Deserialize, Hash, PartialEq, Eq)]
 pub enum Credentials {
-    Basic { username: String, password: String },
+    Basic {
+        username: String,
+        password: String,
+    },
+    /// An authorization code that an OAuth provider gave the user after they logged in with it,
+    /// along with the `state` that the provider sent back. The backend only accepts unexpired
+    /// states that it issued in an [OAuthRedirect] for the same provider, and then exchanges the
+    /// code for the user's identity with the provider.
+    OAuth {
+        provider: OAuthProvider,
+        code: String,
+        state: String,
+    },
+}
+
+/// The external identity providers that users can log in with instead of a password
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Hash, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum OAuthProvider {
+    Google,
+    Discord,
+}
+
+impl OAuthProvider {
+    /// The name of the provider, as it appears in the `session/oauth/<provider>` SC API
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OAuthProvider::Google => "google",
+            OAuthProvider::Discord => "discord",
+        }
+    }
+}
+
+/// The request type taken by the `session/oauth/<provider>` SC API, which returns where users
+/// should be sent to log in with the provider
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GetOAuthRedirect;
+
+/// Where a user should be sent to log in with an OAuth provider. Once the user logs in, the
+/// provider redirects them back to the app with a `code` and a `state` query parameter. The
+/// `state` should match the one given here, and both are then used to log in with
+/// [Credentials::OAuth].
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct OAuthRedirect {
+    /// The provider's login page
+    pub url: String,
+    /// A value signed by the backend that the provider sends back, which guards against forged
+    /// redirects. The backend only accepts it for a few minutes.
+    pub state: String,
+}
+
+/// The request type taken by the `session/oauth/<provider>/link` SC API, which links the identity
+/// that the user logged in to the provider with to their account. Afterwards, they can log in with
+/// either their password or the provider. The `code` and `state` are the same as in
+/// [Credentials::OAuth].
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct LinkIdentity {
+    pub code: String,
+    pub state: String,
+}
+
+/// The reasons that an external identity could not be linked to an account
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum LinkError {
+    /// The provider is not configured, the state was not accepted, or the provider did not vouch
+    /// for the code
+    NotVerified,
+    /// The identity is already linked to a different account
+    LinkedToOtherAccount,
+    /// The link could not be saved
+    NotSaved,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Hash, PartialEq, Eq)]
@@ -12,6 +87,14 @@ pub struct Login(pub Credentials);
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GuestSession;
 
+/// How long the guest session of a connection handle lasts before the backend expires it
+pub const HANDOFF_LIFETIME: Duration = Duration::from_secs(300);
+
+/// Requests a short-lived guest session for a connection handle. The backend expires the session
+/// once [HANDOFF_LIFETIME] has passed and returns when that will happen.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HandoffSession;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Terminate;
 
@@ -30,3 +113,132 @@ pub enum SessionStatus {
     ExpiredGuest,
     UnknownUser,
 }
+
+/// A type-checked Uuid for API tokens
+pub type ApiTokenId = TypeId<ApiToken>;
+
+/// A long-lived token that scripts and bots use to act as a user. Unlike sessions, API tokens do
+/// not expire and are only invalidated when they are revoked. The token itself is only given out
+/// when it is created.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ApiToken {
+    /// The token's id, which is used to revoke it
+    pub id: ApiTokenId,
+    /// A name that the user gave the token, so they can tell their tokens apart
+    pub name: String,
+    /// When the token was created
+    pub created: DateTime<Utc>,
+}
+
+/// The request type taken by the `session/tokens` SC API, which creates an API token
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct CreateApiToken {
+    /// The name of the new token
+    pub name: String,
+}
+
+/// A newly created API token. The token is sent in the `Authorization` header, optionally with a
+/// `Bearer` prefix, in the same way as a session token.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct NewApiToken {
+    /// The info about the token
+    pub info: ApiToken,
+    /// The token itself. It can not be retrieved again, so it should be stored by the user.
+    pub token: SessionToken,
+}
+
+/// The request type taken by the `session/tokens` SC API, which lists the user's API tokens
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ListApiTokens;
+
+/// The request type taken by the `session/tokens/<id>` SC API, which revokes an API token
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RevokeApiToken;
+
+/// A type-checked Uuid for sessions
+pub type SessionId = TypeId<SessionInfo>;
+
+/// One of the sessions that a user is logged in with, e.g. one on each of their devices. The
+/// session token itself is never shared.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SessionInfo {
+    /// The session's id, which is used to revoke it
+    pub id: SessionId,
+    /// A name that the user gave the device that the session is on, if any
+    pub device: Option<String>,
+    /// When the session was created
+    pub created: DateTime<Utc>,
+    /// The last time that the session was used
+    pub last_active: DateTime<Utc>,
+    /// The IP address that the session was created from, if it is known
+    pub ip: Option<String>,
+    /// Whether this is the session that asked for the list
+    pub current: bool,
+}
+
+/// The request type taken by the `session/devices` SC API, which lists the user's sessions
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ListSessions;
+
+/// The request type taken by the `session/devices/<id>` SC API, which revokes one of the user's
+/// sessions
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RevokeSession;
+
+/// The request type taken by the `session/device` SC API, which names the device that the current
+/// session is on
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct NameSession {
+    /// The name of the device
+    pub device: String,
+}
+
+/// The request type taken by the `session/invites` SC API, which accepts an invite as a guest.
+/// Roles can only be given to accounts, so guests hold on to the invites that they accept until
+/// they register an account (see [UpgradeGuest]).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct AcceptInvite {
+    /// The token of the invite, as given by [TournamentInvite::token]
+    pub token: String,
+}
+
+/// The request type taken by the `session/invites/redeem` SC API, which gives the user's account
+/// the role that an invite grants
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct RedeemInvite {
+    /// The token of the invite, as given by [TournamentInvite::token]
+    pub token: String,
+}
+
+/// The request type taken by the `session/upgrade` SC API, which registers an account for a
+/// guest. The guest's session becomes a session for the new account, so their connections to
+/// tournaments are kept, and the account is given the roles of the invites that they accepted.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct UpgradeGuest(pub RegForm);
+
+/// A guest that has registered an account
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct GuestUpgrade {
+    /// The new account
+    pub account: SquireAccount,
+    /// The invites whose roles were given to the account
+    pub granted: Vec<TournamentInvite>,
+    /// The invites whose roles could not be given to the account, along with why, e.g. because
+    /// the tournament was deleted or its registration closed. Those that failed with
+    /// `InviteError::NotOwner` can be redeemed again later.
+    #[serde(default)]
+    pub not_granted: Vec<(TournamentInvite, InviteError)>,
+}
+
+/// The reasons that a guest could not register an account
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum UpgradeError {
+    /// The session is not an active guest session
+    NotGuest,
+    /// Another account already has the username
+    UsernameTaken,
+    /// The password is shorter than [MIN_PASSWORD_LENGTH](crate::api::MIN_PASSWORD_LENGTH)
+    PasswordTooShort,
+    /// The account could not be created
+    NotCreated,
+}
@@ -0,0 +1,231 @@
+use std::{collections::HashMap, ops::AddAssign};
+
+use serde::{Deserialize, Serialize};
+use squire_lib::{identifiers::PlayerId, rounds::Round};
+
+use super::HistoryEntry;
+
+/// The request type taken by the `accounts/<id>/stats` SC API, which summarizes every tournament
+/// that an account played in
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GetStats;
+
+/// A player's match record in a tournament. Only certified rounds are counted.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct MatchRecord {
+    /// The rounds that the player won
+    pub wins: u32,
+    /// The rounds that someone else won
+    pub losses: u32,
+    /// The rounds that no one won
+    pub draws: u32,
+    /// The byes that the player was given
+    pub byes: u32,
+}
+
+impl MatchRecord {
+    /// Adds the result of a certified round to the given player's record
+    pub(crate) fn add_round(&mut self, rnd: &Round, id: &PlayerId) {
+        match rnd.winner {
+            _ if rnd.is_bye() => self.byes += 1,
+            Some(winner) if winner == *id => self.wins += 1,
+            Some(_) => self.losses += 1,
+            None => self.draws += 1,
+        }
+    }
+
+    /// The number of matches played, not including byes
+    pub fn matches(&self) -> u32 {
+        self.wins + self.losses + self.draws
+    }
+
+    /// The share of the matches played that were won. Byes are not counted. Returns `None` if no
+    /// matches have been played.
+    pub fn win_rate(&self) -> Option<f64> {
+        (self.matches() != 0).then(|| f64::from(self.wins) / f64::from(self.matches()))
+    }
+}
+
+impl AddAssign for MatchRecord {
+    fn add_assign(&mut self, rhs: Self) {
+        self.wins += rhs.wins;
+        self.losses += rhs.losses;
+        self.draws += rhs.draws;
+        self.byes += rhs.byes;
+    }
+}
+
+/// A player's record in the rounds that they played with another player
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct HeadToHead {
+    /// The other player's id. Players with accounts have the same id in every tournament.
+    pub opponent: PlayerId,
+    /// The other player's public name
+    pub name: String,
+    /// The player's record in the rounds that they shared with the other player
+    pub record: MatchRecord,
+}
+
+/// The number of tournaments that a player played in a format
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct FormatCount {
+    /// The format
+    pub format: String,
+    /// The number of tournaments
+    pub tournaments: u32,
+}
+
+/// A summary of every tournament that an account played in
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct PlayerStats {
+    /// The number of tournaments played in
+    pub tournaments: u32,
+    /// The lifetime match record
+    pub record: MatchRecord,
+    /// The share of the matches played that were won, if any have been played
+    pub win_rate: Option<f64>,
+    /// The formats played, most played first
+    pub formats: Vec<FormatCount>,
+    /// The player's record against each of their opponents, most played first
+    pub head_to_head: Vec<HeadToHead>,
+}
+
+impl PlayerStats {
+    /// Aggregates the entries of a player's tournament history
+    pub fn new(history: &[HistoryEntry]) -> Self {
+        let mut record = MatchRecord::default();
+        let mut formats: HashMap<&str, u32> = HashMap::new();
+        let mut opponents: HashMap<PlayerId, HeadToHead> = HashMap::new();
+        // Opponents keep the name that they had in the most recent tournament
+        let mut entries: Vec<_> = history.iter().collect();
+        entries.sort_by(|a, b| b.last_active.cmp(&a.last_active));
+        for entry in entries {
+            record += entry.record;
+            *formats.entry(entry.tourn.format.as_str()).or_default() += 1;
+            for opp in &entry.opponents {
+                opponents
+                    .entry(opp.opponent)
+                    .and_modify(|h2h| h2h.record += opp.record)
+                    .or_insert_with(|| opp.clone());
+            }
+        }
+        let mut formats: Vec<_> = formats
+            .into_iter()
+            .map(|(format, tournaments)| FormatCount {
+                format: format.to_owned(),
+                tournaments,
+            })
+            .collect();
+        formats.sort_by(|a, b| {
+            b.tournaments
+                .cmp(&a.tournaments)
+                .then_with(|| a.format.cmp(&b.format))
+        });
+        let mut head_to_head: Vec<_> = opponents.into_values().collect();
+        head_to_head.sort_by(|a, b| {
+            b.record
+                .matches()
+                .cmp(&a.record.matches())
+                .then_with(|| a.name.cmp(&b.name))
+        });
+        Self {
+            tournaments: history.len() as u32,
+            record,
+            win_rate: record.win_rate(),
+            formats,
+            head_to_head,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use squire_lib::{identifiers::PlayerId, tournament::TournamentStatus};
+    use uuid::Uuid;
+
+    use super::{FormatCount, HeadToHead, MatchRecord, PlayerStats};
+    use crate::api::{HistoryEntry, TournamentSummary};
+
+    fn entry(format: &str, record: MatchRecord, opponents: Vec<HeadToHead>) -> HistoryEntry {
+        HistoryEntry {
+            tourn: TournamentSummary {
+                id: Uuid::new_v4().into(),
+                name: "Tournament".to_owned(),
+                format: format.to_owned(),
+                status: TournamentStatus::Ended,
+            },
+            player: PlayerId::new(Uuid::new_v4()),
+            last_active: None,
+            record,
+            place: None,
+            opponents,
+        }
+    }
+
+    #[test]
+    fn stats_sum_up_history() {
+        let rival = PlayerId::new(Uuid::new_v4());
+        let vs_rival = |wins, losses| HeadToHead {
+            opponent: rival,
+            name: "Rival".to_owned(),
+            record: MatchRecord {
+                wins,
+                losses,
+                ..Default::default()
+            },
+        };
+        let history = vec![
+            entry(
+                "Pioneer",
+                MatchRecord {
+                    wins: 2,
+                    losses: 1,
+                    byes: 1,
+                    ..Default::default()
+                },
+                vec![vs_rival(1, 0)],
+            ),
+            entry(
+                "Modern",
+                MatchRecord {
+                    wins: 1,
+                    losses: 1,
+                    draws: 1,
+                    ..Default::default()
+                },
+                vec![vs_rival(0, 1)],
+            ),
+            entry("Pioneer", MatchRecord::default(), Vec::new()),
+        ];
+
+        let stats = PlayerStats::new(&history);
+        assert_eq!(stats.tournaments, 3);
+        assert_eq!(
+            stats.record,
+            MatchRecord {
+                wins: 3,
+                losses: 2,
+                draws: 1,
+                byes: 1,
+            }
+        );
+        assert_eq!(stats.win_rate, Some(0.5));
+        assert_eq!(
+            stats.formats,
+            vec![
+                FormatCount {
+                    format: "Pioneer".to_owned(),
+                    tournaments: 2,
+                },
+                FormatCount {
+                    format: "Modern".to_owned(),
+                    tournaments: 1,
+                },
+            ]
+        );
+        assert_eq!(stats.head_to_head, vec![vs_rival(1, 1)]);
+
+        // Players that have not played a match have no win rate
+        assert_eq!(PlayerStats::new(&[]).win_rate, None);
+    }
+}
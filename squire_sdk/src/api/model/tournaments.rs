@@ -1,13 +1,31 @@
+use std::time::Duration;
+
+use chrono::{DateTime, TimeZone, Utc};
 use serde::{Deserialize, Serialize};
-use squire_lib::tournament::TournamentStatus;
+use squire_lib::{
+    error::TournamentError,
+    identifiers::{PlayerId, RoundId},
+    operations::TournOp,
+    players::{Player, PlayerStatus},
+    prizes::Payout,
+    r64,
+    report::ReportKind,
+    rounds::{Round, RoundResult, RoundStatus},
+    schedule::EventSchedule,
+    scoring::{ScoreExplanation, StandardScore, Standings},
+    seating::DEFAULT_POD_SIZE,
+    settings::PublicNameStyle,
+    tournament::TournamentStatus,
+    venue::VenueMap,
+};
 
 use crate::{
     model::{
-        identifiers::TournamentId,
+        identifiers::{SquireAccountId, TournamentId},
         tournament::{Tournament, TournamentPreset},
     },
     response::SquireResponse,
-    sync::TournamentManager,
+    sync::{AuditEntry, TournamentManager},
 };
 
 /// Information useful for understanding the tournament at a glance, as well as for performing a
@@ -42,6 +60,36 @@ impl From<&TournamentManager> for TournamentSummary {
     }
 }
 
+/// The current state of a tournament, as shown on an organizer's dashboard
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
+pub struct TournamentOverview {
+    /// The tournament's id
+    pub id: TournamentId,
+    /// The display name of the tournament
+    pub name: String,
+    /// The status of the tournament
+    pub status: TournamentStatus,
+    /// The number of the latest pairing round, which is zero before the first round is paired
+    pub current_round: u64,
+    /// The number of rounds that are not yet confirmed
+    pub active_rounds: usize,
+    /// The number of players that are still in the tournament
+    pub players: usize,
+}
+
+impl From<&Tournament> for TournamentOverview {
+    fn from(value: &Tournament) -> Self {
+        Self {
+            id: value.id,
+            name: value.name.clone(),
+            status: value.status,
+            current_round: value.round_reg.pairing_round_count(),
+            active_rounds: value.round_reg.active_round_count(),
+            players: value.player_reg.active_player_count(),
+        }
+    }
+}
+
 fn default_page_size() -> usize {
     20
 }
@@ -56,16 +104,748 @@ pub struct ListPageSize {
     pub page_size: usize,
 }
 
-/// The response type used by the `tournaments/list/<page>[?page_size=number]` SC API. The vector
-/// returned contains a list of tournament summaries, which each contain an ID which can be used to
-/// query more about the tournament. The vector does not necessarily contain as many elements as the
-/// page size, *even when you haven't reached the end of the complete list of tournaments*.
-pub type ListTournamentsResponse = SquireResponse<Vec<TournamentSummary>>;
+/// The filters taken as query parameters by the `tournaments/list/<page>` SC API, e.g.
+/// `?status=Started&format=Pioneer`. Every filter is optional, and a tournament is only listed if
+/// it matches all of the given filters. Unlisted and private tournaments are never listed.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq)]
+pub struct TournamentFilter {
+    /// Only list tournaments with this status
+    pub status: Option<TournamentStatus>,
+    /// Only list tournaments with exactly this format
+    pub format: Option<String>,
+    /// Only list tournaments that were created by this account
+    pub organizer: Option<SquireAccountId>,
+    /// Only list tournaments whose last operation was applied at or after this time. Tournaments
+    /// without any operations are not listed when this is given.
+    pub after: Option<DateTime<Utc>>,
+    /// Only list tournaments whose last operation was applied before this time. Tournaments without
+    /// any operations are not listed when this is given.
+    pub before: Option<DateTime<Utc>>,
+}
+
+impl TournamentFilter {
+    /// Returns if the tournament matches every filter. Tournaments that are unlisted or private
+    /// never match.
+    pub fn matches(&self, tourn: &TournamentManager) -> bool {
+        let last_active = tourn.op_log().last_op().map(|op| op.salt);
+        tourn.settings.visibility.is_listed()
+            && self.status.map_or(true, |status| tourn.status == status)
+            && self
+                .format
+                .as_ref()
+                .map_or(true, |format| tourn.settings.format == *format)
+            && self
+                .organizer
+                .map_or(true, |id| tourn.seed_and_creator().1.id == id)
+            && self
+                .after
+                .map_or(true, |after| last_active.is_some_and(|t| t >= after))
+            && self
+                .before
+                .map_or(true, |before| last_active.is_some_and(|t| t < before))
+    }
+}
+
+/// The filters taken as query parameters by the `tournaments/<id>/audit` SC API, e.g.
+/// `?player=<id>`. Every filter is optional, and an entry is only listed if it matches all of the
+/// given filters.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct AuditFilter {
+    /// Only list operations that acted on this player
+    pub player: Option<PlayerId>,
+    /// Only list operations that acted on this round
+    pub round: Option<RoundId>,
+    /// Only list operations that were submitted by this account
+    pub account: Option<SquireAccountId>,
+}
+
+impl AuditFilter {
+    /// Returns if the entry matches every filter
+    pub fn matches(&self, entry: &AuditEntry) -> bool {
+        self.player.map_or(true, |id| entry.affects_player(id))
+            && self.round.map_or(true, |id| entry.affects_round(id))
+            && self.account.map_or(true, |id| entry.account == id)
+    }
+}
+
+/// A page of the tournaments that match a [TournamentFilter], as returned by the
+/// `tournaments/list/<page>` SC API
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
+pub struct TournamentPage {
+    /// The summaries of the tournaments on this page, starting with the latest registered one
+    pub summaries: Vec<TournamentSummary>,
+    /// The index of this page, starting at 0
+    pub page: usize,
+    /// The maximum number of summaries on a page
+    pub page_size: usize,
+    /// The number of tournaments that match the filter across all pages
+    pub total: usize,
+}
+
+impl TournamentPage {
+    /// Returns the number of pages needed to list every matching tournament
+    pub fn page_count(&self) -> usize {
+        match self.page_size {
+            0 => 0,
+            size => self.total.div_ceil(size),
+        }
+    }
+}
+
+fn default_pod_size() -> usize {
+    DEFAULT_POD_SIZE
+}
+
+/// The formats that the `tournaments/<id>/seating` SC API can render a seat map in.
+#[derive(Deserialize, Serialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SeatMapFormat {
+    /// The seat map is returned as JSON
+    #[default]
+    Json,
+    /// The seat map is returned as a printable HTML page
+    Html,
+    /// The seat map is returned as a CSV
+    Csv,
+}
+
+/// The query parameters used by the `tournaments/<id>/seating[?pod_size=number&format=html]` SC
+/// API. The pod size defaults to 8 and the format defaults to JSON.
+#[derive(Deserialize, Debug)]
+pub struct SeatMapQuery {
+    #[serde(default = "default_pod_size")]
+    pub pod_size: usize,
+    #[serde(default)]
+    pub format: SeatMapFormat,
+}
+
+/// The query parameters used by the `tournaments/<id>/report[?kind=standings]` SC API. The kind
+/// defaults to pairings by table.
+#[derive(Deserialize, Debug)]
+pub struct ReportQuery {
+    #[serde(default)]
+    pub kind: ReportKind,
+}
+
+/// A player as shown by the public, read-only tournament APIs
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
+pub struct PublicPlayer {
+    /// The player's id
+    pub id: PlayerId,
+    /// The player's name
+    pub name: String,
+    /// The player's registration status
+    pub status: PlayerStatus,
+}
+
+impl PublicPlayer {
+    /// Creates the public view of a player, with their name shown in the given style
+    pub fn with_name_style(plyr: &Player, style: PublicNameStyle) -> Self {
+        Self {
+            name: plyr.public_name(style),
+            ..Self::from(plyr)
+        }
+    }
+}
+
+impl From<&Player> for PublicPlayer {
+    fn from(value: &Player) -> Self {
+        Self {
+            id: value.id,
+            name: value.name.clone(),
+            status: value.status,
+        }
+    }
+}
+
+/// A single round of a pairing round, as shown by the public, read-only tournament APIs
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
+pub struct PublicPairing {
+    /// The round's match number
+    pub match_number: u64,
+    /// The round's table number
+    pub table_number: u64,
+    /// The venue zone that the round's table is in, if the tournament has a venue
+    #[serde(default)]
+    pub zone: Option<String>,
+    /// The players in the round
+    pub players: Vec<PublicPlayer>,
+    /// Whether the round is a bye
+    pub is_bye: bool,
+    /// The status of the round
+    pub status: RoundStatus,
+    /// The winner of the round, if the result has been recorded and there is a winner
+    pub winner: Option<PlayerId>,
+}
+
+impl PublicPairing {
+    /// Creates the public view of one of the tournament's rounds. Players' names are shown in the
+    /// style set by the tournament.
+    pub fn new(tourn: &Tournament, rnd: &Round) -> Self {
+        Self {
+            match_number: rnd.match_number,
+            table_number: rnd.table_number,
+            zone: tourn
+                .round_reg
+                .zone_of(rnd.table_number)
+                .map(|z| z.name.clone()),
+            players: rnd
+                .players
+                .iter()
+                .filter_map(|p| tourn.player_reg.get_player(p).ok())
+                .map(|p| PublicPlayer::with_name_style(p, tourn.settings.public_names))
+                .collect(),
+            is_bye: rnd.is_bye(),
+            status: rnd.status,
+            winner: rnd.winner,
+        }
+    }
+}
+
+/// The requesting player's current round, as returned by the `tournaments/<id>/my-round` SC API.
+/// This is just enough for a player to find their seat without syncing the whole tournament.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
+pub struct MyRound {
+    /// The round's id
+    pub id: RoundId,
+    /// The round's match number
+    pub match_number: u64,
+    /// The round's table number
+    pub table_number: u64,
+    /// The venue zone that the round's table is in (e.g. the hall), if the tournament has a venue
+    #[serde(default)]
+    pub zone: Option<String>,
+    /// The other players in the round
+    pub opponents: Vec<PublicPlayer>,
+    /// Whether the round is a bye
+    pub is_bye: bool,
+    /// When the round's time runs out, factoring in time extensions
+    pub deadline: DateTime<Utc>,
+    /// The time that was left in the round when it was looked up
+    pub time_left: Duration,
+}
+
+impl MyRound {
+    /// Creates the given player's view of one of their rounds at the given time. The opponents'
+    /// names are shown in the style set by the tournament.
+    pub fn new(tourn: &Tournament, rnd: &Round, p_id: PlayerId, now: DateTime<Utc>) -> Self {
+        Self {
+            id: rnd.id,
+            match_number: rnd.match_number,
+            table_number: rnd.table_number,
+            zone: tourn
+                .round_reg
+                .zone_of(rnd.table_number)
+                .map(|z| z.name.clone()),
+            opponents: rnd
+                .players
+                .iter()
+                .filter(|p| **p != p_id)
+                .filter_map(|p| tourn.player_reg.get_player(p).ok())
+                .map(|p| PublicPlayer::with_name_style(p, tourn.settings.public_names))
+                .collect(),
+            is_bye: rnd.is_bye(),
+            deadline: rnd.deadline(),
+            time_left: rnd.time_left_at(now),
+        }
+    }
+}
+
+/// The standings within one of a tournament's draft pods, as shown by the public, read-only
+/// tournament APIs
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+pub struct PublicGroupStandings {
+    /// The standings of the players in the pod. Ranks are the players' places within the pod.
+    pub standings: Vec<PublicStanding>,
+    /// The players that qualify for the next stage, in order
+    pub qualified: Vec<PlayerId>,
+}
+
+/// A player's place in the standings, as shown by the public, read-only tournament APIs. The
+/// scores are given as decimals and the percentages range from 0 to 1.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+pub struct PublicStanding {
+    /// The player's place in the standings, starting at 1
+    pub rank: usize,
+    /// The player's id
+    pub player: PlayerId,
+    /// The player's name
+    pub name: String,
+    /// The player's match points
+    pub match_points: f64,
+    /// The player's game points
+    pub game_points: f64,
+    /// The player's match win percentage
+    pub mwp: f64,
+    /// The player's game win percentage
+    pub gwp: f64,
+    /// The average match win percentage of the player's opponents
+    pub opp_mwp: f64,
+    /// The average game win percentage of the player's opponents
+    pub opp_gwp: f64,
+}
+
+impl PublicStanding {
+    /// Calculates the public view of the tournament's current standings. Players' names are shown
+    /// in the style set by the tournament.
+    pub fn standings(tourn: &Tournament) -> Vec<Self> {
+        Self::from_standings(tourn, tourn.get_standings())
+    }
+
+    /// Calculates the public view of the current standings of one of the tournament's
+    /// (one-indexed) flights
+    pub fn flight_standings(tourn: &Tournament, flight: usize) -> Vec<Self> {
+        Self::from_standings(tourn, tourn.get_flight_standings(flight))
+    }
+
+    /// Calculates the public view of the current standings within each of the tournament's draft
+    /// pods, where the top `qualifiers` players of each pod qualify for the next stage
+    pub fn group_standings(tourn: &Tournament, qualifiers: usize) -> Vec<PublicGroupStandings> {
+        tourn
+            .get_group_standings(&tourn.pairing_sys.pods.pods, qualifiers)
+            .into_iter()
+            .map(|group| PublicGroupStandings {
+                qualified: group.qualified().copied().collect(),
+                standings: Self::from_standings(tourn, group.standings),
+            })
+            .collect()
+    }
+
+    fn from_standings(tourn: &Tournament, standings: Standings<StandardScore>) -> Vec<Self> {
+        standings
+            .scores
+            .into_iter()
+            .enumerate()
+            .map(|(i, (player, score))| Self {
+                rank: i + 1,
+                player,
+                name: tourn
+                    .player_reg
+                    .get_player(&player)
+                    .map(|p| p.public_name(tourn.settings.public_names))
+                    .unwrap_or_default(),
+                match_points: to_f64(score.match_points),
+                game_points: to_f64(score.game_points),
+                mwp: to_f64(score.mwp),
+                gwp: to_f64(score.gwp),
+                opp_mwp: to_f64(score.opp_mwp),
+                opp_gwp: to_f64(score.opp_gwp),
+            })
+            .collect()
+    }
+}
+
+fn to_f64(val: r64) -> f64 {
+    f64::from(*val.numer()) / f64::from(*val.denom())
+}
+
+/// A tournament export that has been signed by the server. The digests are hex-encoded SHA-256
+/// hashes of the tournament's final state and of its operations log. The signature covers the
+/// tournament's id, both digests, and the signing time, so any edit made to the export after it
+/// was signed can be detected by the server that signed it.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct SignedExport {
+    /// The exported tournament, including its operations log
+    pub tourn: TournamentManager,
+    /// The digest of the tournament's final state
+    pub state_digest: String,
+    /// The digest of the tournament's operations log
+    pub log_digest: String,
+    /// When the export was signed
+    pub signed_at: DateTime<Utc>,
+    /// The hex-encoded signature of the export
+    pub signature: String,
+}
+
+/// The outcome of checking a [SignedExport] using the `tournaments/verify` SC API.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum ExportVerification {
+    /// The export was signed by the server and has not been edited since
+    Verified,
+    /// The tournament no longer matches the digests in the export
+    Modified,
+    /// The signature does not match the export's digests, so the digests (or signing time) were
+    /// edited or the export was not signed by this server
+    InvalidSignature,
+}
+
+/// The roles that an invite to a tournament can grant
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum InviteRole {
+    /// The invitee is preregistered as a player
+    Player,
+    /// The invitee joins the tournament's staff as a judge
+    Judge,
+    /// The invitee can follow the tournament
+    Spectator,
+}
+
+/// The request type taken by the `tournaments/<id>/invite` SC API. Admins can invite users to any
+/// role, but judges can only invite players and spectators.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub struct CreateInvite {
+    /// The role that the invite grants
+    pub role: InviteRole,
+    /// When the invite stops being accepted. This is rounded down to the second.
+    pub expires: DateTime<Utc>,
+}
+
+/// A shareable invite to a tournament, signed by the server that created it. The invite can be
+/// passed around as a single token (see [TournamentInvite::token]), so that TOs can hand it out as
+/// part of a link without looking up the accounts of the invitees.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct TournamentInvite {
+    /// The tournament that the invite is for
+    pub tourn: TournamentId,
+    /// The role that the invite grants
+    pub role: InviteRole,
+    /// When the invite stops being accepted
+    pub expires: DateTime<Utc>,
+    /// A random number that identifies the invite, so that it can be revoked (see
+    /// `AdminOp::RevokeInvite`)
+    pub nonce: u64,
+    /// The hex-encoded signature of the invite
+    pub signature: String,
+}
+
+impl TournamentInvite {
+    /// The header that an invite's token is sent in. Private tournaments can only be followed by
+    /// their officials and players, or with an invite to the tournament.
+    pub const HEADER_NAME: &'static str = "x-squire-invite";
+
+    /// Encodes the invite as a URL-safe token
+    pub fn token(&self) -> String {
+        let role = match self.role {
+            InviteRole::Player => "p",
+            InviteRole::Judge => "j",
+            InviteRole::Spectator => "s",
+        };
+        format!(
+            "{}.{role}.{}.{}.{}",
+            self.tourn,
+            self.expires.timestamp(),
+            self.nonce,
+            self.signature
+        )
+    }
+
+    /// Decodes a token created by [TournamentInvite::token]. The signature is not checked here.
+    pub fn from_token(token: &str) -> Option<Self> {
+        let mut parts = token.split('.');
+        let tourn = parts.next()?.parse().ok()?;
+        let role = match parts.next()? {
+            "p" => InviteRole::Player,
+            "j" => InviteRole::Judge,
+            "s" => InviteRole::Spectator,
+            _ => return None,
+        };
+        let expires = Utc.timestamp_opt(parts.next()?.parse().ok()?, 0).single()?;
+        let nonce = parts.next()?.parse().ok()?;
+        let signature = parts.next()?.to_owned();
+        parts.next().is_none().then_some(Self {
+            tourn,
+            role,
+            expires,
+            nonce,
+            signature,
+        })
+    }
+
+    /// Returns if the invite is no longer accepted at the given time
+    pub fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        self.expires <= now
+    }
+}
+
+/// The request type taken by the `tournaments/invite/check` SC API
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct CheckInvite {
+    /// The token of the invite, as given by [TournamentInvite::token]
+    pub token: String,
+}
+
+/// The request type taken by the `tournaments/<id>/deck_check` SC API, which notifies players that
+/// a judge wants to check their decks. Only players that opted in to deck check notifications are
+/// notified.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct RequestDeckCheck {
+    /// The players whose decks will be checked
+    pub players: Vec<PlayerId>,
+}
+
+/// The reasons that an invite could not be created or accepted
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum InviteError {
+    /// The tournament could not be found
+    TournamentNotFound,
+    /// The user is not allowed to invite others to the requested role
+    Unauthorized,
+    /// The invite has expired or would expire immediately
+    Expired,
+    /// The token is malformed or was not signed by this server
+    InvalidToken,
+    /// The invite has been revoked, or it was a staff invite that has already been used
+    Revoked,
+    /// The role that the invite grants could not be given to the user
+    NotRedeemed(TournamentError),
+    /// Another server hosts the tournament, so the invite could not be redeemed here. It can be
+    /// redeemed again later.
+    NotOwner,
+}
+
+/// The request type taken by the `tournaments/<id>/report_tokens` SC API. A reporting token is
+/// created for each player in the round.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub struct CreateReportTokens {
+    /// The round that the tokens are for
+    pub round: RoundId,
+    /// When the tokens stop being accepted. This is rounded down to the second.
+    pub expires: DateTime<Utc>,
+}
+
+/// A token that lets a player report the result of one of their rounds without logging in, signed
+/// by the server that created it. Officials of paper events can print the tokens (see
+/// [ReportToken::token]) as QR codes on the match slips, which players scan to report.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct ReportToken {
+    /// The tournament that the round is in
+    pub tourn: TournamentId,
+    /// The round that the result is reported for
+    pub round: RoundId,
+    /// The player that reports the result
+    pub player: PlayerId,
+    /// When the token stops being accepted
+    pub expires: DateTime<Utc>,
+    /// The hex-encoded signature of the token
+    pub signature: String,
+}
+
+impl ReportToken {
+    /// Encodes the reporting token as a URL-safe token
+    pub fn token(&self) -> String {
+        format!(
+            "{}.{}.{}.{}.{}",
+            self.tourn,
+            self.round,
+            self.player,
+            self.expires.timestamp(),
+            self.signature
+        )
+    }
+
+    /// Decodes a token created by [ReportToken::token]. The signature is not checked here.
+    pub fn from_token(token: &str) -> Option<Self> {
+        let mut parts = token.split('.');
+        let tourn = parts.next()?.parse().ok()?;
+        let round = parts.next()?.parse().ok()?;
+        let player = parts.next()?.parse().ok()?;
+        let expires = Utc.timestamp_opt(parts.next()?.parse().ok()?, 0).single()?;
+        let signature = parts.next()?.to_owned();
+        parts.next().is_none().then_some(Self {
+            tourn,
+            round,
+            player,
+            expires,
+            signature,
+        })
+    }
+
+    /// Returns if the token is no longer accepted at the given time
+    pub fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        self.expires <= now
+    }
+}
+
+/// The request type taken by the `tournaments/report` SC API. The result is recorded on behalf of
+/// the player that the token was created for.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct ReportResult {
+    /// The reporting token, as given by [ReportToken::token]
+    pub token: String,
+    /// The result being reported
+    pub result: RoundResult,
+}
+
+/// The reasons that reporting tokens could not be created or a result could not be reported
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub enum ReportError {
+    /// The tournament could not be found
+    TournamentNotFound,
+    /// The user is not allowed to create reporting tokens for the tournament
+    Unauthorized,
+    /// The token has expired or would expire immediately
+    Expired,
+    /// The token is malformed or was not signed by this server
+    InvalidToken,
+    /// The result could not be recorded
+    NotRecorded(TournamentError),
+    /// Another server hosts the tournament, so the result could not be recorded here. It can be
+    /// reported again later.
+    NotOwner,
+}
+
+/// Where a registration sheet is read from by the `tournaments/<id>/import` SC API.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub enum ImportSource {
+    /// The sheet is fetched by the server from a URL that serves CSV, i.e. the CSV export link of
+    /// a Google Sheet
+    Url(String),
+    /// The sheet was uploaded as CSV text
+    Csv(String),
+}
+
+/// The headers of the columns that hold each piece of player data in a registration sheet.
+/// Headers are matched ignoring case and surrounding whitespace.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct ImportColumns {
+    /// The column that holds the players' names
+    pub name: String,
+    /// The column that holds the players' emails, if any
+    pub email: Option<String>,
+    /// The column that holds the players' decklists, if any
+    pub decklist: Option<String>,
+}
+
+/// The request type taken by the `tournaments/<id>/import` SC API, which reads the players in a
+/// registration sheet (i.e. from an external signup form) and registers them as guests.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct ImportRegistrations {
+    /// Where the sheet is read from
+    pub source: ImportSource,
+    /// Which columns of the sheet hold which player data
+    pub columns: ImportColumns,
+}
+
+/// What happened to a single row of a registration sheet
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum RowStatus {
+    /// The player will be registered
+    Registered,
+    /// The row has no name, so it was skipped
+    MissingName,
+    /// The player could not be registered, i.e. because the name is already taken
+    Rejected(TournamentError),
+}
+
+/// The report for a single row of a registration sheet
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct RowReport {
+    /// The row of the sheet, where the header is row 1
+    pub row: usize,
+    /// The player's name
+    pub name: String,
+    /// The player's email
+    pub email: Option<String>,
+    /// The player's decklist, as it was written in the sheet
+    pub decklist: Option<String>,
+    /// What happened to the row
+    pub status: RowStatus,
+}
+
+/// The outcome of reading a registration sheet. Each row that can be registered has an operation.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct RegistrationImport {
+    /// A report for every non-empty row of the sheet
+    pub rows: Vec<RowReport>,
+    /// The operations that register the players, in the order of the sheet
+    pub ops: Vec<TournOp>,
+}
+
+/// The reasons that a registration sheet could not be imported
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub enum ImportError {
+    /// The tournament could not be found
+    TournamentNotFound,
+    /// Only tournament officials can import registrations
+    Unauthorized,
+    /// The sheet could not be fetched from its URL, or the server could not be reached
+    FetchFailed,
+    /// The sheet has no header with the given name
+    MissingColumn(String),
+    /// The registrations were read but could not be applied to the tournament
+    NotApplied(TournamentError),
+}
+
+/// The response type used by the `tournaments/list/<page>[?page_size=number]` SC API. The page
+/// contains the summaries of the tournaments that match the filter, which each contain an ID which
+/// can be used to query more about the tournament, as well as the total number of matching
+/// tournaments. The page does not necessarily contain as many summaries as the page size, *even
+/// when you haven't reached the end of the complete list of tournaments*.
+pub type ListTournamentsResponse = SquireResponse<TournamentPage>;
 
 /// The response type used by the `tournaments/<id>/get` SC API. The option encodes that the
 /// requested tournament might not be found.
 pub type GetTournamentResponse = SquireResponse<Option<TournamentManager>>;
 
+/// The response type used by the `tournaments/<id>/export` SC API. The option encodes that the
+/// requested tournament might not be found.
+pub type ExportTournamentResponse = SquireResponse<Option<SignedExport>>;
+
+/// The response type used by the `tournaments/<id>/standings` SC API. The option encodes that the
+/// requested tournament might not be found.
+pub type GetStandingsResponse = SquireResponse<Option<Vec<PublicStanding>>>;
+
+/// The response type used by the `tournaments/<id>/pairings/<round>` SC API. The option encodes
+/// that the requested tournament might not be found.
+pub type GetPairingsResponse = SquireResponse<Option<Vec<PublicPairing>>>;
+
+/// The response type used by the `tournaments/<id>/players` SC API. The option encodes that the
+/// requested tournament might not be found.
+pub type GetPlayersResponse = SquireResponse<Option<Vec<PublicPlayer>>>;
+
+/// The response type used by the `tournaments/<id>/schedule` SC API. The option encodes that the
+/// requested tournament might not be found.
+pub type GetScheduleResponse = SquireResponse<Option<EventSchedule>>;
+
+/// The response type used by the `tournaments/<id>/venue` SC API. The option encodes that the
+/// requested tournament might not be found.
+pub type GetVenueResponse = SquireResponse<Option<VenueMap>>;
+
+/// The response type used by the `tournaments/<id>/payouts` SC API. The option encodes that the
+/// requested tournament might not be found.
+pub type GetPayoutsResponse = SquireResponse<Option<Vec<Payout>>>;
+
+/// The response type used by the `tournaments/<id>/flights/<flight>/standings` SC API. The option
+/// encodes that the requested tournament or flight might not be found.
+pub type GetFlightStandingsResponse = SquireResponse<Option<Vec<PublicStanding>>>;
+
+/// The response type used by the `tournaments/<id>/pods/standings/<qualifiers>` SC API. The
+/// option encodes that the requested tournament might not be found.
+pub type GetGroupStandingsResponse = SquireResponse<Option<Vec<PublicGroupStandings>>>;
+
+/// The response type used by the `tournaments/<id>/my-round` SC API. The option encodes that the
+/// tournament might not be found, the user might not be one of its players, or the player might
+/// not have an active round.
+pub type GetMyRoundResponse = SquireResponse<Option<MyRound>>;
+
+/// The response type used by the `tournaments/<id>/standings/<p_id>` SC API. The option encodes
+/// that the requested tournament or player might not be found.
+pub type ExplainScoreResponse = SquireResponse<Option<ScoreExplanation>>;
+
+/// The response type used by the `tournaments/<id>/audit` SC API. The option encodes that the
+/// requested tournament might not be found or that the user is not one of its officials.
+pub type GetAuditLogResponse = SquireResponse<Option<Vec<AuditEntry>>>;
+
+/// The response type used by the `tournaments/<id>/import` SC API.
+pub type ImportRegistrationsResponse = SquireResponse<Result<RegistrationImport, ImportError>>;
+
+/// The response type used by the `tournaments/<id>/invite` and `tournaments/invite/check` SC APIs.
+pub type InviteResponse = SquireResponse<Result<TournamentInvite, InviteError>>;
+
+/// The response type used by the `tournaments/<id>/report_tokens` SC API.
+pub type CreateReportTokensResponse = SquireResponse<Result<Vec<ReportToken>, ReportError>>;
+
+/// The response type used by the `tournaments/report` SC API.
+pub type ReportResultResponse = SquireResponse<Result<(), ReportError>>;
+
+/// The response type used by the `tournaments/<id>/clone` SC API. The option encodes that the
+/// requested tournament might not be found, that the user is not one of its admins, or that the
+/// new tournament could not be saved.
+pub type CloneTournamentResponse = SquireResponse<Option<TournamentId>>;
+
+/// The response type used by the `tournaments/verify` SC API.
+pub type VerifyExportResponse = SquireResponse<ExportVerification>;
+
 /// The response type used by the `tournaments/all` SC API. The option encodes that the
 /// requested tournament might not be found.
 pub type GetAllTournamentsResponse = SquireResponse<Vec<TournamentManager>>;
@@ -85,3 +865,87 @@ pub struct CreateTournamentRequest {
 /// The response type used by the `tournaments/all` SC API. The inner data is the newly created
 /// tournament object.
 pub type CreateTournamentResponse = SquireResponse<TournamentManager>;
+
+#[cfg(feature = "client")]
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, Utc};
+    use squire_lib::{
+        operations::{AdminOp, TournOp},
+        settings::{GeneralSetting, Visibility},
+        tournament::TournamentStatus,
+    };
+    use squire_tests::{get_seed, spoof_account};
+
+    use super::{TournamentFilter, TournamentPage};
+    use crate::sync::TournamentManager;
+
+    #[test]
+    fn tournament_filters() {
+        let admin = spoof_account();
+        let admin_id = admin.id.0.into();
+        let mut tourn = TournamentManager::new(admin.clone(), get_seed());
+        let any = TournamentFilter::default();
+        assert!(any.matches(&tourn));
+
+        // Tournaments without operations have no time to compare against
+        let recent = TournamentFilter {
+            after: Some(Utc::now() - Duration::hours(1)),
+            ..Default::default()
+        };
+        assert!(!recent.matches(&tourn));
+
+        let _ = tourn
+            .apply_op(TournOp::AdminOp(admin_id, AdminOp::Start))
+            .unwrap();
+        assert!(recent.matches(&tourn));
+        let old = TournamentFilter {
+            before: Some(Utc::now() - Duration::hours(1)),
+            ..Default::default()
+        };
+        assert!(!old.matches(&tourn));
+
+        let started = TournamentFilter {
+            status: Some(TournamentStatus::Started),
+            format: Some("Pioneer".into()),
+            organizer: Some(admin.id),
+            ..Default::default()
+        };
+        assert!(started.matches(&tourn));
+        let planned = TournamentFilter {
+            status: Some(TournamentStatus::Planned),
+            ..started.clone()
+        };
+        assert!(!planned.matches(&tourn));
+        let other_org = TournamentFilter {
+            organizer: Some(spoof_account().id),
+            ..started
+        };
+        assert!(!other_org.matches(&tourn));
+
+        // Unlisted tournaments can only be found by their ids
+        let setting = GeneralSetting::Visibility(Visibility::Unlisted).into();
+        let _ = tourn
+            .apply_op(TournOp::AdminOp(
+                admin_id,
+                AdminOp::UpdateTournSetting(setting),
+            ))
+            .unwrap();
+        assert!(!any.matches(&tourn));
+    }
+
+    #[test]
+    fn tournament_page_count() {
+        let mut page = TournamentPage {
+            summaries: Vec::new(),
+            page: 0,
+            page_size: 20,
+            total: 41,
+        };
+        assert_eq!(page.page_count(), 3);
+        page.total = 40;
+        assert_eq!(page.page_count(), 2);
+        page.page_size = 0;
+        assert_eq!(page.page_count(), 0);
+    }
+}
@@ -0,0 +1,213 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use squire_lib::{
+    identifiers::{SquireAccountId, TournamentId, TypeId},
+    simulation::TournamentDiff,
+    tournament::{Tournament, TournamentStatus},
+};
+
+use super::PublicPairing;
+use crate::response::SquireResponse;
+
+/// A type-checked Uuid for webhooks
+pub type WebhookId = TypeId<Webhook>;
+
+/// A URL that the server POSTs a tournament's events to, e.g. for a Discord bot or a stream
+/// overlay. Each event is sent as a JSON-encoded [WebhookEvent], and the hex-encoded HMAC-SHA256
+/// of the body, keyed with the webhook's secret, is sent in the `X-Squire-Signature` header.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Webhook {
+    /// The webhook's id
+    pub id: WebhookId,
+    /// The tournament whose events are sent
+    pub tourn: TournamentId,
+    /// The URL that events are sent to
+    pub url: String,
+    /// The admin that registered the webhook
+    pub owner: SquireAccountId,
+    /// The secret that the webhook's requests are signed with. Webhooks that were registered
+    /// before requests were signed have no secret, and their requests are sent unsigned.
+    #[serde(default)]
+    pub secret: String,
+}
+
+/// The request body used to register a webhook with the `tournaments/<id>/webhooks` SC API
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct RegisterWebhook {
+    /// The URL that events should be sent to. Only `https` URLs whose host is on the public
+    /// internet are accepted.
+    pub url: String,
+}
+
+/// The reasons that a webhook can not be registered
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WebhookError {
+    /// The tournament could not be found
+    TournamentNotFound,
+    /// Only the tournament's admins can manage its webhooks
+    Unauthorized,
+    /// The URL could not be parsed, is not an `https` URL, or its host could not be found
+    InvalidUrl,
+    /// The URL's host is not on the public internet (e.g. it is a loopback or private address)
+    PrivateAddress,
+    /// The webhook could not be saved
+    NotSaved,
+}
+
+/// Something that happened in a tournament, which is sent to the tournament's webhooks
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct WebhookEvent {
+    /// The tournament that the event is for
+    pub tourn: TournamentId,
+    /// When the server noticed the event
+    pub time: DateTime<Utc>,
+    /// What happened
+    pub kind: WebhookEventKind,
+}
+
+/// The kinds of events that are sent to webhooks
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub enum WebhookEventKind {
+    /// The given pairing round (starting at 1) was paired
+    RoundPaired {
+        /// The number of the pairing round
+        round_number: u64,
+        /// The rounds that were created, ordered by match number
+        rounds: Vec<PublicPairing>,
+    },
+    /// The result of a round was certified
+    RoundCertified(PublicPairing),
+    /// The tournament was ended
+    TournamentEnded,
+}
+
+impl WebhookEvent {
+    /// Calculates the events that happened between two copies of a tournament. Rounds that were
+    /// created by hand are not reported as paired.
+    pub fn between(old: &Tournament, new: &Tournament) -> Vec<Self> {
+        let diff = TournamentDiff::new(old, new);
+        let mut paired: BTreeMap<u64, Vec<PublicPairing>> = BTreeMap::new();
+        for rnd in diff
+            .new_rounds
+            .iter()
+            .filter_map(|id| new.round_reg.rounds.get(id))
+        {
+            if let Some(number) = rnd.context.pairing_round() {
+                paired
+                    .entry(number)
+                    .or_default()
+                    .push(PublicPairing::new(new, rnd));
+            }
+        }
+        let certified = diff
+            .new_rounds
+            .iter()
+            .chain(&diff.updated_rounds)
+            .filter_map(|id| new.round_reg.rounds.get(id))
+            .filter(|rnd| rnd.is_certified())
+            .filter(|rnd| {
+                !old.round_reg
+                    .rounds
+                    .get(&rnd.id)
+                    .is_some_and(|r| r.is_certified())
+            })
+            .map(|rnd| WebhookEventKind::RoundCertified(PublicPairing::new(new, rnd)));
+        let ended = (diff.status == Some(TournamentStatus::Ended))
+            .then_some(WebhookEventKind::TournamentEnded);
+        let time = Utc::now();
+        paired
+            .into_iter()
+            .map(|(round_number, rounds)| WebhookEventKind::RoundPaired {
+                round_number,
+                rounds,
+            })
+            .chain(certified)
+            .chain(ended)
+            .map(|kind| Self {
+                tourn: new.id,
+                time,
+                kind,
+            })
+            .collect()
+    }
+}
+
+/// The response type used by the `tournaments/<id>/webhooks` SC API when registering a webhook
+pub type RegisterWebhookResponse = SquireResponse<Result<Webhook, WebhookError>>;
+
+/// The response type used by the `tournaments/<id>/webhooks` SC API when listing webhooks. The
+/// option encodes that the requested tournament might not be found or that the user is not one of
+/// its admins.
+pub type ListWebhooksResponse = SquireResponse<Option<Vec<Webhook>>>;
+
+/// The response type used by the `tournaments/<id>/webhooks/<w_id>` SC API. The boolean encodes
+/// whether the webhook was removed.
+pub type DeleteWebhookResponse = SquireResponse<bool>;
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+    use squire_lib::{
+        identifiers::AdminId,
+        operations::{AdminOp, JudgeOp, TournOp},
+        rounds::RoundResult,
+    };
+    use squire_tests::{get_seed, spoof_account};
+
+    use super::{WebhookEvent, WebhookEventKind};
+
+    #[test]
+    fn webhook_events_between_copies() {
+        let acc = spoof_account();
+        let admin_id: AdminId = acc.id.0.into();
+        let mut tourn = acc.create_tournament(get_seed());
+        for _ in 0..4 {
+            _ = tourn
+                .apply_op(Utc::now(), TournOp::RegisterPlayer(spoof_account(), None))
+                .unwrap();
+        }
+        _ = tourn
+            .apply_op(Utc::now(), TournOp::AdminOp(admin_id, AdminOp::Start))
+            .unwrap();
+        let old = tourn.clone();
+        assert!(WebhookEvent::between(&old, &tourn).is_empty());
+
+        let pairings = tourn.create_pairings().unwrap();
+        let op = TournOp::AdminOp(admin_id, AdminOp::PairRound(pairings));
+        let rounds = tourn.apply_op(Utc::now(), op).unwrap().assume_pair();
+        let events = WebhookEvent::between(&old, &tourn);
+        assert_eq!(events.len(), 1);
+        let WebhookEventKind::RoundPaired {
+            round_number,
+            rounds: paired,
+        } = &events[0].kind
+        else {
+            panic!("Expected the round to be paired")
+        };
+        assert_eq!(*round_number, 1);
+        assert_eq!(paired.len(), rounds.len());
+
+        // Certifying a round and ending the tournament are reported on their own
+        let old = tourn.clone();
+        for op in [
+            JudgeOp::AdminRecordResult(rounds[0], RoundResult::Draw(1)),
+            JudgeOp::ConfirmRound(rounds[0]),
+        ] {
+            _ = tourn
+                .apply_op(Utc::now(), TournOp::JudgeOp(admin_id.into(), op))
+                .unwrap();
+        }
+        _ = tourn
+            .apply_op(Utc::now(), TournOp::AdminOp(admin_id, AdminOp::End))
+            .unwrap();
+        let kinds: Vec<_> = WebhookEvent::between(&old, &tourn)
+            .into_iter()
+            .map(|event| event.kind)
+            .collect();
+        assert_eq!(kinds.len(), 2);
+        assert!(matches!(kinds[0], WebhookEventKind::RoundCertified(_)));
+        assert_eq!(kinds[1], WebhookEventKind::TournamentEnded);
+    }
+}
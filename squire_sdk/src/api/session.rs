@@ -37,10 +37,13 @@ impl SessionToken {
     }
 }
 
+/// Tokens can be sent with or without the `Bearer` scheme. Scripts that use API tokens tend to
+/// include it, but the SDK does not.
 impl FromStr for SessionToken {
     type Err = TokenParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.strip_prefix("Bearer ").unwrap_or(s);
         let mut inner = [0; 32];
         decode_to_slice(s, &mut inner).map_err(|_| TokenParseError::InvalidToken)?;
         Ok(Self(inner))
@@ -75,3 +78,13 @@ pub enum AuthUser {
     Guest(SessionToken),
     User(SquireAccountId),
 }
+
+impl AuthUser {
+    /// Returns the id of the user's account. Guests don't have one.
+    pub fn account(&self) -> Option<SquireAccountId> {
+        match self {
+            AuthUser::Guest(_) => None,
+            AuthUser::User(id) => Some(*id),
+        }
+    }
+}
@@ -1,9 +1,14 @@
 use squire_lib::accounts::SquireAccount;
 
 use super::{
-    error::ClientError, network::NetworkState, tournaments::TournsClient, OnUpdate, SquireClient,
+    error::ClientError, handoff::ConnectionHandle, network::NetworkState, request_url,
+    tournaments::TournsClient, OnUpdate, SquireClient,
+};
+use crate::{
+    actor::ActorBuilder,
+    api::{Credentials, GetRequest, GetSessionStatus, SessionStatus, SessionToken},
+    compat::{Client, Request, Storage},
 };
-use crate::{actor::ActorBuilder, api::Credentials};
 
 /// A builder for the SquireClient. This builder is generic over most of its fields. This is used
 /// to gate access to the build methods, requiring all necessary fields are filled before
@@ -13,6 +18,7 @@ pub struct ClientBuilder<UP = Box<dyn OnUpdate>, URL = (), USER = ()> {
     url: URL,
     user: USER,
     on_update: UP,
+    storage: Option<Storage>,
 }
 
 impl ClientBuilder {
@@ -22,6 +28,7 @@ impl ClientBuilder {
             url: (),
             user: (),
             on_update: Box::new(drop),
+            storage: None,
         }
     }
 }
@@ -37,45 +44,112 @@ impl<UP: OnUpdate, URL, USER> ClientBuilder<UP, URL, USER> {
     /// If there was already a URL in the configuration, it is discarded
     pub fn url(self, url: String) -> ClientBuilder<UP, String, USER> {
         let ClientBuilder {
-            user, on_update, ..
+            user,
+            on_update,
+            storage,
+            ..
         } = self;
         ClientBuilder {
             url,
             user,
             on_update,
+            storage,
         }
     }
 
     /// Adds a SquireAccount to the configuration of the client. This method is required for
     /// construction. If there was already an account in the configuration, it is discarded
     pub fn account_login(self, user: Credentials) -> ClientBuilder<UP, URL, Credentials> {
-        let ClientBuilder { url, on_update, .. } = self;
+        let ClientBuilder {
+            url,
+            on_update,
+            storage,
+            ..
+        } = self;
         ClientBuilder {
             url,
             user,
             on_update,
+            storage,
         }
     }
 
     /// Adds a SquireAccount to the configuration of the client. This method is required for
     /// construction. If there was already an account in the configuration, it is discarded
     pub fn account(self, user: SquireAccount) -> ClientBuilder<UP, URL, SquireAccount> {
-        let ClientBuilder { url, on_update, .. } = self;
+        let ClientBuilder {
+            url,
+            on_update,
+            storage,
+            ..
+        } = self;
         ClientBuilder {
             url,
             user,
             on_update,
+            storage,
+        }
+    }
+
+    /// Adds a connection handle, minted by another client, to the configuration of the client.
+    /// If there was already an account in the configuration, it is discarded
+    pub fn connection_handle(
+        self,
+        user: ConnectionHandle,
+    ) -> ClientBuilder<UP, URL, ConnectionHandle> {
+        let ClientBuilder {
+            url,
+            on_update,
+            storage,
+            ..
+        } = self;
+        ClientBuilder {
+            url,
+            user,
+            on_update,
+            storage,
+        }
+    }
+
+    /// Adds an API token to the configuration of the client. The client acts as the user that
+    /// created the token instead of starting a session. If there was already an account in the
+    /// configuration, it is discarded
+    pub fn api_token(self, user: SessionToken) -> ClientBuilder<UP, URL, SessionToken> {
+        let ClientBuilder {
+            url,
+            on_update,
+            storage,
+            ..
+        } = self;
+        ClientBuilder {
+            url,
+            user,
+            on_update,
+            storage,
         }
     }
 
     /// Adds a function that is called on update to the configuration of the client.
     /// If there was already a function in the configuration, it is discarded
     pub fn on_update<F: OnUpdate>(self, on_update: F) -> ClientBuilder<F, URL, USER> {
-        let ClientBuilder { url, user, .. } = self;
+        let ClientBuilder {
+            url, user, storage, ..
+        } = self;
         ClientBuilder {
             url,
             user,
             on_update,
+            storage,
+        }
+    }
+
+    /// Adds a storage to the configuration of the client. Tournaments with operations that have
+    /// not been synced with the backend are written to the storage, so they survive the client
+    /// being restarted. If there was already a storage in the configuration, it is discarded
+    pub fn storage(self, storage: Storage) -> Self {
+        Self {
+            storage: Some(storage),
+            ..self
         }
     }
 }
@@ -84,11 +158,13 @@ impl<UP: OnUpdate> ClientBuilder<UP, String, ()> {
     /// Attempts to create a client. Construction will fail if a Squire server can not be reached
     /// using the given URL or a guest session can not be gotten from the server.
     pub async fn guest_build(self) -> Result<SquireClient, ClientError> {
-        let ClientBuilder { on_update, .. } = self;
+        let ClientBuilder {
+            on_update, storage, ..
+        } = self;
         let state = NetworkState::new();
         let user = state.subscribe();
         let client = ActorBuilder::new(state).launch();
-        let tourns = TournsClient::new(client.clone(), on_update);
+        let tourns = TournsClient::new(client.clone(), on_update, storage);
         Ok(SquireClient {
             client,
             tourns,
@@ -98,11 +174,13 @@ impl<UP: OnUpdate> ClientBuilder<UP, String, ()> {
 
     /// Creates a client but does not check if the URL is valid.
     pub fn guest_build_unchecked(self) -> SquireClient {
-        let ClientBuilder { on_update, .. } = self;
+        let ClientBuilder {
+            on_update, storage, ..
+        } = self;
         let state = NetworkState::new();
         let user = state.subscribe();
         let client = ActorBuilder::new(state).launch();
-        let tourns = TournsClient::new(client.clone(), on_update);
+        let tourns = TournsClient::new(client.clone(), on_update, storage);
         SquireClient {
             client,
             tourns,
@@ -115,11 +193,13 @@ impl<UP: OnUpdate> ClientBuilder<UP, String, Credentials> {
     /// Attempts to create a client. Construction will fail if a Squire server can not be reached
     /// using the given URL or if the login credentials are not valid.
     pub async fn build(self) -> Result<SquireClient, ClientError> {
-        let ClientBuilder { on_update, .. } = self;
+        let ClientBuilder {
+            on_update, storage, ..
+        } = self;
         let state = NetworkState::new();
         let user = state.subscribe();
         let client = ActorBuilder::new(state).launch();
-        let tourns = TournsClient::new(client.clone(), on_update);
+        let tourns = TournsClient::new(client.clone(), on_update, storage);
         Ok(SquireClient {
             client,
             tourns,
@@ -133,12 +213,15 @@ impl<UP: OnUpdate> ClientBuilder<UP, String, SquireAccount> {
     /// using the given URL.
     pub async fn build(self) -> Result<SquireClient, ClientError> {
         let ClientBuilder {
-            user, on_update, ..
+            user,
+            on_update,
+            storage,
+            ..
         } = self;
         let state = NetworkState::new_with_user(user);
         let user = state.subscribe();
         let client = ActorBuilder::new(state).launch();
-        let tourns = TournsClient::new(client.clone(), on_update);
+        let tourns = TournsClient::new(client.clone(), on_update, storage);
         Ok(SquireClient {
             client,
             tourns,
@@ -149,12 +232,15 @@ impl<UP: OnUpdate> ClientBuilder<UP, String, SquireAccount> {
     /// Creates a client but does not check if the URL is valid.
     pub fn build_unchecked(self) -> SquireClient {
         let ClientBuilder {
-            user, on_update, ..
+            user,
+            on_update,
+            storage,
+            ..
         } = self;
         let state = NetworkState::new_with_user(user);
         let user = state.subscribe();
         let client = ActorBuilder::new(state).launch();
-        let tourns = TournsClient::new(client.clone(), on_update);
+        let tourns = TournsClient::new(client.clone(), on_update, storage);
         SquireClient {
             client,
             tourns,
@@ -162,3 +248,58 @@ impl<UP: OnUpdate> ClientBuilder<UP, String, SquireAccount> {
         }
     }
 }
+
+impl<UP: OnUpdate> ClientBuilder<UP, String, ConnectionHandle> {
+    /// Creates a client that uses the guest session held by the connection handle. Construction
+    /// will fail if the handle has expired. Once built, the client can subscribe to the handle's
+    /// tournament.
+    pub fn build(self) -> Result<SquireClient, ClientError> {
+        let ClientBuilder {
+            user,
+            on_update,
+            storage,
+            ..
+        } = self;
+        if user.is_expired() {
+            return Err(ClientError::HandleExpired);
+        }
+        let state = NetworkState::new_with_handle(&user);
+        let user = state.subscribe();
+        let client = ActorBuilder::new(state).launch();
+        let tourns = TournsClient::new(client.clone(), on_update, storage);
+        Ok(SquireClient {
+            client,
+            tourns,
+            user,
+        })
+    }
+}
+
+impl<UP: OnUpdate> ClientBuilder<UP, String, SessionToken> {
+    /// Attempts to create a client that authenticates with an API token. Construction will fail if
+    /// a Squire server can not be reached using the given URL or if the token is not valid.
+    pub async fn build(self) -> Result<SquireClient, ClientError> {
+        let ClientBuilder {
+            user,
+            on_update,
+            storage,
+            ..
+        } = self;
+        let url = request_url(<GetSessionStatus as GetRequest<0>>::ROUTE.replace([]));
+        let resp = Client::new()
+            .execute(Request::get(&url).session(Some(&user)))
+            .await?;
+        let SessionStatus::ActiveUser(acc) = resp.json().await? else {
+            return Err(ClientError::LogInFailed);
+        };
+        let state = NetworkState::new_with_token(acc, user);
+        let user = state.subscribe();
+        let client = ActorBuilder::new(state).launch();
+        let tourns = TournsClient::new(client.clone(), on_update, storage);
+        Ok(SquireClient {
+            client,
+            tourns,
+            user,
+        })
+    }
+}
@@ -11,6 +11,7 @@ pub enum ClientError {
     NotLoggedIn,
     LogInFailed,
     FailedToConnect,
+    HandleExpired,
     Network(NetworkError),
     RequestStatus(StatusCode),
     Tournament(TournamentError),
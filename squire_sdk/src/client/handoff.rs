@@ -0,0 +1,78 @@
+use std::{fmt::Display, str::FromStr};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use squire_lib::identifiers::TournamentId;
+
+use crate::api::SessionToken;
+
+/// A short-lived handle that lets a second window or tab (e.g. a pairings popout) subscribe to a
+/// tournament without sharing the client that minted it.
+///
+/// The handle holds its own guest session rather than the session of the minting client, so the
+/// client built from it can watch the tournament but carries none of the user's permissions. The
+/// backend expires that session once the handle expires, so a handle can not be used after it,
+/// even by a client that ignores the expiry.
+/// Handles can be passed between windows as strings via their `Display` and `FromStr` impls.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConnectionHandle {
+    /// The tournament that the handle is for
+    pub tourn: TournamentId,
+    /// The guest session that the new client will use
+    pub token: SessionToken,
+    /// When the backend expires the handle's session
+    pub expires: DateTime<Utc>,
+}
+
+/// The error returned when a string can not be parsed into a `ConnectionHandle`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HandleParseError;
+
+impl ConnectionHandle {
+    pub fn new(tourn: TournamentId, token: SessionToken, expires: DateTime<Utc>) -> Self {
+        Self {
+            tourn,
+            token,
+            expires,
+        }
+    }
+
+    /// Calculates if the handle can no longer be used
+    pub fn is_expired(&self) -> bool {
+        self.expires <= Utc::now()
+    }
+}
+
+impl Display for ConnectionHandle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let bytes = postcard::to_allocvec(self).map_err(|_| std::fmt::Error)?;
+        write!(f, "{}", hex::encode(bytes))
+    }
+}
+
+impl FromStr for ConnectionHandle {
+    type Err = HandleParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let bytes = hex::decode(s).map_err(|_| HandleParseError)?;
+        postcard::from_bytes(&bytes).map_err(|_| HandleParseError)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, Utc};
+
+    use super::ConnectionHandle;
+    use crate::api::SessionToken;
+
+    #[test]
+    fn handle_string_round_trip() {
+        let expires = Utc::now() + Duration::minutes(5);
+        let handle = ConnectionHandle::new(Default::default(), SessionToken([7; 32]), expires);
+        assert!(!handle.is_expired());
+        let parsed: ConnectionHandle = handle.to_string().parse().unwrap();
+        assert_eq!(parsed, handle);
+        assert!("not a handle".parse::<ConnectionHandle>().is_err());
+    }
+}
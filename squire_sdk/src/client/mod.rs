@@ -1,27 +1,54 @@
-use std::marker::PhantomData;
+use std::{collections::HashMap, marker::PhantomData};
 
+use chrono::{DateTime, Utc};
 use serde::de::DeserializeOwned;
 use squire_lib::{operations::OpResult, tournament::TournRole};
 use tokio::sync::watch::Receiver as Subscriber;
 
 use self::{
     builder::ClientBuilder,
-    network::{LoginError, NetworkClient},
+    handoff::ConnectionHandle,
+    network::{ConnectionState, LoginError, NetworkClient},
     session::SessionWatcher,
     tournaments::{TournsClient, UpdateType},
 };
 use crate::{
     actor::Tracker,
     api::{
-        Credentials, GetRequest, ListTournaments, PostRequest, RegForm, SessionToken,
-        TournamentSummary,
+        AcceptInvite, AccountProfile, ApiToken, ApiTokenId, ChangePassword, CheckInvite,
+        CreateApiToken, CreateInvite, CreateReportTokens, Credentials, DeleteRequest,
+        DeleteWebhook, ExplainScore, GetAuditLog, GetFlightStandings, GetGroupStandings,
+        GetHistory, GetMyRound, GetNotificationPrefs, GetOAuthRedirect, GetPairings, GetPayouts,
+        GetPlayers, GetProfile, GetRequest, GetSchedule, GetSeries, GetSeriesStandings,
+        GetStandings, GetStats, GetVenue, GuestUpgrade, HistoryEntry, ImportError,
+        ImportRegistrations, InviteError, InviteRole, LinkError, LinkIdentity, ListApiTokens,
+        ListSessions, ListTemplates, ListTournaments, ListWebhooks, MyRound, NameSession,
+        NewApiToken, NotificationPrefs, OAuthProvider, OAuthRedirect, PasswordError, PlayerStats,
+        PostRequest, ProfileError, PublicGroupStandings, PublicPairing, PublicPlayer,
+        PublicProfile, PublicStanding, RedeemInvite, RegForm, RegisterWebhook, RegistrationImport,
+        ReportError, ReportResult, ReportToken, RequestDeckCheck, RequestPasswordReset,
+        ResetPassword, RevokeApiToken, RevokeSession, SessionId, SessionInfo, SessionToken,
+        TournamentInvite, TournamentOverview, TournamentSummary, UpgradeError, Webhook,
+        WebhookError, WebhookId,
     },
     compat::{NetworkError, NetworkResponse, Request, Sendable},
     model::{
-        accounts::SquireAccount, identifiers::TournamentId, operations::TournOp,
-        players::PlayerRegistry, rounds::RoundRegistry, tournament::TournamentSeed,
+        accounts::SquireAccount,
+        identifiers::{PlayerId, RoundId, SeriesId, SquireAccountId, TournamentId},
+        operations::TournOp,
+        players::PlayerRegistry,
+        prizes::Payout,
+        rounds::{RoundRegistry, RoundResult},
+        schedule::EventSchedule,
+        scoring::ScoreExplanation,
+        series::{Series, SeriesStandings},
+        settings::TournamentTemplate,
+        tournament::{SeedOverrides, TournamentSeed},
+        venue::VenueMap,
+    },
+    sync::{
+        AuditEntry, Divergence, FullOp, Presence, RollbackError, ServerClock, TournamentManager,
     },
-    sync::TournamentManager,
 };
 
 #[cfg(not(debug_assertions))]
@@ -31,6 +58,26 @@ pub const HOST_ADDRESS: &str = "s://squire.shuttleapp.rs";
 /// The address of the local hosh
 pub const HOST_ADDRESS: &str = "://localhost:8000";
 
+/// Converts an API route into the URL that requests are sent to. Native clients need the full
+/// address of the host while requests from the browser are relative to the current page.
+pub(crate) fn request_url(route: String) -> String {
+    #[cfg(not(target_family = "wasm"))]
+    let url = format!("http{HOST_ADDRESS}{route}");
+    #[cfg(target_family = "wasm")]
+    let url = route;
+    url
+}
+
+/// Converts an API route into the URL that websockets are opened with. Like with `request_url`,
+/// native clients need the full address of the host.
+pub(crate) fn websocket_url(route: String) -> String {
+    #[cfg(not(target_family = "wasm"))]
+    let url = format!("ws{HOST_ADDRESS}{route}");
+    #[cfg(target_family = "wasm")]
+    let url = route;
+    url
+}
+
 // This needs to be `'static + Send` because of constraints on `async_trait`. Ideally, it would
 // just be `Sendable`.
 pub trait OnUpdate: 'static + Send + FnMut(TournamentId) {}
@@ -39,6 +86,7 @@ impl<T> OnUpdate for T where T: 'static + Send + FnMut(TournamentId) {}
 
 pub mod builder;
 pub mod error;
+pub mod handoff;
 pub mod network;
 pub mod session;
 pub mod tournaments;
@@ -143,20 +191,85 @@ impl SquireClient {
         self.tourns.subscribe(id).await
     }
 
+    /// Returns a subscriber for the state of a subscription's connection to the backend, which
+    /// can be rendered to show when the client is reconnecting. Dropped websockets are
+    /// reconnected automatically. `None` is returned if the tournament is not subscribed to.
+    pub async fn connection_state(&self, id: TournamentId) -> Option<Subscriber<ConnectionState>> {
+        self.tourns.connection_state(id).await
+    }
+
+    /// Returns a subscriber for who is currently connected to the tournament (e.g. "3 judges and
+    /// 42 spectators online"). `None` is returned if the tournament is not subscribed to.
+    pub async fn presence(&self, id: TournamentId) -> Option<Subscriber<Presence>> {
+        self.tourns.presence(id).await
+    }
+
+    /// Returns a subscriber for the backend's clock. Round countdowns should be calculated with it
+    /// (e.g. `ServerClock::round_time_left`) so that every client shows the same time, even if
+    /// the local clock has drifted. `None` is returned if the tournament is not subscribed to.
+    pub async fn clock(&self, id: TournamentId) -> Option<Subscriber<ServerClock>> {
+        self.tourns.clock(id).await
+    }
+
+    /// Returns a subscriber for the players that have not checked in at their tables within the
+    /// grace period of the tournament's tardiness policy. Judges can penalize them with
+    /// `JudgeOp::ApplyTardiness`. `None` is returned if the tournament is not subscribed to.
+    pub async fn tardy_players(
+        &self,
+        id: TournamentId,
+    ) -> Option<Subscriber<Vec<(RoundId, PlayerId)>>> {
+        self.tourns.tardy_players(id).await
+    }
+
+    /// Returns a subscriber for the last time that the local copy of the tournament was found to
+    /// no longer match the backend's copy. Divergences are fixed by re-fetching the tournament, so
+    /// this is only meant for diagnostics. `None` is returned if the tournament is not subscribed
+    /// to.
+    pub async fn divergence(&self, id: TournamentId) -> Option<Subscriber<Option<Divergence>>> {
+        self.tourns.divergence(id).await
+    }
+
+    /// Returns a subscriber for the last unsynced operations that were dropped because they could
+    /// no longer be applied on top of the backend's copy of the tournament, e.g. after a rollback
+    /// or while reconnecting. Dropped operations are never synced. `None` is returned if the
+    /// tournament is not subscribed to.
+    pub async fn dropped_ops(&self, id: TournamentId) -> Option<Subscriber<Vec<FullOp>>> {
+        self.tourns.dropped_ops(id).await
+    }
+
+    /// Asks the backend to roll the tournament back to the most recent checkpoint with the given
+    /// name (see `AdminOp::Checkpoint`). Only admins can roll back tournaments. `None` is returned
+    /// if the tournament is not subscribed to or the connection drops before the backend responds.
+    pub async fn rollback(
+        &self,
+        id: TournamentId,
+        checkpoint: String,
+    ) -> Option<Result<(), RollbackError>> {
+        self.tourns.rollback(id, checkpoint).await
+    }
+
     fn get_request<const N: usize, R>(&self, subs: [&str; N]) -> ResponseTracker<R::Response>
     where
         R: 'static + GetRequest<N>,
         R::Response: Sendable,
     {
-        #[cfg(not(target_family = "wasm"))]
-        let url = format!("http{HOST_ADDRESS}{}", R::ROUTE.replace(subs));
-        #[cfg(target_family = "wasm")]
-        let url = R::ROUTE.replace(subs);
+        let url = request_url(R::ROUTE.replace(subs));
         let req = Request::get(&url);
         let tracker = self.client.track(req);
         ResponseTracker::new(tracker)
     }
 
+    fn delete_request<const N: usize, R>(&self, subs: [&str; N]) -> ResponseTracker<R::Response>
+    where
+        R: 'static + DeleteRequest<N>,
+        R::Response: Sendable,
+    {
+        let url = request_url(R::ROUTE.replace(subs));
+        let req = Request::delete(&url);
+        let tracker = self.client.track(req);
+        ResponseTracker::new(tracker)
+    }
+
     fn post_request<const N: usize, B>(
         &self,
         body: B,
@@ -166,10 +279,7 @@ impl SquireClient {
         B: Sendable + Sync + PostRequest<N>,
         B::Response: Sendable,
     {
-        #[cfg(not(target_family = "wasm"))]
-        let url = format!("http{HOST_ADDRESS}{}", B::ROUTE.replace(subs));
-        #[cfg(target_family = "wasm")]
-        let url = B::ROUTE.replace(subs);
+        let url = request_url(B::ROUTE.replace(subs));
         let req = Request::post(&url).json(&body);
         let tracker = self.client.track(req);
         ResponseTracker::new(tracker)
@@ -219,14 +329,239 @@ impl SquireClient {
         self.tourns.query(id, move |tourn| query(&tourn.round_reg))
     }
 
+    /// Runs a query against several tournaments in one message to the tournament management task,
+    /// e.g. for dashboards. The results are keyed by tournament id, and tournaments that are not
+    /// held by the client are left out.
+    pub fn query_many<F, T>(
+        &self,
+        ids: Vec<TournamentId>,
+        query: F,
+    ) -> Tracker<HashMap<TournamentId, T>>
+    where
+        F: 'static + Send + FnMut(&TournamentManager) -> T,
+        T: 'static + Send,
+    {
+        self.tourns.query_many(ids, query)
+    }
+
+    /// Subscribes to part of a tournament (see `TournsClient::subscribe_query`). The subscriber is
+    /// only notified when the selected value changes. `None` is returned if the tournament is not
+    /// held by the client.
+    pub async fn subscribe_query<F, T>(
+        &self,
+        id: TournamentId,
+        selector: F,
+    ) -> Option<Subscriber<T>>
+    where
+        F: 'static + Send + FnMut(&TournamentManager) -> T,
+        T: 'static + Send + Sync + PartialEq,
+    {
+        self.tourns.subscribe_query(id, selector).await
+    }
+
     pub fn register(&self, body: RegForm) -> ResponseTracker<bool> {
         self.post_request(body, [])
     }
 
+    /// Changes the user's password. The user's current password is needed.
+    pub fn change_password(
+        &self,
+        old_password: String,
+        new_password: String,
+    ) -> ResponseTracker<Result<(), PasswordError>> {
+        let req = ChangePassword {
+            old_password,
+            new_password,
+        };
+        self.post_request(req, [])
+    }
+
+    /// Asks the backend to email a password reset token to the account with the given username
+    pub fn request_password_reset(&self, username: String) -> ResponseTracker<()> {
+        self.post_request(RequestPasswordReset { username }, [])
+    }
+
+    /// Sets a new password for an account using a token from
+    /// [SquireClient::request_password_reset]
+    pub fn reset_password(
+        &self,
+        token: String,
+        new_password: String,
+    ) -> ResponseTracker<Result<(), PasswordError>> {
+        self.post_request(
+            ResetPassword {
+                token,
+                new_password,
+            },
+            [],
+        )
+    }
+
+    /// Saves a tournament template to the user's account. Templates can be given to a
+    /// `TournamentSeed` to create new tournaments with the template's settings and officials.
+    pub fn save_template(&self, template: TournamentTemplate) -> ResponseTracker<bool> {
+        self.post_request(template, [])
+    }
+
+    /// Lists the tournament templates saved to the user's account
+    pub async fn list_templates(&self) -> Vec<TournamentTemplate> {
+        self.get_request::<0, ListTemplates>([])
+            .output()
+            .await
+            .unwrap_or_default()
+    }
+
+    /// Saves the user's notification preferences. Notifications are opt-in, so nothing is sent
+    /// until the user adds a channel and the kinds of notifications they want.
+    pub fn save_notification_prefs(&self, prefs: NotificationPrefs) -> ResponseTracker<bool> {
+        self.post_request(prefs, [])
+    }
+
+    /// Fetches the user's notification preferences
+    pub async fn get_notification_prefs(&self) -> Option<NotificationPrefs> {
+        self.get_request::<0, GetNotificationPrefs>([])
+            .output()
+            .await
+            .ok()
+    }
+
+    /// Saves the optional parts of the user's profile, e.g. their avatar and pronouns
+    pub fn save_profile(
+        &self,
+        profile: AccountProfile,
+    ) -> ResponseTracker<Result<(), ProfileError>> {
+        self.post_request(profile, [])
+    }
+
+    /// Fetches an account's public profile. Users that do not share their name are redacted.
+    pub async fn get_profile(&self, id: SquireAccountId) -> Option<PublicProfile> {
+        self.get_request::<1, GetProfile>([&id.to_string()])
+            .output()
+            .await
+            .ok()
+            .flatten()
+    }
+
+    /// Fetches the tournaments that an account played in, most recent first
+    pub async fn get_history(&self, id: SquireAccountId) -> Option<Vec<HistoryEntry>> {
+        self.get_request::<1, GetHistory>([&id.to_string()])
+            .output()
+            .await
+            .ok()
+            .flatten()
+    }
+
+    /// Fetches the lifetime stats of an account
+    pub async fn get_stats(&self, id: SquireAccountId) -> Option<PlayerStats> {
+        self.get_request::<1, GetStats>([&id.to_string()])
+            .output()
+            .await
+            .ok()
+            .flatten()
+    }
+
+    /// Asks the backend to notify the given players that a judge wants to check their decks. Only
+    /// the tournament's admins and judges can request deck checks.
+    pub fn request_deck_check(
+        &self,
+        id: TournamentId,
+        players: Vec<PlayerId>,
+    ) -> ResponseTracker<bool> {
+        self.post_request(RequestDeckCheck { players }, [&id.to_string()])
+    }
+
+    /// Asks the backend to create a new tournament with the same settings and officials as the
+    /// given tournament, e.g. to run a weekly event again. Only the tournament's admins can clone
+    /// it. The id of the new tournament is returned.
+    pub fn clone_tournament(
+        &self,
+        id: TournamentId,
+        overrides: SeedOverrides,
+    ) -> ResponseTracker<Option<TournamentId>> {
+        self.post_request(overrides, [&id.to_string()])
+    }
+
     pub fn login(&self, cred: Credentials) -> Tracker<Result<SquireAccount, LoginError>> {
         self.client.track(cred)
     }
 
+    /// Fetches where the user needs to be sent to log in with the given OAuth provider. `None` is
+    /// returned if the backend does not support the provider.
+    pub async fn oauth_redirect(&self, provider: OAuthProvider) -> Option<OAuthRedirect> {
+        self.get_request::<1, GetOAuthRedirect>([provider.as_str()])
+            .output()
+            .await
+            .ok()
+            .flatten()
+    }
+
+    /// Logs in with the authorization code and state that an OAuth provider gave the user (see
+    /// `oauth_redirect`). The first time that someone logs in with a provider, an account is
+    /// created for them.
+    pub fn login_oauth(
+        &self,
+        provider: OAuthProvider,
+        code: String,
+        state: String,
+    ) -> Tracker<Result<SquireAccount, LoginError>> {
+        self.login(Credentials::OAuth {
+            provider,
+            code,
+            state,
+        })
+    }
+
+    /// Links the identity that the current user logged in to an OAuth provider with to their
+    /// account, using the authorization code and state that the provider gave them (see
+    /// `oauth_redirect`). Afterwards, they can log in with the provider as well.
+    pub fn link_oauth(
+        &self,
+        provider: OAuthProvider,
+        code: String,
+        state: String,
+    ) -> ResponseTracker<Result<(), LinkError>> {
+        self.post_request(LinkIdentity { code, state }, [provider.as_str()])
+    }
+
+    /// Creates a long-lived API token that scripts and bots can use to act as the current user.
+    /// See `ClientBuilder::api_token` for building a client with it.
+    pub fn create_api_token(&self, name: String) -> ResponseTracker<NewApiToken> {
+        self.post_request(CreateApiToken { name }, [])
+    }
+
+    /// Lists the current user's API tokens
+    pub async fn list_api_tokens(&self) -> Option<Vec<ApiToken>> {
+        self.get_request::<0, ListApiTokens>([]).output().await.ok()
+    }
+
+    /// Revokes one of the current user's API tokens
+    pub fn revoke_api_token(&self, id: ApiTokenId) -> ResponseTracker<bool> {
+        self.delete_request::<1, RevokeApiToken>([&id.to_string()])
+    }
+
+    /// Lists the sessions that the current user is logged in with, e.g. one for each of their
+    /// devices, most recently used first
+    pub async fn list_sessions(&self) -> Option<Vec<SessionInfo>> {
+        self.get_request::<0, ListSessions>([]).output().await.ok()
+    }
+
+    /// Revokes one of the current user's sessions, which logs that device out
+    pub fn revoke_session(&self, id: SessionId) -> ResponseTracker<bool> {
+        self.delete_request::<1, RevokeSession>([&id.to_string()])
+    }
+
+    /// Names the device that the current session is on, so the user can tell their sessions apart
+    pub fn name_session(&self, device: String) -> ResponseTracker<bool> {
+        self.post_request(NameSession { device }, [])
+    }
+
+    /// Mints a short-lived handle that another window or tab can use to build its own client and
+    /// subscribe to the given tournament. The handle has its own guest session, so the new client
+    /// does not share this client's session.
+    pub fn mint_connection_handle(&self, id: TournamentId) -> Tracker<Option<ConnectionHandle>> {
+        self.client.track(id)
+    }
+
     pub fn guest_login(&self) -> Tracker<SessionWatcher> {
         self.client.track(())
     }
@@ -236,6 +571,288 @@ impl SquireClient {
             .output()
             .await
             .ok()
+            .map(|page| page.summaries)
+    }
+
+    /// Fetches the current standings of a tournament from the backend without subscribing to it
+    pub async fn get_standings(&self, id: TournamentId) -> Option<Vec<PublicStanding>> {
+        self.get_request::<1, GetStandings>([&id.to_string()])
+            .output()
+            .await
+            .ok()
+            .flatten()
+    }
+
+    /// Fetches the current standings of one of a tournament's flights (starting at 1) from the
+    /// backend without subscribing to the tournament
+    pub async fn get_flight_standings(
+        &self,
+        id: TournamentId,
+        flight: usize,
+    ) -> Option<Vec<PublicStanding>> {
+        self.get_request::<2, GetFlightStandings>([&id.to_string(), &flight.to_string()])
+            .output()
+            .await
+            .ok()
+            .flatten()
+    }
+
+    /// Fetches the current standings within each of a tournament's draft pods from the backend
+    /// without subscribing to the tournament. The top `qualifiers` players of each pod are marked
+    /// as qualifying for the next stage.
+    pub async fn get_group_standings(
+        &self,
+        id: TournamentId,
+        qualifiers: usize,
+    ) -> Option<Vec<PublicGroupStandings>> {
+        self.get_request::<2, GetGroupStandings>([&id.to_string(), &qualifiers.to_string()])
+            .output()
+            .await
+            .ok()
+            .flatten()
+    }
+
+    /// Fetches the rounds of one of a tournament's pairing rounds (starting at 1) from the backend
+    /// without subscribing to the tournament
+    pub async fn get_pairings(&self, id: TournamentId, round: u64) -> Option<Vec<PublicPairing>> {
+        self.get_request::<2, GetPairings>([&id.to_string(), &round.to_string()])
+            .output()
+            .await
+            .ok()
+            .flatten()
+    }
+
+    /// Fetches when each of a tournament's pairing rounds starts from the backend without
+    /// subscribing to the tournament
+    pub async fn get_schedule(&self, id: TournamentId) -> Option<EventSchedule> {
+        self.get_request::<1, GetSchedule>([&id.to_string()])
+            .output()
+            .await
+            .ok()
+            .flatten()
+    }
+
+    /// Fetches a floor map of a tournament's venue from the backend without subscribing to the
+    /// tournament
+    pub async fn get_venue(&self, id: TournamentId) -> Option<VenueMap> {
+        self.get_request::<1, GetVenue>([&id.to_string()])
+            .output()
+            .await
+            .ok()
+            .flatten()
+    }
+
+    /// Fetches how much each player is paid from a tournament's prize pool from the backend
+    /// without subscribing to the tournament
+    pub async fn get_payouts(&self, id: TournamentId) -> Option<Vec<Payout>> {
+        self.get_request::<1, GetPayouts>([&id.to_string()])
+            .output()
+            .await
+            .ok()
+            .flatten()
+    }
+
+    /// Fetches the players of a tournament from the backend without subscribing to it
+    pub async fn get_players(&self, id: TournamentId) -> Option<Vec<PublicPlayer>> {
+        self.get_request::<1, GetPlayers>([&id.to_string()])
+            .output()
+            .await
+            .ok()
+            .flatten()
+    }
+
+    /// Fetches the user's current round in a tournament (i.e. where they sit) from the backend
+    /// without subscribing to the tournament. `None` is returned if the tournament can not be
+    /// found, the user is not one of its players, or they do not have an active round.
+    pub async fn my_round(&self, id: TournamentId) -> Option<MyRound> {
+        self.get_request::<1, GetMyRound>([&id.to_string()])
+            .output()
+            .await
+            .ok()
+            .flatten()
+    }
+
+    /// Asks the backend to explain how a player's score and tiebreakers were calculated
+    pub async fn explain_score(
+        &self,
+        id: TournamentId,
+        p_id: PlayerId,
+    ) -> Option<ScoreExplanation> {
+        self.get_request::<2, ExplainScore>([&id.to_string(), &p_id.to_string()])
+            .output()
+            .await
+            .ok()
+            .flatten()
+    }
+
+    /// Fetches the operations that the tournament's judges and admins have applied. `None` is
+    /// returned if the tournament can not be found or the user is not one of its admins or judges.
+    pub async fn audit_log(&self, id: TournamentId) -> Option<Vec<AuditEntry>> {
+        self.get_request::<1, GetAuditLog>([&id.to_string()])
+            .output()
+            .await
+            .ok()
+            .flatten()
+    }
+
+    /// Creates or updates a series on the backend. Only the owner of a series can update it.
+    pub fn save_series(&self, series: Series) -> ResponseTracker<bool> {
+        self.post_request(series, [])
+    }
+
+    /// Fetches a series from the backend
+    pub async fn get_series(&self, id: SeriesId) -> Option<Series> {
+        self.get_request::<1, GetSeries>([&id.to_string()])
+            .output()
+            .await
+            .ok()
+            .flatten()
+    }
+
+    /// Fetches the combined leaderboard of a series from the backend
+    pub async fn get_series_standings(&self, id: SeriesId) -> Option<SeriesStandings> {
+        self.get_request::<1, GetSeriesStandings>([&id.to_string()])
+            .output()
+            .await
+            .ok()
+            .flatten()
+    }
+
+    /// Asks the backend for a signed invite to a tournament. The invite's token can be shared with
+    /// the invitees, who check it using [SquireClient::check_invite].
+    pub fn create_invite(
+        &self,
+        id: TournamentId,
+        role: InviteRole,
+        expires: DateTime<Utc>,
+    ) -> ResponseTracker<Result<TournamentInvite, InviteError>> {
+        self.post_request(CreateInvite { role, expires }, [&id.to_string()])
+    }
+
+    /// Asks the backend whether an invite token is valid and, if so, what it grants
+    pub fn check_invite(
+        &self,
+        token: String,
+    ) -> ResponseTracker<Result<TournamentInvite, InviteError>> {
+        self.post_request(CheckInvite { token }, [])
+    }
+
+    /// Sends the invite whenever the client subscribes to the tournament that it is for, which
+    /// lets users follow private tournaments that they are not officials or players of
+    pub fn use_invite(&self, invite: TournamentInvite) {
+        self.client.send(invite)
+    }
+
+    /// Accepts an invite as a guest. Roles can only be given to accounts, so the invite is held by
+    /// the guest's session until they register with [SquireClient::upgrade_guest].
+    pub fn accept_invite(
+        &self,
+        token: String,
+    ) -> ResponseTracker<Result<TournamentInvite, InviteError>> {
+        self.post_request(AcceptInvite { token }, [])
+    }
+
+    /// Gives the logged-in user's account the role that an invite grants. Staff invites can only
+    /// be redeemed once.
+    pub fn redeem_invite(
+        &self,
+        token: String,
+    ) -> ResponseTracker<Result<TournamentInvite, InviteError>> {
+        self.post_request(RedeemInvite { token }, [])
+    }
+
+    /// Registers an account for the current guest. The guest's session is kept, so their
+    /// subscriptions carry on as the new user, and the account is given the roles of the invites
+    /// that the guest accepted. `None` is returned if the backend could not be reached.
+    pub fn upgrade_guest(
+        &self,
+        form: RegForm,
+    ) -> Tracker<Option<Result<GuestUpgrade, UpgradeError>>> {
+        self.client.track(form)
+    }
+
+    /// Asks the backend for a reporting token for each player in a round. The tokens can be
+    /// printed as QR codes so that players can report using [SquireClient::report_result] without
+    /// logging in.
+    pub fn create_report_tokens(
+        &self,
+        id: TournamentId,
+        round: RoundId,
+        expires: DateTime<Utc>,
+    ) -> ResponseTracker<Result<Vec<ReportToken>, ReportError>> {
+        self.post_request(CreateReportTokens { round, expires }, [&id.to_string()])
+    }
+
+    /// Reports the result of a round using a reporting token
+    pub fn report_result(
+        &self,
+        token: String,
+        result: RoundResult,
+    ) -> ResponseTracker<Result<(), ReportError>> {
+        self.post_request(ReportResult { token, result }, [])
+    }
+
+    /// Registers a URL that the backend will POST the tournament's events to (e.g. when a round
+    /// is paired). Only the tournament's admins can register webhooks.
+    pub fn register_webhook(
+        &self,
+        id: TournamentId,
+        url: String,
+    ) -> ResponseTracker<Result<Webhook, WebhookError>> {
+        self.post_request(RegisterWebhook { url }, [&id.to_string()])
+    }
+
+    /// Fetches the webhooks that are registered for a tournament. `None` is returned if the
+    /// tournament can not be found or the user is not one of its admins.
+    pub async fn list_webhooks(&self, id: TournamentId) -> Option<Vec<Webhook>> {
+        self.get_request::<1, ListWebhooks>([&id.to_string()])
+            .output()
+            .await
+            .ok()
+            .flatten()
+    }
+
+    /// Removes one of a tournament's webhooks. Only the tournament's admins can remove webhooks.
+    pub fn delete_webhook(&self, id: TournamentId, w_id: WebhookId) -> ResponseTracker<bool> {
+        self.delete_request::<2, DeleteWebhook>([&id.to_string(), &w_id.to_string()])
+    }
+
+    /// Asks the backend to read a registration sheet, then applies the registrations from every row
+    /// that could be registered. The returned report lists how each row of the sheet was handled.
+    pub async fn import_registrations(
+        &self,
+        id: TournamentId,
+        req: ImportRegistrations,
+    ) -> Result<RegistrationImport, ImportError> {
+        let digest = self
+            .post_request(req, [&id.to_string()])
+            .output()
+            .await
+            .map_err(|_| ImportError::FetchFailed)??;
+        match self.bulk_update(id, digest.ops.clone()).await {
+            None => Err(ImportError::TournamentNotFound),
+            Some(Err(err)) => Err(ImportError::NotApplied(err)),
+            Some(Ok(_)) => Ok(digest),
+        }
+    }
+
+    /// Returns the summaries of all tournaments known to the client. This includes tournaments
+    /// that only exist locally as well as those the backend has listed. The backend's list is
+    /// refreshed in the background, and the `on_update` callback is called with the id of each
+    /// tournament that is added, removed, or changed by a refresh.
+    pub fn get_tourn_directory(&self) -> Tracker<Vec<TournamentSummary>> {
+        self.tourns.directory()
+    }
+
+    /// Returns the current state of every tournament that the client holds in a single query,
+    /// ordered by name
+    pub fn get_tourn_overviews(&self) -> Tracker<Vec<TournamentOverview>> {
+        self.tourns.overviews()
+    }
+
+    /// Refreshes the tournament directory without waiting for the next scheduled refresh
+    pub fn refresh_tourn_directory(&self) {
+        self.tourns.refresh_directory()
     }
 
     pub async fn get_tourn_role(&self, id: TournamentId) -> TournRole {
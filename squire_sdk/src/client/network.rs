@@ -1,26 +1,115 @@
-use std::fmt::Debug;
+use std::{collections::HashMap, fmt::Debug, time::Duration};
 
+use chrono::{DateTime, Utc};
 use derive_more::From;
 use futures::SinkExt;
 use squire_lib::{accounts::SquireAccount, tournament::TournamentId};
 
-use super::session::{SessionBroadcaster, SessionWatcher};
+use super::{
+    handoff::ConnectionHandle,
+    request_url,
+    session::{SessionBroadcaster, SessionWatcher},
+    websocket_url,
+};
 use crate::{
     actor::*,
-    api::{Credentials, GuestSession, Login, PostRequest, SessionToken},
+    api::{
+        Credentials, GuestSession, GuestUpgrade, HandoffSession, Login, PostRequest, RegForm,
+        SessionToken, TournamentInvite, UpgradeError, UpgradeGuest,
+    },
     compat::{
         log, Client, NetworkError, NetworkResponse, Request, Response, Sendable, SendableFuture,
         Websocket, WebsocketMessage,
     },
+    sync::{Compression, Handshake},
 };
 
 pub type NetworkClient = ActorClient<NetworkState>;
 
+/// The compression that the client asks the backend to use for Websocket messages. The client also
+/// compresses large messages that it sends with this scheme.
+pub(crate) const WS_COMPRESSION: Compression = Compression::Deflate;
+
 #[derive(Debug)]
 pub struct NetworkState {
     session: SessionBroadcaster,
     token: Option<SessionToken>,
     client: Client,
+    /// The tokens of the invites that are sent when subscribing to private tournaments
+    invites: HashMap<TournamentId, String>,
+}
+
+/// The state of a subscription's connection to the backend
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ConnectionState {
+    /// The websocket is open and updates are being received
+    #[default]
+    Connected,
+    /// The websocket dropped and the client is trying to reconnect. This holds the number of
+    /// attempts that have failed so far.
+    Reconnecting(u32),
+}
+
+/// How long to wait between attempts to reconnect a dropped websocket. The delay doubles after
+/// every failed attempt until it reaches the maximum.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Backoff {
+    /// The delay before the first attempt
+    pub initial: Duration,
+    /// The longest that the client will wait between attempts
+    pub max: Duration,
+}
+
+impl Backoff {
+    /// Returns how long to wait after the given number of failed attempts
+    pub fn delay(&self, failures: u32) -> Duration {
+        self.initial
+            .saturating_mul(2u32.saturating_pow(failures))
+            .min(self.max)
+    }
+}
+
+impl Default for Backoff {
+    fn default() -> Self {
+        Self {
+            initial: Duration::from_millis(500),
+            max: Duration::from_secs(30),
+        }
+    }
+}
+
+/// Tracks the subscriptions whose websockets have dropped and how many times they have failed to
+/// reconnect.
+#[derive(Debug, Default)]
+pub(crate) struct ReconnectionManager {
+    backoff: Backoff,
+    failures: HashMap<TournamentId, u32>,
+}
+
+impl ReconnectionManager {
+    /// Marks the subscription as dropped and returns how long to wait before reconnecting
+    pub(crate) fn dropped(&mut self, id: TournamentId) -> Duration {
+        _ = self.failures.insert(id, 0);
+        self.backoff.delay(0)
+    }
+
+    /// Records a failed attempt to reconnect. Returns the number of failed attempts and how long
+    /// to wait before the next one.
+    pub(crate) fn failed(&mut self, id: TournamentId) -> (u32, Duration) {
+        let failures = self.failures.entry(id).or_default();
+        *failures += 1;
+        (*failures, self.backoff.delay(*failures))
+    }
+
+    /// Returns if the subscription is waiting to be reconnected
+    pub(crate) fn is_reconnecting(&self, id: &TournamentId) -> bool {
+        self.failures.contains_key(id)
+    }
+
+    /// Marks the subscription as reconnected
+    pub(crate) fn reconnected(&mut self, id: &TournamentId) {
+        _ = self.failures.remove(id);
+    }
 }
 
 /// Encapsulates all of the ways that a login attempt can fail.
@@ -43,9 +132,15 @@ pub enum NetworkCommand {
         OneshotSender<Result<SquireAccount, LoginError>>,
     ),
     LoginComplete(Option<(SquireAccount, SessionToken)>),
+    Upgrade(
+        RegForm,
+        OneshotSender<Option<Result<GuestUpgrade, UpgradeError>>>,
+    ),
     GuestLogin(OneshotSender<SessionWatcher>),
     GuestLoginComplete(Option<SessionToken>, OneshotSender<SessionWatcher>),
     OpenWebsocket(TournamentId, OneshotSender<Option<Websocket>>),
+    MintHandle(TournamentId, OneshotSender<Option<ConnectionHandle>>),
+    UseInvite(TournamentInvite),
 }
 
 #[async_trait]
@@ -53,6 +148,10 @@ impl ActorState for NetworkState {
     type Message = NetworkCommand;
 
     async fn start_up(&mut self, _scheduler: &mut Scheduler<Self>) {
+        // Clients built from a connection handle already have a session
+        if self.token.is_some() {
+            return;
+        }
         // TODO: The browser should store a cookie. We should ping the server to get the session
         // info. If that fails, we should ping the server for an guest session.
         let token = self
@@ -101,6 +200,20 @@ impl ActorState for NetworkState {
                     self.session.user_auth(acc);
                 }
             }
+            NetworkCommand::Upgrade(form, send) => {
+                let req = self.json_post_request(UpgradeGuest(form), []);
+                let token = self.token.clone();
+                scheduler.add_task(async move {
+                    let digest = req.await.ok();
+                    // The guest's session now belongs to the new account
+                    let login = match (&digest, token) {
+                        (Some(Ok(upgrade)), Some(token)) => Some((upgrade.account.clone(), token)),
+                        _ => None,
+                    };
+                    drop(send.send(digest));
+                    login
+                });
+            }
             NetworkCommand::GuestLogin(send) => {
                 let req = self.post_request(GuestSession, []);
                 scheduler.add_task(async move {
@@ -118,13 +231,37 @@ impl ActorState for NetworkState {
             }
             NetworkCommand::OpenWebsocket(id, send) => match self.token.clone() {
                 Some(token) => {
-                    let url = format!("/api/v1/tournaments/subscribe/{id}");
+                    let url = websocket_url(format!("/api/v1/tournaments/subscribe/{id}"));
+                    let invite = self.invites.get(&id).cloned();
                     scheduler.process(async move {
-                        drop(send.send(init_ws(Websocket::new(&url).await.ok(), token).await));
+                        let ws = Websocket::new(&url).await.ok();
+                        drop(send.send(init_ws(ws, token, invite).await));
                     });
                 }
                 None => drop(send.send(None)),
             },
+            NetworkCommand::MintHandle(id, send) => {
+                // The handle gets a new guest session so that it does not share this client's
+                // session (or permissions). The backend decides when that session expires.
+                let req = self.post_request(HandoffSession, []);
+                scheduler.process(async move {
+                    let handle = match req.await {
+                        Ok(resp) => match resp.session_token() {
+                            Ok(token) => resp
+                                .json::<DateTime<Utc>>()
+                                .await
+                                .ok()
+                                .map(|expires| ConnectionHandle::new(id, token, expires)),
+                            Err(_) => None,
+                        },
+                        Err(_) => None,
+                    };
+                    drop(send.send(handle));
+                });
+            }
+            NetworkCommand::UseInvite(invite) => {
+                _ = self.invites.insert(invite.tourn, invite.token());
+            }
         }
     }
 }
@@ -135,6 +272,7 @@ impl NetworkState {
         Self {
             session: SessionBroadcaster::new(),
             client: Client::new(),
+            invites: HashMap::new(),
             token: None,
         }
     }
@@ -143,10 +281,33 @@ impl NetworkState {
         Self {
             session: SessionBroadcaster::new_with_user(user),
             client: Client::new(),
+            invites: HashMap::new(),
             token: None,
         }
     }
 
+    /// Creates a network state that authenticates as the user with one of their API tokens
+    pub fn new_with_token(user: SquireAccount, token: SessionToken) -> Self {
+        Self {
+            session: SessionBroadcaster::new_with_user(user),
+            client: Client::new(),
+            invites: HashMap::new(),
+            token: Some(token),
+        }
+    }
+
+    /// Creates a network state that uses the guest session held by the handle
+    pub fn new_with_handle(handle: &ConnectionHandle) -> Self {
+        let mut session = SessionBroadcaster::new();
+        session.guest_auth();
+        Self {
+            session,
+            client: Client::new(),
+            invites: HashMap::new(),
+            token: Some(handle.token.clone()),
+        }
+    }
+
     pub fn subscribe(&self) -> SessionWatcher {
         self.session.subscribe()
     }
@@ -159,7 +320,7 @@ impl NetworkState {
     where
         B: Sendable + PostRequest<N>,
     {
-        let req = Request::post(&B::ROUTE.replace(subs))
+        let req = Request::post(&request_url(B::ROUTE.replace(subs)))
             .session(self.token.as_ref())
             .json(&body);
         self.client.execute(req)
@@ -179,9 +340,18 @@ impl NetworkState {
     }
 }
 
-async fn init_ws(mut ws: Option<Websocket>, token: SessionToken) -> Option<Websocket> {
+async fn init_ws(
+    mut ws: Option<Websocket>,
+    token: SessionToken,
+    invite: Option<String>,
+) -> Option<Websocket> {
     if let Some(ws) = ws.as_mut() {
-        let msg = WebsocketMessage::Bytes(postcard::to_allocvec(&token).unwrap());
+        let handshake = Handshake {
+            token,
+            compression: WS_COMPRESSION,
+            invite,
+        };
+        let msg = WebsocketMessage::Bytes(postcard::to_allocvec(&handshake).unwrap());
         ws.send(msg).await.ok()?;
     }
     ws
@@ -193,6 +363,7 @@ impl Debug for NetworkCommand {
             NetworkCommand::Request(_, _) => write!(f, "NetworkCommand::Request(..)"),
             NetworkCommand::Login(cred, _) => write!(f, "NetworkCommand::Login({cred:?})"),
             NetworkCommand::GuestLogin(_) => write!(f, "NetworkCommand::GuestLogin"),
+            NetworkCommand::Upgrade(_, _) => write!(f, "NetworkCommand::Upgrade(..)"),
             NetworkCommand::LoginComplete(login_comp) => {
                 write!(f, "NetworkCommand::LoginComplete({login_comp:?})")
             }
@@ -203,6 +374,12 @@ impl Debug for NetworkCommand {
             NetworkCommand::OpenWebsocket(id, _) => {
                 write!(f, "NetworkCommand::OpenWebsocket({id})")
             }
+            NetworkCommand::MintHandle(id, _) => {
+                write!(f, "NetworkCommand::MintHandle({id})")
+            }
+            NetworkCommand::UseInvite(invite) => {
+                write!(f, "NetworkCommand::UseInvite({})", invite.tourn)
+            }
         }
     }
 }
@@ -212,3 +389,40 @@ impl From<((), OneshotSender<SessionWatcher>)> for NetworkCommand {
         NetworkCommand::GuestLogin(send)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use squire_lib::identifiers::TournamentId;
+    use uuid::Uuid;
+
+    use super::{Backoff, ReconnectionManager};
+
+    #[test]
+    fn backoff_doubles_until_max() {
+        let backoff = Backoff {
+            initial: Duration::from_secs(1),
+            max: Duration::from_secs(10),
+        };
+        assert_eq!(backoff.delay(0), Duration::from_secs(1));
+        assert_eq!(backoff.delay(1), Duration::from_secs(2));
+        assert_eq!(backoff.delay(3), Duration::from_secs(8));
+        assert_eq!(backoff.delay(4), Duration::from_secs(10));
+        assert_eq!(backoff.delay(u32::MAX), Duration::from_secs(10));
+    }
+
+    #[test]
+    fn reconnection_tracking() {
+        let backoff = Backoff::default();
+        let mut manager = ReconnectionManager::default();
+        let id = TournamentId::new(Uuid::new_v4());
+        assert!(!manager.is_reconnecting(&id));
+        assert_eq!(manager.dropped(id), backoff.delay(0));
+        assert!(manager.is_reconnecting(&id));
+        assert_eq!(manager.failed(id), (1, backoff.delay(1)));
+        assert_eq!(manager.failed(id), (2, backoff.delay(2)));
+        manager.reconnected(&id);
+        assert!(!manager.is_reconnecting(&id));
+    }
+}
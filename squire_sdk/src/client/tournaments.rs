@@ -1,23 +1,38 @@
-use std::collections::{hash_map::Entry, HashMap};
+use std::{
+    collections::{hash_map::Entry, HashMap, HashSet},
+    mem,
+    time::Duration,
+};
 
+use chrono::Utc;
 use derive_more::From;
-use futures::{stream::SplitSink, FutureExt, SinkExt, StreamExt};
+use futures::{
+    future::ready,
+    stream::{once, SplitSink},
+    FutureExt, SinkExt, StreamExt,
+};
 use instant::Instant;
 use squire_lib::{
+    identifiers::{PlayerId, RoundId},
     operations::{OpData, OpResult, TournOp},
     tournament::TournamentId,
 };
 use tokio::sync::watch::{channel as watch_channel, Receiver as Watcher, Sender as Broadcaster};
 use uuid::Uuid;
 
-use super::{network::NetworkState, OnUpdate};
+use super::{
+    network::{ConnectionState, NetworkState, ReconnectionManager, WS_COMPRESSION},
+    request_url, OnUpdate,
+};
 use crate::{
     actor::*,
-    compat::{log, Websocket, WebsocketError, WebsocketMessage, WebsocketResult},
+    api::{GetRequest, ListTournaments, TournamentOverview, TournamentPage, TournamentSummary},
+    compat::{log, Request, Storage, Websocket, WebsocketMessage, WebsocketResult},
     sync::{
         ClientBound, ClientBoundMessage, ClientForwardingManager, ClientOpLink, ClientSyncManager,
-        OpSync, ServerBound, ServerBoundMessage, ServerOpLink, SyncForwardResp, TournamentManager,
-        WebSocketMessage, RETRY_LIMIT,
+        Disagreement, Divergence, FullOp, LogDigest, OpId, OpSync, Presence, RecentMessages,
+        Rollback, RollbackError, ServerBound, ServerBoundMessage, ServerClock, ServerOpLink,
+        SyncForwardResp, TournamentManager, WebSocketMessage, RETRY_LIMIT,
     },
 };
 
@@ -30,12 +45,44 @@ pub struct TournsClient {
 #[derive(From)]
 pub(crate) enum ManagementCommand {
     Query(TournamentId, Query),
+    QueryMany(Vec<TournamentId>, QueryMany),
+    SubscribeQuery(TournamentId, NewSelection),
     Update((TournamentId, UpdateType), OneshotSender<Option<OpResult>>),
     Import(Box<TournamentManager>, OneshotSender<TournamentId>),
     Subscribe(TournamentId, OneshotSender<Option<Watcher<()>>>),
-    Connection(Option<Websocket>, OneshotSender<Option<Watcher<()>>>),
+    Connection(
+        TournamentId,
+        Option<Websocket>,
+        OneshotSender<Option<Watcher<()>>>,
+    ),
+    Status(
+        TournamentId,
+        OneshotSender<Option<Watcher<ConnectionState>>>,
+    ),
+    Presence(TournamentId, OneshotSender<Option<Watcher<Presence>>>),
+    Clock(TournamentId, OneshotSender<Option<Watcher<ServerClock>>>),
+    Tardy(
+        TournamentId,
+        OneshotSender<Option<Watcher<Vec<(RoundId, PlayerId)>>>>,
+    ),
+    Divergence(
+        TournamentId,
+        OneshotSender<Option<Watcher<Option<Divergence>>>>,
+    ),
+    DroppedOps(TournamentId, OneshotSender<Option<Watcher<Vec<FullOp>>>>),
+    Rollback(
+        (TournamentId, Rollback),
+        OneshotSender<Option<Result<(), RollbackError>>>,
+    ),
     Remote(WebsocketResult),
+    Dropped(Dropped),
+    Reconnect(Reconnect),
+    Reconnected(TournamentId, Option<Websocket>),
     Retry(MessageRetry),
+    Directory(OneshotSender<Vec<TournamentSummary>>),
+    Overview(OneshotSender<Vec<TournamentOverview>>),
+    Refresh(DirectoryRefresh),
+    Refreshed(DirectoryRefresh, Option<Vec<TournamentSummary>>),
 }
 
 /// A struct that contains all of the state that the management task maintains
@@ -45,18 +92,46 @@ struct ManagerState {
     syncs: ClientSyncManager,
     network: ActorClient<NetworkState>,
     forwarded: ClientForwardingManager,
-    on_update: Box<dyn OnUpdate>,
+    directory: TournDirectory,
+    notifier: Notifier,
+    /// The query subscriptions for each tournament
+    selections: HashMap<TournamentId, Vec<Box<dyn Selection>>>,
+    storage: Option<Storage>,
+    reconnections: ReconnectionManager,
+    rollbacks: PendingRollbacks,
 }
 
+/// The rollback requests that are waiting on a response from the backend, keyed by message id
+type PendingRollbacks = HashMap<
+    Uuid,
+    (
+        TournamentId,
+        OneshotSender<Option<Result<(), RollbackError>>>,
+    ),
+>;
+
 #[async_trait]
 impl ActorState for ManagerState {
     type Message = ManagementCommand;
 
+    async fn start_up(&mut self, scheduler: &mut Scheduler<Self>) {
+        self.restore().await;
+        self.refresh_directory(scheduler, DirectoryRefresh::Scheduled);
+    }
+
     async fn process(&mut self, scheduler: &mut Scheduler<Self>, msg: Self::Message) {
         match msg {
             ManagementCommand::Query(id, query) => {
                 self.handle_query(id, query);
             }
+            ManagementCommand::QueryMany(ids, query) => {
+                self.handle_query_many(&ids, query);
+            }
+            ManagementCommand::SubscribeQuery(id, init) => {
+                if let Some(selection) = init(self.cache.get(&id).map(|tc| &tc.tourn)) {
+                    self.selections.entry(id).or_default().push(selection);
+                }
+            }
             ManagementCommand::Import(tourn, send) => {
                 let _ = send.send(self.handle_import(*tourn));
             }
@@ -72,21 +147,93 @@ impl ActorState for ManagerState {
                     let tracker = self.network.track(id);
                     scheduler.add_task(tracker.map(|ws| {
                         log("Got response from network actor!");
-                        ManagementCommand::Connection(ws, send)
+                        ManagementCommand::Connection(id, ws, send)
                     }));
                 }
             },
-            ManagementCommand::Connection(res, send) => match res {
-                Some(mut ws) => {
-                    let tourn = wait_for_tourn(&mut ws).await;
-                    drop(send.send(Some(self.handle_connection(scheduler, ws, tourn))));
-                }
-                None => drop(send.send(None)),
-            },
+            ManagementCommand::Connection(id, res, send) => {
+                let sub = match res {
+                    Some(ws) => self.connect(scheduler, id, ws).await,
+                    None => None,
+                };
+                drop(send.send(sub));
+            }
+            ManagementCommand::Status(id, send) => {
+                let status = self
+                    .cache
+                    .get(&id)
+                    .and_then(|tc| tc.comm.as_ref())
+                    .map(|comm| comm.status.subscribe());
+                drop(send.send(status));
+            }
+            ManagementCommand::Presence(id, send) => {
+                let presence = self
+                    .cache
+                    .get(&id)
+                    .and_then(|tc| tc.comm.as_ref())
+                    .map(|comm| comm.presence.subscribe());
+                drop(send.send(presence));
+            }
+            ManagementCommand::Clock(id, send) => {
+                let clock = self
+                    .cache
+                    .get(&id)
+                    .and_then(|tc| tc.comm.as_ref())
+                    .map(|comm| comm.clock.subscribe());
+                drop(send.send(clock));
+            }
+            ManagementCommand::Tardy(id, send) => {
+                let tardy = self
+                    .cache
+                    .get(&id)
+                    .and_then(|tc| tc.comm.as_ref())
+                    .map(|comm| comm.tardy.subscribe());
+                drop(send.send(tardy));
+            }
+            ManagementCommand::Divergence(id, send) => {
+                let divergence = self
+                    .cache
+                    .get(&id)
+                    .and_then(|tc| tc.comm.as_ref())
+                    .map(|comm| comm.divergence.subscribe());
+                drop(send.send(divergence));
+            }
+            ManagementCommand::DroppedOps(id, send) => {
+                let dropped = self
+                    .cache
+                    .get(&id)
+                    .and_then(|tc| tc.comm.as_ref())
+                    .map(|comm| comm.dropped.subscribe());
+                drop(send.send(dropped));
+            }
+            ManagementCommand::Rollback((id, rollback), send) => {
+                self.handle_rollback(scheduler, id, rollback, send).await
+            }
             ManagementCommand::Remote(ws_res) => match ws_res {
                 Ok(msg) => drop(self.handle_ws_msg(scheduler, msg)),
-                Err(err) => self.handle_ws_err(err),
+                // The websocket's stream ends after an error, which is handled as a drop
+                Err(err) => log(&format!("Got error from Websocket: {err:?}")),
             },
+            ManagementCommand::Dropped(Dropped(id)) => self.handle_dropped(scheduler, id),
+            ManagementCommand::Reconnect(Reconnect(id)) => {
+                // The tournament might have been removed while waiting
+                if self.cache.get(&id).is_some_and(|tc| tc.comm.is_some()) {
+                    let tracker = self.network.track(id);
+                    scheduler
+                        .add_task(tracker.map(move |ws| ManagementCommand::Reconnected(id, ws)));
+                } else {
+                    self.reconnections.reconnected(&id);
+                }
+            }
+            ManagementCommand::Reconnected(id, res) => {
+                let connected = match res {
+                    Some(ws) => self.connect(scheduler, id, ws).await.is_some(),
+                    None => false,
+                };
+                if !connected {
+                    self.handle_failed_reconnect(scheduler, id);
+                }
+            }
             ManagementCommand::Retry(MessageRetry { msg, id }) => {
                 if self.syncs.is_latest_msg(&msg) {
                     if let Some(comm) = self.cache.get_mut(&id) {
@@ -94,12 +241,46 @@ impl ActorState for ManagerState {
                     }
                 }
             }
+            ManagementCommand::Directory(send) => {
+                let _ = send.send(self.directory.summaries(&self.cache));
+            }
+            ManagementCommand::Overview(send) => {
+                let mut overviews: Vec<_> = self
+                    .cache
+                    .values()
+                    .map(|tc| TournamentOverview::from(tc.tourn.tourn()))
+                    .collect();
+                overviews.sort_by(|a, b| a.name.cmp(&b.name).then(a.id.cmp(&b.id)));
+                let _ = send.send(overviews);
+            }
+            ManagementCommand::Refresh(refresh) => self.refresh_directory(scheduler, refresh),
+            ManagementCommand::Refreshed(refresh, summaries) => {
+                if let Some(summaries) = summaries {
+                    self.handle_directory(summaries);
+                }
+                // Only the scheduled refreshes keep the refresh loop going, so manual refreshes
+                // never start a second loop
+                if refresh == DirectoryRefresh::Scheduled {
+                    scheduler.schedule(
+                        Instant::now() + DIRECTORY_REFRESH,
+                        DirectoryRefresh::Scheduled,
+                    );
+                }
+            }
         }
+        self.update_selections();
     }
 }
 
 pub const MANAGEMENT_PANICKED_MSG: &str = "tournament management task panicked";
 
+/// How long the management task waits between refreshes of the tournament directory
+pub const DIRECTORY_REFRESH: Duration = Duration::from_secs(30);
+
+/// How many times a tournament is fetched while connecting before a copy that does not match its
+/// digest is treated as a failed connection
+const FETCH_ATTEMPTS: usize = 3;
+
 #[derive(Debug, Clone)]
 pub enum UpdateType {
     Removal,
@@ -109,9 +290,64 @@ pub enum UpdateType {
 
 type Query = Box<dyn Send + FnOnce(Option<&TournamentManager>)>;
 
+type QueryMany = Box<dyn Send + FnOnce(Vec<&TournamentManager>)>;
+
+/// Starts a query subscription. Nothing is returned if the tournament is not held by the client.
+type NewSelection =
+    Box<dyn Send + FnOnce(Option<&TournamentManager>) -> Option<Box<dyn Selection>>>;
+
+/// A query subscription, which re-evaluates its selector whenever its tournament changes
+pub(crate) trait Selection: Send {
+    /// Re-evaluates the selector and notifies the watchers if the selected value changed. Returns
+    /// `false` once every watcher has been dropped, at which point the selection can be removed.
+    fn update(&mut self, tourn: &TournamentManager) -> bool;
+}
+
+struct Selector<F, T> {
+    select: F,
+    broad: Broadcaster<T>,
+}
+
+impl<F, T> Selection for Selector<F, T>
+where
+    F: 'static + Send + FnMut(&TournamentManager) -> T,
+    T: 'static + Send + Sync + PartialEq,
+{
+    fn update(&mut self, tourn: &TournamentManager) -> bool {
+        let value = (self.select)(tourn);
+        _ = self.broad.send_if_modified(|current| {
+            let changed = *current != value;
+            if changed {
+                *current = value;
+            }
+            changed
+        });
+        !self.broad.is_closed()
+    }
+}
+
+/// Tells the UI when a tournament changes and tracks which tournaments have changed, so that their
+/// query subscriptions can be updated
+struct Notifier {
+    on_update: Box<dyn OnUpdate>,
+    /// The tournaments that have changed since the query subscriptions were last updated
+    changed: HashSet<TournamentId>,
+}
+
+impl Notifier {
+    fn notify(&mut self, id: TournamentId) {
+        (self.on_update)(id);
+        _ = self.changed.insert(id);
+    }
+}
+
 impl TournsClient {
-    pub fn new<O: OnUpdate>(network: ActorClient<NetworkState>, on_update: O) -> Self {
-        let client = ActorBuilder::new(ManagerState::new(network, on_update)).launch();
+    pub fn new<O: OnUpdate>(
+        network: ActorClient<NetworkState>,
+        on_update: O,
+        storage: Option<Storage>,
+    ) -> Self {
+        let client = ActorBuilder::new(ManagerState::new(network, on_update, storage)).launch();
         Self { client }
     }
 
@@ -123,6 +359,58 @@ impl TournsClient {
         self.client.track(id)
     }
 
+    /// Returns a watcher for the state of the subscription's connection to the backend. `None` is
+    /// returned if the tournament is not subscribed to.
+    pub fn connection_state(&self, id: TournamentId) -> Tracker<Option<Watcher<ConnectionState>>> {
+        self.client.track(id)
+    }
+
+    /// Returns a watcher for who is connected to the tournament. `None` is returned if the
+    /// tournament is not subscribed to.
+    pub fn presence(&self, id: TournamentId) -> Tracker<Option<Watcher<Presence>>> {
+        self.client.track(id)
+    }
+
+    /// Returns a watcher for the backend's clock, which is used to calculate how much time is left
+    /// in the tournament's rounds. `None` is returned if the tournament is not subscribed to.
+    pub fn clock(&self, id: TournamentId) -> Tracker<Option<Watcher<ServerClock>>> {
+        self.client.track(id)
+    }
+
+    /// Returns a watcher for the players that have not checked in at their tables within the grace
+    /// period of the tournament's tardiness policy. `None` is returned if the tournament is not
+    /// subscribed to.
+    pub fn tardy_players(
+        &self,
+        id: TournamentId,
+    ) -> Tracker<Option<Watcher<Vec<(RoundId, PlayerId)>>>> {
+        self.client.track(id)
+    }
+
+    /// Returns a watcher for the last time that the local copy of the tournament was found to no
+    /// longer match the backend's copy. `None` is returned if the tournament is not subscribed to.
+    pub fn divergence(&self, id: TournamentId) -> Tracker<Option<Watcher<Option<Divergence>>>> {
+        self.client.track(id)
+    }
+
+    /// Returns a watcher for the last unsynced operations that were dropped because they could no
+    /// longer be applied on top of the backend's copy. `None` is returned if the tournament is not
+    /// subscribed to.
+    pub fn dropped_ops(&self, id: TournamentId) -> Tracker<Option<Watcher<Vec<FullOp>>>> {
+        self.client.track(id)
+    }
+
+    /// Asks the backend to roll the tournament back to a checkpoint. Once the backend has rolled it
+    /// back, the local copy catches up to the backend's. `None` is returned if the tournament is
+    /// not connected to the backend or the connection drops before the backend responds.
+    pub fn rollback(
+        &self,
+        id: TournamentId,
+        checkpoint: String,
+    ) -> Tracker<Option<Result<(), RollbackError>>> {
+        self.client.track((id, Rollback { checkpoint }))
+    }
+
     pub fn query<F, T>(&self, id: TournamentId, query: F) -> Tracker<Option<T>>
     where
         F: 'static + Send + FnOnce(&TournamentManager) -> T,
@@ -131,6 +419,53 @@ impl TournsClient {
         self.client.track((id, query))
     }
 
+    /// Runs a query against several tournaments at once, which saves a round trip through the
+    /// management task for each of them. The results are keyed by tournament id. Tournaments that
+    /// are not held by the client are left out.
+    pub fn query_many<F, T>(
+        &self,
+        ids: Vec<TournamentId>,
+        query: F,
+    ) -> Tracker<HashMap<TournamentId, T>>
+    where
+        F: 'static + Send + FnMut(&TournamentManager) -> T,
+        T: 'static + Send,
+    {
+        self.client.track((ids, query))
+    }
+
+    /// Subscribes to part of a tournament. The selector is re-evaluated whenever the tournament
+    /// changes, but the watcher is only notified when the selected value differs from the last
+    /// one. This lets UI components skip re-rendering on unrelated updates. `None` is returned if
+    /// the tournament is not held by the client.
+    pub fn subscribe_query<F, T>(
+        &self,
+        id: TournamentId,
+        mut selector: F,
+    ) -> Tracker<Option<Watcher<T>>>
+    where
+        F: 'static + Send + FnMut(&TournamentManager) -> T,
+        T: 'static + Send + Sync + PartialEq,
+    {
+        let (send, recv) = oneshot_channel();
+        let init: NewSelection = Box::new(move |tourn| {
+            let Some(tourn) = tourn else {
+                let _ = send.send(None);
+                return None;
+            };
+            let (broad, watcher) = watch_channel(selector(tourn));
+            let _ = send.send(Some(watcher));
+            let selection = Selector {
+                select: selector,
+                broad,
+            };
+            Some(Box::new(selection) as Box<dyn Selection>)
+        });
+        self.client
+            .send(ManagementCommand::SubscribeQuery(id, init));
+        Tracker::new(recv)
+    }
+
     pub async fn query_or_default<F, T>(&self, id: TournamentId, query: F) -> T
     where
         F: 'static + Send + FnOnce(&TournamentManager) -> T,
@@ -142,6 +477,23 @@ impl TournsClient {
     pub fn update(&self, id: TournamentId, update: UpdateType) -> Tracker<Option<OpResult>> {
         self.client.track((id, update))
     }
+
+    pub fn directory(&self) -> Tracker<Vec<TournamentSummary>> {
+        self.client.track(())
+    }
+
+    /// Returns the current state of every tournament held by the client, e.g. for an organizer's
+    /// dashboard. Unlike the directory, tournaments that are only known to the backend are not
+    /// included.
+    pub fn overviews(&self) -> Tracker<Vec<TournamentOverview>> {
+        self.client.track(())
+    }
+
+    /// Requests that the directory be refreshed now rather than waiting for the next scheduled
+    /// refresh.
+    pub fn refresh_directory(&self) {
+        self.client.send(DirectoryRefresh::Manual)
+    }
 }
 
 /// Contains all the info needed to track a tournament and all outbound communication related to
@@ -150,31 +502,163 @@ impl TournsClient {
 #[derive(Debug)]
 struct TournComm {
     tourn: TournamentManager,
-    comm: Option<(SplitSink<Websocket, WebsocketMessage>, Broadcaster<()>)>,
+    comm: Option<Comm>,
+}
+
+/// The connection to the backend for a tournament that has been subscribed to. The subscription
+/// outlives any one websocket, so the broadcasters are kept while the client reconnects.
+#[derive(Debug)]
+struct Comm {
+    /// The sending half of the websocket. This is `None` while the client is reconnecting.
+    sink: Option<SplitSink<Websocket, WebsocketMessage>>,
+    broad: Broadcaster<()>,
+    status: Broadcaster<ConnectionState>,
+    presence: Broadcaster<Presence>,
+    clock: Broadcaster<ServerClock>,
+    tardy: Broadcaster<Vec<(RoundId, PlayerId)>>,
+    divergence: Broadcaster<Option<Divergence>>,
+    /// The unsynced operations that were dropped the last time any could not be reapplied
+    dropped: Broadcaster<Vec<FullOp>>,
+    /// The messages that were recently received, which are used to spot retransmissions
+    seen: RecentMessages,
+}
+
+impl Comm {
+    fn new() -> Self {
+        Self {
+            sink: None,
+            broad: watch_channel(()).0,
+            status: watch_channel(ConnectionState::Connected).0,
+            presence: watch_channel(Presence::default()).0,
+            clock: watch_channel(ServerClock::default()).0,
+            tardy: watch_channel(Vec::new()).0,
+            divergence: watch_channel(None).0,
+            dropped: watch_channel(Vec::new()).0,
+            seen: RecentMessages::new(),
+        }
+    }
 }
 
 type TournamentCache = HashMap<TournamentId, TournComm>;
 
+/// The summaries of the tournaments that the backend has told us about. The backend's list is
+/// periodically refreshed and is merged with any locally known tournaments on read.
+#[derive(Debug, Default)]
+struct TournDirectory {
+    remote: HashMap<TournamentId, TournamentSummary>,
+}
+
+impl TournDirectory {
+    /// Replaces the known remote summaries and returns the ids of all tournaments that were added,
+    /// removed, or changed.
+    fn update(&mut self, summaries: Vec<TournamentSummary>) -> Vec<TournamentId> {
+        let remote: HashMap<_, _> = summaries.into_iter().map(|s| (s.id, s)).collect();
+        let mut digest: Vec<_> = remote
+            .iter()
+            .filter(|(id, s)| self.remote.get(id) != Some(s))
+            .map(|(id, _)| *id)
+            .collect();
+        digest.extend(self.remote.keys().filter(|id| !remote.contains_key(id)));
+        self.remote = remote;
+        digest
+    }
+
+    /// Returns the summaries of all known tournaments. Locally cached tournaments take precedence
+    /// over the backend's summaries since they are the more up-to-date copy.
+    fn summaries(&self, cache: &TournamentCache) -> Vec<TournamentSummary> {
+        let mut digest: Vec<_> = cache
+            .values()
+            .map(|tc| TournamentSummary::from(&tc.tourn))
+            .chain(
+                self.remote
+                    .values()
+                    .filter(|s| !cache.contains_key(&s.id))
+                    .cloned(),
+            )
+            .collect();
+        digest.sort_by(|a, b| a.name.cmp(&b.name).then(a.id.cmp(&b.id)));
+        digest
+    }
+}
+
+/// The backend's response to fetching a tournament when connecting to it
+enum Fetched {
+    /// A full copy of the tournament
+    Full(Box<TournamentManager>),
+    /// The operations after the last operation that was synced
+    Delta(OpSync),
+}
+
 enum SubCreation {
     Connected(Watcher<()>),
     Connect(TournamentId),
 }
 
 impl ManagerState {
-    fn new<O: OnUpdate>(network: ActorClient<NetworkState>, on_update: O) -> Self {
+    fn new<O: OnUpdate>(
+        network: ActorClient<NetworkState>,
+        on_update: O,
+        storage: Option<Storage>,
+    ) -> Self {
         Self {
-            on_update: Box::new(on_update),
+            notifier: Notifier {
+                on_update: Box::new(on_update),
+                changed: HashSet::new(),
+            },
+            selections: Default::default(),
+            storage,
             cache: Default::default(),
             syncs: Default::default(),
             forwarded: Default::default(),
+            directory: Default::default(),
+            reconnections: Default::default(),
+            rollbacks: Default::default(),
             network,
         }
     }
 
+    /// Fetches every page of the backend's tournament list in the background. The directory is
+    /// only updated if all of the pages were fetched.
+    fn refresh_directory(&mut self, scheduler: &mut Scheduler<Self>, refresh: DirectoryRefresh) {
+        let network = self.network.clone();
+        scheduler.add_task(async move {
+            let mut summaries = Vec::new();
+            let mut page = 0;
+            let mut page_count = 1;
+            while page < page_count {
+                let route = <ListTournaments as GetRequest<1>>::ROUTE.replace([&page.to_string()]);
+                let tracker = network.track(Request::get(&request_url(route)));
+                let fetched = match tracker.await.inner() {
+                    Ok(resp) => resp.json::<TournamentPage>().await.ok(),
+                    Err(_) => None,
+                };
+                let Some(fetched) = fetched else {
+                    return ManagementCommand::Refreshed(refresh, None);
+                };
+                page_count = fetched.page_count();
+                summaries.extend(fetched.summaries);
+                page += 1;
+            }
+            ManagementCommand::Refreshed(refresh, Some(summaries))
+        });
+    }
+
+    /// Updates the directory and notifies the UI of all changes that are not hidden by local
+    /// tournaments.
+    fn handle_directory(&mut self, summaries: Vec<TournamentSummary>) {
+        for id in self.directory.update(summaries) {
+            if !self.cache.contains_key(&id) {
+                self.notifier.notify(id);
+            }
+        }
+    }
+
     fn handle_import(&mut self, tourn: TournamentManager) -> TournamentId {
         let id = tourn.id;
         let tc = TournComm { tourn, comm: None };
-        _ = self.cache.insert(id, tc);
+        if self.cache.insert(id, tc).is_none() {
+            self.notifier.notify(id);
+        }
         id
     }
 
@@ -190,82 +674,241 @@ impl ManagerState {
             UpdateType::Bulk(ops) => tourn.tourn.bulk_apply_ops(ops),
             UpdateType::Removal => {
                 let _ = self.cache.remove(&id);
+                // Dropping the selections lets their watchers know that the tournament is gone
+                let _ = self.selections.remove(&id);
+                self.persist(id).await;
                 return Some(Ok(OpData::Nothing));
             }
         };
         if res.is_ok() {
-            (self.on_update)(id);
-            let id = Uuid::new_v4();
-            let sync: ClientOpLink = tourn.tourn.sync_request().into();
-            self.syncs
-                .initialize_chain(id, tourn.tourn.id, sync.clone())
-                .unwrap(); // TODO: Remove unwrap
-            let msg = ServerBoundMessage {
-                id,
-                body: sync.into(),
-            };
-            tourn.send(scheduler, msg).await;
+            self.notifier.notify(id);
+            self.persist(id).await;
+            self.start_sync(scheduler, id).await;
         }
         Some(res)
     }
 
+    /// Loads the tournaments that had unsynced operations when the client last stopped. They are
+    /// synced once they are subscribed to.
+    async fn restore(&mut self) {
+        let Some(storage) = self.storage.as_ref() else {
+            return;
+        };
+        for bytes in storage.load_all().await {
+            let Ok(tourn) = postcard::from_bytes::<TournamentManager>(&bytes) else {
+                continue;
+            };
+            let id = tourn.id;
+            if let Entry::Vacant(entry) = self.cache.entry(id) {
+                let _ = entry.insert(TournComm { tourn, comm: None });
+                self.notifier.notify(id);
+            }
+        }
+    }
+
+    /// Writes the tournament to storage if it has operations that have not been synced. Otherwise,
+    /// the stored copy is removed since the backend has everything that it holds.
+    async fn persist(&self, id: TournamentId) {
+        let Some(storage) = self.storage.as_ref() else {
+            return;
+        };
+        let key = id.to_string();
+        match self.cache.get(&id).filter(|tc| !tc.tourn.is_synced()) {
+            Some(tc) => {
+                let bytes = postcard::to_allocvec(&tc.tourn).unwrap();
+                storage.save(key, bytes).await;
+            }
+            None => storage.remove(key).await,
+        }
+    }
+
+    /// Sends the backend every operation that has not been synced yet
+    async fn start_sync(&mut self, scheduler: &mut Scheduler<Self>, t_id: TournamentId) {
+        let Some(tourn) = self.cache.get_mut(&t_id) else {
+            return;
+        };
+        let id = Uuid::new_v4();
+        let sync: ClientOpLink = tourn.tourn.sync_request().into();
+        self.syncs.initialize_chain(id, t_id, sync.clone()).unwrap(); // TODO: Remove unwrap
+        let msg = ServerBoundMessage {
+            id,
+            body: sync.into(),
+        };
+        tourn.send(scheduler, msg).await;
+    }
+
     fn handle_query(&self, id: TournamentId, query: Query) {
         query(self.cache.get(&id).map(|tc| &tc.tourn));
     }
 
+    fn handle_query_many(&self, ids: &[TournamentId], query: QueryMany) {
+        query(
+            ids.iter()
+                .filter_map(|id| self.cache.get(id))
+                .map(|tc| &tc.tourn)
+                .collect(),
+        );
+    }
+
+    /// Re-evaluates the query subscriptions of every tournament that has changed. Subscriptions
+    /// without any watchers left are removed.
+    fn update_selections(&mut self) {
+        for id in self.notifier.changed.drain() {
+            let (Some(selections), Some(tc)) = (self.selections.get_mut(&id), self.cache.get(&id))
+            else {
+                continue;
+            };
+            selections.retain_mut(|selection| selection.update(&tc.tourn));
+            if selections.is_empty() {
+                let _ = self.selections.remove(&id);
+            }
+        }
+    }
+
     // Needs to take a &mut to the SelectAll WS listener so it can be updated if need be
     fn handle_sub(&mut self, id: TournamentId) -> SubCreation {
-        match self.cache.get(&id) {
-            Some(TournComm {
-                comm: Some((_, broad)),
-                ..
-            }) => SubCreation::Connected(broad.subscribe()),
-            _ => SubCreation::Connect(id),
+        match self.cache.get(&id).and_then(|tc| tc.comm.as_ref()) {
+            Some(comm) => SubCreation::Connected(comm.broad.subscribe()),
+            None => SubCreation::Connect(id),
+        }
+    }
+
+    /// Fetches the tournament over a newly opened websocket and starts listening to it. Returns
+    /// `None` if the backend turned the connection away.
+    async fn connect(
+        &mut self,
+        scheduler: &mut Scheduler<Self>,
+        id: TournamentId,
+        mut ws: Websocket,
+    ) -> Option<Watcher<()>> {
+        // If we already have a copy, we only need what we missed while disconnected
+        let since = self.cache.get(&id).and_then(|tc| tc.tourn.last_sync());
+        let fetched = wait_for_tourn(&mut ws, since).await?;
+        Some(self.handle_connection(scheduler, id, ws, fetched).await)
+    }
+
+    /// Sends a rollback request to the backend. The response is sent once the backend replies.
+    async fn handle_rollback(
+        &mut self,
+        scheduler: &mut Scheduler<Self>,
+        id: TournamentId,
+        rollback: Rollback,
+        send: OneshotSender<Option<Result<(), RollbackError>>>,
+    ) {
+        let Some(tc) = self
+            .cache
+            .get_mut(&id)
+            .filter(|tc| tc.comm.as_ref().is_some_and(|comm| comm.sink.is_some()))
+        else {
+            drop(send.send(None));
+            return;
+        };
+        let msg = ServerBoundMessage::new(rollback.into());
+        _ = self.rollbacks.insert(msg.id, (id, send));
+        tc.send(scheduler, msg).await;
+    }
+
+    /// Handles a subscription's websocket closing. The subscription is kept alive and the client
+    /// tries to reconnect after a short delay.
+    fn handle_dropped(&mut self, scheduler: &mut Scheduler<Self>, id: TournamentId) {
+        // The backend can not respond to rollbacks that were sent over the closed websocket
+        let (dropped, pending) = mem::take(&mut self.rollbacks)
+            .into_iter()
+            .partition(|(_, (t_id, _))| *t_id == id);
+        self.rollbacks = pending;
+        for (_, (_, send)) in dropped {
+            drop(send.send(None));
         }
+        let Some(comm) = self.cache.get_mut(&id).and_then(|tc| tc.comm.as_mut()) else {
+            return;
+        };
+        comm.sink = None;
+        _ = comm.status.send_replace(ConnectionState::Reconnecting(0));
+        let delay = self.reconnections.dropped(id);
+        scheduler.schedule(Instant::now() + delay, Reconnect(id));
     }
 
-    fn handle_connection(
+    /// Schedules another attempt to reconnect, backing off further after every failure
+    fn handle_failed_reconnect(&mut self, scheduler: &mut Scheduler<Self>, id: TournamentId) {
+        let Some(comm) = self.cache.get_mut(&id).and_then(|tc| tc.comm.as_mut()) else {
+            self.reconnections.reconnected(&id);
+            return;
+        };
+        let (failures, delay) = self.reconnections.failed(id);
+        _ = comm
+            .status
+            .send_replace(ConnectionState::Reconnecting(failures));
+        scheduler.schedule(Instant::now() + delay, Reconnect(id));
+    }
+
+    async fn handle_connection(
         &mut self,
         scheduler: &mut Scheduler<Self>,
+        id: TournamentId,
         ws: Websocket,
-        tourn: Box<TournamentManager>,
+        fetched: Fetched,
     ) -> Watcher<()> {
-        match self.cache.entry(tourn.id) {
-            Entry::Occupied(mut entry) => match &mut entry.get_mut().comm {
-                // Tournament is cached and communication is set up for it
-                Some((_, broad)) => broad.subscribe(),
-                // Tournament is cached but there is no communication for it
-                None => {
-                    let (sink, stream) = ws.split();
-                    let (broad, sub) = watch_channel(());
-                    entry.get_mut().comm = Some((sink, broad));
-                    scheduler.add_stream(stream);
-                    sub
+        let (tc, dropped) = match self.cache.entry(id) {
+            Entry::Occupied(entry) => {
+                let tc = entry.into_mut();
+                // The tournament is already connected, so the new websocket is not needed
+                if let Some(comm) = tc.comm.as_ref().filter(|comm| comm.sink.is_some()) {
+                    return comm.broad.subscribe();
                 }
-            },
-            // Tournament is not cached
+                let dropped = match fetched {
+                    Fetched::Full(tourn) => Some(tc.tourn.catch_up(*tourn)),
+                    Fetched::Delta(sync) => tc.tourn.apply_delta(sync),
+                };
+                (tc, dropped.unwrap_or_default())
+            }
+            // Tournament is not cached, so the backend sent the whole thing
             Entry::Vacant(entry) => {
-                let (sink, stream) = ws.split();
-                let (broad, sub) = watch_channel(());
-                let tc = TournComm {
-                    tourn: *tourn,
-                    comm: Some((sink, broad)),
+                let Fetched::Full(tourn) = fetched else {
+                    unreachable!("a delta is only requested for cached tournaments")
                 };
-                let _ = entry.insert(tc);
-                scheduler.add_stream(stream);
-                sub
+                let mut tourn = *tourn;
+                // The copy from the backend has nothing left to sync
+                tourn.mark_synced();
+                (entry.insert(TournComm { tourn, comm: None }), Vec::new())
             }
+        };
+        let (sink, stream) = ws.split();
+        let comm = tc.comm.get_or_insert_with(Comm::new);
+        comm.sink = Some(sink);
+        _ = comm.status.send_replace(ConnectionState::Connected);
+        let sub = comm.broad.subscribe();
+        tc.report_dropped(dropped);
+        let resync = !tc.tourn.is_synced();
+        self.reconnections.reconnected(&id);
+        // Once the websocket closes, the stream tells the management task that it was dropped
+        let dropped = once(ready(ManagementCommand::Dropped(Dropped(id))));
+        scheduler.add_stream(stream.map(ManagementCommand::Remote).chain(dropped));
+        // Presence is only broadcast when it changes, so the client asks for the current state
+        tc.send(scheduler, ServerBoundMessage::new(ServerBound::Presence))
+            .await;
+        tc.send(scheduler, ServerBoundMessage::new(ServerBound::ClockSync))
+            .await;
+        self.notifier.notify(id);
+        self.persist(id).await;
+        if resync {
+            self.start_sync(scheduler, id).await;
         }
+        sub
     }
 
     async fn handle_ws_msg(&mut self, scheduler: &mut Scheduler<Self>, msg: WebsocketMessage) {
         let WebsocketMessage::Bytes(data) = msg else {
             panic!("Server did not send bytes of Websocket")
         };
-        let WebSocketMessage { body, id } =
-            postcard::from_bytes::<ClientBoundMessage>(&data).unwrap();
+        let WebSocketMessage { body, id } = ClientBoundMessage::from_bytes(&data).unwrap();
         match body {
-            ClientBound::FetchResp(_) => { /* Do nothing, handled elsewhere */ }
+            // Outside of connecting, the tournament is only fetched after it diverged
+            ClientBound::FetchResp(tourn, digest) => {
+                if tourn.check_digest(digest).is_ok() {
+                    self.replace_tourn(scheduler, tourn.id, *tourn).await
+                }
+            }
+            ClientBound::FetchSinceResp(_) => { /* Do nothing, handled elsewhere */ }
             ClientBound::SyncChain(link) => {
                 self.handle_server_op_link(scheduler, &id, link).await;
             }
@@ -275,11 +918,56 @@ impl ManagerState {
             ClientBound::Unauthorized => {
                 // TODO: Properly handle this. We should try to reauth or something...
             }
+            // Rejections are only sent before the tournament is fetched
+            ClientBound::Rejected(_) => {}
+            ClientBound::PresenceUpdate(t_id, presence) => {
+                if let Some(comm) = self.cache.get(&t_id).and_then(|tc| tc.comm.as_ref()) {
+                    _ = comm.presence.send_replace(presence);
+                }
+            }
+            ClientBound::ClockSync(t_id, sync) => {
+                if let Some(comm) = self.cache.get(&t_id).and_then(|tc| tc.comm.as_ref()) {
+                    _ = comm.clock.send_replace(ServerClock::new(sync, Utc::now()));
+                }
+            }
+            ClientBound::TardyPlayers(t_id, tardy) => {
+                if let Some(comm) = self.cache.get(&t_id).and_then(|tc| tc.comm.as_ref()) {
+                    _ = comm.tardy.send_replace(tardy);
+                }
+            }
+            ClientBound::RollbackResp(resp) => {
+                if let Some((_, send)) = self.rollbacks.remove(&id) {
+                    drop(send.send(Some(resp)));
+                }
+            }
+            ClientBound::RolledBack(t_id, server) => {
+                self.replace_tourn(scheduler, t_id, *server).await
+            }
         }
     }
 
-    fn handle_ws_err(&mut self, err: WebsocketError) {
-        panic!("Got error from Websocket: {err:?}")
+    /// Replaces the local copy of a tournament with the backend's copy, i.e. after the backend
+    /// rolled the tournament back, sent a copy without private operations, or the local copy
+    /// diverged. Any syncs that were in progress are dropped, so the unsynced operations are sent
+    /// again.
+    async fn replace_tourn(
+        &mut self,
+        scheduler: &mut Scheduler<Self>,
+        t_id: TournamentId,
+        server: TournamentManager,
+    ) {
+        let Some(tc) = self.cache.get_mut(&t_id) else {
+            return;
+        };
+        let dropped = tc.tourn.catch_up(server);
+        tc.report_dropped(dropped);
+        let resync = !tc.tourn.is_synced();
+        self.syncs.finalize_tourn(&t_id);
+        self.notifier.notify(t_id);
+        self.persist(t_id).await;
+        if resync {
+            self.start_sync(scheduler, t_id).await;
+        }
     }
 
     async fn handle_server_op_link(
@@ -309,9 +997,26 @@ impl ManagerState {
                 tourn.send(scheduler, msg).await;
             }
             ServerOpLink::Completed(comp) => {
+                let digest = comp.digest();
                 tourn.tourn.handle_completion(comp).unwrap();
+                let diverged = tourn.tourn.check_digest(digest).err();
                 self.syncs.finalize_chain(msg_id);
-                (self.on_update)(t_id);
+                self.notifier.notify(t_id);
+                self.persist(t_id).await;
+                if let Some(digests) = diverged {
+                    self.handle_divergence(scheduler, t_id, digests).await;
+                }
+            }
+            ServerOpLink::CatchUp(server) => {
+                let dropped = tourn.tourn.catch_up(*server);
+                tourn.report_dropped(dropped);
+                let resync = !tourn.tourn.is_synced();
+                self.syncs.finalize_chain(msg_id);
+                self.notifier.notify(t_id);
+                self.persist(t_id).await;
+                if resync {
+                    self.start_sync(scheduler, t_id).await;
+                }
             }
             ServerOpLink::Error(_) | ServerOpLink::TerminatedSeen { .. } => {
                 self.syncs.finalize_chain(msg_id);
@@ -319,6 +1024,32 @@ impl ManagerState {
         }
     }
 
+    /// Surfaces that the local copy of a tournament no longer matches the backend's copy and
+    /// fetches the backend's copy, which replaces the local copy once it arrives
+    async fn handle_divergence(
+        &mut self,
+        scheduler: &mut Scheduler<Self>,
+        t_id: TournamentId,
+        digests: Disagreement<LogDigest>,
+    ) {
+        let Some(tc) = self.cache.get_mut(&t_id) else {
+            return;
+        };
+        log(&format!(
+            "Tournament {t_id} diverged from the backend: {digests:?}"
+        ));
+        if let Some(comm) = tc.comm.as_ref() {
+            let divergence = Divergence {
+                tourn: t_id,
+                digests,
+                detected: Utc::now(),
+            };
+            _ = comm.divergence.send_replace(Some(divergence));
+        }
+        tc.send(scheduler, ServerBoundMessage::new(ServerBound::Fetch))
+            .await;
+    }
+
     async fn handle_forwarded_sync(
         &mut self,
         scheduler: &mut Scheduler<Self>,
@@ -329,15 +1060,25 @@ impl ManagerState {
         let Some(comm) = self.cache.get_mut(t_id) else {
             return;
         };
-        let resp = if self.forwarded.contains_resp(&msg_id) {
-            self.forwarded.get_resp(&msg_id).unwrap()
-        } else {
-            let resp = comm.tourn.handle_forwarded_sync(sync);
-            if matches!(resp, SyncForwardResp::Success) {
-                (self.on_update)(*t_id);
-            }
-            self.forwarded.add_resp(msg_id, resp.clone());
-            resp
+        let fresh = comm
+            .comm
+            .as_mut()
+            .map_or(true, |c| c.seen.insert(msg_id, &sync));
+        let mut applied = false;
+        let resp = match self.forwarded.get_resp(&msg_id) {
+            Some(resp) => resp,
+            // The sync was already handled, but its response has been cleaned up. The backend
+            // only needs to hear that the sync arrived, so it must not be applied again.
+            None if !fresh => SyncForwardResp::Success,
+            None => {
+                let resp = comm.tourn.handle_forwarded_sync(sync);
+                applied = matches!(resp, SyncForwardResp::Success);
+                if applied {
+                    self.notifier.notify(*t_id);
+                }
+                self.forwarded.add_resp(msg_id, resp.clone());
+                resp
+            }
         };
         self.forwarded.clean();
         let msg = ServerBoundMessage {
@@ -345,29 +1086,49 @@ impl ManagerState {
             body: resp.into(),
         };
         comm.send(scheduler, msg).await;
+        if applied {
+            self.persist(*t_id).await;
+        }
     }
 }
 
-async fn wait_for_tourn(stream: &mut Websocket) -> Box<TournamentManager> {
-    let msg = postcard::to_allocvec(&ServerBoundMessage::new(ServerBound::Fetch)).unwrap();
-    stream.send(WebsocketMessage::Bytes(msg)).await.unwrap();
+/// Fetches the tournament from the server. If the last synced operation is given, only the
+/// operations after it are requested. Returns `None` if the server rejected the connection or
+/// every copy that was fetched failed to match its digest.
+async fn wait_for_tourn(stream: &mut Websocket, since: Option<OpId>) -> Option<Fetched> {
+    let body = since.map_or(ServerBound::Fetch, ServerBound::FetchSince);
+    let msg = ServerBoundMessage::new(body).to_bytes(WS_COMPRESSION);
+    stream.send(WebsocketMessage::Bytes(msg)).await.ok()?;
+    let mut attempts = 1;
     loop {
-        let Some(Ok(WebsocketMessage::Bytes(msg))) = stream.next().await else {
+        let Ok(WebsocketMessage::Bytes(msg)) = stream.next().await? else {
             continue;
         };
-        let ClientBoundMessage { body, .. } = postcard::from_bytes(&msg).unwrap();
-        let ClientBound::FetchResp(tourn) = body else {
-            panic!("Server did not return a tournament")
-        };
-        return tourn;
+        let ClientBoundMessage { body, .. } = ClientBoundMessage::from_bytes(&msg)?;
+        match body {
+            ClientBound::FetchResp(tourn, digest) => match tourn.check_digest(digest) {
+                Ok(()) => return Some(Fetched::Full(tourn)),
+                // The copy does not match what the backend sent, so it is fetched again
+                Err(_) if attempts < FETCH_ATTEMPTS => {
+                    attempts += 1;
+                    let msg = ServerBoundMessage::new(ServerBound::Fetch).to_bytes(WS_COMPRESSION);
+                    stream.send(WebsocketMessage::Bytes(msg)).await.ok()?;
+                }
+                Err(_) => return None,
+            },
+            ClientBound::FetchSinceResp(sync) => return Some(Fetched::Delta(sync)),
+            ClientBound::Rejected(_) => return None,
+            // Other messages, like presence updates, can arrive before the tournament
+            _ => continue,
+        }
     }
 }
 
 impl TournComm {
     async fn send(&mut self, scheduler: &mut Scheduler<ManagerState>, msg: ServerBoundMessage) {
-        if let Some(comm) = self.comm.as_mut() {
-            let bytes = WebsocketMessage::Bytes(postcard::to_allocvec(&msg).unwrap());
-            let _ = comm.0.send(bytes).await;
+        if let Some(sink) = self.comm.as_mut().and_then(|comm| comm.sink.as_mut()) {
+            let bytes = WebsocketMessage::Bytes(msg.to_bytes(WS_COMPRESSION));
+            let _ = sink.send(bytes).await;
             let retry = MessageRetry {
                 msg,
                 id: self.tourn.id,
@@ -375,6 +1136,20 @@ impl TournComm {
             scheduler.schedule(Instant::now() + RETRY_LIMIT, retry);
         }
     }
+
+    /// Surfaces the unsynced operations that were dropped because they could no longer be applied
+    /// after catching up to the backend's copy of the tournament
+    fn report_dropped(&self, dropped: Vec<FullOp>) {
+        let Some(comm) = self.comm.as_ref().filter(|_| !dropped.is_empty()) else {
+            return;
+        };
+        log(&format!(
+            "Dropped {} operations from tournament {}",
+            dropped.len(),
+            self.tourn.id
+        ));
+        _ = comm.dropped.send_replace(dropped);
+    }
 }
 
 impl<F, T> From<((TournamentId, F), OneshotSender<Option<T>>)> for ManagementCommand
@@ -390,8 +1165,181 @@ where
     }
 }
 
+impl<F, T>
+    From<(
+        (Vec<TournamentId>, F),
+        OneshotSender<HashMap<TournamentId, T>>,
+    )> for ManagementCommand
+where
+    F: 'static + Send + FnMut(&TournamentManager) -> T,
+    T: 'static + Send,
+{
+    fn from(
+        ((ids, mut query), send): (
+            (Vec<TournamentId>, F),
+            OneshotSender<HashMap<TournamentId, T>>,
+        ),
+    ) -> Self {
+        let query = Box::new(move |tourns: Vec<&TournamentManager>| {
+            let digest = tourns.into_iter().map(|t| (t.id, query(t))).collect();
+            let _ = send.send(digest);
+        });
+        Self::QueryMany(ids, query)
+    }
+}
+
+impl From<((), OneshotSender<Vec<TournamentSummary>>)> for ManagementCommand {
+    fn from(((), send): ((), OneshotSender<Vec<TournamentSummary>>)) -> Self {
+        Self::Directory(send)
+    }
+}
+
+impl From<((), OneshotSender<Vec<TournamentOverview>>)> for ManagementCommand {
+    fn from(((), send): ((), OneshotSender<Vec<TournamentOverview>>)) -> Self {
+        Self::Overview(send)
+    }
+}
+
+/// A message that tells the management task that a subscription's websocket has closed
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Dropped(TournamentId);
+
+/// A message that tells the management task to try to reconnect a subscription
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Reconnect(TournamentId);
+
+/// A message that tells the management task to refresh its tournament directory
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum DirectoryRefresh {
+    /// The periodic refresh, which schedules the next one once it is done
+    Scheduled,
+    /// A one-off refresh that was requested by the user
+    Manual,
+}
+
 #[derive(Debug)]
 pub(crate) struct MessageRetry {
     id: TournamentId,
     msg: ServerBoundMessage,
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use squire_lib::tournament::TournamentId;
+    use squire_tests::{get_seed, spoof_account};
+
+    use super::{ManagerState, TournsClient, UpdateType};
+    use crate::{
+        actor::ActorClient, api::TournamentSummary, client::network::NetworkState,
+        model::operations::TournOp, sync::TournamentManager,
+    };
+
+    fn spoof_tourn() -> TournamentManager {
+        TournamentManager::new(spoof_account(), get_seed())
+    }
+
+    fn spoof_client() -> TournsClient {
+        let network = ActorClient::builder(NetworkState::new()).launch();
+        TournsClient::new(network, |_: TournamentId| {}, None)
+    }
+
+    fn register() -> UpdateType {
+        UpdateType::Single(Box::new(TournOp::RegisterPlayer(spoof_account(), None)))
+    }
+
+    #[tokio::test]
+    async fn directory_merges_local_and_remote_tournaments() {
+        let updates = Arc::new(Mutex::new(Vec::new()));
+        let on_update = {
+            let updates = updates.clone();
+            move |id: TournamentId| updates.lock().unwrap().push(id)
+        };
+        let network = ActorClient::builder(NetworkState::new()).launch();
+        let mut state = ManagerState::new(network, on_update, None);
+        let take = || std::mem::take(&mut *updates.lock().unwrap());
+
+        let local = spoof_tourn();
+        let local_id = state.handle_import(local.clone());
+        assert_eq!(take(), vec![local_id]);
+
+        // The backend's copy of a local tournament is hidden by the local copy
+        let remote = TournamentSummary::from(&spoof_tourn());
+        let mut stale = TournamentSummary::from(&local);
+        stale.name = "Stale".to_owned();
+        state.handle_directory(vec![remote.clone(), stale.clone()]);
+        assert_eq!(take(), vec![remote.id]);
+        let summaries = state.directory.summaries(&state.cache);
+        assert_eq!(summaries.len(), 2);
+        assert!(summaries.contains(&remote));
+        assert!(summaries.contains(&TournamentSummary::from(&local)));
+
+        // Refreshes that don't change anything don't notify the UI
+        state.handle_directory(vec![stale.clone(), remote.clone()]);
+        assert!(take().is_empty());
+
+        let mut renamed = remote.clone();
+        renamed.name = "Renamed".to_owned();
+        state.handle_directory(vec![renamed.clone(), stale]);
+        assert_eq!(take(), vec![remote.id]);
+        assert!(state.directory.summaries(&state.cache).contains(&renamed));
+
+        // Tournaments that the backend stops listing are removed
+        state.handle_directory(Vec::new());
+        assert_eq!(take(), vec![remote.id]);
+        assert_eq!(
+            state.directory.summaries(&state.cache),
+            vec![TournamentSummary::from(&local)]
+        );
+    }
+
+    #[tokio::test]
+    async fn query_subscriptions_only_notify_on_changes() {
+        let client = spoof_client();
+        let id = client.import(spoof_tourn()).await;
+        let missing = client.subscribe_query(spoof_tourn().id, |t| t.name.clone());
+        assert!(missing.await.is_none());
+
+        let mut players = client
+            .subscribe_query(id, |t| t.get_player_count())
+            .await
+            .unwrap();
+        let mut name = client
+            .subscribe_query(id, |t| t.name.clone())
+            .await
+            .unwrap();
+        assert_eq!(*players.borrow_and_update(), 0);
+        assert_eq!(*name.borrow_and_update(), get_seed().name);
+
+        assert!(client.update(id, register()).await.unwrap().is_ok());
+        // Queries are handled in order, so the subscriptions have been updated once this returns
+        client.query(id, |_| ()).await.unwrap();
+        assert!(players.has_changed().unwrap());
+        assert_eq!(*players.borrow_and_update(), 1);
+        assert!(!name.has_changed().unwrap());
+
+        // Dropped subscriptions are cleaned up, and removing the tournament closes the rest
+        drop(name);
+        assert!(client.update(id, register()).await.unwrap().is_ok());
+        client.query(id, |_| ()).await.unwrap();
+        assert_eq!(*players.borrow_and_update(), 2);
+        assert!(client.update(id, UpdateType::Removal).await.is_some());
+        assert!(players.changed().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn batch_queries_skip_unknown_tournaments() {
+        let client = spoof_client();
+        let first = client.import(spoof_tourn()).await;
+        let second = client.import(spoof_tourn()).await;
+        assert!(client.update(second, register()).await.unwrap().is_ok());
+
+        let ids = vec![first, second, spoof_tourn().id];
+        let counts = client.query_many(ids, |t| t.get_player_count()).await;
+        assert_eq!(counts.len(), 2);
+        assert_eq!(counts[&first], 0);
+        assert_eq!(counts[&second], 1);
+        assert!(client.query_many(Vec::new(), |t| t.id).await.is_empty());
+    }
+}
@@ -82,3 +82,23 @@ impl NetworkResponse {
         self.0.take()
     }
 }
+
+/* ------ Compression ------ */
+/// The most bytes that a compressed payload is allowed to decompress into. This guards against
+/// payloads that decompress into far more data than any tournament could hold.
+#[cfg(any(feature = "client", feature = "server"))]
+pub const MAX_DECOMPRESSED_LEN: usize = 64 * 1024 * 1024;
+
+/// Compresses the bytes with DEFLATE. `miniz_oxide` is written in pure Rust, so the same
+/// implementation is used for both native and WASM targets.
+#[cfg(any(feature = "client", feature = "server"))]
+pub fn deflate(bytes: &[u8]) -> Vec<u8> {
+    miniz_oxide::deflate::compress_to_vec(bytes, 6)
+}
+
+/// Decompresses bytes that were compressed with `deflate`. Returns `None` if the bytes are not
+/// valid or decompress into more than `MAX_DECOMPRESSED_LEN` bytes.
+#[cfg(any(feature = "client", feature = "server"))]
+pub fn inflate(bytes: &[u8]) -> Option<Vec<u8>> {
+    miniz_oxide::inflate::decompress_to_vec_with_limit(bytes, MAX_DECOMPRESSED_LEN).ok()
+}
@@ -100,6 +100,7 @@ pub use client::*;
 #[cfg(feature = "client")]
 mod client {
     use std::{
+        path::PathBuf,
         pin::Pin,
         task::{Context, Poll},
     };
@@ -275,7 +276,7 @@ mod client {
             tokio_tungstenite::connect_async(url)
                 .await
                 .map(|(ws, _)| Websocket(ws))
-                .map_err(|err| panic!("{err}"))
+                .map_err(|_| ())
         }
     }
 
@@ -348,4 +349,64 @@ mod client {
             }
         }
     }
+
+    /* ------ Storage ------ */
+
+    /// Stores data that needs to outlive the client, such as operations that have not been synced
+    /// with the backend. Natively, every entry is a file in a directory.
+    #[derive(Debug, Clone)]
+    pub struct Storage(PathBuf);
+
+    impl Storage {
+        /// Creates a storage that keeps its entries in the directory at the given path. The
+        /// directory is created when the first entry is saved.
+        pub fn new(path: impl Into<PathBuf>) -> Self {
+            Self(path.into())
+        }
+
+        /// Writes an entry, replacing any entry with the same key
+        pub fn save(&self, key: String, bytes: Vec<u8>) -> impl SendableFuture<Output = ()> {
+            let dir = self.0.clone();
+            async move {
+                if tokio::fs::create_dir_all(&dir).await.is_err() {
+                    return;
+                }
+                // The entry is written to a temporary file first so that a crash mid-write does
+                // not corrupt the last saved copy
+                let tmp = dir.join(format!("{key}.tmp"));
+                if tokio::fs::write(&tmp, bytes).await.is_ok() {
+                    let _ = tokio::fs::rename(tmp, dir.join(key)).await;
+                }
+            }
+        }
+
+        /// Removes an entry, if it exists
+        pub fn remove(&self, key: String) -> impl SendableFuture<Output = ()> {
+            let path = self.0.join(key);
+            async move {
+                let _ = tokio::fs::remove_file(path).await;
+            }
+        }
+
+        /// Reads every saved entry
+        pub fn load_all(&self) -> impl SendableFuture<Output = Vec<Vec<u8>>> {
+            let dir = self.0.clone();
+            async move {
+                let mut digest = Vec::new();
+                let Ok(mut entries) = tokio::fs::read_dir(dir).await else {
+                    return digest;
+                };
+                while let Ok(Some(entry)) = entries.next_entry().await {
+                    // Skip temporary files that were left behind by an interrupted save
+                    if entry.path().extension().is_some() {
+                        continue;
+                    }
+                    if let Ok(bytes) = tokio::fs::read(entry.path()).await {
+                        digest.push(bytes);
+                    }
+                }
+                digest
+            }
+        }
+    }
 }
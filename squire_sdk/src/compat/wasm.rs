@@ -58,8 +58,11 @@ mod client {
     use gloo_net::websocket::{
         futures::WebSocket as GlooSocket, Message as GlooMessage, WebSocketError as GlooError,
     };
+    use js_sys::Uint8Array;
+    use rexie::{ObjectStore, Rexie, TransactionMode};
     use send_wrapper::SendWrapper;
     use serde::{de::DeserializeOwned, Serialize};
+    use wasm_bindgen::JsValue;
 
     use crate::{
         api::{SessionToken, TokenParseError},
@@ -225,7 +228,7 @@ mod client {
             GlooSocket::open(url)
                 .map(|sock| Websocket(SendWrapper::new(sock)))
                 // TODO: Make this a real error...
-                .map_err(|err| panic!("{err}"))
+                .map_err(|_| ())
         }
     }
 
@@ -294,4 +297,99 @@ mod client {
             }
         }
     }
+
+    /* ------ Storage ------ */
+
+    /// The IndexedDB object store that holds every entry
+    const STORE: &str = "entries";
+
+    /// Stores data that needs to outlive the client, such as operations that have not been synced
+    /// with the backend. In the browser, the entries are kept in an IndexedDB database.
+    #[derive(Debug, Clone)]
+    pub struct Storage(String);
+
+    impl Storage {
+        /// Creates a storage that keeps its entries in the IndexedDB database with the given name
+        pub fn new(name: impl Into<String>) -> Self {
+            Self(name.into())
+        }
+
+        async fn open(name: &str) -> Option<Rexie> {
+            Rexie::builder(name)
+                .version(1)
+                .add_object_store(ObjectStore::new(STORE))
+                .build()
+                .await
+                .ok()
+        }
+
+        /// Writes an entry, replacing any entry with the same key
+        pub fn save(&self, key: String, bytes: Vec<u8>) -> impl SendableFuture<Output = ()> {
+            let name = self.0.clone();
+            SendWrapper::new(async move {
+                let Some(db) = Self::open(&name).await else {
+                    return;
+                };
+                let Ok(tx) = db.transaction(&[STORE], TransactionMode::ReadWrite) else {
+                    return;
+                };
+                let Ok(store) = tx.store(STORE) else {
+                    return;
+                };
+                let value: JsValue = Uint8Array::from(bytes.as_slice()).into();
+                if store
+                    .put(&value, Some(&JsValue::from_str(&key)))
+                    .await
+                    .is_ok()
+                {
+                    let _ = tx.done().await;
+                }
+            })
+        }
+
+        /// Removes an entry, if it exists
+        pub fn remove(&self, key: String) -> impl SendableFuture<Output = ()> {
+            let name = self.0.clone();
+            SendWrapper::new(async move {
+                let Some(db) = Self::open(&name).await else {
+                    return;
+                };
+                let Ok(tx) = db.transaction(&[STORE], TransactionMode::ReadWrite) else {
+                    return;
+                };
+                let Ok(store) = tx.store(STORE) else {
+                    return;
+                };
+                if store.delete(&JsValue::from_str(&key)).await.is_ok() {
+                    let _ = tx.done().await;
+                }
+            })
+        }
+
+        /// Reads every saved entry
+        pub fn load_all(&self) -> impl SendableFuture<Output = Vec<Vec<u8>>> {
+            let name = self.0.clone();
+            SendWrapper::new(async move {
+                let Some(db) = Self::open(&name).await else {
+                    return Vec::new();
+                };
+                let Ok(tx) = db.transaction(&[STORE], TransactionMode::ReadOnly) else {
+                    return Vec::new();
+                };
+                let Ok(store) = tx.store(STORE) else {
+                    return Vec::new();
+                };
+                store
+                    .get_all(None, None, None, None)
+                    .await
+                    .map(|entries| {
+                        entries
+                            .into_iter()
+                            .map(|(_, value)| Uint8Array::new(&value).to_vec())
+                            .collect()
+                    })
+                    .unwrap_or_default()
+            })
+        }
+    }
 }
@@ -0,0 +1,663 @@
+//! Turns the decklists that players have on hand into the `Deck`s used by `PlayerOp::AddDeck`.
+//!
+//! Decklists can be given as text, in either the MTGO (`.txt`) or Arena export formats, or as a
+//! Moxfield or Archidekt URL. Card names are then resolved to their canonical spellings, either
+//! against a list of known names (i.e. the names in the MTGJSON atomics) or by Scryfall's fuzzy
+//! search, so typos and different capitalizations are fixed before the deck is submitted.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::model::players::Deck;
+
+/// The endpoint of Scryfall's fuzzy card search
+const SCRYFALL_NAMED: &str = "https://api.scryfall.com/cards/named";
+
+/// The errors that can occur while importing a deck
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub enum DeckImportError {
+    /// The decklist has no cards
+    Empty,
+    /// The line (counting from one) could not be read as a card
+    BadLine(usize, String),
+    /// The URL is not from a supported deck site
+    UnsupportedUrl,
+    /// The deck could not be fetched from its site
+    FetchFailed,
+    /// The deck site's response could not be read
+    Malformed,
+    /// These cards did not match any known card
+    UnknownCards(Vec<String>),
+    /// The decklist could not be converted into a deck
+    InvalidDeck(String),
+}
+
+/// A number of copies of a card
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct DeckEntry {
+    /// The number of copies
+    pub count: u32,
+    /// The card's name
+    pub name: String,
+}
+
+/// A decklist that has been read but not yet turned into a `Deck`
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq)]
+pub struct DeckList {
+    /// The deck's name, if the list had one
+    pub name: Option<String>,
+    /// The cards in the main deck
+    pub main: Vec<DeckEntry>,
+    /// The cards in the sideboard, including companions
+    pub side: Vec<DeckEntry>,
+    /// The deck's commanders
+    pub commander: Vec<DeckEntry>,
+}
+
+/// The part of a decklist that a card belongs to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Board {
+    Main,
+    Side,
+    Commander,
+    /// Cards that are not in the deck, like Moxfield's maybeboard
+    Ignored,
+}
+
+impl DeckList {
+    /// Reads a decklist in the MTGO or Arena export formats. In the MTGO format, the sideboard
+    /// follows the main deck after a blank line or a "Sideboard" header, or its lines are prefixed
+    /// with "SB:". In the Arena format, the sections are headed by "Deck", "Sideboard",
+    /// "Commander", and "Companion", and each card can be followed by its printing, which is
+    /// ignored.
+    pub fn parse(text: &str) -> Result<Self, DeckImportError> {
+        let mut list = Self::default();
+        let mut board = Board::Main;
+        let mut has_headers = false;
+        let mut in_about = false;
+        for (i, line) in text.trim_start_matches('\u{feff}').lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() {
+                // MTGO lists separate the sideboard with a blank line
+                if !has_headers && board == Board::Main && !list.main.is_empty() {
+                    board = Board::Side;
+                }
+                continue;
+            }
+            if line.starts_with("//") || line.starts_with('#') {
+                continue;
+            }
+            if let Some(section) = parse_header(line) {
+                has_headers = true;
+                in_about = section.is_none();
+                if let Some(section) = section {
+                    board = section;
+                }
+                continue;
+            }
+            if in_about {
+                if let Some(name) = line.strip_prefix("Name ") {
+                    list.name = Some(name.trim().to_owned());
+                }
+                continue;
+            }
+            let (line_board, line) = match line.strip_prefix("SB:") {
+                Some(rest) => (Board::Side, rest.trim()),
+                None => (board, line),
+            };
+            let entry = parse_entry(line)
+                .ok_or_else(|| DeckImportError::BadLine(i + 1, line.to_owned()))?;
+            list.add(line_board, entry);
+        }
+        list.check()
+    }
+
+    /// Reads the response of Moxfield's deck API
+    fn from_moxfield(deck: &Value) -> Result<Self, DeckImportError> {
+        let mut list = Self {
+            name: deck["name"].as_str().map(str::to_owned),
+            ..Self::default()
+        };
+        let boards = deck["boards"]
+            .as_object()
+            .ok_or(DeckImportError::Malformed)?;
+        for (board, contents) in boards {
+            let board = match board.as_str() {
+                "mainboard" => Board::Main,
+                "sideboard" | "companions" => Board::Side,
+                "commanders" => Board::Commander,
+                _ => Board::Ignored,
+            };
+            let Some(cards) = contents["cards"].as_object() else {
+                continue;
+            };
+            for card in cards.values() {
+                let entry = read_entry(&card["quantity"], &card["card"]["name"])?;
+                list.add(board, entry);
+            }
+        }
+        list.check()
+    }
+
+    /// Reads the response of Archidekt's deck API
+    fn from_archidekt(deck: &Value) -> Result<Self, DeckImportError> {
+        let mut list = Self {
+            name: deck["name"].as_str().map(str::to_owned),
+            ..Self::default()
+        };
+        let cards = deck["cards"].as_array().ok_or(DeckImportError::Malformed)?;
+        for card in cards {
+            let categories = card["categories"].as_array();
+            let in_category = |name: &str| {
+                categories
+                    .map(|cats| cats.iter().any(|cat| cat.as_str() == Some(name)))
+                    .unwrap_or_default()
+            };
+            let board = if in_category("Maybeboard") {
+                Board::Ignored
+            } else if in_category("Commander") {
+                Board::Commander
+            } else if in_category("Sideboard") {
+                Board::Side
+            } else {
+                Board::Main
+            };
+            let entry = read_entry(&card["quantity"], &card["card"]["oracleCard"]["name"])?;
+            list.add(board, entry);
+        }
+        list.check()
+    }
+
+    fn add(&mut self, board: Board, entry: DeckEntry) {
+        let cards = match board {
+            Board::Main => &mut self.main,
+            Board::Side => &mut self.side,
+            Board::Commander => &mut self.commander,
+            Board::Ignored => return,
+        };
+        match cards.iter_mut().find(|e| e.name == entry.name) {
+            Some(existing) => existing.count += entry.count,
+            None => cards.push(entry),
+        }
+    }
+
+    fn check(self) -> Result<Self, DeckImportError> {
+        if self.main.is_empty() && self.commander.is_empty() {
+            Err(DeckImportError::Empty)
+        } else {
+            Ok(self)
+        }
+    }
+
+    /// Returns the number of cards in the main deck, including its commanders
+    pub fn main_count(&self) -> u32 {
+        self.main
+            .iter()
+            .chain(&self.commander)
+            .map(|e| e.count)
+            .sum()
+    }
+
+    /// Returns the number of cards in the sideboard
+    pub fn side_count(&self) -> u32 {
+        self.side.iter().map(|e| e.count).sum()
+    }
+
+    fn into_boards(self) -> (Option<String>, [Vec<DeckEntry>; 3]) {
+        (self.name, [self.main, self.side, self.commander])
+    }
+
+    /// Renames every card using the given function. Cards that it can not name are returned as
+    /// an error, and the list is left unchanged.
+    fn rename<F>(self, mut f: F) -> Result<Self, DeckImportError>
+    where
+        F: FnMut(&str) -> Option<String>,
+    {
+        let mut unknown = Vec::new();
+        let (name, boards) = self.into_boards();
+        let mut list = Self {
+            name,
+            ..Self::default()
+        };
+        for (board, cards) in [Board::Main, Board::Side, Board::Commander]
+            .into_iter()
+            .zip(boards)
+        {
+            for DeckEntry { count, name } in cards {
+                match f(&name) {
+                    Some(name) => list.add(board, DeckEntry { count, name }),
+                    None => unknown.push(name),
+                }
+            }
+        }
+        if unknown.is_empty() {
+            Ok(list)
+        } else {
+            Err(DeckImportError::UnknownCards(unknown))
+        }
+    }
+
+    /// Replaces each card's name with the closest known card name
+    pub fn resolve(self, matcher: &CardMatcher) -> Result<Self, DeckImportError> {
+        self.rename(|name| matcher.find(name).map(str::to_owned))
+    }
+
+    /// Replaces each card's name using Scryfall's fuzzy search. Each card is looked up one at a
+    /// time, so this is best suited to a single deck rather than a whole event's worth.
+    pub async fn resolve_with_scryfall(self) -> Result<Self, DeckImportError> {
+        let client = reqwest::Client::new();
+        let mut names = HashMap::new();
+        for entry in self.main.iter().chain(&self.side).chain(&self.commander) {
+            if !names.contains_key(&entry.name) {
+                let found = scryfall_name(&client, &entry.name).await?;
+                _ = names.insert(entry.name.clone(), found);
+            }
+        }
+        self.rename(|name| names.get(name).cloned().flatten())
+    }
+
+    /// Converts the list into a `Deck`, using the MTGJSON deck encoding
+    pub fn into_deck(self) -> Result<Deck, DeckImportError> {
+        let (name, [main, side, commander]) = self.into_boards();
+        let deck_type = if commander.is_empty() {
+            "Constructed"
+        } else {
+            "Commander"
+        };
+        let deck = json!({
+            "name": name.unwrap_or_default(),
+            "code": "",
+            "type": deck_type,
+            "releaseDate": null,
+            "mainBoard": main,
+            "sideBoard": side,
+            "commander": commander,
+        });
+        serde_json::from_value(deck).map_err(|err| DeckImportError::InvalidDeck(err.to_string()))
+    }
+}
+
+/// Matches the section headers of MTGO and Arena lists. The "About" section, which holds the
+/// deck's name, is given as `None`.
+fn parse_header(line: &str) -> Option<Option<Board>> {
+    let header = line.trim_end_matches(':').trim().to_lowercase();
+    let board = match header.as_str() {
+        "deck" | "main" | "mainboard" | "main deck" => Board::Main,
+        "sideboard" | "side" | "companion" => Board::Side,
+        "commander" | "commanders" => Board::Commander,
+        "maybeboard" | "considering" => Board::Ignored,
+        "about" => return Some(None),
+        _ => return None,
+    };
+    Some(Some(board))
+}
+
+/// Reads a card line, like "4 Lightning Bolt", "4x Lightning Bolt", or Arena's
+/// "4 Lightning Bolt (M10) 146"
+fn parse_entry(line: &str) -> Option<DeckEntry> {
+    let (count, name) = line.split_once(char::is_whitespace)?;
+    let count: u32 = count
+        .strip_suffix(['x', 'X'])
+        .unwrap_or(count)
+        .parse()
+        .ok()?;
+    let name = strip_printing(name.trim());
+    (count > 0 && !name.is_empty()).then(|| DeckEntry {
+        count,
+        name: name.to_owned(),
+    })
+}
+
+/// Removes the printing that Arena and Moxfield put after a card's name, i.e. the " (M10) 146" in
+/// "Lightning Bolt (M10) 146", and Moxfield's foil marker
+fn strip_printing(name: &str) -> &str {
+    let name = name.trim_end_matches("*F*").trim_end();
+    let Some(start) = name.rfind(" (") else {
+        return name;
+    };
+    let Some((set, number)) = name[start + 2..].split_once(')') else {
+        return name;
+    };
+    let is_set = !set.is_empty() && set.chars().all(|c| c.is_ascii_alphanumeric());
+    let is_number = !number.trim().contains(char::is_whitespace);
+    if is_set && is_number {
+        name[..start].trim_end()
+    } else {
+        name
+    }
+}
+
+fn read_entry(count: &Value, name: &Value) -> Result<DeckEntry, DeckImportError> {
+    match (count.as_u64(), name.as_str()) {
+        (Some(count), Some(name)) => Ok(DeckEntry {
+            count: count.try_into().map_err(|_| DeckImportError::Malformed)?,
+            name: name.to_owned(),
+        }),
+        _ => Err(DeckImportError::Malformed),
+    }
+}
+
+/// The deck sites that decks can be fetched from
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub enum DeckUrl {
+    /// A deck on Moxfield, given by its public id
+    Moxfield(String),
+    /// A deck on Archidekt, given by its id
+    Archidekt(u64),
+}
+
+impl DeckUrl {
+    /// Reads the URL of a deck's page, e.g. `https://www.moxfield.com/decks/<id>` or
+    /// `https://archidekt.com/decks/<id>/<name>`
+    pub fn parse(url: &str) -> Result<Self, DeckImportError> {
+        let url = reqwest::Url::parse(url.trim()).map_err(|_| DeckImportError::UnsupportedUrl)?;
+        let host = url.host_str().unwrap_or_default();
+        let host = host.strip_prefix("www.").unwrap_or(host);
+        let mut path = url
+            .path_segments()
+            .into_iter()
+            .flatten()
+            .filter(|s| !s.is_empty());
+        match (host, path.next(), path.next()) {
+            ("moxfield.com", Some("decks"), Some(id)) => Ok(Self::Moxfield(id.to_owned())),
+            ("archidekt.com", Some("decks"), Some(id)) => id
+                .parse()
+                .map(Self::Archidekt)
+                .map_err(|_| DeckImportError::UnsupportedUrl),
+            _ => Err(DeckImportError::UnsupportedUrl),
+        }
+    }
+
+    /// The URL of the site's API for the deck
+    pub fn api_url(&self) -> String {
+        match self {
+            Self::Moxfield(id) => format!("https://api2.moxfield.com/v3/decks/all/{id}"),
+            Self::Archidekt(id) => format!("https://archidekt.com/api/decks/{id}/"),
+        }
+    }
+
+    /// Fetches the deck from its site
+    pub async fn fetch(&self) -> Result<DeckList, DeckImportError> {
+        let deck: Value = reqwest::get(self.api_url())
+            .await
+            .and_then(|resp| resp.error_for_status())
+            .map_err(|_| DeckImportError::FetchFailed)?
+            .json()
+            .await
+            .map_err(|_| DeckImportError::Malformed)?;
+        match self {
+            Self::Moxfield(_) => DeckList::from_moxfield(&deck),
+            Self::Archidekt(_) => DeckList::from_archidekt(&deck),
+        }
+    }
+}
+
+/// Looks up a card's name using Scryfall's fuzzy search. Unknown and ambiguous names are `None`.
+async fn scryfall_name(
+    client: &reqwest::Client,
+    name: &str,
+) -> Result<Option<String>, DeckImportError> {
+    let resp = client
+        .get(SCRYFALL_NAMED)
+        .query(&[("fuzzy", name)])
+        .send()
+        .await
+        .map_err(|_| DeckImportError::FetchFailed)?;
+    if resp.status() == reqwest::StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+    let card: Value = resp
+        .error_for_status()
+        .map_err(|_| DeckImportError::FetchFailed)?
+        .json()
+        .await
+        .map_err(|_| DeckImportError::Malformed)?;
+    Ok(card["name"].as_str().map(str::to_owned))
+}
+
+/// Reads a decklist, given either as text or as a deck site URL, and resolves its card names
+/// using Scryfall
+pub async fn import_deck(input: &str) -> Result<Deck, DeckImportError> {
+    let input = input.trim();
+    let list = if input.starts_with("https://") || input.starts_with("http://") {
+        DeckUrl::parse(input)?.fetch().await?
+    } else {
+        DeckList::parse(input)?
+    };
+    list.resolve_with_scryfall().await?.into_deck()
+}
+
+/// Matches card names against a list of known names. Names match if they are the same after
+/// ignoring case, punctuation, and accents, or if only the front face of a double-faced card is
+/// given. Otherwise, the closest name within a few typos is used.
+#[derive(Debug, Clone, Default)]
+pub struct CardMatcher {
+    names: HashMap<String, String>,
+}
+
+impl CardMatcher {
+    /// Creates a matcher for the given card names, e.g. the keys of the MTGJSON atomics
+    pub fn new<I, S>(names: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let mut matcher = Self::default();
+        for name in names {
+            let name = name.into();
+            if let Some((front, _)) = name.split_once(" // ") {
+                _ = matcher
+                    .names
+                    .entry(normalize(front))
+                    .or_insert_with(|| name.clone());
+            }
+            _ = matcher.names.insert(normalize(&name), name);
+        }
+        matcher
+    }
+
+    /// Returns the known name that best matches the given name. If two names are equally close,
+    /// neither is used.
+    pub fn find(&self, name: &str) -> Option<&str> {
+        let key = normalize(name);
+        if let Some(found) = self.names.get(&key) {
+            return Some(found);
+        }
+        let max_dist = (key.chars().count() / 5).clamp(1, 3);
+        let mut best: Option<(usize, &str)> = None;
+        let mut tied = false;
+        for (known, name) in &self.names {
+            let dist = edit_distance(&key, known);
+            if dist > max_dist {
+                continue;
+            }
+            match best {
+                Some((best_dist, best_name)) if dist == best_dist => {
+                    tied |= best_name != name.as_str()
+                }
+                Some((best_dist, _)) if dist > best_dist => {}
+                _ => {
+                    best = Some((dist, name));
+                    tied = false;
+                }
+            }
+        }
+        best.filter(|_| !tied).map(|(_, name)| name)
+    }
+}
+
+/// Lowercases a name, strips its accents, and removes everything but letters, digits, and single
+/// spaces. Hyphens are treated as spaces.
+fn normalize(name: &str) -> String {
+    let mut norm = String::with_capacity(name.len());
+    for c in name.chars().flat_map(char::to_lowercase) {
+        let c = match c {
+            'á' | 'à' | 'â' | 'ä' => 'a',
+            'é' | 'è' | 'ê' | 'ë' => 'e',
+            'í' | 'ì' | 'î' | 'ï' => 'i',
+            'ó' | 'ò' | 'ô' | 'ö' => 'o',
+            'ú' | 'ù' | 'û' | 'ü' => 'u',
+            'æ' => {
+                norm.push('a');
+                'e'
+            }
+            c => c,
+        };
+        if c.is_alphanumeric() {
+            norm.push(c);
+        } else if (c.is_whitespace() || c == '-') && !norm.is_empty() && !norm.ends_with(' ') {
+            norm.push(' ');
+        }
+    }
+    norm.trim_end().to_owned()
+}
+
+/// The Levenshtein distance between two strings
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    let mut row = vec![0; b.len() + 1];
+    for (i, ca) in a.chars().enumerate() {
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let sub = prev[j] + usize::from(ca != *cb);
+            row[j + 1] = sub.min(prev[j + 1] + 1).min(row[j] + 1);
+        }
+        std::mem::swap(&mut prev, &mut row);
+    }
+    prev[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{CardMatcher, DeckEntry, DeckImportError, DeckList, DeckUrl};
+
+    fn entry(count: u32, name: &str) -> DeckEntry {
+        DeckEntry {
+            count,
+            name: name.to_owned(),
+        }
+    }
+
+    #[test]
+    fn mtgo_lists() {
+        let list =
+            DeckList::parse("4 Lightning Bolt\n20 Mountain\n2x Lightning Bolt\n\n3 Abrade\n")
+                .unwrap();
+        assert_eq!(
+            list.main,
+            vec![entry(6, "Lightning Bolt"), entry(20, "Mountain")]
+        );
+        assert_eq!(list.side, vec![entry(3, "Abrade")]);
+        assert_eq!((list.main_count(), list.side_count()), (26, 3));
+
+        let list = DeckList::parse("4 Lightning Bolt\nSB: 2 Abrade").unwrap();
+        assert_eq!(list.side, vec![entry(2, "Abrade")]);
+
+        assert_eq!(
+            DeckList::parse("4 Lightning Bolt\nfour Mountain"),
+            Err(DeckImportError::BadLine(2, "four Mountain".to_owned()))
+        );
+        assert_eq!(
+            DeckList::parse("\n// Nothing\n"),
+            Err(DeckImportError::Empty)
+        );
+    }
+
+    #[test]
+    fn arena_lists() {
+        let text = "About\nName Boros Burn\n\nCommander\n1 Norin the Wary (TSR) 178\n\n\
+                    Deck\n4 Lightning Bolt (M10) 146\n20 Mountain (ZNR) 381 *F*\n\n\
+                    Sideboard\n2 Abrade (HOU) 83\n";
+        let list = DeckList::parse(text).unwrap();
+        assert_eq!(list.name.as_deref(), Some("Boros Burn"));
+        assert_eq!(list.commander, vec![entry(1, "Norin the Wary")]);
+        assert_eq!(
+            list.main,
+            vec![entry(4, "Lightning Bolt"), entry(20, "Mountain")]
+        );
+        assert_eq!(list.side, vec![entry(2, "Abrade")]);
+    }
+
+    #[test]
+    fn deck_site_urls() {
+        assert_eq!(
+            DeckUrl::parse("https://www.moxfield.com/decks/Ab3_x9"),
+            Ok(DeckUrl::Moxfield("Ab3_x9".to_owned()))
+        );
+        assert_eq!(
+            DeckUrl::parse("https://archidekt.com/decks/123456/boros_burn"),
+            Ok(DeckUrl::Archidekt(123456))
+        );
+        assert_eq!(
+            DeckUrl::parse("https://example.com/decks/1"),
+            Err(DeckImportError::UnsupportedUrl)
+        );
+    }
+
+    #[test]
+    fn deck_site_responses() {
+        let card = |count: u32, name: &str| json!({ "quantity": count, "card": { "name": name } });
+        let moxfield = json!({
+            "name": "Burn",
+            "boards": {
+                "mainboard": { "cards": { "a": card(4, "Lightning Bolt") } },
+                "sideboard": { "cards": { "b": card(2, "Abrade") } },
+                "maybeboard": { "cards": { "c": card(1, "Shock") } },
+            }
+        });
+        let list = DeckList::from_moxfield(&moxfield).unwrap();
+        assert_eq!(list.main, vec![entry(4, "Lightning Bolt")]);
+        assert_eq!(list.side, vec![entry(2, "Abrade")]);
+
+        let card = |count: u32, category: &str, name: &str| {
+            json!({
+                "quantity": count,
+                "categories": [category],
+                "card": { "oracleCard": { "name": name } },
+            })
+        };
+        let archidekt = json!({
+            "name": "Burn",
+            "cards": [
+                card(4, "Burn", "Lightning Bolt"),
+                card(2, "Sideboard", "Abrade"),
+                card(1, "Maybeboard", "Shock"),
+            ]
+        });
+        assert_eq!(DeckList::from_archidekt(&archidekt).unwrap(), list);
+    }
+
+    #[test]
+    fn fuzzy_card_names() {
+        let matcher = CardMatcher::new([
+            "Lightning Bolt",
+            "Lim-Dûl's Vault",
+            "Fable of the Mirror-Breaker // Reflection of Kiki-Jiki",
+            "Shock",
+            "Shack",
+        ]);
+        assert_eq!(matcher.find("lightning bolt"), Some("Lightning Bolt"));
+        assert_eq!(matcher.find("Lightning Blot"), Some("Lightning Bolt"));
+        assert_eq!(matcher.find("Lim-Dul's Vault"), Some("Lim-Dûl's Vault"));
+        assert_eq!(
+            matcher.find("Fable of the Mirror Breaker"),
+            Some("Fable of the Mirror-Breaker // Reflection of Kiki-Jiki")
+        );
+        // "Shick" is as close to "Shock" as to "Shack"
+        assert_eq!(matcher.find("Shick"), None);
+        assert_eq!(matcher.find("Counterspell"), None);
+
+        let list = DeckList::parse("4 lightning blot\n4 Lightning Bolt\n1 Counterspel").unwrap();
+        assert_eq!(
+            list.resolve(&matcher),
+            Err(DeckImportError::UnknownCards(
+                vec!["Counterspel".to_owned()]
+            ))
+        );
+    }
+}
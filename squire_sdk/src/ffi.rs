@@ -0,0 +1,389 @@
+//! C bindings for the `TournamentManager`, which let native clients (like SquireDesktop) keep a
+//! copy of a tournament that can be synced with SquireCore.
+//!
+//! Managers are handed out as opaque pointers. Everything else crosses the boundary as a
+//! null-terminated, JSON-encoded string using the same encoding as the rest of the SDK. Each
+//! manager that is returned must be released with `squire_manager_free`, and each string with
+//! `squire_string_free`. Functions that can fail return a null pointer.
+//!
+//! Standings and pairings are also given as flat arrays, so that they can be rendered without
+//! decoding the tournament. Each of these has its own free function.
+
+use std::{
+    ffi::{c_char, CStr, CString},
+    ptr,
+};
+
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{
+    model::{
+        accounts::SquireAccount, identifiers::PlayerId, operations::TournOp, r64,
+        tournament::TournamentSeed,
+    },
+    sync::TournamentManager,
+};
+
+/// Decodes a JSON string that was passed in over the boundary
+///
+/// # Safety
+/// The pointer must be null or point to a valid, null-terminated string.
+unsafe fn from_json<T: DeserializeOwned>(json: *const c_char) -> Option<T> {
+    if json.is_null() {
+        return None;
+    }
+    let json = CStr::from_ptr(json).to_str().ok()?;
+    serde_json::from_str(json).ok()
+}
+
+/// Encodes a value as a JSON string that is handed out over the boundary
+fn to_json<T: Serialize>(value: &T) -> *mut c_char {
+    serde_json::to_string(value)
+        .ok()
+        .and_then(|json| CString::new(json).ok())
+        .map_or(ptr::null_mut(), CString::into_raw)
+}
+
+/// Creates a manager for a new tournament from a `TournamentSeed` and the `SquireAccount` of the
+/// tournament's owner. Null is returned if either can not be decoded.
+///
+/// # Safety
+/// Both pointers must be null or point to valid, null-terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn squire_manager_new(
+    seed: *const c_char,
+    owner: *const c_char,
+) -> *mut TournamentManager {
+    let (Some(seed), Some(owner)) = (
+        from_json::<TournamentSeed>(seed),
+        from_json::<SquireAccount>(owner),
+    ) else {
+        return ptr::null_mut();
+    };
+    Box::into_raw(Box::new(TournamentManager::new(owner, seed)))
+}
+
+/// Recreates a manager from an export made by `squire_manager_export` or a manager that was
+/// fetched from SquireCore. Null is returned if the manager can not be decoded.
+///
+/// # Safety
+/// The pointer must be null or point to a valid, null-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn squire_manager_import(export: *const c_char) -> *mut TournamentManager {
+    match from_json::<TournamentManager>(export) {
+        Some(manager) => Box::into_raw(Box::new(manager)),
+        None => ptr::null_mut(),
+    }
+}
+
+/// Exports the manager, which includes both the tournament and its operations log
+///
+/// # Safety
+/// The manager must have been created by this library and not yet freed.
+#[no_mangle]
+pub unsafe extern "C" fn squire_manager_export(manager: *const TournamentManager) -> *mut c_char {
+    match manager.as_ref() {
+        Some(manager) => to_json(manager),
+        None => ptr::null_mut(),
+    }
+}
+
+/// Returns the manager's current tournament
+///
+/// # Safety
+/// The manager must have been created by this library and not yet freed.
+#[no_mangle]
+pub unsafe extern "C" fn squire_manager_tourn(manager: *const TournamentManager) -> *mut c_char {
+    match manager.as_ref() {
+        Some(manager) => to_json(manager.tourn()),
+        None => ptr::null_mut(),
+    }
+}
+
+/// Returns the manager's operations log
+///
+/// # Safety
+/// The manager must have been created by this library and not yet freed.
+#[no_mangle]
+pub unsafe extern "C" fn squire_manager_op_log(manager: *const TournamentManager) -> *mut c_char {
+    match manager.as_ref() {
+        Some(manager) => to_json(manager.op_log()),
+        None => ptr::null_mut(),
+    }
+}
+
+/// Applies a `TournOp` to the manager's tournament and returns the result, i.e. either the
+/// `OpData` or the `TournamentError`. Null is returned if the operation can not be decoded.
+///
+/// # Safety
+/// The manager must have been created by this library and not yet freed. The operation must be
+/// null or point to a valid, null-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn squire_manager_apply_op(
+    manager: *mut TournamentManager,
+    op: *const c_char,
+) -> *mut c_char {
+    let (Some(manager), Some(op)) = (manager.as_mut(), from_json::<TournOp>(op)) else {
+        return ptr::null_mut();
+    };
+    to_json(&manager.apply_op(op))
+}
+
+/// A player's place in the standings. Standings are given best first.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SquireStanding {
+    /// The bytes of the player's id
+    pub player: [u8; 16],
+    /// The number of match points the player has
+    pub match_points: f64,
+    /// The number of game points the player has
+    pub game_points: f64,
+    /// The player's match win percentage
+    pub mwp: f64,
+    /// The player's game win percentage
+    pub gwp: f64,
+    /// The average match win percentage of the player's opponents
+    pub opp_mwp: f64,
+    /// The average game win percentage of the player's opponents
+    pub opp_gwp: f64,
+}
+
+/// The standings of a tournament. Release them with `squire_standings_free`.
+#[repr(C)]
+#[derive(Debug)]
+pub struct SquireStandings {
+    /// The standings, best first
+    pub data: *mut SquireStanding,
+    /// The number of players in the standings
+    pub len: usize,
+}
+
+/// The pairings for a tournament's next round. Players are given by the bytes of their ids.
+/// Release them with `squire_pairings_free`.
+#[repr(C)]
+#[derive(Debug)]
+pub struct SquirePairings {
+    /// The players that are paired, one group after another
+    pub players: *mut [u8; 16],
+    /// The number of paired players
+    pub players_len: usize,
+    /// The number of players in each group, in the same order as the players
+    pub group_sizes: *mut usize,
+    /// The number of groups
+    pub groups_len: usize,
+    /// The players that could not be paired
+    pub rejected: *mut [u8; 16],
+    /// The number of players that could not be paired
+    pub rejected_len: usize,
+}
+
+/// Hands a vector out over the boundary as a pointer and a length
+fn into_raw_parts<T>(items: Vec<T>) -> (*mut T, usize) {
+    let len = items.len();
+    (Box::into_raw(items.into_boxed_slice()).cast(), len)
+}
+
+/// Reclaims a vector that was handed out by `into_raw_parts`
+///
+/// # Safety
+/// The pointer and length must have come from `into_raw_parts` and not yet been reclaimed.
+unsafe fn free_raw_parts<T>(data: *mut T, len: usize) {
+    if !data.is_null() {
+        drop(Box::from_raw(ptr::slice_from_raw_parts_mut(data, len)));
+    }
+}
+
+fn to_f64(val: r64) -> f64 {
+    f64::from(*val.numer()) / f64::from(*val.denom())
+}
+
+fn id_bytes(id: &PlayerId) -> [u8; 16] {
+    *id.as_bytes()
+}
+
+/// Returns the current standings of the manager's tournament. If the manager is null, the
+/// standings are empty.
+///
+/// # Safety
+/// The manager must be null or have been created by this library and not yet freed.
+#[no_mangle]
+pub unsafe extern "C" fn squire_manager_standings(
+    manager: *const TournamentManager,
+) -> SquireStandings {
+    let standings = manager
+        .as_ref()
+        .map(|manager| manager.get_standings().scores)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(id, score)| SquireStanding {
+            player: id_bytes(&id),
+            match_points: to_f64(score.match_points),
+            game_points: to_f64(score.game_points),
+            mwp: to_f64(score.mwp),
+            gwp: to_f64(score.gwp),
+            opp_mwp: to_f64(score.opp_mwp),
+            opp_gwp: to_f64(score.opp_gwp),
+        })
+        .collect();
+    let (data, len) = into_raw_parts(standings);
+    SquireStandings { data, len }
+}
+
+/// Releases standings that were returned by `squire_manager_standings`
+///
+/// # Safety
+/// The standings must have been returned by this library and not yet freed.
+#[no_mangle]
+pub unsafe extern "C" fn squire_standings_free(standings: SquireStandings) {
+    free_raw_parts(standings.data, standings.len);
+}
+
+/// Proposes the pairings for the tournament's next round. Nothing is applied to the tournament;
+/// to pair the round, apply an `AdminOp::PairRound` with the pairings. If the tournament can not
+/// be paired (e.g. it has not started) or the manager is null, the pairings are empty.
+///
+/// # Safety
+/// The manager must be null or have been created by this library and not yet freed.
+#[no_mangle]
+pub unsafe extern "C" fn squire_manager_pairings(
+    manager: *const TournamentManager,
+) -> SquirePairings {
+    let pairings = manager
+        .as_ref()
+        .and_then(|manager| manager.create_pairings())
+        .unwrap_or_default();
+    let group_sizes = pairings.paired.iter().map(Vec::len).collect();
+    let players = pairings.paired.iter().flatten().map(id_bytes).collect();
+    let rejected = pairings.rejected.iter().map(id_bytes).collect();
+    let (players, players_len) = into_raw_parts(players);
+    let (group_sizes, groups_len) = into_raw_parts(group_sizes);
+    let (rejected, rejected_len) = into_raw_parts(rejected);
+    SquirePairings {
+        players,
+        players_len,
+        group_sizes,
+        groups_len,
+        rejected,
+        rejected_len,
+    }
+}
+
+/// Releases pairings that were returned by `squire_manager_pairings`
+///
+/// # Safety
+/// The pairings must have been returned by this library and not yet freed.
+#[no_mangle]
+pub unsafe extern "C" fn squire_pairings_free(pairings: SquirePairings) {
+    free_raw_parts(pairings.players, pairings.players_len);
+    free_raw_parts(pairings.group_sizes, pairings.groups_len);
+    free_raw_parts(pairings.rejected, pairings.rejected_len);
+}
+
+/// Releases a manager
+///
+/// # Safety
+/// The manager must be null or have been created by this library and not yet freed.
+#[no_mangle]
+pub unsafe extern "C" fn squire_manager_free(manager: *mut TournamentManager) {
+    if !manager.is_null() {
+        drop(Box::from_raw(manager));
+    }
+}
+
+/// Releases a string that was returned by this library
+///
+/// # Safety
+/// The string must be null or have been returned by this library and not yet freed.
+#[no_mangle]
+pub unsafe extern "C" fn squire_string_free(json: *mut c_char) {
+    if !json.is_null() {
+        drop(CString::from_raw(json));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::{c_char, CStr, CString};
+
+    use squire_tests::{get_seed, spoof_account};
+
+    use super::{
+        squire_manager_apply_op, squire_manager_export, squire_manager_free, squire_manager_import,
+        squire_manager_new, squire_manager_pairings, squire_manager_standings,
+        squire_pairings_free, squire_standings_free, squire_string_free,
+    };
+    use crate::{
+        model::operations::{AdminOp, OpResult, TournOp},
+        sync::TournamentManager,
+    };
+
+    fn json<T: serde::Serialize>(value: &T) -> CString {
+        CString::new(serde_json::to_string(value).unwrap()).unwrap()
+    }
+
+    unsafe fn take<T: serde::de::DeserializeOwned>(json: *mut c_char) -> T {
+        assert!(!json.is_null());
+        let value = serde_json::from_str(CStr::from_ptr(json).to_str().unwrap()).unwrap();
+        squire_string_free(json);
+        value
+    }
+
+    #[test]
+    fn managers_round_trip() {
+        unsafe {
+            let manager =
+                squire_manager_new(json(&get_seed()).as_ptr(), json(&spoof_account()).as_ptr());
+            assert!(!manager.is_null());
+
+            let op = json(&TournOp::RegisterPlayer(spoof_account(), None));
+            let result: OpResult = take(squire_manager_apply_op(manager, op.as_ptr()));
+            assert!(result.is_ok());
+            // Invalid operations are turned away
+            let bad = CString::new("not an op").unwrap();
+            assert!(squire_manager_apply_op(manager, bad.as_ptr()).is_null());
+
+            let export: TournamentManager = take(squire_manager_export(manager));
+            assert_eq!(export, *manager);
+            let imported = squire_manager_import(json(&export).as_ptr());
+            assert_eq!(*imported, *manager);
+            assert_eq!((*imported).op_log().len(), 1);
+
+            squire_manager_free(manager);
+            squire_manager_free(imported);
+        }
+    }
+
+    #[test]
+    fn standings_and_pairings_are_flat() {
+        let admin = spoof_account();
+        let admin_id = admin.id.0.into();
+        let mut manager = TournamentManager::new(admin, get_seed());
+        for _ in 0..4 {
+            let _ = manager
+                .apply_op(TournOp::RegisterPlayer(spoof_account(), None))
+                .unwrap();
+        }
+        let _ = manager
+            .apply_op(TournOp::AdminOp(admin_id, AdminOp::Start))
+            .unwrap();
+        unsafe {
+            let standings = squire_manager_standings(&manager);
+            assert_eq!(standings.len, 4);
+            let scores = std::slice::from_raw_parts(standings.data, standings.len);
+            assert!(scores.iter().all(|s| s.match_points == 0.0));
+            squire_standings_free(standings);
+
+            let pairings = squire_manager_pairings(&manager);
+            let sizes = std::slice::from_raw_parts(pairings.group_sizes, pairings.groups_len);
+            assert_eq!(sizes.iter().sum::<usize>(), pairings.players_len);
+            assert_eq!(pairings.players_len + pairings.rejected_len, 4);
+            squire_pairings_free(pairings);
+
+            // Null managers give empty arrays
+            let standings = squire_manager_standings(std::ptr::null());
+            assert_eq!(standings.len, 0);
+            squire_standings_free(standings);
+        }
+    }
+}
@@ -33,6 +33,14 @@ pub mod client;
 /// The default client used by non-squire_core services to communicate with squire_core
 pub mod server;
 
+#[cfg(feature = "ffi")]
+/// C bindings for the tournament manager, used by native clients
+pub mod ffi;
+
+#[cfg(any(feature = "client", feature = "server"))]
+/// Reads decklists from text and deck sites for use in tournaments
+pub mod decks;
+
 /// Contains the definition of the actor model used by both the client and server
 pub mod actor;
 /// Contains all of the API definitions
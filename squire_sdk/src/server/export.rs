@@ -0,0 +1,251 @@
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use serde::Serialize;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+use crate::{
+    api::{ExportVerification, SignedExport},
+    model::identifiers::TournamentId,
+    sync::{OpLog, TournamentManager},
+};
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Signs the current state and operations log of a tournament using the given key.
+pub fn sign_export(key: &[u8], tourn: TournamentManager) -> SignedExport {
+    let state_digest = digest(tourn.tourn());
+    let log_digest = log_digest(tourn.op_log());
+    let signed_at = Utc::now();
+    let signature = signer(key, tourn.id, &state_digest, &log_digest, signed_at)
+        .finalize()
+        .into_bytes();
+    SignedExport {
+        tourn,
+        state_digest,
+        log_digest,
+        signed_at,
+        signature: hex::encode(signature),
+    }
+}
+
+/// Checks that the export was signed with the given key and that the exported tournament has not
+/// been changed since it was signed.
+pub fn verify_export(key: &[u8], export: &SignedExport) -> ExportVerification {
+    let SignedExport {
+        tourn,
+        state_digest,
+        log_digest,
+        signed_at,
+        signature,
+    } = export;
+    let Ok(signature) = hex::decode(signature) else {
+        return ExportVerification::InvalidSignature;
+    };
+    if signer(key, tourn.id, state_digest, log_digest, *signed_at)
+        .verify_slice(&signature)
+        .is_err()
+    {
+        return ExportVerification::InvalidSignature;
+    }
+    if digest(tourn.tourn()) != *state_digest || log_digest(tourn.op_log()) != *log_digest {
+        return ExportVerification::Modified;
+    }
+    ExportVerification::Verified
+}
+
+/// Hashes a canonical JSON form of the given value. The tournament serializes its maps and sets
+/// in sorted order, so only the keys of JSON objects need to be put in order before hashing. The
+/// order of every list is kept, so reordering the players of a round (or any other list) changes
+/// the digest.
+fn digest<T: Serialize>(value: &T) -> String {
+    let mut value = serde_json::to_value(value).unwrap();
+    canonicalize(&mut value);
+    hex::encode(Sha256::digest(serde_json::to_vec(&value).unwrap()))
+}
+
+/// Hashes an operations log. Each operation is hashed in its canonical form, and the operations
+/// are hashed in the order that they were applied.
+fn log_digest(log: &OpLog) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(digest(&(&log.owner, &log.seed, &log.snapshot)));
+    for op in &log.ops {
+        hasher.update(digest(op));
+    }
+    hex::encode(hasher.finalize())
+}
+
+/// Orders the keys of every object in the value. Arrays are left in the order they were
+/// serialized in.
+fn canonicalize(value: &mut Value) {
+    match value {
+        Value::Array(items) => items.iter_mut().for_each(canonicalize),
+        Value::Object(map) => {
+            let mut entries: Vec<_> = std::mem::take(map).into_iter().collect();
+            entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+            for (key, mut value) in entries {
+                canonicalize(&mut value);
+                let _ = map.insert(key, value);
+            }
+        }
+        Value::Null | Value::Bool(_) | Value::Number(_) | Value::String(_) => {}
+    }
+}
+
+fn signer(
+    key: &[u8],
+    id: TournamentId,
+    state_digest: &str,
+    log_digest: &str,
+    signed_at: DateTime<Utc>,
+) -> HmacSha256 {
+    let mut mac = HmacSha256::new_from_slice(key).expect("HMAC can take a key of any size");
+    for field in [
+        id.to_string().as_str(),
+        state_digest,
+        log_digest,
+        &signed_at.to_rfc3339(),
+    ] {
+        mac.update(field.as_bytes());
+        mac.update(b"\n");
+    }
+    mac
+}
+
+#[cfg(feature = "client")]
+#[cfg(test)]
+mod tests {
+    use squire_tests::{get_seed, spoof_account};
+
+    use super::{sign_export, verify_export};
+    use crate::{
+        api::ExportVerification,
+        decks::DeckList,
+        model::operations::{AdminOp, PlayerOp, TournOp},
+        sync::TournamentManager,
+    };
+
+    const KEY: &[u8] = b"export signing key";
+
+    fn spoof_export(players: usize) -> TournamentManager {
+        let admin = spoof_account();
+        let admin_id = admin.id.0.into();
+        let mut tourn = TournamentManager::new(admin, get_seed());
+        let deck = DeckList::parse("60 Mountain").unwrap().into_deck().unwrap();
+        for _ in 0..players {
+            let account = spoof_account();
+            let p_id = account.id.0.into();
+            let _ = tourn
+                .apply_op(TournOp::RegisterPlayer(account, None))
+                .unwrap();
+            let _ = tourn
+                .apply_op(TournOp::PlayerOp(
+                    p_id,
+                    PlayerOp::AddDeck("Mono Red".into(), deck.clone()),
+                ))
+                .unwrap();
+        }
+        let _ = tourn
+            .apply_op(TournOp::AdminOp(admin_id, AdminOp::Start))
+            .unwrap();
+        tourn
+    }
+
+    #[test]
+    fn signed_exports_verify() {
+        let export = sign_export(KEY, spoof_export(4));
+        assert_eq!(verify_export(KEY, &export), ExportVerification::Verified);
+
+        // Round tripping through JSON does not change the digests
+        let json = serde_json::to_string(&export).unwrap();
+        let export = serde_json::from_str(&json).unwrap();
+        assert_eq!(verify_export(KEY, &export), ExportVerification::Verified);
+
+        // Only the signing key can verify the export
+        assert_eq!(
+            verify_export(b"another key", &export),
+            ExportVerification::InvalidSignature
+        );
+    }
+
+    #[test]
+    fn exports_verify_after_many_round_trips() {
+        // With this many players, the maps of the tournament are very unlikely to be iterated in
+        // the same order after being deserialized
+        let mut export = sign_export(KEY, spoof_export(64));
+        for _ in 0..5 {
+            let json = serde_json::to_string(&export).unwrap();
+            export = serde_json::from_str(&json).unwrap();
+            assert_eq!(verify_export(KEY, &export), ExportVerification::Verified);
+        }
+    }
+
+    #[test]
+    fn edited_exports_fail_verification() {
+        let mut export = sign_export(KEY, spoof_export(4));
+        let admin_id = export.tourn.tourn().admins.keys().next().copied().unwrap();
+        let _ = export
+            .tourn
+            .apply_op(TournOp::AdminOp(admin_id, AdminOp::Freeze))
+            .unwrap();
+        assert_eq!(verify_export(KEY, &export), ExportVerification::Modified);
+
+        // Recomputing the digests does not help without the key
+        let resigned = sign_export(b"another key", export.tourn.clone());
+        export.state_digest = resigned.state_digest;
+        export.log_digest = resigned.log_digest;
+        assert_eq!(
+            verify_export(KEY, &export),
+            ExportVerification::InvalidSignature
+        );
+    }
+
+    #[test]
+    fn reordered_round_players_fail_verification() {
+        let mut tourn = spoof_export(4);
+        let admin_id = tourn.tourn().admins.keys().next().copied().unwrap();
+        let pairings = tourn.tourn().create_pairings().unwrap();
+        let _ = tourn
+            .apply_op(TournOp::AdminOp(admin_id, AdminOp::PairRound(pairings)))
+            .unwrap();
+        let export = sign_export(KEY, tourn);
+        assert_eq!(verify_export(KEY, &export), ExportVerification::Verified);
+
+        // Swapping the seats of two players is an edit, even though the round has the same players
+        let mut json = serde_json::to_value(&export).unwrap();
+        let players = json["tourn"]["tourn"]["round_reg"]["rounds"][0][1]["players"]
+            .as_array_mut()
+            .unwrap();
+        players.swap(0, 1);
+        let export = serde_json::from_value(json).unwrap();
+        assert_eq!(verify_export(KEY, &export), ExportVerification::Modified);
+    }
+
+    #[test]
+    fn spectator_exports_carry_no_decklists() {
+        let tourn = spoof_export(4);
+        assert!(tourn
+            .tourn()
+            .player_reg
+            .players
+            .values()
+            .all(|plyr| !plyr.decks.is_empty()));
+
+        // This is the copy of the tournament that is exported for users that aren't officials
+        let export = sign_export(KEY, tourn.redacted());
+        assert_eq!(verify_export(KEY, &export), ExportVerification::Verified);
+        assert!(export
+            .tourn
+            .tourn()
+            .player_reg
+            .players
+            .values()
+            .all(|plyr| plyr.decks.is_empty()));
+        assert!(export
+            .tourn
+            .op_log()
+            .ops
+            .iter()
+            .all(|op| !op.op.has_decklist()));
+    }
+}
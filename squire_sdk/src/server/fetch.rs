@@ -0,0 +1,188 @@
+use std::{
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr},
+    time::Duration,
+};
+
+use reqwest::{redirect::Policy, Client, Url};
+
+/// How long the server waits to connect to a host that it fetches from or posts to
+pub const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How long the server waits for a whole request to a host that it fetches from or posts to
+pub const REQUEST_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// The reasons that the server will not send a request to a URL that a user gave it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FetchError {
+    /// The URL could not be parsed or does not use HTTPS
+    InvalidUrl,
+    /// The URL's host resolves to an address that is not on the public internet, such as a
+    /// loopback, private, or link-local address
+    PrivateAddress,
+    /// The request failed, timed out, or was answered with an error
+    Failed,
+    /// The response was larger than allowed
+    TooLarge,
+}
+
+/// Checks that a URL that a user gave the server is safe for the server to send requests to. The
+/// URL must use HTTPS and every address that its host resolves to must be public, so that users
+/// can not have the server reach the services on its own network.
+///
+/// Returns the address that requests to the URL should be sent to.
+pub async fn check_public_url(url: &str) -> Result<(Url, SocketAddr), FetchError> {
+    let url = Url::parse(url).map_err(|_| FetchError::InvalidUrl)?;
+    if url.scheme() != "https" {
+        return Err(FetchError::InvalidUrl);
+    }
+    let host = url
+        .host_str()
+        .ok_or(FetchError::InvalidUrl)?
+        .trim_start_matches('[')
+        .trim_end_matches(']');
+    let port = url.port_or_known_default().ok_or(FetchError::InvalidUrl)?;
+    let addrs: Vec<_> = tokio::net::lookup_host((host, port))
+        .await
+        .map_err(|_| FetchError::Failed)?
+        .collect();
+    if addrs.is_empty() {
+        return Err(FetchError::Failed);
+    }
+    if !addrs.iter().all(|addr| is_public(addr.ip())) {
+        return Err(FetchError::PrivateAddress);
+    }
+    Ok((url, addrs[0]))
+}
+
+/// Creates a client for sending requests to a URL that was checked by [check_public_url]. The
+/// client is pinned to the checked address, so the host can not be re-resolved to a private
+/// address, and it does not follow redirects, which could point anywhere.
+pub fn pinned_client(url: &Url, addr: SocketAddr) -> Result<Client, FetchError> {
+    let mut builder = Client::builder()
+        .redirect(Policy::none())
+        .connect_timeout(CONNECT_TIMEOUT)
+        .timeout(REQUEST_TIMEOUT);
+    if let Some(host) = url.domain() {
+        builder = builder.resolve(host, addr);
+    }
+    builder.build().map_err(|_| FetchError::Failed)
+}
+
+/// Fetches the text at a URL that a user gave the server. Responses that are larger than the limit
+/// are dropped without being read in full.
+pub async fn fetch_text(url: &str, limit: usize) -> Result<String, FetchError> {
+    let (url, addr) = check_public_url(url).await?;
+    let mut resp = pinned_client(&url, addr)?
+        .get(url)
+        .send()
+        .await
+        .and_then(|resp| resp.error_for_status())
+        .map_err(|_| FetchError::Failed)?;
+    if resp.content_length().is_some_and(|len| len > limit as u64) {
+        return Err(FetchError::TooLarge);
+    }
+    let mut body = Vec::new();
+    while let Some(chunk) = resp.chunk().await.map_err(|_| FetchError::Failed)? {
+        if body.len() + chunk.len() > limit {
+            return Err(FetchError::TooLarge);
+        }
+        body.extend_from_slice(&chunk);
+    }
+    String::from_utf8(body).map_err(|_| FetchError::Failed)
+}
+
+/// Calculates if the address is on the public internet
+pub fn is_public(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => is_public_v4(ip),
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(ip) => is_public_v4(ip),
+            None => is_public_v6(ip),
+        },
+    }
+}
+
+fn is_public_v4(ip: Ipv4Addr) -> bool {
+    let [a, b, ..] = ip.octets();
+    !(ip.is_unspecified()
+        || ip.is_loopback()
+        || ip.is_private()
+        || ip.is_link_local()
+        || ip.is_broadcast()
+        || ip.is_multicast()
+        || ip.is_documentation()
+        // Shared address space (RFC 6598), which is used behind carrier-grade NATs
+        || (a == 100 && (64..128).contains(&b))
+        // "This network" (RFC 791)
+        || a == 0)
+}
+
+fn is_public_v6(ip: Ipv6Addr) -> bool {
+    let first = ip.segments()[0];
+    !(ip.is_unspecified()
+        || ip.is_loopback()
+        || ip.is_multicast()
+        // Unique local addresses (fc00::/7)
+        || (first & 0xfe00) == 0xfc00
+        // Link-local addresses (fe80::/10)
+        || (first & 0xffc0) == 0xfe80)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::IpAddr;
+
+    use super::{check_public_url, is_public, FetchError};
+
+    fn ip(ip: &str) -> IpAddr {
+        ip.parse().unwrap()
+    }
+
+    #[test]
+    fn only_public_addresses_are_public() {
+        for addr in ["1.1.1.1", "142.250.80.46", "2606:4700:4700::1111"] {
+            assert!(is_public(ip(addr)), "{addr}");
+        }
+        for addr in [
+            "127.0.0.1",
+            "10.0.0.1",
+            "172.16.5.4",
+            "192.168.1.1",
+            "169.254.169.254",
+            "100.64.0.1",
+            "0.0.0.0",
+            "::1",
+            "::",
+            "fd00::1",
+            "fe80::1",
+            "::ffff:127.0.0.1",
+            "::ffff:169.254.169.254",
+        ] {
+            assert!(!is_public(ip(addr)), "{addr}");
+        }
+    }
+
+    #[tokio::test]
+    async fn private_urls_are_rejected() {
+        assert_eq!(
+            check_public_url("http://example.com/sheet.csv").await,
+            Err(FetchError::InvalidUrl)
+        );
+        assert_eq!(
+            check_public_url("https://127.0.0.1/sheet.csv").await,
+            Err(FetchError::PrivateAddress)
+        );
+        assert_eq!(
+            check_public_url("https://[::1]:8000/").await,
+            Err(FetchError::PrivateAddress)
+        );
+        assert_eq!(
+            check_public_url("https://169.254.169.254/latest/meta-data").await,
+            Err(FetchError::PrivateAddress)
+        );
+        assert_eq!(
+            check_public_url("https://localhost/").await,
+            Err(FetchError::PrivateAddress)
+        );
+    }
+}
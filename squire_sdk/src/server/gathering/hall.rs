@@ -1,19 +1,24 @@
-use std::collections::{HashMap, HashSet};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
 
 use async_trait::async_trait;
 use axum::extract::ws::WebSocket;
 use instant::{Duration, Instant};
-use squire_lib::tournament::TournamentId;
+use squire_lib::{error::TournamentError, operations::TournOp, tournament::TournamentId};
 use tokio::sync::{
     mpsc::{channel, Receiver, Sender},
-    oneshot::channel as oneshot_channel,
+    oneshot::{channel as oneshot_channel, Sender as OneshotSender},
 };
 
-use super::{Gathering, GatheringMessage, PersistMessage, PersistReadyMessage};
+use super::{
+    Gathering, GatheringMessage, HallCounters, PersistMessage, PersistReadyMessage, RateLimit,
+};
 use crate::{
     actor::{ActorBuilder, ActorClient, ActorState, Scheduler},
     server::session::SessionWatcher,
-    sync::TournamentManager,
+    sync::{Compression, FullOp, Rejection, TournamentManager},
 };
 
 /* TODO:
@@ -48,15 +53,97 @@ where
     );
 }
 
+/// The reply to an operation that the server applies to a tournament through its gathering. The
+/// reply is `None` if the gathering could not be started.
+pub type ApplyReply = OneshotSender<Option<Result<(), TournamentError>>>;
+
 /// A message sent to a `GatheringHall` that communicates some command that it needs to process.
 #[derive(Debug)]
 pub enum GatheringHallMessage {
     /// Create a new gathering
     NewGathering(TournamentId),
     /// Adds an onlooker to a gathering
-    NewConnection(TournamentId, SessionWatcher, WebSocket),
+    NewConnection(TournamentId, SessionWatcher, Compression, WebSocket),
     /// Perist all the tournaments that need to be persisted
     Persist,
+    /// Applies an operation to a tournament on behalf of the server, starting its gathering if
+    /// needed
+    ApplyOp(TournamentId, TournOp, ApplyReply),
+    /// Reports the current load on the hall
+    GetMetrics(OneshotSender<HallMetrics>),
+    /// Stops accepting connections, closes every gathering, and persists their tournaments. The
+    /// reply is sent once the persister has finished.
+    Shutdown(OneshotSender<()>),
+    /// Closes a tournament's gathering without persisting its tournament, i.e. because another
+    /// node has taken the gathering over. Later connections start a new gathering.
+    CloseGathering(TournamentId),
+}
+
+impl From<((), OneshotSender<HallMetrics>)> for GatheringHallMessage {
+    fn from(((), send): ((), OneshotSender<HallMetrics>)) -> Self {
+        Self::GetMetrics(send)
+    }
+}
+
+impl From<((TournamentId, TournOp), ApplyReply)> for GatheringHallMessage {
+    fn from(((id, op), send): ((TournamentId, TournOp), ApplyReply)) -> Self {
+        Self::ApplyOp(id, op, send)
+    }
+}
+
+impl From<((), OneshotSender<()>)> for GatheringHallMessage {
+    fn from(((), send): ((), OneshotSender<()>)) -> Self {
+        Self::Shutdown(send)
+    }
+}
+
+/// The limits that a `GatheringHall` places on new connections. Once a limit is reached, new
+/// connections are sent a [`Rejection`] and closed, so the server turns new users away instead of
+/// falling over.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HallLimits {
+    /// The maximum number of tournaments that can have a gathering at once
+    pub max_gatherings: usize,
+    /// The maximum number of onlookers in a single gathering
+    pub max_onlookers: usize,
+    /// The maximum number of websockets across all gatherings
+    pub max_sockets: usize,
+    /// The rate at which each connection can send sync messages to its gathering
+    pub sync_rate: RateLimit,
+}
+
+impl Default for HallLimits {
+    fn default() -> Self {
+        Self {
+            max_gatherings: 1_000,
+            max_onlookers: 1_000,
+            max_sockets: 20_000,
+            sync_rate: RateLimit::default(),
+        }
+    }
+}
+
+/// A snapshot of the load on a `GatheringHall` and of the connections that it has turned away
+/// since it was started.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct HallMetrics {
+    /// The number of tournaments that have a gathering
+    pub gatherings: usize,
+    /// The number of open websockets across all gatherings
+    pub sockets: usize,
+    /// The number of connections rejected because their tournament could not be found
+    pub rejected_not_found: usize,
+    /// The number of connections rejected because the hall had too many gatherings
+    pub rejected_gatherings: usize,
+    /// The number of connections rejected because their gathering was full
+    pub rejected_full: usize,
+    /// The number of connections rejected because the hall had too many websockets
+    pub rejected_sockets: usize,
+    /// The number of connections rejected because the server was shutting down
+    pub rejected_shutdown: usize,
+    /// The number of times a gathering could not send something off to be persisted because the
+    /// persistance queue was full. These persists are retried.
+    pub dropped_persists: usize,
 }
 
 /// This structure manages all of the `Gathering`s around tournaments. This includes adding new
@@ -68,6 +155,10 @@ pub struct GatheringHall<P: ActorState<Message = PersistMessage>> {
     persists: Receiver<PersistReadyMessage>,
     persist_sender: Sender<PersistReadyMessage>,
     persister: ActorClient<P>,
+    limits: HallLimits,
+    counters: Arc<HallCounters>,
+    /// Whether the hall has been shut down
+    closed: bool,
 }
 
 #[async_trait]
@@ -84,29 +175,32 @@ where
     async fn process(&mut self, scheduler: &mut Scheduler<Self>, msg: Self::Message) {
         match msg {
             GatheringHallMessage::NewGathering(id) => self.process_new_gathering(id).await,
-            GatheringHallMessage::NewConnection(id, user, ws) => {
-                self.process_new_onlooker(id, user, ws).await
+            GatheringHallMessage::NewConnection(id, user, compression, ws) => {
+                self.process_new_onlooker(id, user, compression, ws).await
             }
             GatheringHallMessage::Persist => {
-                let mut to_persist = HashSet::new();
-                let mut persist_reqs = HashMap::new();
-                while let Ok(PersistReadyMessage(id)) = self.persists.try_recv() {
-                    let _ = to_persist.insert(id);
-                }
-                for id in to_persist.drain() {
-                    let sender = self.gatherings.get_mut(&id).unwrap();
-                    let (send, recv) = oneshot_channel();
-                    let msg = GatheringMessage::GetTournament(send);
-                    sender.send(msg);
-                    let tourn = recv.await.unwrap();
-                    let _ = persist_reqs.insert(id, tourn);
+                // Everything has already been persisted during the shutdown
+                if self.closed {
+                    return;
                 }
-
-                persist_reqs
-                    .drain()
-                    .for_each(|(_, tourn)| self.persister.send(tourn));
+                self.persist_ready().await;
                 schedule_persist(scheduler);
             }
+            GatheringHallMessage::ApplyOp(id, op, send) => {
+                let digest = match self.get_or_init_gathering(id).await {
+                    Ok(gathering) => Some(gathering.track(op).await),
+                    Err(_) => None,
+                };
+                let _ = send.send(digest);
+            }
+            GatheringHallMessage::GetMetrics(send) => {
+                let _ = send.send(self.counters.snapshot(self.gatherings.len()));
+            }
+            GatheringHallMessage::Shutdown(send) => {
+                self.shutdown().await;
+                let _ = send.send(());
+            }
+            GatheringHallMessage::CloseGathering(id) => self.close_gathering(id).await,
         }
     }
 }
@@ -118,49 +212,177 @@ where
     /// Creates a new `GatheringHall` from receiver halves of channels that communicate new
     /// gatherings and subscriptions
     pub fn new(persister: ActorClient<P>) -> Self {
+        Self::with_limits(persister, HallLimits::default())
+    }
+
+    /// Creates a new `GatheringHall` that turns away connections beyond the given limits
+    pub fn with_limits(persister: ActorClient<P>, limits: HallLimits) -> Self {
         let (persist_sender, persists) = channel(1000);
         Self {
             gatherings: HashMap::new(),
             persists,
             persist_sender,
             persister,
+            limits,
+            counters: Arc::default(),
+            closed: false,
         }
     }
 
     async fn spawn_gathering(&self, id: TournamentId) -> Option<ActorClient<Gathering>> {
-        let tourn = self.get_tourn(&id).await?;
-        let gathering = Gathering::new(*tourn, self.persist_sender.clone());
+        let Some(tourn) = self.get_tourn(&id).await else {
+            tracing::debug!(tourn = %id, "Could not find tournament to start a gathering for");
+            return None;
+        };
+        tracing::debug!(tourn = %id, "Starting gathering");
+        let gathering = Gathering::new(
+            *tourn,
+            self.persist_sender.clone(),
+            self.limits.max_onlookers,
+            self.limits.sync_rate,
+            self.counters.clone(),
+        );
         let client = ActorBuilder::new(gathering).launch();
         Some(client)
     }
 
-    async fn process_new_gathering(&mut self, id: TournamentId) {
-        // TODO: We need a way to communicate that a tournament can not be found
-        let Some(send) = self.spawn_gathering(id).await else {
+    /// Sends the persister every tournament and event that has been marked as ready to persist
+    async fn persist_ready(&mut self) {
+        let mut to_persist = HashSet::new();
+        let mut persist_reqs = HashMap::new();
+        let mut new_ops: HashMap<TournamentId, (u64, Vec<FullOp>)> = HashMap::new();
+        let mut events = Vec::new();
+        while let Ok(msg) = self.persists.try_recv() {
+            match msg {
+                PersistReadyMessage::Tournament(id) => {
+                    let _ = to_persist.insert(id);
+                }
+                PersistReadyMessage::Ops(id, version, ops) => {
+                    let batch = new_ops.entry(id).or_default();
+                    batch.0 = version;
+                    batch.1.extend(ops);
+                }
+                PersistReadyMessage::Events(evs) => events.extend(evs),
+            }
+        }
+        if !events.is_empty() {
+            self.persister.send(PersistMessage::RecordEvents(events));
+        }
+        // The full copies that are about to be persisted already hold these operations. Gatherings
+        // that were closed have nothing more to persist.
+        for (id, (version, ops)) in new_ops {
+            if !to_persist.contains(&id) && self.gatherings.contains_key(&id) {
+                self.persister
+                    .send(PersistMessage::AppendOps(id, version, ops));
+            }
+        }
+        for id in to_persist.drain() {
+            let Some(sender) = self.gatherings.get_mut(&id) else {
+                continue;
+            };
+            let (send, recv) = oneshot_channel();
+            let msg = GatheringMessage::GetTournament(send);
+            sender.send(msg);
+            let tourn = recv.await.unwrap();
+            let _ = persist_reqs.insert(id, tourn);
+        }
+
+        persist_reqs
+            .drain()
+            .for_each(|(_, tourn)| self.persister.send(tourn));
+    }
+
+    /// Turns away all new connections, closes every gathering, and waits until the final copy of
+    /// each gathering's tournament has been persisted
+    async fn shutdown(&mut self) {
+        if self.closed {
+            return;
+        }
+        self.closed = true;
+        tracing::info!(
+            gatherings = self.gatherings.len(),
+            "Shutting down gathering hall"
+        );
+        // Lifecycle events are recorded before the final copies of the tournaments are persisted
+        let mut events = Vec::new();
+        while let Ok(msg) = self.persists.try_recv() {
+            if let PersistReadyMessage::Events(evs) = msg {
+                events.extend(evs);
+            }
+        }
+        if !events.is_empty() {
+            self.persister.send(PersistMessage::RecordEvents(events));
+        }
+        for (_, gathering) in self.gatherings.drain() {
+            let (send, recv) = oneshot_channel();
+            gathering.send(GatheringMessage::Shutdown(send));
+            if let Ok(tourn) = recv.await {
+                self.persister.send(PersistMessage::Persist(tourn));
+            }
+        }
+        let (send, recv) = oneshot_channel();
+        self.persister.send(PersistMessage::Flush(send));
+        let _ = recv.await;
+        tracing::info!("Gathering hall shut down");
+    }
+
+    /// Closes a gathering and drops its copy of the tournament. This is only done once the
+    /// gathering has been taken over by another node, which persists the tournament from then on.
+    async fn close_gathering(&mut self, id: TournamentId) {
+        let Some(gathering) = self.gatherings.remove(&id) else {
             return;
         };
-        _ = self.gatherings.insert(id, send);
+        tracing::warn!(tourn = %id, "Closing a gathering that this node no longer hosts");
+        let (send, recv) = oneshot_channel();
+        gathering.send(GatheringMessage::Shutdown(send));
+        _ = recv.await;
+    }
+
+    async fn process_new_gathering(&mut self, id: TournamentId) {
+        // TODO: We need a way to communicate that a tournament can not be found
+        _ = self.get_or_init_gathering(id).await;
     }
 
     async fn process_new_onlooker(
         &mut self,
         id: TournamentId,
         user: SessionWatcher,
+        compression: Compression,
         ws: WebSocket,
     ) {
-        let msg = GatheringMessage::NewConnection(user, ws);
-        let send = self.get_or_init_gathering(id).await;
-        send.send(msg)
+        if self.closed {
+            self.counters.reject(ws, Rejection::ShuttingDown);
+            return;
+        }
+        if self.counters.sockets() >= self.limits.max_sockets {
+            self.counters.reject(ws, Rejection::TooManyConnections);
+            return;
+        }
+        match self.get_or_init_gathering(id).await {
+            Ok(send) => send.send(GatheringMessage::NewConnection(user, compression, ws)),
+            Err(reason) => self.counters.reject(ws, reason),
+        }
     }
 
-    async fn get_or_init_gathering(&mut self, id: TournamentId) -> ActorClient<Gathering> {
+    async fn get_or_init_gathering(
+        &mut self,
+        id: TournamentId,
+    ) -> Result<ActorClient<Gathering>, Rejection> {
         if let Some(send) = self.gatherings.get(&id).cloned() {
-            return send;
+            return Ok(send);
+        }
+        if self.closed {
+            return Err(Rejection::ShuttingDown);
         }
-        // FIXME: This can fail. We need a way to signal this possibility.
-        let send = self.spawn_gathering(id).await.unwrap();
+        if self.gatherings.len() >= self.limits.max_gatherings {
+            return Err(Rejection::TooManyGatherings);
+        }
+        let send = self
+            .spawn_gathering(id)
+            .await
+            .ok_or(Rejection::TournamentNotFound)?;
         _ = self.gatherings.insert(id, send.clone());
-        send
+        Ok(send)
     }
 
     async fn get_tourn(&self, id: &TournamentId) -> Option<Box<TournamentManager>> {
@@ -171,3 +393,113 @@ where
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::HashMap,
+        sync::{Arc, Mutex},
+    };
+
+    use async_trait::async_trait;
+    use squire_lib::{error::TournamentError, operations::TournOp, tournament::TournamentId};
+    use squire_tests::{get_seed, spoof_account};
+
+    use super::{GatheringHall, HallLimits, HallMetrics};
+    use crate::{
+        actor::{ActorClient, ActorState, Scheduler, Tracker},
+        server::gathering::PersistMessage,
+        sync::{Rejection, TournamentManager},
+    };
+
+    type Store = Arc<Mutex<HashMap<TournamentId, TournamentManager>>>;
+
+    /// Keeps the tournaments that the hall persists in memory
+    #[derive(Debug)]
+    struct TestPersister(Store);
+
+    #[async_trait]
+    impl ActorState for TestPersister {
+        type Message = PersistMessage;
+
+        async fn process(&mut self, _: &mut Scheduler<Self>, msg: Self::Message) {
+            let mut tourns = self.0.lock().unwrap();
+            match msg {
+                PersistMessage::Get(id, send) => {
+                    _ = send.send(tourns.get(&id).cloned().map(Box::new));
+                }
+                PersistMessage::Persist(tourn) => {
+                    _ = tourns.insert(tourn.id, *tourn);
+                }
+                PersistMessage::RecordEvents(_) | PersistMessage::AppendOps(..) => {}
+                PersistMessage::Flush(send) => {
+                    _ = send.send(());
+                }
+            }
+        }
+    }
+
+    /// Creates a persister that holds the given number of tournaments
+    fn persister(count: usize) -> (ActorClient<TestPersister>, Store, Vec<TournamentId>) {
+        let tourns: HashMap<_, _> = (0..count)
+            .map(|_| TournamentManager::new(spoof_account(), get_seed()))
+            .map(|tourn| (tourn.id, tourn))
+            .collect();
+        let ids = tourns.keys().copied().collect();
+        let store = Arc::new(Mutex::new(tourns));
+        let client = ActorClient::builder(TestPersister(store.clone())).launch();
+        (client, store, ids)
+    }
+
+    type Hall = ActorClient<GatheringHall<TestPersister>>;
+
+    fn register(hall: &Hall, id: TournamentId) -> Tracker<Option<Result<(), TournamentError>>> {
+        hall.track((id, TournOp::RegisterPlayer(spoof_account(), None)))
+    }
+
+    fn metrics(hall: &Hall) -> Tracker<HallMetrics> {
+        hall.track(())
+    }
+
+    fn shutdown(hall: &Hall) -> Tracker<()> {
+        hall.track(())
+    }
+
+    #[tokio::test]
+    async fn gatherings_are_limited() {
+        let (persister, _, ids) = persister(3);
+        let limits = HallLimits {
+            max_gatherings: 2,
+            ..Default::default()
+        };
+        let mut hall = GatheringHall::with_limits(persister, limits);
+        let unknown = TournamentManager::new(spoof_account(), get_seed()).id;
+        assert!(hall.get_or_init_gathering(ids[0]).await.is_ok());
+        assert_eq!(
+            hall.get_or_init_gathering(unknown).await.err(),
+            Some(Rejection::TournamentNotFound)
+        );
+        assert!(hall.get_or_init_gathering(ids[1]).await.is_ok());
+        assert_eq!(
+            hall.get_or_init_gathering(ids[2]).await.err(),
+            Some(Rejection::TooManyGatherings)
+        );
+        // Gatherings that are already running can still be reached
+        assert!(hall.get_or_init_gathering(ids[0]).await.is_ok());
+        assert_eq!(hall.counters.snapshot(hall.gatherings.len()).gatherings, 2);
+    }
+
+    #[tokio::test]
+    async fn shutdown_persists_the_latest_copies() {
+        let (persister, store, ids) = persister(1);
+        let hall = ActorClient::builder(GatheringHall::new(persister)).launch();
+        assert_eq!(register(&hall, ids[0]).await, Some(Ok(())));
+        // The periodic persist has not run yet, so only the gathering has the new player
+        assert!(store.lock().unwrap()[&ids[0]].player_reg.players.is_empty());
+        shutdown(&hall).await;
+        assert_eq!(store.lock().unwrap()[&ids[0]].player_reg.players.len(), 1);
+        assert_eq!(metrics(&hall).await.gatherings, 0);
+        // Closed halls do not start new gatherings
+        assert_eq!(register(&hall, ids[0]).await, None);
+    }
+}
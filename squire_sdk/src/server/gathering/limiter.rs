@@ -0,0 +1,151 @@
+use instant::{Duration, Instant};
+
+/// The rate at which a single connection may send sync messages to a `Gathering`. Each connection
+/// has a bucket of `capacity` messages that refills by one message every `refill`. Messages sent
+/// while the bucket is empty are rejected with `SyncError::RateLimited`. Once a connection has
+/// had `max_violations` messages rejected without letting its bucket refill, its websocket is
+/// closed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimit {
+    /// The number of messages that can be sent in a single burst
+    pub capacity: u32,
+    /// The time it takes to regain the ability to send one message
+    pub refill: Duration,
+    /// The number of rejected messages after which the connection is closed
+    pub max_violations: u32,
+}
+
+impl Default for RateLimit {
+    fn default() -> Self {
+        Self {
+            capacity: 50,
+            refill: Duration::from_millis(100),
+            max_violations: 20,
+        }
+    }
+}
+
+/// The outcome of checking a message against a connection's rate limit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum RateDecision {
+    /// The message can be processed
+    Allowed,
+    /// The message needs to be rejected
+    Limited,
+    /// The message needs to be rejected and the connection closed
+    Exceeded,
+}
+
+/// Tracks how many messages a single connection can still send
+#[derive(Debug, Clone)]
+pub(crate) struct RateLimiter {
+    limit: RateLimit,
+    tokens: u32,
+    last_refill: Instant,
+    violations: u32,
+}
+
+impl RateLimiter {
+    pub(crate) fn new(limit: RateLimit) -> Self {
+        Self::new_at(limit, Instant::now())
+    }
+
+    fn new_at(limit: RateLimit, now: Instant) -> Self {
+        Self {
+            limit,
+            tokens: limit.capacity,
+            last_refill: now,
+            violations: 0,
+        }
+    }
+
+    /// Checks if the connection can send another message and records that it was sent
+    pub(crate) fn check(&mut self) -> RateDecision {
+        self.check_at(Instant::now())
+    }
+
+    fn check_at(&mut self, now: Instant) -> RateDecision {
+        self.refill(now);
+        if self.tokens > 0 {
+            self.tokens -= 1;
+            return RateDecision::Allowed;
+        }
+        self.violations += 1;
+        if self.violations >= self.limit.max_violations {
+            RateDecision::Exceeded
+        } else {
+            RateDecision::Limited
+        }
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let refill = self.limit.refill.as_nanos().max(1);
+        let elapsed = now.saturating_duration_since(self.last_refill).as_nanos();
+        let gained = elapsed / refill;
+        if gained == 0 {
+            return;
+        }
+        let gained = u32::try_from(gained).unwrap_or(u32::MAX);
+        self.tokens = self.tokens.saturating_add(gained).min(self.limit.capacity);
+        self.last_refill += self.limit.refill * gained;
+        // A connection that has let its bucket fill back up has stopped flooding the gathering
+        if self.tokens == self.limit.capacity {
+            self.violations = 0;
+            self.last_refill = now;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use instant::{Duration, Instant};
+
+    use super::{RateDecision, RateLimit, RateLimiter};
+
+    fn limit() -> RateLimit {
+        RateLimit {
+            capacity: 3,
+            refill: Duration::from_secs(1),
+            max_violations: 2,
+        }
+    }
+
+    #[test]
+    fn burst_then_limited() {
+        let now = Instant::now();
+        let mut limiter = RateLimiter::new_at(limit(), now);
+        for _ in 0..3 {
+            assert_eq!(limiter.check_at(now), RateDecision::Allowed);
+        }
+        assert_eq!(limiter.check_at(now), RateDecision::Limited);
+        assert_eq!(limiter.check_at(now), RateDecision::Exceeded);
+    }
+
+    #[test]
+    fn bucket_refills() {
+        let now = Instant::now();
+        let mut limiter = RateLimiter::new_at(limit(), now);
+        for _ in 0..3 {
+            assert_eq!(limiter.check_at(now), RateDecision::Allowed);
+        }
+        assert_eq!(limiter.check_at(now), RateDecision::Limited);
+        let later = now + Duration::from_secs(1);
+        assert_eq!(limiter.check_at(later), RateDecision::Allowed);
+        assert_eq!(limiter.check_at(later), RateDecision::Exceeded);
+    }
+
+    #[test]
+    fn full_bucket_forgives_violations() {
+        let now = Instant::now();
+        let mut limiter = RateLimiter::new_at(limit(), now);
+        for _ in 0..3 {
+            assert_eq!(limiter.check_at(now), RateDecision::Allowed);
+        }
+        assert_eq!(limiter.check_at(now), RateDecision::Limited);
+        let later = now + Duration::from_secs(10);
+        for _ in 0..3 {
+            assert_eq!(limiter.check_at(later), RateDecision::Allowed);
+        }
+        assert_eq!(limiter.check_at(later), RateDecision::Limited);
+    }
+}
@@ -1,38 +1,152 @@
-use std::collections::HashMap;
+use std::{
+    collections::HashMap,
+    mem,
+    sync::{
+        atomic::{AtomicUsize, Ordering::Relaxed},
+        Arc,
+    },
+};
 
 use async_trait::async_trait;
-use axum::extract::ws::WebSocket;
+use axum::extract::ws::{Message, WebSocket};
+use chrono::{DateTime, Utc};
 use derive_more::From;
-use futures::StreamExt;
-use squire_lib::{identifiers::SquireAccountId, tournament::TournamentId};
-use tokio::sync::{mpsc::Sender, oneshot::Sender as OneshotSender};
+use futures::{SinkExt, StreamExt};
+use instant::{Duration, Instant};
+use squire_lib::{
+    error::TournamentError,
+    identifiers::{AdminId, PlayerId, RoundId, SquireAccountId},
+    operations::{JudgeOp, TournOp},
+    settings::Visibility,
+    tournament::{TournRole, TournamentId},
+};
+use tokio::sync::{
+    mpsc::{error::TrySendError, Sender},
+    oneshot::Sender as OneshotSender,
+};
+use tracing::{field, Instrument};
 use uuid::Uuid;
 
 use crate::{
     actor::{ActorState, Scheduler},
-    api::AuthUser,
+    api::{AuthUser, LifecycleEvent},
     sync::{
-        processor::{SyncCompletion, SyncDecision},
-        ClientBound, ClientBoundMessage, ClientOpLink, ForwardingRetry, OpSync, ServerBound,
-        ServerBoundMessage, ServerForwardingManager, ServerOpLink, ServerSyncManager, SyncError,
-        SyncForwardResp, TournamentManager,
+        processor::SyncCompletion, ClientBound, ClientBoundMessage, ClientOpLink, ClockSync,
+        Compression, ForwardingRetry, FullOp, OpId, OpLog, OpSlice, OpSync, Presence,
+        RecentMessages, Rejection, Rollback, RollbackError, ServerBound, ServerBoundMessage,
+        ServerForwardingManager, ServerOpLink, ServerSyncManager, SyncError, SyncForwardResp,
+        TournamentManager,
     },
 };
 
 mod hall;
+mod limiter;
 mod onlooker;
 pub use hall::*;
+pub use limiter::RateLimit;
+use limiter::{RateDecision, RateLimiter};
 pub use onlooker::*;
 
 use super::session::SessionWatcher;
 
+/// Once a tournament's log holds more than this many operations, the gathering compacts it
+const COMPACTION_THRESHOLD: usize = 2_000;
+
+/// The number of recent operations that are kept when the log is compacted. Clients whose last
+/// sync is within these operations can sync as usual instead of catching up to the snapshot. This
+/// also keeps syncs that are in progress (e.g. waiting on a conflict decision) anchored in the log.
+const RECENT_OPS: usize = 500;
+
+/// Once this many operations have been appended to a tournament's write-ahead log, the gathering
+/// persists a full copy of the tournament instead, which lets the log be cleared
+const SNAPSHOT_INTERVAL: usize = 250;
+
+/// How often a gathering pings its onlookers
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(15);
+
+/// The number of heartbeats an onlooker can miss before their connection is pruned
+const MISSED_HEARTBEATS: u32 = 3;
+
+/// How long a gathering waits after someone connects or disconnects before broadcasting its
+/// presence. Changes within this window are sent as a single update.
+const PRESENCE_DELAY: Duration = Duration::from_secs(1);
+
+/// How often a gathering broadcasts its clock and the deadlines of the tournament's active rounds
+const CLOCK_SYNC_INTERVAL: Duration = Duration::from_secs(30);
+
+/// How often a gathering checks for rounds whose results are due to be certified automatically
+const AUTO_CERTIFY_INTERVAL: Duration = Duration::from_secs(30);
+
+/// How often a gathering checks for players that have not checked in at their tables
+const TARDINESS_INTERVAL: Duration = Duration::from_secs(30);
+
+/// How far the time that a registration or deck submission was created at can be from the time
+/// that the gathering receives it. Registration windows are checked using the operation's time, so
+/// this keeps users from backdating operations into a window that has closed.
+const REG_OP_TOLERANCE: Duration = Duration::from_secs(120);
+
+/// How long a gathering waits before retrying a persist that did not fit in the hall's queue
+const PERSIST_RETRY_INTERVAL: Duration = Duration::from_secs(5);
+
 /// A message sent to a `Gathering` that subscribes a new `Onlooker`.
 #[derive(Debug)]
 pub enum GatheringMessage {
     GetTournament(OneshotSender<Box<TournamentManager>>),
-    NewConnection(SessionWatcher, WebSocket),
+    NewConnection(SessionWatcher, Compression, WebSocket),
     WebsocketMessage(CrierMessage),
     ResendMessage(Box<(AuthUser, ClientBoundMessage)>),
+    /// Pings all onlookers and prunes the ones that have gone silent
+    Heartbeat,
+    /// Tells all onlookers who is currently connected
+    BroadcastPresence,
+    /// Tells all onlookers the current time and when the active rounds end
+    ClockSync,
+    /// Certifies the results of the rounds that are due to be certified automatically
+    AutoCertify,
+    /// Tells all onlookers which players have not checked in at their tables
+    TardinessCheck,
+    /// Retries sending the persists that did not fit in the hall's queue
+    RetryPersist,
+    /// Applies an operation on behalf of the server and forwards it to all onlookers
+    ApplyOp(TournOp, OneshotSender<Result<(), TournamentError>>),
+    /// Closes every onlooker's connection and returns the final copy of the tournament. Once shut
+    /// down, the gathering turns away all new connections.
+    Shutdown(OneshotSender<Box<TournamentManager>>),
+}
+
+fn schedule_heartbeat(scheduler: &mut Scheduler<Gathering>) {
+    scheduler.schedule(
+        Instant::now() + HEARTBEAT_INTERVAL,
+        GatheringMessage::Heartbeat,
+    );
+}
+
+fn schedule_clock_sync(scheduler: &mut Scheduler<Gathering>) {
+    scheduler.schedule(
+        Instant::now() + CLOCK_SYNC_INTERVAL,
+        GatheringMessage::ClockSync,
+    );
+}
+
+fn schedule_auto_certify(scheduler: &mut Scheduler<Gathering>) {
+    scheduler.schedule(
+        Instant::now() + AUTO_CERTIFY_INTERVAL,
+        GatheringMessage::AutoCertify,
+    );
+}
+
+fn schedule_tardiness_check(scheduler: &mut Scheduler<Gathering>) {
+    scheduler.schedule(
+        Instant::now() + TARDINESS_INTERVAL,
+        GatheringMessage::TardinessCheck,
+    );
+}
+
+fn schedule_persist_retry(scheduler: &mut Scheduler<Gathering>) {
+    scheduler.schedule(
+        Instant::now() + PERSIST_RETRY_INTERVAL,
+        GatheringMessage::RetryPersist,
+    );
 }
 
 impl From<((), OneshotSender<Box<TournamentManager>>)> for GatheringMessage {
@@ -41,15 +155,102 @@ impl From<((), OneshotSender<Box<TournamentManager>>)> for GatheringMessage {
     }
 }
 
+impl From<(TournOp, OneshotSender<Result<(), TournamentError>>)> for GatheringMessage {
+    fn from((op, send): (TournOp, OneshotSender<Result<(), TournamentError>>)) -> Self {
+        Self::ApplyOp(op, send)
+    }
+}
+
 /// A message that communicates to the `GatheringHall` that it needs to backup tournament data.
 /// How this data is backed up depends on the server implementation.
 #[derive(Debug, Clone, PartialEq, Eq)]
-struct PersistReadyMessage(TournamentId);
+enum PersistReadyMessage {
+    /// The tournament needs a full copy of it persisted
+    Tournament(TournamentId),
+    /// These operations have been added to the tournament and need to be appended to its log. The
+    /// version is that of the last full copy that was persisted, which the operations build on.
+    Ops(TournamentId, u64, Vec<FullOp>),
+    /// The tournament's lifecycle has changed
+    Events(Vec<LifecycleEvent>),
+}
 
 #[derive(Debug, From)]
 pub enum PersistMessage {
     Get(TournamentId, OneshotSender<Option<Box<TournamentManager>>>),
     Persist(Box<TournamentManager>),
+    RecordEvents(Vec<LifecycleEvent>),
+    /// Appends newly synced operations to the tournament's write-ahead log. The persisted copy of
+    /// the tournament plus these operations make up the latest copy of the tournament. The
+    /// operations build on the persisted copy with the given version, so they should not be
+    /// appended if a newer copy has since been persisted.
+    AppendOps(TournamentId, u64, Vec<FullOp>),
+    /// Replies once everything sent to the persister before this message has been persisted
+    Flush(OneshotSender<()>),
+}
+
+/// The load counters that are shared between the `GatheringHall` and its `Gathering`s
+#[derive(Debug, Default)]
+struct HallCounters {
+    sockets: AtomicUsize,
+    rejected_not_found: AtomicUsize,
+    rejected_gatherings: AtomicUsize,
+    rejected_full: AtomicUsize,
+    rejected_sockets: AtomicUsize,
+    rejected_shutdown: AtomicUsize,
+    dropped_persists: AtomicUsize,
+}
+
+impl HallCounters {
+    fn sockets(&self) -> usize {
+        self.sockets.load(Relaxed)
+    }
+
+    fn add_socket(&self) {
+        _ = self.sockets.fetch_add(1, Relaxed);
+    }
+
+    fn remove_socket(&self) {
+        _ = self.sockets.fetch_sub(1, Relaxed);
+    }
+
+    fn drop_persist(&self) {
+        _ = self.dropped_persists.fetch_add(1, Relaxed);
+    }
+
+    fn snapshot(&self, gatherings: usize) -> HallMetrics {
+        HallMetrics {
+            gatherings,
+            sockets: self.sockets(),
+            rejected_not_found: self.rejected_not_found.load(Relaxed),
+            rejected_gatherings: self.rejected_gatherings.load(Relaxed),
+            rejected_full: self.rejected_full.load(Relaxed),
+            rejected_sockets: self.rejected_sockets.load(Relaxed),
+            rejected_shutdown: self.rejected_shutdown.load(Relaxed),
+            dropped_persists: self.dropped_persists.load(Relaxed),
+        }
+    }
+
+    /// Records the rejection and, in a separate task, tells the client why it was rejected before
+    /// closing the connection
+    fn reject(&self, mut ws: WebSocket, reason: Rejection) {
+        let counter = match reason {
+            Rejection::TournamentNotFound => &self.rejected_not_found,
+            Rejection::TooManyGatherings => &self.rejected_gatherings,
+            Rejection::GatheringFull => &self.rejected_full,
+            Rejection::TooManyConnections => &self.rejected_sockets,
+            Rejection::ShuttingDown => &self.rejected_shutdown,
+        };
+        _ = counter.fetch_add(1, Relaxed);
+        tracing::warn!("Turning away connection: {reason:?}");
+        let msg = ClientBoundMessage::new(reason.into());
+        _ = tokio::spawn(async move {
+            // Rejections are small enough that they are never compressed
+            _ = ws
+                .send(Message::Binary(msg.to_bytes(Compression::None)))
+                .await;
+            _ = ws.close().await;
+        });
+    }
 }
 
 /// This structure contains all users currently subscribed to a tournament and can be thought of as
@@ -66,9 +267,40 @@ pub enum PersistMessage {
 pub struct Gathering {
     tourn: TournamentManager,
     onlookers: HashMap<AuthUser, Onlooker>,
+    max_onlookers: usize,
+    sync_rate: RateLimit,
+    limiters: HashMap<AuthUser, RateLimiter>,
+    /// The messages that each user has recently sent, which are used to spot retransmissions.
+    /// These are kept after a user disconnects since a flaky connection often resends messages
+    /// once it is replaced.
+    seen: HashMap<AuthUser, RecentMessages>,
+    /// Whether someone has connected or disconnected since the last presence broadcast
+    presence_changed: bool,
+    presence_scheduled: bool,
+    /// The tardy players that were last broadcast
+    tardy: Vec<(RoundId, PlayerId)>,
+    /// Whether the gathering has been shut down
+    closed: bool,
+    counters: Arc<HallCounters>,
     persist: Sender<PersistReadyMessage>,
+    /// The last operation that was sent off to be persisted
+    persisted: Option<OpId>,
+    /// The number of operations that have been appended to the write-ahead log since the last
+    /// full copy was persisted
+    appended: usize,
+    /// Whether the latest copy of the tournament could not be sent off to be persisted. Until it
+    /// is, the gathering only sends full copies, which supersede any operations it missed.
+    unsaved: bool,
+    /// The lifecycle events that could not be sent off to be recorded
+    unsent_events: Vec<LifecycleEvent>,
+    /// Whether a retry of the persists that did not fit in the hall's queue is scheduled, so that
+    /// only one is scheduled at a time
+    persist_retry_scheduled: bool,
     syncs: ServerSyncManager,
     forwarding: ServerForwardingManager,
+    /// The tournament's visibility and number of revoked invites when the onlookers' access was
+    /// last checked
+    access: (Visibility, usize),
 }
 
 // Send forwarding message
@@ -83,24 +315,53 @@ pub struct Gathering {
 impl ActorState for Gathering {
     type Message = GatheringMessage;
 
+    async fn start_up(&mut self, scheduler: &mut Scheduler<Self>) {
+        schedule_heartbeat(scheduler);
+        schedule_clock_sync(scheduler);
+        schedule_auto_certify(scheduler);
+        schedule_tardiness_check(scheduler);
+    }
+
     async fn process(&mut self, scheduler: &mut Scheduler<Self>, msg: Self::Message) {
         match msg {
             GatheringMessage::GetTournament(send) => {
                 send.send(Box::new(self.tourn.clone())).unwrap()
             }
-            GatheringMessage::NewConnection(session, ws) => {
-                let (sink, stream) = ws.split();
-                let onlooker = Onlooker::new(sink);
+            GatheringMessage::NewConnection(session, compression, ws) => {
                 // If we get a session watcher that is not valid, we ignore it.
-                if let Some(user) = session.auth_user() {
-                    match self.onlookers.get_mut(&user) {
-                        Some(ol) => *ol = onlooker,
-                        None => {
-                            _ = self.onlookers.insert(user.clone(), onlooker);
-                        }
-                    }
-                    scheduler.add_stream(Crier::new(stream, user.clone(), session));
+                let Some(user) = session.auth_user() else {
+                    return;
+                };
+                if self.closed {
+                    self.counters.reject(ws, Rejection::ShuttingDown);
+                    return;
+                }
+                // Users that reconnect replace their old connection, so they are never turned away
+                let is_new = !self.onlookers.contains_key(&user);
+                if is_new && self.onlookers.len() >= self.max_onlookers {
+                    self.counters.reject(ws, Rejection::GatheringFull);
+                    return;
                 }
+                tracing::debug!(
+                    tourn = %self.tourn.id,
+                    account = user.account().map(field::display),
+                    "New connection",
+                );
+                let (sink, stream) = ws.split();
+                if is_new {
+                    self.counters.add_socket();
+                    self.presence_changed = true;
+                }
+                _ = self
+                    .onlookers
+                    .insert(user.clone(), Onlooker::new(sink, compression));
+                // Users that replace their connection keep their rate limit
+                let sync_rate = self.sync_rate;
+                _ = self
+                    .limiters
+                    .entry(user.clone())
+                    .or_insert_with(|| RateLimiter::new(sync_rate));
+                scheduler.add_stream(Crier::new(stream, user, session));
             }
             GatheringMessage::WebsocketMessage(msg) => {
                 self.process_websocket_message(scheduler, msg).await
@@ -108,35 +369,215 @@ impl ActorState for Gathering {
             GatheringMessage::ResendMessage(retry) => match self.onlookers.get_mut(&retry.0) {
                 Some(onlooker) => {
                     let (user, msg) = *retry;
-                    if !self.forwarding.is_terminated(&msg.id) {
+                    if !self.forwarding.is_terminated(msg.id, user.clone()) {
                         let _ = onlooker.send_msg(&msg).await;
                         let fut = ForwardingRetry::new(user, msg);
                         scheduler.add_task(fut);
                     }
                 }
                 None => {
-                    self.forwarding.terminate_chain(&retry.1.id);
+                    let (user, msg) = *retry;
+                    self.forwarding.terminate_chain(msg.id, user);
                 }
             },
+            GatheringMessage::Heartbeat => {
+                self.heartbeat().await;
+                schedule_heartbeat(scheduler);
+            }
+            GatheringMessage::BroadcastPresence => {
+                self.presence_scheduled = false;
+                self.broadcast_presence().await;
+            }
+            GatheringMessage::ClockSync => {
+                self.broadcast_clock().await;
+                schedule_clock_sync(scheduler);
+            }
+            GatheringMessage::AutoCertify => {
+                self.auto_certify(scheduler).await;
+                schedule_auto_certify(scheduler);
+            }
+            GatheringMessage::TardinessCheck => {
+                self.broadcast_tardiness().await;
+                schedule_tardiness_check(scheduler);
+            }
+            GatheringMessage::RetryPersist => {
+                self.persist_retry_scheduled = false;
+                self.retry_persist();
+            }
+            GatheringMessage::ApplyOp(op, send) => {
+                let digest = self.apply_server_op(scheduler, op).await;
+                let _ = send.send(digest);
+            }
+            GatheringMessage::Shutdown(send) => {
+                self.shutdown().await;
+                self.tourn.bump_version();
+                let _ = send.send(Box::new(self.tourn.clone()));
+            }
+        }
+        self.check_access().await;
+        // Presence is computed when it is broadcast, so one broadcast covers all of the changes
+        // made while it was scheduled
+        if mem::take(&mut self.presence_changed) && !self.presence_scheduled {
+            self.presence_scheduled = true;
+            scheduler.schedule(
+                Instant::now() + PRESENCE_DELAY,
+                GatheringMessage::BroadcastPresence,
+            );
+        }
+        // Persists that did not fit in the queue are retried until they do
+        let backlogged = self.unsaved || !self.unsent_events.is_empty();
+        if backlogged && !self.closed && !self.persist_retry_scheduled {
+            self.persist_retry_scheduled = true;
+            schedule_persist_retry(scheduler);
         }
     }
 }
 
 impl Gathering {
-    fn new(tourn: TournamentManager, persist: Sender<PersistReadyMessage>) -> Self {
-        let count = tourn.tourn().get_player_count();
+    fn new(
+        tourn: TournamentManager,
+        persist: Sender<PersistReadyMessage>,
+        max_onlookers: usize,
+        sync_rate: RateLimit,
+        counters: Arc<HallCounters>,
+    ) -> Self {
+        let count = tourn.tourn().get_player_count().min(max_onlookers);
+        let persisted = tourn.op_log().last_id();
+        let access = access(&tourn);
         Self {
             tourn,
             onlookers: HashMap::with_capacity(count),
+            max_onlookers,
+            sync_rate,
+            limiters: HashMap::with_capacity(count),
+            seen: HashMap::with_capacity(count),
+            presence_changed: false,
+            presence_scheduled: false,
+            tardy: Vec::new(),
+            closed: false,
+            counters,
             persist,
+            persisted,
+            appended: 0,
+            unsaved: false,
+            unsent_events: Vec::new(),
+            persist_retry_scheduled: false,
             syncs: ServerSyncManager::default(),
             forwarding: ServerForwardingManager::new(),
+            access,
         }
     }
 
+    /// Asks for a full copy of the tournament to be persisted
     fn send_persist_message(&mut self) {
-        // If the persistance queue is full, we continue on
-        let _persist_fut = self.persist.send(PersistReadyMessage(self.tourn.id));
+        // The copy that is persisted needs to replace the copy that was persisted last
+        self.tourn.bump_version();
+        // If the persistance queue is full, we continue on and a retry is scheduled. The hall
+        // fetches the tournament when it persists it, so one message covers every later change.
+        match self
+            .persist
+            .try_send(PersistReadyMessage::Tournament(self.tourn.id))
+        {
+            Ok(()) => {
+                self.persisted = self.tourn.op_log().last_id();
+                self.appended = 0;
+                self.unsaved = false;
+            }
+            Err(err) => self.unsaved = self.drop_persist(&err),
+        }
+    }
+
+    /// Sends the operations that have been added since the last persist off to be appended to the
+    /// tournament's write-ahead log. A full copy is persisted instead if the last persisted
+    /// operation is no longer in the log or if enough operations have been appended.
+    fn send_new_ops(&mut self) {
+        if self.unsaved {
+            return self.send_persist_message();
+        }
+        let Some(ops) = self.tourn.ops_since(self.persisted) else {
+            return self.send_persist_message();
+        };
+        if ops.is_empty() {
+            return;
+        }
+        if self.appended + ops.len() >= SNAPSHOT_INTERVAL {
+            return self.send_persist_message();
+        }
+        let count = ops.len();
+        // If the persistance queue is full, a full copy is persisted once there is room
+        match self.persist.try_send(PersistReadyMessage::Ops(
+            self.tourn.id,
+            self.tourn.version(),
+            ops,
+        )) {
+            Ok(()) => {
+                self.persisted = self.tourn.op_log().last_id();
+                self.appended += count;
+            }
+            Err(err) => self.unsaved = self.drop_persist(&err),
+        }
+    }
+
+    /// Records that a persist could not be sent to the hall. Returns whether it should be retried,
+    /// which it only is if the hall's queue was full. Once the hall has stopped, nothing that is
+    /// sent to it will be persisted.
+    fn drop_persist<T>(&self, err: &TrySendError<T>) -> bool {
+        match err {
+            TrySendError::Full(_) => {
+                self.counters.drop_persist();
+                tracing::warn!(tourn = %self.tourn.id, "Persistance queue is full, retrying later");
+                true
+            }
+            TrySendError::Closed(_) => {
+                tracing::error!(tourn = %self.tourn.id, "The hall stopped taking persists");
+                false
+            }
+        }
+    }
+
+    /// Sends off the lifecycle events that have not been recorded yet
+    fn send_events(&mut self) {
+        if self.unsent_events.is_empty() {
+            return;
+        }
+        let events = mem::take(&mut self.unsent_events);
+        if let Err(err) = self.persist.try_send(PersistReadyMessage::Events(events)) {
+            if !self.drop_persist(&err) {
+                return;
+            }
+            let PersistReadyMessage::Events(events) = err.into_inner() else {
+                unreachable!("The message that failed to send is the one that was sent");
+            };
+            self.unsent_events = events;
+        }
+    }
+
+    /// Retries the persists that did not fit in the hall's queue. Only the latest copy of the
+    /// tournament is sent.
+    fn retry_persist(&mut self) {
+        self.send_events();
+        if self.unsaved {
+            self.send_persist_message();
+        }
+    }
+
+    /// Compacts the tournament's log once it grows passed `COMPACTION_THRESHOLD` operations
+    fn compact_log(&mut self) {
+        if self.tourn.op_log().len() > COMPACTION_THRESHOLD {
+            let _ = self.tourn.compact(RECENT_OPS);
+        }
+    }
+
+    /// Finds the operations in a completed sync that changed the tournament's lifecycle and sends
+    /// them off to be recorded
+    fn send_lifecycle_events(&mut self, comp: &SyncCompletion) {
+        let (_, org) = self.tourn.seed_and_creator();
+        let id = self.tourn.id;
+        self.unsent_events.extend(
+            comp.iter()
+                .filter_map(|op| LifecycleEvent::from_op(org.id, id, op)),
+        );
+        self.send_events();
     }
 
     async fn process_websocket_message(
@@ -144,6 +585,9 @@ impl Gathering {
         scheduler: &mut Scheduler<Self>,
         msg: CrierMessage,
     ) {
+        if let Some(onlooker) = self.onlookers.get_mut(msg.user()) {
+            onlooker.mark_seen();
+        }
         match msg {
             CrierMessage::NoAuthMessage(user, bytes) => {
                 self.process_unauth_message(user, bytes).await
@@ -151,51 +595,366 @@ impl Gathering {
             CrierMessage::AuthMessage(user, bytes) => {
                 self.process_incoming_message(scheduler, user, bytes).await
             }
-            CrierMessage::ClosingFrame(user) => drop(self.onlookers.remove(&user)),
+            CrierMessage::Heartbeat(_) => {}
+            CrierMessage::ClosingFrame(user) => {
+                _ = self.remove_onlooker(&user);
+            }
         }
     }
 
     async fn process_unauth_message(&mut self, user: AuthUser, bytes: Vec<u8>) {
-        let Ok(ServerBoundMessage { id, .. }) = postcard::from_bytes(&bytes) else {
+        let Some(ServerBoundMessage { id, .. }) = ServerBoundMessage::from_bytes(&bytes) else {
             // TODO: Send a 'failed to deserialize message' to sender?
             return;
         };
         self.send_reply(user, id, SyncError::Unauthorized).await;
     }
 
-    // TODO: Return a "real" value
     async fn process_incoming_message(
         &mut self,
         scheduler: &mut Scheduler<Self>,
         user: AuthUser,
         bytes: Vec<u8>,
     ) {
-        let Ok(ServerBoundMessage { id, body }) = postcard::from_bytes(&bytes) else {
+        let account = user.account().map(field::display);
+        let Some(ServerBoundMessage { id, body }) = ServerBoundMessage::from_bytes(&bytes) else {
             // TODO: Send a 'failed to deserialize message' to sender?
+            tracing::warn!(tourn = %self.tourn.id, account, "Could not decode message");
             return;
         };
+        let span = tracing::info_span!("message", tourn = %self.tourn.id, account, msg = %id);
+        self.handle_message(scheduler, user, id, body)
+            .instrument(span)
+            .await
+    }
+
+    // TODO: Return a "real" value
+    async fn handle_message(
+        &mut self,
+        scheduler: &mut Scheduler<Self>,
+        user: AuthUser,
+        id: Uuid,
+        body: ServerBound,
+    ) {
+        // Sync links and rollbacks change the tournament, so they must only be handled once
+        let fresh = match &body {
+            ServerBound::SyncChain(_) | ServerBound::Rollback(_) => {
+                self.mark_seen(&user, id, &body)
+            }
+            _ => true,
+        };
         match body {
             ServerBound::Fetch => {
-                self.send_message(user, self.tourn.clone()).await;
+                let tourn = self.visible_tourn(&user);
+                self.send_message(user, tourn).await;
             }
+            ServerBound::FetchSince(op_id) => match self
+                .tourn
+                .op_log()
+                .get_slice(op_id)
+                .filter(|ops| is_official(&self.tourn, &user) || !is_private(ops))
+            {
+                Some(ops) => {
+                    let (seed, owner) = self.tourn.seed_and_creator();
+                    let sync = OpSync { owner, seed, ops };
+                    self.send_reply(user, id, ClientBound::FetchSinceResp(sync))
+                        .await;
+                }
+                // The operation was compacted away (or never synced), or the missed operations
+                // carry decklists or accounts that the user can't see, so the client needs a full
+                // copy
+                None => {
+                    tracing::debug!("Sending a full copy in place of the operations since {op_id}");
+                    let tourn = self.visible_tourn(&user);
+                    self.send_reply(user, id, tourn).await
+                }
+            },
             ServerBound::SyncChain(sync) => {
+                // Retransmissions are answered, but don't count towards the rate limit
+                let rate = if fresh {
+                    self.check_rate(&user)
+                } else {
+                    tracing::debug!("Answering a resent sync link");
+                    RateDecision::Allowed
+                };
+                match rate {
+                    RateDecision::Allowed => {}
+                    RateDecision::Limited => {
+                        tracing::debug!("Sync was rate limited");
+                        return self.send_reply(user, id, SyncError::RateLimited).await;
+                    }
+                    RateDecision::Exceeded => {
+                        tracing::warn!("Closing connection for exceeding the sync rate limit");
+                        self.send_reply(user.clone(), id, SyncError::RateLimited)
+                            .await;
+                        return self.close_onlooker(&user).await;
+                    }
+                }
                 match &user {
                     // If the user is a guest, we reject the message since guests do not have the
                     // credentials to update tournaments.
                     AuthUser::Guest(_) => self.send_reply(user, id, SyncError::Unauthorized).await,
                     AuthUser::User(u_id) => {
-                        // TODO: Check that the user is allowed to send the given update
+                        // The user's permissions are checked in `validate_sync_request`
                         let link = self.handle_sync_request(id, *u_id, sync);
-                        // If completed, send forwarding requests
-                        if let ServerOpLink::Completed(comp) = &link {
-                            self.send_persist_message();
-                            self.send_forwarding(scheduler, &user, comp).await;
+                        match &link {
+                            ServerOpLink::Error(err) => tracing::warn!("Sync failed: {err:?}"),
+                            ServerOpLink::Conflict(_) => tracing::debug!("Sync has a conflict"),
+                            ServerOpLink::CatchUp(_) => tracing::debug!("Client needs to catch up"),
+                            ServerOpLink::Completed(_) => tracing::debug!("Sync completed"),
+                            ServerOpLink::TerminatedSeen { .. } => {}
+                        }
+                        // If completed, send forwarding requests. Resent links are answered with
+                        // the stored response, which was already forwarded.
+                        if let (true, ServerOpLink::Completed(comp)) = (fresh, &link) {
+                            self.send_new_ops();
+                            self.compact_log();
+                            self.send_lifecycle_events(comp);
+                            self.send_forwarding(scheduler, Some(&user), comp).await;
                         }
                         self.send_reply(user, id, link).await;
                     }
                 }
             }
-            ServerBound::ForwardResp(resp) => self.handle_forwarding_resp(&id, resp),
+            ServerBound::ForwardResp(resp) => self.handle_forwarding_resp(id, user, resp),
+            ServerBound::Presence => {
+                let update = ClientBound::PresenceUpdate(self.tourn.id, self.presence());
+                self.send_reply(user, id, update).await;
+            }
+            ServerBound::ClockSync => {
+                let sync =
+                    ClientBound::ClockSync(self.tourn.id, ClockSync::new(self.tourn.tourn()));
+                self.send_reply(user, id, sync).await;
+            }
+            ServerBound::Rollback(_) if !fresh => {
+                tracing::debug!("Ignoring a resent rollback");
+            }
+            ServerBound::Rollback(rollback) => {
+                let checkpoint = rollback.checkpoint.clone();
+                let resp = self.handle_rollback(&user, rollback).await;
+                match &resp {
+                    Ok(()) => tracing::info!("Rolled back to checkpoint {checkpoint:?}"),
+                    Err(err) => tracing::warn!("Could not roll back: {err:?}"),
+                }
+                self.send_reply(user, id, resp).await;
+            }
+        }
+    }
+
+    /// Rolls the tournament back to a checkpoint and sends the result to every onlooker. Syncs
+    /// and forwarded syncs that are in flight might hold operations that were removed, so they are
+    /// all dropped. Clients rebase their unsynced operations when they catch up to the rollback.
+    async fn handle_rollback(
+        &mut self,
+        user: &AuthUser,
+        rollback: Rollback,
+    ) -> Result<(), RollbackError> {
+        let AuthUser::User(u_id) = user else {
+            return Err(RollbackError::Unauthorized);
+        };
+        let TournRole::Admin(_) = self.tourn.tourn().user_role(u_id.0) else {
+            return Err(RollbackError::Unauthorized);
+        };
+        let _ = self.tourn.rollback(&rollback.checkpoint)?;
+        self.syncs = ServerSyncManager::default();
+        self.forwarding = ServerForwardingManager::new();
+        self.send_persist_message();
+        let update = ClientBound::RolledBack(self.tourn.id, Box::new(self.tourn.clone()));
+        let msg = ClientBoundMessage::new(update);
+        for onlooker in self.onlookers.values_mut() {
+            let _ = onlooker.send_msg(&msg).await;
+        }
+        Ok(())
+    }
+
+    /// Applies an operation that did not come from an onlooker's sync and forwards it to every
+    /// onlooker
+    async fn apply_server_op(
+        &mut self,
+        scheduler: &mut Scheduler<Self>,
+        op: TournOp,
+    ) -> Result<(), TournamentError> {
+        let comp = self.tourn.apply_server_op(op)?;
+        self.send_new_ops();
+        self.compact_log();
+        self.send_lifecycle_events(&comp);
+        self.send_forwarding(scheduler, None, &comp).await;
+        Ok(())
+    }
+
+    /// Certifies the results of the rounds whose auto-certify wait has passed. This happens here,
+    /// rather than waiting on an official, so that remote events don't stall on players that stop
+    /// responding.
+    async fn auto_certify(&mut self, scheduler: &mut Scheduler<Self>) {
+        let due = self.tourn.tourn().auto_certify_due(Utc::now());
+        if due.is_empty() {
+            return;
+        }
+        let Some(admin) = self.certifier() else {
+            tracing::warn!(tourn = %self.tourn.id, "No admin to auto-certify rounds as");
+            return;
+        };
+        for r_id in due {
+            let op = TournOp::JudgeOp(admin.into(), JudgeOp::AutoCertifyRound(r_id));
+            if let Err(err) = self.apply_server_op(scheduler, op).await {
+                tracing::warn!(
+                    tourn = %self.tourn.id,
+                    round = %r_id,
+                    "Could not auto-certify round: {err}",
+                );
+            }
+        }
+    }
+
+    /// Returns the admin that rounds are certified automatically as. This is the tournament's
+    /// creator if they are still an admin.
+    fn certifier(&self) -> Option<AdminId> {
+        let (_, owner) = self.tourn.seed_and_creator();
+        let owner: AdminId = owner.id.0.into();
+        let admins = &self.tourn.tourn().admins;
+        admins
+            .contains_key(&owner)
+            .then_some(owner)
+            .or_else(|| admins.keys().min().copied())
+    }
+
+    /// Records that the user sent a sync message and checks it against their rate limit
+    fn check_rate(&mut self, user: &AuthUser) -> RateDecision {
+        let sync_rate = self.sync_rate;
+        self.limiters
+            .entry(user.clone())
+            .or_insert_with(|| RateLimiter::new(sync_rate))
+            .check()
+    }
+
+    /// Records that the user sent a message. Returns `false` if the message is a retransmission of
+    /// one that was already handled.
+    fn mark_seen(&mut self, user: &AuthUser, id: Uuid, body: &ServerBound) -> bool {
+        self.seen.entry(user.clone()).or_default().insert(id, body)
+    }
+
+    /// Removes the user from the gathering and stops retrying any messages sent to them
+    fn remove_onlooker(&mut self, user: &AuthUser) -> Option<Onlooker> {
+        _ = self.limiters.remove(user);
+        self.forwarding.terminate_user(user);
+        let onlooker = self.onlookers.remove(user)?;
+        self.counters.remove_socket();
+        self.presence_changed = true;
+        Some(onlooker)
+    }
+
+    /// Removes the user from the gathering and closes their websocket
+    async fn close_onlooker(&mut self, user: &AuthUser) {
+        if let Some(mut onlooker) = self.remove_onlooker(user) {
+            _ = onlooker.close().await;
+        }
+    }
+
+    /// Calculates if the tournament became private or had an invite revoked since the onlookers'
+    /// access was last checked, either of which can keep spectators from following it
+    fn access_narrowed(&mut self) -> bool {
+        let access = access(&self.tourn);
+        mem::replace(&mut self.access, access) != access && access.0.needs_invite()
+    }
+
+    /// Closes the websockets of the spectators once the tournament is private or an invite is
+    /// revoked. Only the handshake knows which invite a spectator joined with, so every spectator
+    /// is dropped, and their access is checked again when they reconnect.
+    async fn check_access(&mut self) {
+        if !self.access_narrowed() {
+            return;
+        }
+        let spectators: Vec<_> = self
+            .onlookers
+            .keys()
+            .filter(|user| !follows_by_role(&self.tourn, user))
+            .cloned()
+            .collect();
+        for user in spectators {
+            self.close_onlooker(&user).await;
+        }
+    }
+
+    /// Closes every onlooker's websocket and stops taking new connections
+    async fn shutdown(&mut self) {
+        self.closed = true;
+        let users: Vec<_> = self.onlookers.keys().cloned().collect();
+        for user in users {
+            self.close_onlooker(&user).await;
+        }
+        // No one is left to tell about the change
+        self.presence_changed = false;
+        tracing::debug!(tourn = %self.tourn.id, "Gathering shut down");
+    }
+
+    /// Returns the copy of the tournament that the user is allowed to see
+    fn visible_tourn(&self, user: &AuthUser) -> TournamentManager {
+        if is_official(&self.tourn, user) {
+            self.tourn.clone()
+        } else {
+            self.tourn.for_spectators()
+        }
+    }
+
+    /// Calculates who is currently connected to the tournament
+    fn presence(&self) -> Presence {
+        let mut digest = Presence::default();
+        for user in self.onlookers.keys() {
+            match user {
+                AuthUser::Guest(_) => digest.guests += 1,
+                AuthUser::User(id) => {
+                    let role = self.tourn.tourn().user_role(id.0).into();
+                    digest.users.push((*id, role));
+                }
+            }
+        }
+        digest
+    }
+
+    async fn broadcast_presence(&mut self) {
+        let update = ClientBound::PresenceUpdate(self.tourn.id, self.presence());
+        let msg = ClientBoundMessage::new(update);
+        for onlooker in self.onlookers.values_mut() {
+            let _ = onlooker.send_msg(&msg).await;
+        }
+    }
+
+    async fn broadcast_clock(&mut self) {
+        let sync = ClientBound::ClockSync(self.tourn.id, ClockSync::new(self.tourn.tourn()));
+        let msg = ClientBoundMessage::new(sync);
+        for onlooker in self.onlookers.values_mut() {
+            let _ = onlooker.send_msg(&msg).await;
+        }
+    }
+
+    /// Tells all onlookers which players have not checked in at their tables, so that a judge can
+    /// apply the tournament's tardiness policy. Nothing is sent while no one is, or was, tardy.
+    async fn broadcast_tardiness(&mut self) {
+        let tardy = self.tourn.tourn().tardy_players(Utc::now());
+        if tardy.is_empty() && self.tardy.is_empty() {
+            return;
+        }
+        self.tardy = tardy.clone();
+        let msg = ClientBoundMessage::new(ClientBound::TardyPlayers(self.tourn.id, tardy));
+        for onlooker in self.onlookers.values_mut() {
+            let _ = onlooker.send_msg(&msg).await;
+        }
+    }
+
+    /// Prunes the onlookers that have missed too many heartbeats and pings the rest
+    async fn heartbeat(&mut self) {
+        let cutoff = HEARTBEAT_INTERVAL * MISSED_HEARTBEATS;
+        let dead: Vec<_> = self
+            .onlookers
+            .iter()
+            .filter(|(_, onlooker)| onlooker.last_seen().elapsed() > cutoff)
+            .map(|(user, _)| user.clone())
+            .collect();
+        for user in dead {
+            self.close_onlooker(&user).await;
+        }
+        for onlooker in self.onlookers.values_mut() {
+            _ = onlooker.ping().await;
         }
     }
 
@@ -210,44 +969,23 @@ impl Gathering {
         if let Err(link) = self.syncs.validate_sync_message(&id, &link) {
             return link;
         }
-        match link.clone() {
+        match &link {
+            // Check to make sure that the user is allowed to send these operations
             ClientOpLink::Init(sync) => {
-                // Check to make sure that the user is allowed to send these operations
-                if let Err(err) = self.validate_sync_request(u_id, &sync) {
+                if let Err(err) = self.validate_sync_request(u_id, sync) {
                     return err.into();
                 }
-                // Process the init
-                let proc = match self.tourn.init_sync(sync) {
-                    Ok(proc) => proc,
-                    Err(err) => return ServerOpLink::Error(err),
-                };
-                let resp = self.tourn.process_sync(proc);
-                // Convert into a resp
-                self.syncs.add_sync_link(id, link, resp.clone());
-                // Return resp
-                resp
-            }
-            ClientOpLink::Decision(SyncDecision::Plucked(proc)) => {
-                // Continue to try to resolve
-                let resp = self.tourn.process_sync(proc);
-                // Get resp
-                self.syncs.add_sync_link(id, link, resp.clone());
-                // Return resp
-                resp
-            }
-            ClientOpLink::Decision(SyncDecision::Purged(comp)) => {
-                // Apply and get resp
-                if let Err(err) = self.tourn.handle_completion(comp.clone()) {
-                    return ServerOpLink::Error(err);
-                }
-                // Return resp
-                comp.into()
             }
+            ClientOpLink::Decision(_) => {}
             ClientOpLink::Terminated => {
                 let already_done = self.syncs.terminate_chain(&id);
-                ServerOpLink::TerminatedSeen { already_done }
+                return ServerOpLink::TerminatedSeen { already_done };
             }
         }
+        let resp = self.tourn.handle_sync_link(link.clone());
+        // Every response is stored so that resent links are answered without being reprocessed
+        self.syncs.add_sync_link(id, link, resp.clone());
+        resp
     }
 
     async fn send_message<C: Into<ClientBound>>(&mut self, user: AuthUser, msg: C) {
@@ -269,10 +1007,12 @@ impl Gathering {
         }
     }
 
+    /// Forwards the operations of a completed sync to every onlooker other than the user that sent
+    /// them
     async fn send_forwarding(
         &mut self,
         scheduler: &mut Scheduler<Self>,
-        user: &AuthUser,
+        user: Option<&AuthUser>,
         comp: &SyncCompletion,
     ) {
         let (seed, owner) = self.tourn.seed_and_creator();
@@ -282,11 +1022,19 @@ impl Gathering {
             ops: comp.clone().as_slice(),
         };
         let msg = ClientBoundMessage::new((self.tourn.id, sync.clone()).into());
-        for (id, onlooker) in self.onlookers.iter_mut().filter(|on| on.0 != user) {
+        // Users that aren't officials are sent a redacted full copy in place of operations that
+        // carry decklists or accounts
+        let hidden = is_private(&sync.ops)
+            .then(|| ClientBoundMessage::new(self.tourn.for_spectators().into()));
+        for (id, onlooker) in self.onlookers.iter_mut().filter(|on| Some(on.0) != user) {
+            if let Some(hidden) = hidden.as_ref().filter(|_| !is_official(&self.tourn, id)) {
+                let _ = onlooker.send_msg(hidden).await;
+                continue;
+            }
             self.forwarding
                 .add_msg(msg.id, id.clone(), self.tourn.id, sync.clone());
             let _ = onlooker.send_msg(&msg).await;
-            let fut = ForwardingRetry::new(user.clone(), msg.clone());
+            let fut = ForwardingRetry::new(id.clone(), msg.clone());
             scheduler.add_task(fut);
         }
     }
@@ -296,17 +1044,58 @@ impl Gathering {
         id: SquireAccountId,
         sync: &OpSync,
     ) -> Result<(), SyncError> {
-        let role = self.tourn.tourn().user_role(*id);
-        if sync.iter().all(|op| op.op.valid_op(role)) {
-            Ok(())
-        } else {
-            Err(SyncError::Unauthorized)
+        let tourn = self.tourn.tourn();
+        let role = tourn.user_role(*id);
+        let perms = &tourn.settings.permissions;
+        if !sync.iter().all(|op| op.op.valid_op(role, perms)) {
+            return Err(SyncError::Unauthorized);
         }
+        check_op_times(self.tourn.op_log(), sync, Utc::now())
+    }
+
+    fn handle_forwarding_resp(&mut self, id: Uuid, user: AuthUser, _: SyncForwardResp) {
+        self.forwarding.terminate_chain(id, user);
     }
+}
 
-    fn handle_forwarding_resp(&mut self, id: &Uuid, _: SyncForwardResp) {
-        self.forwarding.terminate_chain(id);
+/// Checks that the registrations and deck submissions in a sync were created close to `now`. The
+/// first operation is the anchor, which the log already has, and other operations that the log
+/// already has were checked when they were first received.
+fn check_op_times(log: &OpLog, sync: &OpSync, now: DateTime<Utc>) -> Result<(), SyncError> {
+    let tolerance = REG_OP_TOLERANCE.as_secs() as i64;
+    if sync
+        .iter()
+        .skip(1)
+        .filter(|op| op.op.checks_reg_window())
+        .filter(|op| (now - op.salt).num_seconds().abs() > tolerance)
+        .any(|op| !log.ops.iter().any(|known| known.id == op.id))
+    {
+        return Err(SyncError::StaleOperation);
     }
+    Ok(())
+}
+
+/// Calculates if the user is one of the tournament's officials, who can see everything about the
+/// players
+fn is_official(tourn: &TournamentManager, user: &AuthUser) -> bool {
+    matches!(user, AuthUser::User(id) if tourn.user_role(id.0).is_official())
+}
+
+/// Calculates if the user can follow the tournament without an invite, i.e. they are one of its
+/// officials or players
+fn follows_by_role(tourn: &TournamentManager, user: &AuthUser) -> bool {
+    matches!(user, AuthUser::User(id) if tourn.user_role(id.0) != TournRole::Spectator)
+}
+
+/// Returns what decides whether spectators can follow the tournament
+fn access(tourn: &TournamentManager) -> (Visibility, usize) {
+    (tourn.settings.visibility, tourn.revoked_invites.len())
+}
+
+/// Calculates if any of the operations carry a decklist or a player's account, which users that
+/// aren't officials might not be allowed to see
+fn is_private(ops: &OpSlice) -> bool {
+    ops.iter().any(|op| op.op.is_private())
 }
 
 impl From<CrierMessage> for GatheringMessage {
@@ -320,3 +1109,218 @@ impl From<(AuthUser, ClientBoundMessage)> for GatheringMessage {
         Self::ResendMessage(Box::new((user, msg)))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{atomic::Ordering::Relaxed, Arc};
+
+    use chrono::{Duration, Utc};
+    use squire_tests::{get_seed, spoof_account};
+    use tokio::sync::mpsc::{channel, Receiver};
+
+    use super::{
+        check_op_times, follows_by_role, Gathering, HallCounters, PersistReadyMessage, RateLimit,
+    };
+    use crate::{
+        api::{AuthUser, LifecycleEvent, LifecycleEventKind},
+        decks::DeckList,
+        model::{
+            identifiers::AdminId,
+            operations::{AdminOp, PlayerOp, TournOp},
+            settings::{GeneralSetting, Visibility},
+        },
+        sync::{FullOp, SyncError, TournamentManager},
+    };
+
+    /// Creates a gathering whose persistance queue only has room for one message
+    fn gathering() -> (Gathering, Receiver<PersistReadyMessage>) {
+        let tourn = TournamentManager::new(spoof_account(), get_seed());
+        let (send, recv) = channel(1);
+        let counters = Arc::new(HallCounters::default());
+        let gathering = Gathering::new(tourn, send, 10, RateLimit::default(), counters);
+        (gathering, recv)
+    }
+
+    fn register(gathering: &mut Gathering) {
+        let _ = gathering
+            .tourn
+            .apply_op(TournOp::RegisterPlayer(spoof_account(), None))
+            .unwrap();
+    }
+
+    fn dropped(gathering: &Gathering) -> usize {
+        gathering.counters.dropped_persists.load(Relaxed)
+    }
+
+    /// Creates an operation that was created the given number of minutes ago
+    fn backdated(op: TournOp, minutes: i64) -> FullOp {
+        let mut op = FullOp::new(op);
+        op.salt -= Duration::minutes(minutes);
+        op
+    }
+
+    #[test]
+    fn backdated_registrations_are_rejected() {
+        let mut tourn = TournamentManager::new(spoof_account(), get_seed());
+        let account = spoof_account();
+        let p_id = account.id.0.into();
+        let _ = tourn
+            .apply_op(TournOp::RegisterPlayer(account, None))
+            .unwrap();
+        let _ = tourn
+            .apply_op(TournOp::RegisterPlayer(spoof_account(), None))
+            .unwrap();
+        let log = tourn.op_log();
+        let sync = tourn.sync_request();
+        assert_eq!(check_op_times(log, &sync, Utc::now()), Ok(()));
+        // Operations that the log already has are not checked again, even once they are old
+        let later = Utc::now() + Duration::minutes(10);
+        assert_eq!(check_op_times(log, &sync, later), Ok(()));
+
+        let mut stale = sync.clone();
+        stale.ops.add_op(backdated(
+            TournOp::RegisterPlayer(spoof_account(), None),
+            10,
+        ));
+        assert_eq!(
+            check_op_times(log, &stale, Utc::now()),
+            Err(SyncError::StaleOperation)
+        );
+
+        let deck = DeckList::parse("60 Mountain").unwrap().into_deck().unwrap();
+        let mut stale = sync.clone();
+        stale.ops.add_op(backdated(
+            TournOp::PlayerOp(p_id, PlayerOp::AddDeck("Mono Red".into(), deck.clone())),
+            10,
+        ));
+        assert_eq!(
+            check_op_times(log, &stale, Utc::now()),
+            Err(SyncError::StaleOperation)
+        );
+
+        // Operations created within the tolerance and operations that don't check the
+        // registration window are let through
+        let mut fresh = sync.clone();
+        fresh.ops.add_op(backdated(
+            TournOp::PlayerOp(p_id, PlayerOp::AddDeck("Mono Red".into(), deck)),
+            1,
+        ));
+        fresh
+            .ops
+            .add_op(backdated(TournOp::PlayerOp(p_id, PlayerOp::CheckIn), 10));
+        assert_eq!(check_op_times(log, &fresh, Utc::now()), Ok(()));
+    }
+
+    #[test]
+    fn full_copies_replace_persists_that_did_not_fit() {
+        let (mut gathering, mut recv) = gathering();
+        let id = gathering.tourn.id;
+        register(&mut gathering);
+        gathering.send_new_ops();
+        let Ok(PersistReadyMessage::Ops(_, _, ops)) = recv.try_recv() else {
+            panic!("The new operation was not sent off to be appended")
+        };
+        assert_eq!(ops.len(), 1);
+
+        // Fill the queue, so that the next batches of operations don't fit
+        register(&mut gathering);
+        gathering.send_new_ops();
+        assert!(!gathering.unsaved);
+        register(&mut gathering);
+        gathering.send_new_ops();
+        assert!(gathering.unsaved);
+        assert_eq!(dropped(&gathering), 1);
+        // Once a batch is missed, only full copies are sent, which cover every missed operation
+        register(&mut gathering);
+        gathering.send_new_ops();
+        assert!(gathering.unsaved);
+        assert_eq!(dropped(&gathering), 2);
+
+        assert!(matches!(recv.try_recv(), Ok(PersistReadyMessage::Ops(..))));
+        gathering.retry_persist();
+        assert!(!gathering.unsaved);
+        assert_eq!(dropped(&gathering), 2);
+        assert_eq!(recv.try_recv(), Ok(PersistReadyMessage::Tournament(id)));
+        assert_eq!(gathering.persisted, gathering.tourn.op_log().last_id());
+
+        // Batches of operations are sent again once a full copy is
+        register(&mut gathering);
+        gathering.send_new_ops();
+        let Ok(PersistReadyMessage::Ops(_, _, ops)) = recv.try_recv() else {
+            panic!("The new operation was not sent off to be appended")
+        };
+        assert_eq!(ops.len(), 1);
+    }
+
+    #[test]
+    fn stopped_halls_are_not_retried() {
+        let (mut gathering, mut recv) = gathering();
+        let event = LifecycleEvent {
+            org: Default::default(),
+            tourn: gathering.tourn.id,
+            kind: LifecycleEventKind::Started,
+            actor: Default::default(),
+            time: Utc::now(),
+        };
+        gathering.send_persist_message();
+        gathering.unsent_events.push(event.clone());
+        gathering.send_events();
+        assert_eq!(gathering.unsent_events, vec![event]);
+        assert_eq!(dropped(&gathering), 1);
+
+        recv.close();
+        gathering.send_events();
+        assert!(gathering.unsent_events.is_empty());
+        register(&mut gathering);
+        gathering.send_new_ops();
+        assert!(!gathering.unsaved);
+        gathering.send_persist_message();
+        assert!(!gathering.unsaved);
+        assert_eq!(dropped(&gathering), 1);
+    }
+
+    #[test]
+    fn spectators_are_checked_once_access_narrows() {
+        let admin = spoof_account();
+        let admin_id: AdminId = admin.id.0.into();
+        let tourn = TournamentManager::new(admin.clone(), get_seed());
+        let (send, _recv) = channel(1);
+        let counters = Arc::new(HallCounters::default());
+        let mut gathering = Gathering::new(tourn, send, 10, RateLimit::default(), counters);
+        let player = spoof_account();
+        _ = gathering
+            .tourn
+            .apply_op(TournOp::RegisterPlayer(player.clone(), None))
+            .unwrap();
+        assert!(!gathering.access_narrowed());
+
+        // Anyone can follow unlisted tournaments, so no one needs to be checked
+        let visibility = |vis| {
+            let setting = GeneralSetting::Visibility(vis).into();
+            TournOp::AdminOp(admin_id, AdminOp::UpdateTournSetting(setting))
+        };
+        _ = gathering
+            .tourn
+            .apply_op(visibility(Visibility::Unlisted))
+            .unwrap();
+        assert!(!gathering.access_narrowed());
+        _ = gathering
+            .tourn
+            .apply_op(visibility(Visibility::Private))
+            .unwrap();
+        assert!(gathering.access_narrowed());
+        assert!(!gathering.access_narrowed());
+        let revoke = TournOp::AdminOp(admin_id, AdminOp::RevokeInvite(1));
+        _ = gathering.tourn.apply_op(revoke).unwrap();
+        assert!(gathering.access_narrowed());
+
+        // Officials and players keep following, but spectators are dropped
+        assert!(follows_by_role(&gathering.tourn, &AuthUser::User(admin.id)));
+        assert!(follows_by_role(
+            &gathering.tourn,
+            &AuthUser::User(player.id)
+        ));
+        let spectator = AuthUser::User(spoof_account().id);
+        assert!(!follows_by_role(&gathering.tourn, &spectator));
+    }
+}
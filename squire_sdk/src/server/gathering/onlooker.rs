@@ -11,8 +11,13 @@ use futures::{
     stream::{FusedStream, SplitSink, SplitStream},
     Sink, SinkExt, Stream, StreamExt,
 };
+use instant::Instant;
 
-use crate::{api::AuthUser, server::session::SessionWatcher, sync::ClientBoundMessage};
+use crate::{
+    api::AuthUser,
+    server::session::SessionWatcher,
+    sync::{ClientBoundMessage, Compression},
+};
 
 /// This structure captures messages being sent to a person that is in some way participating in
 /// the tournament. This person could be a spectator, player, judge, or admin. Messages they pass
@@ -39,32 +44,64 @@ impl Crier {
 /// This structure captures messages being sent to a person that is in some way participating in
 /// the tournament. This person could be a spectator, player, judge, or admin. Messages passed to
 /// them are usually from other users that are submitting operations to the tournament.
+///
+/// The onlooker also tracks the last time that anything was heard from them. Onlookers that stay
+/// silent through several heartbeats are assumed to be dead and are pruned from their gathering.
 #[derive(Debug)]
-pub struct Onlooker(SplitSink<WebSocket, Message>);
+pub struct Onlooker(SplitSink<WebSocket, Message>, Compression, Instant);
 
 impl Onlooker {
-    pub fn new(sink: SplitSink<WebSocket, Message>) -> Self {
-        Self(sink)
+    pub fn new(sink: SplitSink<WebSocket, Message>, compression: Compression) -> Self {
+        Self(sink, compression, Instant::now())
     }
 
     pub async fn send_msg(&mut self, msg: &ClientBoundMessage) -> Result<(), AxumError> {
-        let bytes = Message::Binary(postcard::to_allocvec(msg).unwrap());
+        let bytes = Message::Binary(msg.to_bytes(self.1));
         self.send(bytes).await
     }
+
+    /// Sends a ping frame. The client's websocket implementation answers with a pong frame.
+    pub async fn ping(&mut self) -> Result<(), AxumError> {
+        self.send(Message::Ping(Vec::new())).await
+    }
+
+    /// Records that a frame was just received from the onlooker
+    pub fn mark_seen(&mut self) {
+        self.2 = Instant::now();
+    }
+
+    /// Returns the last time that a frame was received from the onlooker
+    pub fn last_seen(&self) -> Instant {
+        self.2
+    }
 }
 
 #[derive(Debug, Clone)]
 pub enum CrierMessage {
     NoAuthMessage(AuthUser, Vec<u8>),
     AuthMessage(AuthUser, Vec<u8>),
+    /// A non-data frame (e.g. a pong) was received, so the connection is still alive
+    Heartbeat(AuthUser),
     ClosingFrame(AuthUser),
 }
 
+impl CrierMessage {
+    /// Returns the user that sent the message
+    pub fn user(&self) -> &AuthUser {
+        match self {
+            CrierMessage::NoAuthMessage(user, _)
+            | CrierMessage::AuthMessage(user, _)
+            | CrierMessage::Heartbeat(user)
+            | CrierMessage::ClosingFrame(user) => user,
+        }
+    }
+}
+
 /// A `Crier` is a simple wrapper around an account and a websocket connection. We only support
-/// binary-encoded messages (using `postcard`). All other messages types are ignored. Moreover,
-/// this stream will send exactly one `None` value. This corresponds to the closing frame set by the
-/// Websocket when the connection is closed. After that, this stream will return `Poll::Pending`
-/// forever.
+/// binary-encoded messages (using `postcard`). All other messages types are only reported as
+/// heartbeats. Moreover, this stream will send exactly one `ClosingFrame` value. This corresponds to
+/// the closing frame set by the Websocket when the connection is closed (or errors). After that,
+/// this stream is terminated.
 impl Stream for Crier {
     type Item = CrierMessage;
 
@@ -75,8 +112,8 @@ impl Stream for Crier {
             return Poll::Ready(None);
         }
         match self.stream.poll_next_unpin(cx) {
-            Poll::Ready(Some(Err(_))) => Poll::Pending,
-            Poll::Ready(None) => {
+            // A socket that errors or sends a close frame is treated as closed
+            Poll::Ready(Some(Err(_) | Ok(Message::Close(_)))) | Poll::Ready(None) => {
                 self.is_done = true;
                 Poll::Ready(Some(CrierMessage::ClosingFrame(self.user.clone())))
             }
@@ -87,7 +124,10 @@ impl Stream for Crier {
                     Poll::Ready(Some(CrierMessage::NoAuthMessage(self.user.clone(), val)))
                 }
             }
-            _ => Poll::Pending,
+            Poll::Ready(Some(Ok(_))) => {
+                Poll::Ready(Some(CrierMessage::Heartbeat(self.user.clone())))
+            }
+            Poll::Pending => Poll::Pending,
         }
     }
 }
@@ -0,0 +1,198 @@
+use chrono::Utc;
+
+use super::fetch::fetch_text;
+use crate::{
+    api::{ImportColumns, ImportError, ImportSource, RegistrationImport, RowReport, RowStatus},
+    model::{
+        admin::TournOfficialId,
+        operations::{JudgeOp, TournOp},
+        tournament::Tournament,
+    },
+};
+
+/// The largest registration sheet that the server will fetch, in bytes
+const MAX_SHEET_SIZE: usize = 1 << 20;
+
+/// Reads the text of a registration sheet. Sheets given by URL are fetched by the server, which
+/// lets organizers import from a published Google Sheet without a CORS-enabled host. Only HTTPS
+/// URLs on the public internet are fetched (see [check_public_url]), and sheets are at most 1 MiB.
+///
+/// [check_public_url]: super::fetch::check_public_url
+pub async fn read_source(source: ImportSource) -> Result<String, ImportError> {
+    match source {
+        ImportSource::Csv(sheet) => Ok(sheet),
+        ImportSource::Url(url) => fetch_text(&url, MAX_SHEET_SIZE)
+            .await
+            .map_err(|_| ImportError::FetchFailed),
+    }
+}
+
+/// Reads the rows of a registration sheet and creates the operations that register each player as
+/// a guest. The registrations are tried against a copy of the tournament, so each row is checked
+/// against the tournament and the rows above it. Rows that can not be registered are reported but
+/// have no operation.
+pub fn plan_import(
+    tourn: &Tournament,
+    official: TournOfficialId,
+    sheet: &str,
+    columns: &ImportColumns,
+) -> Result<RegistrationImport, ImportError> {
+    let mut rows = parse_csv(sheet).into_iter();
+    let header = rows.next().unwrap_or_default();
+    let name_col = find_column(&header, &columns.name)?;
+    let email_col = columns
+        .email
+        .as_ref()
+        .map(|name| find_column(&header, name))
+        .transpose()?;
+    let deck_col = columns
+        .decklist
+        .as_ref()
+        .map(|name| find_column(&header, name))
+        .transpose()?;
+    let mut buffer = tourn.clone();
+    let mut digest = RegistrationImport {
+        rows: Vec::new(),
+        ops: Vec::new(),
+    };
+    for (i, row) in rows.enumerate() {
+        if row.iter().all(|field| field.trim().is_empty()) {
+            continue;
+        }
+        let name = get_field(&row, Some(name_col)).unwrap_or_default();
+        let status = if name.is_empty() {
+            RowStatus::MissingName
+        } else {
+            let op = TournOp::JudgeOp(official, JudgeOp::RegisterGuest(name.clone()));
+            match buffer.apply_op(Utc::now(), op.clone()) {
+                Ok(_) => {
+                    digest.ops.push(op);
+                    RowStatus::Registered
+                }
+                Err(err) => RowStatus::Rejected(err),
+            }
+        };
+        digest.rows.push(RowReport {
+            row: i + 2,
+            name,
+            email: get_field(&row, email_col),
+            decklist: get_field(&row, deck_col),
+            status,
+        });
+    }
+    Ok(digest)
+}
+
+fn find_column(header: &[String], name: &str) -> Result<usize, ImportError> {
+    header
+        .iter()
+        .position(|h| h.trim().eq_ignore_ascii_case(name.trim()))
+        .ok_or_else(|| ImportError::MissingColumn(name.to_owned()))
+}
+
+fn get_field(row: &[String], col: Option<usize>) -> Option<String> {
+    col.and_then(|col| row.get(col))
+        .map(|field| field.trim().to_owned())
+        .filter(|field| !field.is_empty())
+}
+
+/// Splits CSV text into rows of fields. Quoted fields can contain commas, line breaks, and quotes
+/// (written as `""`).
+pub fn parse_csv(text: &str) -> Vec<Vec<String>> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = text.trim_start_matches('\u{feff}').chars().peekable();
+    while let Some(c) = chars.next() {
+        match (quoted, c) {
+            (true, '"') if chars.peek() == Some(&'"') => {
+                _ = chars.next();
+                field.push('"');
+            }
+            (true, '"') => quoted = false,
+            (true, c) => field.push(c),
+            (false, '"') => quoted = true,
+            (false, ',') => row.push(std::mem::take(&mut field)),
+            (false, '\n') => {
+                row.push(std::mem::take(&mut field));
+                rows.push(std::mem::take(&mut row));
+            }
+            (false, '\r') => {}
+            (false, c) => field.push(c),
+        }
+    }
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push(row);
+    }
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use squire_tests::{get_seed, spoof_account};
+
+    use super::{parse_csv, plan_import};
+    use crate::{
+        api::{ImportColumns, ImportError, RowStatus},
+        model::{error::TournamentError, identifiers::AdminId},
+    };
+
+    #[test]
+    fn csv_parsing() {
+        let sheet = "Name,Notes\r\n\"Doe, Jane\",\"says \"\"hi\"\"\nand bye\"\r\nJohn,\n";
+        assert_eq!(
+            parse_csv(sheet),
+            vec![
+                vec!["Name", "Notes"],
+                vec!["Doe, Jane", "says \"hi\"\nand bye"],
+                vec!["John", ""],
+            ]
+        );
+    }
+
+    #[test]
+    fn import_report() {
+        let admin = spoof_account();
+        let admin_id: AdminId = admin.id.0.into();
+        let tourn = admin.create_tournament(get_seed());
+        let sheet = "Timestamp,Player Name,Email,Deck\n\
+            1,Alice,alice@example.com,\n\
+            2,,bob@example.com,\n\
+            ,,,\n\
+            3,Alice,other@example.com,4 Island\n\
+            4,Carol,,https://example.com/deck\n";
+        let mut columns = ImportColumns {
+            name: "player name".to_owned(),
+            email: Some("Email".to_owned()),
+            decklist: Some("Deck".to_owned()),
+        };
+        let import = plan_import(&tourn, admin_id.into(), sheet, &columns).unwrap();
+        let statuses: Vec<_> = import.rows.iter().map(|r| (r.row, r.status)).collect();
+        assert_eq!(
+            statuses,
+            vec![
+                (2, RowStatus::Registered),
+                (3, RowStatus::MissingName),
+                (
+                    5,
+                    RowStatus::Rejected(TournamentError::PlayerAlreadyRegistered)
+                ),
+                (6, RowStatus::Registered),
+            ]
+        );
+        assert_eq!(import.ops.len(), 2);
+        assert_eq!(import.rows[0].email.as_deref(), Some("alice@example.com"));
+        assert_eq!(
+            import.rows[3].decklist.as_deref(),
+            Some("https://example.com/deck")
+        );
+
+        columns.decklist = Some("Decklist".to_owned());
+        assert_eq!(
+            plan_import(&tourn, admin_id.into(), sheet, &columns),
+            Err(ImportError::MissingColumn("Decklist".to_owned()))
+        );
+    }
+}
@@ -0,0 +1,337 @@
+use std::{convert::Infallible, future::Future, pin::Pin};
+
+use axum::extract::FromRequestParts;
+use chrono::{DateTime, TimeZone, Utc};
+use hmac::{Hmac, Mac};
+use http::request::Parts;
+use sha2::Sha256;
+use uuid::Uuid;
+
+use crate::{
+    api::{InviteError, InviteRole, TournamentInvite},
+    model::{
+        accounts::SquireAccount,
+        identifiers::{SquireAccountId, TournamentId},
+        operations::{AdminOp, TournOp},
+        tournament::TournRole,
+    },
+    sync::TournamentManager,
+};
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Signs an invite to a tournament using the given key. The expiry is rounded down to the second
+/// so that the invite survives being encoded as a token. Each invite is given a random nonce so
+/// that it can be revoked on its own.
+pub fn sign_invite(
+    key: &[u8],
+    tourn: TournamentId,
+    role: InviteRole,
+    expires: DateTime<Utc>,
+) -> TournamentInvite {
+    let mut invite = TournamentInvite {
+        tourn,
+        role,
+        expires: Utc.timestamp_opt(expires.timestamp(), 0).unwrap(),
+        nonce: Uuid::new_v4().as_u64_pair().0,
+        signature: String::new(),
+    };
+    invite.signature = hex::encode(signer(key, &invite).finalize().into_bytes());
+    invite
+}
+
+/// Decodes an invite token and checks that it was signed with the given key and has not expired.
+pub fn verify_invite(
+    key: &[u8],
+    token: &str,
+    now: DateTime<Utc>,
+) -> Result<TournamentInvite, InviteError> {
+    let invite = TournamentInvite::from_token(token).ok_or(InviteError::InvalidToken)?;
+    let signature = hex::decode(&invite.signature).map_err(|_| InviteError::InvalidToken)?;
+    signer(key, &invite)
+        .verify_slice(&signature)
+        .map_err(|_| InviteError::InvalidToken)?;
+    if invite.is_expired(now) {
+        return Err(InviteError::Expired);
+    }
+    Ok(invite)
+}
+
+/// Returns if the invite has been revoked by the tournament's admins, or if it is a staff invite
+/// that has already been used
+pub fn is_revoked(tourn: &TournamentManager, invite: &TournamentInvite) -> bool {
+    tourn.revoked_invites.contains(&invite.nonce)
+}
+
+/// Returns the operations that give an account the role granted by an invite, which are to be
+/// applied in order. Spectating needs no operations.
+///
+/// Invites are signed by the server rather than by the official that created them, so judges are
+/// registered on behalf of the tournament's creator, or another admin if the creator is no longer
+/// one. Staff invites can only be used once, so they are revoked before the judge is registered.
+/// If the invite has been used in the meantime, revoking it fails and the judge is not registered.
+pub fn redeem_invite(
+    tourn: &TournamentManager,
+    invite: &TournamentInvite,
+    account: SquireAccount,
+) -> Result<Vec<TournOp>, InviteError> {
+    if is_revoked(tourn, invite) {
+        return Err(InviteError::Revoked);
+    }
+    let digest = match invite.role {
+        InviteRole::Spectator => Vec::new(),
+        InviteRole::Player => vec![TournOp::RegisterPlayer(account, None)],
+        InviteRole::Judge => {
+            let (_, creator) = tourn.seed_and_creator();
+            let admin = match tourn.user_role(*creator.id) {
+                TournRole::Admin(a_id) => a_id,
+                _ => *tourn.admins.keys().min().ok_or(InviteError::Unauthorized)?,
+            };
+            vec![
+                TournOp::AdminOp(admin, AdminOp::RevokeInvite(invite.nonce)),
+                TournOp::AdminOp(admin, AdminOp::RegisterJudge(account)),
+            ]
+        }
+    };
+    Ok(digest)
+}
+
+/// Returns if the user can follow the tournament, i.e. see its standings and pairings or subscribe
+/// to it. Private tournaments can only be followed by their officials and players, or by users with
+/// an invite to the tournament. An invite to any role lets the user follow the tournament, unless
+/// it has been revoked.
+pub fn can_follow(
+    key: &[u8],
+    tourn: &TournamentManager,
+    user: Option<SquireAccountId>,
+    invite: Option<&str>,
+    now: DateTime<Utc>,
+) -> bool {
+    if !tourn.settings.visibility.needs_invite() {
+        return true;
+    }
+    let role = user.map(|u_id| tourn.user_role(*u_id)).unwrap_or_default();
+    role != TournRole::Spectator
+        || invite
+            .and_then(|token| verify_invite(key, token, now).ok())
+            .is_some_and(|invite| invite.tourn == tourn.id && !is_revoked(tourn, &invite))
+}
+
+/// An extractor for the invite token that a request was sent with (see
+/// [TournamentInvite::HEADER_NAME]). Requests without one are not rejected.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct InviteHeader(pub Option<String>);
+
+impl InviteHeader {
+    /// Returns the invite token, if there is one
+    pub fn token(&self) -> Option<&str> {
+        self.0.as_deref()
+    }
+}
+
+impl<St: Send + Sync> FromRequestParts<St> for InviteHeader {
+    type Rejection = Infallible;
+
+    fn from_request_parts<'life0, 'life1, 'async_trait>(
+        parts: &'life0 mut Parts,
+        _state: &'life1 St,
+    ) -> Pin<Box<dyn 'async_trait + Send + Future<Output = Result<Self, Self::Rejection>>>>
+    where
+        'life0: 'async_trait,
+        'life1: 'async_trait,
+        Self: 'async_trait,
+    {
+        let token = parts
+            .headers
+            .get(TournamentInvite::HEADER_NAME)
+            .and_then(|h| h.to_str().ok())
+            .map(ToOwned::to_owned);
+        Box::pin(async move { Ok(InviteHeader(token)) })
+    }
+}
+
+fn signer(key: &[u8], invite: &TournamentInvite) -> HmacSha256 {
+    let mut mac = HmacSha256::new_from_slice(key).expect("HMAC can take a key of any size");
+    // The signature is not part of what is signed
+    let token = invite.token();
+    let signed = token
+        .rsplit_once('.')
+        .map_or(token.as_str(), |(body, _)| body);
+    mac.update(b"invite\n");
+    mac.update(signed.as_bytes());
+    mac
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, Utc};
+    use squire_lib::{
+        error::TournamentError,
+        identifiers::{AdminId, TournamentId},
+        operations::{AdminOp, TournOp},
+        settings::{GeneralSetting, Visibility},
+        tournament::TournRole,
+    };
+    use squire_tests::{get_seed, spoof_account};
+    use uuid::Uuid;
+
+    use super::{can_follow, is_revoked, redeem_invite, sign_invite, verify_invite};
+    use crate::{
+        api::{InviteError, InviteRole, TournamentInvite},
+        sync::TournamentManager,
+    };
+
+    const KEY: &[u8] = b"invite signing key";
+
+    #[test]
+    fn invite_tokens_round_trip() {
+        let now = Utc::now();
+        let t_id = TournamentId::new(Uuid::new_v4());
+        let invite = sign_invite(KEY, t_id, InviteRole::Judge, now + Duration::days(1));
+        let token = invite.token();
+        assert_eq!(TournamentInvite::from_token(&token), Some(invite.clone()));
+        assert_eq!(verify_invite(KEY, &token, now), Ok(invite));
+
+        // Only the signing key can verify the invite
+        assert_eq!(
+            verify_invite(b"another key", &token, now),
+            Err(InviteError::InvalidToken)
+        );
+        // The invite stops working once it expires
+        assert_eq!(
+            verify_invite(KEY, &token, now + Duration::days(2)),
+            Err(InviteError::Expired)
+        );
+    }
+
+    #[test]
+    fn edited_invites_are_rejected() {
+        let now = Utc::now();
+        let t_id = TournamentId::new(Uuid::new_v4());
+        let token = sign_invite(KEY, t_id, InviteRole::Player, now + Duration::days(1)).token();
+
+        // Upgrading the role breaks the signature
+        let judge = token.replacen(".p.", ".j.", 1);
+        assert_eq!(
+            verify_invite(KEY, &judge, now),
+            Err(InviteError::InvalidToken)
+        );
+
+        // As does extending the expiry
+        let mut invite = TournamentInvite::from_token(&token).unwrap();
+        invite.expires += Duration::days(7);
+        assert_eq!(
+            verify_invite(KEY, &invite.token(), now),
+            Err(InviteError::InvalidToken)
+        );
+
+        assert_eq!(
+            verify_invite(KEY, "not a token", now),
+            Err(InviteError::InvalidToken)
+        );
+    }
+
+    #[test]
+    fn redeemed_invites_grant_their_roles() {
+        let expires = Utc::now() + Duration::days(1);
+        let mut tourn = TournamentManager::new(spoof_account(), get_seed());
+
+        let judge = spoof_account();
+        let invite = sign_invite(KEY, tourn.id, InviteRole::Judge, expires);
+        for op in redeem_invite(&tourn, &invite, judge.clone()).unwrap() {
+            _ = tourn.apply_op(op).unwrap();
+        }
+        assert!(matches!(tourn.user_role(*judge.id), TournRole::Judge(_)));
+
+        let player = spoof_account();
+        let invite = sign_invite(KEY, tourn.id, InviteRole::Player, expires);
+        for op in redeem_invite(&tourn, &invite, player.clone()).unwrap() {
+            _ = tourn.apply_op(op).unwrap();
+        }
+        assert!(matches!(tourn.user_role(*player.id), TournRole::Player(_)));
+
+        // Spectating needs no operation
+        let invite = sign_invite(KEY, tourn.id, InviteRole::Spectator, expires);
+        assert_eq!(
+            redeem_invite(&tourn, &invite, spoof_account()),
+            Ok(Vec::new())
+        );
+    }
+
+    #[test]
+    fn staff_invites_are_single_use() {
+        let expires = Utc::now() + Duration::days(1);
+        let mut tourn = TournamentManager::new(spoof_account(), get_seed());
+        let invite = sign_invite(KEY, tourn.id, InviteRole::Judge, expires);
+
+        // Both redemptions are planned before either is applied, like two users racing
+        let first = redeem_invite(&tourn, &invite, spoof_account()).unwrap();
+        let second_judge = spoof_account();
+        let second = redeem_invite(&tourn, &invite, second_judge.clone()).unwrap();
+        for op in first {
+            _ = tourn.apply_op(op).unwrap();
+        }
+        assert!(is_revoked(&tourn, &invite));
+        assert_eq!(
+            tourn.apply_op(second[0].clone()),
+            Err(TournamentError::InviteRevoked)
+        );
+        assert_eq!(tourn.user_role(*second_judge.id), TournRole::Spectator);
+        assert_eq!(
+            redeem_invite(&tourn, &invite, second_judge),
+            Err(InviteError::Revoked)
+        );
+    }
+
+    #[test]
+    fn revoked_invites_are_rejected() {
+        let now = Utc::now();
+        let expires = now + Duration::days(1);
+        let admin = spoof_account();
+        let admin_id: AdminId = admin.id.0.into();
+        let mut tourn = TournamentManager::new(admin, get_seed());
+        let setting = GeneralSetting::Visibility(Visibility::Private);
+        let op = TournOp::AdminOp(admin_id, AdminOp::UpdateTournSetting(setting.into()));
+        _ = tourn.apply_op(op).unwrap();
+
+        let invite = sign_invite(KEY, tourn.id, InviteRole::Player, expires);
+        let other = sign_invite(KEY, tourn.id, InviteRole::Player, expires);
+        assert_ne!(invite.nonce, other.nonce);
+        let op = TournOp::AdminOp(admin_id, AdminOp::RevokeInvite(invite.nonce));
+        _ = tourn.apply_op(op).unwrap();
+
+        assert_eq!(
+            redeem_invite(&tourn, &invite, spoof_account()),
+            Err(InviteError::Revoked)
+        );
+        assert!(!can_follow(KEY, &tourn, None, Some(&invite.token()), now));
+        // Other invites still work
+        assert!(redeem_invite(&tourn, &other, spoof_account()).is_ok());
+        assert!(can_follow(KEY, &tourn, None, Some(&other.token()), now));
+    }
+
+    #[test]
+    fn private_tournaments_need_invites() {
+        let now = Utc::now();
+        let expires = now + Duration::days(1);
+        let admin = spoof_account();
+        let admin_id: AdminId = admin.id.0.into();
+        let mut tourn = TournamentManager::new(admin.clone(), get_seed());
+        let spectator = spoof_account();
+        assert!(can_follow(KEY, &tourn, Some(spectator.id), None, now));
+
+        let setting = GeneralSetting::Visibility(Visibility::Private);
+        let op = TournOp::AdminOp(admin_id, AdminOp::UpdateTournSetting(setting.into()));
+        _ = tourn.apply_op(op).unwrap();
+        assert!(!can_follow(KEY, &tourn, Some(spectator.id), None, now));
+        assert!(!can_follow(KEY, &tourn, None, None, now));
+        assert!(can_follow(KEY, &tourn, Some(admin.id), None, now));
+
+        let token = sign_invite(KEY, tourn.id, InviteRole::Spectator, expires).token();
+        assert!(can_follow(KEY, &tourn, None, Some(&token), now));
+        // Invites only work for the tournament that they were made for
+        let other = sign_invite(KEY, Uuid::new_v4().into(), InviteRole::Spectator, expires);
+        assert!(!can_follow(KEY, &tourn, None, Some(&other.token()), now));
+        assert!(!can_follow(KEY, &tourn, None, Some(&token), expires));
+    }
+}
@@ -0,0 +1,90 @@
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// What a signing key is used for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum KeyPurpose {
+    /// Signing tournament invites
+    Invite,
+    /// Signing the tokens that let players report their results
+    Report,
+    /// Signing tournament exports
+    Export,
+    /// Signing the states of OAuth redirects
+    OAuthState,
+}
+
+/// The keys that the server signs with. Each key is derived from a single master key, so that
+/// something that was signed for one purpose (e.g. an export) can not be passed off as another
+/// (e.g. an invite).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SigningKeys {
+    invite: [u8; 32],
+    report: [u8; 32],
+    export: [u8; 32],
+    oauth_state: [u8; 32],
+}
+
+impl KeyPurpose {
+    /// The label that the purpose's key is derived with
+    fn label(self) -> &'static [u8] {
+        match self {
+            KeyPurpose::Invite => b"invite",
+            KeyPurpose::Report => b"report",
+            KeyPurpose::Export => b"export",
+            KeyPurpose::OAuthState => b"oauth_state",
+        }
+    }
+}
+
+impl SigningKeys {
+    /// Derives the key for each purpose from the master key
+    pub fn derive(master: &[u8]) -> Self {
+        Self {
+            invite: derive_key(master, KeyPurpose::Invite),
+            report: derive_key(master, KeyPurpose::Report),
+            export: derive_key(master, KeyPurpose::Export),
+            oauth_state: derive_key(master, KeyPurpose::OAuthState),
+        }
+    }
+
+    /// Returns the key for the given purpose
+    pub fn get(&self, purpose: KeyPurpose) -> &[u8] {
+        match purpose {
+            KeyPurpose::Invite => &self.invite,
+            KeyPurpose::Report => &self.report,
+            KeyPurpose::Export => &self.export,
+            KeyPurpose::OAuthState => &self.oauth_state,
+        }
+    }
+}
+
+fn derive_key(master: &[u8], purpose: KeyPurpose) -> [u8; 32] {
+    let mut mac = HmacSha256::new_from_slice(master).expect("HMAC can take a key of any size");
+    mac.update(purpose.label());
+    mac.finalize().into_bytes().into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{KeyPurpose, SigningKeys};
+
+    #[test]
+    fn each_purpose_has_its_own_key() {
+        let keys = SigningKeys::derive(b"master");
+        let invite = keys.get(KeyPurpose::Invite);
+        assert_ne!(invite, keys.get(KeyPurpose::Report));
+        assert_ne!(invite, keys.get(KeyPurpose::Export));
+        assert_ne!(keys.get(KeyPurpose::Report), keys.get(KeyPurpose::Export));
+        assert_ne!(invite, keys.get(KeyPurpose::OAuthState));
+        assert_ne!(
+            keys.get(KeyPurpose::Export),
+            keys.get(KeyPurpose::OAuthState)
+        );
+        assert_ne!(invite, b"master");
+        assert_eq!(keys, SigningKeys::derive(b"master"));
+        assert_ne!(keys, SigningKeys::derive(b"other"));
+    }
+}
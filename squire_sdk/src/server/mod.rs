@@ -3,13 +3,25 @@ use axum::{extract::State, handler::Handler, Router};
 use self::state::ServerState;
 use crate::api::*;
 
+pub mod export;
+pub mod fetch;
 pub mod gathering;
+pub mod import;
+pub mod invite;
+pub mod keys;
+pub mod orgs;
+pub mod report;
+pub mod series;
 pub mod session;
 pub mod state;
 pub mod tournaments;
+pub mod webhooks;
 
 pub fn create_router<S: ServerState>() -> SquireRouter<S> {
-    get_routes::<S>().merge(tournaments::get_routes::<S>())
+    get_routes::<S>()
+        .merge(tournaments::get_routes::<S>())
+        .merge(series::get_routes::<S>())
+        .merge(orgs::get_routes::<S>())
 }
 
 fn get_routes<S: ServerState>() -> SquireRouter<S> {
@@ -37,7 +49,7 @@ where
         T: 'static,
         H: Handler<T, S>,
     {
-        println!("Adding route: {} {}", R::METHOD, R::ROUTE);
+        tracing::debug!("Adding route: {} {}", R::METHOD, R::ROUTE);
         Self {
             router: self.router.route(R::ROUTE.as_str(), R::as_route(handler)),
         }
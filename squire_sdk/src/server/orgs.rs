@@ -0,0 +1,31 @@
+use axum::extract::{Path, State};
+use squire_lib::identifiers::SquireAccountId;
+
+use super::{
+    session::{Session, UserSession},
+    SquireRouter,
+};
+use crate::{api::*, server::state::ServerState};
+
+pub fn get_routes<S: ServerState>() -> SquireRouter<S> {
+    SquireRouter::new().add_route::<1, GET, GetOrgEvents, _, _>(get_org_events::<S>)
+}
+
+/// Returns the lifecycle events (creation, starting, cuts, and ending) of every tournament run by
+/// an organization. These are kept separately from the operation logs of the tournaments.
+///
+/// This api can be accessed via `/api/v1/orgs/<id>/events`. Only the organization itself can see
+/// its events.
+pub async fn get_org_events<S>(
+    State(state): State<S>,
+    Session(UserSession(u_id)): Session<UserSession>,
+    Path(id): Path<SquireAccountId>,
+) -> GetOrgEventsResponse
+where
+    S: ServerState,
+{
+    if u_id != id {
+        return GetOrgEventsResponse::new(None);
+    }
+    GetOrgEventsResponse::new(Some(state.get_org_events(id).await))
+}
@@ -0,0 +1,130 @@
+use chrono::{DateTime, TimeZone, Utc};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+use crate::{
+    api::{ReportError, ReportToken},
+    model::identifiers::{PlayerId, RoundId, TournamentId},
+};
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Signs a token that lets a player report the result of a round using the given key. The expiry
+/// is rounded down to the second so that the token survives being encoded.
+pub fn sign_report_token(
+    key: &[u8],
+    tourn: TournamentId,
+    round: RoundId,
+    player: PlayerId,
+    expires: DateTime<Utc>,
+) -> ReportToken {
+    let mut token = ReportToken {
+        tourn,
+        round,
+        player,
+        expires: Utc.timestamp_opt(expires.timestamp(), 0).unwrap(),
+        signature: String::new(),
+    };
+    token.signature = hex::encode(signer(key, &token).finalize().into_bytes());
+    token
+}
+
+/// Decodes a reporting token and checks that it was signed with the given key and has not
+/// expired.
+pub fn verify_report_token(
+    key: &[u8],
+    token: &str,
+    now: DateTime<Utc>,
+) -> Result<ReportToken, ReportError> {
+    let token = ReportToken::from_token(token).ok_or(ReportError::InvalidToken)?;
+    let signature = hex::decode(&token.signature).map_err(|_| ReportError::InvalidToken)?;
+    signer(key, &token)
+        .verify_slice(&signature)
+        .map_err(|_| ReportError::InvalidToken)?;
+    if token.is_expired(now) {
+        return Err(ReportError::Expired);
+    }
+    Ok(token)
+}
+
+fn signer(key: &[u8], token: &ReportToken) -> HmacSha256 {
+    let mut mac = HmacSha256::new_from_slice(key).expect("HMAC can take a key of any size");
+    // The signature is not part of what is signed
+    let encoded = token.token();
+    let signed = encoded
+        .rsplit_once('.')
+        .map_or(encoded.as_str(), |(body, _)| body);
+    // Keeps reporting tokens from being passed off as other signed tokens, like invites
+    mac.update(b"report\n");
+    mac.update(signed.as_bytes());
+    mac
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, Utc};
+    use squire_lib::identifiers::{PlayerId, RoundId, TournamentId};
+    use uuid::Uuid;
+
+    use super::{sign_report_token, verify_report_token};
+    use crate::api::{ReportError, ReportToken};
+
+    const KEY: &[u8] = b"report signing key";
+
+    fn new_token(expires: Duration) -> ReportToken {
+        sign_report_token(
+            KEY,
+            TournamentId::new(Uuid::new_v4()),
+            RoundId::new(Uuid::new_v4()),
+            PlayerId::new(Uuid::new_v4()),
+            Utc::now() + expires,
+        )
+    }
+
+    #[test]
+    fn report_tokens_round_trip() {
+        let now = Utc::now();
+        let report = new_token(Duration::hours(2));
+        let token = report.token();
+        assert_eq!(ReportToken::from_token(&token), Some(report.clone()));
+        assert_eq!(verify_report_token(KEY, &token, now), Ok(report));
+
+        // Only the signing key can verify the token
+        assert_eq!(
+            verify_report_token(b"another key", &token, now),
+            Err(ReportError::InvalidToken)
+        );
+        // The token stops working once it expires
+        assert_eq!(
+            verify_report_token(KEY, &token, now + Duration::hours(3)),
+            Err(ReportError::Expired)
+        );
+    }
+
+    #[test]
+    fn edited_report_tokens_are_rejected() {
+        let now = Utc::now();
+        let report = new_token(Duration::hours(2));
+
+        // Reporting for another player breaks the signature
+        let mut other = report.clone();
+        other.player = PlayerId::new(Uuid::new_v4());
+        assert_eq!(
+            verify_report_token(KEY, &other.token(), now),
+            Err(ReportError::InvalidToken)
+        );
+
+        // As does extending the expiry
+        let mut later = report;
+        later.expires += Duration::days(1);
+        assert_eq!(
+            verify_report_token(KEY, &later.token(), now),
+            Err(ReportError::InvalidToken)
+        );
+
+        assert_eq!(
+            verify_report_token(KEY, "not a token", now),
+            Err(ReportError::InvalidToken)
+        );
+    }
+}
@@ -0,0 +1,102 @@
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use squire_lib::{identifiers::SeriesId, series::Series, tournament::TournRole};
+
+use super::{
+    invite::InviteHeader,
+    session::{Session, SquireSession, UserSession},
+    tournaments::get_visible_tourn,
+    SquireRouter,
+};
+use crate::{api::*, server::state::ServerState};
+
+pub fn get_routes<S: ServerState>() -> SquireRouter<S> {
+    SquireRouter::new()
+        .add_route::<0, POST, Series, _, _>(save_series::<S>)
+        .add_route::<0, GET, ListSeries, _, _>(list_series::<S>)
+        .add_route::<1, GET, GetSeries, _, _>(get_series::<S>)
+        .add_route::<1, GET, GetSeriesStandings, _, _>(get_series_standings::<S>)
+}
+
+/// Returns every known series.
+///
+/// This api can be accessed via `/api/v1/series/list`.
+pub async fn list_series<S>(State(state): State<S>) -> ListSeriesResponse
+where
+    S: ServerState,
+{
+    ListSeriesResponse::new(state.list_series().await)
+}
+
+/// Returns a series, which includes the ids of all of its events.
+///
+/// This api can be accessed via `/api/v1/series/<id>`.
+pub async fn get_series<S>(State(state): State<S>, Path(id): Path<SeriesId>) -> GetSeriesResponse
+where
+    S: ServerState,
+{
+    GetSeriesResponse::new(state.get_series(id).await)
+}
+
+/// Returns the combined leaderboard of a series, which is calculated from the current standings of
+/// each of its events. Events that can not be found or that the user can not follow are skipped,
+/// and the players' information is redacted in the events that the user is not an official of.
+///
+/// This api can be accessed via `/api/v1/series/<id>/standings`.
+pub async fn get_series_standings<S>(
+    State(state): State<S>,
+    Session(session): Session<SquireSession>,
+    invite: InviteHeader,
+    Path(id): Path<SeriesId>,
+) -> GetSeriesStandingsResponse
+where
+    S: ServerState,
+{
+    let Some(series) = state.get_series(id).await else {
+        return GetSeriesStandingsResponse::new(None);
+    };
+    let mut tourns = Vec::with_capacity(series.events.len());
+    for t_id in series.events.iter().copied() {
+        if let Some(tourn) = get_visible_tourn(&state, &session, &invite, t_id).await {
+            tourns.push(tourn);
+        }
+    }
+    let standings = series.standings(tourns.iter().map(|tourn| tourn.tourn()));
+    GetSeriesStandingsResponse::new(Some(standings))
+}
+
+/// Creates or updates a series. The user that creates a series becomes its owner, and only the
+/// owner can update it. Only tournaments that the owner is an admin of can be added to a series.
+///
+/// This api can be accessed by posting a [Series] to `/api/v1/series`.
+pub async fn save_series<S>(
+    State(state): State<S>,
+    Session(UserSession(u_id)): Session<UserSession>,
+    Json(mut series): Json<Series>,
+) -> SaveSeriesResponse
+where
+    S: ServerState,
+{
+    let saved = state.get_series(series.id).await;
+    if saved.as_ref().is_some_and(|saved| saved.owner != u_id) {
+        return SaveSeriesResponse::new(false);
+    }
+    let added = series.events.iter().filter(|t_id| {
+        saved
+            .as_ref()
+            .map_or(true, |saved| !saved.events.contains(t_id))
+    });
+    for t_id in added {
+        let is_admin = state
+            .get_tourn(*t_id)
+            .await
+            .is_some_and(|tourn| matches!(tourn.user_role(*u_id), TournRole::Admin(_)));
+        if !is_admin {
+            return SaveSeriesResponse::new(false);
+        }
+    }
+    series.owner = u_id;
+    SaveSeriesResponse::new(state.persist_series(&series).await)
+}
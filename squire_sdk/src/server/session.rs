@@ -4,7 +4,6 @@ use axum::{
     extract::FromRequestParts,
     response::{IntoResponse, IntoResponseParts, Response, ResponseParts},
 };
-use hex::decode_to_slice;
 use http::{request::Parts, HeaderMap, StatusCode};
 use squire_lib::identifiers::SquireAccountId;
 use tokio::sync::watch::Receiver;
@@ -43,7 +42,7 @@ impl SessionWatcher {
     pub fn is_valid(&self) -> bool {
         matches!(
             *self.watcher.borrow(),
-            SquireSession::Guest(_) | SquireSession::Active(_)
+            SquireSession::Guest(_) | SquireSession::Active(_) | SquireSession::ApiToken(_)
         )
     }
 
@@ -58,7 +57,7 @@ impl SessionWatcher {
         let session = self.watcher.borrow();
         match *session {
             SquireSession::Guest(ref token) => Some(AuthUser::Guest(token.clone())),
-            SquireSession::Active(id) => Some(AuthUser::User(id)),
+            SquireSession::Active(id) | SquireSession::ApiToken(id) => Some(AuthUser::User(id)),
             _ => None,
         }
     }
@@ -76,6 +75,9 @@ pub enum SquireSession {
     Guest(SessionToken),
     /// Credentials were present and corresponded to a logged-in user
     Active(SquireAccountId),
+    /// An API token of a user was present. These act as the user, but can not manage the user's
+    /// tokens, password, or sessions.
+    ApiToken(SquireAccountId),
     /// Credentials for a user were present but were past the expiry
     Expired(SquireAccountId),
     /// Credentials for a guest were present but were past the expiry
@@ -116,9 +118,10 @@ impl SessionConvert for AnyUser {
             SquireSession::Active(_id) => Ok(AnyUser::Active(token)),
             SquireSession::Expired(_id) => Ok(AnyUser::Expired(token)),
             SquireSession::ExpiredGuest(token) => Ok(AnyUser::ExpiredGuest(token)),
-            SquireSession::NotLoggedIn | SquireSession::UnknownUser => {
-                Err(StatusCode::UNAUTHORIZED)
-            }
+            // API tokens are not sessions, so they can not be reauthed or terminated
+            SquireSession::NotLoggedIn
+            | SquireSession::UnknownUser
+            | SquireSession::ApiToken(_) => Err(StatusCode::UNAUTHORIZED),
         }
     }
 
@@ -159,7 +162,7 @@ impl SessionConvert for UserSession {
 
     fn convert(_token: SessionToken, session: SquireSession) -> Result<Self, Self::Error> {
         match session {
-            SquireSession::Active(id) => Ok(Self(id)),
+            SquireSession::Active(id) | SquireSession::ApiToken(id) => Ok(Self(id)),
             SquireSession::NotLoggedIn => Err(UserSessionError::NotLoggedIn),
             SquireSession::Expired(_) => Err(UserSessionError::Expired),
             SquireSession::UnknownUser => Err(UserSessionError::UnknownUser),
@@ -227,12 +230,10 @@ impl TryFrom<&mut Parts> for SessionToken {
 
     fn try_from(parts: &mut Parts) -> Result<Self, Self::Error> {
         match parts.headers.get(Self::HEADER_NAME) {
-            Some(header) => {
-                let mut inner = [0; 32];
-                let s = header.to_str().map_err(|_| TokenParseError::InvalidToken)?;
-                decode_to_slice(s, &mut inner).map_err(|_| TokenParseError::InvalidToken)?;
-                Ok(Self(inner))
-            }
+            Some(header) => header
+                .to_str()
+                .map_err(|_| TokenParseError::InvalidToken)?
+                .parse(),
             None => Err(TokenParseError::NoAuthHeader),
         }
     }
@@ -267,7 +268,7 @@ impl SessionConvert for AuthUser {
             | SquireSession::Expired(_)
             | SquireSession::ExpiredGuest(_) => Err(StatusCode::UNAUTHORIZED),
             SquireSession::Guest(token) => Ok(Self::Guest(token)),
-            SquireSession::Active(id) => Ok(Self::User(id)),
+            SquireSession::Active(id) | SquireSession::ApiToken(id) => Ok(Self::User(id)),
         }
     }
 
@@ -2,21 +2,66 @@ use std::ops::Range;
 
 use async_trait::async_trait;
 use axum::extract::ws::WebSocket;
-use squire_lib::identifiers::SquireAccountId;
+use chrono::{DateTime, Utc};
+use squire_lib::{
+    error::TournamentError,
+    identifiers::{SeriesId, SquireAccountId},
+    operations::TournOp,
+    series::Series,
+};
 
-use super::session::{AnyUser, SessionWatcher, SquireSession};
+use super::{
+    keys::KeyPurpose,
+    session::{AnyUser, SessionWatcher, SquireSession},
+};
 use crate::{
-    api::{SessionToken, TournamentSummary, Version},
+    api::{
+        LifecycleEvent, SessionToken, TournamentFilter, TournamentSummary, Version, Webhook,
+        WebhookId,
+    },
     model::tournament::TournamentId,
-    sync::TournamentManager,
+    sync::{Compression, TournamentManager},
 };
 
+/// Which node hosts a tournament's gathering when several servers share a database
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GatheringNode {
+    /// This node hosts the gathering
+    Local,
+    /// Another node hosts the gathering, and this is its address
+    Remote(String),
+    /// It is not known which node hosts the gathering, e.g. because the database could not be
+    /// reached. No node should take the gathering on until this is known.
+    Unknown,
+}
+
+/// The reasons that the server could not apply an operation to a tournament
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ApplyOpError {
+    /// The tournament could not be found
+    NotFound,
+    /// Another node hosts the tournament's gathering, so only it can change the tournament. This
+    /// is the node's address, if it is known. The operation can be retried once the lease moves.
+    NotOwner(Option<String>),
+    /// The tournament rejected the operation
+    Rejected(TournamentError),
+}
+
 #[async_trait]
 pub trait ServerState: 'static + Clone + Send + Sync {
     fn get_version(&self) -> Version;
 
+    /// The secret key used to sign and verify invites, report tokens, or tournament exports
+    fn signing_key(&self, purpose: KeyPurpose) -> &[u8];
+
     /* ------ Tournament-related methods ------ */
-    async fn get_tourn_summaries(&self, including: Range<usize>) -> Vec<TournamentSummary>;
+    /// Returns the summaries of the tournaments that match the filter, starting with the latest
+    /// registered one, along with the total number of matching tournaments
+    async fn get_tourn_summaries(
+        &self,
+        filter: &TournamentFilter,
+        including: Range<usize>,
+    ) -> (Vec<TournamentSummary>, usize);
 
     async fn get_tourn(&self, id: TournamentId) -> Option<TournamentManager>;
 
@@ -36,16 +81,64 @@ pub trait ServerState: 'static + Clone + Send + Sync {
         digest
     }
 
-    async fn handle_new_onlooker(&self, id: TournamentId, user: SessionWatcher, ws: WebSocket);
+    /// Returns the node that hosts the tournament's gathering. New connections to gatherings that
+    /// are hosted by other nodes are proxied to those nodes. Servers that run as a single node
+    /// always host their own gatherings.
+    async fn gathering_node(&self, _id: TournamentId) -> GatheringNode {
+        GatheringNode::Local
+    }
+
+    /// Adds a user to the tournament's gathering. Messages sent to the user are compressed with
+    /// the given scheme.
+    async fn handle_new_onlooker(
+        &self,
+        id: TournamentId,
+        user: SessionWatcher,
+        compression: Compression,
+        ws: WebSocket,
+    );
+
+    /// Applies an operation to the tournament on behalf of the server (i.e. one that was not
+    /// synced by a client) and forwards it to everyone in the tournament's gathering. Only the node
+    /// that hosts the gathering can do this, so `NotOwner` is returned on any other node.
+    async fn apply_op(&self, id: TournamentId, op: TournOp) -> Result<(), ApplyOpError>;
+
+    /* ------ Lifecycle-related methods ------ */
+    async fn record_events(&self, events: Vec<LifecycleEvent>);
+
+    async fn get_org_events(&self, org: SquireAccountId) -> Vec<LifecycleEvent>;
+
+    /* ------ Series-related methods ------ */
+    async fn list_series(&self) -> Vec<Series>;
+
+    async fn get_series(&self, id: SeriesId) -> Option<Series>;
+
+    async fn persist_series(&self, series: &Series) -> bool;
+
+    /* ------ Webhook-related methods ------ */
+    /// Returns the webhooks that are registered for the tournament
+    async fn list_webhooks(&self, tourn: TournamentId) -> Vec<Webhook>;
+
+    async fn persist_webhook(&self, hook: &Webhook) -> bool;
+
+    /// Removes one of the tournament's webhooks. Returns `false` if the tournament has no webhook
+    /// with that id.
+    async fn delete_webhook(&self, tourn: TournamentId, id: WebhookId) -> bool;
 
     /* ------ Session-related methods ------ */
     async fn create_session(&self, id: SquireAccountId) -> SessionToken;
 
     async fn guest_session(&self) -> SessionToken;
 
+    /// Creates a guest session for a connection handle, which expires once the handle does.
+    /// Returns the session's token and when it expires.
+    async fn handoff_session(&self) -> (SessionToken, DateTime<Utc>);
+
     async fn get_session(&self, token: SessionToken) -> SquireSession;
 
-    async fn reauth_session(&self, session: AnyUser) -> SessionToken;
+    /// Issues a new session token to replace the given session. Returns `None` if the session can
+    /// not be reauthenticated, i.e. it has expired for good.
+    async fn reauth_session(&self, session: AnyUser) -> Option<SessionToken>;
 
     async fn terminate_session(&self, session: AnyUser) -> bool;
 
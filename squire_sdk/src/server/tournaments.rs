@@ -5,29 +5,78 @@ use axum::{
         ws::{Message, WebSocket},
         Path, Query, State, WebSocketUpgrade,
     },
-    response::Response,
+    response::{Html, IntoResponse, Response},
     Json,
 };
-use http::StatusCode;
-use squire_lib::tournament::TournamentId;
+use chrono::Utc;
+use futures::{SinkExt, StreamExt};
+use http::{header, StatusCode};
+use squire_lib::{
+    admin::TournOfficialId,
+    collation::NameCollator,
+    identifiers::{PlayerId, SquireAccountId},
+    operations::{PlayerOp, TournOp},
+    seating::SeatMap,
+    tournament::{SeedOverrides, TournRole, TournamentId},
+};
+use tokio_tungstenite::tungstenite::Message as NodeMessage;
+use uuid::Uuid;
 
 use super::{
-    session::{AnyUser, Session, SessionConvert, UserSession},
+    export::{sign_export, verify_export},
+    fetch::{check_public_url, FetchError},
+    import::{plan_import, read_source},
+    invite::{can_follow, is_revoked, sign_invite, verify_invite, InviteHeader},
+    keys::KeyPurpose,
+    report::{sign_report_token, verify_report_token},
+    session::{AnyUser, Session, SessionConvert, SquireSession, UserSession},
+    webhooks::new_webhook_secret,
     SquireRouter,
 };
-use crate::{api::*, compat::sleep, server::state::ServerState, sync::TournamentManager};
+use crate::{
+    api::*,
+    compat::sleep,
+    response::SquireResponse,
+    server::state::{ApplyOpError, GatheringNode, ServerState},
+    sync::{Handshake, TournamentManager},
+};
 
 pub fn get_routes<S: ServerState>() -> SquireRouter<S> {
     SquireRouter::new()
         .add_route::<0, POST, TournamentManager, _, _>(import_tournament::<S>)
         .add_route::<1, GET, ListTournaments, _, _>(get_tournament_list::<S>)
         .add_route::<1, GET, GetTournament, _, _>(get_tournament::<S>)
+        .add_route::<1, GET, GetSeatMap, _, _>(get_seat_map::<S>)
+        .add_route::<1, GET, GetStandings, _, _>(get_standings::<S>)
+        .add_route::<2, GET, GetFlightStandings, _, _>(get_flight_standings::<S>)
+        .add_route::<2, GET, GetGroupStandings, _, _>(get_group_standings::<S>)
+        .add_route::<2, GET, GetPairings, _, _>(get_pairings::<S>)
+        .add_route::<1, GET, GetPlayers, _, _>(get_players::<S>)
+        .add_route::<1, GET, GetSchedule, _, _>(get_schedule::<S>)
+        .add_route::<1, GET, GetVenue, _, _>(get_venue::<S>)
+        .add_route::<1, GET, GetPayouts, _, _>(get_payouts::<S>)
+        .add_route::<1, GET, GetMyRound, _, _>(get_my_round::<S>)
+        .add_route::<1, GET, GetReport, _, _>(get_report::<S>)
+        .add_route::<2, GET, ExplainScore, _, _>(explain_score::<S>)
+        .add_route::<1, GET, GetAuditLog, _, _>(get_audit_log::<S>)
+        .add_route::<1, GET, ExportTournament, _, _>(export_tournament::<S>)
+        .add_route::<0, POST, SignedExport, _, _>(verify_tournament_export::<S>)
+        .add_route::<1, POST, ImportRegistrations, _, _>(import_registrations::<S>)
+        .add_route::<1, POST, CreateInvite, _, _>(create_invite::<S>)
+        .add_route::<0, POST, CheckInvite, _, _>(check_invite::<S>)
+        .add_route::<1, POST, CreateReportTokens, _, _>(create_report_tokens::<S>)
+        .add_route::<0, POST, ReportResult, _, _>(report_result::<S>)
+        .add_route::<1, POST, SeedOverrides, _, _>(clone_tournament::<S>)
+        .add_route::<1, POST, RegisterWebhook, _, _>(register_webhook::<S>)
+        .add_route::<1, GET, ListWebhooks, _, _>(list_webhooks::<S>)
+        .add_route::<2, DELETE, DeleteWebhook, _, _>(delete_webhook::<S>)
         .add_route::<1, GET, Subscribe, _, _>(join_gathering::<S>)
 }
 
-/// Returns a list of [TournamentSummary], which can be used to see information about a collection
+/// Returns a page of [TournamentSummary], which can be used to see information about a collection
 /// of tournaments at a glance and query more information about a particular tournament using the
-/// `id` field in [get_tournament].
+/// `id` field in [get_tournament]. The page also holds the total number of tournaments that match
+/// the filter, so clients know how many pages there are.
 ///
 /// This api can be accessed via `/api/v1/tournaments/list/<page>[?page_size=number]`. For example,
 /// `/api/v1/tournaments/list/0` will give back at most 20 tournament summaries, starting from the
@@ -36,36 +85,628 @@ pub fn get_routes<S: ServerState>() -> SquireRouter<S> {
 /// return from), but it almost always will. In the same way, accessing
 /// `/api/v1/tournaments/list/0?page_size=10` will give back at most 10 summaries, starting from the
 /// latest registered tournament possible.
+///
+/// The list can be narrowed down using the query parameters of [TournamentFilter]. For example,
+/// `/api/v1/tournaments/list/0?status=Started&format=Pioneer` only lists the Pioneer tournaments
+/// that are underway.
 pub async fn get_tournament_list<S>(
     State(state): State<S>,
     Path(page): Path<usize>,
     Query(ListPageSize { page_size }): Query<ListPageSize>,
+    Query(filter): Query<TournamentFilter>,
 ) -> ListTournamentsResponse
 where
     S: ServerState,
 {
-    dbg!(page_size);
     let offset = page * page_size;
-    ListTournamentsResponse::new(
-        state
-            .get_tourn_summaries(offset..(offset + page_size))
-            .await,
-    )
+    let (summaries, total) = state
+        .get_tourn_summaries(&filter, offset..(offset + page_size))
+        .await;
+    ListTournamentsResponse::new(TournamentPage {
+        summaries,
+        page,
+        page_size,
+        total,
+    })
 }
 
+/// Returns the tournament. Unless the user is one of the tournament's officials, the players'
+/// information is redacted according to their sharing permissions (see
+/// [TournamentManager::redacted]).
 pub async fn get_tournament<S>(
     State(state): State<S>,
+    Session(session): Session<SquireSession>,
+    invite: InviteHeader,
     Path(id): Path<TournamentId>,
 ) -> GetTournamentResponse
 where
     S: ServerState,
 {
-    GetTournamentResponse::new(state.get_tourn(id).await)
+    GetTournamentResponse::new(get_visible_tourn(&state, &session, &invite, id).await)
+}
+
+/// Fetches the tournament if the user can follow it (see [can_follow]). Private tournaments need
+/// an invite unless the user is one of their officials or players.
+async fn get_followed_tourn<S>(
+    state: &S,
+    session: &SquireSession,
+    invite: &InviteHeader,
+    id: TournamentId,
+) -> Option<TournamentManager>
+where
+    S: ServerState,
+{
+    let tourn = state.get_tourn(id).await?;
+    let user = match session {
+        SquireSession::Active(u_id) | SquireSession::ApiToken(u_id) => Some(*u_id),
+        _ => None,
+    };
+    let key = state.signing_key(KeyPurpose::Invite);
+    can_follow(key, &tourn, user, invite.token(), Utc::now()).then_some(tourn)
+}
+
+/// Fetches the tournament if the user can follow it, redacting the players' information if the
+/// user is not one of the tournament's officials
+pub(super) async fn get_visible_tourn<S>(
+    state: &S,
+    session: &SquireSession,
+    invite: &InviteHeader,
+    id: TournamentId,
+) -> Option<TournamentManager>
+where
+    S: ServerState,
+{
+    let tourn = get_followed_tourn(state, session, invite, id).await?;
+    Some(visible_copy(tourn, session))
+}
+
+/// Redacts the players' information from the tournament if the user is not one of its officials
+fn visible_copy(tourn: TournamentManager, session: &SquireSession) -> TournamentManager {
+    let is_official = match session {
+        SquireSession::Active(u_id) | SquireSession::ApiToken(u_id) => {
+            tourn.user_role(u_id.0).is_official()
+        }
+        _ => false,
+    };
+    if is_official {
+        tourn
+    } else {
+        tourn.redacted()
+    }
+}
+
+/// Returns the seating of all active players across draft pods, for posting at the venue.
+///
+/// This api can be accessed via `/api/v1/tournaments/<id>/seating[?pod_size=number&format=fmt]`,
+/// where `fmt` is one of `json` (the default), `html`, or `csv`. Pods hold at most 8 players unless
+/// `pod_size` is given.
+pub async fn get_seat_map<S>(
+    State(state): State<S>,
+    Session(session): Session<SquireSession>,
+    invite: InviteHeader,
+    Path(id): Path<TournamentId>,
+    Query(SeatMapQuery { pod_size, format }): Query<SeatMapQuery>,
+) -> Response
+where
+    S: ServerState,
+{
+    let Some(tourn) = get_visible_tourn(&state, &session, &invite, id).await else {
+        return SquireResponse::new(None::<SeatMap>).into_response();
+    };
+    let seats = tourn.seat_map(pod_size);
+    match format {
+        SeatMapFormat::Json => SquireResponse::new(Some(seats)).into_response(),
+        SeatMapFormat::Html => Html(seats.to_html(&tourn.name, "")).into_response(),
+        SeatMapFormat::Csv => {
+            ([(header::CONTENT_TYPE, "text/csv")], seats.to_csv()).into_response()
+        }
+    }
+}
+
+/// Returns the standings of the tournament, which are recalculated from every certified round.
+/// Each player's place is given with their name and scores, so the standings can be shown without
+/// syncing the tournament.
+///
+/// This api can be accessed via `/api/v1/tournaments/<id>/standings`.
+pub async fn get_standings<S>(
+    State(state): State<S>,
+    Session(session): Session<SquireSession>,
+    invite: InviteHeader,
+    Path(id): Path<TournamentId>,
+) -> GetStandingsResponse
+where
+    S: ServerState,
+{
+    let Some(tourn) = get_visible_tourn(&state, &session, &invite, id).await else {
+        return GetStandingsResponse::new(None);
+    };
+    GetStandingsResponse::new(Some(PublicStanding::standings(&tourn)))
+}
+
+/// Returns the current standings of one of the tournament's flights (starting at 1), such as a
+/// consolation flight. Only the rounds that were played in the flight are counted.
+///
+/// This api can be accessed via `/api/v1/tournaments/<id>/flights/<flight>/standings`.
+pub async fn get_flight_standings<S>(
+    State(state): State<S>,
+    Session(session): Session<SquireSession>,
+    invite: InviteHeader,
+    Path((id, flight)): Path<(TournamentId, usize)>,
+) -> GetFlightStandingsResponse
+where
+    S: ServerState,
+{
+    let standings = get_visible_tourn(&state, &session, &invite, id)
+        .await
+        .filter(|tourn| tourn.pairing_sys.flights.get(flight).is_some())
+        .map(|tourn| PublicStanding::flight_standings(&tourn, flight));
+    GetFlightStandingsResponse::new(standings)
+}
+
+/// Returns the current standings within each of the tournament's draft pods, in pod order. The top
+/// `qualifiers` players of each pod are marked as qualifying for the next stage. An empty list is
+/// returned if the tournament has no pods.
+///
+/// This api can be accessed via `/api/v1/tournaments/<id>/pods/standings/<qualifiers>`.
+pub async fn get_group_standings<S>(
+    State(state): State<S>,
+    Session(session): Session<SquireSession>,
+    invite: InviteHeader,
+    Path((id, qualifiers)): Path<(TournamentId, usize)>,
+) -> GetGroupStandingsResponse
+where
+    S: ServerState,
+{
+    let standings = get_visible_tourn(&state, &session, &invite, id)
+        .await
+        .map(|tourn| PublicStanding::group_standings(&tourn, qualifiers));
+    GetGroupStandingsResponse::new(standings)
+}
+
+/// Returns the rounds of one of the tournament's pairing rounds (starting at 1), ordered by match
+/// number. Rounds that were created by hand are not part of any pairing round. An empty list is
+/// returned if the pairing round does not exist yet.
+///
+/// This api can be accessed via `/api/v1/tournaments/<id>/pairings/<round>`.
+pub async fn get_pairings<S>(
+    State(state): State<S>,
+    Session(session): Session<SquireSession>,
+    invite: InviteHeader,
+    Path((id, round)): Path<(TournamentId, u64)>,
+) -> GetPairingsResponse
+where
+    S: ServerState,
+{
+    let pairings = get_visible_tourn(&state, &session, &invite, id)
+        .await
+        .map(|tourn| {
+            let mut rounds: Vec<_> = tourn
+                .round_reg
+                .rounds
+                .values()
+                .filter(|rnd| rnd.context.pairing_round() == Some(round))
+                .collect();
+            rounds.sort_by_key(|rnd| rnd.match_number);
+            rounds
+                .into_iter()
+                .map(|rnd| PublicPairing::new(&tourn, rnd))
+                .collect()
+        });
+    GetPairingsResponse::new(pairings)
+}
+
+/// Returns every player that has registered for the tournament, including those that have
+/// dropped, ordered by name.
+///
+/// This api can be accessed via `/api/v1/tournaments/<id>/players`.
+pub async fn get_players<S>(
+    State(state): State<S>,
+    Session(session): Session<SquireSession>,
+    invite: InviteHeader,
+    Path(id): Path<TournamentId>,
+) -> GetPlayersResponse
+where
+    S: ServerState,
+{
+    let players = get_visible_tourn(&state, &session, &invite, id)
+        .await
+        .map(|tourn| {
+            tourn
+                .player_reg
+                .get_players_by_name(&NameCollator::new())
+                .into_iter()
+                .map(PublicPlayer::from)
+                .collect()
+        });
+    GetPlayersResponse::new(players)
+}
+
+/// Returns when each of the tournament's pairing rounds starts, including the rounds of its second
+/// day. Rounds that have not been given a time are estimated from the round before them.
+///
+/// This api can be accessed via `/api/v1/tournaments/<id>/schedule`.
+pub async fn get_schedule<S>(
+    State(state): State<S>,
+    Session(session): Session<SquireSession>,
+    invite: InviteHeader,
+    Path(id): Path<TournamentId>,
+) -> GetScheduleResponse
+where
+    S: ServerState,
+{
+    let schedule = get_visible_tourn(&state, &session, &invite, id)
+        .await
+        .map(|tourn| tourn.schedule());
+    GetScheduleResponse::new(schedule)
+}
+
+/// Returns a floor map of the tournament's venue, which shows the round that is seated at each of
+/// its tables. Tournaments without a venue have a map without any zones.
+///
+/// This api can be accessed via `/api/v1/tournaments/<id>/venue`.
+pub async fn get_venue<S>(
+    State(state): State<S>,
+    Session(session): Session<SquireSession>,
+    invite: InviteHeader,
+    Path(id): Path<TournamentId>,
+) -> GetVenueResponse
+where
+    S: ServerState,
+{
+    let map = get_visible_tourn(&state, &session, &invite, id)
+        .await
+        .map(|tourn| tourn.round_reg.venue_map());
+    GetVenueResponse::new(map)
+}
+
+/// Returns how much each player is paid from the tournament's prize pool, based on the current
+/// standings. Tournaments without a prize structure pay no one.
+///
+/// This api can be accessed via `/api/v1/tournaments/<id>/payouts`.
+pub async fn get_payouts<S>(
+    State(state): State<S>,
+    Session(session): Session<SquireSession>,
+    invite: InviteHeader,
+    Path(id): Path<TournamentId>,
+) -> GetPayoutsResponse
+where
+    S: ServerState,
+{
+    let payouts = get_visible_tourn(&state, &session, &invite, id)
+        .await
+        .map(|tourn| tourn.payouts());
+    GetPayoutsResponse::new(payouts)
+}
+
+/// Returns the requesting player's current round, i.e. their table, opponents, and time left, so
+/// that a player can find their seat without syncing the whole tournament. If the player is in
+/// several active rounds, the one with the lowest match number is returned.
+///
+/// This api can be accessed via `/api/v1/tournaments/<id>/my-round`. Only the tournament's players
+/// can use it.
+pub async fn get_my_round<S>(
+    State(state): State<S>,
+    Session(UserSession(u_id)): Session<UserSession>,
+    Path(id): Path<TournamentId>,
+) -> GetMyRoundResponse
+where
+    S: ServerState,
+{
+    let my_round = state.get_tourn(id).await.and_then(|tourn| {
+        let TournRole::Player(p_id) = tourn.user_role(*u_id) else {
+            return None;
+        };
+        tourn
+            .round_reg
+            .rounds
+            .values()
+            .filter(|rnd| rnd.is_active() && rnd.players.contains(&p_id))
+            .min_by_key(|rnd| rnd.match_number)
+            .map(|rnd| MyRound::new(&tourn, rnd, p_id, Utc::now()))
+    });
+    GetMyRoundResponse::new(my_round)
+}
+
+/// Returns a printable sheet for posting at the venue, i.e. the current pairings or standings.
+///
+/// This api can be accessed via `/api/v1/tournaments/<id>/report[?kind=kind]`, where `kind` is one
+/// of `pairings_by_table` (the default), `pairings_by_name`, or `standings`. The sheet is an HTML
+/// page, which can be printed or saved as a PDF from a browser.
+pub async fn get_report<S>(
+    State(state): State<S>,
+    Session(session): Session<SquireSession>,
+    invite: InviteHeader,
+    Path(id): Path<TournamentId>,
+    Query(ReportQuery { kind }): Query<ReportQuery>,
+) -> Response
+where
+    S: ServerState,
+{
+    match get_visible_tourn(&state, &session, &invite, id).await {
+        Some(tourn) => Html(tourn.report_html(kind, "")).into_response(),
+        None => StatusCode::NOT_FOUND.into_response(),
+    }
+}
+
+/// Explains how a player's score and tiebreakers were calculated, i.e. which rounds counted and
+/// what each of their opponents contributed to their opponent win percentages.
+///
+/// This api can be accessed via `/api/v1/tournaments/<id>/standings/<player id>`.
+pub async fn explain_score<S>(
+    State(state): State<S>,
+    Session(session): Session<SquireSession>,
+    invite: InviteHeader,
+    Path((id, p_id)): Path<(TournamentId, PlayerId)>,
+) -> ExplainScoreResponse
+where
+    S: ServerState,
+{
+    let explanation = get_followed_tourn(&state, &session, &invite, id)
+        .await
+        .and_then(|tourn| tourn.explain_score(&p_id).ok());
+    ExplainScoreResponse::new(explanation)
+}
+
+/// Returns every operation that the tournament's judges and admins have applied, oldest first, so
+/// that organizers can review who dropped a player or overrode a result.
+///
+/// This api can be accessed via `/api/v1/tournaments/<id>/audit`. The entries can be narrowed down
+/// using the query parameters of [AuditFilter], e.g. `/api/v1/tournaments/<id>/audit?player=<p_id>`.
+/// Only the tournament's admins and judges can use it.
+pub async fn get_audit_log<S>(
+    State(state): State<S>,
+    Session(UserSession(u_id)): Session<UserSession>,
+    Path(id): Path<TournamentId>,
+    Query(filter): Query<AuditFilter>,
+) -> GetAuditLogResponse
+where
+    S: ServerState,
+{
+    let log = state
+        .get_tourn(id)
+        .await
+        .and_then(|tourn| match tourn.user_role(*u_id) {
+            TournRole::Admin(_) | TournRole::Judge(_) => Some(
+                tourn
+                    .audit_log()
+                    .into_iter()
+                    .filter(|entry| filter.matches(entry))
+                    .collect(),
+            ),
+            TournRole::Scorekeeper(_) | TournRole::Player(_) | TournRole::Spectator => None,
+        });
+    GetAuditLogResponse::new(log)
+}
+
+/// Returns the tournament along with a signature of its final state and operations log. The
+/// signed export can later be checked using [verify_tournament_export]. Unless the user is one of
+/// the tournament's officials, the tournament is redacted before it is signed, just as it is for
+/// [get_tournament].
+///
+/// This api can be accessed via `/api/v1/tournaments/<id>/export`.
+pub async fn export_tournament<S>(
+    State(state): State<S>,
+    Session(session): Session<SquireSession>,
+    invite: InviteHeader,
+    Path(id): Path<TournamentId>,
+) -> ExportTournamentResponse
+where
+    S: ServerState,
+{
+    let export = get_visible_tourn(&state, &session, &invite, id)
+        .await
+        .map(|tourn| sign_export(state.signing_key(KeyPurpose::Export), tourn));
+    ExportTournamentResponse::new(export)
+}
+
+/// Checks that a tournament export was signed by this server and has not been edited since.
+///
+/// This api can be accessed by posting a [SignedExport] to `/api/v1/tournaments/verify`.
+pub async fn verify_tournament_export<S>(
+    State(state): State<S>,
+    Json(export): Json<SignedExport>,
+) -> VerifyExportResponse
+where
+    S: ServerState,
+{
+    let key = state.signing_key(KeyPurpose::Export);
+    VerifyExportResponse::new(verify_export(key, &export))
+}
+
+/// Reads a registration sheet (e.g. a published Google Sheet) and creates the operations that
+/// register each row as a player. Nothing is applied to the tournament here; the client reviews the
+/// report and sends the operations through the tournament's gathering.
+///
+/// This api can be accessed by posting an [ImportRegistrations] to
+/// `/api/v1/tournaments/<id>/import`. Only the tournament's admins and judges can use it.
+pub async fn import_registrations<S>(
+    State(state): State<S>,
+    Session(UserSession(u_id)): Session<UserSession>,
+    Path(id): Path<TournamentId>,
+    Json(req): Json<ImportRegistrations>,
+) -> ImportRegistrationsResponse
+where
+    S: ServerState,
+{
+    let Some(tourn) = state.get_tourn(id).await else {
+        return ImportRegistrationsResponse::new(Err(ImportError::TournamentNotFound));
+    };
+    let official: TournOfficialId = match tourn.user_role(*u_id) {
+        TournRole::Admin(a_id) => a_id.into(),
+        TournRole::Judge(j_id) => j_id.into(),
+        TournRole::Scorekeeper(_) | TournRole::Player(_) | TournRole::Spectator => {
+            return ImportRegistrationsResponse::new(Err(ImportError::Unauthorized))
+        }
+    };
+    let digest = match read_source(req.source).await {
+        Ok(sheet) => plan_import(&tourn, official, &sheet, &req.columns),
+        Err(err) => Err(err),
+    };
+    ImportRegistrationsResponse::new(digest)
+}
+
+/// Creates a signed invite to the tournament that grants the requested role. The invite's token
+/// can be shared as part of a link, so officials don't need to look up the invitees' accounts.
+///
+/// This api can be accessed by posting a [CreateInvite] to `/api/v1/tournaments/<id>/invite`.
+/// Admins can invite users to any role, but judges can only invite players and spectators.
+pub async fn create_invite<S>(
+    State(state): State<S>,
+    Session(UserSession(u_id)): Session<UserSession>,
+    Path(id): Path<TournamentId>,
+    Json(req): Json<CreateInvite>,
+) -> InviteResponse
+where
+    S: ServerState,
+{
+    let Some(tourn) = state.get_tourn(id).await else {
+        return InviteResponse::new(Err(InviteError::TournamentNotFound));
+    };
+    let allowed = match tourn.user_role(*u_id) {
+        TournRole::Admin(_) => true,
+        TournRole::Judge(_) => req.role != InviteRole::Judge,
+        TournRole::Scorekeeper(_) | TournRole::Player(_) | TournRole::Spectator => false,
+    };
+    if !allowed {
+        return InviteResponse::new(Err(InviteError::Unauthorized));
+    }
+    if req.expires <= Utc::now() {
+        return InviteResponse::new(Err(InviteError::Expired));
+    }
+    InviteResponse::new(Ok(sign_invite(
+        state.signing_key(KeyPurpose::Invite),
+        id,
+        req.role,
+        req.expires,
+    )))
+}
+
+/// Checks that an invite token was signed by this server, has not expired or been revoked, and is
+/// for a tournament that still exists. The decoded invite is returned so that the client knows what
+/// it grants.
+///
+/// This api can be accessed by posting a [CheckInvite] to `/api/v1/tournaments/invite/check`.
+pub async fn check_invite<S>(
+    State(state): State<S>,
+    Json(CheckInvite { token }): Json<CheckInvite>,
+) -> InviteResponse
+where
+    S: ServerState,
+{
+    let invite = match verify_invite(state.signing_key(KeyPurpose::Invite), &token, Utc::now()) {
+        Ok(invite) => invite,
+        Err(err) => return InviteResponse::new(Err(err)),
+    };
+    match state.get_tourn(invite.tourn).await {
+        None => InviteResponse::new(Err(InviteError::TournamentNotFound)),
+        Some(tourn) if is_revoked(&tourn, &invite) => {
+            InviteResponse::new(Err(InviteError::Revoked))
+        }
+        Some(_) => InviteResponse::new(Ok(invite)),
+    }
+}
+
+/// Creates a signed reporting token for each player in a round. Each token lets its player report
+/// the round's result without logging in, so officials of paper events can print them as QR codes
+/// on the match slips.
+///
+/// This api can be accessed by posting a [CreateReportTokens] to
+/// `/api/v1/tournaments/<id>/report_tokens`. Only the tournament's officials can use it.
+pub async fn create_report_tokens<S>(
+    State(state): State<S>,
+    Session(UserSession(u_id)): Session<UserSession>,
+    Path(id): Path<TournamentId>,
+    Json(req): Json<CreateReportTokens>,
+) -> CreateReportTokensResponse
+where
+    S: ServerState,
+{
+    let Some(tourn) = state.get_tourn(id).await else {
+        return CreateReportTokensResponse::new(Err(ReportError::TournamentNotFound));
+    };
+    match tourn.user_role(*u_id) {
+        TournRole::Admin(_) | TournRole::Scorekeeper(_) | TournRole::Judge(_) => {}
+        TournRole::Player(_) | TournRole::Spectator => {
+            return CreateReportTokensResponse::new(Err(ReportError::Unauthorized))
+        }
+    }
+    if req.expires <= Utc::now() {
+        return CreateReportTokensResponse::new(Err(ReportError::Expired));
+    }
+    let rnd = match tourn.round_reg.get_round(&req.round) {
+        Ok(rnd) => rnd,
+        Err(err) => return CreateReportTokensResponse::new(Err(ReportError::NotRecorded(err))),
+    };
+    let key = state.signing_key(KeyPurpose::Report);
+    let tokens = rnd
+        .players
+        .iter()
+        .map(|p_id| sign_report_token(key, id, rnd.id, *p_id, req.expires))
+        .collect();
+    CreateReportTokensResponse::new(Ok(tokens))
+}
+
+/// Checks a reporting token and records the result on behalf of the player that the token was
+/// created for. The result is applied by the tournament's gathering, so everyone following the
+/// tournament sees it right away.
+///
+/// This api can be accessed by posting a [ReportResult] to `/api/v1/tournaments/report`. No login
+/// is needed, as the token itself proves who is reporting.
+pub async fn report_result<S>(
+    State(state): State<S>,
+    Json(ReportResult { token, result }): Json<ReportResult>,
+) -> ReportResultResponse
+where
+    S: ServerState,
+{
+    let key = state.signing_key(KeyPurpose::Report);
+    let token = match verify_report_token(key, &token, Utc::now()) {
+        Ok(token) => token,
+        Err(err) => return ReportResultResponse::new(Err(err)),
+    };
+    let op = TournOp::PlayerOp(token.player, PlayerOp::RecordResult(token.round, result));
+    let digest = match state.apply_op(token.tourn, op).await {
+        Ok(()) => Ok(()),
+        Err(ApplyOpError::Rejected(err)) => Err(ReportError::NotRecorded(err)),
+        Err(ApplyOpError::NotOwner(_)) => Err(ReportError::NotOwner),
+        Err(ApplyOpError::NotFound) => Err(ReportError::TournamentNotFound),
+    };
+    ReportResultResponse::new(digest)
+}
+
+/// Creates a new tournament with the same settings and officials as the given tournament, and
+/// optionally the same players (see [TournamentManager::clone_as_new]). The id of the new
+/// tournament is returned.
+///
+/// This api can be accessed by posting a [SeedOverrides] to `/api/v1/tournaments/<id>/clone`.
+/// Only the tournament's admins can use it.
+pub async fn clone_tournament<S>(
+    State(state): State<S>,
+    Session(UserSession(u_id)): Session<UserSession>,
+    Path(id): Path<TournamentId>,
+    Json(overrides): Json<SeedOverrides>,
+) -> CloneTournamentResponse
+where
+    S: ServerState,
+{
+    let tourn = match state.get_tourn(id).await {
+        Some(tourn) if matches!(tourn.user_role(*u_id), TournRole::Admin(_)) => tourn,
+        _ => return CloneTournamentResponse::new(None),
+    };
+    let digest = tourn.clone_as_new(overrides);
+    if !state.persist_tourn(&digest).await {
+        return CloneTournamentResponse::new(None);
+    }
+    let (_, org) = digest.seed_and_creator();
+    state
+        .record_events(vec![LifecycleEvent::created(org.id, digest.id, u_id)])
+        .await;
+    CloneTournamentResponse::new(Some(digest.id))
 }
 
 pub async fn import_tournament<S>(
     State(state): State<S>,
-    _user: Session<UserSession>,
+    Session(UserSession(u_id)): Session<UserSession>,
     Json(tourn): Json<TournamentManager>,
 ) -> StatusCode
 where
@@ -75,6 +716,10 @@ where
         Some(_) => StatusCode::BAD_REQUEST,
         None => {
             let _ = state.persist_tourn(&tourn).await;
+            let (_, org) = tourn.seed_and_creator();
+            state
+                .record_events(vec![LifecycleEvent::created(org.id, tourn.id, u_id)])
+                .await;
             StatusCode::OK
         }
     }
@@ -86,12 +731,72 @@ pub async fn join_gathering<S: ServerState>(
     ws: WebSocketUpgrade,
     Path(id): Path<TournamentId>,
 ) -> Response {
-    ws.on_upgrade(move |ws| handle_new_onlooker(state, id, ws))
+    ws.on_upgrade(move |ws| async move {
+        match state.gathering_node(id).await {
+            GatheringNode::Local => handle_new_onlooker(state, id, ws).await,
+            GatheringNode::Remote(node) => proxy_onlooker(node, id, ws).await,
+            GatheringNode::Unknown => {
+                tracing::warn!(tourn = %id, "Could not tell which node hosts the gathering");
+                _ = ws.close().await;
+            }
+        }
+    })
+}
+
+/// Passes messages between a user and the node that hosts the tournament's gathering, which lets
+/// users connect to any node. Each side of the proxy answers its own pings, so only data and close
+/// frames are passed along.
+async fn proxy_onlooker(node: String, id: TournamentId, ws: WebSocket) {
+    let url = format!("{node}{}", Subscribe::ROUTE.replace([&id.to_string()]));
+    let upstream = match tokio_tungstenite::connect_async(url).await {
+        Ok((upstream, _)) => upstream,
+        Err(err) => {
+            tracing::warn!(tourn = %id, %node, "Could not reach the gathering's node: {err}");
+            return;
+        }
+    };
+    tracing::debug!(tourn = %id, %node, "Proxying connection to the gathering's node");
+    let (mut node_sink, mut node_stream) = upstream.split();
+    let (mut user_sink, mut user_stream) = ws.split();
+    let to_node = async {
+        while let Some(Ok(msg)) = user_stream.next().await {
+            let msg = match msg {
+                Message::Binary(bytes) => NodeMessage::Binary(bytes),
+                Message::Text(text) => NodeMessage::Text(text),
+                Message::Close(_) => break,
+                Message::Ping(_) | Message::Pong(_) => continue,
+            };
+            if node_sink.send(msg).await.is_err() {
+                break;
+            }
+        }
+        _ = node_sink.close().await;
+    };
+    let to_user = async {
+        while let Some(Ok(msg)) = node_stream.next().await {
+            let msg = match msg {
+                NodeMessage::Binary(bytes) => Message::Binary(bytes),
+                NodeMessage::Text(text) => Message::Text(text),
+                NodeMessage::Close(_) => break,
+                NodeMessage::Ping(_) | NodeMessage::Pong(_) | NodeMessage::Frame(_) => continue,
+            };
+            if user_sink.send(msg).await.is_err() {
+                break;
+            }
+        }
+        _ = user_sink.close().await;
+    };
+    // Once either side hangs up, the whole connection is done
+    tokio::select! {
+        () = to_node => {}
+        () = to_user => {}
+    }
 }
 
 async fn handle_new_onlooker<S: ServerState>(state: S, id: TournamentId, mut ws: WebSocket) {
     // Wait either 10 seconds or until we get a message
-    // First message should be the user's session token, which we then must validate.
+    // First message should be the handshake, which contains the user's session token that we then
+    // must validate.
     let bytes = tokio::select! {
         msg = ws.recv() => match msg {
             Some(Ok(Message::Binary(bytes))) => bytes,
@@ -99,13 +804,107 @@ async fn handle_new_onlooker<S: ServerState>(state: S, id: TournamentId, mut ws:
         },
         () = sleep(Duration::from_secs(10)) => return,
     };
-    let Ok(token) = postcard::from_bytes::<SessionToken>(&bytes) else {
+    let Ok(Handshake {
+        token,
+        compression,
+        invite,
+    }) = postcard::from_bytes(&bytes)
+    else {
         return;
     };
     let session = state.get_session(token.clone()).await;
+    let invite = InviteHeader(invite);
+    if get_followed_tourn(&state, &session, &invite, id)
+        .await
+        .is_none()
+    {
+        return;
+    }
     let Ok(session) = AnyUser::convert(token, session) else {
         return;
     };
     let user = state.watch_session(session).await.unwrap();
-    state.handle_new_onlooker(id, user, ws).await;
+    state.handle_new_onlooker(id, user, compression, ws).await;
+}
+
+/// Returns if the user is one of the tournament's admins
+async fn is_admin<S: ServerState>(state: &S, id: TournamentId, u_id: SquireAccountId) -> bool {
+    state
+        .get_tourn(id)
+        .await
+        .is_some_and(|tourn| matches!(tourn.user_role(u_id), TournRole::Admin(_)))
+}
+
+/// Registers a URL that the server will POST the tournament's events to (see [WebhookEvent]).
+///
+/// This api can be accessed by posting a [RegisterWebhook] to
+/// `/api/v1/tournaments/<id>/webhooks`. Only the tournament's admins can use it.
+pub async fn register_webhook<S>(
+    State(state): State<S>,
+    Session(UserSession(u_id)): Session<UserSession>,
+    Path(id): Path<TournamentId>,
+    Json(RegisterWebhook { url }): Json<RegisterWebhook>,
+) -> RegisterWebhookResponse
+where
+    S: ServerState,
+{
+    let Some(tourn) = state.get_tourn(id).await else {
+        return RegisterWebhookResponse::new(Err(WebhookError::TournamentNotFound));
+    };
+    if !matches!(tourn.user_role(*u_id), TournRole::Admin(_)) {
+        return RegisterWebhookResponse::new(Err(WebhookError::Unauthorized));
+    }
+    match check_public_url(&url).await {
+        Ok(_) => {}
+        Err(FetchError::PrivateAddress) => {
+            return RegisterWebhookResponse::new(Err(WebhookError::PrivateAddress))
+        }
+        Err(_) => return RegisterWebhookResponse::new(Err(WebhookError::InvalidUrl)),
+    }
+    let hook = Webhook {
+        id: WebhookId::new(Uuid::new_v4()),
+        tourn: id,
+        url,
+        owner: *u_id,
+        secret: new_webhook_secret(),
+    };
+    if state.persist_webhook(&hook).await {
+        RegisterWebhookResponse::new(Ok(hook))
+    } else {
+        RegisterWebhookResponse::new(Err(WebhookError::NotSaved))
+    }
+}
+
+/// Returns the webhooks that are registered for the tournament.
+///
+/// This api can be accessed via `/api/v1/tournaments/<id>/webhooks`. Only the tournament's admins
+/// can use it.
+pub async fn list_webhooks<S>(
+    State(state): State<S>,
+    Session(UserSession(u_id)): Session<UserSession>,
+    Path(id): Path<TournamentId>,
+) -> ListWebhooksResponse
+where
+    S: ServerState,
+{
+    if !is_admin(&state, id, *u_id).await {
+        return ListWebhooksResponse::new(None);
+    }
+    ListWebhooksResponse::new(Some(state.list_webhooks(id).await))
+}
+
+/// Removes one of the tournament's webhooks, which stops the server from sending events to it.
+///
+/// This api can be accessed by sending a DELETE request to
+/// `/api/v1/tournaments/<id>/webhooks/<w_id>`. Only the tournament's admins can use it.
+pub async fn delete_webhook<S>(
+    State(state): State<S>,
+    Session(UserSession(u_id)): Session<UserSession>,
+    Path((id, w_id)): Path<(TournamentId, WebhookId)>,
+) -> DeleteWebhookResponse
+where
+    S: ServerState,
+{
+    let deleted = is_admin(&state, id, *u_id).await && state.delete_webhook(id, w_id).await;
+    DeleteWebhookResponse::new(deleted)
 }
@@ -0,0 +1,53 @@
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use uuid::Uuid;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// The header that holds the signature of a webhook request's body
+pub const SIGNATURE_HEADER: &str = "X-Squire-Signature";
+
+/// Generates the secret that a webhook's requests are signed with
+pub fn new_webhook_secret() -> String {
+    let mut secret = [0; 32];
+    secret[..16].copy_from_slice(Uuid::new_v4().as_bytes());
+    secret[16..].copy_from_slice(Uuid::new_v4().as_bytes());
+    hex::encode(secret)
+}
+
+/// Signs the body of a webhook request with the webhook's secret. The signature is the
+/// hex-encoded HMAC-SHA256 of the body, which receivers can recompute to check that the request
+/// came from this server.
+pub fn sign_webhook_body(secret: &str, body: &[u8]) -> String {
+    let mut mac =
+        HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC can take a key of any size");
+    mac.update(body);
+    hex::encode(mac.finalize().into_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{new_webhook_secret, sign_webhook_body};
+
+    #[test]
+    fn webhook_bodies_are_signed_with_the_secret() {
+        let secret = new_webhook_secret();
+        assert_eq!(secret.len(), 64);
+        assert_ne!(secret, new_webhook_secret());
+
+        let signature = sign_webhook_body(&secret, b"{}");
+        assert_eq!(signature, sign_webhook_body(&secret, b"{}"));
+        assert_ne!(signature, sign_webhook_body(&secret, b"[]"));
+        assert_ne!(signature, sign_webhook_body(&new_webhook_secret(), b"{}"));
+    }
+
+    #[test]
+    fn webhook_signatures_are_hmac_sha256() {
+        // The HMAC-SHA256 test vector from the HMAC article on Wikipedia, which receivers can check
+        // their own implementations against
+        assert_eq!(
+            sign_webhook_body("key", b"The quick brown fox jumps over the lazy dog"),
+            "f7bc83f430538424b13298e6aa6fb143ef4d59a14946175997479dbc2d1a3cd8"
+        );
+    }
+}
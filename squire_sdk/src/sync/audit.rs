@@ -0,0 +1,81 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use squire_lib::{
+    admin::TournOfficialId,
+    identifiers::{PlayerId, RoundId, SquireAccountId},
+    operations::TournOp,
+    tournament::Tournament,
+};
+
+use crate::sync::{FullOp, OpId};
+
+/// A record of an operation that a judge or admin applied to a tournament, which lets organizers
+/// review who did what and when
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct AuditEntry {
+    /// The id of the operation
+    pub id: OpId,
+    /// When the operation was submitted
+    pub time: DateTime<Utc>,
+    /// The official that submitted the operation
+    pub official: TournOfficialId,
+    /// The account of the official that submitted the operation
+    pub account: SquireAccountId,
+    /// The name of the official, if they are still an official of the tournament
+    pub name: Option<String>,
+    /// A short, human-readable name for the operation, e.g. "Drop player"
+    pub action: String,
+    /// The players that the operation acted on
+    pub players: Vec<PlayerId>,
+    /// The rounds that the operation acted on
+    pub rounds: Vec<RoundId>,
+    /// The operation itself
+    pub op: TournOp,
+}
+
+impl AuditEntry {
+    /// Creates an entry for the given operation. `None` is returned if the operation was not
+    /// submitted by a judge or admin.
+    pub(crate) fn new(f_op: &FullOp, tourn: &Tournament) -> Option<Self> {
+        let official = match &f_op.op {
+            TournOp::JudgeOp(official, _) => *official,
+            TournOp::AdminOp(a_id, _) => TournOfficialId::Admin(*a_id),
+            TournOp::RegisterPlayer(_, _) | TournOp::PlayerOp(_, _) => return None,
+        };
+        let (account, name) = match official {
+            TournOfficialId::Judge(j_id) => (
+                j_id.convert(),
+                tourn.judges.get(&j_id).map(|judge| judge.name.clone()),
+            ),
+            TournOfficialId::Scorekeeper(s_id) => (
+                s_id.convert(),
+                tourn.scorekeepers.get(&s_id).map(|sk| sk.name.clone()),
+            ),
+            TournOfficialId::Admin(a_id) => (
+                a_id.convert(),
+                tourn.admins.get(&a_id).map(|admin| admin.name.clone()),
+            ),
+        };
+        Some(Self {
+            id: f_op.id,
+            time: f_op.salt,
+            official,
+            account,
+            name,
+            action: f_op.op.name().to_owned(),
+            players: f_op.op.player_ids(),
+            rounds: f_op.op.round_ids(),
+            op: f_op.op.clone(),
+        })
+    }
+
+    /// Returns if the entry acted on the given player
+    pub fn affects_player(&self, id: PlayerId) -> bool {
+        self.players.contains(&id)
+    }
+
+    /// Returns if the entry acted on the given round
+    pub fn affects_round(&self, id: RoundId) -> bool {
+        self.rounds.contains(&id)
+    }
+}
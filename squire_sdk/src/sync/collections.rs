@@ -11,15 +11,37 @@ use crate::{
         accounts::SquireAccount,
         tournament::{Tournament, TournamentSeed},
     },
-    sync::{FullOp, OpId},
+    sync::{FullOp, LogDigest, OpId},
 };
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
-/// An ordered list of all operations applied to a tournament
+/// An ordered list of all operations applied to a tournament. Older operations might have been
+/// compacted into a snapshot, in which case the log only holds the operations since.
 pub struct OpLog {
     pub(crate) owner: SquireAccount,
     pub(crate) seed: TournamentSeed,
     pub(crate) ops: Vec<FullOp>,
+    #[serde(default)]
+    pub(crate) snapshot: Option<Snapshot>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+/// The state of a tournament after some of the oldest operations in its log, which stands in for
+/// those operations once they are compacted away
+pub struct Snapshot {
+    /// The tournament after the compacted operations were applied
+    pub(crate) tourn: Tournament,
+    /// The last compacted operation. This is kept so that syncs can still be anchored at it.
+    pub(crate) anchor: FullOp,
+    /// The total number of operations that have been compacted
+    pub(crate) len: usize,
+    /// The compacted operations that were submitted by judges and admins. These are kept so that
+    /// the tournament's audit log is not lost to compaction.
+    #[serde(default)]
+    pub(crate) officials: Vec<FullOp>,
+    /// The digest of the compacted operations
+    #[serde(default)]
+    pub(crate) digest: LogDigest,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
@@ -35,6 +57,7 @@ impl OpLog {
             owner,
             seed,
             ops: vec![],
+            snapshot: None,
         }
     }
 
@@ -48,12 +71,31 @@ impl OpLog {
         self.ops.is_empty()
     }
 
+    /// Returns if some of the log's operations have been compacted into a snapshot
+    pub fn is_compacted(&self) -> bool {
+        self.snapshot.is_some()
+    }
+
+    /// Returns the number of operations that have been compacted into a snapshot
+    pub fn compacted_len(&self) -> usize {
+        self.snapshot.as_ref().map_or(0, |snap| snap.len)
+    }
+
+    /// Returns the id of the last compacted operation, if there is a snapshot
+    fn anchor_id(&self) -> Option<OpId> {
+        self.snapshot.as_ref().map(|snap| snap.anchor.id)
+    }
+
     /// Returns an iterator for the log that ignores all elements before the given `OpId`. The
     /// given `OpId` is also ignored. None is returned if the given operation is not found.
     #[cfg(feature = "client")]
     pub(crate) fn iter_passed_op(&self, id: OpId) -> Option<impl Iterator<Item = &FullOp>> {
-        let mut iter = self.ops.iter();
-        iter.by_ref().find(|op| op.id == id).map(|_| iter)
+        let start = match self.ops.iter().position(|op| op.id == id) {
+            Some(index) => index + 1,
+            None if self.anchor_id() == Some(id) => 0,
+            None => return None,
+        };
+        Some(self.ops[start..].iter())
     }
 
     /*
@@ -72,7 +114,14 @@ impl OpLog {
     pub(crate) fn create_sync_request(&self, op: Option<OpId>) -> OpSync {
         let ops = match op {
             Some(id) => self.get_slice(id).unwrap(),
-            None => self.ops.iter().cloned().collect(),
+            // Without a prior sync, the whole log is sent, starting from the snapshot's anchor
+            None => self
+                .snapshot
+                .iter()
+                .map(|snap| &snap.anchor)
+                .chain(&self.ops)
+                .cloned()
+                .collect(),
         };
         OpSync {
             owner: self.owner.clone(),
@@ -81,66 +130,117 @@ impl OpLog {
         }
     }
 
-    /// Creates the initial state of the tournament
+    /// Creates the earliest state of the tournament that the log can recreate, i.e. the snapshot if
+    /// the log has been compacted and the initial state otherwise
     pub(crate) fn init_tourn(&self) -> Tournament {
-        self.owner.create_tournament(self.seed.clone())
+        match &self.snapshot {
+            Some(snap) => snap.tourn.clone(),
+            None => self.owner.create_tournament(self.seed.clone()),
+        }
+    }
+
+    /// Returns the number of stored operations up to and including the given operation. The
+    /// snapshot's anchor is treated as coming right before the stored operations.
+    fn anchor_position(&self, id: OpId) -> Option<usize> {
+        match self.ops.iter().rposition(|op| op.id == id) {
+            Some(index) => Some(index + 1),
+            None if self.anchor_id() == Some(id) => Some(0),
+            None => None,
+        }
     }
 
+    /// Replaces every operation after the slice's first (anchor) operation with the rest of the
+    /// slice and returns the resulting state of the tournament. `None` is returned if the anchor is
+    /// not in the log or if the operations can not be applied.
     pub(crate) fn get_state_with_slice(&mut self, ops: OpSlice) -> Option<Tournament> {
         let id = ops.first_id()?;
         // TODO: We should actually be able to do better on this check since the log should not
         // have updated since the sync started
-        _ = self.ops.iter().rev().find(|op| op.id == id)?;
+        let kept = self.anchor_position(id)?;
         let mut tourn = self.init_tourn();
-        let mut iter = self.ops.iter().cloned();
-        for FullOp { op, salt, .. } in iter.by_ref().take_while(|op| op.id != id) {
-            // TODO: This should never error, but if it doesn't, it needs to be logged
-            _ = tourn.apply_op(salt, op.clone()).ok()?;
-        }
-        for FullOp { op, salt, .. } in iter {
+        for FullOp { op, salt, .. } in self.ops[..kept].iter().chain(ops.iter().skip(1)).cloned() {
             // TODO: This should never error, but if it doesn't, it needs to be logged
             _ = tourn.apply_op(salt, op).ok()?;
         }
-        let mut not_found = true;
-        self.ops.retain(|op| {
-            not_found &= op.id != id;
-            not_found
-        });
-        self.ops.extend(ops);
+        self.ops.truncate(kept);
+        self.ops.extend(ops.into_iter().skip(1));
         Some(tourn)
     }
 
-    /// Creates a slice of this log starting at the given index. `None` is returned if `index` is
-    /// out of bounds.
+    /// Creates a slice of this log starting at the given operation. `None` is returned if the
+    /// operation is not in the log.
     #[cfg(any(feature = "server", feature = "client"))]
     pub(crate) fn get_slice(&self, id: OpId) -> Option<OpSlice> {
-        if self.is_empty() {
-            return None;
+        match self.ops.iter().rposition(|op| op.id == id) {
+            Some(index) => Some(self.ops[index..].iter().cloned().collect()),
+            None => {
+                let snap = self.snapshot.as_ref().filter(|snap| snap.anchor.id == id)?;
+                Some(
+                    std::iter::once(&snap.anchor)
+                        .chain(&self.ops)
+                        .cloned()
+                        .collect(),
+                )
+            }
         }
-        let mut not_found = true;
-        let mut ops = self
-            .ops
-            .iter()
-            .rev()
-            .take_while(|op| std::mem::replace(&mut not_found, op.id != id))
-            .cloned()
-            .collect::<Vec<_>>();
-        if ops.len() < self.len() || ops.last().map(|op| op.id == id).unwrap_or_default() {
-            ops.reverse();
-            Some(OpSlice { ops: ops.into() })
-        } else {
-            None
+    }
+
+    /// Compacts all but the `keep` most recent operations into a snapshot. Returns the number of
+    /// operations that were compacted.
+    pub(crate) fn compact(&mut self, keep: usize) -> usize {
+        let count = self.ops.len().saturating_sub(keep);
+        if count == 0 {
+            return 0;
         }
+        let mut tourn = self.init_tourn();
+        for FullOp { op, salt, .. } in self.ops[..count].iter().cloned() {
+            // The operations have already been applied once, so this should never error. If it
+            // does, the log is left as is.
+            if tourn.apply_op(salt, op).is_err() {
+                return 0;
+            }
+        }
+        let anchor = self.ops[count - 1].clone();
+        let len = self.compacted_len() + count;
+        let mut digest = self.base_digest();
+        digest.extend(self.ops[..count].iter().map(|op| op.id));
+        let mut officials = self
+            .snapshot
+            .take()
+            .map(|snap| snap.officials)
+            .unwrap_or_default();
+        officials.extend(self.ops.drain(..count).filter(FullOp::is_official));
+        self.snapshot = Some(Snapshot {
+            tourn,
+            anchor,
+            len,
+            officials,
+            digest,
+        });
+        count
     }
 
-    /// Returns the last operation in the log.
+    /// Returns an iterator over every operation submitted by a judge or admin, including those that
+    /// have been compacted
+    pub fn official_ops(&self) -> impl Iterator<Item = &FullOp> {
+        self.snapshot
+            .iter()
+            .flat_map(|snap| &snap.officials)
+            .chain(self.ops.iter().filter(|op| op.is_official()))
+    }
+
+    /// Returns the last operation in the log. If every operation has been compacted, this is the
+    /// last compacted operation.
     pub fn last_op(&self) -> Option<FullOp> {
-        self.ops.last().cloned()
+        self.ops
+            .last()
+            .or(self.snapshot.as_ref().map(|snap| &snap.anchor))
+            .cloned()
     }
 
-    /// Returns the id of the last operation in the log.
+    /// Returns the id of the last operation in the log, including compacted operations.
     pub(crate) fn last_id(&self) -> Option<OpId> {
-        self.ops.last().map(|op| op.id)
+        self.ops.last().map(|op| op.id).or(self.anchor_id())
     }
 }
 
@@ -0,0 +1,171 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use squire_lib::tournament::TournamentId;
+
+use super::{Disagreement, OpId, OpLog, TournamentManager};
+
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// A checksum of a tournament's operation log, which the client and backend compare to spot that
+/// their copies of the tournament have diverged. Every operation has a unique id, so the digest
+/// only covers the ids of the operations and the order they were applied in.
+///
+/// The digest of the compacted operations is kept in the log's snapshot, so two copies that hold
+/// the same operations have the same digest no matter how much of their logs was compacted. The
+/// hash is computed by hand (FNV-1a) so that it is the same on every platform.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct LogDigest {
+    len: usize,
+    hash: u64,
+}
+
+impl Default for LogDigest {
+    fn default() -> Self {
+        Self {
+            len: 0,
+            hash: FNV_OFFSET,
+        }
+    }
+}
+
+impl LogDigest {
+    /// Returns the number of operations that the digest covers
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns if the digest covers no operations
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Adds operations to the end of the digest
+    pub(crate) fn extend<I: IntoIterator<Item = OpId>>(&mut self, ids: I) {
+        for id in ids {
+            for byte in id.0.as_bytes() {
+                self.hash ^= u64::from(*byte);
+                self.hash = self.hash.wrapping_mul(FNV_PRIME);
+            }
+            self.len += 1;
+        }
+    }
+}
+
+impl OpLog {
+    /// Calculates the digest of every operation in the log, including compacted operations
+    pub fn digest(&self) -> LogDigest {
+        let mut digest = self.base_digest();
+        digest.extend(self.ops.iter().map(|op| op.id));
+        digest
+    }
+
+    /// Calculates the digest of the first `len` operations in the log. `None` is returned if the
+    /// log has fewer operations or if some of them were compacted.
+    pub fn digest_of_first(&self, len: usize) -> Option<LogDigest> {
+        let base = self.base_digest();
+        let count = len.checked_sub(base.len)?;
+        let mut digest = base;
+        digest.extend(self.ops.get(..count)?.iter().map(|op| op.id));
+        Some(digest)
+    }
+
+    /// Returns the digest of the compacted operations
+    pub(crate) fn base_digest(&self) -> LogDigest {
+        self.snapshot
+            .as_ref()
+            .map(|snap| snap.digest)
+            .unwrap_or_default()
+    }
+}
+
+impl TournamentManager {
+    /// Calculates the digest of the tournament's log
+    pub fn digest(&self) -> LogDigest {
+        self.op_log().digest()
+    }
+
+    /// Checks this copy of the tournament against the digest of another copy's log. Only the
+    /// operations that the digest covers are checked, so operations that were added to this copy
+    /// since are ignored. If this copy has compacted some of those operations, there is nothing to
+    /// check against.
+    pub fn check_digest(&self, digest: LogDigest) -> Result<(), Disagreement<LogDigest>> {
+        let log = self.op_log();
+        if digest.len < log.base_digest().len {
+            return Ok(());
+        }
+        match log.digest_of_first(digest.len) {
+            Some(local) if local == digest => Ok(()),
+            Some(local) => Err(Disagreement::new(digest, local)),
+            None => Err(Disagreement::new(digest, log.digest())),
+        }
+    }
+}
+
+/// A diagnostic for when the client's copy of a tournament was found to no longer match the
+/// backend's copy. When this happens, the client re-fetches the tournament from the backend.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Divergence {
+    /// The tournament that diverged
+    pub tourn: TournamentId,
+    /// The backend's digest and the client's digest of the same operations
+    pub digests: Disagreement<LogDigest>,
+    /// When the divergence was found
+    pub detected: DateTime<Utc>,
+}
+
+#[cfg(test)]
+mod tests {
+    use squire_lib::operations::TournOp;
+    use squire_tests::{get_seed, spoof_account};
+
+    use crate::sync::{Disagreement, TournamentManager};
+
+    fn register(tourn: &mut TournamentManager) {
+        let _ = tourn
+            .apply_op(TournOp::RegisterPlayer(spoof_account(), None))
+            .unwrap();
+    }
+
+    #[test]
+    fn digests_survive_compaction() {
+        let mut tourn = TournamentManager::new(spoof_account(), get_seed());
+        (0..5).for_each(|_| register(&mut tourn));
+        let mut compacted = tourn.clone();
+        assert_eq!(compacted.compact(2), 3);
+        assert_eq!(compacted.digest(), tourn.digest());
+        assert_eq!(compacted.digest().len(), 5);
+        assert_eq!(tourn.check_digest(compacted.digest()), Ok(()));
+
+        // Operations that are added after the digest was taken are not checked
+        let digest = tourn.digest();
+        register(&mut tourn);
+        assert_eq!(tourn.check_digest(digest), Ok(()));
+
+        // Digests that only cover compacted operations can't be checked
+        let first = tourn.op_log().digest_of_first(1).unwrap();
+        assert_eq!(first.len(), 1);
+        assert_eq!(compacted.check_digest(first), Ok(()));
+        assert_eq!(compacted.op_log().digest_of_first(1), None);
+    }
+
+    #[test]
+    fn divergence_is_detected() {
+        let mut server = TournamentManager::new(spoof_account(), get_seed());
+        register(&mut server);
+        let mut client = server.clone();
+        register(&mut server);
+        register(&mut client);
+        assert_eq!(
+            client.check_digest(server.digest()),
+            Err(Disagreement::new(server.digest(), client.digest()))
+        );
+
+        // A client that is missing operations has also diverged
+        let mut behind = server.clone();
+        register(&mut server);
+        assert!(behind.check_digest(server.digest()).is_err());
+        register(&mut behind);
+        assert!(behind.check_digest(server.digest()).is_err());
+    }
+}
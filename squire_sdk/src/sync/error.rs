@@ -1,5 +1,8 @@
 use serde::{Deserialize, Serialize};
-use squire_lib::{accounts::SquireAccount, error::TournamentError, tournament::TournamentSeed};
+use squire_lib::{
+    accounts::SquireAccount, error::TournamentError, operations::TournOp,
+    tournament::TournamentSeed,
+};
 
 use super::OpId;
 
@@ -21,6 +24,10 @@ pub enum SyncError {
     AlreadyCompleted,
     /// The starting operation of the slice in unknown to the other log
     UnknownOperation(OpId),
+    /// The starting operation of the slice is unknown to the other log, but that log has been
+    /// compacted, so the operation might be one of the compacted operations. The sender needs to
+    /// catch up to the other log's snapshot before the logs can be merged.
+    Compacted(OpId),
     /// The `OpSync` was a mismatch for the tournament manager (e.g. wrong account or seed)
     InvalidRequest(Box<RequestError>),
     /// The user was not authorized to send the message that was sent.
@@ -30,6 +37,13 @@ pub enum SyncError {
     /// initialization checks (like the "are you allowed to perform these operations check") then
     /// replying with a completely different set of operations.
     InvalidReply,
+    /// The client has sent too many sync messages in too short of a time. The message was not
+    /// processed and can be resent later. Clients that keep sending messages have their
+    /// connection closed.
+    RateLimited,
+    /// An operation that is checked against the registration window was created too long before
+    /// (or after) the server received it, so the time that it was created at can not be trusted
+    StaleOperation,
 }
 
 /// An error used in the server-initialized sync process that the client uses to signal that an
@@ -82,6 +96,23 @@ pub enum RequestError {
     TournError(TournamentError),
 }
 
+/// An error that can occur when undoing or redoing an operation in a `TournamentManager`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub enum UndoError {
+    /// No judge or admin operations have been applied since the last undo
+    NothingToUndo,
+    /// No operations have been undone since the last operation was applied
+    NothingToRedo,
+    /// The most recent judge or admin operation has been compacted into a snapshot, so the state
+    /// that it was applied to can not be recreated
+    Compacted,
+    /// The most recent judge or admin operation can not be reversed, e.g. ending the tournament
+    Irreversible(Box<TournOp>),
+    /// The operations that reverse (or redo) the operation could not be applied to the current
+    /// state of the tournament
+    TournError(TournamentError),
+}
+
 impl<T> Disagreement<T> {
     pub fn new(known: T, given: T) -> Self {
         Self { known, given }
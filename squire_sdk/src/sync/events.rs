@@ -0,0 +1,240 @@
+use std::collections::{HashMap, VecDeque};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use squire_lib::{
+    identifiers::{PlayerId, RoundId},
+    operations::{AdminOp, JudgeOp, PlayerOp, TournOp},
+    players::PlayerStatus,
+    rounds::RoundStatus,
+    tournament::{Tournament, TournamentStatus},
+};
+
+use crate::sync::{OpId, Replay, TournamentManager};
+
+/// The kinds of high-level changes that applying an operation can make to a tournament
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TournEventKind {
+    /// A player registered for the tournament or was registered by an official
+    PlayerRegistered(PlayerId),
+    /// A player dropped or was dropped from the tournament
+    PlayerDropped(PlayerId),
+    /// A round was created, i.e. by pairing the next set of rounds or giving a player a bye
+    RoundPaired(RoundId),
+    /// A result was recorded for a round. The result has not necessarily been certified.
+    ResultRecorded(RoundId),
+    /// A round's result was certified
+    RoundCertified(RoundId),
+    /// The tournament changed status, e.g. it was started or ended
+    StatusChanged(TournamentStatus),
+}
+
+/// A high-level change made to a tournament, which is derived from the operation that caused it.
+/// This lets consumers react to what happened without diffing whole tournaments.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TournEvent {
+    /// The operation that caused the event
+    pub op: OpId,
+    /// When the operation was made
+    pub time: DateTime<Utc>,
+    /// What happened
+    pub kind: TournEventKind,
+}
+
+/// The parts of a tournament that events are derived from
+struct Snapshot {
+    status: TournamentStatus,
+    players: HashMap<PlayerId, PlayerStatus>,
+    rounds: HashMap<RoundId, RoundStatus>,
+}
+
+impl Snapshot {
+    fn new(tourn: &Tournament) -> Self {
+        Self {
+            status: tourn.status,
+            players: tourn
+                .player_reg
+                .players
+                .iter()
+                .map(|(id, plyr)| (*id, plyr.status))
+                .collect(),
+            rounds: tourn
+                .round_reg
+                .rounds
+                .iter()
+                .map(|(id, rnd)| (*id, rnd.status))
+                .collect(),
+        }
+    }
+
+    /// Calculates what changed between this snapshot and the given tournament
+    fn changes(&self, tourn: &Tournament) -> Vec<TournEventKind> {
+        let mut digest = Vec::new();
+        for (id, plyr) in tourn.player_reg.players.iter() {
+            match self.players.get(id) {
+                None => digest.push(TournEventKind::PlayerRegistered(*id)),
+                Some(status) if *status != PlayerStatus::Dropped => {
+                    if plyr.status == PlayerStatus::Dropped {
+                        digest.push(TournEventKind::PlayerDropped(*id));
+                    }
+                }
+                Some(_) => {}
+            }
+        }
+        let mut rounds: Vec<_> = tourn.round_reg.rounds.values().collect();
+        rounds.sort_by_key(|rnd| rnd.match_number);
+        for rnd in rounds {
+            let before = self.rounds.get(&rnd.id);
+            if before.is_none() {
+                digest.push(TournEventKind::RoundPaired(rnd.id));
+            }
+            if rnd.status == RoundStatus::Certified && before != Some(&RoundStatus::Certified) {
+                digest.push(TournEventKind::RoundCertified(rnd.id));
+            }
+        }
+        if tourn.status != self.status {
+            digest.push(TournEventKind::StatusChanged(tourn.status));
+        }
+        digest
+    }
+}
+
+/// Returns the round that an operation records a result for, if any
+fn recorded_round(op: &TournOp) -> Option<RoundId> {
+    match op {
+        TournOp::PlayerOp(_, PlayerOp::RecordResult(r_id, _))
+        | TournOp::JudgeOp(_, JudgeOp::AdminRecordResult(r_id, _))
+        | TournOp::AdminOp(_, AdminOp::AdminOverwriteResult(r_id, _)) => Some(*r_id),
+        _ => None,
+    }
+}
+
+/// Derives the events of a tournament by replaying its log (see [`Replay`]). Operations that
+/// fail to apply cause no events.
+#[derive(Debug, Clone)]
+pub struct Events<'a> {
+    replay: Replay<'a>,
+    pending: VecDeque<TournEvent>,
+}
+
+impl<'a> Events<'a> {
+    pub(crate) fn new(manager: &'a TournamentManager) -> Self {
+        Self {
+            replay: manager.replay(),
+            pending: VecDeque::new(),
+        }
+    }
+}
+
+impl Iterator for Events<'_> {
+    type Item = TournEvent;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.pending.is_empty() {
+            let before = Snapshot::new(self.replay.tourn());
+            let step = self.replay.next()?;
+            if step.result.is_err() {
+                continue;
+            }
+            let mut kinds = before.changes(self.replay.tourn());
+            if let Some(r_id) = recorded_round(&step.op.op) {
+                kinds.insert(0, TournEventKind::ResultRecorded(r_id));
+            }
+            self.pending
+                .extend(kinds.into_iter().map(|kind| TournEvent {
+                    op: step.op.id,
+                    time: step.op.salt,
+                    kind,
+                }));
+        }
+        self.pending.pop_front()
+    }
+}
+
+impl TournamentManager {
+    /// Returns the high-level events of the tournament (e.g. players registering or rounds being
+    /// certified), oldest first. Consumers can keep the id of the last operation they have seen
+    /// and skip past it to only react to new changes. Compacted operations have no events.
+    pub fn events(&self) -> Events<'_> {
+        Events::new(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use squire_lib::{
+        identifiers::{AdminId, PlayerId},
+        operations::{AdminOp, JudgeOp, PlayerOp, TournOp},
+        rounds::RoundResult,
+        tournament::TournamentStatus,
+    };
+    use squire_tests::{get_seed, spoof_account};
+
+    use super::TournEventKind;
+    use crate::sync::TournamentManager;
+
+    #[test]
+    fn events_follow_the_tournament() {
+        let admin = spoof_account();
+        let admin_id: AdminId = admin.id.0.into();
+        let mut tourn = TournamentManager::new(admin, get_seed());
+        let plyrs: Vec<PlayerId> = (0..2)
+            .map(|_| {
+                tourn
+                    .apply_op(TournOp::RegisterPlayer(spoof_account(), None))
+                    .unwrap()
+                    .assume_register_player()
+            })
+            .collect();
+        let _ = tourn
+            .apply_op(TournOp::AdminOp(admin_id, AdminOp::Start))
+            .unwrap();
+        let r_id = tourn
+            .apply_op(TournOp::AdminOp(
+                admin_id,
+                AdminOp::CreateRound(plyrs.clone()),
+            ))
+            .unwrap()
+            .assume_create_round();
+        let result = RoundResult::Wins(plyrs[0], 2);
+        let _ = tourn
+            .apply_op(TournOp::PlayerOp(
+                plyrs[0],
+                PlayerOp::RecordResult(r_id, result),
+            ))
+            .unwrap();
+        let _ = tourn
+            .apply_op(TournOp::JudgeOp(
+                admin_id.into(),
+                JudgeOp::ConfirmRound(r_id),
+            ))
+            .unwrap();
+        let _ = tourn
+            .apply_op(TournOp::PlayerOp(plyrs[1], PlayerOp::DropPlayer))
+            .unwrap();
+
+        let kinds: Vec<_> = tourn.events().map(|event| event.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                TournEventKind::PlayerRegistered(plyrs[0]),
+                TournEventKind::PlayerRegistered(plyrs[1]),
+                TournEventKind::StatusChanged(TournamentStatus::Started),
+                TournEventKind::RoundPaired(r_id),
+                TournEventKind::ResultRecorded(r_id),
+                TournEventKind::RoundCertified(r_id),
+                TournEventKind::PlayerDropped(plyrs[1]),
+            ]
+        );
+
+        // Consumers can pick up after the last operation they saw
+        let last = tourn.events().nth(3).unwrap().op;
+        let new: Vec<_> = tourn
+            .events()
+            .skip_while(|event| event.op != last)
+            .skip_while(|event| event.op == last)
+            .map(|event| event.kind)
+            .collect();
+        assert_eq!(new.len(), 3);
+    }
+}
@@ -22,4 +22,24 @@ impl FullOp {
         let id = id_from_item(salt, &op);
         Self { op, id, salt }
     }
+
+    /// Returns the wrapped operation
+    pub fn op(&self) -> &TournOp {
+        &self.op
+    }
+
+    /// Returns the operation's id
+    pub fn id(&self) -> OpId {
+        self.id
+    }
+
+    /// Returns when the operation was created
+    pub fn salt(&self) -> DateTime<Utc> {
+        self.salt
+    }
+
+    /// Returns if the operation was submitted by a judge or admin
+    pub fn is_official(&self) -> bool {
+        matches!(self.op, TournOp::JudgeOp(_, _) | TournOp::AdminOp(_, _))
+    }
 }
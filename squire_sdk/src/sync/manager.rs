@@ -1,23 +1,30 @@
+#[cfg(feature = "client")]
+use std::collections::HashSet;
 use std::ops::Deref;
 
 use serde::{Deserialize, Serialize};
 use squire_lib::{
     accounts::SquireAccount,
-    tournament::{Tournament, TournamentSeed},
+    collation::NameCollator,
+    error::TournamentError,
+    formats::Format,
+    identifiers::AdminId,
+    operations::{AdminOp, JudgeOp, PlayerOp, TournOp},
+    players::{Player, PlayerRegistry},
+    scoring::{ScoreMismatch, StandardScore},
+    settings::TournamentTemplate,
+    tournament::{SeedOverrides, Tournament, TournamentSeed},
 };
 
-use super::{processor::SyncCompletion, OpId, OpLog, SyncError};
-#[cfg(feature = "server")]
-use crate::sync::{processor::SyncDecision, ServerOpLink};
+use super::{processor::SyncCompletion, AuditEntry, FullOp, OpId, OpLog, SyncError};
 #[cfg(feature = "client")]
-use crate::{
-    model::operations::TournOp,
-    sync::{error::ForwardError, SyncForwardResp},
-};
+use crate::sync::{error::ForwardError, SyncForwardResp, UndoError};
+#[cfg(feature = "server")]
+use crate::sync::{processor::SyncDecision, ClientOpLink, ServerOpLink};
 #[cfg(any(feature = "client", feature = "server"))]
 use crate::{
     model::operations::{OpData, OpResult},
-    sync::{processor::SyncProcessor, FullOp, OpSync},
+    sync::{processor::SyncProcessor, OpSync},
 };
 
 /// A state manager for the tournament struct
@@ -31,6 +38,26 @@ pub struct TournamentManager {
     log: OpLog,
     /// The last OpId of the last operation after a successful sync
     last_sync: Option<OpId>,
+    /// Increases each time the backend hands a full copy of the manager off to be persisted. A
+    /// persisted copy is only replaced by a copy with a newer version.
+    #[serde(default)]
+    version: u64,
+    /// The operations that have been undone and can be redone. This is local to the manager and is
+    /// neither synced nor stored.
+    #[cfg(feature = "client")]
+    #[serde(skip)]
+    history: UndoHistory,
+}
+
+/// Tracks which operations a manager has undone
+#[cfg(feature = "client")]
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+struct UndoHistory {
+    /// The operations that have been undone and the operations that undid them. These are skipped
+    /// when looking for the operation to undo.
+    hidden: HashSet<OpId>,
+    /// The operations that can be redone, most recently undone last
+    redo: Vec<TournOp>,
 }
 
 impl TournamentManager {
@@ -42,6 +69,9 @@ impl TournamentManager {
             tourn,
             log,
             last_sync: None,
+            version: 0,
+            #[cfg(feature = "client")]
+            history: UndoHistory::default(),
         }
     }
 
@@ -51,6 +81,141 @@ impl TournamentManager {
         &self.tourn
     }
 
+    /// Returns the log of every operation that has been applied to the tournament
+    pub fn op_log(&self) -> &OpLog {
+        &self.log
+    }
+
+    /// Returns a copy of the manager that only holds what the tournament's players agreed to
+    /// share, which is what users that aren't officials are given.
+    ///
+    /// NOTE: The operations log carries everything that the players registered with, so the copy
+    /// starts with an empty log. The copy can be read, but it can not be synced.
+    pub fn redacted(&self) -> Self {
+        let mut tourn = self.tourn.clone();
+        redact(&mut tourn);
+        Self {
+            tourn,
+            log: OpLog::new(self.log.owner.clone(), self.log.seed.clone()),
+            last_sync: None,
+            version: 0,
+            #[cfg(feature = "client")]
+            history: UndoHistory::default(),
+        }
+    }
+
+    /// Returns a copy of the manager that only holds what the tournament's players agreed to
+    /// share, which is what users that aren't officials are sent over their websockets.
+    ///
+    /// The players' accounts and decklists are carried by the operations that register players and
+    /// add decks, so, if there are any, the copy's operations are all compacted into a snapshot.
+    /// Unlike [TournamentManager::redacted], the copy can still be synced.
+    pub fn for_spectators(&self) -> Self {
+        let mut digest = self.clone();
+        if digest.log.ops.iter().any(|op| op.op.is_private()) {
+            _ = digest.log.compact(0);
+        }
+        redact(&mut digest.tourn);
+        if let Some(snap) = digest.log.snapshot.as_mut() {
+            redact(&mut snap.tourn);
+            snap.officials.retain(|op| !op.op.has_decklist());
+            let reg = &snap.tourn.player_reg;
+            for op in snap.officials.iter_mut() {
+                withhold_accounts(&mut op.op, reg);
+            }
+            withhold_decklist(&mut snap.anchor.op);
+            withhold_accounts(&mut snap.anchor.op, reg);
+        }
+        digest
+    }
+
+    /// Creates a new tournament with the same owner, settings, and officials as this one, so that
+    /// recurring events don't need to be set up from scratch. If the overrides ask for it, every
+    /// player is also registered for the new tournament, regardless of their status here.
+    ///
+    /// NOTE: Players are registered from what this tournament knows about them, so guests are
+    /// registered as if they had accounts.
+    pub fn clone_as_new(&self, overrides: SeedOverrides) -> Self {
+        let SeedOverrides {
+            name,
+            format,
+            keep_players,
+        } = overrides;
+        let name = name.unwrap_or_else(|| self.tourn.name.clone());
+        let format = format.unwrap_or_else(|| Format::from_name(&self.tourn.settings.format));
+        let template =
+            TournamentTemplate::from_tournament(self.log.owner.id, name.clone(), &self.tourn);
+        let seed = TournamentSeed {
+            name,
+            preset: template.preset,
+            format,
+            template: Some(template),
+        };
+        let mut digest = Self::new(self.log.owner.clone(), seed);
+        if !keep_players {
+            return digest;
+        }
+        // Players that can play are registered first so that they keep their spots if there is a
+        // player cap
+        let mut players = self
+            .tourn
+            .player_reg
+            .get_players_by_name(&NameCollator::new());
+        players.sort_by_key(|plyr| !plyr.can_play());
+        let players: Vec<_> = players
+            .into_iter()
+            .map(|plyr| (player_account(plyr), Some(plyr.name.clone())))
+            .collect();
+        if !players.is_empty() {
+            let admin = AdminId::new(self.log.owner.id.0);
+            let f_op = FullOp::new(TournOp::JudgeOp(
+                admin.into(),
+                JudgeOp::AdminRegisterPlayers(players),
+            ));
+            if digest.tourn.apply_op(f_op.salt, f_op.op.clone()).is_ok() {
+                digest.log.ops.push(f_op);
+            }
+        }
+        digest
+    }
+
+    /// Returns every operation that a judge or admin has applied to the tournament, oldest first.
+    /// Operations that were compacted into a snapshot are included.
+    pub fn audit_log(&self) -> Vec<AuditEntry> {
+        self.log
+            .official_ops()
+            .filter_map(|op| AuditEntry::new(op, &self.tourn))
+            .collect()
+    }
+
+    /// Recalculates the standings from scratch by replaying the log and compares them against the
+    /// standings of the tournament, which was built up one operation at a time. Any mismatch means
+    /// that the two have drifted apart, and each mismatch is logged. An error is returned if the log
+    /// can not be replayed.
+    pub fn audit_standings(&self) -> Result<Vec<ScoreMismatch<StandardScore>>, TournamentError> {
+        let mut tourn = self.log.init_tourn();
+        for FullOp { op, salt, .. } in self.log.ops.iter().cloned() {
+            _ = tourn.apply_op(salt, op)?;
+        }
+        let mismatches = tourn.audit_standings(&self.tourn.get_standings());
+        #[cfg(feature = "server")]
+        for ScoreMismatch {
+            player,
+            cached,
+            computed,
+        } in mismatches.iter()
+        {
+            tracing::warn!(
+                tourn = %self.tourn.id,
+                %player,
+                ?cached,
+                ?computed,
+                "Standings do not match recalculated standings"
+            );
+        }
+        Ok(mismatches)
+    }
+
     /// Takes the manager, removes all unnecessary data for storage, and return the underlying
     /// tournament, consuming the manager in the process.
     pub fn extract(self) -> Tournament {
@@ -74,6 +239,51 @@ impl TournamentManager {
         Ok(OpData::Nothing)
     }
 
+    /// Compacts all but the `keep` most recent operations in the log into a snapshot of the
+    /// tournament, which keeps the log (and everything that ships it) from growing without bound.
+    /// Returns the number of operations that were compacted.
+    ///
+    /// Syncs that are anchored at a compacted operation can no longer be merged. Instead, the
+    /// other side catches up to this manager (see [crate::sync::ServerOpLink::CatchUp]).
+    pub fn compact(&mut self, keep: usize) -> usize {
+        let digest = self.log.compact(keep);
+        if let Some(snap) = self.log.snapshot.as_mut() {
+            snap.tourn.id = self.tourn.id;
+        }
+        digest
+    }
+
+    /// Replaces the tournament with one that was recreated from the log. A new tournament is given
+    /// a random id, which the log does not know, so the current id is carried over.
+    fn set_rebuilt(&mut self, mut tourn: Tournament) {
+        tourn.id = self.tourn.id;
+        self.tourn = tourn;
+    }
+
+    /// Removes every operation after the first `kept` operations in the log and recalculates the
+    /// tournament from the rest. The removed operations are returned.
+    pub(crate) fn truncate(&mut self, kept: usize) -> Result<Vec<FullOp>, TournamentError> {
+        let mut tourn = self.log.init_tourn();
+        for FullOp { op, salt, .. } in self.log.ops[..kept].iter().cloned() {
+            _ = tourn.apply_op(salt, op)?;
+        }
+        let removed = self.log.ops.split_off(kept);
+        self.set_rebuilt(tourn);
+        if self
+            .last_sync
+            .is_some_and(|id| removed.iter().any(|op| op.id == id))
+        {
+            self.last_sync = self.log.last_id();
+        }
+        Ok(removed)
+    }
+
+    /// Returns the version of this copy of the tournament. Copies that were persisted later have
+    /// higher versions.
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+
     pub fn seed_and_creator(&self) -> (TournamentSeed, SquireAccount) {
         (self.log.seed.clone(), self.log.owner.clone())
     }
@@ -82,15 +292,15 @@ impl TournamentManager {
     pub fn handle_completion(&mut self, comp: SyncCompletion) -> Result<(), SyncError> {
         let digest = match comp {
             // The client's operations were the only operations. There is nothing to update
-            SyncCompletion::ForeignOnly(_) => Ok(()),
-            SyncCompletion::Mixed(ops) => {
+            SyncCompletion::ForeignOnly(..) => Ok(()),
+            SyncCompletion::Mixed(ops, _) => {
                 let Some(id) = ops.first_id() else {
                     return Err(SyncError::EmptySync);
                 };
                 let Some(tourn) = self.log.get_state_with_slice(ops) else {
                     return Err(SyncError::UnknownOperation(id));
                 };
-                self.tourn = tourn;
+                self.set_rebuilt(tourn);
                 Ok(())
             }
         };
@@ -122,7 +332,7 @@ impl TournamentManager {
         // `Processing` iterator is dropped.
         if self.bulk_apply_ops_inner(&mut iter).is_ok() {
             iter.conclude();
-            proc.finalize().into()
+            proc.finalize().stamp(&self.log).into()
         } else {
             drop(iter);
             proc.into()
@@ -134,16 +344,86 @@ impl TournamentManager {
         match dec {
             SyncDecision::Plucked(proc) => self.process_sync(proc),
             SyncDecision::Purged(comp) => match self.handle_completion(comp.clone()) {
-                Ok(()) => comp.into(),
+                Ok(()) => comp.stamp(&self.log).into(),
                 Err(err) => err.into(),
             },
         }
     }
 
+    /// Processes a link in a client's sync chain and returns the backend's response. Resent links
+    /// should be caught by a `ServerSyncManager` before reaching this method, as processing a
+    /// decision twice is not idempotent.
+    pub fn handle_sync_link(&mut self, link: ClientOpLink) -> ServerOpLink {
+        match link {
+            ClientOpLink::Init(sync) => match self.init_sync(sync) {
+                Ok(proc) => self.process_sync(proc),
+                // The client is behind the snapshot, so it needs to catch up
+                Err(SyncError::Compacted(_)) => ServerOpLink::CatchUp(Box::new(self.clone())),
+                Err(err) => err.into(),
+            },
+            ClientOpLink::Decision(dec) => self.handle_decision(dec),
+            // Whether or not the chain had already completed is tracked by the sync manager
+            ClientOpLink::Terminated => ServerOpLink::TerminatedSeen {
+                already_done: false,
+            },
+        }
+    }
+
+    /// Applies an operation that was made by the server rather than synced by a client (e.g. a
+    /// result reported with a reporting token). The completed sync holds the stored operation so
+    /// that it can be forwarded to the clients.
+    pub fn apply_server_op(&mut self, op: TournOp) -> Result<SyncCompletion, TournamentError> {
+        let f_op = FullOp::new(op);
+        let FullOp { op, salt, .. } = f_op.clone();
+        _ = self.tourn.apply_op(salt, op)?;
+        self.log.ops.push(f_op.clone());
+        let ops = std::iter::once(f_op).collect();
+        Ok(SyncCompletion::ForeignOnly(ops, self.log.digest()))
+    }
+
+    /// Marks this copy of the tournament as newer than every copy that has been persisted so far
+    pub(crate) fn bump_version(&mut self) {
+        self.version += 1;
+    }
+
+    /// Returns the operations in the log after the given operation, oldest first. Without an
+    /// operation, every operation in the log is returned. `None` is returned if the operation is
+    /// not in the log or, without an operation, if part of the log has been compacted.
+    pub fn ops_since(&self, id: Option<OpId>) -> Option<Vec<FullOp>> {
+        match id {
+            Some(id) => {
+                let mut ops = self.log.get_slice(id)?;
+                _ = ops.pop_front();
+                Some(ops.into_iter().collect())
+            }
+            None if self.log.is_compacted() => None,
+            None => Some(self.log.ops.clone()),
+        }
+    }
+
+    /// Applies operations that were stored separately from this copy of the tournament, like those
+    /// in a write-ahead log. Operations that are already in the log are skipped, and replaying
+    /// stops at the first operation that can not be applied. Returns the number of operations that
+    /// were applied.
+    pub fn replay_ops(&mut self, ops: Vec<FullOp>) -> usize {
+        let mut count = 0;
+        for op in ops {
+            if self.log.get_slice(op.id).is_some() {
+                continue;
+            }
+            if self.bulk_apply_ops_inner(std::iter::once(op)).is_err() {
+                break;
+            }
+            count += 1;
+        }
+        self.last_sync = self.log.last_id();
+        count
+    }
+
     /// Creates an `OpSync` that will be forwarded to all clients
     pub fn init_sync_forwarding(&self, comp: SyncCompletion) -> OpSync {
         match comp {
-            SyncCompletion::ForeignOnly(ops) | SyncCompletion::Mixed(ops) => OpSync {
+            SyncCompletion::ForeignOnly(ops, _) | SyncCompletion::Mixed(ops, _) => OpSync {
                 owner: self.log.owner.clone(),
                 seed: self.log.seed.clone(),
                 ops,
@@ -157,7 +437,11 @@ impl TournamentManager {
     /// Takes an operation, ensures all idents are their Id variants, stores the operation, applies
     /// it to the tournament, and returns the result.
     pub fn apply_op(&mut self, op: TournOp) -> OpResult {
-        self.apply_op_inner(FullOp::new(op))
+        let digest = self.apply_op_inner(FullOp::new(op));
+        if digest.is_ok() {
+            self.history.redo.clear();
+        }
+        digest
     }
 
     fn apply_op_inner(&mut self, f_op: FullOp) -> OpResult {
@@ -172,7 +456,128 @@ impl TournamentManager {
     /// Takes an vector of operations and attempts to update the tournament. All operations must
     /// succeed in order for the bulk update the succeed. The update is sandboxed to ensure this.
     pub fn bulk_apply_ops(&mut self, ops: Vec<TournOp>) -> OpResult {
-        self.bulk_apply_ops_inner(ops.into_iter().map(FullOp::new))
+        let digest = self.bulk_apply_ops_inner(ops.into_iter().map(FullOp::new));
+        if digest.is_ok() {
+            self.history.redo.clear();
+        }
+        digest
+    }
+
+    /// Reverses the most recent judge or admin operation that has not already been undone, e.g.
+    /// removing the rounds that were just paired or reinstating a player that was just dropped.
+    /// The reversing operations are submitted by the given admin and are synced like any other
+    /// operation. Operations that can not be reversed (like ending the tournament) cause an error
+    /// rather than being skipped.
+    pub fn undo(&mut self, admin: AdminId) -> Result<(), UndoError> {
+        let Some(index) = self
+            .log
+            .ops
+            .iter()
+            .rposition(|op| op.is_official() && !self.history.hidden.contains(&op.id))
+        else {
+            let compacted = self
+                .log
+                .official_ops()
+                .any(|op| !self.history.hidden.contains(&op.id));
+            return Err(if compacted {
+                UndoError::Compacted
+            } else {
+                UndoError::NothingToUndo
+            });
+        };
+        let mut before = self.log.init_tourn();
+        for FullOp { op, salt, .. } in self.log.ops[..index].iter().cloned() {
+            _ = before.apply_op(salt, op).map_err(UndoError::TournError)?;
+        }
+        let FullOp { op, salt, id } = self.log.ops[index].clone();
+        let Some(inverse) = op.inverse(admin, salt, &before) else {
+            return Err(UndoError::Irreversible(Box::new(op)));
+        };
+        let f_ops: Vec<_> = inverse.into_iter().map(FullOp::new).collect();
+        let ids = f_ops.iter().map(|op| op.id).collect::<Vec<_>>();
+        _ = self
+            .bulk_apply_ops_inner(f_ops.into_iter())
+            .map_err(UndoError::TournError)?;
+        self.history.hidden.extend(ids);
+        _ = self.history.hidden.insert(id);
+        self.history.redo.push(op);
+        Ok(())
+    }
+
+    /// Reapplies the most recently undone operation, as it was originally submitted. Applying any
+    /// other operation clears the operations that can be redone.
+    pub fn redo(&mut self) -> Result<(), UndoError> {
+        let op = self.history.redo.pop().ok_or(UndoError::NothingToRedo)?;
+        if let Err(err) = self.apply_op_inner(FullOp::new(op.clone())) {
+            self.history.redo.push(op);
+            return Err(UndoError::TournError(err));
+        }
+        Ok(())
+    }
+
+    /// Returns if every operation in the log has been synced with the backend
+    pub fn is_synced(&self) -> bool {
+        self.last_sync == self.log.last_id()
+    }
+
+    /// Marks every operation in the log as synced. This is used when the manager is a fresh copy
+    /// from the backend.
+    pub(crate) fn mark_synced(&mut self) {
+        self.last_sync = self.log.last_id();
+    }
+
+    /// Replaces this manager with the backend's copy of the tournament. This is needed when the
+    /// backend has compacted the operation that this manager last synced at. The operations that
+    /// have not been synced are reapplied on top of the backend's copy so that they can be sent in
+    /// the next sync. Any operations that can no longer be applied are dropped and returned.
+    pub fn catch_up(&mut self, mut server: TournamentManager) -> Vec<FullOp> {
+        let unsynced: Vec<FullOp> = match self.last_sync {
+            Some(id) => self
+                .log
+                .iter_passed_op(id)
+                .map(|iter| iter.cloned().collect())
+                .unwrap_or_default(),
+            None => self.log.ops.clone(),
+        };
+        server.mark_synced();
+        let mut dropped = Vec::new();
+        for op in unsynced {
+            // Operations that the backend already has were synced, but the completion was lost
+            if server.log.get_slice(op.id).is_none() && server.apply_op_inner(op.clone()).is_err() {
+                dropped.push(op);
+            }
+        }
+        *self = server;
+        dropped
+    }
+
+    /// Returns the id of the last operation that was synced with the backend, if any
+    pub fn last_sync(&self) -> Option<OpId> {
+        self.last_sync
+    }
+
+    /// Applies the operations that the backend sent in response to a `FetchSince`. The sync must
+    /// start at the last synced operation. Like with `catch_up`, operations that have not been
+    /// synced are reapplied on top of the backend's operations, and any that can no longer be
+    /// applied are dropped and returned. `None` is returned, and the manager is left unchanged, if
+    /// the sync can not be applied.
+    pub fn apply_delta(&mut self, sync: OpSync) -> Option<Vec<FullOp>> {
+        let anchor = sync.first_id().ok()?;
+        if self.last_sync != Some(anchor) {
+            return None;
+        }
+        let unsynced: Vec<FullOp> = self.log.iter_passed_op(anchor)?.cloned().collect();
+        let tourn = self.log.get_state_with_slice(sync.ops)?;
+        self.set_rebuilt(tourn);
+        self.mark_synced();
+        let mut dropped = Vec::new();
+        for op in unsynced {
+            // Operations that the backend already has were synced, but the completion was lost
+            if self.log.get_slice(op.id).is_none() && self.apply_op_inner(op.clone()).is_err() {
+                dropped.push(op);
+            }
+        }
+        Some(dropped)
     }
 
     /// Method used by clients to create a request for syncing with the remote backend.
@@ -213,9 +618,9 @@ impl TournamentManager {
             Ok(proc) => proc,
             Err(err) => {
                 return match err {
-                    SyncError::UnknownOperation(_) | SyncError::TournUpdated => {
-                        SyncForwardResp::Aborted
-                    }
+                    SyncError::UnknownOperation(_)
+                    | SyncError::Compacted(_)
+                    | SyncError::TournUpdated => SyncForwardResp::Aborted,
                     SyncError::EmptySync => ForwardError::EmptySync.into(),
                     SyncError::InvalidRequest(err) => (*err).into(),
                     // TODO: Figure out what to do here... They shouldn't happen
@@ -224,6 +629,9 @@ impl TournamentManager {
                     SyncError::AlreadyCompleted => todo!(),
                     SyncError::Unauthorized => todo!(),
                     SyncError::InvalidReply => todo!(),
+                    // Only the server limits how often syncs are sent and checks when operations
+                    // were created, so the forwarded sync can not be trusted
+                    SyncError::RateLimited | SyncError::StaleOperation => SyncForwardResp::Aborted,
                 };
             }
         };
@@ -246,18 +654,91 @@ impl Deref for TournamentManager {
     }
 }
 
+/// Recreates the account that a player registered with, which is used to register them for a
+/// cloned tournament
+fn player_account(plyr: &Player) -> SquireAccount {
+    let display_name = plyr.game_name.clone().unwrap_or_else(|| plyr.name.clone());
+    let mut account = SquireAccount::new(plyr.name.clone(), display_name);
+    account.id = plyr.id.0.into();
+    account.permissions = plyr.sharing;
+    account
+}
+
+/// Removes everything from the tournament that users that aren't officials can't see
+fn redact(tourn: &mut Tournament) {
+    tourn.redact();
+    if !tourn.decklists_visible() {
+        tourn.hide_decklists();
+    }
+}
+
+/// Replaces the names in the accounts of players that don't share their names. Like
+/// [withhold_decklist], this is only done to operations that are kept by snapshots and never
+/// applied.
+fn withhold_accounts(op: &mut TournOp, reg: &PlayerRegistry) {
+    let accounts: Vec<(&mut SquireAccount, Option<&mut Option<String>>)> = match op {
+        TournOp::RegisterPlayer(account, name)
+        | TournOp::JudgeOp(_, JudgeOp::AdminRegisterPlayer(account, name)) => {
+            vec![(account, Some(name))]
+        }
+        TournOp::JudgeOp(_, JudgeOp::AdminRegisterPlayers(plyrs)) => plyrs
+            .iter_mut()
+            .map(|(account, name)| (account, Some(name)))
+            .collect(),
+        TournOp::AdminOp(_, AdminOp::LinkGuestAccount(_, account)) => vec![(account, None)],
+        _ => return,
+    };
+    for (account, name) in accounts {
+        if account.permissions.shares_name() {
+            continue;
+        }
+        // The player's name in the redacted registry is already anonymous
+        let anonymous = reg
+            .get_player(&account.id.0.into())
+            .map(|plyr| plyr.name.clone())
+            .unwrap_or_default();
+        account.user_name = anonymous.clone();
+        account.display_name = anonymous;
+        account.gamer_tags.clear();
+        if let Some(name) = name {
+            *name = None;
+        }
+    }
+}
+
+/// Swaps an operation that adds a deck for the removal of that deck. This is only done to the
+/// anchors of snapshots, which are matched by id and never applied.
+fn withhold_decklist(op: &mut TournOp) {
+    let withheld = match op {
+        TournOp::PlayerOp(p_id, PlayerOp::AddDeck(name, _)) => {
+            TournOp::PlayerOp(*p_id, PlayerOp::RemoveDeck(name.clone()))
+        }
+        TournOp::JudgeOp(official, JudgeOp::AdminAddDeck(p_id, name, _)) => {
+            TournOp::JudgeOp(*official, JudgeOp::AdminRemoveDeck(*p_id, name.clone()))
+        }
+        _ => return,
+    };
+    *op = withheld;
+}
+
 #[cfg(all(feature = "client", feature = "server"))]
 #[cfg(test)]
 #[allow(unused_results)]
 mod tests {
+    use chrono::Utc;
     use squire_lib::{
+        accounts::SharingPermissions,
+        admin::TournOfficialId,
         identifiers::AdminId,
-        operations::{AdminOp, TournOp},
+        operations::{AdminOp, JudgeOp, OpData, TournOp},
+        players::PlayerStatus,
+        rounds::RoundResult,
     };
     use squire_tests::{get_seed, spoof_account};
 
     use crate::sync::{
-        processor::SyncCompletion, OpSync, ServerOpLink, SyncForwardResp, TournamentManager,
+        processor::SyncCompletion, OpSync, RollbackError, ServerOpLink, SyncError, SyncForwardResp,
+        TournamentManager, UndoError,
     };
 
     fn reg_op() -> TournOp {
@@ -293,7 +774,7 @@ mod tests {
         let ServerOpLink::Completed(comp) = link.clone() else {
             panic!()
         };
-        let SyncCompletion::ForeignOnly(ref ops) = &comp else {
+        let SyncCompletion::ForeignOnly(ref ops, _) = &comp else {
             panic!()
         };
         assert_eq!(ops.len(), proc_len[0] + proc_len[2]);
@@ -342,6 +823,39 @@ mod tests {
         (server, c1, c2)
     }
 
+    #[test]
+    fn spectator_copies_hide_private_names() {
+        let mut tourn = TournamentManager::new(spoof_account(), get_seed());
+        let mut private = spoof_account();
+        private.user_name = "Jane Doe".to_owned();
+        private.display_name = "Jane Doe".to_owned();
+        private.permissions = SharingPermissions::Nothing;
+        let private_id = private.id.0.into();
+        let public = spoof_account();
+        let public_id = public.id.0.into();
+        tourn
+            .apply_op(TournOp::RegisterPlayer(private, Some("Jane".to_owned())))
+            .unwrap();
+        tourn
+            .apply_op(TournOp::RegisterPlayer(public.clone(), None))
+            .unwrap();
+
+        let mut spectator = tourn.for_spectators();
+        let plyr = spectator.get_player_by_id(&private_id).unwrap();
+        assert_eq!(plyr.name, "Anonymous 1");
+        let plyr = spectator.get_player_by_id(&public_id).unwrap();
+        assert_eq!(plyr.name, public.user_name);
+        // The name isn't left anywhere in the copy, including its operations log
+        let json = serde_json::to_string(&spectator).unwrap();
+        assert!(!json.contains("Jane"));
+        assert!(serde_json::to_string(&tourn).unwrap().contains("Jane"));
+
+        // The copy can still be synced
+        spectator.apply_op(reg_op()).unwrap();
+        let proc = tourn.init_sync(spectator.sync_request()).unwrap();
+        assert_eq!(proc.to_process.len(), 1);
+    }
+
     // Models what happens during the first sync a full initial sync
     #[test]
     fn initial_sync_test() {
@@ -439,7 +953,7 @@ mod tests {
         let ServerOpLink::Completed(comp) = link else {
             panic!()
         };
-        let SyncCompletion::Mixed(ref ops) = &comp else {
+        let SyncCompletion::Mixed(ref ops, _) = &comp else {
             panic!()
         };
         assert_eq!(ops.len(), 3);
@@ -500,7 +1014,7 @@ mod tests {
         let ServerOpLink::Completed(comp) = link else {
             panic!()
         };
-        let SyncCompletion::ForeignOnly(ref ops) = &comp else {
+        let SyncCompletion::ForeignOnly(ref ops, _) = &comp else {
             panic!()
         };
         assert_eq!(ops.len(), 2);
@@ -591,7 +1105,7 @@ mod tests {
         let ServerOpLink::Completed(comp) = link else {
             panic!()
         };
-        let SyncCompletion::ForeignOnly(ref ops) = &comp else {
+        let SyncCompletion::ForeignOnly(ref ops, _) = &comp else {
             panic!()
         };
         assert_eq!(ops.len(), 2);
@@ -632,7 +1146,7 @@ mod tests {
         let ServerOpLink::Completed(comp) = link else {
             panic!()
         };
-        let SyncCompletion::Mixed(ref ops) = &comp else {
+        let SyncCompletion::Mixed(ref ops, _) = &comp else {
             panic!()
         };
         assert_eq!(ops.len(), 3);
@@ -653,6 +1167,249 @@ mod tests {
         assert_eq!(c1.log.last_op().unwrap().op, c2_op);
     }
 
+    // Models what happens when a client syncs after the server has compacted its log, but the
+    // client's last sync is still the last compacted operation
+    #[test]
+    fn compacted_sync_test() {
+        let (mut server, mut c1, mut c2) = init_server_and_clients();
+        let tourn = server.tourn.clone();
+        assert_eq!(server.compact(0), 1);
+        assert!(server.log.is_empty());
+        assert_eq!(server.log.compacted_len(), 1);
+        assert_eq!(server.tourn, tourn);
+        assert_eq!(server.log.init_tourn(), tourn);
+
+        // The client's sync is anchored at the last compacted operation
+        let op = reg_op();
+        let sync = apply_op(&mut c1, op.clone(), 2);
+        let link = proc_sync(&mut server, sync, [1, 0, 1], &op);
+        let ServerOpLink::Completed(comp) = link else {
+            panic!()
+        };
+        c1.handle_completion(comp.clone()).unwrap();
+        let forward = server.init_sync_forwarding(comp);
+        assert_eq!(c2.handle_forwarded_sync(forward), SyncForwardResp::Success);
+        assert_eq!(server.tourn, c1.tourn);
+        assert_eq!(server.tourn, c2.tourn);
+    }
+
+    // Models what happens when a client falls behind the server's snapshot and needs to catch up
+    #[test]
+    fn catch_up_test() {
+        let (mut server, mut c1, mut c2) = init_server_and_clients();
+
+        // Client one sends many updates that client two never hears about
+        for _ in 0..10 {
+            let op = reg_op();
+            let sync = apply_op(&mut c1, op.clone(), 2);
+            let ServerOpLink::Completed(comp) = proc_sync(&mut server, sync, [1, 0, 1], &op) else {
+                panic!()
+            };
+            c1.handle_completion(comp).unwrap();
+        }
+        assert_eq!(server.compact(2), 9);
+        assert_eq!(server.log.len(), 2);
+
+        // Client two's sync is anchored at a compacted operation
+        let op = reg_op();
+        let sync = apply_op(&mut c2, op.clone(), 2);
+        assert_eq!(
+            server.init_sync(sync).unwrap_err(),
+            SyncError::Compacted(c2.last_sync.unwrap())
+        );
+
+        // Client two catches up and then syncs its update
+        assert!(c2.catch_up(server.clone()).is_empty());
+        assert!(!c2.is_synced());
+        assert_eq!(c2.log.compacted_len(), 9);
+        assert_eq!(c2.log.last_op().unwrap().op, op);
+        let sync = c2.sync_request();
+        assert_eq!(sync.len(), 2);
+        let ServerOpLink::Completed(comp) = proc_sync(&mut server, sync, [1, 0, 1], &op) else {
+            panic!()
+        };
+        c2.handle_completion(comp).unwrap();
+        assert!(c2.is_synced());
+        assert_eq!(server.tourn, c2.tourn);
+        assert_eq!(server.log, c2.log);
+    }
+
+    // Models what happens when a reconnecting client only fetches the operations it missed
+    #[test]
+    fn apply_delta_test() {
+        let (mut server, mut c1, mut c2) = init_server_and_clients();
+
+        // Client one sends updates while client two is offline
+        for _ in 0..3 {
+            let op = reg_op();
+            let sync = apply_op(&mut c1, op.clone(), 2);
+            let ServerOpLink::Completed(comp) = proc_sync(&mut server, sync, [1, 0, 1], &op) else {
+                panic!()
+            };
+            c1.handle_completion(comp).unwrap();
+        }
+
+        // Client two made an update of its own while offline
+        let op = reg_op();
+        let _ = apply_op(&mut c2, op.clone(), 2);
+
+        // The server sends everything after client two's last sync
+        let ops = server.log.get_slice(c2.last_sync().unwrap()).unwrap();
+        let (seed, owner) = server.seed_and_creator();
+        let delta = OpSync { owner, seed, ops };
+        assert_eq!(delta.len(), 4);
+
+        // A delta that is not anchored at the last sync is rejected
+        let mut bad = delta.clone();
+        let _ = bad.ops.ops.pop_front();
+        assert!(c2.apply_delta(bad).is_none());
+        assert_eq!(c2.log.len(), 2);
+
+        assert!(c2.apply_delta(delta).unwrap().is_empty());
+        assert!(!c2.is_synced());
+        assert_eq!(c2.log.len(), 5);
+        assert_eq!(c2.log.last_op().unwrap().op, op);
+        let sync = c2.sync_request();
+        assert_eq!(sync.len(), 2);
+        let ServerOpLink::Completed(comp) = proc_sync(&mut server, sync, [1, 0, 1], &op) else {
+            panic!()
+        };
+        c2.handle_completion(comp).unwrap();
+        assert!(c2.is_synced());
+        assert_eq!(server.tourn, c2.tourn);
+        assert_eq!(server.log, c2.log);
+    }
+
+    #[test]
+    fn audit_log_test() {
+        let owner = spoof_account();
+        let admin: AdminId = owner.id.0.into();
+        let mut manager = TournamentManager::new(owner.clone(), get_seed());
+        let OpData::RegisterPlayer(p_id) = manager.apply_op(reg_op()).unwrap() else {
+            panic!()
+        };
+        assert!(manager.audit_log().is_empty());
+
+        let drop_op = TournOp::AdminOp(admin, AdminOp::AdminDropPlayer(p_id));
+        _ = manager.apply_op(drop_op.clone()).unwrap();
+        _ = manager.apply_op(reg_op()).unwrap();
+        let log = manager.audit_log();
+        assert_eq!(log.len(), 1);
+        let entry = &log[0];
+        assert_eq!(entry.official, TournOfficialId::Admin(admin));
+        assert_eq!(entry.account, owner.id);
+        assert!(entry.name.is_some());
+        assert_eq!(entry.action, "Drop player");
+        assert!(entry.affects_player(p_id));
+        assert_eq!(entry.op, drop_op);
+
+        // Compacting the log does not lose the audit log
+        assert_eq!(manager.compact(1), 2);
+        assert_eq!(manager.audit_log(), log);
+        assert_eq!(manager.compact(0), 1);
+        assert_eq!(manager.audit_log(), log);
+    }
+
+    #[test]
+    fn undo_redo_test() {
+        let owner = spoof_account();
+        let admin: AdminId = owner.id.0.into();
+        let mut manager = TournamentManager::new(owner, get_seed());
+        let OpData::RegisterPlayer(p_id) = manager.apply_op(reg_op()).unwrap() else {
+            panic!()
+        };
+        assert_eq!(manager.undo(admin), Err(UndoError::NothingToUndo));
+        let status = |manager: &TournamentManager| manager.player_reg.players[&p_id].status;
+
+        let drop_op = TournOp::AdminOp(admin, AdminOp::AdminDropPlayer(p_id));
+        _ = manager.apply_op(drop_op.clone()).unwrap();
+        assert_eq!(status(&manager), PlayerStatus::Dropped);
+
+        // Undoing adds the reversing operations to the log
+        manager.undo(admin).unwrap();
+        assert_eq!(status(&manager), PlayerStatus::Registered);
+        assert_eq!(manager.log.len(), 3);
+        assert_eq!(manager.undo(admin), Err(UndoError::NothingToUndo));
+
+        manager.redo().unwrap();
+        assert_eq!(status(&manager), PlayerStatus::Dropped);
+        assert_eq!(manager.log.last_op().unwrap().op, drop_op);
+        assert_eq!(manager.redo(), Err(UndoError::NothingToRedo));
+        manager.undo(admin).unwrap();
+        assert_eq!(status(&manager), PlayerStatus::Registered);
+
+        // Applying a new operation clears what can be redone
+        let start = start_op(admin);
+        _ = manager.apply_op(start.clone()).unwrap();
+        assert_eq!(manager.redo(), Err(UndoError::NothingToRedo));
+        assert_eq!(
+            manager.undo(admin),
+            Err(UndoError::Irreversible(Box::new(start)))
+        );
+    }
+
+    #[test]
+    fn rollback_test() {
+        let owner = spoof_account();
+        let admin: AdminId = owner.id.0.into();
+        let mut manager = TournamentManager::new(owner, get_seed());
+        _ = manager.apply_op(reg_op()).unwrap();
+        let checkpoint = TournOp::AdminOp(admin, AdminOp::Checkpoint("Opening".into()));
+        _ = manager.apply_op(checkpoint).unwrap();
+        let tourn = manager.tourn.clone();
+        _ = manager.apply_op(reg_op()).unwrap();
+        _ = manager.apply_op(start_op(admin)).unwrap();
+        assert_eq!(manager.checkpoints().len(), 1);
+        assert_eq!(manager.checkpoints()[0].name, "Opening");
+
+        assert_eq!(
+            manager.rollback("Closing"),
+            Err(RollbackError::UnknownCheckpoint)
+        );
+        assert_eq!(manager.rollback("Opening").unwrap().len(), 2);
+        assert_eq!(manager.tourn, tourn);
+        assert_eq!(manager.log.len(), 2);
+
+        // Checkpoints that are compacted can only be rolled back to if they are the anchor
+        assert_eq!(manager.compact(0), 2);
+        assert_eq!(manager.rollback("Opening").unwrap().len(), 0);
+        _ = manager.apply_op(reg_op()).unwrap();
+        let checkpoint = TournOp::AdminOp(admin, AdminOp::Checkpoint("Second".into()));
+        _ = manager.apply_op(checkpoint).unwrap();
+        _ = manager.apply_op(reg_op()).unwrap();
+        assert_eq!(manager.compact(0), 3);
+        assert_eq!(manager.rollback("Opening"), Err(RollbackError::Compacted));
+    }
+
+    #[test]
+    fn standings_are_audited_against_the_log() {
+        let owner = spoof_account();
+        let admin: AdminId = owner.id.0.into();
+        let mut manager = TournamentManager::new(owner, get_seed());
+        for _ in 0..4 {
+            manager.apply_op(reg_op()).unwrap();
+        }
+        manager.apply_op(start_op(admin)).unwrap();
+        let pairings = manager.tourn.create_pairings().unwrap();
+        let rounds = manager
+            .apply_op(TournOp::AdminOp(admin, AdminOp::PairRound(pairings)))
+            .unwrap()
+            .assume_pair();
+        assert_eq!(manager.audit_standings(), Ok(Vec::new()));
+
+        // Changes that were made without going through the log are caught
+        for op in [
+            JudgeOp::AdminRecordResult(rounds[0], RoundResult::Draw(1)),
+            JudgeOp::ConfirmRound(rounds[0]),
+        ] {
+            manager
+                .tourn
+                .apply_op(Utc::now(), TournOp::JudgeOp(admin.into(), op))
+                .unwrap();
+        }
+        assert_eq!(manager.audit_standings().unwrap().len(), 2);
+    }
+
     // TODO: I think this is covered by second sync collision test
     // Models what happens during the second sync of a tournament, after client one and the server
     // have synced but client two and the server have drifted and there is a conflict
@@ -45,6 +45,7 @@ impl SyncChain {
                 None
             }
             ServerOpLink::Completed(_)
+            | ServerOpLink::CatchUp(_)
             | ServerOpLink::TerminatedSeen { .. }
             | ServerOpLink::Error(_) => Some((client, server)),
         }
@@ -54,9 +55,11 @@ impl SyncChain {
     /// we have seen this message, we return `Err(Ok(ServerOpLink))`. This signals that the message
     /// should not be processed and instead, the returned message should be returned
     pub fn validate_client_message(&self, msg: &ClientOpLink) -> Result<(), ServerOpLink> {
-        // TODO: Turn these panics into errors? This should only happen if a chain is created from
-        // a Init message and then that message is validated.
-        let last = self.links.last().unwrap();
+        // The chain's first message was turned away before it could be processed (e.g. it was
+        // unauthorized), so a resent copy is processed as if it were new
+        let Some(last) = self.links.last() else {
+            return Ok(());
+        };
         if msg == &last.0 {
             return Err(last.1.clone());
         }
@@ -0,0 +1,84 @@
+use std::{collections::HashMap, time::Duration};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use squire_lib::{identifiers::RoundId, rounds::Round, tournament::Tournament};
+
+/// The backend's notion of the current time and when each of a tournament's active rounds ends.
+/// The backend periodically broadcasts this so that every client shows the same countdown, no
+/// matter how far its own clock has drifted.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ClockSync {
+    /// The backend's time when the message was sent
+    pub now: DateTime<Utc>,
+    /// The active rounds, paired with when their time runs out
+    pub deadlines: Vec<(RoundId, DateTime<Utc>)>,
+}
+
+impl ClockSync {
+    /// Reads the current time and the deadlines of the tournament's active rounds
+    pub fn new(tourn: &Tournament) -> Self {
+        let deadlines = tourn
+            .round_reg
+            .rounds
+            .values()
+            .filter(|rnd| rnd.is_active())
+            .map(|rnd| (rnd.id, rnd.deadline()))
+            .collect();
+        Self {
+            now: Utc::now(),
+            deadlines,
+        }
+    }
+}
+
+/// A client's view of the backend's clock, built from the most recent `ClockSync`. Countdowns
+/// should be calculated from this rather than from the client's own clock.
+///
+/// NOTE: The time that the `ClockSync` spent in transit is not accounted for, so the corrected
+/// time trails the backend's by the latency of the connection.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ServerClock {
+    offset: chrono::Duration,
+    deadlines: HashMap<RoundId, DateTime<Utc>>,
+}
+
+impl ServerClock {
+    /// Creates a clock from a sync that the client received at the given (local) time
+    pub fn new(sync: ClockSync, received: DateTime<Utc>) -> Self {
+        Self {
+            offset: sync.now - received,
+            deadlines: sync.deadlines.into_iter().collect(),
+        }
+    }
+
+    /// Returns how far ahead of the client's clock the backend's clock is. This is negative if the
+    /// backend's clock is behind.
+    pub fn offset(&self) -> chrono::Duration {
+        self.offset
+    }
+
+    /// Calculates the backend's current time
+    pub fn now(&self) -> DateTime<Utc> {
+        Utc::now() + self.offset
+    }
+
+    /// Returns when the backend says the given round ends. `None` is returned if the round was not
+    /// active when the clock was last synced.
+    pub fn deadline(&self, id: &RoundId) -> Option<DateTime<Utc>> {
+        self.deadlines.get(id).copied()
+    }
+
+    /// Calculates the time left in the given round according to the backend. `None` is returned if
+    /// the round was not active when the clock was last synced.
+    pub fn time_left(&self, id: &RoundId) -> Option<Duration> {
+        let left = self.deadline(id)? - self.now();
+        Some(left.to_std().unwrap_or_default())
+    }
+
+    /// Calculates the time left in the round according to the backend's clock. Unlike
+    /// `time_left`, this also works for rounds created or extended since the last sync.
+    pub fn round_time_left(&self, rnd: &Round) -> Duration {
+        rnd.time_left_at(self.now())
+    }
+}
@@ -0,0 +1,77 @@
+use std::{
+    collections::{hash_map::DefaultHasher, HashSet, VecDeque},
+    hash::{Hash, Hasher},
+};
+
+use serde::Serialize;
+use uuid::Uuid;
+
+/// The number of messages that are remembered for each websocket. Retransmissions are sent every
+/// `RETRY_LIMIT`, so this comfortably covers the messages that can arrive while a connection is
+/// being replaced.
+pub const RECENT_MESSAGES: usize = 256;
+
+/// Remembers the messages that were most recently received over a websocket so that
+/// retransmissions (e.g. resent sync links or forwarded syncs) are handled at most once. Every
+/// message in a sync chain shares an id, so messages are told apart by their id and their body.
+#[derive(Debug, Default, Clone)]
+pub struct RecentMessages {
+    seen: HashSet<(Uuid, u64)>,
+    order: VecDeque<(Uuid, u64)>,
+}
+
+impl RecentMessages {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records that a message was received. Returns `false` if the message was already received
+    /// recently, i.e. it is a retransmission.
+    pub fn insert<B: Serialize>(&mut self, id: Uuid, body: &B) -> bool {
+        let key = (id, fingerprint(body));
+        if !self.seen.insert(key) {
+            return false;
+        }
+        self.order.push_back(key);
+        if self.order.len() > RECENT_MESSAGES {
+            if let Some(old) = self.order.pop_front() {
+                _ = self.seen.remove(&old);
+            }
+        }
+        true
+    }
+}
+
+fn fingerprint<B: Serialize>(body: &B) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    postcard::to_allocvec(body).unwrap().hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use uuid::Uuid;
+
+    use super::{RecentMessages, RECENT_MESSAGES};
+    use crate::sync::{ClientOpLink, ServerBound};
+
+    #[test]
+    fn retransmissions_are_spotted() {
+        let mut seen = RecentMessages::new();
+        let id = Uuid::new_v4();
+        assert!(seen.insert(id, &ServerBound::Presence));
+        assert!(!seen.insert(id, &ServerBound::Presence));
+
+        // Links in the same chain share an id but are not retransmissions of each other
+        let terminated = ServerBound::SyncChain(ClientOpLink::Terminated);
+        assert!(seen.insert(id, &terminated));
+        assert!(!seen.insert(id, &terminated));
+        assert!(seen.insert(Uuid::new_v4(), &terminated));
+
+        // Only the most recent messages are remembered
+        for _ in 0..RECENT_MESSAGES {
+            assert!(seen.insert(Uuid::new_v4(), &ServerBound::Presence));
+        }
+        assert!(seen.insert(id, &ServerBound::Presence));
+    }
+}
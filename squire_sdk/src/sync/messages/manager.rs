@@ -145,7 +145,12 @@ impl ClientSyncManager {
 
     pub fn is_latest_msg(&self, msg: &ServerBoundMessage) -> bool {
         match &msg.body {
-            ServerBound::Fetch | ServerBound::ForwardResp(_) => false,
+            ServerBound::Fetch
+            | ServerBound::FetchSince(_)
+            | ServerBound::ForwardResp(_)
+            | ServerBound::Presence
+            | ServerBound::ClockSync
+            | ServerBound::Rollback(_) => false,
             ServerBound::SyncChain(link) => self
                 .syncs
                 .get(&msg.id)
@@ -180,6 +185,11 @@ impl ClientSyncManager {
     pub fn finalize_chain(&mut self, id: &Uuid) {
         _ = self.syncs.remove(id);
     }
+
+    /// Finalizes every chain for the given tournament, e.g. after the backend has dropped them
+    pub fn finalize_tourn(&mut self, t_id: &TournamentId) {
+        self.syncs.retain(|_, sync| sync.id != *t_id);
+    }
 }
 
 impl ClientSyncTracker {
@@ -260,9 +270,11 @@ impl TimerStack {
     }
 }
 
+/// Tracks the forwarded syncs that are waiting on a response. The same message is forwarded to
+/// every onlooker, so chains are tracked per message and recipient.
 #[derive(Debug, Default)]
 pub struct ServerForwardingManager {
-    outbound: HashMap<Uuid, (AuthUser, TournamentId, OpSync)>,
+    outbound: HashMap<(Uuid, AuthUser), (TournamentId, OpSync)>,
 }
 
 impl ServerForwardingManager {
@@ -271,15 +283,21 @@ impl ServerForwardingManager {
     }
 
     pub fn add_msg(&mut self, id: Uuid, user: AuthUser, t_id: TournamentId, msg: OpSync) {
-        _ = self.outbound.insert(id, (user, t_id, msg));
+        _ = self.outbound.insert((id, user), (t_id, msg));
+    }
+
+    pub fn terminate_chain(&mut self, id: Uuid, user: AuthUser) {
+        _ = self.outbound.remove(&(id, user));
     }
 
-    pub fn terminate_chain(&mut self, id: &Uuid) {
-        _ = self.outbound.remove(id);
+    pub fn is_terminated(&self, id: Uuid, user: AuthUser) -> bool {
+        !self.outbound.contains_key(&(id, user))
     }
 
-    pub fn is_terminated(&self, id: &Uuid) -> bool {
-        self.outbound.contains_key(id)
+    /// Terminates all chains that are waiting on a response from the given user. This is used
+    /// when the user's connection is dropped, so that nothing is retried to them.
+    pub fn terminate_user(&mut self, user: &AuthUser) {
+        self.outbound.retain(|(_, u), _| u != user);
     }
 }
 
@@ -341,3 +359,43 @@ impl ClientForwardingManager {
             .retain(|_, (_, time)| time.elapsed() < TO_CLEAR_TIME_LIMIT);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use squire_tests::{get_seed, spoof_account};
+    use uuid::Uuid;
+
+    use super::ServerForwardingManager;
+    use crate::{
+        api::AuthUser,
+        sync::{OpSlice, OpSync},
+    };
+
+    fn spoof_user() -> AuthUser {
+        AuthUser::User(Uuid::new_v4().into())
+    }
+
+    #[test]
+    fn forwarding_is_tracked_per_user() {
+        let sync = OpSync {
+            owner: spoof_account(),
+            seed: get_seed(),
+            ops: OpSlice::new(),
+        };
+        let t_id = Uuid::new_v4().into();
+        let msg_id = Uuid::new_v4();
+        let (alice, bob) = (spoof_user(), spoof_user());
+        let mut manager = ServerForwardingManager::new();
+        manager.add_msg(msg_id, alice.clone(), t_id, sync.clone());
+        manager.add_msg(msg_id, bob.clone(), t_id, sync);
+        assert!(!manager.is_terminated(msg_id, alice.clone()));
+        assert!(!manager.is_terminated(msg_id, bob.clone()));
+
+        manager.terminate_chain(msg_id, alice.clone());
+        assert!(manager.is_terminated(msg_id, alice));
+        assert!(!manager.is_terminated(msg_id, bob.clone()));
+
+        manager.terminate_user(&bob);
+        assert!(manager.is_terminated(msg_id, bob));
+    }
+}
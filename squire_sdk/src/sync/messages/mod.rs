@@ -1,16 +1,35 @@
+#[cfg(any(feature = "client", feature = "server"))]
+use std::borrow::Cow;
+
+#[cfg(any(feature = "client", feature = "server"))]
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
-use squire_lib::tournament::TournamentId;
+use squire_lib::{
+    identifiers::{PlayerId, RoundId},
+    tournament::TournamentId,
+};
 use uuid::Uuid;
 
 use super::{
     processor::{SyncCompletion, SyncDecision, SyncProcessor},
-    ForwardError, OpSync, SyncError, TournamentManager,
+    ForwardError, LogDigest, OpId, OpSync, Rollback, RollbackError, SyncError, TournamentManager,
 };
+use crate::api::SessionToken;
+#[cfg(any(feature = "client", feature = "server"))]
+use crate::compat::{deflate, inflate};
 
 mod chain;
+mod clock;
+#[cfg(any(feature = "client", feature = "server"))]
+mod dedup;
 mod manager;
+mod presence;
 pub use chain::*;
+pub use clock::*;
+#[cfg(any(feature = "client", feature = "server"))]
+pub use dedup::*;
 pub use manager::*;
+pub use presence::*;
 
 pub type ServerBoundMessage = WebSocketMessage<ServerBound>;
 pub type ClientBoundMessage = WebSocketMessage<ClientBound>;
@@ -63,24 +82,119 @@ impl<B> WebSocketMessage<B> {
     }
 }
 
+/// Messages that are smaller than this (in bytes) are never compressed since there is little to
+/// gain from doing so.
+pub const COMPRESSION_THRESHOLD: usize = 1024;
+
+/// The schemes that can be used to compress the messages sent over a Websocket
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Compression {
+    /// Messages are sent as is
+    #[default]
+    None,
+    /// Large messages are compressed with DEFLATE
+    Deflate,
+}
+
+#[cfg(any(feature = "client", feature = "server"))]
+impl Compression {
+    /// The byte that marks which scheme was used to compress a message
+    fn tag(self) -> u8 {
+        match self {
+            Compression::None => 0,
+            Compression::Deflate => 1,
+        }
+    }
+
+    fn from_tag(tag: u8) -> Option<Self> {
+        match tag {
+            0 => Some(Compression::None),
+            1 => Some(Compression::Deflate),
+            _ => None,
+        }
+    }
+}
+
+/// The first message that a client sends after opening a Websocket. Unlike every other message,
+/// the handshake is never compressed.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Handshake {
+    /// The client's session token, which the server uses to authenticate the client
+    pub token: SessionToken,
+    /// The compression that the client can decode. The server only compresses the messages that it
+    /// sends if the client asks for it, but it can decode messages compressed with any scheme.
+    pub compression: Compression,
+    /// The token of an invite to the tournament (see
+    /// [`TournamentInvite::token`](crate::api::TournamentInvite::token)), which is needed to
+    /// follow a private tournament without being one of its officials or players
+    pub invite: Option<String>,
+}
+
+#[cfg(any(feature = "client", feature = "server"))]
+impl<B: Serialize> WebSocketMessage<B> {
+    /// Encodes the message to be sent over a Websocket. The first byte marks how the rest of the
+    /// message was compressed. Messages smaller than the `COMPRESSION_THRESHOLD` are not
+    /// compressed, regardless of the given scheme.
+    pub fn to_bytes(&self, compression: Compression) -> Vec<u8> {
+        let bytes = postcard::to_allocvec(self).unwrap();
+        let (compression, payload) = match compression {
+            Compression::Deflate if bytes.len() >= COMPRESSION_THRESHOLD => {
+                (Compression::Deflate, deflate(&bytes))
+            }
+            _ => (Compression::None, bytes),
+        };
+        let mut digest = Vec::with_capacity(payload.len() + 1);
+        digest.push(compression.tag());
+        digest.extend(payload);
+        digest
+    }
+}
+
+#[cfg(any(feature = "client", feature = "server"))]
+impl<B: DeserializeOwned> WebSocketMessage<B> {
+    /// Decodes a message that was encoded with `to_bytes`. Returns `None` if the bytes can not be
+    /// decompressed or deserialized.
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let (tag, payload) = bytes.split_first()?;
+        let payload = match Compression::from_tag(*tag)? {
+            Compression::None => Cow::Borrowed(payload),
+            Compression::Deflate => Cow::Owned(inflate(payload)?),
+        };
+        postcard::from_bytes(&payload).ok()
+    }
+}
+
 /// This type encodes all of the messages that a client might send to the backend via a Websocket.
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub enum ServerBound {
     /// Asks the server to send back a copy of the tournament manager for the tournament
     Fetch,
+    /// Asks the server for the operations that come after the given operation. This is used by
+    /// clients that already have a copy of the tournament and only need what they have missed.
+    FetchSince(OpId),
     /// The client has operations that it needs to sync with the backend. This encode a link in
     /// the chain of messages needed to sync.
     SyncChain(ClientOpLink),
     /// The backend has sent operations that need to be synced with the client. This is the
     /// client's response.
     ForwardResp(SyncForwardResp),
+    /// Asks the server who is currently connected to the tournament
+    Presence,
+    /// Asks the server for its clock and the deadlines of the tournament's active rounds
+    ClockSync,
+    /// Asks the server to roll the tournament back to a checkpoint. Only admins can do this.
+    Rollback(Rollback),
 }
 
 /// This type encodes all of the messages that the backend might send to a client via a Websocket.
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub enum ClientBound {
-    /// The client has requested a copy of the tournament data. This is that copy.
-    FetchResp(Box<TournamentManager>),
+    /// The client has requested a copy of the tournament data. This is that copy and the digest
+    /// of its log, which the client checks the copy against.
+    FetchResp(Box<TournamentManager>, LogDigest),
+    /// The client has requested the operations after one that it knows about. The sync starts at
+    /// that operation. If the server no longer has the operation, it sends a `FetchResp` instead.
+    FetchSinceResp(OpSync),
     /// The client has started the process of syncing tournament data with the server. This encodes
     /// the server's message in the sync message chain.
     SyncChain(ServerOpLink),
@@ -89,6 +203,40 @@ pub enum ClientBound {
     SyncForward((TournamentId, OpSync)),
     /// The user's session has been expired/deleted, so their message is auto-rejected.
     Unauthorized,
+    /// The server turned away the connection, which is closed after this message is sent.
+    Rejected(Rejection),
+    /// Who is currently connected to the tournament. This is both the response to a `Presence`
+    /// request and broadcast to all clients when someone connects or disconnects.
+    PresenceUpdate(TournamentId, Presence),
+    /// The server's clock and the deadlines of the tournament's active rounds. This is both the
+    /// response to a `ClockSync` request and periodically broadcast to all clients.
+    ClockSync(TournamentId, ClockSync),
+    /// The players that have not checked in at their tables within the grace period of the
+    /// tournament's tardiness policy. This is periodically broadcast to all clients.
+    TardyPlayers(TournamentId, Vec<(RoundId, PlayerId)>),
+    /// The server's response to a `Rollback` request
+    RollbackResp(Result<(), RollbackError>),
+    /// The tournament has been rolled back to a checkpoint. This is the server's copy of the
+    /// tournament after the rollback, which clients catch up to. Any sync that was in progress
+    /// when the rollback happened has been dropped by the server.
+    RolledBack(TournamentId, Box<TournamentManager>),
+}
+
+/// The reasons that the server can turn away a new connection to a tournament. Other than
+/// `TournamentNotFound`, these are only sent while the server is under heavy load or shutting down,
+/// so the client can try again later.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Rejection {
+    /// The tournament could not be found
+    TournamentNotFound,
+    /// The server is already hosting as many tournaments as it can
+    TooManyGatherings,
+    /// The tournament already has as many connections as it can take
+    GatheringFull,
+    /// The server already has as many connections as it can take
+    TooManyConnections,
+    /// The server is shutting down
+    ShuttingDown,
 }
 
 /// The process of syncing two instances of a tournament (between client and server) requires a
@@ -121,6 +269,11 @@ pub enum ServerOpLink {
     /// terminate the request, but it must communicate if the request finished before this message
     /// arrived.
     TerminatedSeen { already_done: bool },
+    /// The operation that the client's sync is anchored at has been compacted into the server's
+    /// snapshot, so the logs can not be merged. The server sends its copy of the tournament, which
+    /// the client rebases its unsynced operations onto before starting a new sync. This implicitly
+    /// closes the request.
+    CatchUp(Box<TournamentManager>),
     /// During the sync process, some kind of error occured between deserializing the message and
     /// processing the first operations (generally, this is an error with the `OpSync`). This needs
     /// to be communicated with the client. This implicitly closes the request.
@@ -141,3 +294,34 @@ pub enum SyncForwardResp {
     /// implicitly cancels the sync.
     Error(Box<ForwardError>),
 }
+
+#[cfg(any(feature = "client", feature = "server"))]
+#[cfg(test)]
+mod tests {
+    use super::{Compression, WebSocketMessage, COMPRESSION_THRESHOLD};
+
+    #[test]
+    fn compression_round_trip() {
+        let small = WebSocketMessage::new(vec![7u8; 16]);
+        let bytes = small.to_bytes(Compression::Deflate);
+        // Small messages are sent as is
+        assert_eq!(bytes[0], Compression::None.tag());
+        assert_eq!(WebSocketMessage::from_bytes(&bytes), Some(small));
+
+        let large = WebSocketMessage::new(vec![7u8; 4 * COMPRESSION_THRESHOLD]);
+        let plain = large.to_bytes(Compression::None);
+        let bytes = large.to_bytes(Compression::Deflate);
+        assert_eq!(bytes[0], Compression::Deflate.tag());
+        assert!(bytes.len() < plain.len());
+        assert_eq!(WebSocketMessage::from_bytes(&bytes), Some(large.clone()));
+        assert_eq!(WebSocketMessage::from_bytes(&plain), Some(large));
+
+        // Unknown schemes and corrupted payloads are rejected
+        assert_eq!(WebSocketMessage::<Vec<u8>>::from_bytes(&[9, 0, 0]), None);
+        assert_eq!(
+            WebSocketMessage::<Vec<u8>>::from_bytes(&[1, 0xFF, 0xFF]),
+            None
+        );
+        assert_eq!(WebSocketMessage::<Vec<u8>>::from_bytes(&[]), None);
+    }
+}
@@ -0,0 +1,76 @@
+use serde::{Deserialize, Serialize};
+use squire_lib::{identifiers::SquireAccountId, tournament::TournRole};
+
+/// The role that a connected user has in a tournament
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PresenceRole {
+    /// The user is an admin of the tournament
+    Admin,
+    /// The user is a scorekeeper in the tournament
+    Scorekeeper,
+    /// The user is a judge in the tournament
+    Judge,
+    /// The user is an active player in the tournament
+    Player,
+    /// The user is only watching the tournament
+    Spectator,
+}
+
+impl From<TournRole> for PresenceRole {
+    fn from(role: TournRole) -> Self {
+        match role {
+            TournRole::Admin(_) => PresenceRole::Admin,
+            TournRole::Scorekeeper(_) => PresenceRole::Scorekeeper,
+            TournRole::Judge(_) => PresenceRole::Judge,
+            TournRole::Player(_) => PresenceRole::Player,
+            TournRole::Spectator => PresenceRole::Spectator,
+        }
+    }
+}
+
+/// Who is currently connected to a tournament. The backend sends this whenever someone connects
+/// to or disconnects from a tournament.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq)]
+pub struct Presence {
+    /// The accounts that are connected, paired with their role in the tournament
+    pub users: Vec<(SquireAccountId, PresenceRole)>,
+    /// The number of guests (i.e. users that are not logged in) that are connected
+    pub guests: usize,
+}
+
+impl Presence {
+    /// Calculates the number of connected users with the given role. Guests are not counted.
+    pub fn count(&self, role: PresenceRole) -> usize {
+        self.users.iter().filter(|(_, r)| *r == role).count()
+    }
+
+    /// Calculates the number of connected admins
+    pub fn admins(&self) -> usize {
+        self.count(PresenceRole::Admin)
+    }
+
+    /// Calculates the number of connected judges
+    pub fn judges(&self) -> usize {
+        self.count(PresenceRole::Judge)
+    }
+
+    /// Calculates the number of connected players
+    pub fn players(&self) -> usize {
+        self.count(PresenceRole::Player)
+    }
+
+    /// Calculates the number of connected spectators, including guests
+    pub fn spectators(&self) -> usize {
+        self.count(PresenceRole::Spectator) + self.guests
+    }
+
+    /// Calculates the total number of connections
+    pub fn len(&self) -> usize {
+        self.users.len() + self.guests
+    }
+
+    /// Calculates if no one is connected
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
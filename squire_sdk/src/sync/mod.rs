@@ -2,19 +2,31 @@ use serde::{Deserialize, Serialize};
 
 use crate::model::{accounts::SquireAccount, identifiers::TypeId, tournament::*};
 
+pub mod audit;
 pub mod collections;
+pub mod digest;
 pub mod error;
+pub mod events;
 pub mod full_op;
 pub mod manager;
 pub mod messages;
 pub mod processor;
+pub mod replay;
+pub mod rollback;
+#[cfg(feature = "testing")]
+pub mod testing;
 mod utils;
 
+pub use audit::*;
 pub use collections::*;
+pub use digest::*;
 pub use error::*;
+pub use events::*;
 pub use full_op::*;
 pub use manager::*;
 pub use messages::*;
+pub use replay::*;
+pub use rollback::*;
 
 /// The id type for `FullOp`
 pub type OpId = TypeId<FullOp>;
@@ -1,10 +1,16 @@
+use std::fmt::Display;
+
 use serde::{Deserialize, Serialize};
 
-#[cfg(feature = "server")]
-use super::OpId;
-use super::{FullOp, OpSlice};
+use super::{FullOp, LogDigest, OpId, OpSlice};
 #[cfg(any(feature = "client", feature = "server"))]
 use super::{OpLog, OpSync, SyncError};
+use crate::model::{
+    admin::TournOfficialId,
+    identifiers::{PlayerId, RoundId},
+    operations::TournOp,
+    tournament::Tournament,
+};
 
 /// This type results from a client making a decision about what operations need to stay and what
 /// operations need to be removed from its log during the sync process.
@@ -17,20 +23,21 @@ pub enum SyncDecision {
     Purged(SyncCompletion),
 }
 
-/// This type encodes the result of a successful sync.
+/// This type encodes the result of a successful sync. Each completion carries the digest of the
+/// backend's log once the sync was completed, which the client checks its own log against.
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
 pub enum SyncCompletion {
     /// The backend did not have any operations that were unknown to the client.
-    ForeignOnly(OpSlice),
+    ForeignOnly(OpSlice, LogDigest),
     /// The backend had one or more operations that were unknown to the client and the logs were
     /// successfully merged.
-    Mixed(OpSlice),
+    Mixed(OpSlice, LogDigest),
 }
 
 impl SyncCompletion {
     pub fn len(&self) -> usize {
         match self {
-            Self::ForeignOnly(ops) | Self::Mixed(ops) => ops.len(),
+            Self::ForeignOnly(ops, _) | Self::Mixed(ops, _) => ops.len(),
         }
     }
 
@@ -40,18 +47,164 @@ impl SyncCompletion {
 
     pub fn as_slice(self) -> OpSlice {
         match self {
-            SyncCompletion::ForeignOnly(ops) | SyncCompletion::Mixed(ops) => ops,
+            SyncCompletion::ForeignOnly(ops, _) | SyncCompletion::Mixed(ops, _) => ops,
         }
     }
 
     /// Returns an iterator over the operations
     pub fn iter(&self) -> impl Iterator<Item = &FullOp> {
         match self {
-            SyncCompletion::ForeignOnly(ops) | SyncCompletion::Mixed(ops) => ops.iter(),
+            SyncCompletion::ForeignOnly(ops, _) | SyncCompletion::Mixed(ops, _) => ops.iter(),
+        }
+    }
+
+    /// Returns the digest of the backend's log once the sync was completed
+    pub fn digest(&self) -> LogDigest {
+        match self {
+            Self::ForeignOnly(_, digest) | Self::Mixed(_, digest) => *digest,
+        }
+    }
+
+    /// Stamps the completion with the digest of the backend's log
+    #[cfg(feature = "server")]
+    pub(crate) fn stamp(mut self, log: &OpLog) -> Self {
+        match &mut self {
+            Self::ForeignOnly(_, digest) | Self::Mixed(_, digest) => *digest = log.digest(),
+        }
+        self
+    }
+}
+
+/// A human-readable description of a single operation. These are used to present the operations
+/// involved in a sync conflict to a user.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct OpDescription {
+    /// The id of the described operation
+    pub id: OpId,
+    /// The name of the player or official that submitted the operation
+    pub actor: String,
+    /// A short name for what the operation does, e.g. "Record result"
+    pub action: &'static str,
+    /// The players that the operation acts on, paired with their names
+    pub players: Vec<(PlayerId, String)>,
+    /// The rounds that the operation acts on, paired with a label for the round
+    pub rounds: Vec<(RoundId, String)>,
+}
+
+/// A structured view of a sync conflict. This is derived from the `SyncProcessor` that the
+/// backend sends when one of the client's operations can not be applied.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ConflictDiff {
+    /// The client operation that the backend failed to apply. Plucking removes only this
+    /// operation.
+    pub local: OpDescription,
+    /// The client operations that follow the conflicting operation. Purging removes these along
+    /// with the conflicting operation.
+    pub pending: Vec<OpDescription>,
+    /// The operations that the backend has applied since the last operation that both sides
+    /// agree on
+    pub remote: Vec<OpDescription>,
+}
+
+impl OpDescription {
+    /// Creates a description of an operation, looking up names in the given tournament. Players
+    /// and officials that the tournament does not know about are described by their ids.
+    pub fn new(op: &FullOp, tourn: &Tournament) -> Self {
+        let actor = match &op.op {
+            TournOp::RegisterPlayer(account, name) => {
+                name.clone().unwrap_or_else(|| account.get_user_name())
+            }
+            TournOp::PlayerOp(p_id, _) => player_name(tourn, p_id),
+            TournOp::JudgeOp(TournOfficialId::Judge(j_id), _) => tourn
+                .judges
+                .get(j_id)
+                .map(|judge| judge.name.clone())
+                .unwrap_or_else(|| j_id.to_string()),
+            TournOp::JudgeOp(TournOfficialId::Scorekeeper(s_id), _) => tourn
+                .scorekeepers
+                .get(s_id)
+                .map(|sk| sk.name.clone())
+                .unwrap_or_else(|| s_id.to_string()),
+            TournOp::JudgeOp(TournOfficialId::Admin(a_id), _) | TournOp::AdminOp(a_id, _) => tourn
+                .admins
+                .get(a_id)
+                .map(|admin| admin.name.clone())
+                .unwrap_or_else(|| a_id.to_string()),
+        };
+        let players = op
+            .op
+            .player_ids()
+            .into_iter()
+            .map(|id| (id, player_name(tourn, &id)))
+            .collect();
+        let rounds = op
+            .op
+            .round_ids()
+            .into_iter()
+            .map(|id| {
+                let label = match tourn.round_reg.get_round_number(&id) {
+                    Ok(num) => format!("Round #{num}"),
+                    Err(_) => id.to_string(),
+                };
+                (id, label)
+            })
+            .collect();
+        Self {
+            id: op.id,
+            actor,
+            action: op.op.name(),
+            players,
+            rounds,
+        }
+    }
+
+    /// Calculates if this operation and another act on any of the same players or rounds
+    pub fn overlaps(&self, other: &Self) -> bool {
+        self.players
+            .iter()
+            .any(|(id, _)| other.players.iter().any(|(o_id, _)| id == o_id))
+            || self
+                .rounds
+                .iter()
+                .any(|(id, _)| other.rounds.iter().any(|(o_id, _)| id == o_id))
+    }
+}
+
+impl Display for OpDescription {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.actor, self.action)?;
+        if !self.rounds.is_empty() {
+            let rounds: Vec<_> = self
+                .rounds
+                .iter()
+                .map(|(_, label)| label.as_str())
+                .collect();
+            write!(f, " in {}", rounds.join(", "))?;
+        }
+        if !self.players.is_empty() {
+            let players: Vec<_> = self.players.iter().map(|(_, name)| name.as_str()).collect();
+            write!(f, " for {}", players.join(", "))?;
         }
+        Ok(())
     }
 }
 
+impl ConflictDiff {
+    /// Returns the remote operations that act on the same players or rounds as the conflicting
+    /// operation. These are the likely cause of the conflict.
+    pub fn related(&self) -> impl Iterator<Item = &OpDescription> {
+        self.remote.iter().filter(|desc| desc.overlaps(&self.local))
+    }
+}
+
+fn player_name(tourn: &Tournament, id: &PlayerId) -> String {
+    tourn
+        .player_reg
+        .get_player_name(id)
+        .cloned()
+        .unwrap_or_else(|| id.to_string())
+}
+
 /// This struct contain an in-progress sync. The processor is mostly used internally by the
 /// `TournamentManager` to process sync requests; however, it is also shared during the sync
 /// process so that the client can audit its log. Those methods produce an `OpDecision`.
@@ -86,6 +239,27 @@ impl SyncProcessor {
         SyncDecision::Purged(self.finalize())
     }
 
+    /// Describes the conflict that this processor encodes, using the given tournament to look up
+    /// the names of players, officials, and rounds. Returns `None` if there is no operation left
+    /// to process, i.e. there is no conflict.
+    pub fn diff(&self, tourn: &Tournament) -> Option<ConflictDiff> {
+        let mut local = self
+            .to_process
+            .iter()
+            .map(|op| OpDescription::new(op, tourn));
+        Some(ConflictDiff {
+            local: local.next()?,
+            pending: local.collect(),
+            // The first known operation is the anchor, which both sides agree on
+            remote: self
+                .known
+                .iter()
+                .skip(1)
+                .map(|op| OpDescription::new(op, tourn))
+                .collect(),
+        })
+    }
+
     /// Returns whether or not the process will merge two logs or just one
     pub fn is_foreign_only(&self) -> bool {
         self.known.len() <= 1
@@ -115,6 +289,7 @@ impl SyncProcessor {
                 }
                 slice
             }
+            None if log.is_compacted() => return Err(SyncError::Compacted(id)),
             None if log.is_empty() => OpSlice::new(),
             _ => return Err(SyncError::UnknownOperation(id)),
         };
@@ -130,13 +305,15 @@ impl SyncProcessor {
         self.known.last_id()
     }
 
+    /// Concludes the sync. The completion is stamped with the backend's digest before it is sent.
     pub(crate) fn finalize(mut self) -> SyncCompletion {
+        let digest = LogDigest::default();
         if self.is_foreign_only() {
             self.known.extend(self.processed);
-            SyncCompletion::ForeignOnly(self.known)
+            SyncCompletion::ForeignOnly(self.known, digest)
         } else {
             self.known.extend(self.processed);
-            SyncCompletion::Mixed(self.known)
+            SyncCompletion::Mixed(self.known, digest)
         }
     }
 
@@ -244,11 +421,14 @@ impl Drop for Processing<'_> {
 
 #[cfg(all(test, feature = "server"))]
 mod tests {
-    use squire_lib::operations::TournOp;
-    use squire_tests::spoof_account;
+    use squire_lib::{
+        identifiers::AdminId,
+        operations::{AdminOp, PlayerOp, TournOp},
+    };
+    use squire_tests::{get_seed, spoof_account};
 
     use super::SyncProcessor;
-    use crate::sync::{FullOp, OpSlice};
+    use crate::sync::{FullOp, OpSlice, TournamentManager};
 
     fn spoof_op() -> FullOp {
         FullOp::new(TournOp::RegisterPlayer(spoof_account(), None))
@@ -358,4 +538,70 @@ mod tests {
             assert_eq!(proc.processed.len(), len);
         })
     }
+
+    #[test]
+    fn conflict_diff() {
+        let owner = spoof_account();
+        let admin: AdminId = owner.id.0.into();
+        let mut tourn = TournamentManager::new(owner, get_seed());
+        let p_one = tourn
+            .apply_op(TournOp::RegisterPlayer(
+                spoof_account(),
+                Some("Alice".into()),
+            ))
+            .unwrap()
+            .assume_register_player();
+        let p_two = tourn
+            .apply_op(TournOp::RegisterPlayer(spoof_account(), Some("Bob".into())))
+            .unwrap()
+            .assume_register_player();
+        tourn
+            .apply_op(TournOp::AdminOp(admin, AdminOp::Start))
+            .unwrap()
+            .assume_nothing();
+        let r_id = tourn
+            .apply_op(TournOp::AdminOp(
+                admin,
+                AdminOp::CreateRound(vec![p_one, p_two]),
+            ))
+            .unwrap()
+            .assume_create_round();
+        let round_num = tourn.round_reg.get_round_number(&r_id).unwrap();
+
+        let proc = SyncProcessor {
+            known: OpSlice::from_iter([
+                spoof_op(),
+                FullOp::new(TournOp::AdminOp(admin, AdminOp::RemoveRound(r_id))),
+                FullOp::new(TournOp::AdminOp(admin, AdminOp::GiveBye(p_two))),
+            ]),
+            processed: OpSlice::new(),
+            to_process: OpSlice::from_iter([
+                FullOp::new(TournOp::PlayerOp(p_one, PlayerOp::ConfirmResult(r_id))),
+                FullOp::new(TournOp::PlayerOp(p_one, PlayerOp::CheckIn)),
+            ]),
+        };
+        let diff = proc.diff(&tourn).unwrap();
+        assert_eq!(diff.local.actor, "Alice");
+        assert_eq!(diff.local.action, "Confirm result");
+        assert_eq!(
+            diff.local.rounds,
+            vec![(r_id, format!("Round #{round_num}"))]
+        );
+        assert_eq!(
+            diff.local.to_string(),
+            format!("Alice: Confirm result in Round #{round_num} for Alice")
+        );
+        assert_eq!(diff.pending.len(), 1);
+        assert_eq!(diff.remote.len(), 2);
+        let related: Vec<_> = diff.related().collect();
+        assert_eq!(related.len(), 1);
+        assert_eq!(related[0].action, "Remove round");
+
+        let empty = SyncProcessor {
+            known: OpSlice::from_iter(spoof_ops(1)),
+            processed: OpSlice::new(),
+            to_process: OpSlice::new(),
+        };
+        assert!(empty.diff(&tourn).is_none());
+    }
 }
@@ -0,0 +1,189 @@
+use std::slice;
+
+use squire_lib::{operations::OpResult, tournament::Tournament};
+
+use crate::sync::{FullOp, TournamentManager};
+
+/// An operation from a tournament's log and the result of applying it during a replay
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReplayStep {
+    /// The position of the operation in the log, starting from one. Compacted operations are
+    /// counted.
+    pub index: usize,
+    /// The operation that was applied
+    pub op: FullOp,
+    /// The result of applying the operation
+    pub result: OpResult,
+}
+
+/// The ways that replaying a tournament's log can diverge from the recorded tournament
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReplayDivergence {
+    /// The operation is in the log, but it could not be applied
+    Failed(ReplayStep),
+    /// Applying the operation to the same state twice gave different results, so the recorded
+    /// state can not be relied on
+    Nondeterministic(ReplayStep),
+    /// Every operation was applied, but the result differs from the recorded tournament. The parts
+    /// of the tournament that differ are listed.
+    Mismatch(Vec<&'static str>),
+}
+
+/// Replays a tournament's log one operation at a time, starting from the earliest state that the
+/// log can recreate (i.e. the snapshot, if the log has been compacted). Each step applies the next
+/// operation and yields its result. The state after the latest step is given by `tourn`.
+#[derive(Debug, Clone)]
+pub struct Replay<'a> {
+    ops: slice::Iter<'a, FullOp>,
+    index: usize,
+    tourn: Tournament,
+}
+
+impl<'a> Replay<'a> {
+    pub(crate) fn new(manager: &'a TournamentManager) -> Self {
+        let mut tourn = manager.op_log().init_tourn();
+        // A new tournament is given a random id, which the log does not know
+        tourn.id = manager.tourn().id;
+        Self {
+            ops: manager.op_log().ops.iter(),
+            index: manager.op_log().compacted_len(),
+            tourn,
+        }
+    }
+
+    /// Returns the tournament as of the latest step
+    pub fn tourn(&self) -> &Tournament {
+        &self.tourn
+    }
+
+    /// Returns the tournament as of the latest step, ending the replay
+    pub fn into_tourn(self) -> Tournament {
+        self.tourn
+    }
+}
+
+impl Iterator for Replay<'_> {
+    type Item = ReplayStep;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let op = self.ops.next()?.clone();
+        let result = self.tourn.apply_op(op.salt, op.op.clone());
+        self.index += 1;
+        Some(ReplayStep {
+            index: self.index,
+            op,
+            result,
+        })
+    }
+}
+
+impl TournamentManager {
+    /// Replays the tournament's log one operation at a time. This is used to debug how a
+    /// tournament got into its current state.
+    pub fn replay(&self) -> Replay<'_> {
+        Replay::new(self)
+    }
+
+    /// Replays the tournament's log and checks that the replay recreates the tournament. Each
+    /// operation is applied twice to catch operations that do not apply the same way every time.
+    /// The first divergence is returned. Otherwise, the number of operations that were replayed is
+    /// returned.
+    pub fn verify_replay(&self) -> Result<usize, ReplayDivergence> {
+        let mut replay = self.replay();
+        let mut count = 0;
+        loop {
+            let mut again = replay.tourn().clone();
+            let Some(step) = replay.next() else {
+                break;
+            };
+            if step.result.is_err() {
+                return Err(ReplayDivergence::Failed(step));
+            }
+            let result = again.apply_op(step.op.salt, step.op.op.clone());
+            if result != step.result || again != *replay.tourn() {
+                return Err(ReplayDivergence::Nondeterministic(step));
+            }
+            count += 1;
+        }
+        let diff = differing_parts(replay.tourn(), self.tourn());
+        if diff.is_empty() {
+            Ok(count)
+        } else {
+            Err(ReplayDivergence::Mismatch(diff))
+        }
+    }
+}
+
+/// Lists the parts of the tournaments that differ
+fn differing_parts(a: &Tournament, b: &Tournament) -> Vec<&'static str> {
+    [
+        ("id", a.id == b.id),
+        ("name", a.name == b.name),
+        ("players", a.player_reg == b.player_reg),
+        ("rounds", a.round_reg == b.round_reg),
+        ("pairings", a.pairing_sys == b.pairing_sys),
+        ("scoring", a.scoring_sys == b.scoring_sys),
+        ("registration", a.reg_open == b.reg_open),
+        ("settings", a.settings == b.settings),
+        ("status", a.status == b.status),
+        ("judges", a.judges == b.judges),
+        ("scorekeepers", a.scorekeepers == b.scorekeepers),
+        ("admins", a.admins == b.admins),
+        ("announcements", a.announcements == b.announcements),
+    ]
+    .into_iter()
+    .filter_map(|(part, same)| (!same).then_some(part))
+    .collect()
+}
+
+#[cfg(feature = "client")]
+#[cfg(test)]
+mod tests {
+    use squire_tests::{get_seed, spoof_account};
+
+    use super::ReplayDivergence;
+    use crate::{
+        model::operations::{AdminOp, TournOp},
+        sync::TournamentManager,
+    };
+
+    fn spoof_tourn() -> TournamentManager {
+        let admin = spoof_account();
+        let admin_id = admin.id.0.into();
+        let mut tourn = TournamentManager::new(admin, get_seed());
+        for _ in 0..4 {
+            let _ = tourn
+                .apply_op(TournOp::RegisterPlayer(spoof_account(), None))
+                .unwrap();
+        }
+        let _ = tourn
+            .apply_op(TournOp::AdminOp(admin_id, AdminOp::Start))
+            .unwrap();
+        tourn
+    }
+
+    #[test]
+    fn replays_recreate_the_tournament() {
+        let tourn = spoof_tourn();
+        assert_eq!(tourn.replay().count(), 5);
+        assert_eq!(tourn.verify_replay(), Ok(5));
+
+        // Compacted operations are counted but not replayed
+        let mut compacted = tourn.clone();
+        assert_eq!(compacted.compact(2), 3);
+        let indices: Vec<_> = compacted.replay().map(|step| step.index).collect();
+        assert_eq!(indices, vec![4, 5]);
+        assert_eq!(compacted.verify_replay(), Ok(2));
+    }
+
+    #[test]
+    fn edited_tournaments_are_caught() {
+        let mut edited = serde_json::to_value(spoof_tourn()).unwrap();
+        edited["tourn"]["reg_open"] = (!edited["tourn"]["reg_open"].as_bool().unwrap()).into();
+        let tourn: TournamentManager = serde_json::from_value(edited).unwrap();
+        assert_eq!(
+            tourn.verify_replay(),
+            Err(ReplayDivergence::Mismatch(vec!["registration"]))
+        );
+    }
+}
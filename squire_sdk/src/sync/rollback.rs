@@ -0,0 +1,104 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use squire_lib::{
+    error::TournamentError,
+    identifiers::AdminId,
+    operations::{AdminOp, TournOp},
+};
+
+use crate::sync::{FullOp, OpId, TournamentManager};
+
+/// A named point in a tournament's history that the tournament can be rolled back to. Admins
+/// create checkpoints by applying `AdminOp::Checkpoint`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Checkpoint {
+    /// The name of the checkpoint
+    pub name: String,
+    /// The id of the operation that created the checkpoint
+    pub id: OpId,
+    /// The admin that created the checkpoint
+    pub admin: AdminId,
+    /// When the checkpoint was created
+    pub time: DateTime<Utc>,
+}
+
+impl Checkpoint {
+    /// Creates a checkpoint from the operation that created it. `None` is returned if the
+    /// operation does not create a checkpoint.
+    fn from_op(f_op: &FullOp) -> Option<Self> {
+        let TournOp::AdminOp(admin, AdminOp::Checkpoint(name)) = &f_op.op else {
+            return None;
+        };
+        Some(Self {
+            name: name.clone(),
+            id: f_op.id,
+            admin: *admin,
+            time: f_op.salt,
+        })
+    }
+}
+
+/// A request, sent by an admin to the backend, to roll a tournament back to the most recent
+/// checkpoint with the given name. The backend removes every operation after the checkpoint and
+/// sends the rolled back tournament to everyone that is connected to it.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Rollback {
+    /// The name of the checkpoint
+    pub checkpoint: String,
+}
+
+/// The errors that can occur when rolling a tournament back to a checkpoint
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub enum RollbackError {
+    /// There is no checkpoint with the given name
+    UnknownCheckpoint,
+    /// The checkpoint has been compacted into the log's snapshot, so the tournament's state at the
+    /// checkpoint can not be recreated
+    Compacted,
+    /// Only the tournament's admins can roll it back
+    Unauthorized,
+    /// The operations before the checkpoint could not be reapplied
+    ///
+    /// NOTE: This should never happen since the operations have already been applied once. If it
+    /// does, it needs to be logged.
+    TournError(TournamentError),
+}
+
+impl TournamentManager {
+    /// Returns the checkpoints that the tournament can be rolled back to, oldest first.
+    /// Checkpoints that have been compacted are not included.
+    pub fn checkpoints(&self) -> Vec<Checkpoint> {
+        let log = self.op_log();
+        log.snapshot
+            .iter()
+            .map(|snap| &snap.anchor)
+            .chain(&log.ops)
+            .filter_map(Checkpoint::from_op)
+            .collect()
+    }
+
+    /// Rolls the tournament back to the most recent checkpoint with the given name by removing
+    /// every operation that came after it. The removed operations are returned.
+    ///
+    /// Rollbacks are decided by the backend, which sends the rolled back tournament to its
+    /// clients. Clients should not roll back their own copies since the backend would not know
+    /// which operations were removed.
+    pub fn rollback(&mut self, name: &str) -> Result<Vec<FullOp>, RollbackError> {
+        let log = self.op_log();
+        let anchor = log.snapshot.as_ref().map(|snap| &snap.anchor);
+        let kept = match log.ops.iter().rposition(|op| is_checkpoint(op, name)) {
+            Some(index) => index + 1,
+            None if anchor.is_some_and(|op| is_checkpoint(op, name)) => 0,
+            None if log.official_ops().any(|op| is_checkpoint(op, name)) => {
+                return Err(RollbackError::Compacted)
+            }
+            None => return Err(RollbackError::UnknownCheckpoint),
+        };
+        self.truncate(kept).map_err(RollbackError::TournError)
+    }
+}
+
+/// Returns if the operation created a checkpoint with the given name
+fn is_checkpoint(f_op: &FullOp, name: &str) -> bool {
+    matches!(&f_op.op, TournOp::AdminOp(_, AdminOp::Checkpoint(n)) if n == name)
+}
@@ -0,0 +1,535 @@
+//! Tools for property testing the sync protocol. These are meant for both our own tests and for
+//! those implementing their own clients or backends.
+//!
+//! There are two pieces. An `OpGenerator` creates sequences of operations that are valid for the
+//! tournament that they are applied to. A `SyncHarness` runs a backend and several clients in
+//! memory and passes the sync messages between them over a simulated network that can drop,
+//! duplicate, and reorder messages. After the network is healed, every client should converge to
+//! the backend's copy of the tournament.
+//!
+//! Everything is driven by a seed, so a failing run can be replayed by reusing its seed. Note that
+//! the ids of operations and accounts are still random, so only the schedule of events is
+//! replayed.
+
+use std::collections::HashMap;
+
+use uuid::Uuid;
+
+use super::{
+    ClientForwardingManager, ClientOpLink, OpSync, ServerOpLink, ServerSyncManager,
+    SyncForwardResp, TournamentManager,
+};
+use crate::model::{
+    accounts::SquireAccount,
+    formats::Constructed,
+    identifiers::{AdminId, PlayerId},
+    operations::{AdminOp, JudgeOp, TournOp},
+    players::PlayerStatus,
+    tournament::{Tournament, TournamentPreset, TournamentSeed},
+};
+
+/// The number of distinct guest names that the generator uses. This is kept small so that
+/// clients often register the same guest at the same time, which causes sync conflicts.
+const GUEST_NAMES: usize = 12;
+
+/// The number of sync chains that a client may start while settling before the harness gives up
+/// on it
+const SETTLE_LIMIT: usize = 16;
+
+/// A small, seedable random number generator (SplitMix64). The quality of the randomness does not
+/// matter here, but reproducibility does.
+#[derive(Debug, Clone)]
+pub struct SimRng(u64);
+
+impl SimRng {
+    /// Creates a generator from a seed
+    pub fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    /// Returns the next random number
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Returns a random number that is less than `n`, which must not be zero
+    pub fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+
+    /// Returns `true` with the given probability
+    pub fn chance(&mut self, p: f64) -> bool {
+        ((self.next_u64() >> 11) as f64 / (1u64 << 53) as f64) < p
+    }
+}
+
+/// Generates operations that are valid for the tournament that they are generated for, e.g. only
+/// registered players are dropped. All operations are submitted by the given admin.
+#[derive(Debug, Clone)]
+pub struct OpGenerator {
+    rng: SimRng,
+    admin: AdminId,
+    count: usize,
+}
+
+impl OpGenerator {
+    /// Creates a generator for the given admin
+    pub fn new(seed: u64, admin: AdminId) -> Self {
+        Self {
+            rng: SimRng::new(seed),
+            admin,
+            count: 0,
+        }
+    }
+
+    /// Generates an operation that can be applied to the given tournament
+    pub fn next_op(&mut self, tourn: &Tournament) -> TournOp {
+        self.count += 1;
+        let op = match self.rng.below(5) {
+            0 => self
+                .pick_player(tourn, PlayerStatus::Registered)
+                .map(AdminOp::AdminDropPlayer),
+            1 => self
+                .pick_player(tourn, PlayerStatus::Dropped)
+                .map(AdminOp::ReinstatePlayer),
+            2 => Some(AdminOp::UpdateReg(!tourn.reg_open)),
+            _ => None,
+        };
+        if let Some(op) = op {
+            return TournOp::AdminOp(self.admin, op);
+        }
+        let name = format!("Guest {}", self.rng.below(GUEST_NAMES));
+        if tourn.player_reg.name_and_id.contains_key(&name) {
+            let op = AdminOp::Announce(format!("Announcement {}", self.count));
+            return TournOp::AdminOp(self.admin, op);
+        }
+        TournOp::JudgeOp(self.admin.into(), JudgeOp::RegisterGuest(name))
+    }
+
+    /// Applies the given number of generated operations to the manager. The operations that were
+    /// applied are returned.
+    pub fn apply_ops(&mut self, tourn: &mut TournamentManager, count: usize) -> Vec<TournOp> {
+        (0..count)
+            .filter_map(|_| {
+                let op = self.next_op(tourn);
+                tourn.apply_op(op.clone()).is_ok().then_some(op)
+            })
+            .collect()
+    }
+
+    fn pick_player(&mut self, tourn: &Tournament, status: PlayerStatus) -> Option<PlayerId> {
+        // Players are sorted by name so that the choice only depends on the seed
+        let mut plyrs: Vec<_> = tourn
+            .player_reg
+            .players
+            .values()
+            .filter(|p| p.status == status)
+            .collect();
+        if plyrs.is_empty() {
+            return None;
+        }
+        plyrs.sort_by(|a, b| a.name.cmp(&b.name));
+        Some(plyrs[self.rng.below(plyrs.len())].id)
+    }
+}
+
+/// How unreliable the simulated network is. Each rate is the probability that it happens to a
+/// given message.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NetworkConditions {
+    /// How often a message is lost in transit
+    pub drop_rate: f64,
+    /// How often a message is delivered twice
+    pub duplicate_rate: f64,
+    /// How often a message is delivered ahead of the messages that were sent before it
+    pub reorder_rate: f64,
+}
+
+impl NetworkConditions {
+    /// A network that delivers every message once and in order
+    pub const RELIABLE: Self = Self {
+        drop_rate: 0.0,
+        duplicate_rate: 0.0,
+        reorder_rate: 0.0,
+    };
+
+    /// A network that often drops, duplicates, and reorders messages
+    pub const LOSSY: Self = Self {
+        drop_rate: 0.2,
+        duplicate_rate: 0.2,
+        reorder_rate: 0.3,
+    };
+}
+
+/// A message in transit
+#[derive(Debug, Clone)]
+enum Packet {
+    /// A link in a sync chain sent by a client
+    Link(usize, Uuid, ClientOpLink),
+    /// A response to a forwarded sync sent by a client
+    ForwardResp(usize, Uuid),
+    /// The backend's response to a link in a sync chain
+    Reply(usize, Uuid, ServerOpLink),
+    /// A sync forwarded by the backend
+    Forward(usize, Uuid, OpSync),
+}
+
+/// A simulated client
+#[derive(Debug)]
+struct SimClient {
+    tourn: TournamentManager,
+    /// The id and most recent link of the client's in-progress sync chain
+    chain: Option<(Uuid, ClientOpLink)>,
+    forwarded: ClientForwardingManager,
+}
+
+/// Runs a backend and several clients in memory and passes sync messages between them over a
+/// simulated network. Each step, a random client might apply a generated operation, clients
+/// start or resend syncs, and a message is delivered. The clients process messages the same way
+/// that the `SquireClient` does, and the backend processes them the same way that a gathering
+/// does.
+#[derive(Debug)]
+pub struct SyncHarness {
+    seed: u64,
+    rng: SimRng,
+    gen: OpGenerator,
+    conditions: NetworkConditions,
+    server: TournamentManager,
+    syncs: ServerSyncManager,
+    /// The forwarded syncs that are waiting on a response, keyed by their id and recipient
+    forwarding: HashMap<(Uuid, usize), OpSync>,
+    clients: Vec<SimClient>,
+    in_flight: Vec<Packet>,
+    /// Errors that should never happen, regardless of the network
+    errors: Vec<String>,
+}
+
+impl SyncHarness {
+    /// Creates a harness with the given number of clients. Every client starts with a synced copy
+    /// of the backend's tournament.
+    pub fn new(seed: u64, clients: usize, conditions: NetworkConditions) -> Self {
+        let owner = SquireAccount::new("Organizer".into(), "Organizer".into());
+        let admin: AdminId = owner.id.0.into();
+        let t_seed = TournamentSeed::new(
+            "Sync Harness".into(),
+            TournamentPreset::Swiss,
+            Constructed::Pioneer.into(),
+        )
+        .unwrap();
+        let mut server = TournamentManager::new(owner, t_seed);
+        // The first operation gives every client an operation to anchor their syncs to
+        let welcome = AdminOp::Announce("Welcome".into());
+        _ = server.apply_op(TournOp::AdminOp(admin, welcome)).unwrap();
+        server.mark_synced();
+        let clients = (0..clients)
+            .map(|_| SimClient {
+                tourn: server.clone(),
+                chain: None,
+                forwarded: ClientForwardingManager::new(),
+            })
+            .collect();
+        let mut rng = SimRng::new(seed);
+        let gen = OpGenerator::new(rng.next_u64(), admin);
+        Self {
+            seed,
+            rng,
+            gen,
+            conditions,
+            server,
+            syncs: ServerSyncManager::new(),
+            forwarding: HashMap::new(),
+            clients,
+            in_flight: Vec::new(),
+            errors: Vec::new(),
+        }
+    }
+
+    /// Returns the seed that the harness was created with
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Returns the backend's copy of the tournament
+    pub fn server(&self) -> &TournamentManager {
+        &self.server
+    }
+
+    /// Returns the client's copy of the tournament
+    pub fn client(&self, index: usize) -> &TournamentManager {
+        &self.clients[index].tourn
+    }
+
+    /// Returns a mutable reference to the client's copy of the tournament, e.g. to apply
+    /// operations that the generator can not create
+    pub fn client_mut(&mut self, index: usize) -> &mut TournamentManager {
+        &mut self.clients[index].tourn
+    }
+
+    /// Runs the given number of steps
+    pub fn run(&mut self, steps: usize) {
+        for _ in 0..steps {
+            self.step();
+        }
+    }
+
+    /// Runs a single step of the simulation
+    pub fn step(&mut self) {
+        if self.rng.chance(0.3) {
+            let index = self.rng.below(self.clients.len());
+            _ = self.gen.apply_ops(&mut self.clients[index].tourn, 1);
+        }
+        // Stands in for the retry timers of both ends
+        if self.rng.chance(0.1) {
+            self.resend();
+        }
+        for index in 0..self.clients.len() {
+            self.start_sync(index);
+        }
+        if !self.in_flight.is_empty() {
+            let index = if self.rng.chance(self.conditions.reorder_rate) {
+                self.rng.below(self.in_flight.len())
+            } else {
+                0
+            };
+            let packet = self.in_flight.remove(index);
+            self.deliver(packet);
+        }
+    }
+
+    /// Heals the network and brings every client up to date. This mirrors what happens when the
+    /// clients reconnect: the messages in transit are lost, each client fetches the operations
+    /// that it has missed, and then each client syncs its remaining operations over a reliable
+    /// network.
+    pub fn settle(&mut self) {
+        self.conditions = NetworkConditions::RELIABLE;
+        self.in_flight.clear();
+        self.forwarding.clear();
+        for index in 0..self.clients.len() {
+            self.reconnect(index);
+        }
+        for index in 0..self.clients.len() {
+            let mut attempts = 0;
+            while !self.clients[index].tourn.is_synced() {
+                if attempts == SETTLE_LIMIT {
+                    self.errors.push(format!(
+                        "Client {index} could not sync over a reliable network"
+                    ));
+                    break;
+                }
+                attempts += 1;
+                self.start_sync(index);
+                while !self.in_flight.is_empty() {
+                    let packet = self.in_flight.remove(0);
+                    self.deliver(packet);
+                }
+            }
+        }
+    }
+
+    /// Checks that every client has converged to the backend's copy of the tournament and that
+    /// nothing went wrong along the way. This should be called after `settle`.
+    pub fn check_convergence(&self) -> Result<(), String> {
+        if let Some(err) = self.errors.first() {
+            return Err(format!("Seed {}: {err}", self.seed));
+        }
+        for (index, client) in self.clients.iter().enumerate() {
+            if !client.tourn.is_synced() {
+                return Err(format!("Seed {}: client {index} is not synced", self.seed));
+            }
+            if client.tourn.op_log().last_id() != self.server.op_log().last_id() {
+                return Err(format!(
+                    "Seed {}: client {index} has a different log than the backend",
+                    self.seed
+                ));
+            }
+            if client.tourn.tourn() != self.server.tourn() {
+                return Err(format!(
+                    "Seed {}: client {index} has a different tournament than the backend",
+                    self.seed
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    fn send(&mut self, packet: Packet) {
+        if self.rng.chance(self.conditions.drop_rate) {
+            return;
+        }
+        if self.rng.chance(self.conditions.duplicate_rate) {
+            self.in_flight.push(packet.clone());
+        }
+        self.in_flight.push(packet);
+    }
+
+    /// Starts a sync chain if the client has operations to sync and no chain in progress
+    fn start_sync(&mut self, index: usize) {
+        let client = &mut self.clients[index];
+        if client.chain.is_some() || client.tourn.is_synced() {
+            return;
+        }
+        let id = Uuid::new_v4();
+        let link: ClientOpLink = client.tourn.sync_request().into();
+        client.chain = Some((id, link.clone()));
+        self.send(Packet::Link(index, id, link));
+    }
+
+    /// Resends every in-progress sync chain and every forwarded sync that has not been answered
+    fn resend(&mut self) {
+        let links: Vec<_> = self
+            .clients
+            .iter()
+            .enumerate()
+            .filter_map(|(index, client)| {
+                let (id, link) = client.chain.clone()?;
+                Some(Packet::Link(index, id, link))
+            })
+            .collect();
+        let forwards: Vec<_> = self
+            .forwarding
+            .iter()
+            .map(|((id, index), sync)| Packet::Forward(*index, *id, sync.clone()))
+            .collect();
+        for packet in links.into_iter().chain(forwards) {
+            self.send(packet);
+        }
+    }
+
+    /// Drops the client's sync chain and fetches the operations that it has missed, falling back
+    /// to a full copy
+    fn reconnect(&mut self, index: usize) {
+        let server = &self.server;
+        self.clients[index].chain = None;
+        let client = &mut self.clients[index].tourn;
+        let delta = client.last_sync().and_then(|id| {
+            let ops = server.op_log().get_slice(id)?;
+            let (seed, owner) = server.seed_and_creator();
+            client.apply_delta(OpSync { owner, seed, ops })
+        });
+        if delta.is_none() {
+            _ = client.catch_up(server.clone());
+        }
+    }
+
+    fn deliver(&mut self, packet: Packet) {
+        match packet {
+            Packet::Link(index, id, link) => {
+                let resp = match self.syncs.validate_sync_message(&id, &link) {
+                    Ok(()) => {
+                        let resp = self.server.handle_sync_link(link.clone());
+                        self.syncs.add_sync_link(id, link, resp.clone());
+                        if let ServerOpLink::Completed(comp) = &resp {
+                            self.forward(index, self.server.init_sync_forwarding(comp.clone()));
+                        }
+                        resp
+                    }
+                    Err(resp) => resp,
+                };
+                self.send(Packet::Reply(index, id, resp));
+            }
+            Packet::ForwardResp(index, id) => {
+                _ = self.forwarding.remove(&(id, index));
+            }
+            Packet::Reply(index, id, resp) => self.handle_reply(index, id, resp),
+            Packet::Forward(index, id, sync) => {
+                let client = &mut self.clients[index];
+                if !client.forwarded.contains_resp(&id) {
+                    let resp = client.tourn.handle_forwarded_sync(sync);
+                    if let SyncForwardResp::Error(err) = &resp {
+                        self.errors
+                            .push(format!("Client {index} could not apply a forward: {err:?}"));
+                    }
+                    client.forwarded.add_resp(id, resp);
+                }
+                self.send(Packet::ForwardResp(index, id));
+            }
+        }
+    }
+
+    /// Forwards a completed sync to every client other than the one that sent it
+    fn forward(&mut self, sender: usize, sync: OpSync) {
+        let id = Uuid::new_v4();
+        for index in (0..self.clients.len()).filter(|i| *i != sender) {
+            _ = self.forwarding.insert((id, index), sync.clone());
+            self.send(Packet::Forward(index, id, sync.clone()));
+        }
+    }
+
+    fn handle_reply(&mut self, index: usize, id: Uuid, resp: ServerOpLink) {
+        let client = &mut self.clients[index];
+        // Replies to chains that are no longer in progress are ignored
+        if client.chain.as_ref().map(|(c_id, _)| *c_id) != Some(id) {
+            return;
+        }
+        match resp {
+            ServerOpLink::Conflict(proc) => {
+                let dec: ClientOpLink = proc.purge().into();
+                client.chain = Some((id, dec.clone()));
+                self.send(Packet::Link(index, id, dec));
+            }
+            ServerOpLink::Completed(comp) => {
+                client.chain = None;
+                if let Err(err) = client.tourn.handle_completion(comp) {
+                    self.errors
+                        .push(format!("Client {index} could not complete a sync: {err:?}"));
+                }
+            }
+            ServerOpLink::CatchUp(server) => {
+                client.chain = None;
+                _ = client.tourn.catch_up(*server);
+            }
+            ServerOpLink::Error(_) | ServerOpLink::TerminatedSeen { .. } => {
+                client.chain = None;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{NetworkConditions, OpGenerator, SyncHarness};
+
+    #[test]
+    fn lossy_syncs_converge() {
+        for seed in 0..50 {
+            let mut harness = SyncHarness::new(seed, 4, NetworkConditions::LOSSY);
+            harness.run(300);
+            harness.settle();
+            if let Err(err) = harness.check_convergence() {
+                panic!("{err}");
+            }
+        }
+    }
+
+    #[test]
+    fn reliable_syncs_converge() {
+        for seed in 0..10 {
+            let mut harness = SyncHarness::new(seed, 3, NetworkConditions::RELIABLE);
+            harness.run(200);
+            harness.settle();
+            if let Err(err) = harness.check_convergence() {
+                panic!("{err}");
+            }
+        }
+    }
+
+    #[test]
+    fn generated_ops_apply() {
+        let mut harness = SyncHarness::new(0, 1, NetworkConditions::RELIABLE);
+        let admin = harness
+            .server()
+            .tourn()
+            .admins
+            .keys()
+            .next()
+            .copied()
+            .unwrap();
+        let mut gen = OpGenerator::new(0, admin);
+        let ops = gen.apply_ops(harness.client_mut(0), 100);
+        assert_eq!(ops.len(), 100);
+    }
+}
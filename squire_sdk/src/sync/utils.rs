@@ -8,8 +8,8 @@ use squire_lib::{
 
 use super::{
     processor::{SyncCompletion, SyncDecision, SyncProcessor},
-    ClientBound, ClientOpLink, Disagreement, ForwardError, RequestError, ServerBound, ServerOpLink,
-    SyncError, SyncForwardResp, TournamentManager,
+    ClientBound, ClientOpLink, Disagreement, ForwardError, Rejection, RequestError, Rollback,
+    RollbackError, ServerBound, ServerOpLink, SyncError, SyncForwardResp, TournamentManager,
 };
 use crate::sync::{FullOp, OpSlice, OpSync};
 
@@ -45,6 +45,12 @@ impl From<SyncForwardResp> for ServerBound {
     }
 }
 
+impl From<Rollback> for ServerBound {
+    fn from(value: Rollback) -> Self {
+        Self::Rollback(value)
+    }
+}
+
 /* ---- ClientBound Helper Traits ---- */
 
 impl From<ServerOpLink> for ClientBound {
@@ -61,7 +67,14 @@ impl From<(TournamentId, OpSync)> for ClientBound {
 
 impl From<TournamentManager> for ClientBound {
     fn from(value: TournamentManager) -> Self {
-        Self::FetchResp(Box::new(value))
+        let digest = value.digest();
+        Self::FetchResp(Box::new(value), digest)
+    }
+}
+
+impl From<Rejection> for ClientBound {
+    fn from(value: Rejection) -> Self {
+        Self::Rejected(value)
     }
 }
 
@@ -71,6 +84,12 @@ impl From<SyncError> for ClientBound {
     }
 }
 
+impl From<Result<(), RollbackError>> for ClientBound {
+    fn from(value: Result<(), RollbackError>) -> Self {
+        Self::RollbackResp(value)
+    }
+}
+
 /* ---- SyncError Helper Traits ---- */
 impl From<RequestError> for SyncError {
     fn from(value: RequestError) -> Self {
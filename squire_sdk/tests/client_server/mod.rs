@@ -1,84 +1,421 @@
-/*
+//! An in-memory server and helpers for driving real clients against it. The server speaks the same
+//! HTTP and websocket protocol as a deployed server, but its tournaments and sessions only live as
+//! long as the test binary.
+//!
+//! Debug builds of the client talk to `localhost:8000`, so every test in the binary shares one
+//! server. The server runs on its own thread and runtime so that it outlives the runtimes of the
+//! individual tests.
+
 use std::{
-    net::SocketAddr,
-    sync::atomic::{AtomicBool, Ordering},
+    ops::Range,
+    sync::{mpsc, Arc, OnceLock},
+    thread,
     time::Duration,
 };
 
-use once_cell::sync::OnceCell;
-use squire_sdk::{client::SquireClient, server::User};
-use tokio::time::interval;
+use async_trait::async_trait;
+use axum::{
+    extract::{ws::WebSocket, State},
+    http::StatusCode,
+    Json,
+};
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use squire_sdk::{
+    actor::*,
+    api::*,
+    client::SquireClient,
+    compat::Storage,
+    model::{
+        accounts::SquireAccount,
+        identifiers::{SeriesId, SquireAccountId, TournamentId},
+        operations::TournOp,
+        series::Series,
+        tournament::Tournament,
+    },
+    server::{
+        self,
+        gathering::{GatheringHall, GatheringHallMessage, PersistMessage},
+        keys::{KeyPurpose, SigningKeys},
+        session::{AnyUser, SessionWatcher, SquireSession},
+        state::{ApplyOpError, ServerState},
+    },
+    sync::{Compression, TournamentManager},
+};
+use tokio::{
+    net::TcpListener,
+    sync::watch,
+    time::{sleep, timeout, Instant},
+};
+use uuid::Uuid;
 
-use crate::server::AppState;
+use crate::utils::spoof_account;
 
 pub mod startup;
+pub mod sync;
 pub mod tournaments;
 
-static STARTING_UP: AtomicBool = AtomicBool::new(false);
-static SERVER_STARTED: AtomicBool = AtomicBool::new(false);
-static CLIENT: OnceCell<SquireClient> = OnceCell::new();
+const SERVER_ADDRESS: &str = "127.0.0.1:8000";
 
-pub async fn init() {
-    let account = SquireAccount::new("Test User".to_owned(), "Test User".to_owned());
-    let user = User {
-        account: account.clone(),
-    };
-    if let Ok(false) =
-        SERVER_STARTED.compare_exchange(false, true, Ordering::Relaxed, Ordering::Relaxed)
-    {
-        let addr = SocketAddr::from(([127, 0, 0, 1], 8000));
-        tokio::spawn(async move {
-            let state = AppState::new();
-            state.users.insert(user.account.id, user);
-            let app = squire_sdk::server::create_router().into().with_state(state);
-            if axum::Server::bind(&addr)
-                .serve(app.into_make_service())
-                .await
-                .is_err()
-            {
-                SERVER_STARTED.store(false, Ordering::Relaxed);
-                println!("Could not start server");
-            }
-        });
+/// How long a test waits for the clients and the server to agree on a tournament
+pub const CONVERGENCE_TIMEOUT: Duration = Duration::from_secs(10);
+
+static SERVER: OnceLock<()> = OnceLock::new();
+
+/// The server state used by the harness. Tournaments are held by the persister, which the
+/// gathering hall reads from and writes to just like it does with a database.
+#[derive(Debug, Clone)]
+pub struct MemoryState {
+    tourns: Arc<DashMap<TournamentId, TournamentManager>>,
+    accounts: Arc<DashMap<String, SquireAccount>>,
+    sessions: Arc<DashMap<SessionToken, watch::Sender<SquireSession>>>,
+    series: Arc<DashMap<SeriesId, Series>>,
+    events: Arc<DashMap<SquireAccountId, Vec<LifecycleEvent>>>,
+    webhooks: Arc<DashMap<TournamentId, Vec<Webhook>>>,
+    gatherings: ActorClient<GatheringHall<MemoryPersister>>,
+    keys: Arc<SigningKeys>,
+}
+
+/// Stores the tournaments that the gathering hall persists
+#[derive(Debug)]
+pub struct MemoryPersister {
+    tourns: Arc<DashMap<TournamentId, TournamentManager>>,
+    events: Arc<DashMap<SquireAccountId, Vec<LifecycleEvent>>>,
+}
+
+fn record_events(
+    store: &DashMap<SquireAccountId, Vec<LifecycleEvent>>,
+    events: Vec<LifecycleEvent>,
+) {
+    for event in events {
+        store.entry(event.org).or_default().push(event);
     }
-    tokio::time::sleep(Duration::from_millis(1)).await;
-    match SquireClient::builder()
-        .url("http://localhost:8000".to_owned())
-        .account(account)
-        .build()
-        .await
-    {
-        Ok(client) => {
-            CLIENT.set(client).unwrap();
-        }
-        Err(err) => {
-            println!("Could not connect client: {err:?}");
+}
+
+#[async_trait]
+impl ActorState for MemoryPersister {
+    type Message = PersistMessage;
+
+    async fn process(&mut self, _scheduler: &mut Scheduler<Self>, msg: Self::Message) {
+        match msg {
+            PersistMessage::Get(id, send) => {
+                let _ = send.send(self.tourns.get(&id).map(|t| Box::new(t.clone())));
+            }
+            PersistMessage::Persist(tourn) => {
+                let _ = self.tourns.insert(tourn.id, *tourn);
+            }
+            PersistMessage::RecordEvents(events) => record_events(&self.events, events),
+            PersistMessage::AppendOps(id, _, ops) => {
+                if let Some(mut tourn) = self.tourns.get_mut(&id) {
+                    let _ = tourn.replay_ops(ops);
+                }
+            }
+            PersistMessage::Flush(send) => {
+                let _ = send.send(());
+            }
         }
     }
-    STARTING_UP.store(false, Ordering::Relaxed);
 }
 
-pub async fn get_client() -> SquireClient {
-    let mut counter = 0;
-    let mut timer = interval(Duration::from_millis(50));
-    loop {
-        if let Some(false) = STARTING_UP
-            .compare_exchange(false, true, Ordering::Relaxed, Ordering::Relaxed)
-            .ok()
-            .map(|b| b || CLIENT.get().is_some())
-        {
-            init().await;
+impl MemoryState {
+    fn new() -> Self {
+        let tourns = Arc::new(DashMap::new());
+        let events = Arc::new(DashMap::new());
+        let persister = ActorClient::builder(MemoryPersister {
+            tourns: tourns.clone(),
+            events: events.clone(),
+        })
+        .launch();
+        let gatherings = ActorBuilder::new(GatheringHall::new(persister)).launch();
+        Self {
+            tourns,
+            accounts: Arc::new(DashMap::new()),
+            sessions: Arc::new(DashMap::new()),
+            series: Arc::new(DashMap::new()),
+            events,
+            webhooks: Arc::new(DashMap::new()),
+            gatherings,
+            keys: Arc::new(SigningKeys::derive(b"squire-sync-harness")),
         }
-        match CLIENT.get() {
-            Some(c) => return c.clone(),
-            None => {
-                counter += 1;
-            }
+    }
+
+    fn new_session(&self, session: SquireSession) -> SessionToken {
+        let mut token = [0; 32];
+        token[..16].copy_from_slice(Uuid::new_v4().as_bytes());
+        token[16..].copy_from_slice(Uuid::new_v4().as_bytes());
+        let token = SessionToken(token);
+        let session = match session {
+            SquireSession::Guest(_) => SquireSession::Guest(token.clone()),
+            session => session,
+        };
+        let _ = self
+            .sessions
+            .insert(token.clone(), watch::channel(session).0);
+        token
+    }
+}
+
+/// Logs a user in. Accounts are created the first time that their username is seen, and passwords
+/// are not checked. OAuth logins are not supported.
+async fn login(
+    State(state): State<MemoryState>,
+    Json(Login(cred)): Json<Login>,
+) -> Result<(SessionToken, Json<SquireAccount>), StatusCode> {
+    let Credentials::Basic { username, .. } = cred else {
+        return Err(StatusCode::UNAUTHORIZED);
+    };
+    let acc = state
+        .accounts
+        .entry(username.clone())
+        .or_insert_with(|| {
+            let mut acc = spoof_account();
+            acc.user_name = username.clone();
+            acc.display_name = username;
+            acc
+        })
+        .clone();
+    let token = state.create_session(acc.id).await;
+    Ok((token, Json(acc)))
+}
+
+async fn guest(State(state): State<MemoryState>) -> SessionToken {
+    state.guest_session().await
+}
+
+#[async_trait]
+impl ServerState for MemoryState {
+    fn get_version(&self) -> Version {
+        Version {
+            version: "0.1.0-test".into(),
+            mode: ServerMode::Basic,
         }
-        timer.tick().await;
-        if counter == 20 {
-            panic!("Unable to start server *and* establish client");
+    }
+
+    fn signing_key(&self, purpose: KeyPurpose) -> &[u8] {
+        self.keys.get(purpose)
+    }
+
+    async fn get_tourn_summaries(
+        &self,
+        filter: &TournamentFilter,
+        including: Range<usize>,
+    ) -> (Vec<TournamentSummary>, usize) {
+        let summaries: Vec<_> = self
+            .tourns
+            .iter()
+            .filter(|t| filter.matches(t))
+            .map(|t| TournamentSummary::from(&*t))
+            .collect();
+        let total = summaries.len();
+        let page = summaries
+            .into_iter()
+            .skip(including.start)
+            .take(including.len())
+            .collect();
+        (page, total)
+    }
+
+    async fn get_tourn(&self, id: TournamentId) -> Option<TournamentManager> {
+        self.tourns.get(&id).map(|t| t.clone())
+    }
+
+    async fn persist_tourn(&self, tourn: &TournamentManager) -> bool {
+        let _ = self.tourns.insert(tourn.id, tourn.clone());
+        true
+    }
+
+    async fn handle_new_onlooker(
+        &self,
+        id: TournamentId,
+        user: SessionWatcher,
+        compression: Compression,
+        ws: WebSocket,
+    ) {
+        self.gatherings.send(GatheringHallMessage::NewConnection(
+            id,
+            user,
+            compression,
+            ws,
+        ))
+    }
+
+    async fn apply_op(&self, id: TournamentId, op: TournOp) -> Result<(), ApplyOpError> {
+        match self.gatherings.track((id, op)).await {
+            Some(digest) => digest.map_err(ApplyOpError::Rejected),
+            None => Err(ApplyOpError::NotFound),
         }
     }
+
+    async fn record_events(&self, events: Vec<LifecycleEvent>) {
+        record_events(&self.events, events)
+    }
+
+    async fn get_org_events(&self, org: SquireAccountId) -> Vec<LifecycleEvent> {
+        self.events.get(&org).map(|e| e.clone()).unwrap_or_default()
+    }
+
+    async fn list_series(&self) -> Vec<Series> {
+        self.series.iter().map(|s| s.clone()).collect()
+    }
+
+    async fn get_series(&self, id: SeriesId) -> Option<Series> {
+        self.series.get(&id).map(|s| s.clone())
+    }
+
+    async fn persist_series(&self, series: &Series) -> bool {
+        let _ = self.series.insert(series.id, series.clone());
+        true
+    }
+
+    async fn list_webhooks(&self, tourn: TournamentId) -> Vec<Webhook> {
+        self.webhooks
+            .get(&tourn)
+            .map(|hooks| hooks.clone())
+            .unwrap_or_default()
+    }
+
+    async fn persist_webhook(&self, hook: &Webhook) -> bool {
+        self.webhooks
+            .entry(hook.tourn)
+            .or_default()
+            .push(hook.clone());
+        true
+    }
+
+    async fn delete_webhook(&self, tourn: TournamentId, id: WebhookId) -> bool {
+        let Some(mut hooks) = self.webhooks.get_mut(&tourn) else {
+            return false;
+        };
+        let len = hooks.len();
+        hooks.retain(|hook| hook.id != id);
+        hooks.len() != len
+    }
+
+    async fn create_session(&self, id: SquireAccountId) -> SessionToken {
+        self.new_session(SquireSession::Active(id))
+    }
+
+    async fn guest_session(&self) -> SessionToken {
+        self.new_session(SquireSession::Guest(SessionToken([0; 32])))
+    }
+
+    async fn handoff_session(&self) -> (SessionToken, DateTime<Utc>) {
+        // Sessions in the test server never expire
+        let expires = Utc::now() + chrono::Duration::from_std(HANDOFF_LIFETIME).unwrap();
+        (self.guest_session().await, expires)
+    }
+
+    async fn get_session(&self, token: SessionToken) -> SquireSession {
+        self.sessions
+            .get(&token)
+            .map(|s| s.borrow().clone())
+            .unwrap_or(SquireSession::UnknownUser)
+    }
+
+    async fn reauth_session(&self, user: AnyUser) -> Option<SessionToken> {
+        let session = self.get_session(user.into_token()).await;
+        Some(self.new_session(session))
+    }
+
+    async fn terminate_session(&self, user: AnyUser) -> bool {
+        self.sessions.remove(&user.into_token()).is_some()
+    }
+
+    async fn watch_session(&self, user: AnyUser) -> Option<SessionWatcher> {
+        self.sessions
+            .get(&user.into_token())
+            .map(|s| SessionWatcher::new(s.subscribe()))
+    }
+}
+
+/// Starts the shared server if it is not already running and waits until it accepts connections
+pub fn start_server() {
+    SERVER.get_or_init(|| {
+        let (send, recv) = mpsc::channel();
+        let _ = thread::spawn(move || {
+            let rt = tokio::runtime::Runtime::new().unwrap();
+            rt.block_on(async move {
+                let router = server::create_router::<MemoryState>()
+                    .add_route::<0, POST, Login, _, _>(login)
+                    .add_route::<0, POST, GuestSession, _, _>(guest)
+                    .into_router()
+                    .with_state(MemoryState::new());
+                let listener = TcpListener::bind(SERVER_ADDRESS).await.unwrap();
+                let _ = send.send(());
+                axum::serve(listener, router).await.unwrap();
+            })
+        });
+        recv.recv().unwrap();
+    });
+}
+
+/// Creates a client with a guest session
+pub async fn guest_client() -> SquireClient {
+    start_server();
+    SquireClient::builder()
+        .url(format!("http://{SERVER_ADDRESS}"))
+        .guest_build()
+        .await
+        .unwrap()
+}
+
+/// Creates a client and logs it in as the user with the given name
+pub async fn user_client(name: &str) -> SquireClient {
+    log_in(guest_client().await, name).await
+}
+
+/// Creates a client that keeps its unsynced tournaments in the given storage and logs it in as
+/// the user with the given name
+pub async fn user_client_with_storage(name: &str, storage: Storage) -> SquireClient {
+    start_server();
+    let client = SquireClient::builder()
+        .url(format!("http://{SERVER_ADDRESS}"))
+        .storage(storage)
+        .guest_build()
+        .await
+        .unwrap();
+    log_in(client, name).await
+}
+
+async fn log_in(client: SquireClient, name: &str) -> SquireClient {
+    let cred = Credentials::Basic {
+        username: name.to_owned(),
+        password: String::new(),
+    };
+    client.login(cred).await.unwrap();
+    // The client swaps to the new session after the login response is handed back
+    let deadline = Instant::now() + CONVERGENCE_TIMEOUT;
+    while client.get_user().is_none() {
+        assert!(Instant::now() < deadline, "{name} was never logged in");
+        sleep(Duration::from_millis(10)).await;
+    }
+    client
+}
+
+/// Fetches the client's current copy of a tournament
+pub async fn client_tourn(client: &SquireClient, id: TournamentId) -> Option<Tournament> {
+    client.query_tourn(id, |t| t.tourn().clone()).await
+}
+
+/// Waits until every client has the same copy of the tournament as the server. The server's copy
+/// is fetched over a fresh subscription so that it reflects the live gathering rather than the
+/// last persisted state.
+pub async fn assert_convergence(clients: &[SquireClient], id: TournamentId) {
+    let res = timeout(CONVERGENCE_TIMEOUT, async {
+        loop {
+            let observer = guest_client().await;
+            let _sub = observer.sub_to_tournament(id).await.unwrap();
+            let server = client_tourn(&observer, id).await.unwrap();
+            let mut converged = true;
+            for client in clients {
+                converged &= client_tourn(client, id).await.as_ref() == Some(&server);
+            }
+            if converged {
+                return;
+            }
+            sleep(Duration::from_millis(50)).await;
+        }
+    })
+    .await;
+    assert!(res.is_ok(), "Clients did not converge on tournament {id}");
 }
-*/
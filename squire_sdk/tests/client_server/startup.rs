@@ -1,34 +1,12 @@
-use super::get_client;
+use super::{guest_client, user_client};
 
 #[tokio::test]
 async fn startup() {
-    let _ = get_client().await;
+    let _ = guest_client().await;
 }
 
-/*
 #[tokio::test]
-async fn lots_of_requests() {
-    send_lots_of_requests(0).await
+async fn login() {
+    let client = user_client("Startup User").await;
+    assert_eq!(client.get_user().unwrap().user_name, "Startup User");
 }
-
-async fn send_lots_of_requests(i: usize) {
-    let mut client = get_client().await;
-    println!("Getting client #{i}");
-    for i in 0..1000 {
-        println!("Sending request #{i}");
-        let resp = client.verify().await;
-        println!("Resp: {resp:?}");
-        resp.unwrap();
-    }
-}
-
-#[tokio::test]
-async fn lots_of_concurrent_requests() {
-    let handles: Vec<_> = (0..10)
-        .map(|i| tokio::spawn(async move { send_lots_of_requests(i).await }))
-        .collect();
-    for h in handles {
-        h.await.unwrap();
-    }
-}
-*/
@@ -0,0 +1,161 @@
+use futures::future::join_all;
+use squire_sdk::{
+    client::{BackendImportStatus, SquireClient},
+    compat::Storage,
+    model::{
+        identifiers::{AdminId, TournamentId},
+        operations::{AdminOp, JudgeOp, TournOp},
+    },
+    sync::{RollbackError, TournamentManager},
+};
+use uuid::Uuid;
+
+use super::{assert_convergence, client_tourn, user_client, user_client_with_storage};
+use crate::utils::{get_seed, spoof_account};
+
+/// Creates a tournament on the server and subscribes the organizer and the given number of players
+/// to it
+async fn set_up(name: &str, players: usize) -> (TournamentId, SquireClient, Vec<SquireClient>) {
+    let organizer = user_client(&format!("{name} Organizer")).await;
+    let id = organizer.create_tournament(get_seed()).await.unwrap();
+    assert!(matches!(
+        organizer.persist_tourn_to_backend(id).await,
+        BackendImportStatus::Success
+    ));
+    let _ = organizer.sub_to_tournament(id).await.unwrap();
+    let players =
+        join_all((0..players).map(|i| user_client_sub(format!("{name} Player {i}"), id))).await;
+    (id, organizer, players)
+}
+
+async fn user_client_sub(name: String, id: TournamentId) -> SquireClient {
+    let client = user_client(&name).await;
+    let _ = client.sub_to_tournament(id).await.unwrap();
+    client
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn sequential_syncs_converge() {
+    let (id, organizer, players) = set_up("Sequential", 3).await;
+    for player in &players {
+        let acc = player.get_user().unwrap();
+        player
+            .update_tourn(id, TournOp::RegisterPlayer(acc, None))
+            .await
+            .unwrap()
+            .unwrap();
+    }
+    let mut clients = players;
+    clients.push(organizer);
+    assert_convergence(&clients, id).await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn concurrent_syncs_converge() {
+    let (id, organizer, players) = set_up("Concurrent", 4).await;
+    let admin_id: AdminId = organizer.get_user().unwrap().id.0.into();
+
+    // Every player registers themselves while the organizer registers guests
+    let registrations = players.iter().map(|player| {
+        let acc = player.get_user().unwrap();
+        player.update_tourn(id, TournOp::RegisterPlayer(acc, None))
+    });
+    let guests = (0..4).map(|i| {
+        let op = JudgeOp::RegisterGuest(format!("Concurrent Guest {i}"));
+        organizer.update_tourn(id, TournOp::JudgeOp(admin_id.into(), op))
+    });
+    for res in join_all(registrations.chain(guests)).await {
+        assert!(res.unwrap().is_ok());
+    }
+
+    let mut clients = players;
+    clients.push(organizer);
+    assert_convergence(&clients, id).await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn unsynced_ops_survive_restart() {
+    let dir = std::env::temp_dir().join(format!("squire-storage-{}", Uuid::new_v4()));
+    let name = "Restarting Organizer";
+
+    // The tournament is only known locally, so its operations can not be synced
+    let client = user_client_with_storage(name, Storage::new(&dir)).await;
+    let id = client.create_tournament(get_seed()).await.unwrap();
+    let acc = client.get_user().unwrap();
+    client
+        .update_tourn(id, TournOp::RegisterPlayer(acc, None))
+        .await
+        .unwrap()
+        .unwrap();
+    let tourn = client_tourn(&client, id).await.unwrap();
+
+    // A restarted client picks up where the last one left off
+    let restarted = user_client_with_storage(name, Storage::new(&dir)).await;
+    assert_eq!(client_tourn(&restarted, id).await, Some(tourn));
+
+    // Once the backend has everything, the stored copy is removed
+    assert!(matches!(
+        restarted.persist_tourn_to_backend(id).await,
+        BackendImportStatus::Success
+    ));
+    let _ = restarted.sub_to_tournament(id).await.unwrap();
+    let fresh = user_client_with_storage(name, Storage::new(&dir)).await;
+    assert!(client_tourn(&fresh, id).await.is_none());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn rollback_to_checkpoint() {
+    let (id, organizer, players) = set_up("Rollback", 2).await;
+    let admin_id: AdminId = organizer.get_user().unwrap().id.0.into();
+    let checkpoint = TournOp::AdminOp(admin_id, AdminOp::Checkpoint("Opening".into()));
+    organizer
+        .update_tourn(id, checkpoint)
+        .await
+        .unwrap()
+        .unwrap();
+    for player in &players {
+        let acc = player.get_user().unwrap();
+        player
+            .update_tourn(id, TournOp::RegisterPlayer(acc, None))
+            .await
+            .unwrap()
+            .unwrap();
+    }
+    let mut clients = players;
+    clients.push(organizer);
+    assert_convergence(&clients, id).await;
+    let organizer = clients.last().unwrap();
+
+    // Only admins can roll back the tournament, and only to known checkpoints
+    assert_eq!(
+        clients[0].rollback(id, "Opening".into()).await,
+        Some(Err(RollbackError::Unauthorized))
+    );
+    assert_eq!(
+        organizer.rollback(id, "Closing".into()).await,
+        Some(Err(RollbackError::UnknownCheckpoint))
+    );
+
+    // The registrations are removed for everyone
+    assert_eq!(organizer.rollback(id, "Opening".into()).await, Some(Ok(())));
+    assert_convergence(&clients, id).await;
+    let tourn = client_tourn(organizer, id).await.unwrap();
+    assert!(tourn.player_reg.players.is_empty());
+}
+
+#[test]
+fn replayed_ops_catch_up_a_persisted_copy() {
+    let persisted = TournamentManager::new(spoof_account(), get_seed());
+    let mut live = persisted.clone();
+    for _ in 0..3 {
+        live.apply_op(TournOp::RegisterPlayer(spoof_account(), None))
+            .unwrap();
+    }
+    let ops = live.ops_since(None).unwrap();
+    assert_eq!(ops.len(), 3);
+    let mut restored = persisted.clone();
+    assert_eq!(restored.replay_ops(ops[..2].to_vec()), 2);
+    // Operations that are already in the log are skipped
+    assert_eq!(restored.replay_ops(ops), 1);
+    assert_eq!(restored.tourn(), live.tourn());
+}
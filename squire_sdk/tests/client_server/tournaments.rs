@@ -1,46 +1,325 @@
-use squire_sdk::{client::error::ClientError, tournaments::TournamentPreset};
+use std::time::Duration;
 
-use crate::client_server::get_client;
+use squire_sdk::{
+    api::WebhookError,
+    client::BackendImportStatus,
+    model::{
+        accounts::{SharingPermissions, SquireAccount},
+        identifiers::{AdminId, PlayerId, TournamentId},
+        operations::{AdminOp, JudgeOp, TournOp},
+        tournament::{SeedOverrides, TournamentStatus},
+    },
+};
+use tokio::time::timeout;
+use uuid::Uuid;
+
+use super::{client_tourn, guest_client, user_client};
+use crate::utils::get_seed;
 
 #[tokio::test]
 async fn create_tournament_requires_login() {
-    let mut client = get_client().await;
-    let resp = client
-        .create_tournament(
-            "Test".to_owned(),
-            TournamentPreset::Swiss,
-            "Test".to_owned(),
-        )
-        .await;
-
-    match resp {
-        Err(ClientError::NotLoggedIn) => {}
-        Err(err) => {
-            panic!("Tournament creation failed in an unexpected way: {err:?}");
-        }
-        Ok(_) => {
-            panic!("Tournament creation successed when it should have failed!");
+    let client = guest_client().await;
+    assert!(client.create_tournament(get_seed()).await.is_none());
+}
+
+#[tokio::test]
+async fn overviews_cover_every_local_tournament() {
+    let organizer = user_client("Dashboard Organizer").await;
+    let first = organizer.create_tournament(get_seed()).await.unwrap();
+    let second = organizer.create_tournament(get_seed()).await.unwrap();
+    let overviews = organizer.get_tourn_overviews().await;
+    assert_eq!(overviews.len(), 2);
+    assert!(overviews.iter().any(|o| o.id == first));
+    assert!(overviews.iter().any(|o| o.id == second));
+    assert!(overviews
+        .iter()
+        .all(|o| o.status == TournamentStatus::Planned && o.current_round == 0));
+}
+
+#[tokio::test]
+async fn subscribe_to_persisted_tournament() {
+    let organizer = user_client("Subscription Organizer").await;
+    let id = organizer.create_tournament(get_seed()).await.unwrap();
+    assert!(matches!(
+        organizer.persist_tourn_to_backend(id).await,
+        BackendImportStatus::Success
+    ));
+
+    let onlooker = guest_client().await;
+    let _sub = onlooker.sub_to_tournament(id).await.unwrap();
+    assert_eq!(
+        client_tourn(&onlooker, id).await,
+        client_tourn(&organizer, id).await
+    );
+}
+
+#[tokio::test]
+async fn presence_tracks_onlookers() {
+    let organizer = user_client("Presence Organizer").await;
+    let id = organizer.create_tournament(get_seed()).await.unwrap();
+    assert!(matches!(
+        organizer.persist_tourn_to_backend(id).await,
+        BackendImportStatus::Success
+    ));
+    let _sub = organizer.sub_to_tournament(id).await.unwrap();
+    let mut presence = organizer.presence(id).await.unwrap();
+
+    let onlooker = guest_client().await;
+    let _onlooker_sub = onlooker.sub_to_tournament(id).await.unwrap();
+
+    // The presence update is broadcast shortly after the guest connects
+    let wait = async {
+        loop {
+            {
+                let current = presence.borrow_and_update();
+                if current.admins() == 1 && current.guests == 1 {
+                    break;
+                }
+            }
+            presence.changed().await.unwrap();
         }
+    };
+    timeout(Duration::from_secs(10), wait).await.unwrap();
+}
+
+#[tokio::test]
+async fn clock_syncs_on_connection() {
+    let organizer = user_client("Clock Organizer").await;
+    let id = organizer.create_tournament(get_seed()).await.unwrap();
+    assert!(matches!(
+        organizer.persist_tourn_to_backend(id).await,
+        BackendImportStatus::Success
+    ));
+    let _sub = organizer.sub_to_tournament(id).await.unwrap();
+    let mut clock = organizer.clock(id).await.unwrap();
+
+    // The backend replies to the clock request that is sent when the client connects
+    timeout(Duration::from_secs(10), clock.changed())
+        .await
+        .unwrap()
+        .unwrap();
+    let clock = clock.borrow();
+    assert!(clock.offset().num_seconds().abs() < 5);
+    assert!(clock.time_left(&Default::default()).is_none());
+}
+
+#[tokio::test]
+async fn public_endpoints_show_event_state() {
+    let organizer = user_client("Public Organizer").await;
+    let admin_id: AdminId = organizer.get_user().unwrap().id.0.into();
+    let id = organizer.create_tournament(get_seed()).await.unwrap();
+    for name in ["Public Guest B", "Public Guest A"] {
+        let op = JudgeOp::RegisterGuest(name.into());
+        organizer
+            .update_tourn(id, TournOp::JudgeOp(admin_id.into(), op))
+            .await
+            .unwrap()
+            .unwrap();
     }
+    organizer
+        .update_tourn(id, TournOp::AdminOp(admin_id, AdminOp::CreateDraftPods(4)))
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(matches!(
+        organizer.persist_tourn_to_backend(id).await,
+        BackendImportStatus::Success
+    ));
+
+    // Anyone can read the event's state without subscribing to it
+    let onlooker = guest_client().await;
+    let players = onlooker.get_players(id).await.unwrap();
+    let names: Vec<_> = players.iter().map(|p| p.name.as_str()).collect();
+    assert_eq!(names, ["Public Guest A", "Public Guest B"]);
+    let standings = onlooker.get_standings(id).await.unwrap();
+    assert!(standings
+        .iter()
+        .all(|s| players.iter().any(|p| p.id == s.player)));
+    assert_eq!(onlooker.get_pairings(id, 1).await, Some(Vec::new()));
+
+    // Both players are in the one pod, and only the top player qualifies
+    let groups = onlooker.get_group_standings(id, 1).await.unwrap();
+    assert_eq!(groups.len(), 1);
+    assert_eq!(groups[0].standings.len(), standings.len());
+    let top: Vec<_> = groups[0]
+        .standings
+        .iter()
+        .take(1)
+        .map(|s| s.player)
+        .collect();
+    assert_eq!(groups[0].qualified, top);
+
+    let unknown = TournamentId::new(Uuid::new_v4());
+    assert!(onlooker.get_players(unknown).await.is_none());
+    assert!(onlooker.get_standings(unknown).await.is_none());
+    assert!(onlooker.get_group_standings(unknown, 1).await.is_none());
 }
 
-/* TODO: Add back in once FailedConnection bug is sorted out
 #[tokio::test]
-async fn create_tournament() {
-    let mut client = get_client().await;
-    let _ = client.verify().await.unwrap();
-    assert!(client.is_verify());
-    let id = client
-        .create_tournament(
-            "Test".to_owned(),
-            TournamentPreset::Swiss,
-            "Test".to_owned(),
-        )
+async fn public_endpoints_redact_private_players() {
+    let organizer = user_client("Redaction Organizer").await;
+    let admin_id: AdminId = organizer.get_user().unwrap().id.0.into();
+    let id = organizer.create_tournament(get_seed()).await.unwrap();
+    let mut account = SquireAccount::new("Private Player".into(), "Private Player".into());
+    account.permissions = SharingPermissions::Nothing;
+    let p_id: PlayerId = account.id.0.into();
+    let op = JudgeOp::AdminRegisterPlayer(account, None);
+    organizer
+        .update_tourn(id, TournOp::JudgeOp(admin_id.into(), op))
         .await
+        .unwrap()
         .unwrap();
-    client
-        .state
-        .query_tournament(&id, |t| assert_eq!(t.name.as_str(), "Test"))
+    assert!(matches!(
+        organizer.persist_tourn_to_backend(id).await,
+        BackendImportStatus::Success
+    ));
+
+    // Officials see the player's name, but no one else does
+    let players = organizer.get_players(id).await.unwrap();
+    assert_eq!(players[0].name, "Private Player");
+    let onlooker = guest_client().await;
+    let players = onlooker.get_players(id).await.unwrap();
+    assert_eq!(players[0].id, p_id);
+    assert_eq!(players[0].name, "Anonymous 1");
+}
+
+#[tokio::test]
+async fn cloned_tournaments_keep_players() {
+    let organizer = user_client("Clone Organizer").await;
+    let admin_id: AdminId = organizer.get_user().unwrap().id.0.into();
+    let id = organizer.create_tournament(get_seed()).await.unwrap();
+    let account = SquireAccount::new("Weekly Regular".into(), "Weekly Regular".into());
+    let op = JudgeOp::AdminRegisterPlayer(account, None);
+    organizer
+        .update_tourn(id, TournOp::JudgeOp(admin_id.into(), op))
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(matches!(
+        organizer.persist_tourn_to_backend(id).await,
+        BackendImportStatus::Success
+    ));
+
+    let overrides = SeedOverrides {
+        name: Some("Next Week".into()),
+        keep_players: true,
+        ..Default::default()
+    };
+    let other = user_client("Clone Onlooker").await;
+    let denied = other.clone_tournament(id, overrides.clone()).output().await;
+    assert_eq!(denied.unwrap(), None);
+    let new_id = organizer
+        .clone_tournament(id, overrides)
+        .output()
+        .await
+        .unwrap()
+        .unwrap();
+    assert_ne!(new_id, id);
+    let players = organizer.get_players(new_id).await.unwrap();
+    assert_eq!(players.len(), 1);
+    assert_eq!(players[0].name, "Weekly Regular");
+}
+
+#[tokio::test]
+async fn webhooks_are_managed_by_admins() {
+    let organizer = user_client("Webhook Organizer").await;
+    let id = organizer.create_tournament(get_seed()).await.unwrap();
+    assert!(matches!(
+        organizer.persist_tourn_to_backend(id).await,
+        BackendImportStatus::Success
+    ));
+
+    let hook = organizer
+        .register_webhook(id, "https://example.com/squire".into())
+        .output()
+        .await
+        .unwrap()
         .unwrap();
+    assert_eq!(hook.tourn, id);
+    assert!(!hook.secret.is_empty());
+    for (url, err) in [
+        ("ftp://example.com", WebhookError::InvalidUrl),
+        ("http://example.com/squire", WebhookError::InvalidUrl),
+        ("https://127.0.0.1/squire", WebhookError::PrivateAddress),
+        (
+            "https://169.254.169.254/latest",
+            WebhookError::PrivateAddress,
+        ),
+    ] {
+        let bad_url = organizer
+            .register_webhook(id, url.into())
+            .output()
+            .await
+            .unwrap();
+        assert_eq!(bad_url, Err(err), "{url}");
+    }
+    assert_eq!(organizer.list_webhooks(id).await, Some(vec![hook.clone()]));
+
+    // Other users can neither see nor change the tournament's webhooks
+    let other = user_client("Webhook Onlooker").await;
+    let denied = other
+        .register_webhook(id, "https://example.com/other".into())
+        .output()
+        .await
+        .unwrap();
+    assert_eq!(denied, Err(WebhookError::Unauthorized));
+    assert!(other.list_webhooks(id).await.is_none());
+    assert!(!other.delete_webhook(id, hook.id).output().await.unwrap());
+
+    assert!(organizer
+        .delete_webhook(id, hook.id)
+        .output()
+        .await
+        .unwrap());
+    assert_eq!(organizer.list_webhooks(id).await, Some(Vec::new()));
+}
+
+#[tokio::test]
+async fn players_can_look_up_their_round() {
+    let organizer = user_client("Seat Organizer").await;
+    let admin_id: AdminId = organizer.get_user().unwrap().id.0.into();
+    let id = organizer.create_tournament(get_seed()).await.unwrap();
+    let players = [
+        user_client("Seat Player A").await,
+        user_client("Seat Player B").await,
+        user_client("Seat Player Benched").await,
+    ];
+    let mut p_ids: Vec<PlayerId> = Vec::new();
+    for player in &players {
+        let account = player.get_user().unwrap();
+        p_ids.push(account.id.0.into());
+        let op = JudgeOp::AdminRegisterPlayer(account, None);
+        organizer
+            .update_tourn(id, TournOp::JudgeOp(admin_id.into(), op))
+            .await
+            .unwrap()
+            .unwrap();
+    }
+    for op in [AdminOp::Start, AdminOp::CreateRound(p_ids[..2].to_vec())] {
+        organizer
+            .update_tourn(id, TournOp::AdminOp(admin_id, op))
+            .await
+            .unwrap()
+            .unwrap();
+    }
+    assert!(matches!(
+        organizer.persist_tourn_to_backend(id).await,
+        BackendImportStatus::Success
+    ));
+
+    // Both paired players see the same table, each with the other as their opponent
+    let a = players[0].my_round(id).await.unwrap();
+    let b = players[1].my_round(id).await.unwrap();
+    assert_eq!(a.id, b.id);
+    assert_eq!(a.table_number, b.table_number);
+    assert!(!a.is_bye);
+    let opponents: Vec<_> = a.opponents.iter().map(|p| p.name.as_str()).collect();
+    assert_eq!(opponents, ["Seat Player B"]);
+    assert_eq!(b.opponents[0].id, p_ids[0]);
+
+    // Players without an active round and users who aren't playing get nothing
+    assert_eq!(players[2].my_round(id).await, None);
+    assert_eq!(organizer.my_round(id).await, None);
+    let unknown = TournamentId::new(Uuid::new_v4());
+    assert_eq!(players[0].my_round(unknown).await, None);
 }
-*/
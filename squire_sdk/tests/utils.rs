@@ -3,6 +3,7 @@ use std::{collections::HashMap, time::Duration};
 
 use squire_lib::{
     accounts::{SharingPermissions, SquireAccount},
+    formats::Constructed,
     pairings::PairingSystem,
     players::{Player, PlayerRegistry},
     rounds::RoundRegistry,
@@ -15,7 +16,7 @@ pub fn get_seed() -> TournamentSeed {
     TournamentSeed::new(
         "Test Tournament".into(),
         TournamentPreset::Swiss,
-        "Pioneer".into(),
+        Constructed::Pioneer.into(),
     )
     .unwrap()
 }
@@ -24,7 +25,7 @@ pub fn get_fluid_seed() -> TournamentSeed {
     TournamentSeed::new(
         "Test Tournament".into(),
         TournamentPreset::Fluid,
-        "Pioneer".into(),
+        Constructed::Pioneer.into(),
     )
     .unwrap()
 }
@@ -6,6 +6,7 @@ mod sdk;
 pub use sdk::*;
 use squire_lib::{
     accounts::{SharingPermissions, SquireAccount},
+    formats::Constructed,
     pairings::PairingSystem,
     players::{Player, PlayerRegistry},
     rounds::RoundRegistry,
@@ -17,7 +18,7 @@ pub fn get_seed() -> TournamentSeed {
     TournamentSeed::new(
         "Test Tournament".into(),
         TournamentPreset::Swiss,
-        "Pioneer".into(),
+        Constructed::Pioneer.into(),
     )
     .unwrap()
 }
@@ -26,7 +27,7 @@ pub fn get_fluid_seed() -> TournamentSeed {
     TournamentSeed::new(
         "Test Tournament".into(),
         TournamentPreset::Fluid,
-        "Pioneer".into(),
+        Constructed::Pioneer.into(),
     )
     .unwrap()
 }
@@ -1,6 +1,9 @@
 use std::borrow::Cow;
 
-use squire_sdk::{api::RegForm, compat::NetworkError};
+use squire_sdk::{
+    api::{RegForm, MIN_PASSWORD_LENGTH},
+    compat::NetworkError,
+};
 use wasm_bindgen::JsCast;
 use web_sys::{window, HtmlDialogElement};
 use yew::prelude::*;
@@ -51,6 +54,11 @@ impl Register {
         if password.is_empty() {
             return Err("You need to enter a password".to_owned());
         }
+        if password.chars().count() < MIN_PASSWORD_LENGTH {
+            return Err(format!(
+                "Your password needs to be at least {MIN_PASSWORD_LENGTH} characters long."
+            ));
+        }
         let repassword = self.input.3.clone().unwrap_or_else(|| "".to_owned());
         if repassword.is_empty() {
             return Err("You need to re-enter your password".to_owned());
@@ -62,6 +70,7 @@ impl Register {
             username: name,
             display_name: display,
             password,
+            email: None,
         })
     }
 }
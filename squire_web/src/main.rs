@@ -5,6 +5,7 @@ use async_std::channel::{unbounded, Receiver};
 use once_cell::sync::OnceCell;
 use squire_sdk::{
     client::{SquireClient, HOST_ADDRESS},
+    compat::Storage,
     model::{accounts::SquireAccount, identifiers::TournamentId},
 };
 use yew::prelude::*;
@@ -73,6 +74,7 @@ fn main() {
         .url(format!("http{HOST_ADDRESS}"))
         .account(SquireAccount::new("Tester".into(), "Tester".into()))
         .on_update(on_update)
+        .storage(Storage::new("squire"))
         .build_unchecked();
 
     CLIENT.set(client).unwrap();
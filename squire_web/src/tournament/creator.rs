@@ -3,6 +3,7 @@ use std::borrow::Cow;
 use squire_sdk::{
     api::TournamentSummary,
     model::{
+        formats::Constructed,
         identifiers::TournamentId,
         tournament::{TournamentPreset, TournamentSeed},
     },
@@ -56,7 +57,7 @@ impl Component for TournamentCreator {
                     let t_seed = TournamentSeed::new(
                         new_tourn_name,
                         TournamentPreset::Swiss,
-                        "EDH".to_owned(),
+                        Constructed::Commander.into(),
                     )
                     .unwrap();
                     let client = CLIENT.get().unwrap();
@@ -0,0 +1,170 @@
+use std::time::Duration;
+
+use squire_sdk::{
+    model::{identifiers::TournamentId, rounds::MatchProgress, tournament::Tournament},
+    sync::TournamentManager,
+};
+use yew::prelude::*;
+
+use super::{
+    InteractionResponse, TournQuery, TournViewerComponent, TournViewerComponentWrapper,
+    WrapperMessage, WrapperState,
+};
+
+#[derive(Debug, Properties, PartialEq, Eq)]
+pub struct DashboardProps {}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum DashboardMessage {
+    Refresh,
+}
+
+pub enum DashboardQueryMessage {
+    MatchesReady(Vec<MatchSummary>),
+}
+
+/// A row on the dashboard. The player names are looked up when the data is queried so that the
+/// view does not need to touch the tournament.
+#[derive(Debug, PartialEq, Clone)]
+pub struct MatchSummary {
+    progress: MatchProgress,
+    players: Vec<String>,
+    unconfirmed: Vec<String>,
+}
+
+/// The view that scorekeepers watch during a round. It lists every match that is still being
+/// played, with the matches that need attention first.
+pub struct MatchDashboard {
+    pub id: TournamentId,
+    matches: Vec<MatchSummary>,
+}
+
+impl TournViewerComponent for MatchDashboard {
+    type InteractionMessage = DashboardMessage;
+    type QueryMessage = DashboardQueryMessage;
+    type Properties = DashboardProps;
+
+    fn v_create(_ctx: &Context<TournViewerComponentWrapper<Self>>, state: &WrapperState) -> Self {
+        MatchDashboard {
+            id: state.t_id,
+            matches: Vec::new(),
+        }
+    }
+
+    fn interaction(
+        &mut self,
+        ctx: &Context<TournViewerComponentWrapper<Self>>,
+        msg: Self::InteractionMessage,
+        state: &WrapperState,
+    ) -> InteractionResponse<Self> {
+        match msg {
+            DashboardMessage::Refresh => InteractionResponse::FetchData(self.query(ctx, state)),
+        }
+    }
+
+    fn load_queried_data(&mut self, msg: Self::QueryMessage, _state: &WrapperState) -> bool {
+        match msg {
+            DashboardQueryMessage::MatchesReady(matches) => {
+                let digest = self.matches != matches;
+                self.matches = matches;
+                digest
+            }
+        }
+    }
+
+    fn query(
+        &mut self,
+        _ctx: &Context<TournViewerComponentWrapper<Self>>,
+        _state: &WrapperState,
+    ) -> TournQuery<Self::QueryMessage> {
+        let q_func = |tourn: &TournamentManager| {
+            let tourn = tourn.tourn();
+            let data = tourn
+                .get_matches_in_progress()
+                .into_iter()
+                .map(|progress| MatchSummary::new(tourn, progress))
+                .collect();
+            DashboardQueryMessage::MatchesReady(data)
+        };
+        Box::new(q_func)
+    }
+
+    fn v_view(
+        &self,
+        ctx: &Context<TournViewerComponentWrapper<Self>>,
+        _state: &WrapperState,
+    ) -> yew::Html {
+        let refresh = ctx
+            .link()
+            .callback(|_| WrapperMessage::Interaction(DashboardMessage::Refresh));
+        html! {
+            <div>
+                <h2>{ format!("Matches in progress: {}", self.matches.len()) }</h2>
+                <button onclick={ refresh }>{ "Refresh" }</button>
+                <div class="overflow-auto py-3 pairings-scroll-box">
+                    <table class="table">
+                        <thead>
+                            <tr>
+                                <th>{ "Table" }</th>
+                                <th>{ "Match" }</th>
+                                <th>{ "Players" }</th>
+                                <th>{ "Time left" }</th>
+                                <th>{ "Status" }</th>
+                            </tr>
+                        </thead>
+                        <tbody>
+                        {
+                            self.matches.iter().map(MatchSummary::view).collect::<Html>()
+                        }
+                        </tbody>
+                    </table>
+                </div>
+            </div>
+        }
+    }
+}
+
+impl MatchSummary {
+    fn new(tourn: &Tournament, progress: MatchProgress) -> Self {
+        let get_name = |p_id| {
+            tourn
+                .player_reg
+                .get_player(p_id)
+                .map(|p| p.name.clone())
+                .unwrap_or_default()
+        };
+        let players = progress.players.iter().map(get_name).collect();
+        let unconfirmed = progress.unconfirmed.iter().map(get_name).collect();
+        Self {
+            progress,
+            players,
+            unconfirmed,
+        }
+    }
+
+    fn status(&self) -> String {
+        if !self.progress.has_result {
+            "Missing result".to_owned()
+        } else {
+            format!("Awaiting confirmation: {}", self.unconfirmed.join(", "))
+        }
+    }
+
+    fn view(&self) -> Html {
+        let class = self.progress.is_overtime().then_some("table-danger");
+        html! {
+            <tr class={ classes!(class) }>
+                <td>{ self.progress.table_number }</td>
+                <td>{ self.progress.match_number }</td>
+                <td>{ self.players.join(", ") }</td>
+                <td>{ format_duration(self.progress.time_left) }</td>
+                <td>{ self.status() }</td>
+            </tr>
+        }
+    }
+}
+
+fn format_duration(dur: Duration) -> String {
+    let secs = dur.as_secs();
+    format!("{}:{:02}", secs / 60, secs % 60)
+}
@@ -18,6 +18,7 @@ use yew::{html, Component, Context, Properties};
 use crate::{utils::console_log, CLIENT, ON_UPDATE};
 
 pub mod creator;
+pub mod dashboard;
 pub mod model;
 pub mod overview;
 pub mod pairings;
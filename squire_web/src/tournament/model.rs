@@ -124,6 +124,8 @@ pub struct RoundProfile {
     pub status: RoundStatus,
     pub results: HashMap<PlayerId, u32>,
     pub draws: u32,
+    /// The most games a player can win, according to the tournament's match structure
+    pub games_to_win: Option<u32>,
     pub confirmations: HashSet<PlayerId>,
     pub length: std::time::Duration,
     pub extensions: std::time::Duration,
@@ -150,6 +152,7 @@ impl RoundProfile {
             timer: rnd.timer,
             results: rnd.results.clone(),
             draws: rnd.draws,
+            games_to_win: tourn.settings.match_structure.games_to_win(),
             confirmations: rnd.confirmations.clone(),
         }
     }
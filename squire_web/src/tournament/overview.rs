@@ -1,7 +1,7 @@
 use squire_sdk::{
     model::{
         identifiers::TournamentId, players::PlayerStatus, rounds::RoundStatus,
-        tournament::TournamentStatus,
+        schedule::ScheduledRound, settings::RegistrationWindow, tournament::TournamentStatus,
     },
     sync::TournamentManager,
 };
@@ -76,6 +76,8 @@ pub struct TournamentProfile {
     cert_rnds: usize,
     judge_count: usize,
     admin_count: usize,
+    schedule: Vec<ScheduledRound>,
+    registration: RegistrationWindow,
 }
 
 impl TournamentProfile {
@@ -99,6 +101,7 @@ impl TournamentProfile {
                     match p.status {
                         PlayerStatus::Registered => acc.0 += 1,
                         PlayerStatus::Dropped => acc.1 += 1,
+                        PlayerStatus::Alternate | PlayerStatus::Waitlisted => {}
                     }
                     acc
                 });
@@ -107,6 +110,8 @@ impl TournamentProfile {
         let status = tourn.status;
         let judge_count = tourn.judges.len();
         let admin_count = tourn.admins.len();
+        let schedule = tourn.schedule().rounds;
+        let registration = tourn.settings.registration;
         Self {
             name,
             format,
@@ -117,6 +122,8 @@ impl TournamentProfile {
             cert_rnds,
             judge_count,
             admin_count,
+            schedule,
+            registration,
         }
     }
 
@@ -131,7 +138,15 @@ impl TournamentProfile {
             cert_rnds,
             judge_count,
             admin_count,
+            schedule,
+            registration,
         } = self;
+        let reg_opens = registration
+            .opens
+            .map(|t| t.format(TIME_FORMAT).to_string());
+        let reg_closes = registration
+            .closes
+            .map(|t| t.format(TIME_FORMAT).to_string());
         html! {
             <div class="m-lg-0 m-md-4 my-3">
                 <div class="p-5 bg-light rounded-3">
@@ -141,6 +156,12 @@ impl TournamentProfile {
                         <p>{ format!("Format : {format}") }</p>
                         <p>{ format!("Status : {status}") }</p>
                         <p>{ format!("Registered players : {reg_plyrs}") }</p>
+                        if let Some(opens) = reg_opens {
+                            <p>{ format!("Registration opens : {opens}") }</p>
+                        }
+                        if let Some(closes) = reg_closes {
+                            <p>{ format!("Registration closes : {closes}") }</p>
+                        }
 
                         if *dropped_plyrs > 0 {
                             <p>{ format!("Dropped players : {dropped_plyrs}") }</p>
@@ -150,9 +171,26 @@ impl TournamentProfile {
                         <p>{ format!("Certified rounds : {cert_rnds}") }</p>
                         <p>{ format!("Number of judges : {judge_count}") }</p>
                         <p>{ format!("Number of admins : {admin_count}") }</p>
+
+                        if !schedule.is_empty() {
+                            <hr class="my-4"/>
+                            <h3>{ "Schedule" }</h3>
+                            { for schedule.iter().map(view_scheduled_round) }
+                        }
                     </div>
                 </div>
             </div>
         }
     }
 }
+
+/// How times are shown in the overview
+const TIME_FORMAT: &str = "%a %b %-d, %H:%M UTC";
+
+fn view_scheduled_round(rnd: &ScheduledRound) -> Html {
+    let start = rnd.start.format(TIME_FORMAT);
+    let estimate = if rnd.estimated { " (estimated)" } else { "" };
+    html! {
+        <p>{ format!("Round {} : Day {}, {start}{estimate}", rnd.round, rnd.day) }</p>
+    }
+}
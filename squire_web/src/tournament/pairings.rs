@@ -2,6 +2,7 @@ use std::{borrow::Cow, collections::HashMap};
 
 use squire_sdk::{
     model::{
+        collation::NameCollator,
         operations::{AdminOp, TournOp},
         pairings::Pairings,
         players::PlayerId,
@@ -69,6 +70,8 @@ pub enum PairingsQueryMessage {
 pub struct PairingsQueryData {
     names: HashMap<PlayerId, String>,
     active: Vec<ActiveRoundSummary>,
+    /// Each player in an active round, along with their round, sorted by the player's name
+    alphabetical: Vec<(String, u64, u64)>,
     max_player_count: u8,
 }
 
@@ -76,6 +79,7 @@ pub struct PairingsQueryData {
 pub enum PairingsViewMode {
     CreatePairings,
     ActivePairings,
+    AlphabeticalPairings,
     CreateSingleMatches,
 }
 
@@ -123,6 +127,7 @@ impl TournViewerComponent for PairingsView {
                 <div>
                     <>{ make_button("Create Pairings", PairingsViewMode::CreatePairings) }</>
                     <>{ make_button("Active Pairings", PairingsViewMode::ActivePairings) }</>
+                    <>{ make_button("Alphabetical Pairings", PairingsViewMode::AlphabeticalPairings) }</>
                     <>{ make_button("Create single matches", PairingsViewMode::CreateSingleMatches) }</>
                 </div>
                 <div>{
@@ -133,6 +138,9 @@ impl TournViewerComponent for PairingsView {
                         PairingsViewMode::ActivePairings => {
                             self.view_active_menu(ctx)
                         }
+                        PairingsViewMode::AlphabeticalPairings => {
+                            self.view_alphabetical_menu()
+                        }
                         PairingsViewMode::CreateSingleMatches => {
                             self.view_single_menu(ctx)
                         }
@@ -270,10 +278,20 @@ impl TournViewerComponent for PairingsView {
                 .into_iter()
                 .map(|r| ActiveRoundSummary::from_round(tourn, r))
                 .collect();
+            let mut alphabetical: Vec<(String, u64, u64)> = active
+                .iter()
+                .flat_map(|ars| {
+                    ars.players
+                        .iter()
+                        .map(|name| (name.clone(), ars.round_number, ars.table_number))
+                })
+                .collect();
+            NameCollator::new().sort_by_name(&mut alphabetical, |(name, _, _)| name.as_str());
             let max_player_count: u8 = tourn.pairing_sys.common.match_size;
             Self::QueryMessage::AllDataReady(PairingsQueryData {
                 names,
                 active,
+                alphabetical,
                 max_player_count,
             })
         };
@@ -355,6 +373,26 @@ impl PairingsView {
         }
     }
 
+    fn view_alphabetical_menu(&self) -> Html {
+        html! {
+            <div class="py-5">
+                <div class="overflow-auto py-3 pairings-scroll-box">
+                    <ul class="force_left">{
+                        match self.query_data.as_ref() {
+                            Some(data) => data.alphabetical.iter().map(|(name, round_number, table_number)| {
+                                html!{
+                                    <li>{ format!("{name} :: Round #{round_number}, Table #{table_number}") }</li>
+                                }
+                            })
+                            .collect::<Html>(),
+                            None => html!{<li>{"..."}</li>},
+                        }
+                    }</ul>
+                </div>
+            </div>
+        }
+    }
+
     fn view_single_menu(&self, ctx: &Context<TournViewerComponentWrapper<Self>>) -> Html {
         if self.query_data.is_some() {
             let mut name_boxes: Vec<VNode> = Vec::new();
@@ -1,5 +1,5 @@
 use squire_sdk::{
-    model::{error::TournamentError, identifiers::TournamentId},
+    model::{collation::NameCollator, error::TournamentError, identifiers::TournamentId},
     sync::TournamentManager,
 };
 use yew::prelude::*;
@@ -84,7 +84,7 @@ impl TournViewerComponent for PlayerView {
                 .values()
                 .map(PlayerSummary::new)
                 .collect();
-            players.sort_by_cached_key(|p| p.name.clone());
+            NameCollator::new().sort_by_name(&mut players, |p| p.name.as_str());
             players.sort_by_cached_key(|p| p.status);
             Self::QueryMessage::AllData(PlayerViewQueryData { players })
         };
@@ -16,6 +16,8 @@ pub struct RoundResultTicker {
     pub label: Cow<'static, str>,
     pub pid: Option<PlayerId>,
     pub stored_result: RoundResult,
+    /// The largest value the result can be ticked up to, if there is one
+    pub max: Option<u32>,
     pub was_changed: bool,
     pub process: Callback<SelectedRoundMessage>,
 }
@@ -32,12 +34,14 @@ impl RoundResultTicker {
         label: Cow<'static, str>,
         pid: Option<PlayerId>,
         stored_result: RoundResult,
+        max: Option<u32>,
         process: Callback<SelectedRoundMessage>,
     ) -> Self {
         Self {
             label,
             pid,
             stored_result,
+            max,
             was_changed: false,
             process,
         }
@@ -51,6 +55,12 @@ impl RoundResultTicker {
     pub fn update(&mut self, msg: RoundResultTickerMessage) -> bool {
         match msg {
             RoundResultTickerMessage::Increment => {
+                if self
+                    .max
+                    .is_some_and(|max| self.stored_result.get_result() >= max)
+                {
+                    return false;
+                }
                 self.stored_result.inc_result();
                 self.was_changed = true;
             }
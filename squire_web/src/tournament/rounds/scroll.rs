@@ -52,7 +52,7 @@ impl RoundScroll {
                     html! {
                         <tr onclick = { move |_| cb.emit(r.id) }>
                             <td>{ r.match_number }</td>
-                            <td>{ r.table_number }</td>
+                            <td>{ r.table_number }{ if r.is_feature { " ★" } else { "" } }</td>
                             <td>{ r.status }</td>
                         </tr>
                     }
@@ -80,6 +80,7 @@ pub struct RoundSummary {
     pub match_number: u64,
     pub table_number: u64,
     pub status: RoundStatus,
+    pub is_feature: bool,
 }
 
 impl RoundSummary {
@@ -89,6 +90,7 @@ impl RoundSummary {
             match_number: rnd.match_number,
             table_number: rnd.table_number,
             status: rnd.status,
+            is_feature: rnd.is_feature,
         }
     }
 }
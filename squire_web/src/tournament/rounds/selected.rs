@@ -203,7 +203,13 @@ impl RoundUpdater {
         let mut rcb = RoundChangesBuffer::new(
             proc.clone(),
             rnd.id,
-            RoundResultTicker::new("Draws".into(), None, RoundResult::Draw(rnd.draws), proc),
+            RoundResultTicker::new(
+                "Draws".into(),
+                None,
+                RoundResult::Draw(rnd.draws),
+                None,
+                proc,
+            ),
         );
         proc = process.clone();
         rcb.win_tickers.extend(rnd.player_names.iter().map(|r| {
@@ -214,6 +220,7 @@ impl RoundUpdater {
                     format!("{} wins: ", r.1).into(),
                     Some(*r.0),
                     RoundResult::Wins(*r.0, found_result),
+                    rnd.games_to_win,
                     proc.clone(),
                 ),
             )
@@ -15,6 +15,8 @@ pub struct GeneralSettings {
     require_checkin: SettingPanel,
     require_decks: SettingPanel,
     round_length: SettingPanel,
+    player_cap: SettingPanel,
+    auto_certify: SettingPanel,
     current: GeneralSettingsTree,
     to_change: GeneralSettingsTree,
 }
@@ -38,6 +40,10 @@ impl GeneralSettings {
             round_length: make_panel(&emitter, "Round length", |l: u64| {
                 RoundLength(Duration::from_secs(l * 60))
             }),
+            player_cap: make_panel(&emitter, "Player cap", PlayerCap),
+            auto_certify: make_panel(&emitter, "Auto-certify after (0 is off)", |l: u64| {
+                AutoCertify((l != 0).then_some(Duration::from_secs(l * 60)))
+            }),
             current: tree.clone(),
             to_change: tree,
         }
@@ -53,6 +59,7 @@ impl GeneralSettings {
     }
 
     pub fn view(&self) -> Html {
+        let auto_certify = self.current.auto_certify.map_or(0, |l| l.as_secs() / 60);
         html! {
             <div>
                 <h2>{ "General Settings:" }</h2>
@@ -63,6 +70,8 @@ impl GeneralSettings {
                 <p> { self.require_checkin.view(self.current.require_check_in) } </p>
                 <p> { self.require_decks.view(self.current.require_deck_reg) } </p>
                 <p> { self.round_length.view(self.current.round_length.as_secs()/60) } </p>
+                <p> { self.player_cap.view(self.current.player_cap) } </p>
+                <p> { self.auto_certify.view(auto_certify) } </p>
             </div>
         }
     }
@@ -61,6 +61,8 @@ struct CommonPairingSection {
     match_size: SettingPanel,
     repair_tolerance: SettingPanel,
     algorithm: SettingPanel,
+    table_assignment: SettingPanel,
+    allow_repeat_byes: SettingPanel,
     current: PairingCommonSettingsTree,
     to_change: PairingCommonSettingsTree,
 }
@@ -72,6 +74,7 @@ enum PairingStyleSection {
 
 struct SwissPairingSection {
     do_checkins: SettingPanel,
+    pair_within_pods: SettingPanel,
     current: SwissPairingSettingsTree,
     to_change: SwissPairingSettingsTree,
 }
@@ -95,6 +98,16 @@ impl CommonPairingSection {
                 "Pairing Algorithm",
                 CommonPairingSetting::Algorithm,
             ),
+            table_assignment: make_panel(
+                &emitter,
+                "Table Assignment",
+                CommonPairingSetting::TableAssignment,
+            ),
+            allow_repeat_byes: make_panel(
+                &emitter,
+                "Allow Repeat Byes",
+                CommonPairingSetting::AllowRepeatByes,
+            ),
             current: common.clone(),
             to_change: common,
         }
@@ -115,6 +128,8 @@ impl CommonPairingSection {
                 <p>{ self.match_size.view(self.current.match_size) }</p>
                 <p>{ self.repair_tolerance.view(self.current.repair_tolerance) }</p>
                 <p>{ self.algorithm.view(self.current.algorithm) }</p>
+                <p>{ self.table_assignment.view(self.current.table_assignment) }</p>
+                <p>{ self.allow_repeat_byes.view(self.current.allow_repeat_byes) }</p>
             </>
         }
     }
@@ -165,6 +180,11 @@ impl SwissPairingSection {
             current: settings.clone(),
             to_change: settings,
             do_checkins: make_panel(&emitter, "Do checkins?", SwissPairingSetting::DoCheckIns),
+            pair_within_pods: make_panel(
+                &emitter,
+                "Pair within draft pods?",
+                SwissPairingSetting::PairWithinPods,
+            ),
         }
     }
 
@@ -181,6 +201,7 @@ impl SwissPairingSection {
             <div>
                 <h3>{ "Swiss Pairing Settings:" }</h3>
                 <p>{ self.do_checkins.view(self.current.do_checkins) }</p>
+                <p>{ self.pair_within_pods.view(self.current.pair_within_pods) }</p>
             </div>
         }
     }
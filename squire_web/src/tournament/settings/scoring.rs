@@ -1,6 +1,6 @@
 use squire_sdk::model::settings::{
-    ScoringSetting, ScoringSettingsTree, ScoringStyleSettingsTree, SettingsTree,
-    StandardScoringSetting, TournamentSetting,
+    CommonScoringSetting, MultiplayerScoringSetting, ScoringSetting, ScoringSettingsTree,
+    ScoringStyleSettingsTree, SettingsTree, StandardScoringSetting, TournamentSetting,
 };
 use yew::prelude::*;
 
@@ -21,6 +21,15 @@ pub struct ScoringSettings {
     include_gwp: SettingPanel,
     include_opp_mwp: SettingPanel,
     include_opp_gwp: SettingPanel,
+    include_dropped_opponents: SettingPanel,
+    first_place_points: SettingPanel,
+    second_place_points: SettingPanel,
+    third_place_points: SettingPanel,
+    fourth_place_points: SettingPanel,
+    draw_points: SettingPanel,
+    placement_bye_points: SettingPanel,
+    placement_include_byes: SettingPanel,
+    stage_scoring: SettingPanel,
     current: ScoringSettingsTree,
     to_change: ScoringSettingsTree,
 }
@@ -100,6 +109,51 @@ impl ScoringSettings {
                 "Include Opponent GWP",
                 StandardScoringSetting::IncludeOppGwp,
             ),
+            include_dropped_opponents: make_panel(
+                &emitter,
+                "Include Dropped Opponents",
+                StandardScoringSetting::IncludeDroppedOpponents,
+            ),
+            first_place_points: make_panel(
+                &emitter,
+                "First Place Points",
+                MultiplayerScoringSetting::FirstPlacePoints,
+            ),
+            second_place_points: make_panel(
+                &emitter,
+                "Second Place Points",
+                MultiplayerScoringSetting::SecondPlacePoints,
+            ),
+            third_place_points: make_panel(
+                &emitter,
+                "Third Place Points",
+                MultiplayerScoringSetting::ThirdPlacePoints,
+            ),
+            fourth_place_points: make_panel(
+                &emitter,
+                "Fourth Place Points",
+                MultiplayerScoringSetting::FourthPlacePoints,
+            ),
+            draw_points: make_panel(
+                &emitter,
+                "Draw Points",
+                MultiplayerScoringSetting::DrawPoints,
+            ),
+            placement_bye_points: make_panel(
+                &emitter,
+                "Bye Points",
+                MultiplayerScoringSetting::ByePoints,
+            ),
+            placement_include_byes: make_panel(
+                &emitter,
+                "Include Byes",
+                MultiplayerScoringSetting::IncludeByes,
+            ),
+            stage_scoring: make_panel(
+                &emitter,
+                "Stage Scoring",
+                CommonScoringSetting::StageScoring,
+            ),
             current: tree.clone(),
             to_change: tree,
         }
@@ -115,14 +169,25 @@ impl ScoringSettings {
     }
 
     pub fn view(&self) -> Html {
-        #[allow(irrefutable_let_patterns)]
-        let ScoringStyleSettingsTree::Standard(style) = &self.current.style
-        else {
-            panic!()
+        let style = match &self.current.style {
+            ScoringStyleSettingsTree::Standard(_) => self.view_standard(),
+            ScoringStyleSettingsTree::Multiplayer(_) => self.view_multiplayer(),
         };
         html! {
             <div>
                 <h2>{ "Scoring Settings:" }</h2>
+                { style }
+                <p> { self.stage_scoring.view(self.current.common.stage_scoring) }</p>
+            </div>
+        }
+    }
+
+    fn view_standard(&self) -> Html {
+        let ScoringStyleSettingsTree::Standard(style) = &self.current.style else {
+            return Html::default();
+        };
+        html! {
+            <>
                 <p> { self.match_win_points.view(style.match_win_points) }</p>
                 <p> { self.match_draw_points.view(style.match_draw_points) }</p>
                 <p> { self.match_loss_points.view(style.match_loss_points) }</p>
@@ -137,7 +202,25 @@ impl ScoringSettings {
                 <p> { self.include_gwp.view(style.include_gwp) }</p>
                 <p> { self.include_opp_mwp.view(style.include_opp_mwp) }</p>
                 <p> { self.include_opp_gwp.view(style.include_opp_gwp) }</p>
-            </div>
+                <p> { self.include_dropped_opponents.view(style.include_dropped_opponents) }</p>
+            </>
+        }
+    }
+
+    fn view_multiplayer(&self) -> Html {
+        let ScoringStyleSettingsTree::Multiplayer(style) = &self.current.style else {
+            return Html::default();
+        };
+        html! {
+            <>
+                <p> { self.first_place_points.view(style.first_place_points) }</p>
+                <p> { self.second_place_points.view(style.second_place_points) }</p>
+                <p> { self.third_place_points.view(style.third_place_points) }</p>
+                <p> { self.fourth_place_points.view(style.fourth_place_points) }</p>
+                <p> { self.draw_points.view(style.draw_points) }</p>
+                <p> { self.placement_bye_points.view(style.bye_points) }</p>
+                <p> { self.placement_include_byes.view(style.include_byes) }</p>
+            </>
         }
     }
 }
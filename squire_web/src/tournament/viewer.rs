@@ -4,7 +4,7 @@ use yew::{html, Component, Context, Html, Properties};
 
 use crate::{
     tournament::{
-        overview::*, pairings::*, players::*, rounds::*, settings::*, standings::*,
+        dashboard::*, overview::*, pairings::*, players::*, rounds::*, settings::*, standings::*,
         TournViewerComponentWrapper,
     },
     CLIENT,
@@ -14,6 +14,7 @@ use crate::{
 pub enum TournViewMode {
     #[default]
     Overview,
+    Dashboard,
     Players,
     Rounds,
     Pairings,
@@ -52,6 +53,7 @@ impl TournamentViewer {
                     <h4 class="text-center text-lg-start">{ self.tourn_name.as_str() }</h4>
                     <hr/>
                     <li>{ make_button("Overview" , TournViewMode::Overview) }</li>
+                    <li>{ make_button("Dashboard", TournViewMode::Dashboard) }</li>
                     <li>{ make_button("Players"  , TournViewMode::Players) }</li>
                     <li>{ make_button("Rounds"   , TournViewMode::Rounds) }</li>
                     <li>{ make_button("Pairings" , TournViewMode::Pairings) }</li>
@@ -73,6 +75,10 @@ impl TournamentViewer {
                 let inner_props = OverviewProps {};
                 html!( <TournViewerComponentWrapper<TournOverview> t_id = {self.id } props = {inner_props} /> )
             }
+            Some(TournViewMode::Dashboard) => {
+                let inner_props = DashboardProps {};
+                html!( <TournViewerComponentWrapper<MatchDashboard> t_id = {self.id } props = {inner_props} /> )
+            }
             Some(TournViewMode::Players) => {
                 let inner_props = PlayerViewProps {};
                 html!( <TournViewerComponentWrapper<PlayerView> t_id = {self.id } props = {inner_props} /> )